@@ -0,0 +1,99 @@
+//! Minimal command-line parsing shared by the subcommands
+
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+/// Remaining arguments of a command, consumed as options are read
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            args: args.into_iter().collect(),
+        }
+    }
+
+    /// Remove and return the value of the last `--name <value>`
+    pub fn option(&mut self, name: &str) -> Result<Option<String>> {
+        Ok(self.options(name)?.pop())
+    }
+
+    /// Remove and return the values of every `--name <value>`
+    pub fn options(&mut self, name: &str) -> Result<Vec<String>> {
+        let mut values = Vec::new();
+        while let Some(index) = self.args.iter().position(|arg| arg == name) {
+            if index + 1 >= self.args.len() {
+                bail!("{name} requires a value");
+            }
+            values.push(self.args.remove(index + 1));
+            self.args.remove(index);
+        }
+        Ok(values)
+    }
+
+    /// Remove `--name` and return whether it was present
+    pub fn flag(&mut self, name: &str) -> bool {
+        let before = self.args.len();
+        self.args.retain(|arg| arg != name);
+        self.args.len() != before
+    }
+
+    /// Remove and return the next positional argument
+    pub fn next(&mut self) -> Option<String> {
+        let index = self.args.iter().position(|arg| !arg.starts_with("--"))?;
+        Some(self.args.remove(index))
+    }
+
+    /// Next positional argument, which must be present
+    pub fn required(&mut self, what: &str) -> Result<String> {
+        self.next().ok_or_else(|| anyhow!("missing {what}"))
+    }
+
+    /// Fail if any arguments were not consumed
+    pub fn finish(self) -> Result<()> {
+        match self.args.first() {
+            Some(arg) => bail!("unexpected argument {arg}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parse a base58 public key
+pub fn pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| anyhow!("invalid public key {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Args {
+        Args::new(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_options_and_positionals() {
+        let mut args = parse("show abc --url x --program a --program b --force");
+        assert_eq!(args.option("--url").unwrap().as_deref(), Some("x"));
+        assert_eq!(args.options("--program").unwrap(), ["a", "b"]);
+        assert!(args.flag("--force"));
+        assert!(!args.flag("--force"));
+        assert_eq!(args.next().as_deref(), Some("show"));
+        assert_eq!(args.required("session").unwrap(), "abc");
+        assert!(args.required("session").is_err());
+        args.finish().unwrap();
+    }
+
+    #[test]
+    fn test_rejects_leftovers() {
+        let mut args = parse("--url");
+        assert!(args.option("--url").is_err());
+
+        let mut args = parse("show --unknown");
+        assert_eq!(args.next().as_deref(), Some("show"));
+        assert!(args.finish().is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! `valence allowlist ...`: manage the kernel's CPI allowlist
+
+use super::inspect;
+use crate::args::{pubkey, Args};
+use crate::config::Config;
+use anyhow::{bail, Result};
+#[allow(deprecated)]
+use solana_sdk::bpf_loader_upgradeable;
+use valence_sdk::{
+    add_to_allowlist_instruction, initialize_allowlist_instruction, program_data_address,
+    remove_from_allowlist_instruction,
+};
+
+pub async fn run(config: &Config, mut args: Args) -> Result<()> {
+    let command = args.required("allowlist command")?;
+    if command == "show" {
+        args.finish()?;
+        return inspect::allowlist(&config.reader()).await;
+    }
+
+    let client = config.client()?;
+    let authority = client.payer();
+    let instruction = match command.as_str() {
+        "init" => {
+            args.finish()?;
+            initialize_allowlist_instruction(authority)
+        }
+        "add" => {
+            let program = pubkey(&args.required("program")?)?;
+            let reject_upgrades = args.flag("--reject-upgrades");
+            args.finish()?;
+
+            // Upgradeable programs pass their ProgramData to record the deployment
+            let account = client.rpc_client().get_account(&program).await?;
+            let program_data = (account.owner == bpf_loader_upgradeable::ID)
+                .then(|| program_data_address(&program));
+            if reject_upgrades && program_data.is_none() {
+                bail!("{program} is not upgradeable; --reject-upgrades does not apply");
+            }
+            add_to_allowlist_instruction(authority, program, reject_upgrades, program_data)
+        }
+        "remove" => {
+            let program = pubkey(&args.required("program")?)?;
+            args.finish()?;
+            remove_from_allowlist_instruction(authority, program)
+        }
+        command => bail!("unknown allowlist command {command}"),
+    };
+
+    let signature = client.send_instructions(&[instruction], &[]).await?;
+    println!("signature: {signature}");
+    Ok(())
+}
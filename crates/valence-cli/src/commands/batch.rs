@@ -0,0 +1,139 @@
+//! `valence batch build`: compose a batch from a manifest, simulate and submit it
+
+use crate::args::Args;
+use crate::config::Config;
+use crate::manifest::{self, Manifest};
+use anchor_lang::AccountDeserialize;
+use anyhow::{bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::path::PathBuf;
+use valence_kernel::{
+    state::{AllowlistAccount, OperationData},
+    Session, SessionAccountLookup,
+};
+use valence_sdk::{
+    compute::ComputeAnalyzer, cpi_allowlist_address, program_data_address, SessionAccounts,
+    ValenceClientAsync,
+};
+
+pub async fn run(config: &Config, mut args: Args) -> Result<()> {
+    match args.required("batch command")?.as_str() {
+        "build" => build(config, args).await,
+        command => bail!("unknown batch command {command}"),
+    }
+}
+
+async fn build(config: &Config, mut args: Args) -> Result<()> {
+    let path = PathBuf::from(args.required("manifest")?);
+    let submit = args.flag("--submit");
+    args.finish()?;
+
+    let manifest = Manifest::load(&path)?;
+    let built = manifest.build()?;
+
+    let client = config.client()?;
+    let session: Session = client.get_account(&built.session).await?;
+    if !session.active {
+        bail!("session {} is not active", built.session);
+    }
+    let lookup: SessionAccountLookup = client.get_account(&session.account_lookup).await?;
+    manifest::validate_borrows(&manifest, &lookup)?;
+
+    // CPI accounts and programs ride in remaining accounts, along with the
+    // ProgramData of any program the allowlist pins to a deployment
+    let payer = client.payer();
+    let cpi_allowlist = cpi_allowlist_address();
+    let allowlist: AllowlistAccount = client.get_account(&cpi_allowlist).await?;
+    let mut remaining: Vec<AccountMeta> = built
+        .cpi_accounts
+        .iter()
+        .map(|account| AccountMeta::new(*account, *account == payer))
+        .collect();
+    for program in &built.programs {
+        remaining.push(AccountMeta::new_readonly(*program, false));
+        if allowlist.pinned_deployment(program).is_some() {
+            remaining.push(AccountMeta::new_readonly(program_data_address(program), false));
+        }
+    }
+
+    // CPI data is read from the session's operation data account, written
+    // ahead of the batch when it does not already hold the built data
+    let accounts = SessionAccounts::from_session(built.session, &session);
+    if !built.operation_data.is_empty() {
+        remaining.push(AccountMeta::new_readonly(accounts.operation_data_address(), false));
+    }
+    let writes = operation_data_writes(&client, &accounts, payer, &built.operation_data).await?;
+
+    let instruction =
+        accounts.execute_batch_instruction(built.batch, cpi_allowlist, payer, payer, remaining);
+    let instructions = [instruction];
+
+    println!("operations:        {}", manifest.operations.len());
+    if !writes.is_empty() {
+        println!("operation data:    {} bytes to write", built.operation_data.len());
+        if submit {
+            for write in &writes {
+                let signature = client.send_instructions(std::slice::from_ref(write), &[]).await?;
+                println!("  written: {signature}");
+            }
+        } else {
+            println!("  written on --submit; until then the batch simulates against the current data");
+        }
+    }
+    println!(
+        "estimated units:   {}",
+        ComputeAnalyzer::new().estimate(&instructions, 1)
+    );
+    let simulation = client.simulate(&instructions, &[]).await?;
+    if let Some(units) = simulation.units_consumed {
+        println!("simulated units:   {units}");
+    }
+    for log in &simulation.logs {
+        println!("  {log}");
+    }
+
+    if submit {
+        let signature = client.send_instructions(&instructions, &[]).await?;
+        println!("signature: {signature}");
+    }
+    Ok(())
+}
+
+/// Instructions bringing the session's operation data to `data`, unless it already holds it
+async fn operation_data_writes(
+    client: &ValenceClientAsync,
+    accounts: &SessionAccounts,
+    owner: Pubkey,
+    data: &[u8],
+) -> Result<Vec<Instruction>> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut instructions = Vec::new();
+    match client.rpc_client().get_account(&accounts.operation_data_address()).await {
+        Ok(account) => {
+            let header = OperationData::try_deserialize(&mut account.data.as_slice())?;
+            if header.written(&account.data)? == data {
+                return Ok(instructions);
+            }
+            let capacity = OperationData::capacity(account.data.len());
+            if capacity < data.len() {
+                bail!(
+                    "operation data account holds {capacity} bytes but the batch needs {}",
+                    data.len()
+                );
+            }
+        }
+        Err(_) => instructions.push(accounts.initialize_operation_data_instruction(
+            owner,
+            data.len() as u32,
+            owner,
+        )),
+    }
+    instructions.extend(accounts.write_operation_data_instructions(owner, data));
+    Ok(instructions)
+}
@@ -0,0 +1,24 @@
+//! `valence events`: stream kernel events as they are confirmed
+
+use crate::args::{pubkey, Args};
+use crate::config::Config;
+use anyhow::Result;
+use solana_sdk::commitment_config::CommitmentConfig;
+use valence_sdk::ValenceEvents;
+
+pub async fn run(config: &Config, mut args: Args) -> Result<()> {
+    let session = args.option("--session")?.map(|value| pubkey(&value)).transpose()?;
+    args.finish()?;
+
+    let mut events = ValenceEvents::subscribe(&config.ws_url(), CommitmentConfig::confirmed()).await?;
+    loop {
+        let next = match &session {
+            Some(session) => events.next_for_session(session).await,
+            None => events.next().await,
+        };
+        let Some(decoded) = next else {
+            return Ok(());
+        };
+        println!("{} {} {:?}", decoded.slot, decoded.signature, decoded.event);
+    }
+}
@@ -0,0 +1,106 @@
+//! Pretty-printed views of kernel accounts
+
+use crate::args::{pubkey, Args};
+use crate::config::Config;
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use valence_kernel::{
+    state::AllowlistAccount, GuardAccount, Session, SessionAccountLookup, ACCESS_MODE_READ,
+    ACCESS_MODE_WRITE,
+};
+use valence_sdk::{cpi_allowlist_address, ValenceClientAsync};
+
+/// `valence guard show <guard>`
+pub async fn guard(config: &Config, mut args: Args) -> Result<()> {
+    match args.required("guard command")?.as_str() {
+        "show" => {
+            let address = pubkey(&args.required("guard")?)?;
+            args.finish()?;
+            let guard: GuardAccount = config.reader().get_account(&address).await?;
+            print_guard(&address, &guard);
+            Ok(())
+        }
+        command => anyhow::bail!("unknown guard command {command}"),
+    }
+}
+
+fn optional(key: Option<Pubkey>) -> String {
+    key.map_or_else(|| "-".to_string(), |key| key.to_string())
+}
+
+fn mode(permissions: u8) -> &'static str {
+    match (permissions & ACCESS_MODE_READ != 0, permissions & ACCESS_MODE_WRITE != 0) {
+        (true, true) => "rw",
+        (true, false) => "r",
+        (false, true) => "w",
+        (false, false) => "-",
+    }
+}
+
+fn print_guard(address: &Pubkey, guard: &GuardAccount) {
+    println!("guard {address}");
+    println!("  session:                {}", guard.session);
+    println!("  allow unregistered cpi: {}", guard.allow_unregistered_cpi);
+    println!("  approval signer:        {}", optional(guard.approval_signer));
+    println!("  require commitment:     {}", guard.require_commitment);
+}
+
+/// Print a session with its guard and registrations
+pub async fn session(client: &ValenceClientAsync, address: &Pubkey) -> Result<()> {
+    let session: Session = client.get_account(address).await?;
+    println!("session {address}");
+    println!("  namespace:      {}", session.namespace.as_str().unwrap_or("<invalid>"));
+    println!("  owner:          {}", session.owner);
+    println!("  shard:          {}", session.shard);
+    println!("  parent:         {}", optional(session.parent_session));
+    println!("  active:         {}", session.active);
+    println!("  usage count:    {}", session.usage_count);
+    println!("  created at:     {}", session.created_at);
+    println!("  updated at:     {}", session.updated_at);
+    println!("  guard:          {}", session.guard_account);
+    println!("  account lookup: {}", session.account_lookup);
+
+    let borrowed: Vec<_> = session
+        .borrowed_accounts
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| session.borrowed_bitmap & (1 << index) != 0)
+        .collect();
+    println!("  borrowed:       {}", borrowed.len());
+    for (_, account) in borrowed {
+        println!("    {} ({}, since {})", account.address, mode(account.mode), account.borrowed_at);
+    }
+
+    let lookup: SessionAccountLookup = client.get_account(&session.account_lookup).await?;
+    println!("  registered accounts:");
+    for account in &lookup.borrowable_accounts[..lookup.borrowable_count as usize] {
+        println!("    {} ({})", account.address, mode(account.permissions));
+    }
+    println!("  registered programs:");
+    for program in &lookup.program_accounts[..lookup.program_count as usize] {
+        let status = if program.active { "active" } else { "inactive" };
+        println!("    {} ({status})", program.address);
+    }
+
+    let guard: GuardAccount = client.get_account(&session.guard_account).await?;
+    print_guard(&session.guard_account, &guard);
+    Ok(())
+}
+
+/// Print the kernel's CPI allowlist
+pub async fn allowlist(client: &ValenceClientAsync) -> Result<()> {
+    let address = cpi_allowlist_address();
+    let allowlist: AllowlistAccount = client.get_account(&address).await?;
+    println!("cpi allowlist {address}");
+    println!("  authority: {}", allowlist.authority);
+    println!("  programs:  {}", allowlist.program_count);
+    for index in 0..allowlist.program_count as usize {
+        let deployed = match allowlist.deployed_slots[index] {
+            0 => "deployment unknown".to_string(),
+            slot => format!("deployed at slot {slot}"),
+        };
+        let pinned = if allowlist.reject_upgrades[index] { ", upgrades rejected" } else { "" };
+        println!("    {} ({deployed}{pinned})", allowlist.allowed_programs[index]);
+    }
+    Ok(())
+}
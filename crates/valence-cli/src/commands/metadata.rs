@@ -0,0 +1,33 @@
+//! Protocol metadata published on IPFS
+
+use crate::args::Args;
+use anyhow::Result;
+use valence_registry::{metadata::DEFAULT_IPFS_GATEWAY, MetadataResolver};
+
+/// `valence metadata <cid> [--gateway <url>]`
+pub async fn run(mut args: Args) -> Result<()> {
+    let cid = args.required("cid")?;
+    let gateway = args
+        .option("--gateway")?
+        .or_else(|| std::env::var("VALENCE_IPFS_GATEWAY").ok())
+        .unwrap_or_else(|| DEFAULT_IPFS_GATEWAY.to_string());
+    args.finish()?;
+
+    let manifest = MetadataResolver::new(&gateway).resolve(&cid).await?;
+    println!("{} {}", manifest.name, manifest.version);
+    if !manifest.description.is_empty() {
+        println!("  {}", manifest.description);
+    }
+    println!("  website:    {}", manifest.website.as_deref().unwrap_or("-"));
+    println!("  repository: {}", manifest.repository.as_deref().unwrap_or("-"));
+    println!("  documentation:");
+    for link in &manifest.documentation {
+        println!("    {}: {}", link.title, link.url);
+    }
+    println!("  audits:");
+    for audit in &manifest.audits {
+        let score = audit.score.as_deref().map(|score| format!(", {score}")).unwrap_or_default();
+        println!("    {} ({}{score}): {}", audit.auditor, audit.audit_date, audit.report_url);
+    }
+    Ok(())
+}
@@ -0,0 +1,6 @@
+pub mod allowlist;
+pub mod batch;
+pub mod events;
+pub mod inspect;
+pub mod metadata;
+pub mod session;
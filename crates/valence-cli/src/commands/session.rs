@@ -0,0 +1,148 @@
+//! `valence session ...`: create, inspect, register into and invalidate sessions
+
+use super::inspect;
+use crate::args::{pubkey, Args};
+use crate::config::Config;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use valence_kernel::{
+    RegisteredAccount, RegisteredProgram, Session, ACCESS_MODE_READ, ACCESS_MODE_READ_WRITE,
+    ACCESS_MODE_WRITE,
+};
+use valence_sdk::{FeePayer, SessionHandle};
+
+pub async fn run(config: &Config, mut args: Args) -> Result<()> {
+    match args.required("session command")?.as_str() {
+        "create" => create(config, args).await,
+        "show" => {
+            let session = pubkey(&args.required("session")?)?;
+            args.finish()?;
+            inspect::session(&config.reader(), &session).await
+        }
+        "register" => register(config, args).await,
+        "invalidate" => invalidate(config, args).await,
+        command => bail!("unknown session command {command}"),
+    }
+}
+
+/// Parse `<pubkey>[:r|w|rw]`, defaulting to read-write
+pub fn borrowable(value: &str) -> Result<RegisteredAccount> {
+    let (address, mode) = value.split_once(':').unwrap_or((value, "rw"));
+    let permissions = match mode {
+        "r" => ACCESS_MODE_READ,
+        "w" => ACCESS_MODE_WRITE,
+        "rw" => ACCESS_MODE_READ_WRITE,
+        _ => bail!("invalid access mode {mode}, expected r, w or rw"),
+    };
+    Ok(RegisteredAccount {
+        address: pubkey(address)?,
+        permissions,
+        label: [0u8; 8],
+    })
+}
+
+fn program(value: &str) -> Result<RegisteredProgram> {
+    Ok(RegisteredProgram {
+        address: pubkey(value)?,
+        active: true,
+        label: [0u8; 8],
+    })
+}
+
+/// Read every `--borrowable` and `--program` registration
+fn registrations(args: &mut Args) -> Result<(Vec<RegisteredAccount>, Vec<RegisteredProgram>)> {
+    let borrowable = args
+        .options("--borrowable")?
+        .iter()
+        .map(|value| borrowable(value))
+        .collect::<Result<_>>()?;
+    let programs = args
+        .options("--program")?
+        .iter()
+        .map(|value| program(value))
+        .collect::<Result<_>>()?;
+    Ok((borrowable, programs))
+}
+
+async fn create(config: &Config, mut args: Args) -> Result<()> {
+    let namespace = args
+        .option("--namespace")?
+        .ok_or_else(|| anyhow!("--namespace is required"))?;
+    let shard = pubkey(
+        &args
+            .option("--shard")?
+            .ok_or_else(|| anyhow!("--shard is required"))?,
+    )?;
+    let allow_unregistered_cpi = args.flag("--allow-unregistered-cpi");
+    let (borrowable, programs) = registrations(&mut args)?;
+    args.finish()?;
+
+    let client = config.client()?;
+    let mut builder = client.kernel_session_builder(shard, namespace);
+    if allow_unregistered_cpi {
+        builder = builder.allow_unregistered_cpi();
+    }
+    for account in borrowable {
+        builder = builder.borrowable(account);
+    }
+    for program in programs {
+        builder = builder.program(program);
+    }
+
+    let session = builder.build()?;
+    let signatures = client.create_kernel_session(&session).await?;
+    println!("session:        {}", session.session.pubkey());
+    println!("guard:          {}", session.guard.pubkey());
+    println!("account lookup: {}", session.account_lookup.pubkey());
+    for signature in signatures {
+        println!("signature:      {signature}");
+    }
+    Ok(())
+}
+
+/// Handle for an existing session, reading its ALT from chain
+async fn handle<'a>(client: &'a dyn FeePayer, config: &Config, session: Pubkey) -> Result<SessionHandle<'a>> {
+    let state: Session = config.reader().get_account(&session).await?;
+    Ok(SessionHandle::new(client, session, state.account_lookup))
+}
+
+async fn register(config: &Config, mut args: Args) -> Result<()> {
+    let session = pubkey(&args.required("session")?)?;
+    let (borrowable, programs) = registrations(&mut args)?;
+    args.finish()?;
+    if borrowable.is_empty() && programs.is_empty() {
+        bail!("nothing to register; pass --borrowable or --program");
+    }
+
+    let client = config.client()?;
+    let handle = handle(&client, config, session).await?;
+    let signature = client.manage_alt(&handle, borrowable, programs, Vec::new()).await?;
+    println!("signature: {signature}");
+    Ok(())
+}
+
+async fn invalidate(config: &Config, mut args: Args) -> Result<()> {
+    let session = pubkey(&args.required("session")?)?;
+    args.finish()?;
+
+    let client = config.client()?;
+    let handle = handle(&client, config, session).await?;
+    let signature = client.invalidate_session(&handle).await?;
+    println!("signature: {signature}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borrowable_modes() {
+        let key = Pubkey::new_unique();
+        assert_eq!(borrowable(&key.to_string()).unwrap().permissions, ACCESS_MODE_READ_WRITE);
+        assert_eq!(borrowable(&format!("{key}:r")).unwrap().permissions, ACCESS_MODE_READ);
+        assert_eq!(borrowable(&format!("{key}:w")).unwrap().permissions, ACCESS_MODE_WRITE);
+        assert!(borrowable(&format!("{key}:x")).is_err());
+        assert!(borrowable("not-a-key").is_err());
+    }
+}
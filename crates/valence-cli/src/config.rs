@@ -0,0 +1,89 @@
+//! Cluster and signer selection shared by all commands
+
+use crate::args::Args;
+use anyhow::{anyhow, Result};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair},
+};
+use std::{path::PathBuf, sync::Arc};
+use valence_sdk::ValenceClientAsync;
+
+const DEFAULT_URL: &str = "http://localhost:8899";
+
+pub struct Config {
+    pub url: String,
+    pub keypair: PathBuf,
+}
+
+impl Config {
+    /// Read `--url` and `--keypair`, falling back to the environment
+    pub fn from_args(args: &mut Args) -> Result<Self> {
+        let url = args
+            .option("--url")?
+            .or_else(|| std::env::var("VALENCE_RPC_URL").ok())
+            .unwrap_or_else(|| DEFAULT_URL.to_string());
+        let keypair = args
+            .option("--keypair")?
+            .or_else(|| std::env::var("VALENCE_KEYPAIR").ok())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                PathBuf::from(home).join(".config/solana/id.json")
+            });
+        Ok(Self { url, keypair })
+    }
+
+    /// Client signing and paying with the configured keypair
+    pub fn client(&self) -> Result<ValenceClientAsync> {
+        let payer = read_keypair_file(&self.keypair)
+            .map_err(|e| anyhow!("reading keypair {}: {e}", self.keypair.display()))?;
+        Ok(self.client_with(payer))
+    }
+
+    /// Client for read-only commands, which need no keypair
+    pub fn reader(&self) -> ValenceClientAsync {
+        self.client_with(Keypair::new())
+    }
+
+    fn client_with(&self, payer: Keypair) -> ValenceClientAsync {
+        ValenceClientAsync::new(
+            self.url.clone(),
+            Arc::new(payer),
+            Some(CommitmentConfig::confirmed()),
+        )
+    }
+
+    /// Websocket URL of the cluster, following the RPC port + 1 convention
+    pub fn ws_url(&self) -> String {
+        let url = self
+            .url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1);
+        match url.rsplit_once(':') {
+            Some((host, port)) => match port.parse::<u16>() {
+                Ok(port) => format!("{host}:{}", port + 1),
+                Err(_) => url,
+            },
+            None => url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_url() {
+        let config = |url: &str| Config {
+            url: url.to_string(),
+            keypair: PathBuf::new(),
+        };
+        assert_eq!(config("http://localhost:8899").ws_url(), "ws://localhost:8900");
+        assert_eq!(
+            config("https://api.devnet.solana.com").ws_url(),
+            "wss://api.devnet.solana.com"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Operator CLI for the Valence kernel
+//!
+//! Usage: `valence [--url <rpc>] [--keypair <path>] <command> ...`; run
+//! without arguments for the list of commands.
+
+mod args;
+mod commands;
+mod config;
+mod manifest;
+
+use anyhow::{bail, Result};
+use args::Args;
+use config::Config;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: valence [--url <rpc>] [--keypair <path>] <command>
+
+commands:
+  session create --namespace <path> --shard <pubkey> [--borrowable <pubkey>[:r|w|rw]]...
+                 [--program <pubkey>]... [--allow-unregistered-cpi]
+  session show <session>
+  session register <session> [--borrowable <pubkey>[:r|w|rw]]... [--program <pubkey>]...
+  session invalidate <session>
+  guard show <guard>
+  allowlist init
+  allowlist add <program> [--reject-upgrades]
+  allowlist remove <program>
+  allowlist show
+  events [--session <pubkey>]
+  batch build <manifest.yaml|json> [--submit]
+  metadata <cid> [--gateway <url>]
+
+--url defaults to $VALENCE_RPC_URL or http://localhost:8899, and --keypair to
+$VALENCE_KEYPAIR or ~/.config/solana/id.json. --gateway defaults to
+$VALENCE_IPFS_GATEWAY or https://ipfs.io.";
+
+async fn run(mut args: Args) -> Result<()> {
+    let config = Config::from_args(&mut args)?;
+    match args.next().as_deref() {
+        Some("session") => commands::session::run(&config, args).await,
+        Some("guard") => commands::inspect::guard(&config, args).await,
+        Some("allowlist") => commands::allowlist::run(&config, args).await,
+        Some("events") => commands::events::run(&config, args).await,
+        Some("batch") => commands::batch::run(&config, args).await,
+        Some("metadata") => commands::metadata::run(args).await,
+        Some(command) => bail!("unknown command {command}\n\n{USAGE}"),
+        None => bail!("{USAGE}"),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::new(std::env::args().skip(1));
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: starting runtime: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match runtime.block_on(run(args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Declarative batch manifests for `valence batch build`
+//!
+//! A manifest names a session and lists operations as single-key maps:
+//!
+//! ```yaml
+//! session: <pubkey>
+//! operations:
+//!   - borrow: { account: <pubkey>, mode: read_write }
+//!   - call_function: { registry_id: 1005, accounts: [<pubkey>], data: [1, 0] }
+//!   - transfer: { from: <pubkey>, to: <pubkey>, lamports: 1000 }
+//!   - release: { account: <pubkey> }
+//! ```
+//!
+//! JSON manifests parse too, since JSON is valid YAML.
+
+use crate::args::pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+#[allow(deprecated)]
+use solana_sdk::system_instruction;
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+use valence_kernel::{state::function_registry::FunctionInfo, SessionAccountLookup};
+use valence_sdk::{AccessMode, BatchBuilder, OperationBatch};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub session: String,
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub operations: Vec<ManifestOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ManifestOperation {
+    Borrow {
+        account: String,
+        #[serde(default)]
+        mode: Mode,
+    },
+    Release {
+        account: String,
+    },
+    /// Call a function from the kernel's registry
+    CallFunction {
+        registry_id: u64,
+        #[serde(default)]
+        accounts: Vec<String>,
+        #[serde(default)]
+        data: Vec<u8>,
+    },
+    /// Move lamports through the system program; `from` must sign the transaction
+    Transfer {
+        from: String,
+        to: String,
+        lamports: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Read,
+    Write,
+    #[default]
+    ReadWrite,
+}
+
+impl From<Mode> for AccessMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Read => AccessMode::Read,
+            Mode::Write => AccessMode::Write,
+            Mode::ReadWrite => AccessMode::ReadWrite,
+        }
+    }
+}
+
+/// A manifest resolved to a batch and the accounts its CPIs touch
+pub struct BuiltBatch {
+    pub session: Pubkey,
+    pub batch: OperationBatch,
+    /// Programs invoked by the batch
+    pub programs: Vec<Pubkey>,
+    /// Accounts passed to CPIs, in first-use order
+    pub cpi_accounts: Vec<Pubkey>,
+    /// Operation data the batch's CPIs reference, written to the session's
+    /// operation data account before execution
+    pub operation_data: Vec<u8>,
+}
+
+impl Manifest {
+    /// Parse a YAML or JSON manifest
+    pub fn parse(source: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(source)?)
+    }
+
+    /// Read and parse a manifest file
+    pub fn load(path: &Path) -> Result<Self> {
+        let source =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&source).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Convert the operations into a batch, checking registry ids and pairing
+    pub fn build(&self) -> Result<BuiltBatch> {
+        let mut batch = BatchBuilder::new();
+        let mut programs = Vec::new();
+        let mut cpi_accounts = Vec::new();
+        let mut note_accounts = |accounts: &[Pubkey]| {
+            for account in accounts {
+                if !cpi_accounts.contains(account) {
+                    cpi_accounts.push(*account);
+                }
+            }
+        };
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            let context = || format!("operation {index}");
+            match operation {
+                ManifestOperation::Borrow { account, mode } => {
+                    batch.borrow(pubkey(account).with_context(context)?, (*mode).into());
+                }
+                ManifestOperation::Release { account } => {
+                    batch.release(pubkey(account).with_context(context)?);
+                }
+                ManifestOperation::CallFunction {
+                    registry_id,
+                    accounts,
+                    data,
+                } => {
+                    let function = FunctionInfo::get_registry_entry(*registry_id)
+                        .filter(|function| function.is_active)
+                        .ok_or_else(|| anyhow!("no active function with registry id {registry_id}"))
+                        .with_context(context)?;
+                    let accounts = accounts
+                        .iter()
+                        .map(|account| pubkey(account))
+                        .collect::<Result<Vec<_>>>()
+                        .with_context(context)?;
+                    batch
+                        .call_function(*registry_id, &accounts, data)
+                        .with_context(context)?;
+                    note_accounts(&accounts);
+                    if !programs.contains(&function.program_id) {
+                        programs.push(function.program_id);
+                    }
+                }
+                ManifestOperation::Transfer { from, to, lamports } => {
+                    let from = pubkey(from).with_context(context)?;
+                    let to = pubkey(to).with_context(context)?;
+                    let transfer = system_instruction::transfer(&from, &to, *lamports);
+                    batch
+                        .raw_cpi(transfer.program_id, &[from, to], &transfer.data)
+                        .with_context(context)?;
+                    note_accounts(&[from, to]);
+                }
+            }
+        }
+
+        let operation_data = batch.operation_data().to_vec();
+        Ok(BuiltBatch {
+            session: pubkey(&self.session).context("session")?,
+            batch: batch.build()?,
+            programs,
+            cpi_accounts,
+            operation_data,
+        })
+    }
+}
+
+/// Check every borrow against the session's registered accounts
+pub fn validate_borrows(manifest: &Manifest, lookup: &SessionAccountLookup) -> Result<()> {
+    for (index, operation) in manifest.operations.iter().enumerate() {
+        if let ManifestOperation::Borrow { account, mode } = operation {
+            let address = pubkey(account)?;
+            if let Err(e) = lookup.validate_borrowable(&address, AccessMode::from(*mode).as_u8()) {
+                bail!("operation {index}: cannot borrow {address}: {e}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use valence_kernel::ACCESS_MODE_READ;
+
+    fn manifest(session: Pubkey, operations: &str) -> Manifest {
+        Manifest::parse(&format!("session: {session}\noperations:\n{operations}")).unwrap()
+    }
+
+    #[test]
+    fn test_build_yaml_manifest() {
+        let (vault, from, to) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let manifest = manifest(
+            Pubkey::new_unique(),
+            &format!(
+                "  - borrow: {{ account: {vault} }}\n\
+                 \x20 - call_function: {{ registry_id: 1005, accounts: [{vault}], data: [1, 2] }}\n\
+                 \x20 - transfer: {{ from: {from}, to: {to}, lamports: 5 }}\n\
+                 \x20 - release: {{ account: {vault} }}\n"
+            ),
+        );
+
+        let built = manifest.build().unwrap();
+        assert_eq!(built.batch.operations_len, 4);
+        assert_eq!(built.cpi_accounts, vec![vault, from, to]);
+        let transfer = system_instruction::transfer(&from, &to, 5);
+        assert_eq!(built.operation_data, [&[1, 2][..], &transfer.data].concat());
+        assert_eq!(
+            built.programs,
+            vec![FunctionInfo::get_registry_entry(1005).unwrap().program_id]
+        );
+    }
+
+    #[test]
+    fn test_parse_json_manifest() {
+        let account = Pubkey::new_unique();
+        let manifest = Manifest::parse(&format!(
+            r#"{{"session": "{}", "operations": [{{"borrow": {{"account": "{account}", "mode": "read"}}}}, {{"release": {{"account": "{account}"}}}}]}}"#,
+            Pubkey::new_unique()
+        ))
+        .unwrap();
+        assert!(matches!(
+            manifest.operations[0],
+            ManifestOperation::Borrow { mode: Mode::Read, .. }
+        ));
+        assert!(manifest.build().is_ok());
+    }
+
+    #[test]
+    fn test_rejects_unknown_function() {
+        let manifest = manifest(
+            Pubkey::new_unique(),
+            "  - call_function: { registry_id: 42 }\n",
+        );
+        let error = format!("{:#}", manifest.build().err().unwrap());
+        assert!(error.contains("registry id 42"), "{error}");
+    }
+
+    #[test]
+    fn test_rejects_double_borrow() {
+        let account = Pubkey::new_unique();
+        let manifest = manifest(
+            Pubkey::new_unique(),
+            &format!("  - borrow: {{ account: {account} }}\n  - borrow: {{ account: {account} }}\n"),
+        );
+        assert!(manifest.build().is_err());
+    }
+
+    #[test]
+    fn test_validate_borrows() {
+        let readable = Pubkey::new_unique();
+        let mut lookup = SessionAccountLookup::new(Pubkey::new_unique(), Pubkey::new_unique());
+        lookup
+            .register_borrowable(readable, ACCESS_MODE_READ, [0u8; 8])
+            .unwrap();
+
+        let read = manifest(
+            Pubkey::new_unique(),
+            &format!("  - borrow: {{ account: {readable}, mode: read }}\n"),
+        );
+        assert!(validate_borrows(&read, &lookup).is_ok());
+
+        let write = manifest(
+            Pubkey::new_unique(),
+            &format!("  - borrow: {{ account: {readable} }}\n"),
+        );
+        assert!(validate_borrows(&write, &lookup).is_err());
+
+        let unregistered = manifest(
+            Pubkey::new_unique(),
+            &format!("  - borrow: {{ account: {} }}\n", Pubkey::new_unique()),
+        );
+        assert!(validate_borrows(&unregistered, &lookup).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// Canonical event schema for Valence programs
+//
+// Every event a Valence program emits is defined here once. Programs emit them
+// with `emit!`, and off-chain crates decode the same structs from `Program data:`
+// logs (borsh) or exchange them as JSON (serde). Anchor discriminators depend
+// only on the struct name, so events must not be renamed.
+//
+// serde derives are path-qualified so `serialize` stays unambiguous for `#[event]`.
+use anchor_lang::prelude::*;
+
+// ================================
+// Session Lifecycle Events
+// ================================
+
+/// Event emitted when a session is created
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SessionCreated {
+    /// The session that was created
+    pub session: Pubkey,
+    /// Owner of the session
+    pub owner: Pubkey,
+    /// Shard the session was created for
+    pub shard: Pubkey,
+    /// Parent session, if created as a child
+    pub parent_session: Option<Pubkey>,
+    /// Timestamp of creation
+    pub timestamp: i64,
+}
+
+/// Event emitted after a batch of operations executes in a session
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct BatchExecuted {
+    /// Session the batch executed in
+    pub session: Pubkey,
+    /// Caller that submitted the batch
+    pub caller: Pubkey,
+    /// Number of operations in the batch
+    pub operations: u8,
+    /// Session usage count after the batch
+    pub usage_count: u64,
+    /// Timestamp of execution
+    pub timestamp: i64,
+}
+
+/// Event emitted just before a batch invokes a registered function
+///
+/// Logs outlive failed transactions, so when the function fails this is the
+/// record of which operation and function raised the transaction's error.
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FunctionInvoked {
+    /// Session the batch executes in
+    pub session: Pubkey,
+    /// Index of the operation within the batch
+    pub operation_index: u8,
+    /// Registry ID of the function
+    pub registry_id: u64,
+    /// Program implementing the function
+    pub program_id: Pubkey,
+}
+
+/// Event emitted when an asynchronous execution reports its result
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionCallback {
+    /// Session the execution belongs to
+    pub session: Pubkey,
+    /// Identifier of the execution, unique per session
+    pub execution_id: u64,
+    /// Whether the execution succeeded
+    pub success: bool,
+    /// Program error code when the execution failed
+    pub error_code: Option<u32>,
+    /// Timestamp of the callback
+    pub timestamp: i64,
+}
+
+/// Event emitted when a guard or authorization is consumed
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AuthorizationUsed {
+    /// Session the authorization was checked against
+    pub session: Pubkey,
+    /// Guard or authorization account that approved the call
+    pub authorization: Pubkey,
+    /// Caller that was authorized
+    pub caller: Pubkey,
+    /// Timestamp of use
+    pub timestamp: i64,
+}
+
+// ================================
+// Cascading Invalidation Events
+// ================================
+
+/// Event emitted when a session is invalidated
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SessionInvalidated {
+    /// The session that was invalidated
+    pub session: Pubkey,
+    /// Number of direct children invalidated
+    pub children_invalidated: u8,
+    /// Maximum cascade depth attempted
+    pub cascade_depth: u8,
+    /// Timestamp of invalidation
+    pub timestamp: i64,
+}
+
+/// Event emitted when cascade invalidation is required but couldn't complete due to limits
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CascadeInvalidationRequired {
+    /// Parent session that triggered the cascade
+    pub parent_session: Pubkey,
+    /// Child sessions that need invalidation
+    pub child_sessions: Vec<Pubkey>,
+    /// Remaining depth for cascade
+    pub depth_remaining: u8,
+    /// Reason cascade was deferred
+    pub reason: CascadeDeferReason,
+}
+
+/// Event emitted when a batch of sessions is invalidated
+#[event]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct BatchInvalidated {
+    /// Parent session that triggered the batch
+    pub parent: Pubkey,
+    /// Number of sessions successfully invalidated
+    pub invalidated: u32,
+    /// Total sessions attempted
+    pub total: u32,
+    /// Compute units consumed
+    pub compute_units_used: u64,
+}
+
+/// Reasons why cascade invalidation might be deferred
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum CascadeDeferReason {
+    /// Not enough compute units remaining
+    ComputeUnitsExhausted,
+    /// Maximum cascade depth reached
+    MaxDepthReached,
+    /// Child session account not found in remaining_accounts
+    ChildAccountNotFound,
+    /// Transaction size limits
+    TransactionTooLarge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{Discriminator, Event};
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = BatchExecuted {
+            session: Pubkey::new_unique(),
+            caller: Pubkey::new_unique(),
+            operations: 3,
+            usage_count: 7,
+            timestamp: 1_700_000_000,
+        };
+
+        // Borsh, as emitted in `Program data:` logs
+        let data = event.data();
+        let mut body = data.strip_prefix(BatchExecuted::DISCRIMINATOR).unwrap();
+        let decoded = BatchExecuted::deserialize(&mut body).unwrap();
+        assert_eq!(decoded.session, event.session);
+        assert_eq!(decoded.usage_count, 7);
+
+        // JSON, as exchanged by off-chain services
+        let json = serde_json::to_string(&event).unwrap();
+        let decoded: BatchExecuted = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.caller, event.caller);
+    }
+}
@@ -0,0 +1,299 @@
+// Instruction introspection over the instructions sysvar
+//
+// Guards that depend on "same-transaction" facts (a signature verified by the
+// ed25519 precompile, a compute budget request, an instruction from a specific
+// program) read them from the instructions sysvar. These helpers enumerate the
+// transaction's instructions and locate or require the ones a guard needs.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+#[allow(deprecated)]
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+/// Compute budget program, whose instructions set limits and priority fees
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// ed25519 signature verification precompile
+pub const ED25519_PROGRAM_ID: Pubkey = anchor_lang::solana_program::ed25519_program::ID;
+
+/// Instruction index meaning "the precompile instruction itself"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Size of the ed25519 precompile's per-signature offsets
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Error type for instruction introspection
+///
+/// Programs propagate these from their own instructions, so the codes start
+/// at 9000 to stay clear of the programs' error enums numbered from 6000.
+#[error_code(offset = 9000)]
+pub enum IntrospectionError {
+    #[msg("Account is not the instructions sysvar")]
+    InvalidInstructionsSysvar,
+    #[msg("Required instruction is not present in the transaction")]
+    MissingInstruction,
+    #[msg("Malformed ed25519 verification instruction")]
+    InvalidEd25519Instruction,
+}
+
+/// Index of the currently executing top-level instruction
+#[allow(deprecated)]
+pub fn current_index(instructions_sysvar: &AccountInfo) -> Result<u16> {
+    check_sysvar(instructions_sysvar)?;
+    Ok(load_current_index_checked(instructions_sysvar)?)
+}
+
+/// Every top-level instruction of the current transaction, in order
+#[allow(deprecated)]
+pub fn transaction_instructions(instructions_sysvar: &AccountInfo) -> Result<Vec<Instruction>> {
+    check_sysvar(instructions_sysvar)?;
+    let mut instructions = Vec::new();
+    // Loading past the last instruction fails, which ends the enumeration
+    while let Ok(instruction) = load_instruction_at_checked(instructions.len(), instructions_sysvar)
+    {
+        instructions.push(instruction);
+    }
+    Ok(instructions)
+}
+
+/// First instruction invoking `program_id`, with its index
+pub fn find_instruction(
+    instructions_sysvar: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Option<(u16, Instruction)>> {
+    Ok(transaction_instructions(instructions_sysvar)?
+        .into_iter()
+        .enumerate()
+        .find(|(_, instruction)| instruction.program_id == *program_id)
+        .map(|(index, instruction)| (index as u16, instruction)))
+}
+
+/// First instruction invoking `program_id`, failing if there is none
+pub fn require_instruction(
+    instructions_sysvar: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<(u16, Instruction)> {
+    find_instruction(instructions_sysvar, program_id)?
+        .ok_or_else(|| error!(IntrospectionError::MissingInstruction))
+}
+
+/// Check if the transaction requests a compute budget
+pub fn has_compute_budget(instructions_sysvar: &AccountInfo) -> Result<bool> {
+    Ok(find_instruction(instructions_sysvar, &COMPUTE_BUDGET_PROGRAM_ID)?.is_some())
+}
+
+/// Signature checked by the ed25519 precompile
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ed25519Signature {
+    pub public_key: Pubkey,
+    pub signature: [u8; 64],
+    pub message: Vec<u8>,
+}
+
+/// Signatures verified by an ed25519 precompile instruction
+///
+/// The precompile fails the whole transaction on any invalid signature, so
+/// every signature returned here has been verified. Offsets pointing into
+/// other instructions are rejected, as their data is not covered here.
+pub fn ed25519_signatures(instruction: &Instruction) -> Result<Vec<Ed25519Signature>> {
+    require_keys_eq!(
+        instruction.program_id,
+        ED25519_PROGRAM_ID,
+        IntrospectionError::InvalidEd25519Instruction
+    );
+
+    let data = &instruction.data;
+    let count = *data
+        .first()
+        .ok_or(IntrospectionError::InvalidEd25519Instruction)? as usize;
+    let slice = |offset: u16, len: usize| {
+        data.get(offset as usize..offset as usize + len)
+            .ok_or(IntrospectionError::InvalidEd25519Instruction)
+    };
+
+    let mut signatures = Vec::with_capacity(count);
+    for index in 0..count {
+        let start = 2 + index * ED25519_OFFSETS_LEN;
+        let offsets = data
+            .get(start..start + ED25519_OFFSETS_LEN)
+            .ok_or(IntrospectionError::InvalidEd25519Instruction)?;
+        let field = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+        let [signature_offset, signature_ix, public_key_offset, public_key_ix, message_offset, message_size, message_ix] =
+            [0, 1, 2, 3, 4, 5, 6].map(field);
+
+        require!(
+            [signature_ix, public_key_ix, message_ix]
+                .iter()
+                .all(|ix| *ix == CURRENT_INSTRUCTION),
+            IntrospectionError::InvalidEd25519Instruction
+        );
+
+        let public_key = slice(public_key_offset, 32)?;
+        signatures.push(Ed25519Signature {
+            public_key: Pubkey::try_from(public_key)
+                .map_err(|_| IntrospectionError::InvalidEd25519Instruction)?,
+            signature: slice(signature_offset, 64)?
+                .try_into()
+                .map_err(|_| IntrospectionError::InvalidEd25519Instruction)?,
+            message: slice(message_offset, message_size as usize)?.to_vec(),
+        });
+    }
+    Ok(signatures)
+}
+
+/// Check if an ed25519 precompile in the transaction verified `message` from `signer`
+///
+/// Precompile instructions whose signature data lives in other instructions
+/// cannot carry the approval and are skipped rather than failing the check.
+pub fn has_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<bool> {
+    for instruction in transaction_instructions(instructions_sysvar)? {
+        if instruction.program_id != ED25519_PROGRAM_ID {
+            continue;
+        }
+        let Ok(signatures) = ed25519_signatures(&instruction) else {
+            continue;
+        };
+        if signatures
+            .iter()
+            .any(|signature| signature.public_key == *signer && signature.message == message)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn check_sysvar(instructions_sysvar: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *instructions_sysvar.key,
+        anchor_lang::solana_program::sysvar::instructions::ID,
+        IntrospectionError::InvalidInstructionsSysvar
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[allow(deprecated)]
+    use anchor_lang::solana_program::sysvar::instructions::{
+        construct_instructions_data, BorrowedInstruction,
+    };
+
+    /// Instructions sysvar data for `instructions`, executing the last one
+    fn sysvar_data(instructions: &[Instruction]) -> Vec<u8> {
+        let borrowed: Vec<_> = instructions
+            .iter()
+            .map(|ix| BorrowedInstruction {
+                program_id: &ix.program_id,
+                accounts: Vec::new(),
+                data: &ix.data,
+            })
+            .collect();
+        let mut data = construct_instructions_data(&borrowed);
+        let len = data.len();
+        data[len - 2..].copy_from_slice(&(instructions.len() as u16 - 1).to_le_bytes());
+        data
+    }
+
+    /// ed25519 precompile instruction for one signature with inline data
+    fn ed25519_instruction(public_key: &Pubkey, message: &[u8]) -> Instruction {
+        let public_key_offset = 2 + ED25519_OFFSETS_LEN as u16;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            CURRENT_INSTRUCTION,
+            public_key_offset,
+            CURRENT_INSTRUCTION,
+            message_offset,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(public_key.as_ref());
+        data.extend_from_slice(&[7u8; 64]);
+        data.extend_from_slice(message);
+
+        Instruction {
+            program_id: ED25519_PROGRAM_ID,
+            accounts: Vec::new(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_transaction_introspection() {
+        let signer = Pubkey::new_unique();
+        // Unrelated precompile reading its signature from another instruction
+        let mut foreign = ed25519_instruction(&Pubkey::new_unique(), b"elsewhere");
+        foreign.data[4..6].copy_from_slice(&0u16.to_le_bytes());
+        let instructions = vec![
+            Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &[2, 0, 0, 0, 0], vec![]),
+            foreign,
+            ed25519_instruction(&signer, b"batch hash"),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]),
+        ];
+
+        let key = anchor_lang::solana_program::sysvar::instructions::ID;
+        let owner = Pubkey::default();
+        let mut lamports = 0;
+        let mut data = sysvar_data(&instructions);
+        let sysvar = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(current_index(&sysvar).unwrap(), 3);
+        assert_eq!(transaction_instructions(&sysvar).unwrap().len(), 4);
+        assert!(has_compute_budget(&sysvar).unwrap());
+        assert_eq!(
+            require_instruction(&sysvar, &ED25519_PROGRAM_ID).unwrap().0,
+            1
+        );
+        assert!(require_instruction(&sysvar, &Pubkey::new_unique()).is_err());
+
+        assert!(has_ed25519_signature(&sysvar, &signer, b"batch hash").unwrap());
+        assert!(!has_ed25519_signature(&sysvar, &signer, b"other").unwrap());
+        assert!(!has_ed25519_signature(&sysvar, &Pubkey::new_unique(), b"batch hash").unwrap());
+    }
+
+    #[test]
+    fn test_ed25519_rejects_foreign_offsets() {
+        let mut instruction = ed25519_instruction(&Pubkey::new_unique(), b"message");
+        let signatures = ed25519_signatures(&instruction).unwrap();
+        assert_eq!(signatures[0].signature, [7u8; 64]);
+
+        // Signature data taken from instruction 0 instead of the precompile itself
+        instruction.data[4..6].copy_from_slice(&0u16.to_le_bytes());
+        assert!(ed25519_signatures(&instruction).is_err());
+
+        instruction.data.truncate(40);
+        assert!(ed25519_signatures(&instruction).is_err());
+    }
+
+    #[test]
+    fn test_rejects_wrong_sysvar() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = Vec::new();
+        let account =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false, 0);
+        assert!(transaction_instructions(&account).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+// Valence Common - Definitions shared by on-chain programs and off-chain crates
+//
+// Programs depend on this crate for the types they emit and derive, and clients
+// depend on it to decode them, so both sides always agree on the schema.
+
+// ================================
+// Module Declarations
+// ================================
+
+/// Canonical event schema emitted by programs and decoded by clients
+pub mod events;
+
+/// Seed constants and PDA derivations shared across crates
+pub mod pdas;
+
+/// Checked fixed-point and integer math
+pub mod math;
+
+/// Same-transaction checks over the instructions sysvar
+pub mod introspection;
+
+// ================================
+// Public API Re-exports
+// ================================
+
+pub use events::*;
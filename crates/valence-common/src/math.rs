@@ -0,0 +1,241 @@
+// Checked fixed-point and integer math for protocol functions
+//
+// Amounts multiplied together (collateral times price, assets times shares)
+// overflow u64 at realistic values, so every product here is taken at double
+// width and every result is checked. Operations return `None` instead of
+// wrapping or panicking, and round toward zero unless noted.
+use anchor_lang::prelude::*;
+
+/// Basis points in one whole
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// ================================
+// Integer Helpers
+// ================================
+
+/// `a * b / denominator`, rounding down, without intermediate overflow
+pub fn mul_div(a: u64, b: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    u64::try_from(a as u128 * b as u128 / denominator as u128).ok()
+}
+
+/// `a * b / denominator`, rounding up, without intermediate overflow
+pub fn mul_div_ceil(a: u64, b: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    u64::try_from((a as u128 * b as u128).div_ceil(denominator as u128)).ok()
+}
+
+/// `bps` basis points of `amount`, rounding down
+pub fn apply_bps(amount: u64, bps: u16) -> Option<u64> {
+    mul_div(amount, bps as u64, BPS_DENOMINATOR)
+}
+
+/// `part` as basis points of `whole`, rounding down
+pub fn to_bps(part: u64, whole: u64) -> Option<u64> {
+    mul_div(part, BPS_DENOMINATOR, whole)
+}
+
+/// Integer square root, rounding down
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method from an initial guess above the root
+    let mut x = 1u128 << (128 - value.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (x + value / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
+}
+
+// ================================
+// Q64.64 Fixed Point
+// ================================
+
+/// Unsigned fixed-point number with 64 integer and 64 fractional bits
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct Q64x64(u128);
+
+impl Q64x64 {
+    /// Number of fractional bits
+    pub const FRAC_BITS: u32 = 64;
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << Self::FRAC_BITS);
+    pub const MAX: Self = Self(u128::MAX);
+
+    /// Wrap raw Q64.64 bits
+    pub const fn from_bits(bits: u128) -> Self {
+        Self(bits)
+    }
+
+    /// Raw Q64.64 bits
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    /// Exact value of an integer
+    pub const fn from_int(value: u64) -> Self {
+        Self((value as u128) << Self::FRAC_BITS)
+    }
+
+    /// `numerator / denominator`, rounding down
+    pub fn from_ratio(numerator: u64, denominator: u64) -> Option<Self> {
+        Self::from_int(numerator).checked_div(Self::from_int(denominator))
+    }
+
+    /// Value of `bps` basis points
+    pub fn from_bps(bps: u64) -> Option<Self> {
+        Self::from_ratio(bps, BPS_DENOMINATOR)
+    }
+
+    /// Integer part
+    pub const fn floor(self) -> u64 {
+        (self.0 >> Self::FRAC_BITS) as u64
+    }
+
+    /// Smallest integer not below the value
+    pub fn ceil(self) -> Option<u64> {
+        let fraction = self.0 & (u64::MAX as u128);
+        let floor = self.floor();
+        if fraction == 0 {
+            Some(floor)
+        } else {
+            floor.checked_add(1)
+        }
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// Product, rounding down
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        // Split into 64-bit halves so the 256-bit product is never materialized
+        let (a_hi, a_lo) = (self.0 >> 64, self.0 & (u64::MAX as u128));
+        let (b_hi, b_lo) = (other.0 >> 64, other.0 & (u64::MAX as u128));
+
+        let high = a_hi.checked_mul(b_hi)?.checked_mul(Self::ONE.0)?;
+        let cross = (a_hi * b_lo).checked_add(a_lo * b_hi)?;
+        let low = (a_lo * b_lo) >> Self::FRAC_BITS;
+        high.checked_add(cross)?.checked_add(low).map(Self)
+    }
+
+    /// Quotient, rounding down
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.0 == 0 {
+            return None;
+        }
+        let integer = self.0 / other.0;
+        if integer > u64::MAX as u128 {
+            return None;
+        }
+
+        // Long division for the fractional bits, as the remainder may not
+        // fit once shifted left by 64
+        let mut remainder = self.0 % other.0;
+        let mut fraction = 0u128;
+        for _ in 0..Self::FRAC_BITS {
+            let carry = remainder >> 127;
+            remainder <<= 1;
+            fraction <<= 1;
+            if carry == 1 || remainder >= other.0 {
+                remainder = remainder.wrapping_sub(other.0);
+                fraction |= 1;
+            }
+        }
+        Some(Self((integer << Self::FRAC_BITS) | fraction))
+    }
+
+    /// Value raised to an integer power, by repeated squaring
+    pub fn checked_pow(self, mut exponent: u32) -> Option<Self> {
+        let mut base = self;
+        let mut result = Self::ONE;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.checked_mul(base)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.checked_mul(base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Square root, rounding down
+    pub fn sqrt(self) -> Self {
+        // sqrt(bits / 2^64) * 2^64 = sqrt(bits * 2^64); shift as far as fits
+        // (an even amount) and scale the root back up by half the remainder
+        let shift = (self.0.leading_zeros() & !1).min(Self::FRAC_BITS);
+        Self(isqrt(self.0 << shift) << ((Self::FRAC_BITS - shift) / 2))
+    }
+
+    /// Product with an integer amount, rounding down
+    pub fn mul_int(self, amount: u64) -> Option<u64> {
+        self.checked_mul(Self::from_int(amount)).map(Self::floor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_helpers() {
+        // Collateral times price overflows u64 but not the result
+        assert_eq!(
+            mul_div(u64::MAX, 3_000_000_000, 6_000_000_000),
+            Some(u64::MAX / 2)
+        );
+        assert_eq!(mul_div(1, 1, 0), None);
+        assert_eq!(mul_div(u64::MAX, 2, 1), None);
+        assert_eq!(mul_div_ceil(10, 1, 3), Some(4));
+        assert_eq!(apply_bps(1_000_000, 25), Some(2_500));
+        assert_eq!(to_bps(1, 4), Some(2_500));
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt(99), 9);
+    }
+
+    #[test]
+    fn test_fixed_point_arithmetic() {
+        let half = Q64x64::from_ratio(1, 2).unwrap();
+        let three = Q64x64::from_int(3);
+
+        assert_eq!(three.checked_mul(half).unwrap().floor(), 1);
+        assert_eq!(three.checked_mul(half).unwrap().ceil(), Some(2));
+        assert_eq!(three.checked_div(half), Some(Q64x64::from_int(6)));
+        assert_eq!(three.checked_pow(4), Some(Q64x64::from_int(81)));
+        assert_eq!(half.checked_pow(2), Q64x64::from_ratio(1, 4));
+        assert_eq!(Q64x64::from_int(u64::MAX).checked_mul(three), None);
+        assert_eq!(Q64x64::from_int(1 << 40).checked_pow(2), None);
+        assert_eq!(Q64x64::from_bps(2_500).unwrap().mul_int(1_000), Some(250));
+    }
+
+    #[test]
+    fn test_fixed_point_sqrt() {
+        assert_eq!(Q64x64::from_int(144).sqrt(), Q64x64::from_int(12));
+        assert_eq!(
+            Q64x64::from_ratio(1, 4).unwrap().sqrt(),
+            Q64x64::from_ratio(1, 2).unwrap()
+        );
+        assert_eq!(Q64x64::MAX.sqrt().floor(), u32::MAX as u64);
+
+        // sqrt(2) to within the 64 fractional bits
+        let root = Q64x64::from_int(2).sqrt();
+        let square = root.checked_mul(root).unwrap();
+        assert!(Q64x64::from_int(2).checked_sub(square).unwrap().to_bits() < 1 << 2);
+    }
+}
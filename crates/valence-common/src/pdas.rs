@@ -0,0 +1,179 @@
+// PDA seeds and derivations for Valence programs
+//
+// Every seed a Valence program signs with or constrains an account by is
+// defined here, together with the derivation that uses it. Programs, the SDK,
+// and the runtime all derive addresses through these functions so a seed
+// change cannot leave one side deriving stale addresses.
+use anchor_lang::prelude::*;
+
+// ================================
+// Seed Constants
+// ================================
+
+/// Seed prefix for namespace and session child account PDAs
+pub const NAMESPACE_SEED: &[u8] = b"namespace";
+
+/// Seed for session account PDAs
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Seed for session nonce PDAs used by offline-signed batches
+pub const SESSION_NONCE_SEED: &[u8] = b"session_nonce";
+
+/// Seed for session fee vault PDAs that reimburse relayers
+pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+
+/// Seed for session statistics PDAs
+pub const SESSION_STATS_SEED: &[u8] = b"session_stats";
+
+/// Seed for batch commitment PDAs of the commit-reveal flow
+pub const BATCH_COMMITMENT_SEED: &[u8] = b"batch_commitment";
+
+/// Seed for batch buffer PDAs accumulating chunked batches
+pub const BATCH_BUFFER_SEED: &[u8] = b"batch_buffer";
+
+/// Seed for session operation data PDAs holding CPI instruction data
+pub const OPERATION_DATA_SEED: &[u8] = b"operation_data";
+
+/// Seed for the kernel's global CPI allowlist
+pub const CPI_ALLOWLIST_SEED: &[u8] = b"cpi_allowlist";
+
+/// Seeds of the kernel's registered function program addresses
+pub const ZK_GATEWAY_FUNCTION_SEED: &[u8] = b"zk_gateway";
+pub const TOKEN_SWAP_FUNCTION_SEED: &[u8] = b"token_swap";
+
+/// Seed for vault state PDAs
+pub const VAULT_STATE_SEED: &[u8] = b"vault_state";
+
+/// Seed for vault token account PDAs holding deposited assets
+pub const VAULT_TOKEN_SEED: &[u8] = b"vault_token";
+
+/// Seed for vault position PDAs holding an owner's shares
+pub const VAULT_POSITION_SEED: &[u8] = b"vault_position";
+
+/// Seed for escrow state PDAs
+pub const ESCROW_STATE_SEED: &[u8] = b"escrow_state";
+
+/// Seed for escrow agreement PDAs over session child accounts
+pub const ESCROW_AGREEMENT_SEED: &[u8] = b"escrow_agreement";
+
+/// Seed for escrow vault PDAs (accounts holding escrowed funds)
+pub const ESCROW_VAULT_SEED: &[u8] = b"escrow_vault";
+
+// ================================
+// Kernel Derivations
+// ================================
+
+/// Derive a namespace PDA from the bytes of its path
+pub fn namespace(path: &[u8], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NAMESPACE_SEED, path], program_id)
+}
+
+/// Derive the nonce account of a session
+pub fn session_nonce(session: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SESSION_NONCE_SEED, session.as_ref()], program_id)
+}
+
+/// Derive the fee vault of a session
+pub fn fee_vault(session: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_VAULT_SEED, session.as_ref()], program_id)
+}
+
+/// Derive the statistics account of a session
+pub fn session_stats(session: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SESSION_STATS_SEED, session.as_ref()], program_id)
+}
+
+/// Derive the commitment to a batch hash made by a session
+pub fn batch_commitment(session: &Pubkey, batch_hash: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BATCH_COMMITMENT_SEED, session.as_ref(), batch_hash],
+        program_id,
+    )
+}
+
+/// Derive a session's batch buffer with `buffer_id`
+pub fn batch_buffer(session: &Pubkey, buffer_id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BATCH_BUFFER_SEED, session.as_ref(), &buffer_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Derive the operation data account of a session
+pub fn operation_data(session: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OPERATION_DATA_SEED, session.as_ref()], program_id)
+}
+
+/// Derive the kernel's global CPI allowlist
+pub fn cpi_allowlist(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CPI_ALLOWLIST_SEED], program_id)
+}
+
+/// Derive the address a registered function resolves to
+pub fn function_entry(function_seed: &[u8], registry_id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[function_seed, &registry_id.to_le_bytes()], program_id)
+}
+
+// ================================
+// Function State Derivations
+// ================================
+
+/// Derive vault state PDA address
+/// One vault per authority and asset
+pub fn vault_state(authority: &Pubkey, asset_mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_STATE_SEED, authority.as_ref(), asset_mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Derive the token account PDA holding a vault's assets
+pub fn vault_token(vault_state: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_TOKEN_SEED, vault_state.as_ref()], program_id)
+}
+
+/// Derive vault position PDA address
+/// One position per vault and owner
+pub fn vault_position(vault: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_POSITION_SEED, vault.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// Derive escrow state PDA address
+/// Creates deterministic address based on seller, asset, and nonce
+pub fn escrow_state(
+    seller: &Pubkey,
+    asset_mint: &Pubkey,
+    nonce: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ESCROW_STATE_SEED,
+            seller.as_ref(),
+            asset_mint.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derive escrow agreement PDA address
+/// One agreement per depositor and session child account
+pub fn escrow_agreement(
+    depositor: &Pubkey,
+    child_account: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ESCROW_AGREEMENT_SEED, depositor.as_ref(), child_account.as_ref()],
+        program_id,
+    )
+}
+
+/// Derive escrow vault PDA address holding an escrow's funds
+pub fn escrow_vault(escrow_state: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_VAULT_SEED, escrow_state.as_ref()], program_id)
+}
@@ -0,0 +1,368 @@
+//! Read-only query API over the index
+//!
+//! REST routes return JSON rows directly; `POST /graphql` serves the same
+//! data through a GraphQL schema for explorers that prefer one query per page.
+
+use crate::records::{
+    AuthorizationRecord, BatchRecord, ExecutionRecord, FunctionRecord, SessionQuery,
+    SessionRecord, TransferRecord,
+};
+use crate::store::{IndexStore, DEFAULT_LIMIT};
+use crate::{IndexerError, Result};
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+/// GraphQL schema over an [`IndexStore`]
+pub type IndexSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Error returned to API clients as `{"error": ...}`
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl From<IndexerError> for ApiError {
+    fn from(err: IndexerError) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            Json(serde_json::json!({ "error": self.message })),
+        )
+            .into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// `?limit=` for per-session lists
+#[derive(Debug, Default, Deserialize)]
+pub struct LimitQuery {
+    pub limit: Option<u32>,
+}
+
+impl LimitQuery {
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+}
+
+/// Root of the GraphQL schema
+pub struct QueryRoot;
+
+fn store<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Arc<dyn IndexStore>> {
+    ctx.data::<Arc<dyn IndexStore>>()
+}
+
+#[Object]
+impl QueryRoot {
+    async fn session(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+    ) -> async_graphql::Result<Option<SessionRecord>> {
+        Ok(store(ctx)?.session(&address).await?)
+    }
+
+    async fn sessions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: SessionQuery,
+    ) -> async_graphql::Result<Vec<SessionRecord>> {
+        Ok(store(ctx)?.sessions(&filter).await?)
+    }
+
+    async fn batches(
+        &self,
+        ctx: &Context<'_>,
+        session: String,
+        #[graphql(default = 100)] limit: u32,
+    ) -> async_graphql::Result<Vec<BatchRecord>> {
+        Ok(store(ctx)?.batches(&session, limit).await?)
+    }
+
+    async fn executions(
+        &self,
+        ctx: &Context<'_>,
+        session: String,
+        #[graphql(default = 100)] limit: u32,
+    ) -> async_graphql::Result<Vec<ExecutionRecord>> {
+        Ok(store(ctx)?.executions(&session, limit).await?)
+    }
+
+    async fn authorizations(
+        &self,
+        ctx: &Context<'_>,
+        session: String,
+        #[graphql(default = 100)] limit: u32,
+    ) -> async_graphql::Result<Vec<AuthorizationRecord>> {
+        Ok(store(ctx)?.authorizations(&session, limit).await?)
+    }
+
+    async fn transfers(
+        &self,
+        ctx: &Context<'_>,
+        session: String,
+        #[graphql(default = 100)] limit: u32,
+    ) -> async_graphql::Result<Vec<TransferRecord>> {
+        Ok(store(ctx)?.transfers(&session, limit).await?)
+    }
+
+    async fn functions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<FunctionRecord>> {
+        Ok(store(ctx)?.functions().await?)
+    }
+}
+
+/// HTTP server for the index
+pub struct IndexerApi {
+    store: Arc<dyn IndexStore>,
+    schema: IndexSchema,
+    shutdown_tx: broadcast::Sender<()>,
+    server_handle: RwLock<Option<JoinHandle<()>>>,
+}
+
+impl IndexerApi {
+    pub fn new(store: Arc<dyn IndexStore>) -> Self {
+        let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .data(store.clone())
+            .finish();
+        let (shutdown_tx, _) = broadcast::channel(1);
+        Self {
+            store,
+            schema,
+            shutdown_tx,
+            server_handle: RwLock::new(None),
+        }
+    }
+
+    /// GraphQL schema, e.g. for printing its SDL
+    pub fn schema(&self) -> &IndexSchema {
+        &self.schema
+    }
+
+    /// Routes served by the API
+    pub fn router(self: &Arc<Self>) -> Router {
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/sessions", get(list_sessions))
+            .route("/sessions/:address", get(get_session))
+            .route("/sessions/:address/batches", get(list_batches))
+            .route("/sessions/:address/executions", get(list_executions))
+            .route("/sessions/:address/authorizations", get(list_authorizations))
+            .route("/sessions/:address/transfers", get(list_transfers))
+            .route("/functions", get(list_functions))
+            .route("/graphql", post(graphql))
+            .with_state(self.clone())
+    }
+
+    /// Serve the API until [`Self::stop`] is called
+    pub async fn start(self: &Arc<Self>, listen_addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(listen_addr).await?;
+        info!("Serving indexer API on http://{}", listener.local_addr()?);
+
+        let router = self.router();
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let handle = tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = shutdown_rx.recv().await;
+            };
+            if let Err(e) = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                warn!("Indexer API server error: {}", e);
+            }
+        });
+
+        *self.server_handle.write().await = Some(handle);
+        Ok(())
+    }
+
+    /// Stop serving the API
+    pub async fn stop(&self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        if let Some(handle) = self.server_handle.write().await.take() {
+            let _ = handle.await;
+        }
+        Ok(())
+    }
+}
+
+async fn list_sessions(
+    State(api): State<Arc<IndexerApi>>,
+    Query(query): Query<SessionQuery>,
+) -> ApiResult<Json<Vec<SessionRecord>>> {
+    Ok(Json(api.store.sessions(&query).await?))
+}
+
+async fn get_session(
+    State(api): State<Arc<IndexerApi>>,
+    Path(address): Path<String>,
+) -> ApiResult<Json<SessionRecord>> {
+    api.store
+        .session(&address)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("session {} not indexed", address),
+        })
+}
+
+async fn list_batches(
+    State(api): State<Arc<IndexerApi>>,
+    Path(address): Path<String>,
+    Query(query): Query<LimitQuery>,
+) -> ApiResult<Json<Vec<BatchRecord>>> {
+    Ok(Json(api.store.batches(&address, query.limit()).await?))
+}
+
+async fn list_executions(
+    State(api): State<Arc<IndexerApi>>,
+    Path(address): Path<String>,
+    Query(query): Query<LimitQuery>,
+) -> ApiResult<Json<Vec<ExecutionRecord>>> {
+    Ok(Json(api.store.executions(&address, query.limit()).await?))
+}
+
+async fn list_authorizations(
+    State(api): State<Arc<IndexerApi>>,
+    Path(address): Path<String>,
+    Query(query): Query<LimitQuery>,
+) -> ApiResult<Json<Vec<AuthorizationRecord>>> {
+    Ok(Json(api.store.authorizations(&address, query.limit()).await?))
+}
+
+async fn list_transfers(
+    State(api): State<Arc<IndexerApi>>,
+    Path(address): Path<String>,
+    Query(query): Query<LimitQuery>,
+) -> ApiResult<Json<Vec<TransferRecord>>> {
+    Ok(Json(api.store.transfers(&address, query.limit()).await?))
+}
+
+async fn list_functions(
+    State(api): State<Arc<IndexerApi>>,
+) -> ApiResult<Json<Vec<FunctionRecord>>> {
+    Ok(Json(api.store.functions().await?))
+}
+
+async fn graphql(
+    State(api): State<Arc<IndexerApi>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(api.schema.execute(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryIndexStore;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    fn session(address: &str, owner: &str, created_at: i64) -> SessionRecord {
+        SessionRecord {
+            address: address.to_string(),
+            owner: owner.to_string(),
+            shard: "shard".to_string(),
+            parent_session: None,
+            namespace: Some(format!("shard/{address}")),
+            active: true,
+            usage_count: 0,
+            created_at,
+            created_signature: None,
+            last_slot: 1,
+        }
+    }
+
+    async fn api() -> Arc<IndexerApi> {
+        let store = Arc::new(MemoryIndexStore::new());
+        store.put_session(&session("a", "alice", 1)).await.unwrap();
+        store.put_session(&session("b", "bob", 2)).await.unwrap();
+        store.put_session(&session("c", "alice", 3)).await.unwrap();
+        Arc::new(IndexerApi::new(store))
+    }
+
+    async fn body(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rest_routes() {
+        let router = api().await.router();
+
+        let response = router
+            .clone()
+            .oneshot(Request::get("/sessions?owner=alice").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let sessions = body(response).await;
+        let addresses: Vec<&str> = sessions
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|session| session["address"].as_str().unwrap())
+            .collect();
+        assert_eq!(addresses, ["c", "a"]);
+
+        let response = router
+            .oneshot(Request::get("/sessions/missing").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_graphql() {
+        let router = api().await.router();
+        let query = serde_json::json!({
+            "query": "{ sessions(filter: { owner: \"bob\" }) { address usageCount } session(address: \"a\") { owner } }"
+        });
+        let response = router
+            .oneshot(
+                Request::post("/graphql")
+                    .header("content-type", "application/json")
+                    .body(Body::from(query.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = body(response).await;
+        assert_eq!(
+            response["data"],
+            serde_json::json!({
+                "sessions": [{ "address": "b", "usageCount": 0 }],
+                "session": { "owner": "alice" },
+            })
+        );
+    }
+}
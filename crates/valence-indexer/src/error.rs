@@ -0,0 +1,26 @@
+//! Indexer error types
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("Database error: {0}")]
+    Database(#[from] tokio_postgres::Error),
+
+    #[error("Runtime error: {0}")]
+    Runtime(#[from] valence_runtime::RuntimeError),
+
+    #[error("SDK error: {0}")]
+    Sdk(#[from] valence_sdk::SdkError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfiguration(String),
+}
+
+pub type Result<T> = std::result::Result<T, IndexerError>;
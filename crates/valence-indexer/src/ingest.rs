@@ -0,0 +1,508 @@
+//! Applies kernel events, decoded transactions and session account updates to the index
+
+use crate::records::{
+    AuthorizationRecord, BatchRecord, ExecutionRecord, InvocationRecord, SessionRecord,
+    TransferRecord,
+};
+use crate::store::IndexStore;
+use crate::{IndexerError, Result};
+use anchor_lang::AccountDeserialize;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+use valence_kernel::Session;
+use valence_runtime::decoder::{decode_transaction, DecodedTransaction};
+use valence_runtime::monitoring::{Event, EventStream, MonitorSource, StateMonitor, StateUpdate};
+use valence_sdk::{DecodedEvent, ValenceEvent, ValenceEvents};
+
+/// Where the indexer reads kernel activity from
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    /// WebSocket endpoint for log subscriptions and, without Geyser, account updates
+    pub ws_url: String,
+    pub source: MonitorSource,
+    pub commitment: CommitmentConfig,
+    /// RPC endpoint to fetch transactions from for transfer indexing; none disables it
+    pub rpc_url: Option<String>,
+}
+
+/// Writes decoded kernel activity into an [`IndexStore`]
+pub struct Indexer {
+    store: Arc<dyn IndexStore>,
+}
+
+impl Indexer {
+    pub fn new(store: Arc<dyn IndexStore>) -> Self {
+        Self { store }
+    }
+
+    pub fn store(&self) -> &Arc<dyn IndexStore> {
+        &self.store
+    }
+
+    /// Apply a kernel event decoded from transaction logs
+    pub async fn apply_event(&self, decoded: &DecodedEvent) -> Result<()> {
+        let signature = decoded.signature.to_string();
+        let slot = decoded.slot;
+
+        match &decoded.event {
+            ValenceEvent::SessionCreated(event) => {
+                let address = event.session.to_string();
+                let mut session = match self.store.session(&address).await? {
+                    Some(session) => session,
+                    None => SessionRecord {
+                        address,
+                        owner: String::new(),
+                        shard: String::new(),
+                        parent_session: None,
+                        namespace: None,
+                        active: true,
+                        usage_count: 0,
+                        created_at: 0,
+                        created_signature: None,
+                        last_slot: slot,
+                    },
+                };
+                session.owner = event.owner.to_string();
+                session.shard = event.shard.to_string();
+                session.parent_session = event.parent_session.map(|parent| parent.to_string());
+                session.created_at = event.timestamp;
+                session.created_signature = Some(signature);
+                session.last_slot = session.last_slot.max(slot);
+                self.store.put_session(&session).await
+            }
+
+            ValenceEvent::BatchExecuted(event) => {
+                let session = event.session.to_string();
+                self.store
+                    .put_batch(&BatchRecord {
+                        signature,
+                        session: session.clone(),
+                        caller: event.caller.to_string(),
+                        operations: event.operations,
+                        usage_count: event.usage_count,
+                        slot,
+                        timestamp: event.timestamp,
+                    })
+                    .await?;
+
+                // Sessions not yet seen are filled in by their account update
+                if let Some(mut record) = self.store.session(&session).await? {
+                    record.usage_count = record.usage_count.max(event.usage_count);
+                    record.last_slot = record.last_slot.max(slot);
+                    self.store.put_session(&record).await?;
+                }
+                Ok(())
+            }
+
+            ValenceEvent::FunctionInvoked(event) => {
+                self.store
+                    .put_invocation(&InvocationRecord {
+                        signature,
+                        session: event.session.to_string(),
+                        operation_index: event.operation_index,
+                        registry_id: event.registry_id,
+                        program_id: event.program_id.to_string(),
+                        slot,
+                    })
+                    .await
+            }
+
+            ValenceEvent::ExecutionCallback(event) => {
+                self.store
+                    .put_execution(&ExecutionRecord {
+                        session: event.session.to_string(),
+                        execution_id: event.execution_id,
+                        success: event.success,
+                        error_code: event.error_code,
+                        signature,
+                        slot,
+                        timestamp: event.timestamp,
+                    })
+                    .await
+            }
+
+            ValenceEvent::AuthorizationUsed(event) => {
+                self.store
+                    .put_authorization(&AuthorizationRecord {
+                        session: event.session.to_string(),
+                        authorization: event.authorization.to_string(),
+                        caller: event.caller.to_string(),
+                        signature,
+                        slot,
+                        timestamp: event.timestamp,
+                    })
+                    .await
+            }
+
+            ValenceEvent::SessionInvalidated(event) => {
+                if let Some(mut record) = self.store.session(&event.session.to_string()).await? {
+                    record.active = false;
+                    record.last_slot = record.last_slot.max(slot);
+                    self.store.put_session(&record).await?;
+                }
+                Ok(())
+            }
+
+            // Deferred cascades surface as SessionInvalidated once they complete
+            ValenceEvent::CascadeInvalidationRequired(_) | ValenceEvent::BatchInvalidated(_) => {
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply a decoded transaction, indexing the transfers its sessions caused
+    pub async fn apply_transaction(&self, decoded: &DecodedTransaction) -> Result<()> {
+        if !decoded.success {
+            return Ok(());
+        }
+        for transfer in &decoded.transfers {
+            let Some(session) = transfer.session else {
+                continue;
+            };
+            self.store
+                .put_transfer(&TransferRecord {
+                    signature: decoded.signature.clone(),
+                    instruction_index: transfer.position.index as u32,
+                    inner_index: transfer.position.inner_index.map(|index| index as u32),
+                    session: session.to_string(),
+                    program_id: transfer.program_id.to_string(),
+                    source: transfer.source.to_string(),
+                    destination: transfer.destination.to_string(),
+                    mint: transfer.mint.map(|mint| mint.to_string()),
+                    amount: transfer.amount,
+                    slot: decoded.slot,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Fetch and apply a transaction by signature
+    async fn index_transaction(&self, rpc: &RpcClient, signature: &Signature) -> Result<()> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let confirmed = rpc
+            .get_transaction_with_config(signature, config)
+            .await
+            .map_err(valence_runtime::RuntimeError::from)?;
+        self.apply_transaction(&decode_transaction(&confirmed)?).await
+    }
+
+    /// Apply an account update, indexing kernel session accounts
+    ///
+    /// Returns whether the update was a session newer than the indexed row.
+    pub async fn apply_state_update(&self, update: &StateUpdate) -> Result<bool> {
+        if update.owner != valence_kernel::ID {
+            return Ok(false);
+        }
+        let Ok(state) = Session::try_deserialize(&mut update.data.as_slice()) else {
+            return Ok(false);
+        };
+
+        let address = update.account.to_string();
+        let existing = self.store.session(&address).await?;
+        if existing
+            .as_ref()
+            .is_some_and(|session| session.last_slot > update.slot)
+        {
+            return Ok(false);
+        }
+
+        self.store
+            .put_session(&SessionRecord {
+                address,
+                owner: state.owner.to_string(),
+                shard: state.shard.to_string(),
+                parent_session: state.parent_session.map(|parent| parent.to_string()),
+                namespace: state.namespace.as_str().ok().map(str::to_string),
+                active: state.active,
+                usage_count: state.usage_count,
+                created_at: state.created_at,
+                created_signature: existing.and_then(|session| session.created_signature),
+                last_slot: update.slot,
+            })
+            .await?;
+        Ok(true)
+    }
+
+    /// Index kernel activity until `shutdown` fires or a feed closes
+    pub async fn run(
+        &self,
+        config: &IndexerConfig,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<()> {
+        let event_stream = Arc::new(EventStream::new());
+        let mut updates = event_stream.subscribe().await;
+        let monitor = StateMonitor::with_source(
+            config.ws_url.clone(),
+            config.source.clone(),
+            config.commitment,
+            event_stream,
+        )
+        .await?;
+        monitor.watch_program(valence_kernel::ID).await?;
+        monitor.start().await?;
+
+        let mut events = ValenceEvents::subscribe(&config.ws_url, config.commitment).await?;
+        let rpc = config
+            .rpc_url
+            .as_ref()
+            .map(|url| RpcClient::new_with_commitment(url.clone(), config.commitment));
+        // Events of one transaction arrive together; fetch each transaction once
+        let mut last_fetched = None;
+        info!("Indexing kernel {} from {}", valence_kernel::ID, config.ws_url);
+
+        let result = loop {
+            tokio::select! {
+                _ = shutdown.recv() => break Ok(()),
+                decoded = events.next() => {
+                    let Some(decoded) = decoded else {
+                        break Err(IndexerError::InvalidConfiguration(
+                            "kernel log subscription closed".to_string(),
+                        ));
+                    };
+                    if let Err(e) = self.apply_event(&decoded).await {
+                        error!("Failed to index event from {}: {}", decoded.signature, e);
+                    }
+                    if let Some(rpc) = &rpc {
+                        if last_fetched.replace(decoded.signature) != Some(decoded.signature) {
+                            if let Err(e) = self.index_transaction(rpc, &decoded.signature).await {
+                                error!("Failed to index transaction {}: {}", decoded.signature, e);
+                            }
+                        }
+                    }
+                }
+                update = updates.recv() => match update {
+                    Ok(Event::StateUpdate(update)) => {
+                        if let Err(e) = self.apply_state_update(&update).await {
+                            error!("Failed to index account {}: {}", update.account, e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Indexer skipped {} account updates", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break Ok(()),
+                },
+            }
+        };
+
+        monitor.stop().await?;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryIndexStore;
+    use anchor_lang::{prelude::Pubkey, AccountSerialize};
+    use solana_sdk::signature::Signature;
+    use valence_common::events::{
+        BatchExecuted, FunctionInvoked, SessionCreated, SessionInvalidated,
+    };
+
+    fn indexer() -> Indexer {
+        Indexer::new(Arc::new(MemoryIndexStore::new()))
+    }
+
+    fn decoded(slot: u64, event: ValenceEvent) -> DecodedEvent {
+        DecodedEvent {
+            signature: Signature::new_unique(),
+            slot,
+            event,
+        }
+    }
+
+    fn created(session: Pubkey, owner: Pubkey) -> ValenceEvent {
+        ValenceEvent::SessionCreated(SessionCreated {
+            session,
+            owner,
+            shard: Pubkey::new_unique(),
+            parent_session: None,
+            timestamp: 100,
+        })
+    }
+
+    fn session_update(address: Pubkey, session: &Session, slot: u64) -> StateUpdate {
+        let mut data = Vec::new();
+        session.try_serialize(&mut data).unwrap();
+        StateUpdate {
+            account: address,
+            slot,
+            lamports: 1,
+            data,
+            owner: valence_kernel::ID,
+            executable: false,
+            rent_epoch: 0,
+            cluster: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let indexer = indexer();
+        let (session, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let address = session.to_string();
+
+        indexer.apply_event(&decoded(10, created(session, owner))).await.unwrap();
+        let batch = ValenceEvent::BatchExecuted(BatchExecuted {
+            session,
+            caller: owner,
+            operations: 2,
+            usage_count: 1,
+            timestamp: 110,
+        });
+        let batch = decoded(11, batch);
+        indexer.apply_event(&batch).await.unwrap();
+        // Replayed events do not duplicate rows
+        indexer.apply_event(&batch).await.unwrap();
+
+        let record = indexer.store().session(&address).await.unwrap().unwrap();
+        assert_eq!(record.owner, owner.to_string());
+        assert!(record.active);
+        assert_eq!(record.usage_count, 1);
+        assert_eq!(record.last_slot, 11);
+        assert_eq!(indexer.store().batches(&address, 10).await.unwrap().len(), 1);
+
+        let invalidated = ValenceEvent::SessionInvalidated(SessionInvalidated {
+            session,
+            children_invalidated: 0,
+            cascade_depth: 0,
+            timestamp: 120,
+        });
+        indexer.apply_event(&decoded(12, invalidated)).await.unwrap();
+        let record = indexer.store().session(&address).await.unwrap().unwrap();
+        assert!(!record.active);
+    }
+
+    #[tokio::test]
+    async fn test_function_invocations() {
+        let indexer = indexer();
+        let (session, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for (slot, operation_index) in [(5, 0), (5, 1), (9, 0)] {
+            let invoked = ValenceEvent::FunctionInvoked(FunctionInvoked {
+                session,
+                operation_index,
+                registry_id: 1005,
+                program_id,
+            });
+            indexer.apply_event(&decoded(slot, invoked)).await.unwrap();
+        }
+
+        let functions = indexer.store().functions().await.unwrap();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].registry_id, 1005);
+        assert_eq!(functions[0].invocations, 3);
+        assert_eq!(functions[0].last_slot, 9);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_transfers() {
+        use valence_runtime::decoder::{InstructionPosition, Transfer};
+
+        let indexer = indexer();
+        let session = Pubkey::new_unique();
+        let transfer = |inner_index, session| Transfer {
+            position: InstructionPosition {
+                index: 0,
+                inner_index,
+            },
+            session,
+            program_id: solana_sdk::system_program::ID,
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            mint: None,
+            amount: 250,
+        };
+        let mut decoded = DecodedTransaction {
+            signature: Signature::new_unique().to_string(),
+            slot: 8,
+            block_time: None,
+            success: true,
+            instructions: Vec::new(),
+            transfers: vec![
+                transfer(None, None),
+                transfer(Some(0), Some(session)),
+                transfer(Some(1), Some(session)),
+            ],
+        };
+        indexer.apply_transaction(&decoded).await.unwrap();
+        // Replayed transactions do not duplicate rows
+        indexer.apply_transaction(&decoded).await.unwrap();
+
+        let transfers = indexer
+            .store()
+            .transfers(&session.to_string(), 10)
+            .await
+            .unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert!(transfers.iter().all(|transfer| transfer.amount == 250));
+
+        // Failed transactions moved nothing
+        decoded.signature = Signature::new_unique().to_string();
+        decoded.success = false;
+        indexer.apply_transaction(&decoded).await.unwrap();
+        assert_eq!(
+            indexer.store().transfers(&session.to_string(), 10).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_account_updates() {
+        let indexer = indexer();
+        let address = Pubkey::new_unique();
+        let namespace = "shard/indexed";
+        let mut namespace_path = [0u8; 128];
+        namespace_path[..namespace.len()].copy_from_slice(namespace.as_bytes());
+        let params = valence_kernel::CreateSessionParams {
+            namespace_path,
+            namespace_path_len: namespace.len() as u16,
+            metadata: [0u8; 32],
+            parent_session: None,
+        };
+        let mut session = Session::new(
+            params,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &anchor_lang::prelude::Clock::default(),
+        )
+        .unwrap();
+        session.usage_count = 4;
+
+        assert!(indexer
+            .apply_state_update(&session_update(address, &session, 20))
+            .await
+            .unwrap());
+        let record = indexer
+            .store()
+            .session(&address.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.namespace.as_deref(), Some("shard/indexed"));
+        assert_eq!(record.usage_count, 4);
+
+        // An older update is ignored
+        session.usage_count = 2;
+        assert!(!indexer
+            .apply_state_update(&session_update(address, &session, 19))
+            .await
+            .unwrap());
+
+        // Accounts owned by other programs are ignored
+        let mut foreign = session_update(Pubkey::new_unique(), &session, 30);
+        foreign.owner = Pubkey::new_unique();
+        assert!(!indexer.apply_state_update(&foreign).await.unwrap());
+    }
+}
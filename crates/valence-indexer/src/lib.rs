@@ -0,0 +1,26 @@
+//! Valence indexer
+//!
+//! Materializes kernel activity into queryable tables: sessions, the batches
+//! executed in them, asynchronous executions, authorization uses, the
+//! registered functions batches invoke and the transfers sessions cause.
+//! Kernel events are decoded from transaction logs, transfers from the
+//! transactions themselves and session accounts from the runtime's state
+//! monitor feed (WebSocket or Geyser); all are written through an
+//! [`IndexStore`] and served over REST and GraphQL.
+
+pub mod api;
+pub mod error;
+pub mod ingest;
+pub mod postgres;
+pub mod records;
+pub mod store;
+
+pub use api::IndexerApi;
+pub use error::{IndexerError, Result};
+pub use ingest::{Indexer, IndexerConfig};
+pub use postgres::PostgresIndexStore;
+pub use records::{
+    AuthorizationRecord, BatchRecord, ExecutionRecord, FunctionRecord, InvocationRecord,
+    SessionQuery, SessionRecord, TransferRecord,
+};
+pub use store::{IndexStore, MemoryIndexStore};
@@ -0,0 +1,65 @@
+//! Valence indexer service
+//!
+//! Configured through the environment:
+//! - `VALENCE_INDEXER_DATABASE_URL`: Postgres connection string (required)
+//! - `VALENCE_WS_URL`: cluster WebSocket endpoint, default `ws://localhost:8900`
+//! - `VALENCE_RPC_URL`: cluster RPC endpoint for transfer indexing, unset to disable
+//! - `VALENCE_INDEXER_LISTEN`: query API address, default `127.0.0.1:8090`
+
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{net::SocketAddr, process::ExitCode, sync::Arc};
+use tokio::sync::broadcast;
+use tracing::{error, info};
+use valence_indexer::{
+    Indexer, IndexerApi, IndexerConfig, IndexerError, PostgresIndexStore, Result,
+};
+use valence_runtime::MonitorSource;
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+async fn run() -> Result<()> {
+    let database_url = std::env::var("VALENCE_INDEXER_DATABASE_URL").map_err(|_| {
+        IndexerError::InvalidConfiguration("VALENCE_INDEXER_DATABASE_URL is not set".to_string())
+    })?;
+    let listen_addr: SocketAddr = env_or("VALENCE_INDEXER_LISTEN", "127.0.0.1:8090")
+        .parse()
+        .map_err(|e| IndexerError::InvalidConfiguration(format!("VALENCE_INDEXER_LISTEN: {e}")))?;
+    let config = IndexerConfig {
+        ws_url: env_or("VALENCE_WS_URL", "ws://localhost:8900"),
+        source: MonitorSource::WebSocket,
+        commitment: CommitmentConfig::confirmed(),
+        rpc_url: std::env::var("VALENCE_RPC_URL").ok(),
+    };
+
+    let store = Arc::new(PostgresIndexStore::connect(&database_url).await?);
+    let api = Arc::new(IndexerApi::new(store.clone()));
+    api.start(listen_addr).await?;
+
+    let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    tokio::spawn(async move {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Shutting down indexer");
+        let _ = shutdown_tx.send(());
+    });
+
+    let result = Indexer::new(store).run(&config, shutdown_rx).await;
+    api.stop().await?;
+    result
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("Indexer failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
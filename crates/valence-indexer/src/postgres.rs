@@ -0,0 +1,506 @@
+//! Postgres-backed index storage
+//!
+//! Unsigned counters are stored as BIGINT/INTEGER; slots and usage counts
+//! stay far below `i64::MAX`. Token amounts may not and are stored as the
+//! same 64 bits, so they round-trip exactly but do not sort above it.
+
+use crate::records::{
+    AuthorizationRecord, BatchRecord, ExecutionRecord, FunctionRecord, InvocationRecord,
+    SessionQuery, SessionRecord, TransferRecord,
+};
+use crate::store::{IndexStore, DEFAULT_LIMIT};
+use crate::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio_postgres::{types::ToSql, Client, NoTls, Row};
+use tracing::error;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        address TEXT PRIMARY KEY,
+        owner TEXT NOT NULL,
+        shard TEXT NOT NULL,
+        parent_session TEXT,
+        namespace TEXT,
+        active BOOLEAN NOT NULL,
+        usage_count BIGINT NOT NULL,
+        created_at BIGINT NOT NULL,
+        created_signature TEXT,
+        last_slot BIGINT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS sessions_owner_idx ON sessions (owner, created_at);
+    CREATE INDEX IF NOT EXISTS sessions_shard_idx ON sessions (shard, created_at);
+    CREATE TABLE IF NOT EXISTS batches (
+        signature TEXT NOT NULL,
+        session TEXT NOT NULL,
+        caller TEXT NOT NULL,
+        operations INTEGER NOT NULL,
+        usage_count BIGINT NOT NULL,
+        slot BIGINT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        PRIMARY KEY (signature, session)
+    );
+    CREATE INDEX IF NOT EXISTS batches_session_idx ON batches (session, slot);
+    CREATE TABLE IF NOT EXISTS executions (
+        session TEXT NOT NULL,
+        execution_id BIGINT NOT NULL,
+        success BOOLEAN NOT NULL,
+        error_code BIGINT,
+        signature TEXT NOT NULL,
+        slot BIGINT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        PRIMARY KEY (session, execution_id)
+    );
+    CREATE TABLE IF NOT EXISTS authorizations (
+        signature TEXT NOT NULL,
+        session TEXT NOT NULL,
+        authorization_account TEXT NOT NULL,
+        caller TEXT NOT NULL,
+        slot BIGINT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        PRIMARY KEY (signature, authorization_account, caller)
+    );
+    CREATE INDEX IF NOT EXISTS authorizations_session_idx ON authorizations (session, slot);
+    CREATE TABLE IF NOT EXISTS function_invocations (
+        signature TEXT NOT NULL,
+        session TEXT NOT NULL,
+        operation_index INTEGER NOT NULL,
+        registry_id BIGINT NOT NULL,
+        program_id TEXT NOT NULL,
+        slot BIGINT NOT NULL,
+        PRIMARY KEY (signature, session, operation_index)
+    );
+    CREATE INDEX IF NOT EXISTS function_invocations_registry_idx ON function_invocations (registry_id, slot);
+    CREATE TABLE IF NOT EXISTS transfers (
+        signature TEXT NOT NULL,
+        instruction_index INTEGER NOT NULL,
+        inner_index INTEGER,
+        session TEXT NOT NULL,
+        program_id TEXT NOT NULL,
+        source TEXT NOT NULL,
+        destination TEXT NOT NULL,
+        mint TEXT,
+        amount BIGINT NOT NULL,
+        slot BIGINT NOT NULL
+    );
+    -- Top-level transfers have no inner index, which a primary key cannot hold
+    CREATE UNIQUE INDEX IF NOT EXISTS transfers_position_idx
+        ON transfers (signature, instruction_index, (COALESCE(inner_index, -1)));
+    CREATE INDEX IF NOT EXISTS transfers_session_idx ON transfers (session, slot);
+";
+
+type QueryParam = Box<dyn ToSql + Sync + Send>;
+
+/// Index storage in a Postgres database
+pub struct PostgresIndexStore {
+    client: Arc<Client>,
+}
+
+impl PostgresIndexStore {
+    /// Connect using a libpq-style connection string and create the schema if missing
+    pub async fn connect(config: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(config, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("Index database connection closed: {}", e);
+            }
+        });
+
+        let store = Self::from_client(Arc::new(client));
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    /// Use an existing client; call [`Self::migrate`] if the schema may be missing
+    pub fn from_client(client: Arc<Client>) -> Self {
+        Self { client }
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        self.client.batch_execute(SCHEMA).await?;
+        Ok(())
+    }
+
+    async fn query_params(&self, sql: &str, params: &[QueryParam]) -> Result<Vec<Row>> {
+        let params: Vec<&(dyn ToSql + Sync)> = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect();
+        Ok(self.client.query(sql, &params).await?)
+    }
+}
+
+/// Build the SQL and parameters for listing sessions
+fn build_session_query(query: &SessionQuery) -> (String, Vec<QueryParam>) {
+    let mut conditions = Vec::new();
+    let mut params: Vec<QueryParam> = Vec::new();
+    let mut bind = |condition: &str, param: QueryParam| {
+        params.push(param);
+        conditions.push(condition.replace('?', &format!("${}", params.len())));
+    };
+
+    if let Some(owner) = &query.owner {
+        bind("owner = ?", Box::new(owner.clone()));
+    }
+    if let Some(shard) = &query.shard {
+        bind("shard = ?", Box::new(shard.clone()));
+    }
+    if let Some(active) = query.active {
+        bind("active = ?", Box::new(active));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    params.push(Box::new(i64::from(query.limit.unwrap_or(DEFAULT_LIMIT))));
+    params.push(Box::new(i64::from(query.offset)));
+    let sql = format!(
+        "SELECT * FROM sessions{} ORDER BY created_at DESC, address LIMIT ${} OFFSET ${}",
+        where_clause,
+        params.len() - 1,
+        params.len()
+    );
+    (sql, params)
+}
+
+fn session_row(row: &Row) -> Result<SessionRecord> {
+    Ok(SessionRecord {
+        address: row.try_get("address")?,
+        owner: row.try_get("owner")?,
+        shard: row.try_get("shard")?,
+        parent_session: row.try_get("parent_session")?,
+        namespace: row.try_get("namespace")?,
+        active: row.try_get("active")?,
+        usage_count: row.try_get::<_, i64>("usage_count")? as u64,
+        created_at: row.try_get("created_at")?,
+        created_signature: row.try_get("created_signature")?,
+        last_slot: row.try_get::<_, i64>("last_slot")? as u64,
+    })
+}
+
+fn batch_row(row: &Row) -> Result<BatchRecord> {
+    Ok(BatchRecord {
+        signature: row.try_get("signature")?,
+        session: row.try_get("session")?,
+        caller: row.try_get("caller")?,
+        operations: row.try_get::<_, i32>("operations")? as u8,
+        usage_count: row.try_get::<_, i64>("usage_count")? as u64,
+        slot: row.try_get::<_, i64>("slot")? as u64,
+        timestamp: row.try_get("timestamp")?,
+    })
+}
+
+fn execution_row(row: &Row) -> Result<ExecutionRecord> {
+    Ok(ExecutionRecord {
+        session: row.try_get("session")?,
+        execution_id: row.try_get::<_, i64>("execution_id")? as u64,
+        success: row.try_get("success")?,
+        error_code: row
+            .try_get::<_, Option<i64>>("error_code")?
+            .map(|code| code as u32),
+        signature: row.try_get("signature")?,
+        slot: row.try_get::<_, i64>("slot")? as u64,
+        timestamp: row.try_get("timestamp")?,
+    })
+}
+
+fn authorization_row(row: &Row) -> Result<AuthorizationRecord> {
+    Ok(AuthorizationRecord {
+        session: row.try_get("session")?,
+        authorization: row.try_get("authorization_account")?,
+        caller: row.try_get("caller")?,
+        signature: row.try_get("signature")?,
+        slot: row.try_get::<_, i64>("slot")? as u64,
+        timestamp: row.try_get("timestamp")?,
+    })
+}
+
+fn transfer_row(row: &Row) -> Result<TransferRecord> {
+    Ok(TransferRecord {
+        signature: row.try_get("signature")?,
+        instruction_index: row.try_get::<_, i32>("instruction_index")? as u32,
+        inner_index: row
+            .try_get::<_, Option<i32>>("inner_index")?
+            .map(|index| index as u32),
+        session: row.try_get("session")?,
+        program_id: row.try_get("program_id")?,
+        source: row.try_get("source")?,
+        destination: row.try_get("destination")?,
+        mint: row.try_get("mint")?,
+        amount: row.try_get::<_, i64>("amount")? as u64,
+        slot: row.try_get::<_, i64>("slot")? as u64,
+    })
+}
+
+fn function_row(row: &Row) -> Result<FunctionRecord> {
+    Ok(FunctionRecord {
+        registry_id: row.try_get::<_, i64>("registry_id")? as u64,
+        program_id: row.try_get("program_id")?,
+        invocations: row.try_get::<_, i64>("invocations")? as u64,
+        last_session: row.try_get("session")?,
+        last_slot: row.try_get::<_, i64>("slot")? as u64,
+    })
+}
+
+#[async_trait]
+impl IndexStore for PostgresIndexStore {
+    async fn session(&self, address: &str) -> Result<Option<SessionRecord>> {
+        let row = self
+            .client
+            .query_opt("SELECT * FROM sessions WHERE address = $1", &[&address])
+            .await?;
+        row.as_ref().map(session_row).transpose()
+    }
+
+    async fn put_session(&self, session: &SessionRecord) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO sessions
+                    (address, owner, shard, parent_session, namespace, active, usage_count,
+                     created_at, created_signature, last_slot)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (address) DO UPDATE SET
+                    owner = EXCLUDED.owner,
+                    shard = EXCLUDED.shard,
+                    parent_session = EXCLUDED.parent_session,
+                    namespace = EXCLUDED.namespace,
+                    active = EXCLUDED.active,
+                    usage_count = EXCLUDED.usage_count,
+                    created_at = EXCLUDED.created_at,
+                    created_signature = EXCLUDED.created_signature,
+                    last_slot = EXCLUDED.last_slot",
+                &[
+                    &session.address,
+                    &session.owner,
+                    &session.shard,
+                    &session.parent_session,
+                    &session.namespace,
+                    &session.active,
+                    &(session.usage_count as i64),
+                    &session.created_at,
+                    &session.created_signature,
+                    &(session.last_slot as i64),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn sessions(&self, query: &SessionQuery) -> Result<Vec<SessionRecord>> {
+        let (sql, params) = build_session_query(query);
+        self.query_params(&sql, &params)
+            .await?
+            .iter()
+            .map(session_row)
+            .collect()
+    }
+
+    async fn put_batch(&self, batch: &BatchRecord) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO batches
+                    (signature, session, caller, operations, usage_count, slot, timestamp)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (signature, session) DO NOTHING",
+                &[
+                    &batch.signature,
+                    &batch.session,
+                    &batch.caller,
+                    &i32::from(batch.operations),
+                    &(batch.usage_count as i64),
+                    &(batch.slot as i64),
+                    &batch.timestamp,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn batches(&self, session: &str, limit: u32) -> Result<Vec<BatchRecord>> {
+        self.client
+            .query(
+                "SELECT * FROM batches WHERE session = $1 ORDER BY slot DESC LIMIT $2",
+                &[&session, &i64::from(limit)],
+            )
+            .await?
+            .iter()
+            .map(batch_row)
+            .collect()
+    }
+
+    async fn put_execution(&self, execution: &ExecutionRecord) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO executions
+                    (session, execution_id, success, error_code, signature, slot, timestamp)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (session, execution_id) DO UPDATE SET
+                    success = EXCLUDED.success,
+                    error_code = EXCLUDED.error_code,
+                    signature = EXCLUDED.signature,
+                    slot = EXCLUDED.slot,
+                    timestamp = EXCLUDED.timestamp",
+                &[
+                    &execution.session,
+                    &(execution.execution_id as i64),
+                    &execution.success,
+                    &execution.error_code.map(i64::from),
+                    &execution.signature,
+                    &(execution.slot as i64),
+                    &execution.timestamp,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn executions(&self, session: &str, limit: u32) -> Result<Vec<ExecutionRecord>> {
+        self.client
+            .query(
+                "SELECT * FROM executions WHERE session = $1 ORDER BY slot DESC LIMIT $2",
+                &[&session, &i64::from(limit)],
+            )
+            .await?
+            .iter()
+            .map(execution_row)
+            .collect()
+    }
+
+    async fn put_authorization(&self, authorization: &AuthorizationRecord) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO authorizations
+                    (signature, session, authorization_account, caller, slot, timestamp)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (signature, authorization_account, caller) DO NOTHING",
+                &[
+                    &authorization.signature,
+                    &authorization.session,
+                    &authorization.authorization,
+                    &authorization.caller,
+                    &(authorization.slot as i64),
+                    &authorization.timestamp,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn authorizations(
+        &self,
+        session: &str,
+        limit: u32,
+    ) -> Result<Vec<AuthorizationRecord>> {
+        self.client
+            .query(
+                "SELECT * FROM authorizations WHERE session = $1 ORDER BY slot DESC LIMIT $2",
+                &[&session, &i64::from(limit)],
+            )
+            .await?
+            .iter()
+            .map(authorization_row)
+            .collect()
+    }
+
+    async fn put_transfer(&self, transfer: &TransferRecord) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO transfers
+                    (signature, instruction_index, inner_index, session, program_id,
+                     source, destination, mint, amount, slot)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (signature, instruction_index, (COALESCE(inner_index, -1)))
+                 DO NOTHING",
+                &[
+                    &transfer.signature,
+                    &(transfer.instruction_index as i32),
+                    &transfer.inner_index.map(|index| index as i32),
+                    &transfer.session,
+                    &transfer.program_id,
+                    &transfer.source,
+                    &transfer.destination,
+                    &transfer.mint,
+                    &(transfer.amount as i64),
+                    &(transfer.slot as i64),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn transfers(&self, session: &str, limit: u32) -> Result<Vec<TransferRecord>> {
+        self.client
+            .query(
+                "SELECT * FROM transfers WHERE session = $1 ORDER BY slot DESC LIMIT $2",
+                &[&session, &i64::from(limit)],
+            )
+            .await?
+            .iter()
+            .map(transfer_row)
+            .collect()
+    }
+
+    async fn put_invocation(&self, invocation: &InvocationRecord) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO function_invocations
+                    (signature, session, operation_index, registry_id, program_id, slot)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (signature, session, operation_index) DO NOTHING",
+                &[
+                    &invocation.signature,
+                    &invocation.session,
+                    &i32::from(invocation.operation_index),
+                    &(invocation.registry_id as i64),
+                    &invocation.program_id,
+                    &(invocation.slot as i64),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn functions(&self) -> Result<Vec<FunctionRecord>> {
+        // The latest invocation of each function carries its current program
+        self.client
+            .query(
+                "SELECT DISTINCT ON (registry_id)
+                    registry_id, program_id, session, slot,
+                    COUNT(*) OVER (PARTITION BY registry_id) AS invocations
+                 FROM function_invocations
+                 ORDER BY registry_id, slot DESC",
+                &[],
+            )
+            .await?
+            .iter()
+            .map(function_row)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_session_query() {
+        let (sql, params) = build_session_query(&SessionQuery::default());
+        assert!(!sql.contains("WHERE"));
+        assert!(sql.ends_with("LIMIT $1 OFFSET $2"));
+        assert_eq!(params.len(), 2);
+
+        let query = SessionQuery {
+            owner: Some("owner".to_string()),
+            shard: Some("shard".to_string()),
+            active: Some(true),
+            limit: Some(10),
+            offset: 20,
+        };
+        let (sql, params) = build_session_query(&query);
+        assert!(sql.contains("WHERE owner = $1 AND shard = $2 AND active = $3"));
+        assert!(sql.ends_with("LIMIT $4 OFFSET $5"));
+        assert_eq!(params.len(), 5);
+    }
+}
@@ -0,0 +1,113 @@
+//! Rows materialized by the indexer
+//!
+//! Addresses and signatures are stored base58-encoded so rows read the same
+//! in Postgres, JSON and GraphQL.
+
+use async_graphql::{InputObject, SimpleObject};
+use serde::{Deserialize, Serialize};
+
+/// Latest known state of a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct SessionRecord {
+    pub address: String,
+    pub owner: String,
+    pub shard: String,
+    pub parent_session: Option<String>,
+    /// Namespace path, known once the session account has been observed
+    pub namespace: Option<String>,
+    pub active: bool,
+    pub usage_count: u64,
+    pub created_at: i64,
+    /// Transaction that created the session, when its event was observed
+    pub created_signature: Option<String>,
+    /// Slot of the last update applied to this row
+    pub last_slot: u64,
+}
+
+/// A batch executed in a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct BatchRecord {
+    pub signature: String,
+    pub session: String,
+    pub caller: String,
+    pub operations: u8,
+    /// Session usage count after the batch
+    pub usage_count: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+/// Result of an asynchronous execution reported back to a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct ExecutionRecord {
+    pub session: String,
+    pub execution_id: u64,
+    pub success: bool,
+    pub error_code: Option<u32>,
+    pub signature: String,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+/// A guard or authorization consumed by a caller
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct AuthorizationRecord {
+    pub session: String,
+    pub authorization: String,
+    pub caller: String,
+    pub signature: String,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+/// A registered function invoked by one operation of a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct InvocationRecord {
+    pub signature: String,
+    pub session: String,
+    pub operation_index: u8,
+    pub registry_id: u64,
+    pub program_id: String,
+    pub slot: u64,
+}
+
+/// Lamports or tokens moved by an instruction a session caused
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct TransferRecord {
+    pub signature: String,
+    /// Top-level instruction the transfer ran under
+    pub instruction_index: u32,
+    /// Position among that instruction's inner instructions, if a CPI
+    pub inner_index: Option<u32>,
+    pub session: String,
+    /// System program for lamports, token program for tokens
+    pub program_id: String,
+    pub source: String,
+    pub destination: String,
+    pub mint: Option<String>,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/// A registered function and how often batches have invoked it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct FunctionRecord {
+    pub registry_id: u64,
+    pub program_id: String,
+    pub invocations: u64,
+    pub last_session: String,
+    pub last_slot: u64,
+}
+
+/// Filter for listing sessions, newest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, InputObject)]
+#[graphql(name = "SessionFilter")]
+pub struct SessionQuery {
+    pub owner: Option<String>,
+    pub shard: Option<String>,
+    pub active: Option<bool>,
+    pub limit: Option<u32>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub offset: u32,
+}
@@ -0,0 +1,233 @@
+//! Storage backends for indexed records
+
+use crate::records::{
+    AuthorizationRecord, BatchRecord, ExecutionRecord, FunctionRecord, InvocationRecord,
+    SessionQuery, SessionRecord, TransferRecord,
+};
+use crate::Result;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use tokio::sync::RwLock;
+
+/// Maximum rows returned by a list query without an explicit limit
+pub const DEFAULT_LIMIT: u32 = 100;
+
+/// Index storage trait
+///
+/// Writes are idempotent so events replayed after a restart or backfill do
+/// not duplicate rows.
+#[async_trait]
+pub trait IndexStore: Send + Sync {
+    async fn session(&self, address: &str) -> Result<Option<SessionRecord>>;
+    async fn put_session(&self, session: &SessionRecord) -> Result<()>;
+    async fn sessions(&self, query: &SessionQuery) -> Result<Vec<SessionRecord>>;
+
+    async fn put_batch(&self, batch: &BatchRecord) -> Result<()>;
+    /// Batches executed in a session, newest first
+    async fn batches(&self, session: &str, limit: u32) -> Result<Vec<BatchRecord>>;
+
+    async fn put_execution(&self, execution: &ExecutionRecord) -> Result<()>;
+    async fn executions(&self, session: &str, limit: u32) -> Result<Vec<ExecutionRecord>>;
+
+    async fn put_authorization(&self, authorization: &AuthorizationRecord) -> Result<()>;
+    async fn authorizations(&self, session: &str, limit: u32)
+        -> Result<Vec<AuthorizationRecord>>;
+
+    async fn put_transfer(&self, transfer: &TransferRecord) -> Result<()>;
+    /// Transfers caused by a session's instructions, newest first
+    async fn transfers(&self, session: &str, limit: u32) -> Result<Vec<TransferRecord>>;
+
+    async fn put_invocation(&self, invocation: &InvocationRecord) -> Result<()>;
+    /// Functions that batches have invoked, aggregated from their invocations
+    async fn functions(&self) -> Result<Vec<FunctionRecord>>;
+}
+
+/// (signature, instruction index, inner index) of a transfer
+type TransferKey = (String, u32, Option<u32>);
+
+/// In-memory index storage, for tests and short-lived indexers
+#[derive(Default)]
+pub struct MemoryIndexStore {
+    sessions: RwLock<BTreeMap<String, SessionRecord>>,
+    /// Keyed by (signature, session), as one transaction may batch several sessions
+    batches: RwLock<BTreeMap<(String, String), BatchRecord>>,
+    executions: RwLock<BTreeMap<(String, u64), ExecutionRecord>>,
+    /// Keyed by (signature, authorization, caller)
+    authorizations: RwLock<BTreeMap<(String, String, String), AuthorizationRecord>>,
+    /// Keyed by (signature, session, operation index)
+    invocations: RwLock<BTreeMap<(String, String, u8), InvocationRecord>>,
+    transfers: RwLock<BTreeMap<TransferKey, TransferRecord>>,
+}
+
+impl MemoryIndexStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Newest `limit` rows by slot
+fn newest<T: Clone>(rows: impl Iterator<Item = T>, slot: impl Fn(&T) -> u64, limit: u32) -> Vec<T> {
+    let mut rows: Vec<T> = rows.collect();
+    rows.sort_by_key(|row| std::cmp::Reverse(slot(row)));
+    rows.truncate(limit as usize);
+    rows
+}
+
+#[async_trait]
+impl IndexStore for MemoryIndexStore {
+    async fn session(&self, address: &str) -> Result<Option<SessionRecord>> {
+        Ok(self.sessions.read().await.get(address).cloned())
+    }
+
+    async fn put_session(&self, session: &SessionRecord) -> Result<()> {
+        self.sessions
+            .write()
+            .await
+            .insert(session.address.clone(), session.clone());
+        Ok(())
+    }
+
+    async fn sessions(&self, query: &SessionQuery) -> Result<Vec<SessionRecord>> {
+        let sessions = self.sessions.read().await;
+        let mut matching: Vec<SessionRecord> = sessions
+            .values()
+            .filter(|session| query.owner.as_ref().is_none_or(|owner| session.owner == *owner))
+            .filter(|session| query.shard.as_ref().is_none_or(|shard| session.shard == *shard))
+            .filter(|session| query.active.is_none_or(|active| session.active == active))
+            .cloned()
+            .collect();
+        matching.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        Ok(matching
+            .into_iter()
+            .skip(query.offset as usize)
+            .take(query.limit.unwrap_or(DEFAULT_LIMIT) as usize)
+            .collect())
+    }
+
+    async fn put_batch(&self, batch: &BatchRecord) -> Result<()> {
+        self.batches
+            .write()
+            .await
+            .insert((batch.signature.clone(), batch.session.clone()), batch.clone());
+        Ok(())
+    }
+
+    async fn batches(&self, session: &str, limit: u32) -> Result<Vec<BatchRecord>> {
+        let batches = self.batches.read().await;
+        Ok(newest(
+            batches.values().filter(|batch| batch.session == session).cloned(),
+            |batch| batch.slot,
+            limit,
+        ))
+    }
+
+    async fn put_execution(&self, execution: &ExecutionRecord) -> Result<()> {
+        self.executions.write().await.insert(
+            (execution.session.clone(), execution.execution_id),
+            execution.clone(),
+        );
+        Ok(())
+    }
+
+    async fn executions(&self, session: &str, limit: u32) -> Result<Vec<ExecutionRecord>> {
+        let executions = self.executions.read().await;
+        Ok(newest(
+            executions
+                .values()
+                .filter(|execution| execution.session == session)
+                .cloned(),
+            |execution| execution.slot,
+            limit,
+        ))
+    }
+
+    async fn put_authorization(&self, authorization: &AuthorizationRecord) -> Result<()> {
+        self.authorizations.write().await.insert(
+            (
+                authorization.signature.clone(),
+                authorization.authorization.clone(),
+                authorization.caller.clone(),
+            ),
+            authorization.clone(),
+        );
+        Ok(())
+    }
+
+    async fn authorizations(
+        &self,
+        session: &str,
+        limit: u32,
+    ) -> Result<Vec<AuthorizationRecord>> {
+        let authorizations = self.authorizations.read().await;
+        Ok(newest(
+            authorizations
+                .values()
+                .filter(|authorization| authorization.session == session)
+                .cloned(),
+            |authorization| authorization.slot,
+            limit,
+        ))
+    }
+
+    async fn put_transfer(&self, transfer: &TransferRecord) -> Result<()> {
+        self.transfers.write().await.insert(
+            (
+                transfer.signature.clone(),
+                transfer.instruction_index,
+                transfer.inner_index,
+            ),
+            transfer.clone(),
+        );
+        Ok(())
+    }
+
+    async fn transfers(&self, session: &str, limit: u32) -> Result<Vec<TransferRecord>> {
+        let transfers = self.transfers.read().await;
+        Ok(newest(
+            transfers
+                .values()
+                .filter(|transfer| transfer.session == session)
+                .cloned(),
+            |transfer| transfer.slot,
+            limit,
+        ))
+    }
+
+    async fn put_invocation(&self, invocation: &InvocationRecord) -> Result<()> {
+        self.invocations.write().await.insert(
+            (
+                invocation.signature.clone(),
+                invocation.session.clone(),
+                invocation.operation_index,
+            ),
+            invocation.clone(),
+        );
+        Ok(())
+    }
+
+    async fn functions(&self) -> Result<Vec<FunctionRecord>> {
+        let mut functions: BTreeMap<u64, FunctionRecord> = BTreeMap::new();
+        for invocation in self.invocations.read().await.values() {
+            let function = functions
+                .entry(invocation.registry_id)
+                .or_insert_with(|| FunctionRecord {
+                    registry_id: invocation.registry_id,
+                    program_id: invocation.program_id.clone(),
+                    invocations: 0,
+                    last_session: invocation.session.clone(),
+                    last_slot: invocation.slot,
+                });
+            function.invocations += 1;
+            if invocation.slot > function.last_slot {
+                function.program_id = invocation.program_id.clone();
+                function.last_session = invocation.session.clone();
+                function.last_slot = invocation.slot;
+            }
+        }
+        Ok(functions.into_values().collect())
+    }
+}
@@ -37,6 +37,18 @@ pub enum RegistryError {
     
     #[error("Invalid function metadata")]
     InvalidMetadata,
+    
+    #[error("Invalid function package: {0}")]
+    InvalidPackage(String),
+    
+    #[error("Function execution failed: {0}")]
+    ExecutionFailed(String),
+    
+    #[error("Invalid CID: {0}")]
+    InvalidCid(String),
+    
+    #[error("Metadata fetch failed: {0}")]
+    MetadataFetch(String),
 }
 
 impl From<serde_json::Error> for RegistryError {
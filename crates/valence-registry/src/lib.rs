@@ -20,6 +20,16 @@ pub mod shards;
 /// Simplified IDL generation for shard integration
 pub mod idl;
 
+/// IPFS-hosted protocol metadata resolution
+pub mod metadata;
+
+/// WASM function packages for off-chain testing
+pub mod package;
+
+/// Local wasmtime sandbox for function packages
+#[cfg(feature = "sandbox")]
+pub mod sandbox;
+
 // ================================
 // Public API Re-exports
 // ================================
@@ -45,6 +55,15 @@ pub use idl::{
     is_compatible_version,
 };
 
+// Re-export metadata resolution components
+pub use metadata::{DocumentationLink, MetadataResolver, ProtocolManifest};
+
+// Re-export function packaging components
+pub use package::{FunctionAbi, FunctionPackage};
+
+#[cfg(feature = "sandbox")]
+pub use sandbox::{FunctionSandbox, MockState, SandboxExecution};
+
 // ================================
 // Registry Constants
 // ================================
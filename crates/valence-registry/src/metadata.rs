@@ -0,0 +1,432 @@
+// IPFS-hosted protocol metadata resolution
+//
+// Protocols publish a JSON manifest describing themselves, their audits and
+// their documentation on IPFS and reference it by CID. The resolver fetches
+// the content-addressed block from a trustless gateway, checks it against
+// the CID's sha2-256 digest before trusting anything in it, and caches the
+// decoded manifest; a CID's content never changes, so entries never expire.
+
+use crate::error::{RegistryError, Result};
+use crate::shards::{AuditInfo, ShardMetadata};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+// ================================
+// Protocol Manifest
+// ================================
+
+/// Public gateway used when none is configured
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
+
+/// Manifests kept in the resolver cache
+const MANIFEST_CACHE_SIZE: usize = 128;
+
+/// Protocol description published on IPFS
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProtocolManifest {
+    /// Protocol name
+    pub name: String,
+    /// Protocol version
+    pub version: String,
+    /// Protocol description
+    #[serde(default)]
+    pub description: String,
+    /// Official website
+    #[serde(default)]
+    pub website: Option<String>,
+    /// Source code repository
+    #[serde(default)]
+    pub repository: Option<String>,
+    /// Documentation links
+    #[serde(default)]
+    pub documentation: Vec<DocumentationLink>,
+    /// Security audits
+    #[serde(default)]
+    pub audits: Vec<AuditInfo>,
+}
+
+/// Titled link to protocol documentation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocumentationLink {
+    pub title: String,
+    pub url: String,
+}
+
+impl ProtocolManifest {
+    /// Check required fields and that every link is an http(s) or ipfs URL
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.version.trim().is_empty() {
+            return Err(RegistryError::InvalidMetadata);
+        }
+
+        let links = self
+            .website
+            .iter()
+            .chain(&self.repository)
+            .chain(self.documentation.iter().map(|link| &link.url))
+            .chain(self.audits.iter().map(|audit| &audit.report_url));
+        for link in links {
+            let valid = ["https://", "http://", "ipfs://"]
+                .iter()
+                .any(|scheme| link.len() > scheme.len() && link.starts_with(scheme));
+            if !valid {
+                return Err(RegistryError::InvalidMetadata);
+            }
+        }
+        Ok(())
+    }
+
+    /// Shard metadata described by the manifest
+    pub fn shard_metadata(&self) -> ShardMetadata {
+        ShardMetadata {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            description: self.description.clone(),
+            website: self.website.clone(),
+            repository: self.repository.clone(),
+            audits: self.audits.clone(),
+        }
+    }
+}
+
+// ================================
+// Content Identifiers
+// ================================
+
+/// Multicodec of raw bytes
+const CODEC_RAW: u64 = 0x55;
+
+/// Multicodec of a UnixFS dag-pb node, implied by CIDv0
+const CODEC_DAG_PB: u64 = 0x70;
+
+/// Multihash code of sha2-256
+const MULTIHASH_SHA2_256: u64 = 0x12;
+
+/// Parsed CID of a sha2-256 addressed block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ContentId {
+    codec: u64,
+    digest: [u8; 32],
+}
+
+impl ContentId {
+    /// Parse a CIDv0 (`Qm...`) or base32 CIDv1 (`b...`)
+    fn parse(cid: &str) -> Result<Self> {
+        let invalid = |reason: &str| RegistryError::InvalidCid(format!("{}: {}", cid, reason));
+
+        if cid.len() == 46 && cid.starts_with("Qm") {
+            let bytes = bs58::decode(cid).into_vec().map_err(|e| invalid(&e.to_string()))?;
+            let digest = Self::multihash(&mut bytes.as_slice()).map_err(invalid)?;
+            return Ok(Self {
+                codec: CODEC_DAG_PB,
+                digest,
+            });
+        }
+
+        let encoded = cid
+            .strip_prefix('b')
+            .ok_or_else(|| invalid("only CIDv0 and base32 CIDv1 are supported"))?;
+        let bytes = data_encoding::BASE32_NOPAD
+            .decode(encoded.to_ascii_uppercase().as_bytes())
+            .map_err(|e| invalid(&e.to_string()))?;
+        let mut rest = bytes.as_slice();
+        if varint(&mut rest) != Some(1) {
+            return Err(invalid("unsupported CID version"));
+        }
+        let codec = varint(&mut rest).ok_or_else(|| invalid("truncated codec"))?;
+        if codec != CODEC_RAW && codec != CODEC_DAG_PB {
+            return Err(invalid("unsupported codec"));
+        }
+        let digest = Self::multihash(&mut rest).map_err(invalid)?;
+        Ok(Self { codec, digest })
+    }
+
+    /// Read a sha2-256 multihash covering the rest of the input
+    fn multihash(bytes: &mut &[u8]) -> std::result::Result<[u8; 32], &'static str> {
+        if varint(bytes) != Some(MULTIHASH_SHA2_256) {
+            return Err("only sha2-256 multihashes are supported");
+        }
+        if varint(bytes) != Some(32) {
+            return Err("invalid digest length");
+        }
+        (*bytes).try_into().map_err(|_| "invalid digest length")
+    }
+
+    /// File content of a block verified against this CID
+    fn content(&self, block: &[u8]) -> Result<Vec<u8>> {
+        let digest: [u8; 32] = Sha256::digest(block).into();
+        if digest != self.digest {
+            return Err(RegistryError::InvalidContentHash);
+        }
+
+        match self.codec {
+            CODEC_RAW => Ok(block.to_vec()),
+            _ => unixfs_file(block),
+        }
+    }
+}
+
+/// Read an unsigned LEB128 varint
+fn varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Fields of a protobuf message, as (field number, value) with varints widened
+fn protobuf_fields(mut bytes: &[u8]) -> Result<Vec<(u64, ProtobufValue<'_>)>> {
+    let malformed = || RegistryError::SerializationError("malformed dag-pb block".to_string());
+
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = varint(&mut bytes).ok_or_else(malformed)?;
+        let value = match key & 7 {
+            0 => ProtobufValue::Varint(varint(&mut bytes).ok_or_else(malformed)?),
+            2 => {
+                let len = varint(&mut bytes).ok_or_else(malformed)? as usize;
+                if len > bytes.len() {
+                    return Err(malformed());
+                }
+                let (value, rest) = bytes.split_at(len);
+                bytes = rest;
+                ProtobufValue::Bytes(value)
+            }
+            _ => return Err(malformed()),
+        };
+        fields.push((key >> 3, value));
+    }
+    Ok(fields)
+}
+
+enum ProtobufValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Content of a single-block UnixFS file node
+fn unixfs_file(block: &[u8]) -> Result<Vec<u8>> {
+    let unsupported = |reason: &str| RegistryError::SerializationError(reason.to_string());
+
+    // PBNode: Links = 2, Data = 1
+    let mut data = None;
+    for (field, value) in protobuf_fields(block)? {
+        match (field, value) {
+            (1, ProtobufValue::Bytes(bytes)) => data = Some(bytes),
+            (2, _) => return Err(unsupported("multi-block files are not supported")),
+            _ => {}
+        }
+    }
+
+    // UnixFS Data: Type = 1 (2 for files), Data = 2
+    let mut content = Vec::new();
+    for (field, value) in protobuf_fields(data.unwrap_or_default())? {
+        match (field, value) {
+            (1, ProtobufValue::Varint(kind)) if kind != 2 => {
+                return Err(unsupported("CID does not reference a file"))
+            }
+            (2, ProtobufValue::Bytes(bytes)) => content = bytes.to_vec(),
+            _ => {}
+        }
+    }
+    Ok(content)
+}
+
+// ================================
+// Resolver
+// ================================
+
+/// Fetches, verifies and caches protocol manifests by CID
+pub struct MetadataResolver {
+    gateway: String,
+    client: reqwest::Client,
+    cache: Mutex<LruCache<String, ProtocolManifest>>,
+}
+
+impl Default for MetadataResolver {
+    fn default() -> Self {
+        Self::new(DEFAULT_IPFS_GATEWAY)
+    }
+}
+
+impl MetadataResolver {
+    /// Create a resolver for a trustless IPFS gateway
+    pub fn new(gateway: &str) -> Self {
+        Self::with_client(gateway, reqwest::Client::new())
+    }
+
+    /// Create a resolver using an existing HTTP client
+    pub fn with_client(gateway: &str, client: reqwest::Client) -> Self {
+        Self {
+            gateway: gateway.trim_end_matches('/').to_string(),
+            client,
+            cache: Mutex::new(LruCache::new(NonZeroUsize::new(MANIFEST_CACHE_SIZE).unwrap())),
+        }
+    }
+
+    /// Resolve a `Qm...`, `b...` or `ipfs://` reference to a validated manifest
+    pub async fn resolve(&self, cid: &str) -> Result<ProtocolManifest> {
+        let cid = cid.strip_prefix("ipfs://").unwrap_or(cid);
+        if let Some(manifest) = self.cache.lock().unwrap().get(cid) {
+            return Ok(manifest.clone());
+        }
+
+        let content_id = ContentId::parse(cid)?;
+        let block = self.fetch_block(cid).await?;
+        let manifest: ProtocolManifest = serde_json::from_slice(&content_id.content(&block)?)?;
+        manifest.validate()?;
+
+        self.cache
+            .lock()
+            .unwrap()
+            .put(cid.to_string(), manifest.clone());
+        Ok(manifest)
+    }
+
+    /// Resolve a manifest into shard metadata
+    pub async fn resolve_shard_metadata(&self, cid: &str) -> Result<ShardMetadata> {
+        Ok(self.resolve(cid).await?.shard_metadata())
+    }
+
+    /// Fetch the raw block so its hash can be checked without trusting the gateway
+    async fn fetch_block(&self, cid: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/ipfs/{}?format=raw", self.gateway, cid);
+        let response = self
+            .client
+            .get(&url)
+            .header(reqwest::header::ACCEPT, "application/vnd.ipld.raw")
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| RegistryError::MetadataFetch(e.to_string()))?;
+        let block = response
+            .bytes()
+            .await
+            .map_err(|e| RegistryError::MetadataFetch(e.to_string()))?;
+        Ok(block.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn manifest_json() -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "name": "lending",
+            "version": "1.2.0",
+            "description": "Isolated lending markets",
+            "repository": "https://github.com/example/lending",
+            "documentation": [{ "title": "Integration guide", "url": "ipfs://QmGuide" }],
+            "audits": [{
+                "auditor": "Example Security",
+                "report_url": "https://example.com/audit.pdf",
+                "audit_date": 1700000000,
+                "score": null
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn raw_cid(block: &[u8]) -> String {
+        let mut bytes = vec![0x01, 0x55, 0x12, 0x20];
+        bytes.extend_from_slice(&Sha256::digest(block));
+        format!("b{}", data_encoding::BASE32_NOPAD.encode(&bytes).to_lowercase())
+    }
+
+    /// dag-pb node wrapping `content` as a single-block UnixFS file
+    fn dag_pb_block(content: &[u8]) -> Vec<u8> {
+        let mut unixfs = vec![0x08, 0x02, 0x12];
+        unixfs.push(content.len() as u8);
+        unixfs.extend_from_slice(content);
+        let mut node = vec![0x0a, unixfs.len() as u8];
+        node.extend_from_slice(&unixfs);
+        node
+    }
+
+    #[test]
+    fn test_content_verification() {
+        let content = br#"{"name":"a"}"#;
+
+        let block = content.to_vec();
+        let cid = ContentId::parse(&raw_cid(&block)).unwrap();
+        assert_eq!(cid.codec, CODEC_RAW);
+        assert_eq!(cid.content(&block).unwrap(), content);
+        assert!(matches!(cid.content(b"tampered"), Err(RegistryError::InvalidContentHash)));
+
+        let block = dag_pb_block(content);
+        let mut multihash = vec![0x12, 0x20];
+        multihash.extend_from_slice(&Sha256::digest(&block));
+        let cid = ContentId::parse(&bs58::encode(multihash).into_string()).unwrap();
+        assert_eq!(cid.codec, CODEC_DAG_PB);
+        assert_eq!(cid.content(&block).unwrap(), content);
+
+        assert!(matches!(ContentId::parse("zNotSupported"), Err(RegistryError::InvalidCid(_))));
+    }
+
+    #[test]
+    fn test_manifest_validation() {
+        let manifest: ProtocolManifest = serde_json::from_slice(&manifest_json()).unwrap();
+        assert!(manifest.validate().is_ok());
+        assert_eq!(manifest.shard_metadata().audits, manifest.audits);
+
+        let mut invalid = manifest.clone();
+        invalid.website = Some("javascript:alert(1)".to_string());
+        assert!(matches!(invalid.validate(), Err(RegistryError::InvalidMetadata)));
+
+        let mut invalid = manifest;
+        invalid.name = String::new();
+        assert!(invalid.validate().is_err());
+    }
+
+    /// Serve `body` to a single request, returning the gateway URL
+    async fn serve_once(body: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await.unwrap();
+            let header = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/vnd.ipld.raw\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).await.unwrap();
+            stream.write_all(&body).await.unwrap();
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_and_cache() {
+        let block = manifest_json();
+        let cid = raw_cid(&block);
+        let resolver = MetadataResolver::new(&serve_once(block).await);
+
+        let manifest = resolver.resolve(&format!("ipfs://{}", cid)).await.unwrap();
+        assert_eq!(manifest.name, "lending");
+        assert_eq!(manifest.documentation[0].title, "Integration guide");
+
+        // The gateway served one request; this comes from the cache
+        let shard = resolver.resolve_shard_metadata(&cid).await.unwrap();
+        assert_eq!(shard.audits[0].auditor, "Example Security");
+
+        // Content that does not match the CID is rejected
+        let resolver = MetadataResolver::new(&serve_once(b"{}".to_vec()).await);
+        assert!(matches!(
+            resolver.resolve(&cid).await,
+            Err(RegistryError::InvalidContentHash)
+        ));
+    }
+}
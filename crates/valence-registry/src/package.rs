@@ -0,0 +1,240 @@
+// Function packages for off-chain development and testing
+//
+// A package bundles a function's registry metadata with a WASM build of its
+// logic and the ABI the module exposes. Developers execute packages in the
+// local sandbox against mock state before deploying the Solana program that
+// implements the same function.
+
+use crate::error::{RegistryError, Result};
+use crate::functions::{FunctionEntry, FunctionInfo};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ================================
+// Package Format
+// ================================
+
+/// Magic prefix of a serialized package
+pub const PACKAGE_MAGIC: &[u8; 6] = b"VALFN\0";
+
+/// Current package format version
+pub const PACKAGE_FORMAT_VERSION: u8 = 1;
+
+/// Magic prefix of a binary WASM module
+const WASM_MAGIC: &[u8; 4] = b"\0asm";
+
+/// Entrypoint called when the ABI does not name one
+pub const DEFAULT_ENTRYPOINT: &str = "execute";
+
+/// Interface a packaged module exposes to the sandbox
+///
+/// The module exports `memory`, `alloc(len: i32) -> i32` and the entrypoint
+/// `(input_ptr: i32, input_len: i32) -> i64`, which returns the output
+/// location as `ptr << 32 | len` or a negative error code.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionAbi {
+    /// Exported function the sandbox calls
+    pub entrypoint: String,
+    /// JSON Schema describing the input bytes
+    pub input_schema: serde_json::Value,
+    /// JSON Schema describing the output bytes
+    pub output_schema: serde_json::Value,
+}
+
+impl Default for FunctionAbi {
+    fn default() -> Self {
+        Self {
+            entrypoint: DEFAULT_ENTRYPOINT.to_string(),
+            input_schema: serde_json::Value::Null,
+            output_schema: serde_json::Value::Null,
+        }
+    }
+}
+
+/// Package metadata, stored ahead of the module bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PackageManifest {
+    info: FunctionInfo,
+    description: String,
+    tags: Vec<String>,
+    abi: FunctionAbi,
+    /// Hex blake3 hash of the module
+    wasm_hash: String,
+}
+
+/// Function metadata, WASM module and ABI schema
+#[derive(Debug, Clone)]
+pub struct FunctionPackage {
+    /// Registry metadata of the function
+    pub info: FunctionInfo,
+    /// Function description
+    pub description: String,
+    /// Tags for categorization
+    pub tags: Vec<String>,
+    /// Interface the module exposes
+    pub abi: FunctionAbi,
+    /// Binary WASM module
+    pub wasm: Vec<u8>,
+}
+
+impl FunctionPackage {
+    /// Create a package for a binary WASM module
+    pub fn new(info: FunctionInfo, abi: FunctionAbi, wasm: Vec<u8>) -> Result<Self> {
+        if !wasm.starts_with(WASM_MAGIC) {
+            return Err(RegistryError::InvalidPackage(
+                "module is not binary WASM".to_string(),
+            ));
+        }
+        if abi.entrypoint.is_empty() {
+            return Err(RegistryError::InvalidPackage(
+                "ABI has no entrypoint".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            info,
+            description: String::new(),
+            tags: Vec::new(),
+            abi,
+            wasm,
+        })
+    }
+
+    /// Set the description and tags carried into the registry entry
+    pub fn with_description(mut self, description: String, tags: Vec<String>) -> Self {
+        self.description = description;
+        self.tags = tags;
+        self
+    }
+
+    /// Hash of the module for integrity verification
+    pub fn wasm_hash(&self) -> [u8; 32] {
+        blake3::hash(&self.wasm).into()
+    }
+
+    /// Registry entry for the packaged function
+    pub fn entry(&self) -> FunctionEntry {
+        FunctionEntry::new(self.info.clone(), self.description.clone(), self.tags.clone())
+    }
+
+    /// Serialize as magic, format version, manifest length, JSON manifest and module
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let manifest = serde_json::to_vec(&PackageManifest {
+            info: self.info.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            abi: self.abi.clone(),
+            wasm_hash: hex::encode(self.wasm_hash()),
+        })?;
+        let manifest_len = u32::try_from(manifest.len())
+            .map_err(|_| RegistryError::InvalidPackage("manifest too large".to_string()))?;
+
+        let mut bytes = Vec::with_capacity(PACKAGE_MAGIC.len() + 5 + manifest.len() + self.wasm.len());
+        bytes.extend_from_slice(PACKAGE_MAGIC);
+        bytes.push(PACKAGE_FORMAT_VERSION);
+        bytes.extend_from_slice(&manifest_len.to_le_bytes());
+        bytes.extend_from_slice(&manifest);
+        bytes.extend_from_slice(&self.wasm);
+        Ok(bytes)
+    }
+
+    /// Deserialize a package, verifying the module against its recorded hash
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let invalid = |reason: &str| RegistryError::InvalidPackage(reason.to_string());
+
+        let rest = bytes
+            .strip_prefix(PACKAGE_MAGIC.as_slice())
+            .ok_or_else(|| invalid("missing package magic"))?;
+        let (&version, rest) = rest.split_first().ok_or_else(|| invalid("truncated header"))?;
+        if version != PACKAGE_FORMAT_VERSION {
+            return Err(RegistryError::InvalidPackage(format!(
+                "unsupported format version {}",
+                version
+            )));
+        }
+        if rest.len() < 4 {
+            return Err(invalid("truncated header"));
+        }
+        let (len, rest) = rest.split_at(4);
+        let manifest_len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        if rest.len() < manifest_len {
+            return Err(invalid("truncated manifest"));
+        }
+        let (manifest, wasm) = rest.split_at(manifest_len);
+        let manifest: PackageManifest = serde_json::from_slice(manifest)?;
+
+        let package = Self::new(manifest.info, manifest.abi, wasm.to_vec())?
+            .with_description(manifest.description, manifest.tags);
+        if hex::encode(package.wasm_hash()) != manifest.wasm_hash {
+            return Err(RegistryError::InvalidContentHash);
+        }
+        Ok(package)
+    }
+
+    /// Write the package to a file
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_bytes()?)
+            .map_err(|e| RegistryError::StorageError(e.to_string()))
+    }
+
+    /// Read a package from a file
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes =
+            std::fs::read(path).map_err(|e| RegistryError::StorageError(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::FunctionRegistry;
+    use anchor_lang::prelude::Pubkey;
+
+    fn package() -> FunctionPackage {
+        let info = FunctionInfo::new(2001, Pubkey::new_unique(), "counter".to_string(), 1, 10_000);
+        let abi = FunctionAbi {
+            input_schema: serde_json::json!({ "type": "integer" }),
+            ..FunctionAbi::default()
+        };
+        // Smallest valid module: magic and version
+        let wasm = b"\0asm\x01\0\0\0".to_vec();
+        FunctionPackage::new(info, abi, wasm)
+            .unwrap()
+            .with_description("Counter".to_string(), vec!["test".to_string()])
+    }
+
+    #[test]
+    fn test_package_roundtrip() {
+        let package = package();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counter.vfn");
+        package.write_to(&path).unwrap();
+
+        let read = FunctionPackage::read_from(&path).unwrap();
+        assert_eq!(read.info, package.info);
+        assert_eq!(read.abi, package.abi);
+        assert_eq!(read.wasm, package.wasm);
+        assert_eq!(read.tags, vec!["test".to_string()]);
+
+        let mut registry = FunctionRegistry::new();
+        assert_eq!(registry.register_function(read.entry()).unwrap(), 2001);
+    }
+
+    #[test]
+    fn test_package_rejects_tampering() {
+        let mut bytes = package().to_bytes().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(matches!(
+            FunctionPackage::from_bytes(&bytes),
+            Err(RegistryError::InvalidContentHash)
+        ));
+
+        assert!(matches!(
+            FunctionPackage::from_bytes(b"not a package"),
+            Err(RegistryError::InvalidPackage(_))
+        ));
+        assert!(FunctionPackage::new(package().info, FunctionAbi::default(), b"(module)".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,411 @@
+// Local execution sandbox for packaged functions
+//
+// Runs a package's WASM module with wasmtime against in-memory mock state so
+// function logic can be exercised off-chain before the Solana program that
+// implements it is deployed. Execution is metered with fuel derived from the
+// function's compute unit estimate, and state changes only apply when the
+// entrypoint succeeds, mirroring a reverted transaction on failure. Linear
+// memory and tables are capped so a module cannot exhaust host memory, and
+// growing past a cap traps like running out of fuel.
+//
+// Modules import their host functions from the `valence` module:
+// - `state_read(key_ptr, key_len, out_ptr, out_cap) -> i32` copies up to
+//   `out_cap` bytes of the value and returns its full length, or -1 if absent
+// - `state_write(key_ptr, key_len, value_ptr, value_len)`
+// - `state_remove(key_ptr, key_len)`
+// - `log(ptr, len)` records a UTF-8 message
+
+use crate::error::{RegistryError, Result};
+use crate::package::FunctionPackage;
+use std::collections::BTreeMap;
+use wasmtime::{
+    Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+};
+
+// ================================
+// Mock State
+// ================================
+
+/// Key-value state a sandboxed function reads and writes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockState {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MockState {
+    /// Create empty state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the value stored under a key
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    /// Store a value under a key
+    pub fn insert(&mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) {
+        self.entries.insert(key.into(), value.into());
+    }
+
+    /// Remove a key, returning its value
+    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.remove(key)
+    }
+
+    /// Iterate over entries in key order
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+}
+
+// ================================
+// Sandbox
+// ================================
+
+/// Default cap on a module's linear memory, in bytes
+pub const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Default cap on the elements of each module table
+pub const DEFAULT_TABLE_ELEMENTS: usize = 10_000;
+
+/// Result of a successful sandboxed execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxExecution {
+    /// Bytes the entrypoint returned
+    pub output: Vec<u8>,
+    /// Fuel consumed, comparable to compute units
+    pub fuel_consumed: u64,
+    /// Messages logged through the `log` host function
+    pub logs: Vec<String>,
+}
+
+/// Store data visible to host functions
+struct HostState {
+    state: MockState,
+    logs: Vec<String>,
+    limits: StoreLimits,
+}
+
+/// Compiled package ready for repeated local execution
+pub struct FunctionSandbox {
+    engine: Engine,
+    module: Module,
+    linker: Linker<HostState>,
+    entrypoint: String,
+    fuel: u64,
+    memory_limit: usize,
+    table_elements: usize,
+}
+
+impl FunctionSandbox {
+    /// Compile a package, metering it with its compute unit estimate
+    pub fn new(package: &FunctionPackage) -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(execution_error)?;
+        let module = Module::new(&engine, &package.wasm)
+            .map_err(|e| RegistryError::InvalidPackage(e.to_string()))?;
+
+        let mut linker = Linker::new(&engine);
+        Self::link_host(&mut linker).map_err(execution_error)?;
+
+        Ok(Self {
+            engine,
+            module,
+            linker,
+            entrypoint: package.abi.entrypoint.clone(),
+            fuel: package.info.compute_units,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            table_elements: DEFAULT_TABLE_ELEMENTS,
+        })
+    }
+
+    /// Override the fuel available to each execution
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Override the cap on linear memory, in bytes
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Override the cap on the elements of each table
+    pub fn with_table_elements(mut self, elements: usize) -> Self {
+        self.table_elements = elements;
+        self
+    }
+
+    /// Execute the entrypoint, applying its state changes only on success
+    pub fn execute(&self, input: &[u8], state: &mut MockState) -> Result<SandboxExecution> {
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                state: state.clone(),
+                logs: Vec::new(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(self.memory_limit)
+                    .table_elements(self.table_elements)
+                    .instances(1)
+                    .memories(1)
+                    .tables(1)
+                    .trap_on_grow_failure(true)
+                    .build(),
+            },
+        );
+        store.limiter(|host| &mut host.limits);
+        store.set_fuel(self.fuel).map_err(execution_error)?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .map_err(execution_error)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| RegistryError::InvalidPackage("module exports no memory".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| RegistryError::InvalidPackage(e.to_string()))?;
+        let entrypoint = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, &self.entrypoint)
+            .map_err(|e| RegistryError::InvalidPackage(e.to_string()))?;
+
+        let input_len = i32::try_from(input.len())
+            .map_err(|_| RegistryError::ExecutionFailed("input too large".to_string()))?;
+        let input_ptr = alloc.call(&mut store, input_len).map_err(execution_error)?;
+        memory
+            .write(&mut store, input_ptr as u32 as usize, input)
+            .map_err(|e| RegistryError::ExecutionFailed(e.to_string()))?;
+
+        let result = entrypoint
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(execution_error)?;
+        if result < 0 {
+            return Err(RegistryError::ExecutionFailed(format!(
+                "entrypoint returned error code {}",
+                result
+            )));
+        }
+
+        let output_ptr = (result >> 32) as usize;
+        let output_len = (result & 0xffff_ffff) as usize;
+        let output = memory
+            .data(&store)
+            .get(output_ptr..output_ptr + output_len)
+            .ok_or_else(|| RegistryError::ExecutionFailed("output out of bounds".to_string()))?
+            .to_vec();
+
+        let fuel_consumed = self.fuel - store.get_fuel().map_err(execution_error)?;
+        let host = store.into_data();
+        *state = host.state;
+        Ok(SandboxExecution {
+            output,
+            fuel_consumed,
+            logs: host.logs,
+        })
+    }
+
+    /// Register the host functions modules import from `valence`
+    fn link_host(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+        linker.func_wrap(
+            "valence",
+            "state_read",
+            |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, out_ptr: i32, out_cap: i32| {
+                let memory = exported_memory(&mut caller)?;
+                let key = read_bytes(&memory, &caller, key_ptr, key_len)?;
+                let Some(value) = caller.data().state.get(&key).map(<[u8]>::to_vec) else {
+                    return Ok(-1);
+                };
+                let copied = value.len().min(out_cap.max(0) as usize);
+                memory.write(&mut caller, out_ptr as u32 as usize, &value[..copied])?;
+                Ok(value.len() as i32)
+            },
+        )?;
+        linker.func_wrap(
+            "valence",
+            "state_write",
+            |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32| {
+                let memory = exported_memory(&mut caller)?;
+                let key = read_bytes(&memory, &caller, key_ptr, key_len)?;
+                let value = read_bytes(&memory, &caller, value_ptr, value_len)?;
+                caller.data_mut().state.insert(key, value);
+                Ok(())
+            },
+        )?;
+        linker.func_wrap(
+            "valence",
+            "state_remove",
+            |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| {
+                let memory = exported_memory(&mut caller)?;
+                let key = read_bytes(&memory, &caller, key_ptr, key_len)?;
+                caller.data_mut().state.remove(&key);
+                Ok(())
+            },
+        )?;
+        linker.func_wrap(
+            "valence",
+            "log",
+            |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let memory = exported_memory(&mut caller)?;
+                let message = read_bytes(&memory, &caller, ptr, len)?;
+                caller
+                    .data_mut()
+                    .logs
+                    .push(String::from_utf8_lossy(&message).into_owned());
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// Memory exported by the calling module
+fn exported_memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("module exports no memory"))
+}
+
+/// Copy a range of guest memory
+fn read_bytes(
+    memory: &Memory,
+    caller: &Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<Vec<u8>> {
+    let start = ptr as u32 as usize;
+    let end = start + len.max(0) as usize;
+    memory
+        .data(caller)
+        .get(start..end)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| wasmtime::Error::msg("memory access out of bounds"))
+}
+
+fn execution_error(error: wasmtime::Error) -> RegistryError {
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => RegistryError::ExecutionFailed("out of fuel".to_string()),
+        _ => RegistryError::ExecutionFailed(format!("{:#}", error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::FunctionInfo;
+    use crate::package::FunctionAbi;
+    use anchor_lang::prelude::Pubkey;
+
+    /// Adds its u64 input to the `count` entry and returns the new count
+    const COUNTER: &str = r#"
+        (module
+          (import "valence" "state_read" (func $read (param i32 i32 i32 i32) (result i32)))
+          (import "valence" "state_write" (func $write (param i32 i32 i32 i32)))
+          (import "valence" "log" (func $log (param i32 i32)))
+          (memory (export "memory") 1)
+          (global $heap (mut i32) (i32.const 1024))
+          (data (i32.const 0) "count")
+          (data (i32.const 16) "incremented")
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $heap))
+            (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+            (local.get $ptr))
+          (func (export "execute") (param $ptr i32) (param $len i32) (result i64)
+            (if (i32.ne (local.get $len) (i32.const 8))
+              (then (return (i64.const -1))))
+            (i64.store (i32.const 64) (i64.const 0))
+            (drop (call $read (i32.const 0) (i32.const 5) (i32.const 64) (i32.const 8)))
+            (i64.store (i32.const 64)
+              (i64.add (i64.load (i32.const 64)) (i64.load (local.get $ptr))))
+            (call $write (i32.const 0) (i32.const 5) (i32.const 64) (i32.const 8))
+            (call $log (i32.const 16) (i32.const 11))
+            (i64.or (i64.shl (i64.const 64) (i64.const 32)) (i64.const 8)))
+          (func (export "grow") (param i32 i32) (result i64)
+            (drop (memory.grow (i32.const 1024)))
+            (i64.const 0))
+          (func (export "spin") (param i32 i32) (result i64)
+            (call $write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8))
+            (loop $forever (br $forever))
+            (i64.const 0)))
+    "#;
+
+    fn package(entrypoint: &str) -> FunctionPackage {
+        let info = FunctionInfo::new(2001, Pubkey::new_unique(), "counter".to_string(), 1, 10_000);
+        let abi = FunctionAbi {
+            entrypoint: entrypoint.to_string(),
+            ..FunctionAbi::default()
+        };
+        FunctionPackage::new(info, abi, wat::parse_str(COUNTER).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sandbox_execution() {
+        let sandbox = FunctionSandbox::new(&package("execute")).unwrap();
+        let mut state = MockState::new();
+
+        let first = sandbox.execute(&5u64.to_le_bytes(), &mut state).unwrap();
+        assert_eq!(first.output, 5u64.to_le_bytes());
+        assert_eq!(first.logs, vec!["incremented".to_string()]);
+        assert!(first.fuel_consumed > 0);
+
+        let second = sandbox.execute(&3u64.to_le_bytes(), &mut state).unwrap();
+        assert_eq!(second.output, 8u64.to_le_bytes());
+        assert_eq!(state.get(b"count"), Some(8u64.to_le_bytes().as_slice()));
+
+        // Error codes leave state untouched
+        let result = sandbox.execute(b"short", &mut state);
+        assert!(matches!(result, Err(RegistryError::ExecutionFailed(_))));
+        assert_eq!(state.get(b"count"), Some(8u64.to_le_bytes().as_slice()));
+    }
+
+    #[test]
+    fn test_sandbox_fuel_exhaustion() {
+        let sandbox = FunctionSandbox::new(&package("spin")).unwrap().with_fuel(1_000);
+        let mut state = MockState::new();
+        state.insert(b"count".to_vec(), 1u64.to_le_bytes().to_vec());
+
+        let result = sandbox.execute(&[], &mut state);
+        assert!(matches!(result, Err(RegistryError::ExecutionFailed(reason)) if reason == "out of fuel"));
+        assert_eq!(state.get(b"count"), Some(1u64.to_le_bytes().as_slice()));
+
+        // Missing entrypoints are reported as package errors
+        let sandbox = FunctionSandbox::new(&package("missing")).unwrap();
+        assert!(matches!(
+            sandbox.execute(&[], &mut state),
+            Err(RegistryError::InvalidPackage(_))
+        ));
+    }
+
+    #[test]
+    fn test_sandbox_memory_limit() {
+        let mut state = MockState::new();
+
+        // Growing by 64 MiB exceeds the default cap
+        let sandbox = FunctionSandbox::new(&package("grow")).unwrap();
+        assert!(matches!(
+            sandbox.execute(&[], &mut state),
+            Err(RegistryError::ExecutionFailed(_))
+        ));
+
+        let sandbox = FunctionSandbox::new(&package("grow"))
+            .unwrap()
+            .with_memory_limit(128 * 1024 * 1024);
+        assert!(sandbox.execute(&[], &mut state).is_ok());
+
+        // Modules whose initial memory exceeds the cap do not instantiate
+        let sandbox = FunctionSandbox::new(&package("execute"))
+            .unwrap()
+            .with_memory_limit(1024);
+        assert!(matches!(
+            sandbox.execute(&5u64.to_le_bytes(), &mut state),
+            Err(RegistryError::ExecutionFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,29 @@
+//! Replay error types
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("RPC error: {0}")]
+    Rpc(Box<solana_client::client_error::ClientError>),
+
+    #[error("Runtime error: {0}")]
+    Runtime(#[from] valence_runtime::RuntimeError),
+
+    #[error("Transaction decoding failed: {0}")]
+    Decode(String),
+
+    #[error("Session {0} was not created in the replayed range; replay from a snapshot")]
+    SessionNotCreated(String),
+
+    #[error("Invalid slot range: {0}")]
+    InvalidRange(String),
+}
+
+impl From<solana_client::client_error::ClientError> for ReplayError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        Self::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ReplayError>;
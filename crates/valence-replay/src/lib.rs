@@ -0,0 +1,22 @@
+//! Valence replay
+//!
+//! Re-executes a session's history locally for incident forensics. Given a
+//! session and a slot range, the confirmed transactions touching the session
+//! are fetched and every kernel instruction acting on it is replayed through
+//! the kernel's own state logic: batches are validated, offline signatures
+//! and guard approvals checked against the transaction's ed25519 precompiles,
+//! commitments matched to their reveals, and the usage count and nonce
+//! progression compared with the events the kernel emitted. Each step
+//! extends a hash chain over the replayed state, so two replays of the same
+//! history can be compared by their final hash.
+
+pub mod error;
+pub mod replay;
+pub mod source;
+
+pub use error::{ReplayError, Result};
+pub use replay::{
+    replay_session, session_hash, Discrepancy, DiscrepancyKind, GuardConfig, ReplayReport,
+    ReplayState, ReplayStep, Replayer,
+};
+pub use source::{KernelCall, ReplayTransaction, RpcTransactionSource, TransactionSource};
@@ -0,0 +1,945 @@
+//! Replay of a session's kernel instructions
+//!
+//! State transitions go through the kernel's own `Session` methods with a
+//! `Clock` rebuilt from each block, so a replay that saw the full history
+//! reproduces the session account byte for byte. Borrow permissions are not
+//! re-checked against the account lookup table, whose history is not
+//! replayed, and invalidations cascading from beyond the parent session are
+//! only caught by [`ReplayReport::verify_account`].
+
+use crate::source::{KernelCall, ReplayTransaction, TransactionSource};
+use crate::{ReplayError, Result};
+use anchor_lang::{prelude::*, AnchorDeserialize, AnchorSerialize, Discriminator};
+use serde::Serialize;
+use solana_sdk::hash::{hashv, Hash};
+use std::ops::RangeInclusive;
+use tracing::debug;
+use valence_common::pdas::BATCH_COMMITMENT_SEED;
+use valence_kernel::{
+    instruction as kernel_instruction, BatchCommitment, GuardAccount, KernelOperation,
+    OperationBatch, Session,
+};
+use valence_sdk::{decode_logs, ValenceEvent};
+
+/// Guard settings that decide how a batch must be authorized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct GuardConfig {
+    pub approval_signer: Option<Pubkey>,
+    pub require_commitment: bool,
+}
+
+impl From<&GuardAccount> for GuardConfig {
+    fn from(guard: &GuardAccount) -> Self {
+        Self {
+            approval_signer: guard.approval_signer,
+            require_commitment: guard.require_commitment,
+        }
+    }
+}
+
+/// Session state as the kernel would hold it at a point in history
+#[derive(Debug, Clone)]
+pub struct ReplayState {
+    pub session: Session,
+    /// Nonce of the session's offline-signing account, once initialized
+    pub offline_nonce: Option<u64>,
+    pub guard: GuardConfig,
+    /// Commitments made and not yet revealed
+    pub commitments: Vec<BatchCommitment>,
+}
+
+impl ReplayState {
+    /// State to replay forward from, e.g. accounts restored from a snapshot
+    pub fn new(session: Session, offline_nonce: Option<u64>, guard: GuardConfig) -> Self {
+        Self {
+            session,
+            offline_nonce,
+            guard,
+            commitments: Vec::new(),
+        }
+    }
+}
+
+/// Hash of a session account's serialized state
+pub fn session_hash(session: &Session) -> Hash {
+    let mut data = Vec::with_capacity(Session::LEN);
+    // Serializing into a Vec cannot fail
+    let _ = session.serialize(&mut data);
+    solana_sdk::hash::hash(&data)
+}
+
+/// State after one replayed kernel instruction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplayStep {
+    pub signature: String,
+    pub slot: u64,
+    pub instruction: &'static str,
+    pub usage_count: u64,
+    /// Session nonce, advanced when the session is invalidated
+    pub session_nonce: u64,
+    pub offline_nonce: Option<u64>,
+    pub active: bool,
+    /// Hash chaining this step's state to every step before it
+    pub state_hash: String,
+}
+
+/// Where replayed history disagrees with the kernel's rules or its own record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    /// The batch fails the kernel's own validation
+    InvalidBatch { reason: String },
+    /// A batch executed on an inactive session
+    SessionInactive,
+    /// A non-owner executed without the owner's signature at the current nonce
+    MissingOfflineSignature { nonce: Option<u64> },
+    /// The guard's approval signer did not sign the batch at this usage count
+    MissingApproval {
+        approval_signer: Pubkey,
+        usage_count: u64,
+    },
+    /// The guard requires a commitment and none was revealed
+    MissingCommitment,
+    /// A commitment was revealed before its earliest slot
+    CommitmentNotReady { earliest_slot: u64 },
+    /// A revealed commitment was made before the replayed range
+    CommitmentNotObserved,
+    /// A borrow or release the kernel would have rejected
+    BorrowRejected { account: Pubkey, reason: String },
+    /// The usage count the kernel emitted differs from the replayed one
+    UsageCountMismatch { replayed: u64, emitted: u64 },
+    /// The replayed state differs from the observed account
+    AccountMismatch {
+        field: String,
+        replayed: String,
+        observed: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Discrepancy {
+    /// Transaction the discrepancy was found in; empty for final-state checks
+    pub signature: String,
+    pub slot: u64,
+    #[serde(flatten)]
+    pub kind: DiscrepancyKind,
+}
+
+/// Outcome of replaying a session's history
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    pub session: Pubkey,
+    pub transactions: usize,
+    pub steps: Vec<ReplayStep>,
+    pub discrepancies: Vec<Discrepancy>,
+    /// Replayed state at the end of the range, if the session existed
+    pub state: Option<ReplayState>,
+}
+
+impl ReplayReport {
+    /// Whether every replayed instruction was consistent with the kernel's rules
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+
+    /// Final hash of the state chain, equal across replays of the same history
+    pub fn final_hash(&self) -> Option<&str> {
+        self.steps.last().map(|step| step.state_hash.as_str())
+    }
+
+    /// Compare the replayed state with the session account observed at the range end
+    pub fn verify_account(&mut self, observed: &Session) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let replayed = &state.session;
+        let mut mismatch = |field: &str, replayed: String, observed: String| {
+            if replayed != observed {
+                self.discrepancies.push(Discrepancy {
+                    signature: String::new(),
+                    slot: self.steps.last().map_or(0, |step| step.slot),
+                    kind: DiscrepancyKind::AccountMismatch {
+                        field: field.to_string(),
+                        replayed,
+                        observed,
+                    },
+                });
+            }
+        };
+
+        mismatch(
+            "usage_count",
+            replayed.usage_count.to_string(),
+            observed.usage_count.to_string(),
+        );
+        mismatch(
+            "nonce",
+            replayed.nonce.to_string(),
+            observed.nonce.to_string(),
+        );
+        mismatch(
+            "active",
+            replayed.active.to_string(),
+            observed.active.to_string(),
+        );
+        mismatch(
+            "borrowed_bitmap",
+            replayed.borrowed_bitmap.to_string(),
+            observed.borrowed_bitmap.to_string(),
+        );
+        mismatch(
+            "child_count",
+            replayed.child_count.to_string(),
+            observed.child_count.to_string(),
+        );
+        mismatch(
+            "child_session_count",
+            replayed.child_session_count.to_string(),
+            observed.child_session_count.to_string(),
+        );
+        mismatch(
+            "stats_enabled",
+            replayed.stats_enabled.to_string(),
+            observed.stats_enabled.to_string(),
+        );
+        mismatch(
+            "state_hash",
+            session_hash(replayed).to_string(),
+            session_hash(observed).to_string(),
+        );
+    }
+}
+
+/// Anchor instruction arguments when `data` carries `T`'s discriminator
+fn args<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let mut body = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::deserialize(&mut body).ok()
+}
+
+/// Replays kernel instructions acting on one session
+pub struct Replayer {
+    session: Pubkey,
+    state: Option<ReplayState>,
+    chain: Hash,
+    transactions: usize,
+    steps: Vec<ReplayStep>,
+    discrepancies: Vec<Discrepancy>,
+}
+
+impl Replayer {
+    /// Replay from the transaction that created the session
+    pub fn from_creation(session: Pubkey) -> Self {
+        Self {
+            session,
+            state: None,
+            chain: Hash::default(),
+            transactions: 0,
+            steps: Vec::new(),
+            discrepancies: Vec::new(),
+        }
+    }
+
+    /// Replay forward from known state
+    pub fn from_state(session: Pubkey, state: ReplayState) -> Self {
+        Self {
+            state: Some(state),
+            ..Self::from_creation(session)
+        }
+    }
+
+    pub fn state(&self) -> Option<&ReplayState> {
+        self.state.as_ref()
+    }
+
+    /// Apply a confirmed transaction; failed transactions changed nothing
+    pub fn apply(&mut self, transaction: &ReplayTransaction) -> Result<()> {
+        if !transaction.success {
+            return Ok(());
+        }
+        self.transactions += 1;
+
+        let clock = Clock {
+            slot: transaction.slot,
+            unix_timestamp: transaction.block_time.unwrap_or_default(),
+            ..Clock::default()
+        };
+        let events = decode_logs(&transaction.log_messages);
+        let mut emitted_usage = events
+            .iter()
+            .filter_map(|event| match event {
+                ValenceEvent::BatchExecuted(event) if event.session == self.session => {
+                    Some(event.usage_count)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        for call in &transaction.kernel_calls {
+            self.apply_call(transaction, call, &clock, &mut emitted_usage)?;
+        }
+
+        // Children created in this transaction are tracked by their parent
+        for event in &events {
+            if let ValenceEvent::SessionCreated(event) = event {
+                if event.parent_session == Some(self.session) {
+                    if let Some(state) = &mut self.state {
+                        if let Err(e) = state.session.track_child_session(event.session) {
+                            debug!("Child session not tracked: {}", e);
+                        }
+                        self.record(transaction, "track_child_session");
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_call(
+        &mut self,
+        transaction: &ReplayTransaction,
+        call: &KernelCall,
+        clock: &Clock,
+        emitted_usage: &mut impl Iterator<Item = u64>,
+    ) -> Result<()> {
+        let data = call.data.as_slice();
+        let targets_session = call.accounts.first() == Some(&self.session);
+
+        if targets_session {
+            if let Some(args) = args::<kernel_instruction::CreateSessionAccount>(data) {
+                let account = |index: usize| call.accounts.get(index).copied().unwrap_or_default();
+                let session = Session::new(
+                    args.params,
+                    account(3),
+                    args.shard,
+                    account(2),
+                    account(1),
+                    clock,
+                )
+                .map_err(|e| ReplayError::Decode(format!("session creation: {}", e)))?;
+                self.state = Some(ReplayState::new(session, None, GuardConfig::default()));
+                self.record(transaction, "create_session");
+                return Ok(());
+            }
+        }
+
+        let Some(state) = self.state.as_mut() else {
+            // Kernel instructions touching the session before it exists
+            return if targets_session {
+                Err(ReplayError::SessionNotCreated(self.session.to_string()))
+            } else {
+                Ok(())
+            };
+        };
+
+        if !targets_session {
+            // Parent invalidations cascade to children passed alongside them
+            let parent = state.session.parent_session;
+            let invalidates_parent = args::<kernel_instruction::InvalidateSession>(data).is_some()
+                && parent.is_some()
+                && call.accounts.first() == parent.as_ref();
+            let in_batch =
+                args::<kernel_instruction::InvalidateSessionBatch>(data).is_some_and(|args| {
+                    args.session_keys.contains(&self.session)
+                        || parent.is_some_and(|parent| args.session_keys.contains(&parent))
+                });
+            if (invalidates_parent || in_batch)
+                && call
+                    .accounts
+                    .iter()
+                    .skip(1)
+                    .any(|account| *account == self.session)
+                && state.session.active
+            {
+                state.session.active = false;
+                state.session.nonce = state.session.nonce.saturating_add(1);
+                self.record(transaction, "invalidate_session");
+            }
+            return Ok(());
+        }
+
+        let name = if let Some(args) = args::<kernel_instruction::ExecuteBatch>(data) {
+            let caller = call.accounts.get(4).copied().unwrap_or_default();
+            self.replay_batch(transaction, &args.batch, caller, clock, emitted_usage);
+            "execute_batch"
+        } else if let Some(args) = args::<kernel_instruction::CommitBatch>(data) {
+            state.commitments.push(BatchCommitment {
+                session: self.session,
+                batch_hash: args.batch_hash,
+                earliest_slot: args.earliest_slot,
+                committed_slot: clock.slot,
+                bump: 0,
+            });
+            "commit_batch"
+        } else if args::<kernel_instruction::InvalidateSession>(data).is_some() {
+            state.session.active = false;
+            state.session.nonce = state.session.nonce.saturating_add(1);
+            "invalidate_session"
+        } else if args::<kernel_instruction::CloseSession>(data).is_some() {
+            self.record(transaction, "close_session");
+            // Later instructions on the session fail until it is created again
+            self.state = None;
+            return Ok(());
+        } else if let Some(args) = args::<kernel_instruction::SetGuardApprovalSigner>(data) {
+            state.guard.approval_signer = args.approval_signer;
+            "set_guard_approval_signer"
+        } else if let Some(args) = args::<kernel_instruction::SetGuardRequireCommitment>(data) {
+            state.guard.require_commitment = args.require_commitment;
+            "set_guard_require_commitment"
+        } else if args::<kernel_instruction::InitializeSessionNonce>(data).is_some() {
+            state.offline_nonce = Some(0);
+            "initialize_session_nonce"
+        } else if args::<kernel_instruction::InitializeSessionStats>(data).is_some() {
+            state.session.stats_enabled = true;
+            "initialize_session_stats"
+        } else if args::<kernel_instruction::CreateChildAccount>(data).is_some() {
+            let child = call.accounts.get(1).copied().unwrap_or_default();
+            let _ = state.session.track_child_account(child);
+            let _ = state.session.increment_usage(clock);
+            "create_child_account"
+        } else if args::<kernel_instruction::CloseChildAccount>(data).is_some() {
+            let child = call.accounts.get(1).copied().unwrap_or_default();
+            let _ = state.session.untrack_child_account(child);
+            "close_child_account"
+        } else if args::<kernel_instruction::SplTransfer>(data).is_some() {
+            let _ = state.session.increment_usage(clock);
+            "spl_transfer"
+        } else {
+            // Configuration instructions leave the session untouched
+            return Ok(());
+        };
+
+        self.record(transaction, name);
+        Ok(())
+    }
+
+    /// Re-run `execute_batch`'s checks and state changes
+    fn replay_batch(
+        &mut self,
+        transaction: &ReplayTransaction,
+        batch: &OperationBatch,
+        caller: Pubkey,
+        clock: &Clock,
+        emitted_usage: &mut impl Iterator<Item = u64>,
+    ) {
+        let session_key = self.session;
+        let state = self
+            .state
+            .as_mut()
+            .expect("batches replay on existing sessions");
+        let mut found = Vec::new();
+
+        if let Err(e) = batch.validate() {
+            found.push(DiscrepancyKind::InvalidBatch {
+                reason: e.to_string(),
+            });
+        }
+
+        // The owner calls directly or signed the batch offline at the current nonce
+        if caller != state.session.owner {
+            let signed = state.offline_nonce.is_some_and(|nonce| {
+                batch
+                    .offline_message(&session_key, nonce)
+                    .is_ok_and(|message| {
+                        transaction.has_ed25519_signature(&state.session.owner, &message)
+                    })
+            });
+            if signed {
+                state.offline_nonce = state.offline_nonce.map(|nonce| nonce + 1);
+            } else {
+                found.push(DiscrepancyKind::MissingOfflineSignature {
+                    nonce: state.offline_nonce,
+                });
+            }
+        }
+
+        if !state.session.active {
+            found.push(DiscrepancyKind::SessionInactive);
+        }
+
+        if let Some(approval_signer) = state.guard.approval_signer {
+            let approved = batch
+                .approval_message(&session_key, state.session.usage_count)
+                .is_ok_and(|message| transaction.has_ed25519_signature(&approval_signer, &message));
+            if !approved {
+                found.push(DiscrepancyKind::MissingApproval {
+                    approval_signer,
+                    usage_count: state.session.usage_count,
+                });
+            }
+        }
+
+        // A commitment is revealed when its account is passed to the transaction
+        if let Ok(batch_hash) = batch.commitment_hash() {
+            let (address, _) = Pubkey::find_program_address(
+                &[
+                    BATCH_COMMITMENT_SEED,
+                    session_key.as_ref(),
+                    batch_hash.as_ref(),
+                ],
+                &valence_kernel::ID,
+            );
+            let pending = state
+                .commitments
+                .iter()
+                .position(|commitment| commitment.batch_hash == batch_hash);
+            if transaction.account_keys.contains(&address) {
+                match pending {
+                    Some(index) => {
+                        let commitment = state.commitments.remove(index);
+                        if !commitment.is_ready(clock.slot) {
+                            found.push(DiscrepancyKind::CommitmentNotReady {
+                                earliest_slot: commitment.earliest_slot,
+                            });
+                        }
+                    }
+                    None => found.push(DiscrepancyKind::CommitmentNotObserved),
+                }
+            } else if state.guard.require_commitment {
+                found.push(DiscrepancyKind::MissingCommitment);
+            }
+        }
+
+        for operation in batch
+            .operations
+            .iter()
+            .take(batch.operations_len as usize)
+            .flatten()
+        {
+            let account = |index: u8| {
+                batch
+                    .accounts
+                    .get(index as usize)
+                    .copied()
+                    .unwrap_or_default()
+            };
+            let (account, result) = match operation {
+                KernelOperation::BorrowAccount {
+                    account_index,
+                    mode,
+                } => {
+                    let account = account(*account_index);
+                    (
+                        account,
+                        state
+                            .session
+                            .borrow_account(account, *mode, clock)
+                            .map(|_| ()),
+                    )
+                }
+                KernelOperation::ReleaseAccount { account_index } => {
+                    let account = account(*account_index);
+                    (account, state.session.release_account(&account))
+                }
+                _ => continue,
+            };
+            if let Err(e) = result {
+                found.push(DiscrepancyKind::BorrowRejected {
+                    account,
+                    reason: e.to_string(),
+                });
+            }
+        }
+
+        let _ = state.session.increment_usage(clock);
+        let replayed = state.session.usage_count;
+        if let Some(emitted) = emitted_usage.next() {
+            if emitted != replayed {
+                found.push(DiscrepancyKind::UsageCountMismatch { replayed, emitted });
+            }
+        }
+
+        for kind in found {
+            self.discrepancies.push(Discrepancy {
+                signature: transaction.signature.to_string(),
+                slot: transaction.slot,
+                kind,
+            });
+        }
+    }
+
+    /// Extend the state chain with the current state
+    fn record(&mut self, transaction: &ReplayTransaction, instruction: &'static str) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let offline_nonce = state.offline_nonce.map_or([0xff; 8], u64::to_le_bytes);
+        let guard = state.guard.approval_signer.unwrap_or_default();
+        self.chain = hashv(&[
+            self.chain.as_ref(),
+            transaction.signature.as_ref(),
+            session_hash(&state.session).as_ref(),
+            &offline_nonce,
+            guard.as_ref(),
+            &[state.guard.require_commitment as u8],
+        ]);
+
+        self.steps.push(ReplayStep {
+            signature: transaction.signature.to_string(),
+            slot: transaction.slot,
+            instruction,
+            usage_count: state.session.usage_count,
+            session_nonce: state.session.nonce,
+            offline_nonce: state.offline_nonce,
+            active: state.session.active,
+            state_hash: self.chain.to_string(),
+        });
+    }
+
+    pub fn finish(self) -> ReplayReport {
+        ReplayReport {
+            session: self.session,
+            transactions: self.transactions,
+            steps: self.steps,
+            discrepancies: self.discrepancies,
+            state: self.state,
+        }
+    }
+}
+
+/// Fetch and replay a session's transactions within `slots`
+///
+/// Without `start`, the range must include the session's creation.
+pub async fn replay_session(
+    source: &dyn TransactionSource,
+    session: Pubkey,
+    slots: RangeInclusive<u64>,
+    start: Option<ReplayState>,
+) -> Result<ReplayReport> {
+    if slots.is_empty() {
+        return Err(ReplayError::InvalidRange(format!(
+            "{} > {}",
+            slots.start(),
+            slots.end()
+        )));
+    }
+
+    let mut replayer = match start {
+        Some(state) => Replayer::from_state(session, state),
+        None => Replayer::from_creation(session),
+    };
+    for signature in source.signatures(&session, &slots).await? {
+        let transaction = source.transaction(&signature).await?;
+        replayer.apply(&transaction)?;
+    }
+    Ok(replayer.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::TransactionSource;
+    use anchor_lang::InstructionData;
+    use async_trait::async_trait;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use solana_sdk::signature::Signature;
+    use valence_common::events::BatchExecuted;
+    use valence_common::introspection::Ed25519Signature;
+    use valence_kernel::{
+        CreateSessionParams, ACCESS_MODE_READ, MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS,
+    };
+
+    struct History {
+        session: Pubkey,
+        owner: Pubkey,
+        approver: Pubkey,
+        relayer: Pubkey,
+    }
+
+    impl History {
+        fn new() -> Self {
+            Self {
+                session: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                approver: Pubkey::new_unique(),
+                relayer: Pubkey::new_unique(),
+            }
+        }
+
+        fn transaction(&self, slot: u64, kernel_calls: Vec<KernelCall>) -> ReplayTransaction {
+            ReplayTransaction {
+                signature: Signature::new_unique(),
+                slot,
+                block_time: Some(1_700_000_000 + slot as i64),
+                success: true,
+                kernel_calls,
+                ed25519_signatures: Vec::new(),
+                account_keys: Vec::new(),
+                log_messages: Vec::new(),
+            }
+        }
+
+        fn call(&self, data: Vec<u8>, accounts: Vec<Pubkey>) -> KernelCall {
+            KernelCall { accounts, data }
+        }
+
+        fn create(&self) -> ReplayTransaction {
+            let mut namespace_path = [0u8; 128];
+            namespace_path[..5].copy_from_slice(b"alice");
+            let data = kernel_instruction::CreateSessionAccount {
+                shard: Pubkey::new_unique(),
+                params: CreateSessionParams {
+                    namespace_path,
+                    namespace_path_len: 5,
+                    metadata: [0; 32],
+                    parent_session: None,
+                },
+                initial_borrowable: Vec::new(),
+                initial_programs: Vec::new(),
+            }
+            .data();
+            let accounts = vec![
+                self.session,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                self.owner,
+            ];
+            self.transaction(10, vec![self.call(data, accounts)])
+        }
+
+        fn configure(&self) -> ReplayTransaction {
+            let nonce = kernel_instruction::InitializeSessionNonce {}.data();
+            let approval = kernel_instruction::SetGuardApprovalSigner {
+                approval_signer: Some(self.approver),
+            }
+            .data();
+            self.transaction(
+                11,
+                vec![
+                    self.call(nonce, vec![self.session]),
+                    self.call(approval, vec![self.session]),
+                ],
+            )
+        }
+
+        fn batch(&self) -> OperationBatch {
+            let mut accounts = [Pubkey::default(); MAX_BATCH_ACCOUNTS];
+            accounts[0] = Pubkey::new_unique();
+            let mut operations = [const { None }; MAX_BATCH_OPERATIONS];
+            operations[0] = Some(KernelOperation::BorrowAccount {
+                account_index: 0,
+                mode: ACCESS_MODE_READ,
+            });
+            OperationBatch {
+                accounts,
+                accounts_len: 1,
+                operations,
+                operations_len: 1,
+                operation_data_hash: [0; 32],
+            }
+        }
+
+        fn execute(
+            &self,
+            slot: u64,
+            batch: OperationBatch,
+            caller: Pubkey,
+            emitted_usage: u64,
+        ) -> ReplayTransaction {
+            let data = kernel_instruction::ExecuteBatch { batch }.data();
+            let mut accounts = vec![Pubkey::new_unique(); 9];
+            accounts[0] = self.session;
+            accounts[4] = caller;
+
+            let event = BatchExecuted {
+                session: self.session,
+                caller,
+                operations: 1,
+                usage_count: emitted_usage,
+                timestamp: 0,
+            };
+            let mut event_data = BatchExecuted::DISCRIMINATOR.to_vec();
+            AnchorSerialize::serialize(&event, &mut event_data).unwrap();
+
+            let mut transaction = self.transaction(slot, vec![self.call(data, accounts)]);
+            transaction.log_messages = vec![
+                format!("Program {} invoke [1]", valence_kernel::ID),
+                format!("Program data: {}", STANDARD.encode(event_data)),
+                format!("Program {} success", valence_kernel::ID),
+            ];
+            transaction
+        }
+    }
+
+    fn signed(
+        mut transaction: ReplayTransaction,
+        signer: Pubkey,
+        message: [u8; 32],
+    ) -> ReplayTransaction {
+        transaction.ed25519_signatures.push(Ed25519Signature {
+            public_key: signer,
+            signature: [0; 64],
+            message: message.to_vec(),
+        });
+        transaction
+    }
+
+    /// Creation, an owner batch and an offline-signed relayed batch, all approved
+    fn consistent_history(history: &History) -> Vec<ReplayTransaction> {
+        let batch = history.batch();
+        let approval = batch.approval_message(&history.session, 0).unwrap();
+        let owner_batch = signed(
+            history.execute(12, batch, history.owner, 1),
+            history.approver,
+            approval,
+        );
+
+        let relayed = history.batch();
+        let approval = relayed.approval_message(&history.session, 1).unwrap();
+        let offline = relayed.offline_message(&history.session, 0).unwrap();
+        let relayed_batch = signed(
+            signed(
+                history.execute(13, relayed, history.relayer, 2),
+                history.approver,
+                approval,
+            ),
+            history.owner,
+            offline,
+        );
+
+        vec![
+            history.create(),
+            history.configure(),
+            owner_batch,
+            relayed_batch,
+        ]
+    }
+
+    #[test]
+    fn test_replay_consistent_history() {
+        let history = History::new();
+        let transactions = consistent_history(&history);
+
+        let mut replayer = Replayer::from_creation(history.session);
+        for transaction in &transactions {
+            replayer.apply(transaction).unwrap();
+        }
+        let mut report = replayer.finish();
+        assert!(report.is_consistent(), "{:?}", report.discrepancies);
+
+        let progression: Vec<_> = report
+            .steps
+            .iter()
+            .map(|step| (step.instruction, step.usage_count, step.offline_nonce))
+            .collect();
+        assert_eq!(
+            progression,
+            vec![
+                ("create_session", 0, None),
+                ("initialize_session_nonce", 0, Some(0)),
+                ("set_guard_approval_signer", 0, Some(0)),
+                ("execute_batch", 1, Some(0)),
+                ("execute_batch", 2, Some(1)),
+            ]
+        );
+
+        // The same history always chains to the same hash
+        let mut again = Replayer::from_creation(history.session);
+        for transaction in &transactions {
+            again.apply(transaction).unwrap();
+        }
+        assert_eq!(again.finish().final_hash(), report.final_hash());
+
+        let mut observed = report.state.clone().unwrap().session;
+        report.verify_account(&observed);
+        assert!(report.is_consistent());
+
+        observed.usage_count = 7;
+        report.verify_account(&observed);
+        let fields: Vec<_> = report
+            .discrepancies
+            .iter()
+            .map(|discrepancy| match &discrepancy.kind {
+                DiscrepancyKind::AccountMismatch { field, .. } => field.as_str(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(fields, vec!["usage_count", "state_hash"]);
+    }
+
+    #[test]
+    fn test_replay_detects_unauthorized_batch() {
+        let history = History::new();
+        let mut replayer = Replayer::from_creation(history.session);
+        replayer.apply(&history.create()).unwrap();
+        replayer.apply(&history.configure()).unwrap();
+
+        // A relayed batch with neither signature, whose event disagrees on usage
+        let unauthorized = history.execute(12, history.batch(), history.relayer, 5);
+        replayer.apply(&unauthorized).unwrap();
+
+        // Failed transactions leave no trace
+        let mut failed = history.execute(13, history.batch(), history.relayer, 6);
+        failed.success = false;
+        replayer.apply(&failed).unwrap();
+
+        let report = replayer.finish();
+        let kinds: Vec<_> = report
+            .discrepancies
+            .iter()
+            .map(|d| d.kind.clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiscrepancyKind::MissingOfflineSignature { nonce: Some(0) },
+                DiscrepancyKind::MissingApproval {
+                    approval_signer: history.approver,
+                    usage_count: 0,
+                },
+                DiscrepancyKind::UsageCountMismatch {
+                    replayed: 1,
+                    emitted: 5,
+                },
+            ]
+        );
+        assert!(report
+            .discrepancies
+            .iter()
+            .all(|d| d.signature == unauthorized.signature.to_string()));
+        assert_eq!(report.transactions, 3);
+    }
+
+    struct MemorySource(Vec<ReplayTransaction>);
+
+    #[async_trait]
+    impl TransactionSource for MemorySource {
+        async fn signatures(
+            &self,
+            _address: &Pubkey,
+            slots: &RangeInclusive<u64>,
+        ) -> Result<Vec<Signature>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|transaction| slots.contains(&transaction.slot))
+                .map(|transaction| transaction.signature)
+                .collect())
+        }
+
+        async fn transaction(&self, signature: &Signature) -> Result<ReplayTransaction> {
+            Ok(self
+                .0
+                .iter()
+                .find(|transaction| transaction.signature == *signature)
+                .cloned()
+                .unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_session_range() {
+        let history = History::new();
+        let source = MemorySource(consistent_history(&history));
+
+        let report = replay_session(&source, history.session, 0..=100, None)
+            .await
+            .unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(report.state.unwrap().session.usage_count, 2);
+
+        // Starting after creation needs the state at the range start
+        let result = replay_session(&source, history.session, 11..=100, None).await;
+        assert!(matches!(result, Err(ReplayError::SessionNotCreated(_))));
+    }
+}
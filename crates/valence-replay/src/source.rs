@@ -0,0 +1,184 @@
+//! Historical transactions for a session
+
+use crate::{ReplayError, Result};
+use async_trait::async_trait;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, pubkey::Pubkey,
+    signature::Signature,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use std::{ops::RangeInclusive, str::FromStr};
+use valence_common::introspection::{ed25519_signatures, Ed25519Signature, ED25519_PROGRAM_ID};
+use valence_runtime::CompiledTransaction;
+
+/// Signatures requested per page of address history
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+/// A kernel instruction invoked by a transaction, top level or through CPI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelCall {
+    pub accounts: Vec<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// What replay needs from a confirmed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayTransaction {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Cluster timestamp of the block, which the kernel reads from `Clock`
+    pub block_time: Option<i64>,
+    pub success: bool,
+    /// Kernel instructions in execution order
+    pub kernel_calls: Vec<KernelCall>,
+    /// Signatures verified by the transaction's ed25519 precompiles
+    pub ed25519_signatures: Vec<Ed25519Signature>,
+    /// Every account the transaction referenced
+    pub account_keys: Vec<Pubkey>,
+    pub log_messages: Vec<String>,
+}
+
+impl ReplayTransaction {
+    pub fn from_compiled(compiled: &CompiledTransaction) -> Result<Self> {
+        let signature = Signature::from_str(&compiled.signature)
+            .map_err(|e| ReplayError::Decode(format!("signature: {}", e)))?;
+
+        let mut kernel_calls = Vec::new();
+        let mut signatures = Vec::new();
+        for (position, program_id, accounts, data) in compiled.resolved() {
+            if program_id == valence_kernel::ID {
+                kernel_calls.push(KernelCall {
+                    accounts,
+                    data: data.to_vec(),
+                });
+            } else if program_id == ED25519_PROGRAM_ID && position.inner_index.is_none() {
+                // Only top-level precompiles are visible to the instructions sysvar
+                let instruction = Instruction {
+                    program_id,
+                    accounts: Vec::new(),
+                    data: data.to_vec(),
+                };
+                signatures.extend(ed25519_signatures(&instruction).unwrap_or_default());
+            }
+        }
+
+        Ok(Self {
+            signature,
+            slot: compiled.slot,
+            block_time: compiled.block_time,
+            success: compiled.success,
+            kernel_calls,
+            ed25519_signatures: signatures,
+            account_keys: compiled.account_keys.clone(),
+            log_messages: compiled.log_messages.clone(),
+        })
+    }
+
+    /// Whether an ed25519 precompile in the transaction verified `message` from `signer`
+    pub fn has_ed25519_signature(&self, signer: &Pubkey, message: &[u8]) -> bool {
+        self.ed25519_signatures
+            .iter()
+            .any(|signature| signature.public_key == *signer && signature.message == message)
+    }
+}
+
+/// Source of an address's confirmed transactions
+#[async_trait]
+pub trait TransactionSource: Send + Sync {
+    /// Signatures of transactions referencing `address` within `slots`, oldest first
+    async fn signatures(
+        &self,
+        address: &Pubkey,
+        slots: &RangeInclusive<u64>,
+    ) -> Result<Vec<Signature>>;
+
+    async fn transaction(&self, signature: &Signature) -> Result<ReplayTransaction>;
+}
+
+/// Transactions fetched from an RPC node with full history
+pub struct RpcTransactionSource {
+    rpc_client: RpcClient,
+}
+
+impl RpcTransactionSource {
+    pub fn new(rpc_url: String) -> Self {
+        Self::with_client(RpcClient::new_with_commitment(
+            rpc_url,
+            CommitmentConfig::confirmed(),
+        ))
+    }
+
+    pub fn with_client(rpc_client: RpcClient) -> Self {
+        Self { rpc_client }
+    }
+}
+
+#[async_trait]
+impl TransactionSource for RpcTransactionSource {
+    async fn signatures(
+        &self,
+        address: &Pubkey,
+        slots: &RangeInclusive<u64>,
+    ) -> Result<Vec<Signature>> {
+        let mut collected = Vec::new();
+        let mut before = None;
+
+        // History is returned newest first; page back until the range starts
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    address,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: Some(SIGNATURE_PAGE_SIZE),
+                        commitment: Some(self.rpc_client.commitment()),
+                    },
+                )
+                .await?;
+
+            let page_len = page.len();
+            let reached_start = page.iter().any(|status| status.slot < *slots.start());
+            before = page
+                .last()
+                .map(|status| Signature::from_str(&status.signature))
+                .transpose()
+                .map_err(|e| ReplayError::Decode(e.to_string()))?;
+
+            for status in page
+                .into_iter()
+                .filter(|status| slots.contains(&status.slot))
+            {
+                collected.push(
+                    Signature::from_str(&status.signature)
+                        .map_err(|e| ReplayError::Decode(e.to_string()))?,
+                );
+            }
+
+            if reached_start || page_len < SIGNATURE_PAGE_SIZE {
+                break;
+            }
+        }
+
+        collected.reverse();
+        Ok(collected)
+    }
+
+    async fn transaction(&self, signature: &Signature) -> Result<ReplayTransaction> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.rpc_client.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let confirmed = self
+            .rpc_client
+            .get_transaction_with_config(signature, config)
+            .await?;
+        ReplayTransaction::from_compiled(&CompiledTransaction::from_confirmed(&confirmed)?)
+    }
+}
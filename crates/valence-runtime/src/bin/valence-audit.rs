@@ -0,0 +1,70 @@
+//! Audit log verification tool
+//!
+//! Usage: `valence-audit verify <audit-dir | segment...> [--signer <pubkey>]...`
+
+use solana_sdk::pubkey::Pubkey;
+use std::{path::PathBuf, process::ExitCode, str::FromStr};
+use valence_runtime::security::audit::{
+    read_segment, verify_directory, verify_segment, AuditVerification,
+};
+
+fn usage() -> ExitCode {
+    eprintln!("usage: valence-audit verify <audit-dir | segment...> [--signer <pubkey>]...");
+    ExitCode::from(2)
+}
+
+fn verify(paths: &[PathBuf], signers: &[Pubkey]) -> valence_runtime::Result<AuditVerification> {
+    if let [directory] = paths {
+        if directory.is_dir() {
+            return verify_directory(directory, signers);
+        }
+    }
+
+    // Explicit segments are verified as one contiguous export
+    let mut entries = Vec::new();
+    for path in paths {
+        entries.extend(read_segment(path)?);
+    }
+    verify_segment(&entries, &[], signers)
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some("verify") {
+        return usage();
+    }
+
+    let mut paths = Vec::new();
+    let mut signers = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--signer" {
+            match args.next().map(|key| Pubkey::from_str(&key)) {
+                Some(Ok(signer)) => signers.push(signer),
+                _ => return usage(),
+            }
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+    if paths.is_empty() {
+        return usage();
+    }
+
+    match verify(&paths, &signers) {
+        Ok(report) => {
+            println!(
+                "ok: {} entries (sequence {:?}..={:?}), {} checkpoints verified, head {}",
+                report.entries,
+                report.first_sequence,
+                report.last_sequence,
+                report.checkpoints_verified,
+                report.last_hash.as_deref().unwrap_or("-")
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("verification failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
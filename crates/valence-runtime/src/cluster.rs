@@ -0,0 +1,175 @@
+//! Named clusters and their per-cluster clients
+
+use crate::{
+    core::{ClusterConfig, DEFAULT_CLUSTER},
+    monitoring::MetricsExporter,
+    rpc_pool::{RpcPool, RpcPoolConfig},
+    transaction::TransactionBuilder,
+    Result, RuntimeError,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::{collections::HashMap, sync::Arc};
+
+/// A configured cluster with its RPC client
+pub struct Cluster {
+    pub config: ClusterConfig,
+    /// Endpoint pool backing `rpc_client`
+    pub rpc_pool: Arc<RpcPool>,
+    pub rpc_client: Arc<RpcClient>,
+}
+
+impl Cluster {
+    pub fn new(config: ClusterConfig, pool_config: RpcPoolConfig) -> Result<Self> {
+        let rpc_pool = RpcPool::new(config.rpc_urls(), pool_config)?;
+        Ok(Self::with_pool(config, rpc_pool))
+    }
+
+    fn with_pool(config: ClusterConfig, rpc_pool: RpcPool) -> Self {
+        let rpc_pool = Arc::new(rpc_pool);
+        let rpc_client = Arc::new(rpc_pool.client(config.commitment));
+        Self {
+            config,
+            rpc_pool,
+            rpc_client,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+/// Clusters available to the runtime, keyed by name
+pub struct ClusterRegistry {
+    clusters: HashMap<String, Arc<Cluster>>,
+}
+
+impl ClusterRegistry {
+    /// Create clients for each configured cluster; one must be named [`DEFAULT_CLUSTER`]
+    pub fn new(configs: Vec<ClusterConfig>, pool_config: RpcPoolConfig) -> Result<Self> {
+        Self::build(configs, pool_config, None)
+    }
+
+    /// Create clients for each configured cluster, recording their RPC latency
+    pub fn with_metrics(
+        configs: Vec<ClusterConfig>,
+        pool_config: RpcPoolConfig,
+        metrics: Arc<MetricsExporter>,
+    ) -> Result<Self> {
+        Self::build(configs, pool_config, Some(metrics))
+    }
+
+    fn build(
+        configs: Vec<ClusterConfig>,
+        pool_config: RpcPoolConfig,
+        metrics: Option<Arc<MetricsExporter>>,
+    ) -> Result<Self> {
+        let mut clusters = HashMap::new();
+        for config in configs {
+            let name = config.name.clone();
+            let mut rpc_pool = RpcPool::new(config.rpc_urls(), pool_config.clone())?;
+            if let Some(metrics) = &metrics {
+                rpc_pool = rpc_pool.with_metrics(metrics.clone());
+            }
+            let cluster = Arc::new(Cluster::with_pool(config, rpc_pool));
+            if clusters.insert(name.clone(), cluster).is_some() {
+                return Err(RuntimeError::InvalidConfiguration(format!(
+                    "Duplicate cluster name: {}",
+                    name
+                )));
+            }
+        }
+
+        if !clusters.contains_key(DEFAULT_CLUSTER) {
+            return Err(RuntimeError::InvalidConfiguration(format!(
+                "Missing {} cluster",
+                DEFAULT_CLUSTER
+            )));
+        }
+        Ok(Self { clusters })
+    }
+
+    /// Look up a cluster by name
+    pub fn get(&self, name: &str) -> Result<&Arc<Cluster>> {
+        self.clusters.get(name).ok_or_else(|| {
+            RuntimeError::InvalidConfiguration(format!("Unknown cluster: {}", name))
+        })
+    }
+
+    /// Resolve an optional cluster name, falling back to the default cluster
+    pub fn resolve(&self, name: Option<&str>) -> Result<&Arc<Cluster>> {
+        self.get(name.unwrap_or(DEFAULT_CLUSTER))
+    }
+
+    pub fn default_cluster(&self) -> &Arc<Cluster> {
+        &self.clusters[DEFAULT_CLUSTER]
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.clusters.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.clusters.keys().map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<Cluster>> {
+        self.clusters.values()
+    }
+
+    /// Start health probes of every cluster's RPC endpoints
+    pub async fn start(&self) -> Result<()> {
+        for cluster in self.clusters.values() {
+            cluster.rpc_pool.start().await?;
+        }
+        Ok(())
+    }
+
+    /// Stop health probes
+    pub async fn stop(&self) -> Result<()> {
+        for cluster in self.clusters.values() {
+            cluster.rpc_pool.stop().await?;
+        }
+        Ok(())
+    }
+
+    /// Transaction builder using the given cluster's RPC client
+    pub fn transaction_builder(&self, name: Option<&str>) -> Result<TransactionBuilder> {
+        Ok(TransactionBuilder::new(self.resolve(name)?.rpc_client.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ClusterRegistry {
+        let mut default = ClusterConfig::mainnet();
+        default.name = DEFAULT_CLUSTER.to_string();
+        ClusterRegistry::new(vec![default, ClusterConfig::devnet()], RpcPoolConfig::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_clusters() {
+        let registry = registry();
+
+        assert_eq!(registry.resolve(None).unwrap().name(), DEFAULT_CLUSTER);
+        assert_eq!(
+            registry.resolve(Some("devnet")).unwrap().rpc_client.url(),
+            "https://api.devnet.solana.com"
+        );
+        assert!(registry.resolve(Some("testnet")).is_err());
+        assert!(registry.transaction_builder(Some("devnet")).is_ok());
+    }
+
+    #[test]
+    fn test_registry_validation() {
+        let pool = RpcPoolConfig::default();
+        assert!(ClusterRegistry::new(vec![ClusterConfig::devnet()], pool.clone()).is_err());
+
+        let mut default = ClusterConfig::localnet();
+        default.name = DEFAULT_CLUSTER.to_string();
+        assert!(ClusterRegistry::new(vec![default.clone(), default], pool).is_err());
+    }
+}
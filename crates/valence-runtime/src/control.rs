@@ -0,0 +1,638 @@
+//! REST control-plane API for operating the runtime
+//!
+//! Every route except `/health` requires an `Authorization: Bearer` token.
+//! Tokens map to an identity and role; each request runs with a
+//! `SecurityContext` carrying that identity, and mutating routes require
+//! the operator role.
+
+use crate::{
+    coordination::{Coordinator, FlowExecution, ProtocolFlow},
+    monitoring::MetricsExporter,
+    security::{
+        audit::{AuditEntry, AuditEventType, AuditLogger, AuditOutcome},
+        Environment, SecurityContext, SecurityPolicies, SessionInfo,
+    },
+    session::{SessionHealthConfig, SessionHealthReport, SessionManager},
+    Result, RuntimeError,
+};
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+// ================================
+// Configuration
+// ================================
+
+/// Access granted to an API token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRole {
+    /// Read flows, executions, session health and metrics
+    ReadOnly,
+    /// Additionally start, stop and retry flow executions
+    Operator,
+}
+
+/// Bearer token accepted by the control API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub token: String,
+    pub identity: String,
+    pub role: ApiRole,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Control API configuration
+#[derive(Debug, Clone)]
+pub struct ControlApiConfig {
+    pub listen_addr: SocketAddr,
+    pub tokens: Vec<ApiToken>,
+}
+
+// ================================
+// Request and Response Types
+// ================================
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StartFlowRequest {
+    #[serde(default)]
+    pub context: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StartFlowResponse {
+    pub instance_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RetryStepRequest {
+    pub step: String,
+}
+
+/// Health check outcome for one tracked session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionHealthEntry {
+    pub session: Pubkey,
+    pub report: Option<SessionHealthReport>,
+    pub error: Option<String>,
+}
+
+/// Error returned to API clients as `{"error": ...}`
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+    retry_after: Option<std::time::Duration>,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+}
+
+impl From<RuntimeError> for ApiError {
+    fn from(err: RuntimeError) -> Self {
+        let status = match &err {
+            RuntimeError::CoordinationError(message) if message.contains("not found") => {
+                StatusCode::NOT_FOUND
+            }
+            RuntimeError::CoordinationError(_) | RuntimeError::InvalidConfiguration(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            RuntimeError::AuthenticationFailed => StatusCode::UNAUTHORIZED,
+            RuntimeError::SecurityViolation(_) | RuntimeError::BudgetExceeded { .. } => {
+                StatusCode::FORBIDDEN
+            }
+            RuntimeError::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self {
+            retry_after: err.retry_after(),
+            ..Self::new(status, err.to_string())
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response =
+            (self.status, Json(serde_json::json!({ "error": self.message }))).into_response();
+        if let Some(retry_after) = self.retry_after {
+            let seconds = retry_after.as_secs_f64().ceil() as u64;
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, seconds.max(1).into());
+        }
+        response
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// Authenticated caller of a request
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub role: ApiRole,
+    pub context: SecurityContext,
+}
+
+impl Caller {
+    pub fn identity(&self) -> &str {
+        self.context
+            .session
+            .as_ref()
+            .map(|session| session.identity.as_str())
+            .unwrap_or_default()
+    }
+
+    fn require(&self, role: ApiRole) -> ApiResult<()> {
+        if self.role >= role {
+            Ok(())
+        } else {
+            Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                format!("{} requires the {:?} role", self.identity(), role),
+            ))
+        }
+    }
+}
+
+// ================================
+// Control API
+// ================================
+
+/// REST control plane over the runtime's coordinator, sessions and metrics
+pub struct ControlApi {
+    coordinator: Arc<Coordinator>,
+    session_manager: Arc<SessionManager>,
+    metrics: Arc<MetricsExporter>,
+    session_health: SessionHealthConfig,
+    environment: Environment,
+    policies: SecurityPolicies,
+    tokens: HashMap<String, ApiToken>,
+    audit_logger: Option<Arc<AuditLogger>>,
+    shutdown_tx: broadcast::Sender<()>,
+    server_handle: RwLock<Option<JoinHandle<()>>>,
+}
+
+impl ControlApi {
+    pub fn new(
+        coordinator: Arc<Coordinator>,
+        session_manager: Arc<SessionManager>,
+        metrics: Arc<MetricsExporter>,
+        session_health: SessionHealthConfig,
+        tokens: Vec<ApiToken>,
+    ) -> Self {
+        let (shutdown_tx, _) = broadcast::channel(16);
+
+        Self {
+            coordinator,
+            session_manager,
+            metrics,
+            session_health,
+            environment: Environment::Production,
+            policies: SecurityPolicies::default(),
+            tokens: tokens
+                .into_iter()
+                .map(|token| (token.token.clone(), token))
+                .collect(),
+            audit_logger: None,
+            shutdown_tx,
+            server_handle: RwLock::new(None),
+        }
+    }
+
+    /// Environment and policies placed in each request's security context
+    pub fn with_security(mut self, environment: Environment, policies: SecurityPolicies) -> Self {
+        self.environment = environment;
+        self.policies = policies;
+        self
+    }
+
+    /// Record rejected authentication and operator actions
+    pub fn with_audit_logger(mut self, audit_logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// Routes served by the API
+    pub fn router(self: &Arc<Self>) -> Router {
+        let authenticated = Router::new()
+            .route("/flows", get(list_flows))
+            .route("/flows/:flow_id/start", post(start_flow))
+            .route("/executions", get(list_executions))
+            .route("/executions/:instance_id", get(get_execution))
+            .route("/executions/:instance_id/stop", post(stop_execution))
+            .route("/executions/:instance_id/retry", post(retry_step))
+            .route("/sessions/health", get(session_health))
+            .route("/metrics", get(metrics))
+            .route_layer(middleware::from_fn_with_state(self.clone(), authenticate));
+
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .merge(authenticated)
+            .with_state(self.clone())
+    }
+
+    /// Serve the API until stopped
+    pub async fn start(self: &Arc<Self>, listen_addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(listen_addr).await?;
+        info!("Serving control API on http://{}", listener.local_addr()?);
+
+        let router = self.router();
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let handle = tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = shutdown_rx.recv().await;
+            };
+            if let Err(e) = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                warn!("Control API server error: {}", e);
+            }
+        });
+
+        *self.server_handle.write().await = Some(handle);
+        Ok(())
+    }
+
+    /// Stop serving the API
+    pub async fn stop(&self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        if let Some(handle) = self.server_handle.write().await.take() {
+            let _ = handle.await;
+        }
+        Ok(())
+    }
+
+    /// Resolve a bearer token into a caller
+    fn caller(&self, token: &str) -> Option<Caller> {
+        let token = self.tokens.get(token)?;
+        let now = chrono::Utc::now();
+        if token.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return None;
+        }
+
+        Some(Caller {
+            role: token.role,
+            context: SecurityContext {
+                timestamp: now,
+                environment: self.environment,
+                policies: self.policies.clone(),
+                session: Some(SessionInfo {
+                    session_id: uuid::Uuid::new_v4().to_string(),
+                    identity: token.identity.clone(),
+                    started_at: now,
+                    expires_at: token.expires_at,
+                }),
+            },
+        })
+    }
+
+    async fn audit(&self, entry: AuditEntry) {
+        if let Some(audit_logger) = &self.audit_logger {
+            if let Err(e) = audit_logger.log(entry).await {
+                warn!("Failed to audit control API request: {}", e);
+            }
+        }
+    }
+
+    async fn audit_action(&self, caller: &Caller, action: &str, resource: &str) {
+        let mut entry = AuditEntry::builder(AuditEventType::ConfigurationChanged)
+            .actor(caller.identity().to_string())
+            .resource(resource.to_string())
+            .detail("action".to_string(), action);
+        if let Some(session) = &caller.context.session {
+            entry = entry.session_id(session.session_id.clone());
+        }
+        self.audit(entry.build()).await;
+    }
+}
+
+// ================================
+// Handlers
+// ================================
+
+async fn authenticate(
+    State(api): State<Arc<ControlApi>>,
+    mut request: Request,
+    next: Next,
+) -> ApiResult<Response> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token.and_then(|token| api.caller(token)) {
+        Some(caller) => {
+            request.extensions_mut().insert(caller);
+            Ok(next.run(request).await)
+        }
+        None => {
+            let entry = AuditEntry::builder(AuditEventType::AuthenticationAttempt)
+                .resource(request.uri().path().to_string())
+                .outcome(AuditOutcome::Denied)
+                .build();
+            api.audit(entry).await;
+            Err(ApiError::from(RuntimeError::AuthenticationFailed))
+        }
+    }
+}
+
+async fn list_flows(State(api): State<Arc<ControlApi>>) -> Json<Vec<ProtocolFlow>> {
+    Json(api.coordinator.list_flows().await)
+}
+
+async fn start_flow(
+    State(api): State<Arc<ControlApi>>,
+    Extension(caller): Extension<Caller>,
+    Path(flow_id): Path<String>,
+    request: Option<Json<StartFlowRequest>>,
+) -> ApiResult<Json<StartFlowResponse>> {
+    caller.require(ApiRole::Operator)?;
+    let context = request
+        .map(|Json(request)| request.context)
+        .unwrap_or_default();
+
+    let instance_id = api.coordinator.start_flow(flow_id.clone(), context).await?;
+    api.audit_action(&caller, "start_flow", &flow_id).await;
+    Ok(Json(StartFlowResponse { instance_id }))
+}
+
+async fn list_executions(State(api): State<Arc<ControlApi>>) -> Json<Vec<FlowExecution>> {
+    Json(api.coordinator.list_executions())
+}
+
+async fn get_execution(
+    State(api): State<Arc<ControlApi>>,
+    Path(instance_id): Path<String>,
+) -> ApiResult<Json<FlowExecution>> {
+    api.coordinator
+        .get_execution_status(&instance_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Execution not found: {}", instance_id),
+            )
+        })
+}
+
+async fn stop_execution(
+    State(api): State<Arc<ControlApi>>,
+    Extension(caller): Extension<Caller>,
+    Path(instance_id): Path<String>,
+) -> ApiResult<StatusCode> {
+    caller.require(ApiRole::Operator)?;
+    api.coordinator.cancel_execution(&instance_id).await?;
+    api.audit_action(&caller, "stop_execution", &instance_id)
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn retry_step(
+    State(api): State<Arc<ControlApi>>,
+    Extension(caller): Extension<Caller>,
+    Path(instance_id): Path<String>,
+    Json(request): Json<RetryStepRequest>,
+) -> ApiResult<StatusCode> {
+    caller.require(ApiRole::Operator)?;
+    api.coordinator
+        .retry_step(&instance_id, &request.step)
+        .await?;
+    api.audit_action(
+        &caller,
+        "retry_step",
+        &format!("{}/{}", instance_id, request.step),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn session_health(State(api): State<Arc<ControlApi>>) -> Json<Vec<SessionHealthEntry>> {
+    let mut entries = Vec::new();
+    for session in api.session_manager.tracked_sessions().await {
+        let result = api
+            .session_manager
+            .check_session_health(session, &api.session_health)
+            .await;
+        entries.push(SessionHealthEntry {
+            session,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            report: result.ok(),
+        });
+    }
+    Json(entries)
+}
+
+async fn metrics(State(api): State<Arc<ControlApi>>) -> ApiResult<Response> {
+    let body = api.metrics.render().await?;
+    Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coordination::{FlowStep, KernelInstructionType, RetryPolicy},
+        monitoring::event_stream::EventStream,
+        types::RuntimeMetrics,
+    };
+    use axum::body::{to_bytes, Body};
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    async fn api() -> Arc<ControlApi> {
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let coordinator = Arc::new(Coordinator::new(
+            rpc_client.clone(),
+            Arc::new(EventStream::new()),
+        ));
+        coordinator
+            .register_flow(ProtocolFlow {
+                id: "flow".to_string(),
+                name: "Flow".to_string(),
+                steps: vec![FlowStep {
+                    name: "init".to_string(),
+                    description: "Initialize shard".to_string(),
+                    instruction_type: KernelInstructionType::InitializeShard,
+                    on_success: None,
+                    on_failure: None,
+                    cluster: None,
+                }],
+                timeout: Duration::from_secs(60),
+                retry_policy: RetryPolicy::default(),
+            })
+            .await
+            .unwrap();
+
+        let token = |token: &str, role| ApiToken {
+            token: token.to_string(),
+            identity: format!("{}-user", token),
+            role,
+            expires_at: None,
+        };
+        Arc::new(ControlApi::new(
+            coordinator,
+            Arc::new(SessionManager::new(rpc_client)),
+            Arc::new(
+                MetricsExporter::new(Arc::new(RwLock::new(RuntimeMetrics::default()))).unwrap(),
+            ),
+            SessionHealthConfig::default(),
+            vec![
+                token("reader", ApiRole::ReadOnly),
+                token("operator", ApiRole::Operator),
+            ],
+        ))
+    }
+
+    async fn call(
+        api: &Arc<ControlApi>,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = api.router().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_authentication() {
+        let api = api().await;
+
+        assert_eq!(
+            call(&api, "GET", "/health", None, None).await.0,
+            StatusCode::OK
+        );
+        assert_eq!(
+            call(&api, "GET", "/flows", None, None).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            call(&api, "GET", "/flows", Some("unknown"), None).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let (status, flows) = call(&api, "GET", "/flows", Some("reader"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(flows[0]["id"], "flow");
+
+        assert_eq!(
+            call(&api, "POST", "/flows/flow/start", Some("reader"), None)
+                .await
+                .0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execution_control() {
+        let api = api().await;
+
+        let (status, started) =
+            call(&api, "POST", "/flows/flow/start", Some("operator"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let instance_id = started["instance_id"].as_str().unwrap().to_string();
+
+        let (_, executions) = call(&api, "GET", "/executions", Some("reader"), None).await;
+        assert_eq!(executions[0]["instance_id"], instance_id.as_str());
+
+        let retry = serde_json::json!({ "step": "missing" });
+        let uri = format!("/executions/{}/retry", instance_id);
+        assert_eq!(
+            call(&api, "POST", &uri, Some("operator"), Some(retry))
+                .await
+                .0,
+            StatusCode::NOT_FOUND
+        );
+
+        let uri = format!("/executions/{}/stop", instance_id);
+        assert_eq!(
+            call(&api, "POST", &uri, Some("operator"), None).await.0,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            call(&api, "POST", &uri, Some("operator"), None).await.0,
+            StatusCode::BAD_REQUEST
+        );
+
+        let (_, execution) = call(
+            &api,
+            "GET",
+            &format!("/executions/{}", instance_id),
+            Some("reader"),
+            None,
+        )
+        .await;
+        assert_eq!(execution["status"], "Cancelled");
+
+        let (status, _) = call(&api, "GET", "/executions/missing", Some("reader"), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_route() {
+        let api = api().await;
+        assert_eq!(
+            call(&api, "GET", "/metrics", None, None).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .header(header::AUTHORIZATION, "Bearer reader")
+            .body(Body::empty())
+            .unwrap();
+        let response = api.router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            prometheus::TEXT_FORMAT
+        );
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("valence_runtime_transactions_built_total 0"));
+    }
+}
\ No newline at end of file
@@ -1,17 +1,34 @@
 //! Protocol flow coordination and execution
 
-use crate::{monitoring::event_stream::EventStream, Result, RuntimeError};
+use crate::{
+    cluster::ClusterRegistry,
+    monitoring::event_stream::{Event, EventStream},
+    rate_limit::RateLimiter,
+    transaction::{cost::confirmed_cost, BundleOutcome, BundleSubmitter, TransactionBuilder},
+    Result, RuntimeError,
+};
+use async_trait::async_trait;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 use tokio::{
     sync::{broadcast, RwLock},
     task::JoinHandle,
-    time::interval,
+    time::{interval, Instant},
 };
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 // ================================
 // Protocol Flow Types
@@ -35,6 +52,9 @@ pub struct FlowStep {
     pub instruction_type: KernelInstructionType,
     pub on_success: Option<String>, // Next step ID
     pub on_failure: Option<String>, // Fallback step ID
+    /// Cluster the step runs on; the default cluster when unset
+    #[serde(default)]
+    pub cluster: Option<String>,
 }
 
 /// Kernel instruction types that align with actual valence-kernel instructions
@@ -100,7 +120,7 @@ impl Default for RetryPolicy {
 // ================================
 
 /// Flow execution state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlowExecution {
     pub flow_id: String,
     pub instance_id: String,
@@ -109,6 +129,9 @@ pub struct FlowExecution {
     pub context: HashMap<String, serde_json::Value>,
     pub started_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// SOL spent on the execution's transactions
+    #[serde(default)]
+    pub cost: FlowCost,
 }
 
 /// Execution status
@@ -119,6 +142,282 @@ pub enum ExecutionStatus {
     WaitingForSignature,
     Completed,
     Failed(String),
+    Cancelled,
+}
+
+impl ExecutionStatus {
+    /// Whether a step is currently being executed or awaiting signatures
+    pub fn is_in_flight(&self) -> bool {
+        matches!(self, Self::Running | Self::WaitingForSignature)
+    }
+
+    /// Whether the execution has reached a terminal state
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed(_) | Self::Cancelled)
+    }
+}
+
+// ================================
+// Cost Accounting
+// ================================
+
+/// Lamports spent by a flow execution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowCost {
+    pub fees: u64,
+    pub tips: u64,
+    pub rent: u64,
+}
+
+impl FlowCost {
+    pub fn total(&self) -> u64 {
+        self.fees.saturating_add(self.tips).saturating_add(self.rent)
+    }
+
+    /// Both costs added together
+    pub fn combine(self, other: FlowCost) -> FlowCost {
+        FlowCost {
+            fees: self.fees.saturating_add(other.fees),
+            tips: self.tips.saturating_add(other.tips),
+            rent: self.rent.saturating_add(other.rent),
+        }
+    }
+}
+
+/// What lamports were spent on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CostKind {
+    /// Transaction and priority fees
+    Fee,
+    /// Block engine tips
+    Tip,
+    /// Rent for accounts the flow created
+    Rent,
+}
+
+/// Spending limits in lamports; unset limits are unlimited
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Limit for each flow execution
+    pub per_flow: Option<u64>,
+    /// Limit across all executions per UTC day
+    pub per_day: Option<u64>,
+}
+
+/// Budget a spending limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetScope {
+    Flow,
+    Day,
+}
+
+impl std::fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Flow => write!(f, "flow"),
+            Self::Day => write!(f, "daily"),
+        }
+    }
+}
+
+/// Lamports spent across executions on one UTC day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailySpend {
+    pub day: chrono::NaiveDate,
+    pub lamports: u64,
+}
+
+impl DailySpend {
+    fn today() -> Self {
+        Self {
+            day: chrono::Utc::now().date_naive(),
+            lamports: 0,
+        }
+    }
+
+    /// Spend so far today, resetting at UTC midnight
+    fn current(&mut self) -> &mut u64 {
+        let today = chrono::Utc::now().date_naive();
+        if self.day != today {
+            self.day = today;
+            self.lamports = 0;
+        }
+        &mut self.lamports
+    }
+}
+
+/// Spending limits and the lamports spent today
+///
+/// Shared by the coordinator and the transaction builders it hands out, so
+/// every builder refuses to build once the daily budget is exhausted.
+#[derive(Debug)]
+pub struct SpendLedger {
+    budget: BudgetConfig,
+    daily_spend: Mutex<DailySpend>,
+}
+
+impl SpendLedger {
+    pub fn new(budget: BudgetConfig) -> Self {
+        Self {
+            budget,
+            daily_spend: Mutex::new(DailySpend::today()),
+        }
+    }
+
+    /// Configured spending limits
+    pub fn budget(&self) -> &BudgetConfig {
+        &self.budget
+    }
+
+    /// Lamports spent across all executions today (UTC)
+    pub fn daily_spend(&self) -> u64 {
+        *self.daily_spend.lock().unwrap().current()
+    }
+
+    /// Today's spend, as persisted with the execution store
+    pub fn snapshot(&self) -> DailySpend {
+        let mut daily_spend = self.daily_spend.lock().unwrap();
+        daily_spend.current();
+        *daily_spend
+    }
+
+    /// Resume a persisted spend, unless it belongs to an earlier day
+    pub fn restore(&self, spend: DailySpend) {
+        let mut daily_spend = self.daily_spend.lock().unwrap();
+        let today = daily_spend.current();
+        if spend.day == chrono::Utc::now().date_naive() {
+            *today = (*today).max(spend.lamports);
+        }
+    }
+
+    /// Charge lamports to today's spend
+    pub fn record(&self, lamports: u64) {
+        let mut daily_spend = self.daily_spend.lock().unwrap();
+        let today = daily_spend.current();
+        *today = today.saturating_add(lamports);
+    }
+
+    /// The first exhausted budget, given what one flow execution has spent
+    pub fn exhausted(&self, flow_spent: Option<u64>) -> Option<(BudgetScope, u64, u64)> {
+        let flow = flow_spent.map(|spent| (BudgetScope::Flow, spent, self.budget.per_flow));
+        flow.into_iter()
+            .chain([(BudgetScope::Day, self.daily_spend(), self.budget.per_day)])
+            .find_map(|(scope, spent, limit)| {
+                limit.filter(|limit| spent >= *limit).map(|limit| (scope, spent, limit))
+            })
+    }
+
+    /// Refuse further transactions once the daily budget is exhausted
+    pub fn check(&self) -> Result<()> {
+        match self.exhausted(None) {
+            Some((scope, spent, limit)) => Err(RuntimeError::BudgetExceeded { scope, spent, limit }),
+            None => Ok(()),
+        }
+    }
+}
+
+// ================================
+// Execution Storage
+// ================================
+
+/// Storage for unfinished executions and the day's spend across restarts
+#[async_trait]
+pub trait ExecutionStore: Send + Sync {
+    async fn load(&self) -> Result<Vec<FlowExecution>>;
+    async fn save(&self, executions: &[FlowExecution]) -> Result<()>;
+    async fn load_daily_spend(&self) -> Result<Option<DailySpend>>;
+    async fn save_daily_spend(&self, spend: DailySpend) -> Result<()>;
+}
+
+/// In-memory execution storage
+#[derive(Default)]
+pub struct MemoryExecutionStore {
+    executions: RwLock<Vec<FlowExecution>>,
+    daily_spend: RwLock<Option<DailySpend>>,
+}
+
+impl MemoryExecutionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ExecutionStore for MemoryExecutionStore {
+    async fn load(&self) -> Result<Vec<FlowExecution>> {
+        Ok(self.executions.read().await.clone())
+    }
+
+    async fn save(&self, executions: &[FlowExecution]) -> Result<()> {
+        *self.executions.write().await = executions.to_vec();
+        Ok(())
+    }
+
+    async fn load_daily_spend(&self) -> Result<Option<DailySpend>> {
+        Ok(*self.daily_spend.read().await)
+    }
+
+    async fn save_daily_spend(&self, spend: DailySpend) -> Result<()> {
+        *self.daily_spend.write().await = Some(spend);
+        Ok(())
+    }
+}
+
+/// File-based execution storage
+///
+/// The day's spend is kept next to the executions, in a `.spend.json` file.
+pub struct FileExecutionStore {
+    path: PathBuf,
+}
+
+impl FileExecutionStore {
+    pub async fn new(path: PathBuf) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        Ok(Self { path })
+    }
+}
+
+#[async_trait]
+impl ExecutionStore for FileExecutionStore {
+    async fn load(&self) -> Result<Vec<FlowExecution>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, executions: &[FlowExecution]) -> Result<()> {
+        write_atomically(&self.path, &serde_json::to_vec(executions)?).await
+    }
+
+    async fn load_daily_spend(&self) -> Result<Option<DailySpend>> {
+        match tokio::fs::read(self.spend_path()).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save_daily_spend(&self, spend: DailySpend) -> Result<()> {
+        write_atomically(&self.spend_path(), &serde_json::to_vec(&spend)?).await
+    }
+}
+
+impl FileExecutionStore {
+    fn spend_path(&self) -> PathBuf {
+        self.path.with_extension("spend.json")
+    }
+}
+
+/// Write through a temporary file so a crash never leaves a torn file
+async fn write_atomically(path: &std::path::Path, bytes: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, bytes).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(())
 }
 
 // ================================
@@ -127,11 +426,16 @@ pub enum ExecutionStatus {
 
 /// Simplified coordinator for managing protocol flows
 pub struct Coordinator {
-    #[allow(dead_code)]
     rpc_client: Arc<RpcClient>,
     event_stream: Arc<EventStream>,
     flows: Arc<RwLock<HashMap<String, ProtocolFlow>>>,
     executions: Arc<DashMap<String, FlowExecution>>,
+    clusters: Option<Arc<ClusterRegistry>>,
+    execution_store: Option<Arc<dyn ExecutionStore>>,
+    rate_limiter: Arc<RateLimiter>,
+    spend: Arc<SpendLedger>,
+    /// Cleared while draining so no new executions start
+    accepting: AtomicBool,
     shutdown_tx: broadcast::Sender<()>,
     worker_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
@@ -146,14 +450,49 @@ impl Coordinator {
             event_stream,
             flows: Arc::new(RwLock::new(HashMap::new())),
             executions: Arc::new(DashMap::new()),
+            clusters: None,
+            execution_store: None,
+            rate_limiter: Arc::new(RateLimiter::unlimited()),
+            spend: Arc::new(SpendLedger::new(BudgetConfig::default())),
+            accepting: AtomicBool::new(true),
             shutdown_tx,
             worker_handle: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Route flow steps to the named clusters of a registry
+    pub fn with_clusters(mut self, clusters: Arc<ClusterRegistry>) -> Self {
+        self.clusters = Some(clusters);
+        self
+    }
+
+    /// Persist unfinished executions to a store across restarts
+    pub fn with_execution_store(mut self, store: Arc<dyn ExecutionStore>) -> Self {
+        self.execution_store = Some(store);
+        self
+    }
+
+    /// Limit the rate of transactions built for flow steps
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Limit the lamports flow executions may spend
+    pub fn with_budget(mut self, budget: BudgetConfig) -> Self {
+        self.spend = Arc::new(SpendLedger::new(budget));
+        self
+    }
+
+    /// Spending limits and today's spend, shared with transaction builders
+    pub fn spend_ledger(&self) -> &Arc<SpendLedger> {
+        &self.spend
+    }
+
     /// Start the orchestrator
     pub async fn start(&self) -> Result<()> {
         info!("Starting orchestrator");
+        self.accepting.store(true, Ordering::SeqCst);
 
         let executions = self.executions.clone();
         let mut shutdown_rx = self.shutdown_tx.subscribe();
@@ -202,6 +541,18 @@ impl Coordinator {
     /// Register a protocol flow
     pub async fn register_flow(&self, flow: ProtocolFlow) -> Result<()> {
         info!("Registering flow: {}", flow.name);
+
+        for step in &flow.steps {
+            if let Some(cluster) = &step.cluster {
+                if !self.clusters.as_ref().is_some_and(|clusters| clusters.contains(cluster)) {
+                    return Err(RuntimeError::InvalidConfiguration(format!(
+                        "Step {} of flow {} targets unknown cluster {}",
+                        step.name, flow.id, cluster
+                    )));
+                }
+            }
+        }
+
         self.flows.write().await.insert(flow.id.clone(), flow);
         Ok(())
     }
@@ -212,6 +563,13 @@ impl Coordinator {
         flow_id: String,
         context: HashMap<String, serde_json::Value>,
     ) -> Result<String> {
+        if !self.accepting.load(Ordering::SeqCst) {
+            return Err(RuntimeError::CoordinationError(format!(
+                "Coordinator is draining; not starting flow {}",
+                flow_id
+            )));
+        }
+
         let flows = self.flows.read().await;
         let flow = flows.get(&flow_id).ok_or_else(|| {
             RuntimeError::CoordinationError(format!("Flow not found: {}", flow_id))
@@ -227,13 +585,14 @@ impl Coordinator {
             context,
             started_at: chrono::Utc::now(),
             completed_at: None,
+            cost: FlowCost::default(),
         };
 
         self.executions.insert(instance_id.clone(), execution);
 
         // Emit event
         self.event_stream
-            .emit(crate::monitoring::event_stream::Event::FlowStarted {
+            .emit(Event::FlowStarted {
                 flow_id,
                 instance_id: instance_id.clone(),
             })
@@ -265,6 +624,154 @@ impl Coordinator {
         self.build_kernel_instruction(&step.instruction_type, context).await
     }
 
+    /// Transaction builder for the cluster a flow step targets, rate limited as the flow
+    ///
+    /// The builder refuses to build once the daily budget is exhausted.
+    pub async fn step_transaction_builder(
+        &self,
+        flow_id: &str,
+        step_name: &str,
+    ) -> Result<TransactionBuilder> {
+        let flows = self.flows.read().await;
+        let step = flows
+            .get(flow_id)
+            .and_then(|flow| flow.steps.iter().find(|s| s.name == step_name))
+            .ok_or_else(|| {
+                RuntimeError::CoordinationError(format!("Step not found: {}/{}", flow_id, step_name))
+            })?;
+
+        let builder = match &self.clusters {
+            Some(clusters) => clusters.transaction_builder(step.cluster.as_deref())?,
+            None => TransactionBuilder::new(self.rpc_client.clone()),
+        };
+        Ok(builder
+            .with_rate_limiter(self.rate_limiter.clone())
+            .with_spend_ledger(self.spend.clone())
+            .for_flow(flow_id))
+    }
+
+    /// Transaction builder for an execution's current step, refused once a budget is exhausted
+    pub async fn execution_transaction_builder(
+        &self,
+        instance_id: &str,
+    ) -> Result<TransactionBuilder> {
+        let (flow_id, step_name) = self.check_budget(instance_id).await?;
+        self.step_transaction_builder(&flow_id, &step_name).await
+    }
+
+    /// Charge lamports spent by an execution's transactions to its budgets
+    pub fn record_cost(&self, instance_id: &str, kind: CostKind, lamports: u64) -> Result<FlowCost> {
+        let mut execution = self.executions.get_mut(instance_id).ok_or_else(|| {
+            RuntimeError::CoordinationError(format!("Execution not found: {}", instance_id))
+        })?;
+        let spent = match kind {
+            CostKind::Fee => &mut execution.cost.fees,
+            CostKind::Tip => &mut execution.cost.tips,
+            CostKind::Rent => &mut execution.cost.rent,
+        };
+        *spent = spent.saturating_add(lamports);
+
+        self.spend.record(lamports);
+        Ok(execution.cost)
+    }
+
+    /// Charge everything a confirmed transaction or bundle spent to an execution
+    pub fn record_flow_cost(&self, instance_id: &str, cost: FlowCost) -> Result<FlowCost> {
+        self.record_cost(instance_id, CostKind::Fee, cost.fees)?;
+        self.record_cost(instance_id, CostKind::Tip, cost.tips)?;
+        self.record_cost(instance_id, CostKind::Rent, cost.rent)
+    }
+
+    /// Send and confirm a signed transaction for an execution over RPC
+    ///
+    /// The transaction goes to the cluster of the execution's current step,
+    /// and its confirmed fee and rent are charged to the execution.
+    pub async fn submit_transaction(
+        &self,
+        instance_id: &str,
+        transaction: &Transaction,
+    ) -> Result<Signature> {
+        let (flow_id, step_name) = self.check_budget(instance_id).await?;
+        let rpc_client = self.step_rpc_client(&flow_id, &step_name).await?;
+
+        let signature = rpc_client.send_and_confirm_transaction(transaction).await?;
+        self.event_stream
+            .emit(Event::TransactionSubmitted {
+                signature: signature.to_string(),
+                description: format!("{}/{}", flow_id, step_name),
+            })
+            .await;
+
+        let cost = confirmed_cost(&rpc_client, transaction, &[]).await;
+        self.record_flow_cost(instance_id, cost)?;
+        Ok(signature)
+    }
+
+    /// Submit a signed bundle for an execution, charging its fees, tip and rent
+    pub async fn submit_bundle(
+        &self,
+        instance_id: &str,
+        submitter: &BundleSubmitter,
+        transactions: &[Transaction],
+    ) -> Result<BundleOutcome> {
+        let (flow_id, step_name) = self.check_budget(instance_id).await?;
+        let outcome = submitter
+            .submit(transactions, &format!("{}/{}", flow_id, step_name))
+            .await?;
+        self.record_flow_cost(instance_id, outcome.cost)?;
+        Ok(outcome)
+    }
+
+    /// Lamports spent across all executions today (UTC)
+    pub fn daily_spend(&self) -> u64 {
+        self.spend.daily_spend()
+    }
+
+    /// RPC client of the cluster a flow step targets
+    async fn step_rpc_client(&self, flow_id: &str, step_name: &str) -> Result<Arc<RpcClient>> {
+        let flows = self.flows.read().await;
+        let step = flows
+            .get(flow_id)
+            .and_then(|flow| flow.steps.iter().find(|s| s.name == step_name))
+            .ok_or_else(|| {
+                RuntimeError::CoordinationError(format!("Step not found: {}/{}", flow_id, step_name))
+            })?;
+
+        match &self.clusters {
+            Some(clusters) => Ok(clusters.resolve(step.cluster.as_deref())?.rpc_client.clone()),
+            None => Ok(self.rpc_client.clone()),
+        }
+    }
+
+    /// Check an execution's budgets, returning its flow and current step
+    async fn check_budget(&self, instance_id: &str) -> Result<(String, String)> {
+        let (flow_id, step_name, spent) = self
+            .executions
+            .get(instance_id)
+            .map(|e| (e.flow_id.clone(), e.current_step.clone(), e.cost.total()))
+            .ok_or_else(|| {
+                RuntimeError::CoordinationError(format!("Execution not found: {}", instance_id))
+            })?;
+
+        if let Some((scope, spent, limit)) = self.spend.exhausted(Some(spent)) {
+            warn!(
+                "Refusing to build transactions for execution {}: {} budget exhausted ({} of {} lamports)",
+                instance_id, scope, spent, limit
+            );
+            self.event_stream
+                .emit(Event::BudgetExceeded {
+                    flow_id,
+                    instance_id: instance_id.to_string(),
+                    scope,
+                    spent,
+                    limit,
+                })
+                .await;
+            return Err(RuntimeError::BudgetExceeded { scope, spent, limit });
+        }
+        Ok((flow_id, step_name))
+    }
+
     /// Build actual kernel instruction
     async fn build_kernel_instruction(
         &self,
@@ -320,10 +827,181 @@ impl Coordinator {
         }
     }
 
+    /// Get a registered flow definition
+    pub async fn flow(&self, flow_id: &str) -> Option<ProtocolFlow> {
+        self.flows.read().await.get(flow_id).cloned()
+    }
+
+    /// List registered flow definitions
+    pub async fn list_flows(&self) -> Vec<ProtocolFlow> {
+        self.flows.read().await.values().cloned().collect()
+    }
+
+    /// List flow executions, most recently started first
+    pub fn list_executions(&self) -> Vec<FlowExecution> {
+        let mut executions: Vec<FlowExecution> =
+            self.executions.iter().map(|e| e.value().clone()).collect();
+        executions.sort_by_key(|e| std::cmp::Reverse(e.started_at));
+        executions
+    }
+
+    /// Cancel an unfinished execution
+    pub async fn cancel_execution(&self, instance_id: &str) -> Result<()> {
+        let (flow_id, duration_ms) = {
+            let mut execution = self.executions.get_mut(instance_id).ok_or_else(|| {
+                RuntimeError::CoordinationError(format!("Execution not found: {}", instance_id))
+            })?;
+            if matches!(
+                execution.status,
+                ExecutionStatus::Completed | ExecutionStatus::Cancelled
+            ) {
+                return Err(RuntimeError::CoordinationError(format!(
+                    "Execution {} already finished",
+                    instance_id
+                )));
+            }
+
+            let now = chrono::Utc::now();
+            execution.status = ExecutionStatus::Cancelled;
+            execution.completed_at = Some(now);
+            let duration = now.signed_duration_since(execution.started_at);
+            (execution.flow_id.clone(), duration.num_milliseconds().max(0) as u64)
+        };
+
+        self.event_stream
+            .emit(Event::FlowCompleted {
+                flow_id,
+                instance_id: instance_id.to_string(),
+                success: false,
+                duration_ms,
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Re-run an execution from the given step
+    pub async fn retry_step(&self, instance_id: &str, step_name: &str) -> Result<()> {
+        let flow_id = self
+            .executions
+            .get(instance_id)
+            .map(|execution| execution.flow_id.clone())
+            .ok_or_else(|| {
+                RuntimeError::CoordinationError(format!("Execution not found: {}", instance_id))
+            })?;
+        let known = self
+            .flow(&flow_id)
+            .await
+            .is_some_and(|flow| flow.steps.iter().any(|step| step.name == step_name));
+        if !known {
+            return Err(RuntimeError::CoordinationError(format!(
+                "Step not found: {}",
+                step_name
+            )));
+        }
+
+        if let Some(mut execution) = self.executions.get_mut(instance_id) {
+            info!("Retrying execution {} from step {}", instance_id, step_name);
+            execution.current_step = step_name.to_string();
+            execution.status = ExecutionStatus::Pending;
+            execution.completed_at = None;
+        }
+        Ok(())
+    }
+
     /// Get execution status
     pub async fn get_execution_status(&self, instance_id: &str) -> Option<FlowExecution> {
         self.executions.get(instance_id).map(|e| e.clone())
     }
+
+    /// Whether new flow executions are being accepted
+    pub fn is_accepting(&self) -> bool {
+        self.accepting.load(Ordering::SeqCst)
+    }
+
+    /// Executions currently running a step or awaiting signatures
+    pub fn in_flight_executions(&self) -> Vec<FlowExecution> {
+        self.executions
+            .iter()
+            .filter(|e| e.status.is_in_flight())
+            .map(|e| e.value().clone())
+            .collect()
+    }
+
+    /// Stop accepting new executions and wait for in-flight ones to reach a step boundary
+    ///
+    /// Returns the executions still in flight when `timeout` elapsed.
+    pub async fn drain(&self, timeout: Duration) -> Vec<FlowExecution> {
+        self.accepting.store(false, Ordering::SeqCst);
+
+        let deadline = Instant::now() + timeout;
+        let mut interval = interval(Duration::from_millis(100));
+        loop {
+            let in_flight = self.in_flight_executions();
+            if in_flight.is_empty() {
+                info!("All flow executions drained");
+                return in_flight;
+            }
+            if Instant::now() >= deadline {
+                warn!(
+                    "{} flow executions still in flight after {:?}",
+                    in_flight.len(),
+                    timeout
+                );
+                return in_flight;
+            }
+            interval.tick().await;
+        }
+    }
+
+    /// Save unfinished executions to the execution store
+    ///
+    /// Executions interrupted mid-step are saved as pending so they resume
+    /// from the start of their current step.
+    pub async fn persist_executions(&self) -> Result<usize> {
+        let Some(store) = &self.execution_store else {
+            return Ok(0);
+        };
+
+        let unfinished: Vec<FlowExecution> = self
+            .executions
+            .iter()
+            .filter(|e| !e.status.is_finished())
+            .map(|e| {
+                let mut execution = e.value().clone();
+                if execution.status.is_in_flight() {
+                    execution.status = ExecutionStatus::Pending;
+                }
+                execution
+            })
+            .collect();
+
+        store.save(&unfinished).await?;
+        store.save_daily_spend(self.spend.snapshot()).await?;
+        info!("Persisted {} unfinished flow executions", unfinished.len());
+        Ok(unfinished.len())
+    }
+
+    /// Load executions persisted by a previous shutdown
+    pub async fn restore_executions(&self) -> Result<usize> {
+        let Some(store) = &self.execution_store else {
+            return Ok(0);
+        };
+
+        // Spend from earlier today still counts against the daily budget
+        if let Some(spend) = store.load_daily_spend().await? {
+            self.spend.restore(spend);
+        }
+
+        let executions = store.load().await?;
+        let restored = executions.len();
+        for execution in executions {
+            self.executions.insert(execution.instance_id.clone(), execution);
+        }
+        if restored > 0 {
+            info!("Restored {} unfinished flow executions", restored);
+        }
+        Ok(restored)
+    }
 }
 
 /// Get the kernel program ID (placeholder)
@@ -366,6 +1044,7 @@ mod tests {
                 instruction_type: KernelInstructionType::InitializeShard,
                 on_success: None,
                 on_failure: None,
+                cluster: None,
             }],
             timeout: Duration::from_secs(60),
             retry_policy: RetryPolicy::default(),
@@ -373,4 +1052,182 @@ mod tests {
 
         assert!(coordinator.register_flow(flow).await.is_ok());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_budget_enforcement() {
+        let event_stream = Arc::new(EventStream::new());
+        let mut events = event_stream.subscribe().await;
+        let coordinator = Coordinator::new(
+            Arc::new(RpcClient::new_mock("succeeds".to_string())),
+            event_stream,
+        )
+        .with_budget(BudgetConfig {
+            per_flow: Some(10_000),
+            per_day: Some(15_000),
+        });
+        coordinator
+            .register_flow(ProtocolFlow {
+                id: "test-flow".to_string(),
+                name: "Test Flow".to_string(),
+                steps: vec![FlowStep {
+                    name: "init_shard".to_string(),
+                    description: "Initialize shard".to_string(),
+                    instruction_type: KernelInstructionType::InitializeShard,
+                    on_success: None,
+                    on_failure: None,
+                    cluster: None,
+                }],
+                timeout: Duration::from_secs(60),
+                retry_policy: RetryPolicy::default(),
+            })
+            .await
+            .unwrap();
+
+        let first = coordinator
+            .start_flow("test-flow".to_string(), HashMap::new())
+            .await
+            .unwrap();
+        coordinator.record_cost(&first, CostKind::Fee, 5_000).unwrap();
+        assert!(coordinator.execution_transaction_builder(&first).await.is_ok());
+
+        let cost = coordinator.record_cost(&first, CostKind::Tip, 5_000).unwrap();
+        assert_eq!(cost.total(), 10_000);
+        assert!(matches!(
+            coordinator.execution_transaction_builder(&first).await,
+            Err(RuntimeError::BudgetExceeded {
+                scope: BudgetScope::Flow,
+                spent: 10_000,
+                limit: 10_000
+            })
+        ));
+
+        // The daily budget spans executions
+        let second = coordinator
+            .start_flow("test-flow".to_string(), HashMap::new())
+            .await
+            .unwrap();
+        assert!(coordinator.execution_transaction_builder(&second).await.is_ok());
+        coordinator.record_cost(&second, CostKind::Rent, 5_000).unwrap();
+        assert_eq!(coordinator.daily_spend(), 15_000);
+        assert!(matches!(
+            coordinator.execution_transaction_builder(&second).await,
+            Err(RuntimeError::BudgetExceeded {
+                scope: BudgetScope::Day,
+                ..
+            })
+        ));
+
+        // Builders handed out without an execution still honor the daily budget
+        let builder = coordinator
+            .step_transaction_builder("test-flow", "init_shard")
+            .await
+            .unwrap()
+            .add_instruction(crate::transaction::instructions::transfer_instruction(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                1,
+            ));
+        assert!(matches!(
+            builder.build("transfer".to_string()).await,
+            Err(RuntimeError::BudgetExceeded {
+                scope: BudgetScope::Day,
+                ..
+            })
+        ));
+
+        let mut exceeded = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let Event::BudgetExceeded { instance_id, scope, .. } = event {
+                exceeded.push((instance_id, scope));
+            }
+        }
+        assert_eq!(exceeded, vec![(first, BudgetScope::Flow), (second, BudgetScope::Day)]);
+    }
+
+    #[tokio::test]
+    async fn test_step_cluster_routing() {
+        use crate::core::{ClusterConfig, DEFAULT_CLUSTER};
+
+        let mut default = ClusterConfig::mainnet();
+        default.name = DEFAULT_CLUSTER.to_string();
+        let clusters = Arc::new(
+            ClusterRegistry::new(vec![default, ClusterConfig::devnet()], Default::default())
+                .unwrap(),
+        );
+        let rpc_client = clusters.default_cluster().rpc_client.clone();
+        let coordinator = Coordinator::new(rpc_client, Arc::new(EventStream::new()))
+            .with_clusters(clusters);
+
+        let step = |cluster: &str| FlowStep {
+            name: "init_shard".to_string(),
+            description: "Initialize shard".to_string(),
+            instruction_type: KernelInstructionType::InitializeShard,
+            on_success: None,
+            on_failure: None,
+            cluster: Some(cluster.to_string()),
+        };
+        let flow = |cluster: &str| ProtocolFlow {
+            id: format!("{}-flow", cluster),
+            name: "Test Flow".to_string(),
+            steps: vec![step(cluster)],
+            timeout: Duration::from_secs(60),
+            retry_policy: RetryPolicy::default(),
+        };
+
+        assert!(coordinator.register_flow(flow("testnet")).await.is_err());
+        coordinator.register_flow(flow("devnet")).await.unwrap();
+        assert!(coordinator
+            .step_transaction_builder("devnet-flow", "init_shard")
+            .await
+            .is_ok());
+        assert!(coordinator
+            .step_transaction_builder("devnet-flow", "missing")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drain_and_persist() {
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let store = Arc::new(MemoryExecutionStore::new());
+        let coordinator = Coordinator::new(rpc_client.clone(), Arc::new(EventStream::new()))
+            .with_execution_store(store.clone());
+        coordinator
+            .register_flow(ProtocolFlow {
+                id: "flow".to_string(),
+                name: "Flow".to_string(),
+                steps: vec![FlowStep {
+                    name: "init_shard".to_string(),
+                    description: "Initialize shard".to_string(),
+                    instruction_type: KernelInstructionType::InitializeShard,
+                    on_success: None,
+                    on_failure: None,
+                    cluster: None,
+                }],
+                timeout: Duration::from_secs(60),
+                retry_policy: RetryPolicy::default(),
+            })
+            .await
+            .unwrap();
+
+        let running = coordinator.start_flow("flow".to_string(), HashMap::new()).await.unwrap();
+        let finished = coordinator.start_flow("flow".to_string(), HashMap::new()).await.unwrap();
+        coordinator.executions.get_mut(&running).unwrap().status = ExecutionStatus::Running;
+        coordinator.record_cost(&running, CostKind::Fee, 5_000).unwrap();
+        coordinator.cancel_execution(&finished).await.unwrap();
+
+        let interrupted = coordinator.drain(Duration::from_millis(50)).await;
+        assert_eq!(interrupted.len(), 1);
+        assert!(coordinator.start_flow("flow".to_string(), HashMap::new()).await.is_err());
+
+        assert_eq!(coordinator.persist_executions().await.unwrap(), 1);
+        let restarted = Coordinator::new(rpc_client, Arc::new(EventStream::new()))
+            .with_execution_store(store);
+        assert_eq!(restarted.restore_executions().await.unwrap(), 1);
+        let restored = restarted.get_execution_status(&running).await.unwrap();
+        assert!(matches!(restored.status, ExecutionStatus::Pending));
+        assert_eq!(restored.cost.fees, 5_000);
+        assert_eq!(restarted.daily_spend(), 5_000);
+        assert!(restarted.get_execution_status(&finished).await.is_none());
+    }
+}
@@ -1,18 +1,103 @@
 //! Core runtime types: configuration and error handling
 
+use crate::control::ControlApiConfig;
+use crate::coordination::{BudgetConfig, BudgetScope};
+use crate::monitoring::MonitorSource;
+use crate::rate_limit::RateLimitConfig;
+use crate::rpc_pool::RpcPoolConfig;
+use crate::session::SessionHealthConfig;
 use solana_sdk::commitment_config::CommitmentConfig;
+use std::{path::PathBuf, time::Duration};
 use thiserror::Error;
 
 // ================================
 // Configuration Types
 // ================================
 
+/// Name of the cluster described by the top-level `RuntimeConfig` endpoints
+pub const DEFAULT_CLUSTER: &str = "default";
+
+/// Named Solana cluster endpoints
+#[derive(Debug, Clone)]
+pub struct ClusterConfig {
+    pub name: String,
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    /// RPC endpoints used when `rpc_url` is unhealthy
+    pub fallback_rpc_urls: Vec<String>,
+}
+
+impl ClusterConfig {
+    /// Custom cluster with confirmed commitment
+    pub fn custom(
+        name: impl Into<String>,
+        rpc_url: impl Into<String>,
+        ws_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            rpc_url: rpc_url.into(),
+            ws_url: ws_url.into(),
+            commitment: CommitmentConfig::confirmed(),
+            fallback_rpc_urls: Vec::new(),
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self::custom(
+            "mainnet",
+            "https://api.mainnet-beta.solana.com",
+            "wss://api.mainnet-beta.solana.com",
+        )
+    }
+
+    pub fn devnet() -> Self {
+        Self::custom(
+            "devnet",
+            "https://api.devnet.solana.com",
+            "wss://api.devnet.solana.com",
+        )
+    }
+
+    pub fn localnet() -> Self {
+        Self::custom(
+            "localnet",
+            "http://127.0.0.1:8899",
+            "ws://127.0.0.1:8900",
+        )
+    }
+
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_fallback(mut self, rpc_url: impl Into<String>) -> Self {
+        self.fallback_rpc_urls.push(rpc_url.into());
+        self
+    }
+
+    /// Primary and fallback RPC endpoints in priority order
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect()
+    }
+}
+
 /// Runtime configuration
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     /// RPC endpoint URL
     pub rpc_url: String,
 
+    /// RPC endpoints used when `rpc_url` is unhealthy
+    pub fallback_rpc_urls: Vec<String>,
+
+    /// Health probing and rotation across RPC endpoints
+    pub rpc_pool: RpcPoolConfig,
+
     /// WebSocket endpoint URL
     pub ws_url: String,
 
@@ -24,16 +109,73 @@ pub struct RuntimeConfig {
 
     /// Enable transaction simulation before submission
     pub enable_simulation: bool,
+
+    /// Source of on-chain state updates for the state monitor
+    pub monitor_source: MonitorSource,
+
+    /// Checkpoint file for backfilling missed events; disabled when unset
+    pub checkpoint_path: Option<PathBuf>,
+
+    /// File unfinished flow executions are persisted to on shutdown; disabled when unset
+    pub execution_state_path: Option<PathBuf>,
+
+    /// Time allowed for in-flight flow executions to finish their step on shutdown
+    pub drain_timeout: Duration,
+
+    /// Transaction rate limits per flow, per program and globally
+    pub rate_limits: RateLimitConfig,
+
+    /// Lamports flow executions may spend, per execution and per day
+    pub budgets: BudgetConfig,
+
+    /// Health checks of tracked kernel sessions
+    pub session_health: SessionHealthConfig,
+
+    /// Additional clusters flow steps can target by name
+    pub clusters: Vec<ClusterConfig>,
+
+    /// Token-authenticated REST control API; disabled when unset
+    pub control_api: Option<ControlApiConfig>,
+
+    /// Declarative transaction policy file (YAML or JSON) enforced by the
+    /// transaction validator and reloaded when it changes; disabled when unset
+    pub policy_path: Option<PathBuf>,
+}
+
+impl RuntimeConfig {
+    /// All configured clusters, starting with the default cluster
+    pub fn cluster_configs(&self) -> Vec<ClusterConfig> {
+        let default = ClusterConfig {
+            name: DEFAULT_CLUSTER.to_string(),
+            rpc_url: self.rpc_url.clone(),
+            ws_url: self.ws_url.clone(),
+            commitment: self.commitment,
+            fallback_rpc_urls: self.fallback_rpc_urls.clone(),
+        };
+        std::iter::once(default).chain(self.clusters.iter().cloned()).collect()
+    }
 }
 
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            rpc_pool: RpcPoolConfig::default(),
             ws_url: "wss://api.mainnet-beta.solana.com".to_string(),
             commitment: CommitmentConfig::confirmed(),
             max_retries: 3,
             enable_simulation: true,
+            monitor_source: MonitorSource::default(),
+            checkpoint_path: None,
+            execution_state_path: None,
+            drain_timeout: Duration::from_secs(30),
+            rate_limits: RateLimitConfig::default(),
+            budgets: BudgetConfig::default(),
+            session_health: SessionHealthConfig::default(),
+            clusters: Vec::new(),
+            control_api: None,
+            policy_path: None,
         }
     }
 }
@@ -65,8 +207,8 @@ pub enum RuntimeError {
     #[error("Authentication failed")]
     AuthenticationFailed,
 
-    #[error("Rate limit exceeded")]
-    RateLimitExceeded,
+    #[error("Rate limit exceeded; retry after {retry_after:?}")]
+    RateLimitExceeded { retry_after: Duration },
 
     #[error("Invalid configuration: {0}")]
     InvalidConfiguration(String),
@@ -82,6 +224,32 @@ pub enum RuntimeError {
 
     #[error("Timeout occurred")]
     Timeout,
+
+    #[error("Storage error: {0}")]
+    StorageError(String),
+
+    #[error("Transaction decoding failed: {0}")]
+    DecodeError(String),
+
+    #[error("Bundle rejected: {0}")]
+    BundleRejected(String),
+
+    #[error("{scope} budget exhausted: spent {spent} of {limit} lamports")]
+    BudgetExceeded {
+        scope: BudgetScope,
+        spent: u64,
+        limit: u64,
+    },
+}
+
+impl RuntimeError {
+    /// Suggested wait before retrying, for rate-limited operations
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimitExceeded { retry_after } => Some(*retry_after),
+            _ => None,
+        }
+    }
 }
 
 impl From<solana_client::client_error::ClientError> for RuntimeError {
@@ -102,4 +270,10 @@ impl From<std::io::Error> for RuntimeError {
     }
 }
 
+impl From<tokio_postgres::Error> for RuntimeError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        Self::StorageError(err.to_string())
+    }
+}
+
 pub type Result<T> = std::result::Result<T, RuntimeError>;
\ No newline at end of file
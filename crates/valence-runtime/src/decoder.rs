@@ -0,0 +1,961 @@
+//! Decoding of confirmed transactions into typed Valence activity
+//!
+//! Kernel instructions are recognized by their Anchor discriminator wherever
+//! they appear, top level or as inner instructions, and resolved against the
+//! transaction's account keys. Lamport and SPL token transfers are decoded
+//! alongside and attributed to the session of the kernel instruction that
+//! caused them, so consumers can tell which session moved what.
+
+use crate::{Result, RuntimeError};
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use serde::{Deserialize, Serialize};
+#[allow(deprecated)]
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::{instruction::CompiledInstruction, pubkey::Pubkey};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    UiInstruction,
+};
+use std::str::FromStr;
+use valence_kernel::{
+    instruction as kernel_instruction, state::function_registry::FunctionInfo, KernelOperation,
+    OperationBatch,
+};
+
+// ================================
+// Decoded Types
+// ================================
+
+/// A confirmed transaction's Valence instructions and the value it moved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecodedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether the transaction succeeded; failed transactions moved nothing
+    pub success: bool,
+    pub instructions: Vec<DecodedInstruction>,
+    pub transfers: Vec<Transfer>,
+}
+
+/// Position of an instruction within its transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionPosition {
+    /// Index of the top-level instruction
+    pub index: usize,
+    /// Index among the top-level instruction's inner instructions, for CPIs
+    pub inner_index: Option<usize>,
+}
+
+/// A kernel instruction at its position in the transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecodedInstruction {
+    pub position: InstructionPosition,
+    pub instruction: KernelInstruction,
+}
+
+/// Kernel instruction with its accounts resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "instruction", rename_all = "snake_case")]
+pub enum KernelInstruction {
+    CreateSession {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        #[serde(with = "pubkey")]
+        owner: Pubkey,
+        #[serde(with = "pubkey")]
+        shard: Pubkey,
+        namespace: Option<String>,
+        #[serde(with = "optional_pubkey")]
+        parent_session: Option<Pubkey>,
+    },
+    ExecuteBatch {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        #[serde(with = "pubkey")]
+        caller: Pubkey,
+        operations: Vec<BatchOperation>,
+    },
+    CommitBatch {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        earliest_slot: u64,
+    },
+    InvalidateSession {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+    },
+    InvalidateSessionBatch {
+        #[serde(with = "pubkey_vec")]
+        sessions: Vec<Pubkey>,
+    },
+    CreateChildAccount {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        #[serde(with = "pubkey")]
+        child_account: Pubkey,
+        namespace_suffix: String,
+        initial_lamports: u64,
+        #[serde(with = "pubkey")]
+        owner_program: Pubkey,
+    },
+    CloseChildAccount {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        #[serde(with = "pubkey")]
+        child_account: Pubkey,
+    },
+    SplTransfer {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        #[serde(with = "pubkey")]
+        from: Pubkey,
+        #[serde(with = "pubkey")]
+        to: Pubkey,
+        amount: u64,
+    },
+    WithdrawFeeVault {
+        #[serde(with = "pubkey")]
+        session: Pubkey,
+        amount: u64,
+    },
+    /// Configuration and administration instructions, decoded by name
+    Other {
+        name: String,
+        #[serde(with = "optional_pubkey")]
+        session: Option<Pubkey>,
+    },
+}
+
+impl KernelInstruction {
+    /// Session the instruction acts on, if it targets exactly one
+    pub fn session(&self) -> Option<Pubkey> {
+        match self {
+            Self::CreateSession { session, .. }
+            | Self::ExecuteBatch { session, .. }
+            | Self::CommitBatch { session, .. }
+            | Self::InvalidateSession { session }
+            | Self::CreateChildAccount { session, .. }
+            | Self::CloseChildAccount { session, .. }
+            | Self::SplTransfer { session, .. }
+            | Self::WithdrawFeeVault { session, .. } => Some(*session),
+            Self::Other { session, .. } => *session,
+            Self::InvalidateSessionBatch { .. } => None,
+        }
+    }
+}
+
+/// One operation of an executed batch, with account indices resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Borrow {
+        #[serde(with = "pubkey")]
+        account: Pubkey,
+        mode: u8,
+    },
+    Release {
+        #[serde(with = "pubkey")]
+        account: Pubkey,
+    },
+    CallFunction {
+        registry_id: u64,
+        /// Program implementing the function, when the registry knows it
+        #[serde(with = "optional_pubkey")]
+        program_id: Option<Pubkey>,
+    },
+    RawCpi {
+        #[serde(with = "pubkey")]
+        program_id: Pubkey,
+    },
+    AssertAccountData {
+        #[serde(with = "pubkey")]
+        account: Pubkey,
+    },
+    AssertTokenBalance {
+        #[serde(with = "pubkey")]
+        account: Pubkey,
+        min: u64,
+        max: u64,
+    },
+}
+
+/// Lamports or tokens moved by a system or SPL token transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub position: InstructionPosition,
+    /// Session of the kernel instruction that caused the transfer
+    #[serde(with = "optional_pubkey")]
+    pub session: Option<Pubkey>,
+    #[serde(with = "pubkey")]
+    pub program_id: Pubkey,
+    #[serde(with = "pubkey")]
+    pub source: Pubkey,
+    #[serde(with = "pubkey")]
+    pub destination: Pubkey,
+    /// Token mint, when the instruction names it; `None` for lamports
+    #[serde(with = "optional_pubkey")]
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+impl Transfer {
+    /// Whether the transfer moved lamports rather than tokens
+    pub fn is_lamports(&self) -> bool {
+        self.program_id == solana_sdk::system_program::ID
+    }
+}
+
+impl DecodedTransaction {
+    /// Sessions touched by the transaction, in first-seen order
+    pub fn sessions(&self) -> Vec<Pubkey> {
+        let mut sessions = Vec::new();
+        for instruction in &self.instructions {
+            let touched = match &instruction.instruction {
+                KernelInstruction::InvalidateSessionBatch { sessions } => sessions.clone(),
+                other => other.session().into_iter().collect(),
+            };
+            for session in touched {
+                if !sessions.contains(&session) {
+                    sessions.push(session);
+                }
+            }
+        }
+        sessions
+    }
+
+    /// Transfers caused by instructions acting on `session`
+    pub fn transfers_for(&self, session: &Pubkey) -> impl Iterator<Item = &Transfer> {
+        let session = *session;
+        self.transfers
+            .iter()
+            .filter(move |transfer| transfer.session == Some(session))
+    }
+}
+
+// ================================
+// Decoding
+// ================================
+
+/// A confirmed transaction's instructions compiled against its full account key list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub success: bool,
+    /// Static keys followed by addresses loaded from lookup tables
+    pub account_keys: Vec<Pubkey>,
+    pub instructions: Vec<CompiledInstruction>,
+    /// Top-level instruction index paired with the instructions it invoked
+    pub inner: Vec<(usize, Vec<CompiledInstruction>)>,
+    pub log_messages: Vec<String>,
+}
+
+impl CompiledTransaction {
+    /// Extract a transaction fetched with a binary (base58/base64) encoding
+    pub fn from_confirmed(confirmed: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Self> {
+        let transaction = confirmed.transaction.transaction.decode().ok_or_else(|| {
+            RuntimeError::DecodeError("transaction must be fetched with a binary encoding".to_string())
+        })?;
+        let meta = confirmed.transaction.meta.as_ref();
+
+        // Versioned transactions append addresses loaded from lookup tables
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let Some(OptionSerializer::Some(loaded)) = meta.map(|meta| &meta.loaded_addresses) {
+            for address in loaded.writable.iter().chain(&loaded.readonly) {
+                account_keys.push(Pubkey::from_str(address).map_err(|e| {
+                    RuntimeError::DecodeError(format!("loaded address {}: {}", address, e))
+                })?);
+            }
+        }
+
+        let mut inner = Vec::new();
+        if let Some(OptionSerializer::Some(inner_instructions)) =
+            meta.map(|meta| &meta.inner_instructions)
+        {
+            for group in inner_instructions {
+                let mut instructions = Vec::new();
+                for instruction in &group.instructions {
+                    let UiInstruction::Compiled(compiled) = instruction else {
+                        return Err(RuntimeError::DecodeError(
+                            "inner instructions must not be jsonParsed".to_string(),
+                        ));
+                    };
+                    let data = solana_sdk::bs58::decode(&compiled.data)
+                        .into_vec()
+                        .map_err(|e| RuntimeError::DecodeError(format!("inner instruction data: {}", e)))?;
+                    instructions.push(CompiledInstruction {
+                        program_id_index: compiled.program_id_index,
+                        accounts: compiled.accounts.clone(),
+                        data,
+                    });
+                }
+                inner.push((group.index as usize, instructions));
+            }
+        }
+
+        let log_messages = match meta.map(|meta| &meta.log_messages) {
+            Some(OptionSerializer::Some(logs)) => logs.clone(),
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            signature: transaction
+                .signatures
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            slot: confirmed.slot,
+            block_time: confirmed.block_time,
+            success: meta.is_none_or(|meta| meta.err.is_none()),
+            account_keys,
+            instructions: transaction.message.instructions().to_vec(),
+            inner,
+            log_messages,
+        })
+    }
+
+    /// Instructions in execution order, each resolved to its program and accounts
+    ///
+    /// Yields every top-level instruction followed by the instructions it invoked.
+    pub fn resolved(&self) -> impl Iterator<Item = (InstructionPosition, Pubkey, Vec<Pubkey>, &[u8])> {
+        self.instructions
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, instruction)| {
+                let inner = self
+                    .inner
+                    .iter()
+                    .filter(move |(outer, _)| *outer == index)
+                    .flat_map(|(_, instructions)| instructions)
+                    .enumerate()
+                    .map(move |(inner_index, instruction)| {
+                        self.resolve(
+                            InstructionPosition {
+                                index,
+                                inner_index: Some(inner_index),
+                            },
+                            instruction,
+                        )
+                    });
+                std::iter::once(self.resolve(
+                    InstructionPosition {
+                        index,
+                        inner_index: None,
+                    },
+                    instruction,
+                ))
+                .chain(inner)
+            })
+    }
+
+    fn resolve<'a>(
+        &self,
+        position: InstructionPosition,
+        instruction: &'a CompiledInstruction,
+    ) -> (InstructionPosition, Pubkey, Vec<Pubkey>, &'a [u8]) {
+        let program_id = self
+            .account_keys
+            .get(instruction.program_id_index as usize)
+            .copied()
+            .unwrap_or_default();
+        let accounts = instruction
+            .accounts
+            .iter()
+            .filter_map(|index| self.account_keys.get(*index as usize).copied())
+            .collect();
+        (position, program_id, accounts, instruction.data.as_slice())
+    }
+}
+
+/// Decode a transaction fetched with a binary (base58/base64) encoding
+pub fn decode_transaction(
+    confirmed: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<DecodedTransaction> {
+    let compiled = CompiledTransaction::from_confirmed(confirmed)?;
+    let mut decoded = decode_instructions(
+        &compiled.account_keys,
+        &compiled.instructions,
+        &compiled.inner,
+    );
+    decoded.signature = compiled.signature;
+    decoded.slot = compiled.slot;
+    decoded.block_time = compiled.block_time;
+    decoded.success = compiled.success;
+    if !decoded.success {
+        decoded.transfers.clear();
+    }
+    Ok(decoded)
+}
+
+/// Decode compiled instructions against the transaction's full account key list
+///
+/// `inner` pairs a top-level instruction index with the instructions it invoked.
+/// Signature, slot and status are left for the caller to fill in.
+pub fn decode_instructions(
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+    inner: &[(usize, Vec<CompiledInstruction>)],
+) -> DecodedTransaction {
+    let mut decoded = DecodedTransaction {
+        signature: String::new(),
+        slot: 0,
+        block_time: None,
+        success: true,
+        instructions: Vec::new(),
+        transfers: Vec::new(),
+    };
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        let mut session = None;
+        let mut decode = |instruction: &CompiledInstruction, position: InstructionPosition| {
+            let Some(program_id) = account_keys.get(instruction.program_id_index as usize) else {
+                return;
+            };
+            let accounts: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .filter_map(|index| account_keys.get(*index as usize).copied())
+                .collect();
+
+            if *program_id == valence_kernel::ID {
+                if let Some(kernel) = decode_kernel_instruction(&accounts, &instruction.data) {
+                    // Transfers below a kernel instruction belong to its session
+                    session = session.or(kernel.session());
+                    decoded.instructions.push(DecodedInstruction {
+                        position,
+                        instruction: kernel,
+                    });
+                }
+            } else if let Some((source, destination, mint, amount)) =
+                decode_transfer(program_id, &accounts, &instruction.data)
+            {
+                decoded.transfers.push(Transfer {
+                    position,
+                    session,
+                    program_id: *program_id,
+                    source,
+                    destination,
+                    mint,
+                    amount,
+                });
+            }
+        };
+
+        decode(
+            instruction,
+            InstructionPosition {
+                index,
+                inner_index: None,
+            },
+        );
+        for (inner_index, instruction) in inner
+            .iter()
+            .filter(|(outer, _)| *outer == index)
+            .flat_map(|(_, instructions)| instructions)
+            .enumerate()
+        {
+            decode(
+                instruction,
+                InstructionPosition {
+                    index,
+                    inner_index: Some(inner_index),
+                },
+            );
+        }
+    }
+    decoded
+}
+
+/// Deserialize Anchor instruction arguments when `data` carries `T`'s discriminator
+fn args<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let mut body = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::deserialize(&mut body).ok()
+}
+
+/// Name of an argument-free, configuration, batch buffer or operation data instruction
+fn other_name(data: &[u8]) -> Option<&'static str> {
+    let names: [(&[u8], &'static str); 11] = [
+        (kernel_instruction::InitializeShard::DISCRIMINATOR, "initialize_shard"),
+        (kernel_instruction::CreateGuardAccount::DISCRIMINATOR, "create_guard_account"),
+        (kernel_instruction::SetGuardApprovalSigner::DISCRIMINATOR, "set_guard_approval_signer"),
+        (kernel_instruction::SetGuardRequireCommitment::DISCRIMINATOR, "set_guard_require_commitment"),
+        (kernel_instruction::InitializeSessionNonce::DISCRIMINATOR, "initialize_session_nonce"),
+        (kernel_instruction::InitializeSessionStats::DISCRIMINATOR, "initialize_session_stats"),
+        (kernel_instruction::InitializeFeeVault::DISCRIMINATOR, "initialize_fee_vault"),
+        (kernel_instruction::ConfigureFeeVault::DISCRIMINATOR, "configure_fee_vault"),
+        (kernel_instruction::ManageAlt::DISCRIMINATOR, "manage_alt"),
+        (kernel_instruction::InitializeAllowlist::DISCRIMINATOR, "initialize_allowlist"),
+        (kernel_instruction::AddProgramToCpiAllowlist::DISCRIMINATOR, "add_program_to_cpi_allowlist"),
+    ];
+    names
+        .into_iter()
+        .chain([
+            (
+                kernel_instruction::RemoveProgramFromCpiAllowlist::DISCRIMINATOR,
+                "remove_program_from_cpi_allowlist",
+            ),
+            (kernel_instruction::BeginBatch::DISCRIMINATOR, "begin_batch"),
+            (kernel_instruction::AppendOperations::DISCRIMINATOR, "append_operations"),
+            (kernel_instruction::FinalizeBatch::DISCRIMINATOR, "finalize_batch"),
+            (kernel_instruction::DiscardBatch::DISCRIMINATOR, "discard_batch"),
+            (
+                kernel_instruction::InitializeOperationData::DISCRIMINATOR,
+                "initialize_operation_data",
+            ),
+            (kernel_instruction::WriteOperationData::DISCRIMINATOR, "write_operation_data"),
+            (kernel_instruction::CloseOperationData::DISCRIMINATOR, "close_operation_data"),
+            (kernel_instruction::MigrateAllowlist::DISCRIMINATOR, "migrate_allowlist"),
+            (kernel_instruction::MigrateGuardAccount::DISCRIMINATOR, "migrate_guard_account"),
+            (kernel_instruction::CloseSession::DISCRIMINATOR, "close_session"),
+        ])
+        .find(|(discriminator, _)| data.starts_with(discriminator))
+        .map(|(_, name)| name)
+}
+
+/// Decode a kernel instruction from its resolved accounts and data
+pub fn decode_kernel_instruction(accounts: &[Pubkey], data: &[u8]) -> Option<KernelInstruction> {
+    let account = |index: usize| accounts.get(index).copied();
+
+    if let Some(args) = args::<kernel_instruction::ExecuteBatch>(data) {
+        return Some(KernelInstruction::ExecuteBatch {
+            session: account(0)?,
+            caller: account(4)?,
+            operations: batch_operations(&args.batch),
+        });
+    }
+    if let Some(args) = args::<kernel_instruction::CreateSessionAccount>(data) {
+        let namespace = args
+            .params
+            .namespace_path
+            .get(..args.params.namespace_path_len as usize)
+            .and_then(|path| std::str::from_utf8(path).ok())
+            .map(str::to_string);
+        return Some(KernelInstruction::CreateSession {
+            session: account(0)?,
+            owner: account(3)?,
+            shard: args.shard,
+            namespace,
+            parent_session: args.params.parent_session,
+        });
+    }
+    if let Some(args) = args::<kernel_instruction::CommitBatch>(data) {
+        return Some(KernelInstruction::CommitBatch {
+            session: account(0)?,
+            earliest_slot: args.earliest_slot,
+        });
+    }
+    if args::<kernel_instruction::InvalidateSession>(data).is_some() {
+        return Some(KernelInstruction::InvalidateSession {
+            session: account(0)?,
+        });
+    }
+    if let Some(args) = args::<kernel_instruction::InvalidateSessionBatch>(data) {
+        return Some(KernelInstruction::InvalidateSessionBatch {
+            sessions: args.session_keys,
+        });
+    }
+    if let Some(args) = args::<kernel_instruction::CreateChildAccount>(data) {
+        return Some(KernelInstruction::CreateChildAccount {
+            session: account(0)?,
+            child_account: account(1)?,
+            namespace_suffix: args.namespace_suffix,
+            initial_lamports: args.initial_lamports,
+            owner_program: args.owner_program,
+        });
+    }
+    if args::<kernel_instruction::CloseChildAccount>(data).is_some() {
+        return Some(KernelInstruction::CloseChildAccount {
+            session: account(0)?,
+            child_account: account(1)?,
+        });
+    }
+    if let Some(args) = args::<kernel_instruction::SplTransfer>(data) {
+        return Some(KernelInstruction::SplTransfer {
+            session: account(0)?,
+            from: account(2)?,
+            to: account(3)?,
+            amount: args.amount,
+        });
+    }
+    if let Some(args) = args::<kernel_instruction::WithdrawFeeVault>(data) {
+        return Some(KernelInstruction::WithdrawFeeVault {
+            session: account(0)?,
+            amount: args.amount,
+        });
+    }
+
+    // Guard, shard and allowlist instructions carry the session first when they have one
+    let name = other_name(data)?;
+    let session = match name {
+        "initialize_shard" | "create_guard_account" | "initialize_allowlist"
+        | "add_program_to_cpi_allowlist" | "remove_program_from_cpi_allowlist"
+        | "migrate_allowlist" => None,
+        _ => account(0),
+    };
+    Some(KernelInstruction::Other {
+        name: name.to_string(),
+        session,
+    })
+}
+
+/// Resolve a batch's operations against its account list
+pub fn batch_operations(batch: &OperationBatch) -> Vec<BatchOperation> {
+    let account = |index: u8| {
+        batch
+            .accounts
+            .get(..batch.accounts_len as usize)
+            .and_then(|accounts| accounts.get(index as usize))
+            .copied()
+            .unwrap_or_default()
+    };
+
+    batch
+        .operations
+        .iter()
+        .take(batch.operations_len as usize)
+        .flatten()
+        .map(|operation| match operation {
+            KernelOperation::BorrowAccount {
+                account_index,
+                mode,
+            } => BatchOperation::Borrow {
+                account: account(*account_index),
+                mode: *mode,
+            },
+            KernelOperation::ReleaseAccount { account_index } => BatchOperation::Release {
+                account: account(*account_index),
+            },
+            KernelOperation::CallRegisteredFunction { registry_id, .. } => {
+                BatchOperation::CallFunction {
+                    registry_id: *registry_id,
+                    program_id: FunctionInfo::get_registry_entry(*registry_id)
+                        .map(|function| function.program_id),
+                }
+            }
+            KernelOperation::UnsafeRawCpi { program_index, .. } => BatchOperation::RawCpi {
+                program_id: account(*program_index),
+            },
+            KernelOperation::AssertAccountData { account_index, .. } => {
+                BatchOperation::AssertAccountData {
+                    account: account(*account_index),
+                }
+            }
+            KernelOperation::AssertTokenBalance {
+                account_index,
+                min,
+                max,
+            } => BatchOperation::AssertTokenBalance {
+                account: account(*account_index),
+                min: *min,
+                max: *max,
+            },
+        })
+        .collect()
+}
+
+/// Decode a system or SPL token transfer into (source, destination, mint, amount)
+fn decode_transfer(
+    program_id: &Pubkey,
+    accounts: &[Pubkey],
+    data: &[u8],
+) -> Option<(Pubkey, Pubkey, Option<Pubkey>, u64)> {
+    if *program_id == solana_sdk::system_program::ID {
+        #[allow(deprecated)]
+        return match bincode::deserialize::<SystemInstruction>(data).ok()? {
+            SystemInstruction::Transfer { lamports } => {
+                Some((*accounts.first()?, *accounts.get(1)?, None, lamports))
+            }
+            _ => None,
+        };
+    }
+
+    if *program_id == spl_token::ID {
+        use spl_token::instruction::TokenInstruction;
+        return match TokenInstruction::unpack(data).ok()? {
+            #[allow(deprecated)]
+            TokenInstruction::Transfer { amount } => {
+                Some((*accounts.first()?, *accounts.get(1)?, None, amount))
+            }
+            TokenInstruction::TransferChecked { amount, .. } => Some((
+                *accounts.first()?,
+                *accounts.get(2)?,
+                Some(*accounts.get(1)?),
+                amount,
+            )),
+            _ => None,
+        };
+    }
+    None
+}
+
+mod pubkey {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&key.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Pubkey, D::Error> {
+        Pubkey::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+mod optional_pubkey {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(key: &Option<Pubkey>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match key {
+            Some(key) => serializer.serialize_some(&key.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Pubkey>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|key| Pubkey::from_str(&key).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+mod pubkey_vec {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(keys: &[Pubkey], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().map(|key| key.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|key| Pubkey::from_str(key).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::InstructionData;
+    #[allow(deprecated)]
+    use solana_sdk::system_instruction;
+
+    /// Compile instructions against a shared key list, appending keys as needed
+    fn compile(
+        keys: &mut Vec<Pubkey>,
+        instruction: &solana_sdk::instruction::Instruction,
+    ) -> CompiledInstruction {
+        let mut index = |key: &Pubkey| match keys.iter().position(|k| k == key) {
+            Some(index) => index as u8,
+            None => {
+                keys.push(*key);
+                (keys.len() - 1) as u8
+            }
+        };
+        CompiledInstruction {
+            program_id_index: index(&instruction.program_id),
+            accounts: instruction.accounts.iter().map(|meta| index(&meta.pubkey)).collect(),
+            data: instruction.data.clone(),
+        }
+    }
+
+    fn kernel(accounts: &[Pubkey], data: Vec<u8>) -> solana_sdk::instruction::Instruction {
+        solana_sdk::instruction::Instruction {
+            program_id: valence_kernel::ID,
+            accounts: accounts
+                .iter()
+                .map(|key| solana_sdk::instruction::AccountMeta::new(*key, false))
+                .collect(),
+            data,
+        }
+    }
+
+    fn batch(accounts: &[Pubkey], operations: &[KernelOperation]) -> OperationBatch {
+        let mut batch = OperationBatch {
+            accounts: [Pubkey::default(); valence_kernel::MAX_BATCH_ACCOUNTS],
+            accounts_len: accounts.len() as u8,
+            operations: [const { None }; valence_kernel::MAX_BATCH_OPERATIONS],
+            operations_len: operations.len() as u8,
+            operation_data_hash: [0; 32],
+        };
+        batch.accounts[..accounts.len()].copy_from_slice(accounts);
+        for (slot, operation) in batch.operations.iter_mut().zip(operations) {
+            *slot = Some(operation.clone());
+        }
+        batch
+    }
+
+    #[test]
+    fn test_decode_batch_with_inner_transfer() {
+        let (session, caller, vault, recipient) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut accounts = vec![session];
+        accounts.extend((0..3).map(|_| Pubkey::new_unique()));
+        accounts.push(caller);
+
+        let execute = kernel(
+            &accounts,
+            kernel_instruction::ExecuteBatch {
+                batch: batch(
+                    &[vault, solana_sdk::system_program::ID],
+                    &[
+                        KernelOperation::BorrowAccount {
+                            account_index: 0,
+                            mode: valence_kernel::ACCESS_MODE_READ_WRITE,
+                        },
+                        KernelOperation::CallRegisteredFunction {
+                            registry_id: 1005,
+                            account_indices: [0; valence_kernel::MAX_CPI_ACCOUNT_INDICES],
+                            account_indices_len: 0,
+                            data_offset: 0,
+                            data_len: 0,
+                        },
+                        KernelOperation::ReleaseAccount { account_index: 0 },
+                    ],
+                ),
+            }
+            .data(),
+        );
+        #[allow(deprecated)]
+        let transfer = system_instruction::transfer(&caller, &recipient, 500);
+        let unrelated = system_instruction::transfer(&recipient, &caller, 7);
+
+        let mut keys = Vec::new();
+        let outer = vec![compile(&mut keys, &execute), compile(&mut keys, &unrelated)];
+        let inner = vec![(0, vec![compile(&mut keys, &transfer)])];
+        let decoded = decode_instructions(&keys, &outer, &inner);
+
+        assert_eq!(decoded.instructions.len(), 1);
+        match &decoded.instructions[0].instruction {
+            KernelInstruction::ExecuteBatch {
+                session: decoded_session,
+                caller: decoded_caller,
+                operations,
+            } => {
+                assert_eq!(*decoded_session, session);
+                assert_eq!(*decoded_caller, caller);
+                assert_eq!(
+                    operations[1],
+                    BatchOperation::CallFunction {
+                        registry_id: 1005,
+                        program_id: FunctionInfo::get_registry_entry(1005)
+                            .map(|function| function.program_id),
+                    }
+                );
+                assert_eq!(operations[2], BatchOperation::Release { account: vault });
+            }
+            other => panic!("unexpected instruction {other:?}"),
+        }
+
+        assert_eq!(decoded.transfers.len(), 2);
+        let moved: Vec<&Transfer> = decoded.transfers_for(&session).collect();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].amount, 500);
+        assert_eq!(moved[0].destination, recipient);
+        assert!(moved[0].is_lamports());
+        assert_eq!(
+            moved[0].position,
+            InstructionPosition {
+                index: 0,
+                inner_index: Some(0)
+            }
+        );
+        // The top-level transfer was not caused by a session
+        assert_eq!(decoded.transfers[1].session, None);
+        assert_eq!(decoded.sessions(), vec![session]);
+    }
+
+    #[test]
+    fn test_decode_session_instructions() {
+        let (session, child, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let invalidate = decode_kernel_instruction(
+            &[session, owner],
+            &kernel_instruction::InvalidateSession {}.data(),
+        );
+        assert_eq!(invalidate, Some(KernelInstruction::InvalidateSession { session }));
+
+        let child_account = decode_kernel_instruction(
+            &[session, child, owner],
+            &kernel_instruction::CreateChildAccount {
+                namespace_suffix: "vault".to_string(),
+                initial_lamports: 1_000,
+                space: 0,
+                owner_program: owner,
+            }
+            .data(),
+        )
+        .unwrap();
+        assert_eq!(child_account.session(), Some(session));
+
+        let guard = decode_kernel_instruction(
+            &[session, Pubkey::new_unique(), owner],
+            &kernel_instruction::SetGuardRequireCommitment {
+                require_commitment: true,
+            }
+            .data(),
+        );
+        assert_eq!(
+            guard,
+            Some(KernelInstruction::Other {
+                name: "set_guard_require_commitment".to_string(),
+                session: Some(session),
+            })
+        );
+
+        let finalize = decode_kernel_instruction(
+            &[session, Pubkey::new_unique()],
+            &kernel_instruction::FinalizeBatch {}.data(),
+        );
+        assert_eq!(
+            finalize,
+            Some(KernelInstruction::Other {
+                name: "finalize_batch".to_string(),
+                session: Some(session),
+            })
+        );
+
+        assert_eq!(decode_kernel_instruction(&[session], &[0u8; 8]), None);
+    }
+
+    #[test]
+    fn test_decode_token_transfer_checked() {
+        let (source, mint, destination, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let instruction = spl_token::instruction::transfer_checked(
+            &spl_token::ID,
+            &source,
+            &mint,
+            &destination,
+            &authority,
+            &[],
+            42,
+            6,
+        )
+        .unwrap();
+
+        let mut keys = Vec::new();
+        let outer = vec![compile(&mut keys, &instruction)];
+        let decoded = decode_instructions(&keys, &outer, &[]);
+        assert_eq!(decoded.transfers.len(), 1);
+        assert_eq!(decoded.transfers[0].mint, Some(mint));
+        assert_eq!(decoded.transfers[0].destination, destination);
+        assert_eq!(decoded.transfers[0].amount, 42);
+        assert!(!decoded.transfers[0].is_lamports());
+    }
+}
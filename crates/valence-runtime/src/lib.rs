@@ -10,64 +10,123 @@
 pub mod core;
 pub mod types;
 
+// Named clusters and per-cluster clients
+pub mod cluster;
+pub use cluster::{Cluster, ClusterRegistry};
+
+// RPC endpoint failover
+pub mod rpc_pool;
+pub use rpc_pool::{EndpointHealth, RotationStrategy, RpcPool, RpcPoolConfig};
+
 // Core runtime functionality
 // Session management
 pub mod session;
 pub use session::{
     SessionManager, SessionState, SessionMetrics,
     SessionOperationRequest, KernelOperationRequest, AccountRequest, 
-    AccountType, OperationResult,
+    AccountType, OperationResult, SessionHealthConfig, SessionHealthReport,
+};
+
+// Declarative session lifecycle management
+pub mod lifecycle;
+pub use lifecycle::{
+    LifecycleBackend, LifecycleConfig, ManagedSession, SessionLifecycleManager, SessionSpec,
+    SigningLifecycleBackend,
 };
 
 // Transaction building and management
 pub mod transaction {
     pub mod builder;
+    pub mod bundle;
+    pub mod cost;
     pub mod instructions;
     
     pub use builder::{TransactionBuilder, UnsignedTransaction, TransactionMetadata, SimulationResult};
+    pub use bundle::{BundleConfig, BundleOutcome, BundleRoute, BundleSubmitter};
 }
 
 // State monitoring and event streaming
 pub mod monitoring {
     pub mod state_monitor;
     pub mod event_stream;
+    pub mod geyser;
+    pub mod backfill;
+    pub mod metrics;
     
     pub use state_monitor::{StateMonitor, StateUpdate};
-    pub use event_stream::{EventStream, Event};
+    pub use backfill::{
+        BackfillConfig, BackfillService, Checkpoint, CheckpointStore, FileCheckpointStore,
+        MemoryCheckpointStore,
+    };
+    pub use metrics::MetricsExporter;
+    pub use geyser::{GeyserConfig, GeyserSource, MonitorSource};
+    pub use event_stream::{
+        EventStream, Event, EventFilter, EventGroup, FilteredEventStream, FlowEvents,
+        TransactionEvents, TypedEventStream,
+    };
 }
 
 // Flow coordination and execution
 pub mod coordination;
-pub use coordination::{Coordinator, ProtocolFlow};
+pub use coordination::{
+    BudgetConfig, BudgetScope, Coordinator, CostKind, DailySpend, ExecutionStore,
+    FileExecutionStore, FlowCost, MemoryExecutionStore, ProtocolFlow, SpendLedger,
+};
+
+// Dry-run simulation of protocol flows
+pub mod simulator;
+pub use simulator::{
+    FlowSimulationReport, FlowSimulator, RpcSimulationBackend, SimulationBackend, StepSimulation,
+};
+
+// Transaction rate limiting
+pub mod rate_limit;
+pub use rate_limit::{RateLimit, RateLimitConfig, RateLimiter};
 
 // Security utilities and validation
 pub mod security;
 
+// Decoding of confirmed Valence transactions
+pub mod decoder;
+pub use decoder::{
+    decode_transaction, CompiledTransaction, DecodedTransaction, KernelInstruction, Transfer,
+};
+
+// REST control-plane API
+pub mod control;
+pub use control::{ApiRole, ApiToken, ControlApi, ControlApiConfig};
+
 // ================================
 // Public API Re-exports
 // ================================
 
 // Configuration and errors
-pub use core::{RuntimeConfig, RuntimeError, Result};
+pub use core::{ClusterConfig, RuntimeConfig, RuntimeError, Result, DEFAULT_CLUSTER};
 
 // Session management (re-exported above)
 
 // Transaction management  
-pub use transaction::{TransactionBuilder, UnsignedTransaction, TransactionMetadata};
+pub use transaction::{
+    BundleConfig, BundleSubmitter, TransactionBuilder, TransactionMetadata, UnsignedTransaction,
+};
 
 // Monitoring and events
-pub use monitoring::{StateMonitor, StateUpdate, EventStream, Event};
+pub use monitoring::{
+    StateMonitor, StateUpdate, EventStream, Event, EventFilter, FlowEvents, MonitorSource,
+    GeyserConfig,
+};
 
 // Coordination (re-exported above)
 
 // Security
-pub use security::{AuditLogger, SecurityAnalyzer, SecurityContext, TransactionValidator};
+pub use security::{AuditLogger, PolicyEngine, SecurityAnalyzer, SecurityContext, TransactionValidator};
 pub use security::{CompositeSigningService, SigningRequest, SigningResponse, SigningService};
 
 // Common types
 pub use types::{
     RuntimeSessionParams, RuntimeMetrics, RuntimeEvent, RuntimeConfiguration,
-    KernelExecutionPlan, SessionHealth, GuardHealth, SessionStatus,
+    KernelExecutionPlan, SessionHealth, GuardHealth, SessionStatus, UnhealthyReason,
+    LifecycleStep,
 };
 
 // ================================
@@ -76,9 +135,15 @@ pub use types::{
 
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::info;
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+/// How often the policy file is checked for changes
+const POLICY_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Parameters for creating a child account
 #[derive(Debug, Clone)]
@@ -96,15 +161,23 @@ pub struct CreateChildAccountParams {
 pub struct Runtime {
     config: RuntimeConfig,
     rpc_client: Arc<RpcClient>,
+    clusters: Arc<ClusterRegistry>,
     state_monitor: Arc<RwLock<StateMonitor>>,
+    cluster_monitors: Vec<StateMonitor>,
     coordinator: Arc<Coordinator>,
     event_stream: Arc<EventStream>,
     signing_service: Arc<CompositeSigningService>,
     #[allow(dead_code)]
     audit_logger: Arc<AuditLogger>,
     transaction_validator: Arc<TransactionValidator>,
+    policy_engine: Option<Arc<PolicyEngine>>,
+    policy_watch: RwLock<Option<JoinHandle<()>>>,
+    shutdown_tx: broadcast::Sender<()>,
     session_manager: Arc<SessionManager>,
     runtime_metrics: Arc<RwLock<RuntimeMetrics>>,
+    metrics_exporter: Arc<monitoring::MetricsExporter>,
+    rate_limiter: Arc<RateLimiter>,
+    control_api: Option<Arc<ControlApi>>,
 }
 
 impl Runtime {
@@ -112,18 +185,76 @@ impl Runtime {
     pub async fn new(config: RuntimeConfig) -> Result<Self> {
         info!("Initializing Valence runtime");
 
-        let rpc_client = Arc::new(RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            config.commitment,
-        ));
+        // Metrics come first so RPC and signing latency is recorded from the start
+        let runtime_metrics = Arc::new(RwLock::new(RuntimeMetrics::default()));
+        let metrics_exporter = Arc::new(monitoring::MetricsExporter::new(runtime_metrics.clone())?);
 
-        let event_stream = Arc::new(EventStream::new());
+        let clusters = Arc::new(ClusterRegistry::with_metrics(
+            config.cluster_configs(),
+            config.rpc_pool.clone(),
+            metrics_exporter.clone(),
+        )?);
+        let rpc_client = clusters.default_cluster().rpc_client.clone();
 
-        let state_monitor = Arc::new(RwLock::new(
-            StateMonitor::new(config.ws_url.clone(), event_stream.clone()).await?,
-        ));
+        let event_stream = Arc::new(EventStream::new());
 
-        let coordinator = Arc::new(Coordinator::new(rpc_client.clone(), event_stream.clone()));
+        let mut monitor = StateMonitor::with_source(
+            config.ws_url.clone(),
+            config.monitor_source.clone(),
+            config.commitment,
+            Arc::new(event_stream.for_cluster(DEFAULT_CLUSTER)),
+        )
+        .await?;
+
+        // Additional clusters are watched over WebSocket with their own tagged monitors
+        let mut cluster_monitors = Vec::new();
+        for cluster in &config.clusters {
+            cluster_monitors.push(
+                StateMonitor::with_source(
+                    cluster.ws_url.clone(),
+                    MonitorSource::WebSocket,
+                    cluster.commitment,
+                    Arc::new(event_stream.for_cluster(cluster.name.clone())),
+                )
+                .await?,
+            );
+        }
+
+        // Enable backfill of missed events when a checkpoint location is configured
+        if let Some(checkpoint_path) = &config.checkpoint_path {
+            let store = Arc::new(
+                monitoring::FileCheckpointStore::new(checkpoint_path.clone()).await?,
+            );
+            let backfill = Arc::new(monitoring::BackfillService::new(
+                rpc_client.clone(),
+                store,
+                Arc::new(event_stream.for_cluster(DEFAULT_CLUSTER)),
+                monitoring::BackfillConfig {
+                    commitment: config.commitment,
+                    ..Default::default()
+                },
+            ));
+            monitor = monitor.with_backfill(backfill);
+        }
+
+        let state_monitor = Arc::new(RwLock::new(monitor));
+
+        let rate_limiter = Arc::new(RateLimiter::new(config.rate_limits.clone()));
+
+        let mut coordinator = Coordinator::new(rpc_client.clone(), event_stream.clone())
+            .with_clusters(clusters.clone())
+            .with_rate_limiter(rate_limiter.clone())
+            .with_budget(config.budgets.clone());
+
+        // Resume executions left unfinished by the previous shutdown
+        if let Some(execution_state_path) = &config.execution_state_path {
+            let store = Arc::new(
+                coordination::FileExecutionStore::new(execution_state_path.clone()).await?,
+            );
+            coordinator = coordinator.with_execution_store(store);
+            coordinator.restore_executions().await?;
+        }
+        let coordinator = Arc::new(coordinator);
 
         // Initialize security components
         let security_context = SecurityContext {
@@ -134,9 +265,10 @@ impl Runtime {
         };
 
         // Initialize signing service
-        let signing_service = Arc::new(CompositeSigningService::new(
-            security::signing::SigningBackend::LocalKeypair,
-        ));
+        let signing_service = Arc::new(
+            CompositeSigningService::new(security::signing::SigningBackend::LocalKeypair)
+                .with_metrics(metrics_exporter.clone()),
+        );
 
         // Initialize audit logger
         let audit_storage = Arc::new(
@@ -148,28 +280,53 @@ impl Runtime {
         );
 
         // Initialize transaction validator
-        let transaction_validator = Arc::new(TransactionValidator::new(
-            rpc_client.clone(),
-            security_context,
-        ));
+        let policy_engine = config
+            .policy_path
+            .as_ref()
+            .map(|path| PolicyEngine::from_path(path).map(Arc::new))
+            .transpose()?;
+        let mut transaction_validator =
+            TransactionValidator::new(rpc_client.clone(), security_context);
+        if let Some(engine) = &policy_engine {
+            transaction_validator = transaction_validator.with_policy_engine(engine.clone());
+        }
+        let transaction_validator = Arc::new(transaction_validator);
 
         // Initialize session manager for kernel compatibility
         let session_manager = Arc::new(SessionManager::new(rpc_client.clone()));
 
-        // Initialize runtime metrics
-        let runtime_metrics = Arc::new(RwLock::new(RuntimeMetrics::default()));
+        let control_api = config.control_api.as_ref().map(|control| {
+            Arc::new(
+                ControlApi::new(
+                    coordinator.clone(),
+                    session_manager.clone(),
+                    metrics_exporter.clone(),
+                    config.session_health.clone(),
+                    control.tokens.clone(),
+                )
+                .with_audit_logger(audit_logger.clone()),
+            )
+        });
 
         Ok(Self {
             config,
             rpc_client,
+            clusters,
             state_monitor,
+            cluster_monitors,
             coordinator,
             event_stream,
             signing_service,
             audit_logger,
             transaction_validator,
+            policy_engine,
+            policy_watch: RwLock::new(None),
+            shutdown_tx: broadcast::channel(16).0,
             session_manager,
             runtime_metrics,
+            metrics_exporter,
+            rate_limiter,
+            control_api,
         })
     }
 
@@ -177,13 +334,40 @@ impl Runtime {
     pub async fn start(&self) -> Result<()> {
         info!("Starting Valence runtime service");
 
+        // Probe RPC endpoints so failover reacts before requests fail
+        self.clusters.start().await?;
+
+        // Start metrics collection before any events are emitted
+        self.metrics_exporter.start(&self.event_stream).await?;
+
+        // Pick up edits to the policy file without a restart
+        if let Some(engine) = &self.policy_engine {
+            let handle = engine
+                .clone()
+                .watch(POLICY_RELOAD_INTERVAL, self.shutdown_tx.subscribe());
+            *self.policy_watch.write().await = Some(handle);
+        }
+
         // Start state monitoring
         let monitor = self.state_monitor.read().await;
         monitor.start().await?;
+        for monitor in &self.cluster_monitors {
+            monitor.start().await?;
+        }
 
         // Start coordinator
         self.coordinator.start().await?;
 
+        // Start session health checks
+        self.session_manager
+            .start_health_monitor(self.event_stream.clone(), self.config.session_health.clone())
+            .await?;
+
+        // Serve the control API once everything it drives is running
+        if let (Some(control_api), Some(control)) = (&self.control_api, &self.config.control_api) {
+            control_api.start(control.listen_addr).await?;
+        }
+
         info!("Runtime service started successfully");
         Ok(())
     }
@@ -192,12 +376,43 @@ impl Runtime {
     pub async fn stop(&self) -> Result<()> {
         info!("Stopping Valence runtime service");
 
+        // Stop accepting control requests
+        if let Some(control_api) = &self.control_api {
+            control_api.stop().await?;
+        }
+
+        // Refuse new flows and let in-flight steps finish before persisting executions
+        let interrupted = self.coordinator.drain(self.config.drain_timeout).await;
+        for execution in &interrupted {
+            warn!(
+                "Interrupting flow {} execution {} at step {}",
+                execution.flow_id, execution.instance_id, execution.current_step
+            );
+        }
+        self.coordinator.persist_executions().await?;
+
         // Stop coordinator
         self.coordinator.stop().await?;
 
+        // Stop session health checks
+        self.session_manager.stop_health_monitor().await?;
+
         // Stop state monitoring
         let monitor = self.state_monitor.read().await;
         monitor.stop().await?;
+        for monitor in &self.cluster_monitors {
+            monitor.stop().await?;
+        }
+
+        // Stop metrics collection and policy reloads
+        self.metrics_exporter.stop().await?;
+        let _ = self.shutdown_tx.send(());
+        if let Some(handle) = self.policy_watch.write().await.take() {
+            let _ = handle.await;
+        }
+
+        // Stop RPC endpoint probes
+        self.clusters.stop().await?;
 
         info!("Runtime service stopped");
         Ok(())
@@ -208,14 +423,54 @@ impl Runtime {
         self.event_stream.subscribe().await
     }
 
+    /// Subscribe to runtime events passing a filter
+    pub async fn subscribe_filtered(&self, filter: EventFilter) -> monitoring::FilteredEventStream {
+        self.event_stream.subscribe_filtered(filter).await
+    }
+
+    /// Subscribe to a typed group of runtime events
+    pub async fn subscribe_to<G: monitoring::EventGroup>(&self) -> monitoring::TypedEventStream<G> {
+        self.event_stream.subscribe_to::<G>().await
+    }
+
     /// Get the RPC client
     pub fn rpc_client(&self) -> &Arc<RpcClient> {
         &self.rpc_client
     }
 
-    /// Get the transaction builder
+    /// Get the transaction builder, refused once the daily budget is exhausted
     pub fn transaction_builder(&self) -> TransactionBuilder {
         TransactionBuilder::new(self.rpc_client.clone())
+            .with_rate_limiter(self.rate_limiter.clone())
+            .with_spend_ledger(self.coordinator.spend_ledger().clone())
+    }
+
+    /// Get the configured clusters
+    pub fn clusters(&self) -> &Arc<ClusterRegistry> {
+        &self.clusters
+    }
+
+    /// Get the transaction builder for a named cluster
+    pub fn cluster_transaction_builder(&self, cluster: &str) -> Result<TransactionBuilder> {
+        Ok(self
+            .clusters
+            .transaction_builder(Some(cluster))?
+            .with_rate_limiter(self.rate_limiter.clone())
+            .with_spend_ledger(self.coordinator.spend_ledger().clone()))
+    }
+
+    /// Get the transaction rate limiter
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// Dry-run simulator for registered flows, with `payer` paying simulated fees
+    pub fn flow_simulator(&self, payer: Pubkey) -> FlowSimulator {
+        FlowSimulator::new(
+            self.coordinator.clone(),
+            Arc::new(RpcSimulationBackend::new(self.clusters.clone())),
+            payer,
+        )
     }
 
     /// Generate deterministic account addresses
@@ -233,11 +488,26 @@ impl Runtime {
         &self.signing_service
     }
 
+    /// Get the metrics exporter
+    pub fn metrics(&self) -> &Arc<monitoring::MetricsExporter> {
+        &self.metrics_exporter
+    }
+
+    /// Get the control API, when configured
+    pub fn control_api(&self) -> Option<&Arc<ControlApi>> {
+        self.control_api.as_ref()
+    }
+
     /// Get the transaction validator
     pub fn transaction_validator(&self) -> &Arc<TransactionValidator> {
         &self.transaction_validator
     }
 
+    /// Get the policy engine, when a policy file is configured
+    pub fn policy_engine(&self) -> Option<&Arc<PolicyEngine>> {
+        self.policy_engine.as_ref()
+    }
+
     // Session management methods
     pub fn session_manager(&self) -> &Arc<SessionManager> {
         &self.session_manager
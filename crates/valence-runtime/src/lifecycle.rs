@@ -0,0 +1,812 @@
+//! Declarative lifecycle management for kernel sessions
+//!
+//! A [`SessionSpec`] describes the session a flow needs. The manager creates
+//! it, keeps its lookup table registrations in line with the spec, tops up its
+//! balance, rotates it before it exceeds its maximum age, and closes it,
+//! reclaiming its rent, once the owning flow completes. Each step is reported as
+//! `Event::SessionLifecycle`.
+
+use crate::{
+    monitoring::event_stream::{Event, EventStream},
+    security::signing::{merge_signatures, RiskLevel, SigningRequest, SigningResult, SigningService},
+    transaction::{instructions, TransactionBuilder},
+    types::LifecycleStep,
+    Result, RuntimeError,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+use valence_kernel::{
+    state::{CreateSessionParams, RegisteredAccount, RegisteredProgram},
+    MAX_REGISTERED_ACCOUNTS,
+};
+
+// ================================
+// Specs
+// ================================
+
+/// Bytes available for the namespace in `CreateSessionParams::namespace_path`
+const NAMESPACE_CAPACITY: usize = 128;
+
+/// Desired state of a managed session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSpec {
+    /// Stable name identifying the session across rotations
+    pub name: String,
+    pub owner: Pubkey,
+    pub shard: Pubkey,
+    pub namespace: String,
+    #[serde(default)]
+    pub allow_unregistered_cpi: bool,
+    #[serde(default)]
+    pub borrowable: Vec<BorrowableSpec>,
+    #[serde(default)]
+    pub programs: Vec<ProgramSpec>,
+    /// Top up when the session balance drops below this
+    #[serde(default)]
+    pub min_balance: u64,
+    /// Amount transferred per top-up
+    #[serde(default)]
+    pub top_up_amount: u64,
+    /// Rotate once the session is this old; never when unset
+    pub max_age_secs: Option<u64>,
+    /// Close the session when this flow completes
+    pub flow_id: Option<String>,
+}
+
+/// Account the session may borrow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BorrowableSpec {
+    pub address: Pubkey,
+    pub permissions: u8,
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Program the session may call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramSpec {
+    pub address: Pubkey,
+    #[serde(default)]
+    pub label: String,
+}
+
+impl SessionSpec {
+    fn validate(&self) -> Result<()> {
+        if self.borrowable.len() > MAX_REGISTERED_ACCOUNTS || self.programs.len() > MAX_REGISTERED_ACCOUNTS {
+            return Err(RuntimeError::InvalidConfiguration(format!(
+                "Session {} registers more than {} accounts or programs",
+                self.name, MAX_REGISTERED_ACCOUNTS
+            )));
+        }
+        if self.namespace.len() > NAMESPACE_CAPACITY {
+            return Err(RuntimeError::InvalidConfiguration(format!(
+                "Namespace of session {} is longer than {} bytes",
+                self.name, NAMESPACE_CAPACITY
+            )));
+        }
+        valence_kernel::NamespacePath::new(&self.namespace).map_err(|e| {
+            RuntimeError::InvalidConfiguration(format!("Invalid namespace {}: {}", self.namespace, e))
+        })?;
+        Ok(())
+    }
+
+    fn create_params(&self) -> CreateSessionParams {
+        // `validate` guarantees the namespace fits
+        let mut namespace_path = [0u8; NAMESPACE_CAPACITY];
+        let bytes = self.namespace.as_bytes();
+        namespace_path[..bytes.len()].copy_from_slice(bytes);
+
+        CreateSessionParams {
+            namespace_path,
+            namespace_path_len: bytes.len() as u16,
+            metadata: [0u8; 32],
+            parent_session: None,
+        }
+    }
+
+    fn registered_accounts(&self, addresses: &HashSet<Pubkey>) -> Vec<RegisteredAccount> {
+        self.borrowable
+            .iter()
+            .filter(|account| addresses.contains(&account.address))
+            .map(|account| RegisteredAccount {
+                address: account.address,
+                permissions: account.permissions,
+                label: label(&account.label),
+            })
+            .collect()
+    }
+
+    fn registered_programs(&self, addresses: &HashSet<Pubkey>) -> Vec<RegisteredProgram> {
+        self.programs
+            .iter()
+            .filter(|program| addresses.contains(&program.address))
+            .map(|program| RegisteredProgram {
+                address: program.address,
+                active: true,
+                label: label(&program.label),
+            })
+            .collect()
+    }
+
+    fn borrowable_set(&self) -> HashSet<Pubkey> {
+        self.borrowable.iter().map(|account| account.address).collect()
+    }
+
+    fn program_set(&self) -> HashSet<Pubkey> {
+        self.programs.iter().map(|program| program.address).collect()
+    }
+}
+
+/// Truncate a label to the kernel's fixed label size
+fn label(text: &str) -> [u8; 8] {
+    let mut label = [0u8; 8];
+    let len = text.len().min(label.len());
+    label[..len].copy_from_slice(&text.as_bytes()[..len]);
+    label
+}
+
+/// A session currently owned by the manager
+#[derive(Debug, Clone)]
+pub struct ManagedSession {
+    pub spec: SessionSpec,
+    pub session: Pubkey,
+    pub account_lookup: Pubkey,
+    pub guard: Pubkey,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Number of times the session has been rotated
+    pub generation: u32,
+    registered_accounts: HashSet<Pubkey>,
+    registered_programs: HashSet<Pubkey>,
+}
+
+// ================================
+// Backend
+// ================================
+
+/// Chain access used by the lifecycle manager
+#[async_trait]
+pub trait LifecycleBackend: Send + Sync {
+    /// Build, sign and submit a transaction; `new_accounts` co-sign as freshly created accounts
+    async fn submit(
+        &self,
+        payer: Pubkey,
+        instructions: Vec<Instruction>,
+        new_accounts: &[Arc<Keypair>],
+        description: String,
+    ) -> Result<Signature>;
+
+    async fn balance(&self, account: &Pubkey) -> Result<u64>;
+}
+
+/// Backend signing through a [`SigningService`] and submitting over RPC
+pub struct SigningLifecycleBackend {
+    rpc_client: Arc<RpcClient>,
+    signing_service: Arc<dyn SigningService>,
+}
+
+impl SigningLifecycleBackend {
+    pub fn new(rpc_client: Arc<RpcClient>, signing_service: Arc<dyn SigningService>) -> Self {
+        Self {
+            rpc_client,
+            signing_service,
+        }
+    }
+}
+
+#[async_trait]
+impl LifecycleBackend for SigningLifecycleBackend {
+    async fn submit(
+        &self,
+        payer: Pubkey,
+        instructions: Vec<Instruction>,
+        new_accounts: &[Arc<Keypair>],
+        description: String,
+    ) -> Result<Signature> {
+        let unsigned = TransactionBuilder::new(self.rpc_client.clone())
+            .add_instructions(instructions)
+            .with_fee_payer(payer)
+            .build(description.clone())
+            .await?;
+        let message_data = merge_signatures(&unsigned, &[])?.message_data();
+
+        let mut signatures: Vec<(Pubkey, Signature)> = new_accounts
+            .iter()
+            .map(|keypair| (keypair.pubkey(), keypair.sign_message(&message_data)))
+            .collect();
+
+        // Remaining signers (the owner) go through the signing service
+        let local: HashSet<Pubkey> = signatures.iter().map(|(pubkey, _)| *pubkey).collect();
+        let mut request = SigningRequest::new(unsigned.clone(), description, RiskLevel::Low);
+        request.required_signers.retain(|signer| !local.contains(signer));
+
+        match self.signing_service.sign_transaction(request).await?.result {
+            SigningResult::Signed {
+                signed_transaction, ..
+            } => {
+                let signed: Transaction = bincode::deserialize(&signed_transaction)
+                    .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+                signatures.extend(
+                    signed
+                        .message
+                        .account_keys
+                        .iter()
+                        .zip(&signed.signatures)
+                        .filter(|(key, signature)| {
+                            !local.contains(key) && **signature != Signature::default()
+                        })
+                        .map(|(key, signature)| (*key, *signature)),
+                );
+            }
+            SigningResult::Rejected { reason, .. } => {
+                return Err(RuntimeError::SecurityViolation(reason))
+            }
+            other => {
+                return Err(RuntimeError::SecurityViolation(format!(
+                    "Lifecycle transaction not signed: {:?}",
+                    other
+                )))
+            }
+        }
+
+        let transaction = merge_signatures(&unsigned, &signatures)?;
+        Ok(self.rpc_client.send_and_confirm_transaction(&transaction).await?)
+    }
+
+    async fn balance(&self, account: &Pubkey) -> Result<u64> {
+        Ok(self.rpc_client.get_balance(account).await?)
+    }
+}
+
+// ================================
+// Lifecycle Manager
+// ================================
+
+/// Lifecycle manager configuration
+#[derive(Debug, Clone)]
+pub struct LifecycleConfig {
+    /// Interval between top-up and rotation checks
+    pub check_interval: Duration,
+    /// Rotate this long before a session reaches its maximum age
+    pub rotation_margin_secs: u64,
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(60),
+            rotation_margin_secs: 300,
+        }
+    }
+}
+
+/// Creates, maintains, rotates and closes kernel sessions from specs
+pub struct SessionLifecycleManager {
+    backend: Arc<dyn LifecycleBackend>,
+    event_stream: Arc<EventStream>,
+    config: LifecycleConfig,
+    sessions: Arc<RwLock<HashMap<String, ManagedSession>>>,
+    shutdown_tx: broadcast::Sender<()>,
+    worker_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+}
+
+impl SessionLifecycleManager {
+    pub fn new(
+        backend: Arc<dyn LifecycleBackend>,
+        event_stream: Arc<EventStream>,
+        config: LifecycleConfig,
+    ) -> Self {
+        let (shutdown_tx, _) = broadcast::channel(16);
+
+        Self {
+            backend,
+            event_stream,
+            config,
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            shutdown_tx,
+            worker_handle: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Currently managed session for a spec name
+    pub async fn session(&self, name: &str) -> Option<ManagedSession> {
+        self.sessions.read().await.get(name).cloned()
+    }
+
+    /// Create the session for a spec, or bring an existing one in line with it
+    pub async fn apply(&self, spec: SessionSpec) -> Result<Pubkey> {
+        spec.validate()?;
+
+        let existing = self.sessions.read().await.get(&spec.name).cloned();
+        let managed = match existing {
+            Some(mut managed) => {
+                managed.spec = spec;
+                self.sync_registrations(&mut managed).await?;
+                managed
+            }
+            None => self.create(spec, 0).await?,
+        };
+
+        let session = managed.session;
+        self.sessions.write().await.insert(managed.spec.name.clone(), managed);
+        Ok(session)
+    }
+
+    /// Top up low balances and rotate sessions nearing their maximum age
+    pub async fn maintain(&self) -> Result<()> {
+        let managed: Vec<ManagedSession> = self.sessions.read().await.values().cloned().collect();
+
+        for session in managed {
+            if self.needs_rotation(&session) {
+                self.rotate(&session.spec.name).await?;
+            } else {
+                self.top_up(&session).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace a session with a fresh one and invalidate the old one
+    pub async fn rotate(&self, name: &str) -> Result<Pubkey> {
+        let previous = self.managed(name).await?;
+        let replacement = self.create(previous.spec.clone(), previous.generation + 1).await?;
+        let session = replacement.session;
+        self.sessions.write().await.insert(name.to_string(), replacement);
+
+        self.invalidate(&previous).await?;
+        self.emit(name, session, LifecycleStep::Rotated {
+            previous: previous.session,
+        })
+        .await;
+        Ok(session)
+    }
+
+    /// Invalidate and close a session, reclaiming its rent, and stop managing it
+    pub async fn close(&self, name: &str) -> Result<()> {
+        let managed = self.managed(name).await?;
+        let ixs = vec![
+            instructions::invalidate_session_instruction(managed.spec.owner, managed.session)?,
+            instructions::close_session_instruction(
+                managed.spec.owner,
+                managed.session,
+                managed.account_lookup,
+                managed.guard,
+            )?,
+        ];
+        self.backend
+            .submit(managed.spec.owner, ixs, &[], format!("Close session {}", name))
+            .await?;
+        self.sessions.write().await.remove(name);
+        self.emit(name, managed.session, LifecycleStep::Closed).await;
+        Ok(())
+    }
+
+    /// Close sessions whose flow has completed
+    pub async fn on_event(&self, event: &Event) -> Result<()> {
+        let Event::FlowCompleted { flow_id, .. } = event else {
+            return Ok(());
+        };
+
+        let finished: Vec<String> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|managed| managed.spec.flow_id.as_ref() == Some(flow_id))
+            .map(|managed| managed.spec.name.clone())
+            .collect();
+
+        for name in finished {
+            self.close(&name).await?;
+        }
+        Ok(())
+    }
+
+    /// Run maintenance and flow-completion handling until stopped
+    pub async fn start(self: &Arc<Self>) -> Result<()> {
+        info!("Starting session lifecycle manager");
+
+        let manager = Arc::clone(self);
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let mut events = self.event_stream.subscribe().await;
+
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(manager.config.check_interval);
+
+            loop {
+                let result = tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = interval.tick() => manager.maintain().await,
+                    event = events.recv() => match event {
+                        Ok(event) => manager.on_event(&event).await,
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!("Session lifecycle manager lagged by {} events", n);
+                            Ok(())
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+
+                if let Err(e) = result {
+                    manager
+                        .event_stream
+                        .emit(Event::Error {
+                            context: "session_lifecycle".to_string(),
+                            error: e.to_string(),
+                        })
+                        .await;
+                }
+            }
+        });
+
+        *self.worker_handle.write().await = Some(handle);
+        Ok(())
+    }
+
+    /// Stop the manager
+    pub async fn stop(&self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        if let Some(handle) = self.worker_handle.write().await.take() {
+            let _ = handle.await;
+        }
+        Ok(())
+    }
+
+    async fn managed(&self, name: &str) -> Result<ManagedSession> {
+        self.session(name).await.ok_or_else(|| {
+            RuntimeError::InvalidConfiguration(format!("No managed session named {}", name))
+        })
+    }
+
+    fn needs_rotation(&self, managed: &ManagedSession) -> bool {
+        let Some(max_age) = managed.spec.max_age_secs else {
+            return false;
+        };
+        let age = chrono::Utc::now()
+            .signed_duration_since(managed.created_at)
+            .num_seconds()
+            .max(0) as u64;
+        age + self.config.rotation_margin_secs >= max_age
+    }
+
+    async fn create(&self, spec: SessionSpec, generation: u32) -> Result<ManagedSession> {
+        let session = Arc::new(Keypair::new());
+        let account_lookup = Arc::new(Keypair::new());
+        let guard = Arc::new(Keypair::new());
+
+        let registered_accounts = spec.borrowable_set();
+        let registered_programs = spec.program_set();
+        let accounts = instructions::SessionAccounts {
+            owner: spec.owner,
+            session: session.pubkey(),
+            account_lookup: account_lookup.pubkey(),
+            guard: guard.pubkey(),
+        };
+
+        let ixs = vec![
+            instructions::create_guard_instruction(
+                spec.owner,
+                guard.pubkey(),
+                session.pubkey(),
+                spec.allow_unregistered_cpi,
+            )?,
+            instructions::create_session_instruction(
+                accounts,
+                spec.shard,
+                spec.create_params(),
+                spec.registered_accounts(&registered_accounts),
+                spec.registered_programs(&registered_programs),
+            )?,
+        ];
+        self.backend
+            .submit(
+                spec.owner,
+                ixs,
+                &[session.clone(), account_lookup.clone(), guard.clone()],
+                format!("Create session {}", spec.name),
+            )
+            .await?;
+
+        info!("Created session {} for {}", session.pubkey(), spec.name);
+        self.emit(&spec.name, session.pubkey(), LifecycleStep::Created).await;
+
+        Ok(ManagedSession {
+            spec,
+            session: session.pubkey(),
+            account_lookup: account_lookup.pubkey(),
+            guard: guard.pubkey(),
+            created_at: chrono::Utc::now(),
+            generation,
+            registered_accounts,
+            registered_programs,
+        })
+    }
+
+    async fn sync_registrations(&self, managed: &mut ManagedSession) -> Result<()> {
+        let desired_accounts = managed.spec.borrowable_set();
+        let desired_programs = managed.spec.program_set();
+
+        let added_accounts: HashSet<Pubkey> = desired_accounts
+            .difference(&managed.registered_accounts)
+            .copied()
+            .collect();
+        let added_programs: HashSet<Pubkey> = desired_programs
+            .difference(&managed.registered_programs)
+            .copied()
+            .collect();
+        let removed: Vec<Pubkey> = managed
+            .registered_accounts
+            .difference(&desired_accounts)
+            .chain(managed.registered_programs.difference(&desired_programs))
+            .copied()
+            .collect();
+
+        let added = added_accounts.len() + added_programs.len();
+        if added == 0 && removed.is_empty() {
+            return Ok(());
+        }
+
+        let instruction = instructions::manage_alt_instruction(
+            managed.spec.owner,
+            managed.session,
+            managed.account_lookup,
+            managed.spec.registered_accounts(&added_accounts),
+            managed.spec.registered_programs(&added_programs),
+            removed.clone(),
+        )?;
+        self.backend
+            .submit(
+                managed.spec.owner,
+                vec![instruction],
+                &[],
+                format!("Update registrations for session {}", managed.spec.name),
+            )
+            .await?;
+
+        managed.registered_accounts = desired_accounts;
+        managed.registered_programs = desired_programs;
+        self.emit(&managed.spec.name, managed.session, LifecycleStep::RegistrationsUpdated {
+            added,
+            removed: removed.len(),
+        })
+        .await;
+        Ok(())
+    }
+
+    async fn top_up(&self, managed: &ManagedSession) -> Result<()> {
+        let balance = self.backend.balance(&managed.session).await?;
+        if balance >= managed.spec.min_balance {
+            return Ok(());
+        }
+
+        let lamports = managed
+            .spec
+            .top_up_amount
+            .max(managed.spec.min_balance - balance);
+        let instruction =
+            instructions::transfer_instruction(managed.spec.owner, managed.session, lamports);
+        self.backend
+            .submit(
+                managed.spec.owner,
+                vec![instruction],
+                &[],
+                format!("Top up session {}", managed.spec.name),
+            )
+            .await?;
+
+        self.emit(&managed.spec.name, managed.session, LifecycleStep::ToppedUp { lamports })
+            .await;
+        Ok(())
+    }
+
+    async fn invalidate(&self, managed: &ManagedSession) -> Result<()> {
+        let instruction =
+            instructions::invalidate_session_instruction(managed.spec.owner, managed.session)?;
+        self.backend
+            .submit(
+                managed.spec.owner,
+                vec![instruction],
+                &[],
+                format!("Invalidate session {}", managed.spec.name),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn emit(&self, name: &str, session: Pubkey, step: LifecycleStep) {
+        self.event_stream
+            .emit(Event::SessionLifecycle {
+                name: name.to_string(),
+                session,
+                step,
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockBackend {
+        submitted: Mutex<Vec<(String, usize, usize)>>,
+        balances: Mutex<HashMap<Pubkey, u64>>,
+    }
+
+    #[async_trait]
+    impl LifecycleBackend for MockBackend {
+        async fn submit(
+            &self,
+            _payer: Pubkey,
+            instructions: Vec<Instruction>,
+            new_accounts: &[Arc<Keypair>],
+            description: String,
+        ) -> Result<Signature> {
+            self.submitted
+                .lock()
+                .await
+                .push((description, instructions.len(), new_accounts.len()));
+            Ok(Signature::default())
+        }
+
+        async fn balance(&self, account: &Pubkey) -> Result<u64> {
+            Ok(self.balances.lock().await.get(account).copied().unwrap_or(0))
+        }
+    }
+
+    fn spec(name: &str) -> SessionSpec {
+        SessionSpec {
+            name: name.to_string(),
+            owner: Pubkey::new_unique(),
+            shard: Pubkey::new_unique(),
+            namespace: "shard/flow".to_string(),
+            allow_unregistered_cpi: false,
+            borrowable: vec![BorrowableSpec {
+                address: Pubkey::new_unique(),
+                permissions: 1,
+                label: "vault".to_string(),
+            }],
+            programs: Vec::new(),
+            min_balance: 1_000,
+            top_up_amount: 5_000,
+            max_age_secs: None,
+            flow_id: Some("flow".to_string()),
+        }
+    }
+
+    fn manager(backend: Arc<MockBackend>) -> (SessionLifecycleManager, Arc<EventStream>) {
+        let event_stream = Arc::new(EventStream::new());
+        let manager = SessionLifecycleManager::new(
+            backend,
+            event_stream.clone(),
+            LifecycleConfig::default(),
+        );
+        (manager, event_stream)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_sync_registrations() {
+        let backend = Arc::new(MockBackend::default());
+        let (manager, _) = manager(backend.clone());
+
+        let mut desired = spec("vault");
+        let session = manager.apply(desired.clone()).await.unwrap();
+        assert_eq!(backend.submitted.lock().await[0].1, 2);
+        assert_eq!(backend.submitted.lock().await[0].2, 3);
+
+        // Re-applying an unchanged spec submits nothing
+        manager.apply(desired.clone()).await.unwrap();
+        assert_eq!(backend.submitted.lock().await.len(), 1);
+
+        desired.programs.push(ProgramSpec {
+            address: Pubkey::new_unique(),
+            label: "amm".to_string(),
+        });
+        assert_eq!(manager.apply(desired).await.unwrap(), session);
+        let submitted = backend.submitted.lock().await;
+        assert_eq!(submitted.len(), 2);
+        assert!(submitted[1].0.starts_with("Update registrations"));
+    }
+
+    #[tokio::test]
+    async fn test_top_up_and_rotation() {
+        let backend = Arc::new(MockBackend::default());
+        let (manager, event_stream) = manager(backend.clone());
+        let mut events = event_stream.subscribe().await;
+
+        let session = manager.apply(spec("vault")).await.unwrap();
+        manager.maintain().await.unwrap();
+        assert!(backend.submitted.lock().await[1].0.starts_with("Top up"));
+
+        let mut aged = spec("aged");
+        aged.max_age_secs = Some(60);
+        let old = manager.apply(aged).await.unwrap();
+        backend.balances.lock().await.insert(session, 10_000);
+        manager.maintain().await.unwrap();
+
+        let rotated = manager.session("aged").await.unwrap();
+        assert_ne!(rotated.session, old);
+        assert_eq!(rotated.generation, 1);
+
+        let mut steps = Vec::new();
+        while let Ok(Event::SessionLifecycle { step, .. }) = events.try_recv() {
+            steps.push(step);
+        }
+        assert!(steps.contains(&LifecycleStep::Rotated { previous: old }));
+        assert!(steps.contains(&LifecycleStep::ToppedUp { lamports: 5_000 }));
+    }
+
+    #[tokio::test]
+    async fn test_close_on_flow_completion() {
+        let backend = Arc::new(MockBackend::default());
+        let (manager, _) = manager(backend.clone());
+        manager.apply(spec("vault")).await.unwrap();
+
+        manager
+            .on_event(&Event::FlowCompleted {
+                flow_id: "other".to_string(),
+                instance_id: "1".to_string(),
+                success: true,
+                duration_ms: 1,
+            })
+            .await
+            .unwrap();
+        assert!(manager.session("vault").await.is_some());
+
+        manager
+            .on_event(&Event::FlowCompleted {
+                flow_id: "flow".to_string(),
+                instance_id: "1".to_string(),
+                success: true,
+                duration_ms: 1,
+            })
+            .await
+            .unwrap();
+        assert!(manager.session("vault").await.is_none());
+        let submitted = backend.submitted.lock().await;
+        let (description, instruction_count, _) = submitted.last().unwrap();
+        assert!(description.starts_with("Close session"));
+        assert_eq!(*instruction_count, 2);
+    }
+
+    #[test]
+    fn test_spec_validation() {
+        let mut too_many = spec("vault");
+        too_many.programs = (0..=MAX_REGISTERED_ACCOUNTS)
+            .map(|_| ProgramSpec {
+                address: Pubkey::new_unique(),
+                label: String::new(),
+            })
+            .collect();
+        assert!(too_many.validate().is_err());
+
+        let mut bad_namespace = spec("vault");
+        bad_namespace.namespace = "/absolute".to_string();
+        assert!(bad_namespace.validate().is_err());
+
+        let mut long_namespace = spec("vault");
+        long_namespace.namespace = format!("shard/{}", "a".repeat(NAMESPACE_CAPACITY));
+        assert!(long_namespace.validate().is_err());
+        long_namespace.namespace.truncate(NAMESPACE_CAPACITY);
+        long_namespace.validate().unwrap();
+        assert_eq!(long_namespace.create_params().namespace_path_len as usize, NAMESPACE_CAPACITY);
+    }
+}
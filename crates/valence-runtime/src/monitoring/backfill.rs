@@ -0,0 +1,553 @@
+//! Historical backfill of events missed while the runtime was offline
+
+use crate::{
+    monitoring::event_stream::{Event, EventStream},
+    Result, RuntimeError, DEFAULT_CLUSTER,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use std::{
+    collections::{HashSet, VecDeque},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{debug, info, warn};
+
+// ================================
+// Checkpoint Storage
+// ================================
+
+/// Persisted record of the last processed slot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub slot: u64,
+}
+
+/// Checkpoint storage trait
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    async fn load(&self) -> Result<Option<Checkpoint>>;
+    async fn save(&self, checkpoint: Checkpoint) -> Result<()>;
+}
+
+/// In-memory checkpoint storage
+#[derive(Default)]
+pub struct MemoryCheckpointStore {
+    checkpoint: RwLock<Option<Checkpoint>>,
+}
+
+impl MemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpointStore {
+    async fn load(&self) -> Result<Option<Checkpoint>> {
+        Ok(*self.checkpoint.read().await)
+    }
+
+    async fn save(&self, checkpoint: Checkpoint) -> Result<()> {
+        *self.checkpoint.write().await = Some(checkpoint);
+        Ok(())
+    }
+}
+
+/// File-based checkpoint storage
+pub struct FileCheckpointStore {
+    path: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub async fn new(path: PathBuf) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        Ok(Self { path })
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn load(&self) -> Result<Option<Checkpoint>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, checkpoint: Checkpoint) -> Result<()> {
+        // Write to a temporary file first so a crash never leaves a torn checkpoint
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(&checkpoint)?).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+// ================================
+// Backfill Service
+// ================================
+
+/// Backfill configuration
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Page size for signature queries
+    pub page_size: usize,
+
+    /// Upper bound on signatures replayed per address in one pass
+    ///
+    /// Longer gaps are replayed oldest first over several passes.
+    pub max_signatures_per_address: usize,
+
+    /// Commitment used for history queries
+    pub commitment: CommitmentConfig,
+
+    /// How often slots observed on the event stream are persisted
+    pub checkpoint_interval: Duration,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 1000,
+            max_signatures_per_address: 10_000,
+            commitment: CommitmentConfig::confirmed(),
+            checkpoint_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Summary of a backfill run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    pub from_slot: Option<u64>,
+    pub to_slot: Option<u64>,
+    pub events_emitted: usize,
+}
+
+/// Replays transactions missed since the last checkpoint
+pub struct BackfillService {
+    rpc_client: Arc<RpcClient>,
+    store: Arc<dyn CheckpointStore>,
+    event_stream: Arc<EventStream>,
+    config: BackfillConfig,
+}
+
+impl BackfillService {
+    /// Create a new backfill service
+    pub fn new(
+        rpc_client: Arc<RpcClient>,
+        store: Arc<dyn CheckpointStore>,
+        event_stream: Arc<EventStream>,
+        config: BackfillConfig,
+    ) -> Self {
+        Self {
+            rpc_client,
+            store,
+            event_stream,
+            config,
+        }
+    }
+
+    /// Replay transactions for the given addresses since the last checkpoint.
+    ///
+    /// Without a checkpoint there is no known gap, so the current slot is
+    /// recorded as the starting point and nothing is replayed. Gaps longer
+    /// than one pass are replayed oldest first until caught up.
+    pub async fn backfill(&self, addresses: &[Pubkey]) -> Result<BackfillReport> {
+        let Some(checkpoint) = self.store.load().await? else {
+            let slot = self
+                .rpc_client
+                .get_slot_with_commitment(self.config.commitment)
+                .await?;
+            info!("No backfill checkpoint found, starting from slot {}", slot);
+            self.store.save(Checkpoint { slot }).await?;
+            return Ok(BackfillReport {
+                from_slot: None,
+                to_slot: Some(slot),
+                events_emitted: 0,
+            });
+        };
+
+        info!(
+            "Backfilling {} addresses since slot {}",
+            addresses.len(),
+            checkpoint.slot
+        );
+
+        let mut report = BackfillReport {
+            from_slot: Some(checkpoint.slot),
+            ..Default::default()
+        };
+        let mut from_slot = checkpoint.slot;
+
+        loop {
+            let mut statuses = Vec::new();
+            // Slot up to which every address's history has been fetched
+            let mut complete_through = None;
+            for address in addresses {
+                let page = self.fetch_since(address, from_slot).await?;
+                if let Some(slot) = page.complete_through {
+                    complete_through = Some(complete_through.map_or(slot, |s: u64| s.min(slot)));
+                }
+                statuses.extend(page.statuses);
+            }
+
+            let mut missed = collect_missed(statuses, from_slot);
+            if let Some(slot) = complete_through {
+                missed.retain(|status| status.slot <= slot);
+            }
+
+            for status in &missed {
+                self.event_stream
+                    .emit(Event::TransactionConfirmed {
+                        signature: status.signature.clone(),
+                        slot: status.slot,
+                        error: status.err.as_ref().map(ToString::to_string),
+                    })
+                    .await;
+            }
+            report.events_emitted += missed.len();
+
+            // A truncated pass only advances to the slot it fully replayed
+            let Some(slot) = complete_through.or(missed.last().map(|status| status.slot)) else {
+                break;
+            };
+            self.record_slot(slot).await?;
+            report.to_slot = Some(slot);
+
+            if complete_through.is_none() {
+                break;
+            }
+            info!("Backfill replayed through slot {}, continuing", slot);
+            from_slot = slot;
+        }
+
+        Ok(report)
+    }
+
+    /// Advance the checkpoint if the slot is newer than the stored one
+    pub async fn record_slot(&self, slot: u64) -> Result<()> {
+        match self.store.load().await? {
+            Some(checkpoint) if checkpoint.slot >= slot => Ok(()),
+            _ => self.store.save(Checkpoint { slot }).await,
+        }
+    }
+
+    /// Persist slots observed on the event stream until shutdown
+    ///
+    /// The newest slot is written once per checkpoint interval and on
+    /// shutdown rather than on every event.
+    pub async fn track(
+        self: Arc<Self>,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) -> JoinHandle<()> {
+        let mut receiver = self.event_stream.subscribe().await;
+
+        tokio::spawn(async move {
+            let mut flush = tokio::time::interval(self.config.checkpoint_interval);
+            let mut pending: Option<u64> = None;
+
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = flush.tick() => self.flush_slot(&mut pending).await,
+                    event = receiver.recv() => match event {
+                        Ok(event) => observe_slot(&mut pending, &event),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            debug!("Checkpoint tracker lagged by {} events", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+            }
+
+            // Events already delivered before shutdown still count
+            while let Ok(event) = receiver.try_recv() {
+                observe_slot(&mut pending, &event);
+            }
+            self.flush_slot(&mut pending).await;
+        })
+    }
+
+    /// Persist a slot the tracker has observed but not yet written
+    async fn flush_slot(&self, pending: &mut Option<u64>) {
+        if let Some(slot) = pending.take() {
+            if let Err(e) = self.record_slot(slot).await {
+                warn!("Failed to persist checkpoint: {}", e);
+                *pending = Some(slot);
+            }
+        }
+    }
+
+    /// Page backwards through an address's history down to the checkpoint slot
+    ///
+    /// Only the oldest `max_signatures_per_address` statuses are kept, so a
+    /// long gap is replayed from its start.
+    async fn fetch_since(&self, address: &Pubkey, slot: u64) -> Result<HistoryPage> {
+        // Newest first, so the oldest statuses sit at the back
+        let mut collected = VecDeque::new();
+        let mut truncated = false;
+        let mut before = None;
+
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    address,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: Some(self.config.page_size),
+                        commitment: Some(self.config.commitment),
+                    },
+                )
+                .await?;
+
+            let page_len = page.len();
+            let reached_checkpoint = page.iter().any(|status| status.slot <= slot);
+            before = page
+                .last()
+                .map(|status| Signature::from_str(&status.signature))
+                .transpose()
+                .map_err(|e| RuntimeError::StateValidationFailed(e.to_string()))?;
+
+            collected.extend(page.into_iter().filter(|status| status.slot > slot));
+            while collected.len() > self.config.max_signatures_per_address {
+                collected.pop_front();
+                truncated = true;
+            }
+
+            if reached_checkpoint || page_len < self.config.page_size {
+                break;
+            }
+        }
+
+        let statuses: Vec<_> = collected.into_iter().rev().collect();
+        if !truncated {
+            return Ok(HistoryPage {
+                statuses,
+                complete_through: None,
+            });
+        }
+
+        warn!(
+            "Backfill for {} limited to its oldest {} signatures this pass",
+            address, self.config.max_signatures_per_address
+        );
+        Ok(split_truncated(statuses))
+    }
+}
+
+/// Statuses fetched for one address in one backfill pass
+#[derive(Debug, Default, PartialEq, Eq)]
+struct HistoryPage {
+    /// Statuses after the checkpoint, oldest first
+    statuses: Vec<RpcConfirmedTransactionStatusWithSignature>,
+
+    /// Newest slot whose statuses were all fetched, when newer ones were cut off
+    complete_through: Option<u64>,
+}
+
+/// Bound a truncated history to the slots it holds completely
+///
+/// The newest slot kept may have lost statuses to truncation, so it is left
+/// for the next pass unless it is the only slot fetched.
+fn split_truncated(mut statuses: Vec<RpcConfirmedTransactionStatusWithSignature>) -> HistoryPage {
+    let Some(newest) = statuses.last().map(|status| status.slot) else {
+        return HistoryPage::default();
+    };
+    let complete = statuses.iter().rposition(|status| status.slot < newest);
+    let complete_through = match complete {
+        Some(index) => {
+            statuses.truncate(index + 1);
+            statuses[index].slot
+        }
+        None => newest,
+    };
+
+    HistoryPage {
+        statuses,
+        complete_through: Some(complete_through),
+    }
+}
+
+/// Keep statuses after the checkpoint, deduplicated and in slot order
+pub fn collect_missed(
+    statuses: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    checkpoint_slot: u64,
+) -> Vec<RpcConfirmedTransactionStatusWithSignature> {
+    let mut seen = HashSet::new();
+    let mut missed: Vec<_> = statuses
+        .into_iter()
+        .filter(|status| status.slot > checkpoint_slot)
+        .filter(|status| seen.insert(status.signature.clone()))
+        .collect();
+
+    missed.sort_by_key(|status| status.slot);
+    missed
+}
+
+/// Keep the newest slot observed by the tracker
+fn observe_slot(pending: &mut Option<u64>, event: &Event) {
+    if let Some(slot) = event_slot(event) {
+        *pending = Some(pending.map_or(slot, |pending| pending.max(slot)));
+    }
+}
+
+/// Slot carried by an event from the default cluster, if any
+///
+/// The checkpoint tracks the default cluster's RPC, so updates tagged by
+/// other clusters' monitors would advance it past slots never replayed.
+fn event_slot(event: &Event) -> Option<u64> {
+    match event {
+        Event::StateUpdate(update)
+            if update
+                .cluster
+                .as_deref()
+                .is_none_or(|cluster| cluster == DEFAULT_CLUSTER) =>
+        {
+            Some(update.slot)
+        }
+        Event::TransactionConfirmed { slot, .. } => Some(*slot),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::state_monitor::StateUpdate;
+
+    fn status(signature: &str, slot: u64) -> RpcConfirmedTransactionStatusWithSignature {
+        RpcConfirmedTransactionStatusWithSignature {
+            signature: signature.to_string(),
+            slot,
+            err: None,
+            memo: None,
+            block_time: None,
+            confirmation_status: None,
+        }
+    }
+
+    #[test]
+    fn test_collect_missed() {
+        let statuses = vec![
+            status("c", 30),
+            status("a", 10),
+            status("b", 20),
+            status("c", 30),
+            status("old", 5),
+        ];
+
+        let missed = collect_missed(statuses, 5);
+        let signatures: Vec<_> = missed.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(signatures, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_split_truncated() {
+        let page = split_truncated(vec![
+            status("a", 10),
+            status("b", 20),
+            status("c", 30),
+            status("d", 30),
+        ]);
+        let signatures: Vec<_> = page.statuses.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(signatures, vec!["a", "b"]);
+        assert_eq!(page.complete_through, Some(20));
+
+        // A single slot larger than a pass is replayed whole
+        let page = split_truncated(vec![status("a", 10), status("b", 10)]);
+        assert_eq!(page.statuses.len(), 2);
+        assert_eq!(page.complete_through, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_tracker_flushes_on_shutdown() {
+        let rpc_client = Arc::new(RpcClient::new("http://127.0.0.1:8899".to_string()));
+        let store = Arc::new(MemoryCheckpointStore::new());
+        let event_stream = Arc::new(EventStream::new());
+        let service = Arc::new(BackfillService::new(
+            rpc_client,
+            store.clone(),
+            event_stream.clone(),
+            BackfillConfig {
+                checkpoint_interval: Duration::from_secs(3600),
+                ..Default::default()
+            },
+        ));
+
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let tracker = service.track(shutdown_rx).await;
+        for slot in [10, 30, 20] {
+            event_stream
+                .emit(Event::TransactionConfirmed {
+                    signature: slot.to_string(),
+                    slot,
+                    error: None,
+                })
+                .await;
+        }
+        // Slots of other clusters are not part of this checkpoint
+        event_stream
+            .for_cluster("devnet")
+            .emit(Event::StateUpdate(StateUpdate {
+                account: Pubkey::new_unique(),
+                slot: 50,
+                lamports: 0,
+                data: vec![],
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+                cluster: None,
+            }))
+            .await;
+        shutdown_tx.send(()).unwrap();
+        tracker.await.unwrap();
+
+        assert_eq!(store.load().await.unwrap(), Some(Checkpoint { slot: 30 }));
+    }
+
+    #[tokio::test]
+    async fn test_record_slot_only_advances() {
+        let rpc_client = Arc::new(RpcClient::new("http://127.0.0.1:8899".to_string()));
+        let store = Arc::new(MemoryCheckpointStore::new());
+        let service = BackfillService::new(
+            rpc_client,
+            store.clone(),
+            Arc::new(EventStream::new()),
+            BackfillConfig::default(),
+        );
+
+        service.record_slot(100).await.unwrap();
+        service.record_slot(50).await.unwrap();
+        assert_eq!(store.load().await.unwrap(), Some(Checkpoint { slot: 100 }));
+    }
+
+    #[tokio::test]
+    async fn test_file_checkpoint_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCheckpointStore::new(dir.path().join("checkpoint.json"))
+            .await
+            .unwrap();
+
+        assert_eq!(store.load().await.unwrap(), None);
+        store.save(Checkpoint { slot: 42 }).await.unwrap();
+        assert_eq!(store.load().await.unwrap(), Some(Checkpoint { slot: 42 }));
+    }
+}
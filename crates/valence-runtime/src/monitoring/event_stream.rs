@@ -1,5 +1,6 @@
 //! State change event streaming
 
+use crate::coordination::BudgetScope;
 use crate::monitoring::state_monitor::StateUpdate;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -20,6 +21,7 @@ pub enum Event {
 
     /// Flow step completed
     FlowStepCompleted {
+        flow_id: String,
         instance_id: String,
         step_name: String,
         success: bool,
@@ -27,11 +29,21 @@ pub enum Event {
 
     /// Flow execution completed
     FlowCompleted {
+        flow_id: String,
         instance_id: String,
         success: bool,
         duration_ms: u64,
     },
 
+    /// Flow execution refused further transactions over its budget
+    BudgetExceeded {
+        flow_id: String,
+        instance_id: String,
+        scope: BudgetScope,
+        spent: u64,
+        limit: u64,
+    },
+
     /// Transaction built
     TransactionBuilt {
         description: String,
@@ -52,6 +64,25 @@ pub enum Event {
         error: Option<String>,
     },
 
+    /// Transaction blockhash expired before confirmation
+    TransactionExpired {
+        signature: String,
+        description: String,
+    },
+
+    /// Bundle accepted by the block engine
+    BundleSubmitted {
+        bundle_id: String,
+        signatures: Vec<String>,
+        description: String,
+    },
+
+    /// Bundle landed on chain
+    BundleLanded { bundle_id: String, slot: u64 },
+
+    /// Bundle failed or did not land in time
+    BundleFailed { bundle_id: String, error: String },
+
     /// Audit log entry
     AuditLog {
         operation: String,
@@ -78,12 +109,26 @@ pub enum Event {
         namespace: String,
         owner: solana_sdk::pubkey::Pubkey,
     },
+
+    /// Tracked session failed a health check
+    SessionUnhealthy {
+        session: solana_sdk::pubkey::Pubkey,
+        reason: crate::types::UnhealthyReason,
+    },
+
+    /// Managed session advanced through its lifecycle
+    SessionLifecycle {
+        name: String,
+        session: solana_sdk::pubkey::Pubkey,
+        step: crate::types::LifecycleStep,
+    },
 }
 
 /// Event stream for broadcasting runtime events
 pub struct EventStream {
     sender: broadcast::Sender<Event>,
     receiver_count: Arc<RwLock<usize>>,
+    cluster: Option<String>,
 }
 
 impl EventStream {
@@ -94,17 +139,46 @@ impl EventStream {
         Self {
             sender,
             receiver_count: Arc::new(RwLock::new(0)),
+            cluster: None,
+        }
+    }
+
+    /// Handle onto the same stream that tags emitted state updates with a cluster name
+    pub fn for_cluster(&self, cluster: impl Into<String>) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            receiver_count: self.receiver_count.clone(),
+            cluster: Some(cluster.into()),
         }
     }
 
+    /// Cluster this handle tags events with
+    pub fn cluster(&self) -> Option<&str> {
+        self.cluster.as_deref()
+    }
+
     /// Subscribe to events
     pub async fn subscribe(&self) -> broadcast::Receiver<Event> {
         *self.receiver_count.write().await += 1;
         self.sender.subscribe()
     }
 
+    /// Subscribe to events passing a filter
+    pub async fn subscribe_filtered(&self, filter: EventFilter) -> FilteredEventStream {
+        FilteredEventStream::new(self.subscribe().await, filter)
+    }
+
+    /// Subscribe to a typed group of events
+    pub async fn subscribe_to<G: EventGroup>(&self) -> TypedEventStream<G> {
+        TypedEventStream::new(self.subscribe_filtered(G::filter()).await)
+    }
+
     /// Emit an event
-    pub async fn emit(&self, event: Event) {
+    pub async fn emit(&self, mut event: Event) {
+        if let (Some(cluster), Event::StateUpdate(update)) = (&self.cluster, &mut event) {
+            update.cluster.get_or_insert_with(|| cluster.clone());
+        }
+
         debug!("Emitting event: {:?}", event);
 
         match self.sender.send(event) {
@@ -130,7 +204,11 @@ impl Default for EventStream {
     }
 }
 
-/// Event filter for selective subscription
+/// Event filter for selective subscription.
+///
+/// The `include_*` flags select event kinds. Each identifier filter that is
+/// set admits only events carrying one of its identifiers, so events without
+/// that identifier are excluded.
 #[derive(Debug, Clone)]
 pub struct EventFilter {
     pub include_state_updates: bool,
@@ -140,6 +218,10 @@ pub struct EventFilter {
     pub include_errors: bool,
     pub include_warnings: bool,
     pub account_filter: Option<Vec<solana_sdk::pubkey::Pubkey>>,
+    pub program_filter: Option<Vec<solana_sdk::pubkey::Pubkey>>,
+    pub session_filter: Option<Vec<solana_sdk::pubkey::Pubkey>>,
+    pub flow_filter: Option<Vec<String>>,
+    pub cluster_filter: Option<Vec<String>>,
 }
 
 impl Default for EventFilter {
@@ -152,31 +234,82 @@ impl Default for EventFilter {
             include_errors: true,
             include_warnings: true,
             account_filter: None,
+            program_filter: None,
+            session_filter: None,
+            flow_filter: None,
+            cluster_filter: None,
         }
     }
 }
 
 impl EventFilter {
+    /// Filter that excludes every event kind
+    pub fn none() -> Self {
+        Self {
+            include_state_updates: false,
+            include_flow_events: false,
+            include_transaction_events: false,
+            include_audit_logs: false,
+            include_errors: false,
+            include_warnings: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn state_updates(mut self) -> Self { self.include_state_updates = true; self }
+    pub fn flow_events(mut self) -> Self { self.include_flow_events = true; self }
+    pub fn transaction_events(mut self) -> Self { self.include_transaction_events = true; self }
+    pub fn audit_logs(mut self) -> Self { self.include_audit_logs = true; self }
+    pub fn errors(mut self) -> Self { self.include_errors = true; self }
+    pub fn warnings(mut self) -> Self { self.include_warnings = true; self }
+
+    pub fn accounts(mut self, accounts: Vec<solana_sdk::pubkey::Pubkey>) -> Self {
+        self.account_filter = Some(accounts);
+        self
+    }
+
+    pub fn programs(mut self, programs: Vec<solana_sdk::pubkey::Pubkey>) -> Self {
+        self.program_filter = Some(programs);
+        self
+    }
+
+    pub fn sessions(mut self, sessions: Vec<solana_sdk::pubkey::Pubkey>) -> Self {
+        self.session_filter = Some(sessions);
+        self
+    }
+
+    pub fn flows(mut self, flow_ids: Vec<String>) -> Self {
+        self.flow_filter = Some(flow_ids);
+        self
+    }
+
+    pub fn clusters(mut self, clusters: Vec<String>) -> Self {
+        self.cluster_filter = Some(clusters);
+        self
+    }
+
     /// Check if an event passes the filter
     pub fn matches(&self, event: &Event) -> bool {
+        self.matches_kind(event) && self.matches_identifiers(event)
+    }
+
+    /// Check the event kind against the include flags
+    fn matches_kind(&self, event: &Event) -> bool {
         match event {
-            Event::StateUpdate(update) => {
-                if !self.include_state_updates {
-                    return false;
-                }
-                if let Some(accounts) = &self.account_filter {
-                    accounts.contains(&update.account)
-                } else {
-                    true
-                }
-            }
+            Event::StateUpdate(_) => self.include_state_updates,
+
             Event::FlowStarted { .. }
             | Event::FlowStepCompleted { .. }
-            | Event::FlowCompleted { .. } => self.include_flow_events,
+            | Event::FlowCompleted { .. }
+            | Event::BudgetExceeded { .. } => self.include_flow_events,
 
             Event::TransactionBuilt { .. }
             | Event::TransactionSubmitted { .. }
-            | Event::TransactionConfirmed { .. } => self.include_transaction_events,
+            | Event::TransactionConfirmed { .. }
+            | Event::TransactionExpired { .. }
+            | Event::BundleSubmitted { .. }
+            | Event::BundleLanded { .. }
+            | Event::BundleFailed { .. } => self.include_transaction_events,
 
             Event::AuditLog { .. } => self.include_audit_logs,
 
@@ -184,8 +317,52 @@ impl EventFilter {
             Event::Warning { .. } => self.include_warnings,
             Event::ChildAccountCreated { .. } => self.include_transaction_events,
             Event::SessionCreationRequested { .. } => self.include_flow_events,
+            Event::SessionUnhealthy { .. } => self.include_warnings,
+            Event::SessionLifecycle { .. } => self.include_flow_events,
         }
     }
+
+    /// Check identifiers carried by the event against the identifier filters
+    fn matches_identifiers(&self, event: &Event) -> bool {
+        let (account, program, session, flow, cluster) = match event {
+            Event::StateUpdate(update) => (
+                Some(&update.account),
+                Some(&update.owner),
+                None,
+                None,
+                update.cluster.as_ref(),
+            ),
+            Event::FlowStarted { flow_id, .. }
+            | Event::FlowStepCompleted { flow_id, .. }
+            | Event::FlowCompleted { flow_id, .. }
+            | Event::BudgetExceeded { flow_id, .. } => (None, None, None, Some(flow_id), None),
+            Event::ChildAccountCreated {
+                session,
+                child_account,
+                owner_program,
+                ..
+            } => (Some(child_account), Some(owner_program), Some(session), None, None),
+            Event::SessionUnhealthy { session, .. }
+            | Event::SessionLifecycle { session, .. } => (None, None, Some(session), None, None),
+            _ => (None, None, None, None, None),
+        };
+
+        admits(&self.account_filter, account)
+            && admits(&self.program_filter, program)
+            && admits(&self.session_filter, session)
+            && admits(&self.flow_filter, flow)
+            && admits(&self.cluster_filter, cluster)
+    }
+}
+
+/// Whether an optional allow-list admits an event's identifier
+///
+/// A set filter excludes events that do not carry the identifier.
+fn admits<T: PartialEq>(filter: &Option<Vec<T>>, value: Option<&T>) -> bool {
+    match filter {
+        Some(allowed) => value.is_some_and(|value| allowed.contains(value)),
+        None => true,
+    }
 }
 
 /// Filtered event stream wrapper
@@ -222,6 +399,225 @@ impl FilteredEventStream {
     }
 }
 
+/// Typed group of events that can be subscribed to as a unit
+pub trait EventGroup: Sized {
+    /// Filter selecting the events in this group
+    fn filter() -> EventFilter;
+
+    /// Convert a runtime event into the typed representation
+    fn from_event(event: Event) -> Option<Self>;
+}
+
+/// Flow lifecycle events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowEvents {
+    Started {
+        flow_id: String,
+        instance_id: String,
+    },
+    StepCompleted {
+        flow_id: String,
+        instance_id: String,
+        step_name: String,
+        success: bool,
+    },
+    Completed {
+        flow_id: String,
+        instance_id: String,
+        success: bool,
+        duration_ms: u64,
+    },
+    BudgetExceeded {
+        flow_id: String,
+        instance_id: String,
+        scope: BudgetScope,
+        spent: u64,
+        limit: u64,
+    },
+}
+
+impl EventGroup for FlowEvents {
+    fn filter() -> EventFilter {
+        EventFilter::none().flow_events()
+    }
+
+    fn from_event(event: Event) -> Option<Self> {
+        match event {
+            Event::FlowStarted { flow_id, instance_id } => Some(Self::Started {
+                flow_id,
+                instance_id,
+            }),
+            Event::FlowStepCompleted {
+                flow_id,
+                instance_id,
+                step_name,
+                success,
+            } => Some(Self::StepCompleted {
+                flow_id,
+                instance_id,
+                step_name,
+                success,
+            }),
+            Event::FlowCompleted {
+                flow_id,
+                instance_id,
+                success,
+                duration_ms,
+            } => Some(Self::Completed {
+                flow_id,
+                instance_id,
+                success,
+                duration_ms,
+            }),
+            Event::BudgetExceeded {
+                flow_id,
+                instance_id,
+                scope,
+                spent,
+                limit,
+            } => Some(Self::BudgetExceeded {
+                flow_id,
+                instance_id,
+                scope,
+                spent,
+                limit,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Transaction lifecycle events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionEvents {
+    Built {
+        description: String,
+        signers: Vec<solana_sdk::pubkey::Pubkey>,
+        compute_units: Option<u32>,
+    },
+    Submitted {
+        signature: String,
+        description: String,
+    },
+    Confirmed {
+        signature: String,
+        slot: u64,
+        error: Option<String>,
+    },
+    Expired {
+        signature: String,
+        description: String,
+    },
+    BundleSubmitted {
+        bundle_id: String,
+        signatures: Vec<String>,
+        description: String,
+    },
+    BundleLanded {
+        bundle_id: String,
+        slot: u64,
+    },
+    BundleFailed {
+        bundle_id: String,
+        error: String,
+    },
+}
+
+impl EventGroup for TransactionEvents {
+    fn filter() -> EventFilter {
+        EventFilter::none().transaction_events()
+    }
+
+    fn from_event(event: Event) -> Option<Self> {
+        match event {
+            Event::TransactionBuilt {
+                description,
+                signers,
+                compute_units,
+            } => Some(Self::Built {
+                description,
+                signers,
+                compute_units,
+            }),
+            Event::TransactionSubmitted {
+                signature,
+                description,
+            } => Some(Self::Submitted {
+                signature,
+                description,
+            }),
+            Event::TransactionConfirmed {
+                signature,
+                slot,
+                error,
+            } => Some(Self::Confirmed {
+                signature,
+                slot,
+                error,
+            }),
+            Event::TransactionExpired {
+                signature,
+                description,
+            } => Some(Self::Expired {
+                signature,
+                description,
+            }),
+            Event::BundleSubmitted {
+                bundle_id,
+                signatures,
+                description,
+            } => Some(Self::BundleSubmitted {
+                bundle_id,
+                signatures,
+                description,
+            }),
+            Event::BundleLanded { bundle_id, slot } => Some(Self::BundleLanded { bundle_id, slot }),
+            Event::BundleFailed { bundle_id, error } => Some(Self::BundleFailed { bundle_id, error }),
+            _ => None,
+        }
+    }
+}
+
+/// State updates from on-chain monitoring
+impl EventGroup for StateUpdate {
+    fn filter() -> EventFilter {
+        EventFilter::none().state_updates()
+    }
+
+    fn from_event(event: Event) -> Option<Self> {
+        match event {
+            Event::StateUpdate(update) => Some(update),
+            _ => None,
+        }
+    }
+}
+
+/// Event stream yielding a typed event group
+pub struct TypedEventStream<G: EventGroup> {
+    inner: FilteredEventStream,
+    _group: std::marker::PhantomData<G>,
+}
+
+impl<G: EventGroup> TypedEventStream<G> {
+    /// Create a typed event stream over a filtered stream
+    pub fn new(inner: FilteredEventStream) -> Self {
+        Self {
+            inner,
+            _group: std::marker::PhantomData,
+        }
+    }
+
+    /// Receive next typed event
+    pub async fn recv(&mut self) -> Option<G> {
+        loop {
+            let event = self.inner.recv().await?;
+            if let Some(typed) = G::from_event(event) {
+                return Some(typed);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,6 +685,7 @@ mod tests {
             owner: Pubkey::new_unique(),
             executable: false,
             rent_epoch: 0,
+            cluster: None,
         });
 
         let update2 = Event::StateUpdate(StateUpdate {
@@ -299,9 +696,170 @@ mod tests {
             owner: Pubkey::new_unique(),
             executable: false,
             rent_epoch: 0,
+            cluster: None,
         });
 
         assert!(filter.matches(&update1));
         assert!(!filter.matches(&update2));
     }
+
+    #[test]
+    fn test_identifier_filters() {
+        let program = Pubkey::new_unique();
+        let session = Pubkey::new_unique();
+
+        let child = |session, owner_program| Event::ChildAccountCreated {
+            session,
+            child_account: Pubkey::new_unique(),
+            namespace_suffix: "child".to_string(),
+            owner_program,
+        };
+        let filter = EventFilter::default()
+            .programs(vec![program])
+            .sessions(vec![session]);
+        assert!(filter.matches(&child(session, program)));
+        assert!(!filter.matches(&child(Pubkey::new_unique(), program)));
+        assert!(!filter.matches(&child(session, Pubkey::new_unique())));
+
+        let flow = |flow_id: &str| Event::FlowStarted {
+            flow_id: flow_id.to_string(),
+            instance_id: "instance".to_string(),
+        };
+        let filter = EventFilter::default().flows(vec!["flow-a".to_string()]);
+        assert!(filter.matches(&flow("flow-a")));
+        assert!(!filter.matches(&flow("flow-b")));
+    }
+
+    #[test]
+    fn test_identifier_filters_exclude_events_without_identifier() {
+        let session = Pubkey::new_unique();
+        let filter = EventFilter::default().sessions(vec![session]);
+
+        assert!(!filter.matches(&Event::Warning {
+            context: "test".to_string(),
+            message: "test warning".to_string(),
+        }));
+        assert!(!filter.matches(&Event::FlowStarted {
+            flow_id: "flow".to_string(),
+            instance_id: "instance".to_string(),
+        }));
+
+        // State updates carry no session, even for the session's own account
+        assert!(!filter.matches(&Event::StateUpdate(StateUpdate {
+            account: session,
+            slot: 100,
+            lamports: 1000,
+            data: vec![],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+            cluster: None,
+        })));
+
+        // Updates without a cluster tag do not match a cluster filter
+        let filter = EventFilter::default().clusters(vec!["mainnet".to_string()]);
+        assert!(!filter.matches(&Event::StateUpdate(StateUpdate {
+            account: Pubkey::new_unique(),
+            slot: 100,
+            lamports: 1000,
+            data: vec![],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+            cluster: None,
+        })));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_filtered() {
+        let stream = EventStream::new();
+        let mut filtered = stream
+            .subscribe_filtered(EventFilter::none().errors())
+            .await;
+
+        stream
+            .emit(Event::Warning {
+                context: "test".to_string(),
+                message: "ignored".to_string(),
+            })
+            .await;
+        stream
+            .emit(Event::Error {
+                context: "test".to_string(),
+                error: "kept".to_string(),
+            })
+            .await;
+
+        match filtered.recv().await {
+            Some(Event::Error { error, .. }) => assert_eq!(error, "kept"),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_to_flow_events() {
+        let stream = EventStream::new();
+        let mut flows = stream.subscribe_to::<FlowEvents>().await;
+
+        stream
+            .emit(Event::TransactionSubmitted {
+                signature: "sig".to_string(),
+                description: "ignored".to_string(),
+            })
+            .await;
+        stream
+            .emit(Event::FlowCompleted {
+                flow_id: "flow".to_string(),
+                instance_id: "instance".to_string(),
+                success: true,
+                duration_ms: 10,
+            })
+            .await;
+
+        assert_eq!(
+            flows.recv().await,
+            Some(FlowEvents::Completed {
+                flow_id: "flow".to_string(),
+                instance_id: "instance".to_string(),
+                success: true,
+                duration_ms: 10,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cluster_tagging() {
+        let stream = EventStream::new();
+        let devnet = stream.for_cluster("devnet");
+        let mut filtered = stream
+            .subscribe_filtered(
+                EventFilter::none().state_updates().clusters(vec!["mainnet".to_string()]),
+            )
+            .await;
+        let mut receiver = stream.subscribe().await;
+
+        let update = |slot| {
+            Event::StateUpdate(StateUpdate {
+                account: Pubkey::new_unique(),
+                slot,
+                lamports: 0,
+                data: vec![],
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+                cluster: None,
+            })
+        };
+        devnet.emit(update(1)).await;
+        stream.for_cluster("mainnet").emit(update(2)).await;
+
+        match receiver.recv().await.unwrap() {
+            Event::StateUpdate(update) => assert_eq!(update.cluster.as_deref(), Some("devnet")),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        match filtered.recv().await {
+            Some(Event::StateUpdate(update)) => assert_eq!(update.slot, 2),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+    }
 }
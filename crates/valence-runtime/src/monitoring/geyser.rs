@@ -0,0 +1,486 @@
+//! Geyser (Yellowstone gRPC) state source for the state monitor
+
+use crate::{
+    monitoring::{
+        event_stream::{Event, EventStream},
+        state_monitor::{StateUpdate, WatchList},
+    },
+    Result, RuntimeError,
+};
+use futures::{SinkExt, StreamExt};
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel as SolanaCommitmentLevel},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::TransactionError,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, info, warn};
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
+    SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions, SubscribeUpdateAccount,
+    SubscribeUpdateTransaction,
+};
+
+/// Filter key used for watched account subscriptions
+const ACCOUNTS_FILTER: &str = "valence-accounts";
+
+/// Filter key used for subscriptions to accounts owned by watched programs
+const PROGRAMS_FILTER: &str = "valence-programs";
+
+/// Filter key used for transaction subscriptions
+const TRANSACTIONS_FILTER: &str = "valence-transactions";
+
+// ================================
+// Configuration
+// ================================
+
+/// Backend used by the state monitor to receive on-chain updates
+#[derive(Debug, Clone, Default)]
+pub enum MonitorSource {
+    /// Public RPC WebSocket subscriptions
+    #[default]
+    WebSocket,
+
+    /// Geyser gRPC stream, falling back to WebSocket on failure
+    Geyser(GeyserConfig),
+}
+
+/// Geyser gRPC connection settings
+#[derive(Debug, Clone)]
+pub struct GeyserConfig {
+    /// gRPC endpoint URL
+    pub endpoint: String,
+
+    /// Optional access token sent as `x-token`
+    pub x_token: Option<String>,
+
+    /// Timeout for establishing the gRPC connection
+    pub connect_timeout: Duration,
+
+    /// Reconnect attempts before falling back to WebSocket
+    pub max_reconnect_attempts: u32,
+
+    /// Delay between reconnect attempts
+    pub reconnect_delay: Duration,
+}
+
+impl Default for GeyserConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:10000".to_string(),
+            x_token: None,
+            connect_timeout: Duration::from_secs(10),
+            max_reconnect_attempts: 3,
+            reconnect_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+// ================================
+// Geyser Source
+// ================================
+
+/// Streams account and transaction updates from a Geyser gRPC endpoint
+pub struct GeyserSource {
+    config: GeyserConfig,
+    commitment: CommitmentConfig,
+}
+
+/// Why a single Geyser session ended
+enum SessionEnd {
+    Shutdown,
+    /// The watch list was emptied, leaving nothing to subscribe to
+    Unwatched,
+    Disconnected(String),
+}
+
+impl GeyserSource {
+    /// Create a new Geyser source
+    pub fn new(config: GeyserConfig, commitment: CommitmentConfig) -> Self {
+        Self { config, commitment }
+    }
+
+    /// Stream updates for the watch list until shutdown, reconnecting on
+    /// disconnect.
+    ///
+    /// Nothing is subscribed while the watch list is empty, and changes to it
+    /// are applied to the live subscription. Returns an error once reconnect
+    /// attempts are exhausted so the caller can fall back to another source.
+    pub async fn run(
+        &self,
+        watch_rx: &mut watch::Receiver<WatchList>,
+        event_stream: Arc<EventStream>,
+        shutdown_rx: &mut broadcast::Receiver<()>,
+    ) -> Result<()> {
+        let mut attempts = 0;
+
+        loop {
+            let Some(request) = self.next_request(watch_rx, shutdown_rx).await else {
+                return Ok(());
+            };
+
+            match self
+                .stream_session(request, watch_rx, &event_stream, shutdown_rx)
+                .await
+            {
+                Ok(SessionEnd::Shutdown) => return Ok(()),
+                Ok(SessionEnd::Unwatched) => {
+                    attempts = 0;
+                    continue;
+                }
+                Ok(SessionEnd::Disconnected(reason)) => {
+                    // Any session that connected resets the attempt budget,
+                    // even one dropped before delivering updates; only failed
+                    // connections count towards falling back
+                    attempts = 0;
+                    warn!("Geyser stream disconnected: {}", reason);
+                }
+                Err(e) => {
+                    attempts += 1;
+                    warn!(
+                        "Geyser connection attempt {}/{} failed: {}",
+                        attempts, self.config.max_reconnect_attempts, e
+                    );
+                    if attempts >= self.config.max_reconnect_attempts {
+                        return Err(e);
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = shutdown_rx.recv() => return Ok(()),
+                _ = tokio::time::sleep(self.config.reconnect_delay) => {}
+            }
+        }
+    }
+
+    /// Wait until something is watched and build the request subscribing to
+    /// it, or return `None` on shutdown
+    async fn next_request(
+        &self,
+        watch_rx: &mut watch::Receiver<WatchList>,
+        shutdown_rx: &mut broadcast::Receiver<()>,
+    ) -> Option<SubscribeRequest> {
+        loop {
+            let request = self.request_for(&watch_rx.borrow_and_update());
+            if request.is_some() {
+                return request;
+            }
+
+            tokio::select! {
+                _ = shutdown_rx.recv() => return None,
+                changed = watch_rx.changed() => {
+                    if changed.is_err() {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Subscription request for the current watch list
+    fn request_for(&self, watch_list: &WatchList) -> Option<SubscribeRequest> {
+        let accounts: Vec<Pubkey> = watch_list.accounts.iter().copied().collect();
+        let programs: Vec<Pubkey> = watch_list.programs.iter().copied().collect();
+        build_subscribe_request(&accounts, &programs, self.commitment)
+    }
+
+    /// Run a single subscription session
+    async fn stream_session(
+        &self,
+        request: SubscribeRequest,
+        watch_rx: &mut watch::Receiver<WatchList>,
+        event_stream: &EventStream,
+        shutdown_rx: &mut broadcast::Receiver<()>,
+    ) -> Result<SessionEnd> {
+        let mut client = GeyserGrpcClient::build_from_shared(self.config.endpoint.clone())
+            .map_err(|e| RuntimeError::ConnectionError(e.to_string()))?
+            .x_token(self.config.x_token.clone())
+            .map_err(|e| RuntimeError::ConnectionError(e.to_string()))?
+            .connect_timeout(self.config.connect_timeout)
+            .connect()
+            .await
+            .map_err(|e| RuntimeError::ConnectionError(e.to_string()))?;
+
+        // The request sink replaces the subscription when the watch list changes
+        let (subscribe_tx, stream) = client
+            .subscribe_with_request(Some(request))
+            .await
+            .map_err(|e| RuntimeError::ConnectionError(e.to_string()))?;
+        futures::pin_mut!(subscribe_tx);
+        futures::pin_mut!(stream);
+
+        info!("Connected to Geyser endpoint {}", self.config.endpoint);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => return Ok(SessionEnd::Shutdown),
+                changed = watch_rx.changed() => {
+                    if changed.is_err() {
+                        return Ok(SessionEnd::Shutdown);
+                    }
+                    let Some(request) = self.request_for(&watch_rx.borrow_and_update()) else {
+                        return Ok(SessionEnd::Unwatched);
+                    };
+                    if let Err(e) = subscribe_tx.send(request).await {
+                        return Ok(SessionEnd::Disconnected(e.to_string()));
+                    }
+                    debug!("Updated Geyser subscription");
+                }
+                message = stream.next() => match message {
+                    Some(Ok(update)) => {
+                        if let Some(event) = update.update_oneof.and_then(convert_update) {
+                            event_stream.emit(event).await;
+                        }
+                    }
+                    Some(Err(status)) => {
+                        return Ok(SessionEnd::Disconnected(status.to_string()));
+                    }
+                    None => {
+                        return Ok(SessionEnd::Disconnected("stream closed".to_string()));
+                    }
+                },
+            }
+        }
+    }
+}
+
+// ================================
+// Request and Update Conversion
+// ================================
+
+/// Build a subscription request for the watched accounts and programs
+///
+/// Geyser ANDs the fields of one filter and treats an empty filter as
+/// matching everything, so watched accounts and program owners get separate
+/// filters and `None` is returned when nothing is watched.
+pub fn build_subscribe_request(
+    accounts: &[Pubkey],
+    programs: &[Pubkey],
+    commitment: CommitmentConfig,
+) -> Option<SubscribeRequest> {
+    if accounts.is_empty() && programs.is_empty() {
+        return None;
+    }
+
+    let account_keys: Vec<String> = accounts.iter().map(ToString::to_string).collect();
+    let program_keys: Vec<String> = programs.iter().map(ToString::to_string).collect();
+
+    let mut account_filters = HashMap::new();
+    if !account_keys.is_empty() {
+        account_filters.insert(
+            ACCOUNTS_FILTER.to_string(),
+            SubscribeRequestFilterAccounts {
+                account: account_keys.clone(),
+                ..Default::default()
+            },
+        );
+    }
+    if !program_keys.is_empty() {
+        account_filters.insert(
+            PROGRAMS_FILTER.to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: program_keys.clone(),
+                ..Default::default()
+            },
+        );
+    }
+
+    let mut transaction_filters = HashMap::new();
+    transaction_filters.insert(
+        TRANSACTIONS_FILTER.to_string(),
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            account_include: account_keys.into_iter().chain(program_keys).collect(),
+            ..Default::default()
+        },
+    );
+
+    Some(SubscribeRequest {
+        accounts: account_filters,
+        transactions: transaction_filters,
+        commitment: Some(commitment_level(commitment) as i32),
+        ..Default::default()
+    })
+}
+
+/// Map a Solana commitment to the Geyser commitment level
+fn commitment_level(commitment: CommitmentConfig) -> CommitmentLevel {
+    match commitment.commitment {
+        SolanaCommitmentLevel::Processed => CommitmentLevel::Processed,
+        SolanaCommitmentLevel::Confirmed => CommitmentLevel::Confirmed,
+        SolanaCommitmentLevel::Finalized => CommitmentLevel::Finalized,
+    }
+}
+
+/// Convert a Geyser update into a runtime event
+fn convert_update(update: UpdateOneof) -> Option<Event> {
+    match update {
+        UpdateOneof::Account(account) => convert_account_update(account).map(Event::StateUpdate),
+        UpdateOneof::Transaction(transaction) => convert_transaction_update(transaction),
+        other => {
+            debug!("Ignoring Geyser update: {:?}", other);
+            None
+        }
+    }
+}
+
+/// Convert a Geyser account update into a state update
+pub fn convert_account_update(update: SubscribeUpdateAccount) -> Option<StateUpdate> {
+    let info = update.account?;
+
+    Some(StateUpdate {
+        account: Pubkey::try_from(info.pubkey.as_slice()).ok()?,
+        slot: update.slot,
+        lamports: info.lamports,
+        data: info.data,
+        owner: Pubkey::try_from(info.owner.as_slice()).ok()?,
+        executable: info.executable,
+        rent_epoch: info.rent_epoch,
+        cluster: None,
+    })
+}
+
+/// Convert a Geyser transaction update into a confirmation event
+fn convert_transaction_update(update: SubscribeUpdateTransaction) -> Option<Event> {
+    let info = update.transaction?;
+    let signature = Signature::try_from(info.signature.as_slice()).ok()?;
+
+    // Geyser carries the bincode encoding of the transaction's error
+    let error = info.meta.and_then(|meta| meta.err).map(|err| {
+        bincode::deserialize::<TransactionError>(&err.err)
+            .map(|err| err.to_string())
+            .unwrap_or_else(|_| {
+                format!("Undecodable transaction error: {}", hex::encode(&err.err))
+            })
+    });
+
+    Some(Event::TransactionConfirmed {
+        signature: signature.to_string(),
+        slot: update.slot,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::InstructionError;
+    use yellowstone_grpc_proto::prelude::{
+        SubscribeUpdateAccountInfo, SubscribeUpdateTransactionInfo,
+        TransactionError as ProtoTransactionError, TransactionStatusMeta,
+    };
+
+    #[test]
+    fn test_subscribe_request_filters() {
+        let account = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+
+        let request =
+            build_subscribe_request(&[account], &[program], CommitmentConfig::finalized())
+                .unwrap();
+
+        // Accounts and owners are matched by separate filters, not ANDed
+        let accounts = &request.accounts[ACCOUNTS_FILTER];
+        assert_eq!(accounts.account, vec![account.to_string()]);
+        assert!(accounts.owner.is_empty());
+        let programs = &request.accounts[PROGRAMS_FILTER];
+        assert!(programs.account.is_empty());
+        assert_eq!(programs.owner, vec![program.to_string()]);
+
+        let transactions = &request.transactions[TRANSACTIONS_FILTER];
+        assert_eq!(transactions.vote, Some(false));
+        assert_eq!(transactions.account_include.len(), 2);
+
+        assert_eq!(request.commitment, Some(CommitmentLevel::Finalized as i32));
+    }
+
+    #[test]
+    fn test_subscribe_request_skips_empty_watch_list() {
+        assert!(build_subscribe_request(&[], &[], CommitmentConfig::finalized()).is_none());
+
+        let account = Pubkey::new_unique();
+        let request =
+            build_subscribe_request(&[account], &[], CommitmentConfig::finalized()).unwrap();
+        assert_eq!(request.accounts.len(), 1);
+        assert!(!request.accounts.contains_key(PROGRAMS_FILTER));
+    }
+
+    #[test]
+    fn test_convert_account_update() {
+        let account = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        let update = SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: account.to_bytes().to_vec(),
+                lamports: 1000,
+                owner: owner.to_bytes().to_vec(),
+                executable: false,
+                rent_epoch: 0,
+                data: vec![1, 2, 3],
+                write_version: 1,
+                txn_signature: None,
+            }),
+            slot: 42,
+            is_startup: false,
+        };
+
+        let state = convert_account_update(update).unwrap();
+        assert_eq!(state.account, account);
+        assert_eq!(state.owner, owner);
+        assert_eq!(state.slot, 42);
+        assert_eq!(state.data, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_convert_malformed_account_update() {
+        let update = SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: vec![0; 5],
+                ..Default::default()
+            }),
+            slot: 1,
+            is_startup: false,
+        };
+
+        assert!(convert_account_update(update).is_none());
+    }
+
+    #[test]
+    fn test_convert_transaction_update_decodes_error() {
+        let failed = TransactionError::InstructionError(1, InstructionError::Custom(6000));
+        let update = |err: Option<Vec<u8>>| SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: Signature::from([7u8; 64]).as_ref().to_vec(),
+                meta: Some(TransactionStatusMeta {
+                    err: err.map(|err| ProtoTransactionError { err }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            slot: 9,
+        };
+
+        let encoded = bincode::serialize(&failed).unwrap();
+        match convert_transaction_update(update(Some(encoded))) {
+            Some(Event::TransactionConfirmed { slot, error, .. }) => {
+                assert_eq!(slot, 9);
+                assert_eq!(error, Some(failed.to_string()));
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        let event = convert_transaction_update(update(None));
+        assert!(matches!(event, Some(Event::TransactionConfirmed { error: None, .. })));
+
+        let event = convert_transaction_update(update(Some(vec![0xff])));
+        assert!(matches!(
+            event,
+            Some(Event::TransactionConfirmed { error: Some(ref e), .. }) if e.contains("ff")
+        ));
+    }
+}
\ No newline at end of file
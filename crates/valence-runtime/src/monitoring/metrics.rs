@@ -0,0 +1,292 @@
+//! Prometheus metrics export for runtime monitoring
+//!
+//! Metrics are rendered by [`MetricsExporter::render`] and served on the
+//! control API's authenticated `/metrics` route.
+
+use crate::{
+    monitoring::event_stream::{Event, EventStream},
+    types::RuntimeMetrics,
+    Result, RuntimeError,
+};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::warn;
+
+/// Metric name prefix
+const NAMESPACE: &str = "valence_runtime";
+
+/// Latency buckets in seconds shared by RPC and signing histograms
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Prometheus collectors for the runtime
+pub struct MetricsExporter {
+    registry: Registry,
+    runtime_metrics: Arc<RwLock<RuntimeMetrics>>,
+
+    events_processed: IntCounterVec,
+    flows_active: IntGauge,
+    flows_failed: IntCounter,
+    rpc_latency: Histogram,
+    signing_latency: Histogram,
+    transactions_built: IntCounter,
+    transactions_confirmed: IntCounter,
+    transactions_failed: IntCounter,
+    transactions_expired: IntCounter,
+
+    active_sessions: IntGauge,
+    pending_transactions: IntGauge,
+    total_operations: IntGauge,
+    cache_hits: IntGauge,
+    cache_misses: IntGauge,
+    error_count: IntGauge,
+
+    shutdown_tx: broadcast::Sender<()>,
+    handles: RwLock<Vec<JoinHandle<()>>>,
+}
+
+impl MetricsExporter {
+    /// Create a new exporter publishing the given runtime metrics
+    pub fn new(runtime_metrics: Arc<RwLock<RuntimeMetrics>>) -> Result<Self> {
+        let registry = Registry::new_custom(Some(NAMESPACE.to_string()), None)
+            .map_err(metrics_error)?;
+        let (shutdown_tx, _) = broadcast::channel(16);
+
+        let exporter = Self {
+            events_processed: IntCounterVec::new(
+                Opts::new("events_processed_total", "Runtime events processed by kind"),
+                &["kind"],
+            )
+            .map_err(metrics_error)?,
+            flows_active: int_gauge("flows_active", "Flow executions currently running")?,
+            flows_failed: int_counter("flows_failed_total", "Flow executions that failed")?,
+            rpc_latency: latency_histogram("rpc_latency_seconds", "RPC request latency")?,
+            signing_latency: latency_histogram("signing_latency_seconds", "Signing latency")?,
+            transactions_built: int_counter("transactions_built_total", "Transactions built")?,
+            transactions_confirmed: int_counter(
+                "transactions_confirmed_total",
+                "Transactions confirmed on-chain",
+            )?,
+            transactions_failed: int_counter(
+                "transactions_failed_total",
+                "Transactions confirmed with an error",
+            )?,
+            transactions_expired: int_counter(
+                "transactions_expired_total",
+                "Transactions expired before confirmation",
+            )?,
+            active_sessions: int_gauge("active_sessions", "Sessions tracked by the runtime")?,
+            pending_transactions: int_gauge("pending_transactions", "Transactions pending")?,
+            total_operations: int_gauge("operations", "Kernel operations processed")?,
+            cache_hits: int_gauge("cache_hits", "Account cache hits")?,
+            cache_misses: int_gauge("cache_misses", "Account cache misses")?,
+            error_count: int_gauge("errors", "Runtime errors recorded")?,
+            registry,
+            runtime_metrics,
+            shutdown_tx,
+            handles: RwLock::new(Vec::new()),
+        };
+
+        exporter.register_all()?;
+        Ok(exporter)
+    }
+
+    fn register_all(&self) -> Result<()> {
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(self.events_processed.clone()),
+            Box::new(self.flows_active.clone()),
+            Box::new(self.flows_failed.clone()),
+            Box::new(self.rpc_latency.clone()),
+            Box::new(self.signing_latency.clone()),
+            Box::new(self.transactions_built.clone()),
+            Box::new(self.transactions_confirmed.clone()),
+            Box::new(self.transactions_failed.clone()),
+            Box::new(self.transactions_expired.clone()),
+            Box::new(self.active_sessions.clone()),
+            Box::new(self.pending_transactions.clone()),
+            Box::new(self.total_operations.clone()),
+            Box::new(self.cache_hits.clone()),
+            Box::new(self.cache_misses.clone()),
+            Box::new(self.error_count.clone()),
+        ];
+
+        for collector in collectors {
+            self.registry.register(collector).map_err(metrics_error)?;
+        }
+        Ok(())
+    }
+
+    /// Start collecting runtime events
+    pub async fn start(self: &Arc<Self>, event_stream: &EventStream) -> Result<()> {
+        let mut receiver = event_stream.subscribe().await;
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let exporter = self.clone();
+        self.handles.write().await.push(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    event = receiver.recv() => match event {
+                        Ok(event) => exporter.record_event(&event),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!("Metrics collector lagged by {} events", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Stop collecting metrics
+    pub async fn stop(&self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        for handle in self.handles.write().await.drain(..) {
+            let _ = handle.await;
+        }
+        Ok(())
+    }
+
+    /// Update collectors from a runtime event
+    pub fn record_event(&self, event: &Event) {
+        self.events_processed
+            .with_label_values(&[event_kind(event)])
+            .inc();
+
+        match event {
+            Event::FlowStarted { .. } => self.flows_active.inc(),
+            Event::FlowCompleted { success, .. } => {
+                self.flows_active.dec();
+                if !success {
+                    self.flows_failed.inc();
+                }
+            }
+            Event::TransactionBuilt { .. } => self.transactions_built.inc(),
+            Event::TransactionConfirmed { error, .. } => {
+                self.transactions_confirmed.inc();
+                if error.is_some() {
+                    self.transactions_failed.inc();
+                }
+            }
+            Event::TransactionExpired { .. } => self.transactions_expired.inc(),
+            _ => {}
+        }
+    }
+
+    /// Record the latency of an RPC request
+    pub fn observe_rpc_latency(&self, latency: Duration) {
+        self.rpc_latency.observe(latency.as_secs_f64());
+    }
+
+    /// Record the latency of a signing request
+    pub fn observe_signing_latency(&self, latency: Duration) {
+        self.signing_latency.observe(latency.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text format
+    pub async fn render(&self) -> Result<String> {
+        self.sync_runtime_metrics().await;
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(metrics_error)?;
+        String::from_utf8(buffer).map_err(|e| RuntimeError::InvalidConfiguration(e.to_string()))
+    }
+
+    /// Copy the shared runtime counters into their gauges
+    async fn sync_runtime_metrics(&self) {
+        let metrics = self.runtime_metrics.read().await;
+        self.active_sessions.set(metrics.active_sessions.into());
+        self.pending_transactions
+            .set(metrics.pending_transactions.into());
+        self.total_operations
+            .set(i64::try_from(metrics.total_operations).unwrap_or(i64::MAX));
+        self.cache_hits
+            .set(i64::try_from(metrics.cache_hits).unwrap_or(i64::MAX));
+        self.cache_misses
+            .set(i64::try_from(metrics.cache_misses).unwrap_or(i64::MAX));
+        self.error_count.set(metrics.error_count.into());
+    }
+}
+
+/// Label used for an event in `events_processed_total`
+fn event_kind(event: &Event) -> &'static str {
+    match event {
+        Event::StateUpdate(_) => "state_update",
+        Event::FlowStarted { .. } => "flow_started",
+        Event::FlowStepCompleted { .. } => "flow_step_completed",
+        Event::FlowCompleted { .. } => "flow_completed",
+        Event::BudgetExceeded { .. } => "budget_exceeded",
+        Event::TransactionBuilt { .. } => "transaction_built",
+        Event::TransactionSubmitted { .. } => "transaction_submitted",
+        Event::TransactionConfirmed { .. } => "transaction_confirmed",
+        Event::TransactionExpired { .. } => "transaction_expired",
+        Event::BundleSubmitted { .. } => "bundle_submitted",
+        Event::BundleLanded { .. } => "bundle_landed",
+        Event::BundleFailed { .. } => "bundle_failed",
+        Event::AuditLog { .. } => "audit_log",
+        Event::Error { .. } => "error",
+        Event::Warning { .. } => "warning",
+        Event::ChildAccountCreated { .. } => "child_account_created",
+        Event::SessionCreationRequested { .. } => "session_creation_requested",
+        Event::SessionUnhealthy { .. } => "session_unhealthy",
+        Event::SessionLifecycle { .. } => "session_lifecycle",
+    }
+}
+
+fn int_counter(name: &str, help: &str) -> Result<IntCounter> {
+    IntCounter::new(name, help).map_err(metrics_error)
+}
+
+fn int_gauge(name: &str, help: &str) -> Result<IntGauge> {
+    IntGauge::new(name, help).map_err(metrics_error)
+}
+
+fn latency_histogram(name: &str, help: &str) -> Result<Histogram> {
+    Histogram::with_opts(HistogramOpts::new(name, help).buckets(LATENCY_BUCKETS.to_vec()))
+        .map_err(metrics_error)
+}
+
+fn metrics_error(err: prometheus::Error) -> RuntimeError {
+    RuntimeError::InvalidConfiguration(format!("Metrics error: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_events() {
+        let runtime_metrics = Arc::new(RwLock::new(RuntimeMetrics::default()));
+        let exporter = MetricsExporter::new(runtime_metrics.clone()).unwrap();
+
+        exporter.record_event(&Event::FlowStarted {
+            flow_id: "flow".to_string(),
+            instance_id: "instance".to_string(),
+        });
+        exporter.record_event(&Event::FlowCompleted {
+            flow_id: "flow".to_string(),
+            instance_id: "instance".to_string(),
+            success: false,
+            duration_ms: 5,
+        });
+        exporter.observe_rpc_latency(Duration::from_millis(20));
+        runtime_metrics.write().await.active_sessions = 3;
+
+        let output = exporter.render().await.unwrap();
+        assert!(output.contains("valence_runtime_flows_active 0"));
+        assert!(output.contains("valence_runtime_flows_failed_total 1"));
+        assert!(output.contains("valence_runtime_active_sessions 3"));
+        assert!(output.contains("valence_runtime_rpc_latency_seconds_count 1"));
+        assert!(output
+            .contains("valence_runtime_events_processed_total{kind=\"flow_started\"} 1"));
+    }
+}
@@ -1,11 +1,34 @@
-//! WebSocket state monitoring for on-chain account changes
+//! State monitoring for on-chain account changes over WebSocket or Geyser
 
-use crate::{monitoring::event_stream::EventStream, Result};
+use crate::{
+    monitoring::{
+        backfill::BackfillService,
+        event_stream::{Event, EventStream},
+        geyser::{GeyserSource, MonitorSource},
+    },
+    Result, RuntimeError,
+};
+use futures::{
+    future::BoxFuture,
+    stream::{BoxStream, SelectAll},
+    StreamExt,
+};
 use serde::{Deserialize, Serialize};
-use solana_sdk::pubkey::Pubkey;
-use std::sync::Arc;
-use tokio::{sync::broadcast, task::JoinHandle};
-use tracing::{error, info};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, watch, RwLock},
+    task::JoinHandle,
+};
+use tracing::{debug, error, info, warn};
+
+/// Delay before reconnecting a dropped WebSocket connection
+const WEBSOCKET_RECONNECT_DELAY: Duration = Duration::from_secs(1);
 
 /// State update notification
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,40 +40,162 @@ pub struct StateUpdate {
     pub owner: Pubkey,
     pub executable: bool,
     pub rent_epoch: u64,
+    /// Cluster the update was observed on
+    #[serde(default)]
+    pub cluster: Option<String>,
+}
+
+/// Accounts and programs the monitor is watching
+#[derive(Debug, Clone, Default)]
+pub struct WatchList {
+    pub accounts: HashSet<Pubkey>,
+    pub programs: HashSet<Pubkey>,
+}
+
+impl WatchList {
+    /// Check if nothing is watched
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.programs.is_empty()
+    }
 }
 
-/// State monitor for WebSocket subscriptions
+/// State monitor for on-chain subscriptions
 pub struct StateMonitor {
     ws_url: String,
+    source: MonitorSource,
+    commitment: CommitmentConfig,
     event_stream: Arc<EventStream>,
+    watch_tx: watch::Sender<WatchList>,
+    backfill: Option<Arc<BackfillService>>,
     shutdown_tx: broadcast::Sender<()>,
-    worker_handle: Arc<tokio::sync::RwLock<Option<JoinHandle<()>>>>,
+    worker_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+    tracker_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 impl StateMonitor {
-    /// Create a new state monitor
+    /// Create a new state monitor using WebSocket subscriptions
     pub async fn new(ws_url: String, event_stream: Arc<EventStream>) -> Result<Self> {
+        Self::with_source(
+            ws_url,
+            MonitorSource::WebSocket,
+            CommitmentConfig::confirmed(),
+            event_stream,
+        )
+        .await
+    }
+
+    /// Create a new state monitor with an explicit update source
+    pub async fn with_source(
+        ws_url: String,
+        source: MonitorSource,
+        commitment: CommitmentConfig,
+        event_stream: Arc<EventStream>,
+    ) -> Result<Self> {
         let (shutdown_tx, _shutdown_rx) = broadcast::channel(16);
+        let (watch_tx, _watch_rx) = watch::channel(WatchList::default());
 
         Ok(Self {
             ws_url,
+            source,
+            commitment,
             event_stream,
+            watch_tx,
+            backfill: None,
             shutdown_tx,
-            worker_handle: Arc::new(tokio::sync::RwLock::new(None)),
+            worker_handle: Arc::new(RwLock::new(None)),
+            tracker_handle: Arc::new(RwLock::new(None)),
         })
     }
 
+    /// Get the configured update source
+    pub fn source(&self) -> &MonitorSource {
+        &self.source
+    }
+
+    /// Replay missed events through the given backfill service on start
+    pub fn with_backfill(mut self, backfill: Arc<BackfillService>) -> Self {
+        self.backfill = Some(backfill);
+        self
+    }
+
     /// Start the state monitor
     pub async fn start(&self) -> Result<()> {
         info!("Starting state monitor");
 
         let ws_url = self.ws_url.clone();
+        let source = self.source.clone();
+        let commitment = self.commitment;
         let event_stream = self.event_stream.clone();
+        // Sources follow the live watch list, so later subscriptions are streamed too
+        let mut watch_rx = self.watch_tx.subscribe();
+
+        // Track processed slots from before any backfilled or live event is emitted
+        let backfill = match &self.backfill {
+            Some(backfill) => {
+                let tracker = backfill.clone().track(self.shutdown_tx.subscribe()).await;
+                *self.tracker_handle.write().await = Some(tracker);
+                Some(backfill.clone())
+            }
+            None => None,
+        };
+
         let handle = tokio::spawn({
-            let shutdown_rx = self.shutdown_tx.subscribe();
+            let mut shutdown_rx = self.shutdown_tx.subscribe();
             async move {
-                if let Err(e) = Self::monitor_loop(ws_url, event_stream, shutdown_rx).await {
-                    error!("State monitor error: {}", e);
+                if let Some(backfill) = backfill {
+                    let addresses: Vec<Pubkey> = {
+                        let watch_list = watch_rx.borrow();
+                        watch_list.accounts.iter().chain(&watch_list.programs).copied().collect()
+                    };
+                    match backfill.backfill(&addresses).await {
+                        Ok(report) => info!(
+                            "Backfill replayed {} events since slot {:?}",
+                            report.events_emitted, report.from_slot
+                        ),
+                        Err(e) => warn!("Backfill failed: {}", e),
+                    }
+                }
+
+                if let MonitorSource::Geyser(config) = source {
+                    let geyser = GeyserSource::new(config, commitment);
+                    let result = geyser
+                        .run(&mut watch_rx, event_stream.clone(), &mut shutdown_rx)
+                        .await;
+
+                    match result {
+                        Ok(()) => return,
+                        Err(e) => {
+                            warn!("Geyser source failed, falling back to WebSocket: {}", e);
+                            event_stream
+                                .emit(Event::Warning {
+                                    context: "state_monitor".to_string(),
+                                    message: format!("Geyser failover to WebSocket: {}", e),
+                                })
+                                .await;
+                        }
+                    }
+                }
+
+                loop {
+                    let result = Self::monitor_loop(
+                        &ws_url,
+                        commitment,
+                        &mut watch_rx,
+                        &event_stream,
+                        &mut shutdown_rx,
+                    )
+                    .await;
+
+                    match result {
+                        Ok(()) => break,
+                        Err(e) => {
+                            error!("State monitor error: {}", e);
+                            tokio::select! {
+                                _ = shutdown_rx.recv() => break,
+                                _ = tokio::time::sleep(WEBSOCKET_RECONNECT_DELAY) => {}
+                            }
+                        }
+                    }
                 }
             }
         });
@@ -66,10 +211,13 @@ impl StateMonitor {
         // Send shutdown signal
         let _ = self.shutdown_tx.send(());
 
-        // Wait for worker to finish
+        // Wait for worker and checkpoint tracker to finish
         if let Some(handle) = self.worker_handle.write().await.take() {
             let _ = handle.await;
         }
+        if let Some(handle) = self.tracker_handle.write().await.take() {
+            let _ = handle.await;
+        }
 
         Ok(())
     }
@@ -81,9 +229,12 @@ impl StateMonitor {
     {
         info!("Subscribing to account {}", account);
         
-        // In a real implementation, this would add the account to a subscription list
-        // and the monitor loop would handle WebSocket subscription
-        let _ = (account, callback); // Suppress unused warnings
+        // The account is added to the watch list followed by the running source;
+        // callbacks are not yet dispatched, consumers read from the event stream
+        self.watch_tx.send_modify(|watch_list| {
+            watch_list.accounts.insert(account);
+        });
+        let _ = callback; // Suppress unused warning
         
         Ok(())
     }
@@ -91,41 +242,156 @@ impl StateMonitor {
     /// Unsubscribe from account updates
     pub async fn unsubscribe_account(&self, account: &Pubkey) -> Result<()> {
         info!("Unsubscribing from account {}", account);
-        let _ = account; // Suppress unused warning
+        self.watch_tx.send_modify(|watch_list| {
+            watch_list.accounts.remove(account);
+        });
+        Ok(())
+    }
+
+    /// Watch all accounts owned by a program and its transactions
+    pub async fn watch_program(&self, program_id: Pubkey) -> Result<()> {
+        info!("Watching program {}", program_id);
+        self.watch_tx.send_modify(|watch_list| {
+            watch_list.programs.insert(program_id);
+        });
         Ok(())
     }
 
-    /// Main monitoring loop (simplified)
+    /// Accounts currently watched by the monitor
+    pub async fn watched_accounts(&self) -> Vec<Pubkey> {
+        self.watch_tx.borrow().accounts.iter().copied().collect()
+    }
+
+    /// Programs currently watched by the monitor
+    pub async fn watched_programs(&self) -> Vec<Pubkey> {
+        self.watch_tx.borrow().programs.iter().copied().collect()
+    }
+
+    /// Stream watched accounts over WebSocket subscriptions until shutdown
+    ///
+    /// Subscriptions are rebuilt whenever the watch list changes. Returns an
+    /// error when the connection fails or drops, so the caller can reconnect.
     async fn monitor_loop(
-        _ws_url: String,
-        _event_stream: Arc<EventStream>,
-        mut shutdown_rx: broadcast::Receiver<()>,
+        ws_url: &str,
+        commitment: CommitmentConfig,
+        watch_rx: &mut watch::Receiver<WatchList>,
+        event_stream: &EventStream,
+        shutdown_rx: &mut broadcast::Receiver<()>,
     ) -> Result<()> {
-        // Simplified implementation - in production this would:
-        // 1. Connect to WebSocket
-        // 2. Subscribe to accounts
-        // 3. Process incoming messages
-        // 4. Emit events
-        
+        let client = tokio::select! {
+            _ = shutdown_rx.recv() => return Ok(()),
+            client = PubsubClient::new(ws_url) => {
+                client.map_err(|e| RuntimeError::ConnectionError(e.to_string()))?
+            }
+        };
+        info!("Connected to WebSocket endpoint {}", ws_url);
+
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..Default::default()
+        };
+
         loop {
-            tokio::select! {
-                _ = shutdown_rx.recv() => {
-                    info!("State monitor received shutdown signal");
-                    break;
-                }
-                _ = tokio::time::sleep(tokio::time::Duration::from_secs(1)) => {
-                    // Placeholder for actual WebSocket message processing
+            let watch_list = watch_rx.borrow_and_update().clone();
+            let mut updates: SelectAll<BoxStream<'_, Option<StateUpdate>>> = SelectAll::new();
+            let mut unsubscribes: Vec<Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>> =
+                Vec::new();
+
+            for &account in &watch_list.accounts {
+                let (stream, unsubscribe) = client
+                    .account_subscribe(&account, Some(account_config.clone()))
+                    .await
+                    .map_err(|e| RuntimeError::ConnectionError(e.to_string()))?;
+                updates.push(
+                    stream
+                        .map(move |response| {
+                            ui_state_update(account, response.context.slot, &response.value)
+                        })
+                        .boxed(),
+                );
+                unsubscribes.push(unsubscribe);
+            }
+
+            for program in &watch_list.programs {
+                let config = RpcProgramAccountsConfig {
+                    account_config: account_config.clone(),
+                    with_context: Some(true),
+                    ..Default::default()
+                };
+                let (stream, unsubscribe) = client
+                    .program_subscribe(program, Some(config))
+                    .await
+                    .map_err(|e| RuntimeError::ConnectionError(e.to_string()))?;
+                updates.push(
+                    stream
+                        .map(|response| {
+                            let account = Pubkey::from_str(&response.value.pubkey).ok()?;
+                            ui_state_update(account, response.context.slot, &response.value.account)
+                        })
+                        .boxed(),
+                );
+                unsubscribes.push(unsubscribe);
+            }
+
+            debug!(
+                "Subscribed to {} accounts and {} programs over WebSocket",
+                watch_list.accounts.len(),
+                watch_list.programs.len()
+            );
+
+            let result = loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => {
+                        info!("State monitor received shutdown signal");
+                        break Ok(false);
+                    }
+                    changed = watch_rx.changed() => break Ok(changed.is_ok()),
+                    update = updates.next(), if !updates.is_empty() => match update {
+                        Some(Some(update)) => event_stream.emit(Event::StateUpdate(update)).await,
+                        Some(None) => debug!("Ignoring undecodable WebSocket account update"),
+                        None => {
+                            break Err(RuntimeError::ConnectionError(
+                                "WebSocket subscriptions closed".to_string(),
+                            ))
+                        }
+                    },
                 }
+            };
+
+            drop(updates);
+            for unsubscribe in unsubscribes {
+                unsubscribe().await;
             }
-        }
 
-        Ok(())
+            // Resubscribe with the new watch list, or stop
+            if !result? {
+                return Ok(());
+            }
+        }
     }
 }
 
+/// Convert a WebSocket account notification into a state update
+fn ui_state_update(account: Pubkey, slot: u64, ui_account: &UiAccount) -> Option<StateUpdate> {
+    let decoded: Account = ui_account.decode()?;
+
+    Some(StateUpdate {
+        account,
+        slot,
+        lamports: decoded.lamports,
+        data: decoded.data,
+        owner: decoded.owner,
+        executable: decoded.executable,
+        rent_epoch: decoded.rent_epoch,
+        cluster: None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::monitoring::geyser::GeyserConfig;
 
     #[tokio::test]
     async fn test_state_monitor_creation() {
@@ -157,5 +423,69 @@ mod tests {
             .await;
 
         assert!(result.is_ok());
+        assert_eq!(monitor.watched_accounts().await, vec![account]);
+
+        monitor.unsubscribe_account(&account).await.unwrap();
+        assert!(monitor.watched_accounts().await.is_empty());
+    }
+
+    #[test]
+    fn test_websocket_account_update() {
+        let account = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let data = Account {
+            lamports: 1000,
+            data: vec![1, 2, 3],
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let ui_account = solana_account_decoder::encode_ui_account(
+            &account,
+            &data,
+            UiAccountEncoding::Base64,
+            None,
+            None,
+        );
+
+        let update = ui_state_update(account, 42, &ui_account).unwrap();
+        assert_eq!(update.account, account);
+        assert_eq!(update.owner, owner);
+        assert_eq!(update.slot, 42);
+        assert_eq!(update.data, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_geyser_failover_to_websocket() {
+        let event_stream = Arc::new(EventStream::new());
+        let mut receiver = event_stream.subscribe().await;
+
+        // Nothing listens on these endpoints, so the Geyser source gives up
+        // immediately and the WebSocket fallback never leaves the host
+        let source = MonitorSource::Geyser(GeyserConfig {
+            endpoint: "http://127.0.0.1:1".to_string(),
+            connect_timeout: std::time::Duration::from_millis(100),
+            max_reconnect_attempts: 1,
+            ..Default::default()
+        });
+        let monitor = StateMonitor::with_source(
+            "ws://127.0.0.1:1".to_string(),
+            source,
+            CommitmentConfig::confirmed(),
+            event_stream,
+        )
+        .await
+        .unwrap();
+
+        // Nothing is subscribed until an account is watched
+        monitor.subscribe_account(Pubkey::new_unique(), |_update| {}).await.unwrap();
+        monitor.start().await.unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        monitor.stop().await.unwrap();
+
+        assert!(matches!(event, Event::Warning { context, .. } if context == "state_monitor"));
     }
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+//! Token-bucket rate limiting of transaction submissions
+//!
+//! Limits apply globally, per flow and per destination program. A
+//! transaction is admitted only if every applicable bucket has a token;
+//! otherwise no tokens are taken and the caller is told how long to wait.
+
+use crate::{Result, RuntimeError};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// ================================
+// Configuration
+// ================================
+
+/// Sustained rate and burst size of a token bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Transactions admitted per second
+    pub per_second: f64,
+    /// Transactions admitted back to back after idling
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn per_second(per_second: f64) -> Self {
+        Self {
+            per_second,
+            burst: per_second.ceil().max(1.0) as u32,
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+/// Rate limits applied to transactions; unset limits are unlimited
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// Limit across all transactions
+    pub global: Option<RateLimit>,
+    /// Limit for each flow's transactions
+    pub per_flow: Option<RateLimit>,
+    /// Limit for transactions invoking each program
+    pub per_program: Option<RateLimit>,
+}
+
+// ================================
+// Token Bucket
+// ================================
+
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst as f64);
+        self.refilled_at = now;
+    }
+
+    /// Time until a token is available, zero when one is available now
+    fn wait_time(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else if self.limit.per_second <= 0.0 {
+            Duration::MAX
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.limit.per_second)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    global: Option<TokenBucket>,
+    flows: HashMap<String, TokenBucket>,
+    programs: HashMap<Pubkey, TokenBucket>,
+}
+
+impl Buckets {
+    /// Visit the buckets limiting a transaction, creating missing ones full
+    fn for_each(
+        &mut self,
+        config: &RateLimitConfig,
+        flow_id: Option<&str>,
+        programs: &[Pubkey],
+        now: Instant,
+        mut visit: impl FnMut(&mut TokenBucket),
+    ) {
+        if let Some(limit) = config.global {
+            visit(self.global.get_or_insert_with(|| TokenBucket::new(limit, now)));
+        }
+        if let (Some(limit), Some(flow_id)) = (config.per_flow, flow_id) {
+            visit(
+                self.flows
+                    .entry(flow_id.to_string())
+                    .or_insert_with(|| TokenBucket::new(limit, now)),
+            );
+        }
+        if let Some(limit) = config.per_program {
+            for program in programs {
+                visit(
+                    self.programs
+                        .entry(*program)
+                        .or_insert_with(|| TokenBucket::new(limit, now)),
+                );
+            }
+        }
+    }
+}
+
+// ================================
+// Rate Limiter
+// ================================
+
+/// Global, per-flow and per-program transaction rate limiter
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Limiter that admits everything
+    pub fn unlimited() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Take a token for a transaction of `flow_id` invoking `programs`
+    ///
+    /// Fails with [`RuntimeError::RateLimitExceeded`] carrying the time until
+    /// every applicable bucket can admit the transaction.
+    pub fn acquire(&self, flow_id: Option<&str>, programs: &[Pubkey]) -> Result<()> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let mut programs = programs.to_vec();
+        programs.sort();
+        programs.dedup();
+
+        // Check every applicable bucket before taking any token
+        let mut retry_after = Duration::ZERO;
+        buckets.for_each(&self.config, flow_id, &programs, now, |bucket| {
+            retry_after = retry_after.max(bucket.wait_time(now));
+        });
+        if retry_after > Duration::ZERO {
+            return Err(RuntimeError::RateLimitExceeded { retry_after });
+        }
+
+        buckets.for_each(&self.config, flow_id, &programs, now, |bucket| {
+            bucket.tokens -= 1.0;
+        });
+        Ok(())
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry_after(result: Result<()>) -> Duration {
+        match result {
+            Err(RuntimeError::RateLimitExceeded { retry_after }) => retry_after,
+            other => panic!("expected rate limit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_global_and_flow_limits() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            global: Some(RateLimit::per_second(10.0).with_burst(3)),
+            per_flow: Some(RateLimit::per_second(1.0)),
+            per_program: None,
+        });
+
+        limiter.acquire(Some("a"), &[]).unwrap();
+        let wait = retry_after(limiter.acquire(Some("a"), &[]));
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+
+        // A rejected transaction takes no global token
+        limiter.acquire(Some("b"), &[]).unwrap();
+        limiter.acquire(None, &[]).unwrap();
+        let wait = retry_after(limiter.acquire(None, &[]));
+        assert!(wait <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_program_limits() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            per_program: Some(RateLimit::per_second(1.0)),
+            ..Default::default()
+        });
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        limiter.acquire(None, &[first, first]).unwrap();
+        assert!(limiter.acquire(None, &[second, first]).is_err());
+        limiter.acquire(None, &[second]).unwrap();
+        assert!(RateLimiter::unlimited().acquire(Some("a"), &[first]).is_ok());
+    }
+}
@@ -0,0 +1,480 @@
+//! RPC endpoint pool with health probes, latency tracking and failover
+//!
+//! [`RpcPool::client`] returns a regular `RpcClient` whose transport spreads
+//! requests across the pool, so code holding an `Arc<RpcClient>` gets
+//! failover without changes.
+
+use crate::{monitoring::MetricsExporter, Result, RuntimeError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind as ErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    rpc_request::{RpcError, RpcRequest},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+// ================================
+// Configuration
+// ================================
+
+/// Order in which healthy endpoints are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RotationStrategy {
+    /// Always prefer the first healthy endpoint in configured order
+    #[default]
+    Failover,
+    /// Rotate the first endpoint tried on every request
+    RoundRobin,
+    /// Prefer the endpoint with the lowest observed latency
+    LowestLatency,
+}
+
+/// RPC pool configuration
+#[derive(Debug, Clone)]
+pub struct RpcPoolConfig {
+    pub strategy: RotationStrategy,
+    /// Interval between `getHealth` probes of every endpoint
+    pub probe_interval: Duration,
+    /// Consecutive failures before an endpoint is marked unhealthy
+    pub failure_threshold: u32,
+}
+
+impl Default for RpcPoolConfig {
+    fn default() -> Self {
+        Self {
+            strategy: RotationStrategy::Failover,
+            probe_interval: Duration::from_secs(10),
+            failure_threshold: 3,
+        }
+    }
+}
+
+// ================================
+// Endpoint State
+// ================================
+
+/// Health snapshot of a pooled endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// Moving average of request latency
+    pub latency_ms: Option<f64>,
+    pub requests: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+}
+
+struct Endpoint {
+    client: RpcClient,
+    health: Mutex<EndpointHealth>,
+}
+
+impl Endpoint {
+    fn record_success(&self, elapsed: Duration) {
+        let mut health = self.health.lock().unwrap();
+        let sample = elapsed.as_secs_f64() * 1000.0;
+        health.latency_ms = Some(match health.latency_ms {
+            Some(average) => average + LATENCY_SMOOTHING * (sample - average),
+            None => sample,
+        });
+        health.requests += 1;
+        health.consecutive_failures = 0;
+        health.healthy = true;
+    }
+
+    fn record_failure(&self, error: String, failure_threshold: u32) {
+        let mut health = self.health.lock().unwrap();
+        health.requests += 1;
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        health.last_error = Some(error);
+        if health.healthy && health.consecutive_failures >= failure_threshold {
+            warn!("RPC endpoint {} marked unhealthy", health.url);
+            health.healthy = false;
+        }
+    }
+}
+
+/// Whether an error indicates a problem with the endpoint rather than the request
+fn is_endpoint_failure(error: &ClientError) -> bool {
+    match error.kind() {
+        ErrorKind::Io(_) | ErrorKind::Reqwest(_) | ErrorKind::Middleware(_) => true,
+        ErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        ErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
+
+// ================================
+// RPC Pool
+// ================================
+
+/// Pool of RPC endpoints serving one cluster
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    config: RpcPoolConfig,
+    cursor: AtomicUsize,
+    stats: Mutex<RpcTransportStats>,
+    metrics: Option<Arc<MetricsExporter>>,
+    shutdown_tx: broadcast::Sender<()>,
+    probe_handle: RwLock<Option<JoinHandle<()>>>,
+}
+
+impl RpcPool {
+    /// Create a pool of HTTP endpoints
+    pub fn new(urls: Vec<String>, config: RpcPoolConfig) -> Result<Self> {
+        let clients = urls
+            .into_iter()
+            .map(|url| {
+                let client = RpcClient::new(url.clone());
+                (url, client)
+            })
+            .collect();
+        Self::from_clients(clients, config)
+    }
+
+    /// Create a pool over existing clients, keyed by a display URL
+    pub fn from_clients(
+        clients: Vec<(String, RpcClient)>,
+        config: RpcPoolConfig,
+    ) -> Result<Self> {
+        if clients.is_empty() {
+            return Err(RuntimeError::InvalidConfiguration(
+                "RPC pool requires at least one endpoint".to_string(),
+            ));
+        }
+
+        let endpoints = clients
+            .into_iter()
+            .map(|(url, client)| Endpoint {
+                client,
+                health: Mutex::new(EndpointHealth {
+                    url,
+                    healthy: true,
+                    consecutive_failures: 0,
+                    latency_ms: None,
+                    requests: 0,
+                    failures: 0,
+                    last_error: None,
+                }),
+            })
+            .collect();
+        let (shutdown_tx, _) = broadcast::channel(16);
+
+        Ok(Self {
+            endpoints,
+            config,
+            cursor: AtomicUsize::new(0),
+            stats: Mutex::new(RpcTransportStats::default()),
+            metrics: None,
+            shutdown_tx,
+            probe_handle: RwLock::new(None),
+        })
+    }
+
+    /// Record the latency of every request sent through the pool
+    pub fn with_metrics(mut self, metrics: Arc<MetricsExporter>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// RPC client sending through the pool
+    pub fn client(self: &Arc<Self>, commitment: CommitmentConfig) -> RpcClient {
+        RpcClient::new_sender(
+            PoolSender(Arc::clone(self)),
+            RpcClientConfig::with_commitment(commitment),
+        )
+    }
+
+    /// Health of every endpoint in configured order
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.health.lock().unwrap().clone())
+            .collect()
+    }
+
+    /// Probe every endpoint with `getHealth`
+    pub async fn probe(&self) {
+        for endpoint in &self.endpoints {
+            let started = Instant::now();
+            match endpoint.client.get_health().await {
+                Ok(()) => endpoint.record_success(started.elapsed()),
+                Err(e) => {
+                    // A failed probe marks the endpoint unhealthy immediately
+                    endpoint.record_failure(e.to_string(), 1);
+                }
+            }
+        }
+    }
+
+    /// Start periodic health probes
+    pub async fn start(self: &Arc<Self>) -> Result<()> {
+        info!("Starting RPC pool probes for {} endpoints", self.endpoints.len());
+
+        let pool = Arc::clone(self);
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(pool.config.probe_interval);
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = interval.tick() => pool.probe().await,
+                }
+            }
+        });
+
+        *self.probe_handle.write().await = Some(handle);
+        Ok(())
+    }
+
+    /// Stop health probes
+    pub async fn stop(&self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        if let Some(handle) = self.probe_handle.write().await.take() {
+            let _ = handle.await;
+        }
+        Ok(())
+    }
+
+    /// Endpoint indices in the order they should be tried
+    fn candidates(&self) -> Vec<usize> {
+        let count = self.endpoints.len();
+        let mut order: Vec<usize> = match self.config.strategy {
+            RotationStrategy::Failover | RotationStrategy::LowestLatency => (0..count).collect(),
+            RotationStrategy::RoundRobin => {
+                let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+                (0..count).map(|offset| (start + offset) % count).collect()
+            }
+        };
+
+        let health: Vec<EndpointHealth> = self.health();
+        if self.config.strategy == RotationStrategy::LowestLatency {
+            // Endpoints without samples sort first so they get measured
+            order.sort_by(|a, b| {
+                let latency = |index: &usize| health[*index].latency_ms.unwrap_or(0.0);
+                latency(a).total_cmp(&latency(b))
+            });
+        }
+
+        // Unhealthy endpoints remain as a last resort; sort is stable
+        order.sort_by_key(|index| !health[*index].healthy);
+        order
+    }
+
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let started = Instant::now();
+        let mut last_error = None;
+
+        for index in self.candidates() {
+            let endpoint = &self.endpoints[index];
+            let attempt = Instant::now();
+
+            match endpoint.client.send::<serde_json::Value>(request, params.clone()).await {
+                Ok(value) => {
+                    endpoint.record_success(attempt.elapsed());
+                    self.record_stats(started.elapsed());
+                    return Ok(value);
+                }
+                Err(e) if is_endpoint_failure(&e) => {
+                    let url = endpoint.health.lock().unwrap().url.clone();
+                    warn!("RPC request {} failed on {}: {}", request, url, e);
+                    endpoint.record_failure(e.to_string(), self.config.failure_threshold);
+                    last_error = Some(e);
+                }
+                Err(e) => {
+                    // The endpoint answered; the request itself was rejected
+                    endpoint.record_success(attempt.elapsed());
+                    self.record_stats(started.elapsed());
+                    return Err(e);
+                }
+            }
+        }
+
+        self.record_stats(started.elapsed());
+        Err(last_error.expect("pool has at least one endpoint"))
+    }
+
+    fn record_stats(&self, elapsed: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.request_count += 1;
+        stats.elapsed_time += elapsed;
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_rpc_latency(elapsed);
+        }
+    }
+}
+
+/// Transport routing `RpcClient` requests through a pool
+struct PoolSender(Arc<RpcPool>);
+
+#[async_trait]
+impl RpcSender for PoolSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        self.0.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.0.stats.lock().unwrap().clone()
+    }
+
+    fn url(&self) -> String {
+        let index = self.0.candidates()[0];
+        self.0.endpoints[index].health.lock().unwrap().url.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transport whose connection always fails
+    struct UnreachableSender;
+
+    #[async_trait]
+    impl RpcSender for UnreachableSender {
+        async fn send(
+            &self,
+            _request: RpcRequest,
+            _params: serde_json::Value,
+        ) -> ClientResult<serde_json::Value> {
+            Err(ErrorKind::Io(std::io::Error::from(std::io::ErrorKind::ConnectionRefused)).into())
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "unreachable".to_string()
+        }
+    }
+
+    fn unreachable() -> (String, RpcClient) {
+        (
+            "unreachable".to_string(),
+            RpcClient::new_sender(UnreachableSender, RpcClientConfig::default()),
+        )
+    }
+
+    fn mock() -> (String, RpcClient) {
+        ("mock".to_string(), RpcClient::new_mock("succeeds".to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_healthy_endpoint() {
+        let pool = Arc::new(
+            RpcPool::from_clients(vec![unreachable(), mock()], RpcPoolConfig::default()).unwrap(),
+        );
+        let client = pool.client(CommitmentConfig::confirmed());
+
+        for _ in 0..3 {
+            assert_eq!(client.get_slot().await.unwrap(), 0);
+        }
+
+        let health = pool.health();
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].failures, 3);
+        assert!(health[1].healthy);
+        assert!(health[1].latency_ms.is_some());
+
+        // Unhealthy endpoints are skipped once marked
+        client.get_slot().await.unwrap();
+        assert_eq!(pool.health()[0].failures, 3);
+        assert_eq!(client.url(), "mock");
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_failing() {
+        let pool = Arc::new(
+            RpcPool::from_clients(vec![unreachable(), unreachable()], RpcPoolConfig::default())
+                .unwrap(),
+        );
+        let client = pool.client(CommitmentConfig::confirmed());
+
+        assert!(client.get_slot().await.is_err());
+        assert!(pool.health().iter().all(|health| health.failures == 1));
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_and_probe() {
+        let config = RpcPoolConfig {
+            strategy: RotationStrategy::RoundRobin,
+            ..Default::default()
+        };
+        let pool = Arc::new(RpcPool::from_clients(vec![mock(), mock()], config).unwrap());
+        let client = pool.client(CommitmentConfig::confirmed());
+
+        for _ in 0..4 {
+            client.get_slot().await.unwrap();
+        }
+        assert!(pool.health().iter().all(|health| health.requests == 2));
+
+        let mocks = [(RpcRequest::GetHealth, serde_json::json!("ok"))].into();
+        let healthy = (
+            "healthy".to_string(),
+            RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks),
+        );
+        let pool = Arc::new(
+            RpcPool::from_clients(vec![unreachable(), healthy], RpcPoolConfig::default()).unwrap(),
+        );
+        pool.probe().await;
+        assert!(!pool.health()[0].healthy);
+        assert!(pool.health()[1].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_request_latency_observed() {
+        let metrics = Arc::new(
+            MetricsExporter::new(Arc::new(RwLock::new(crate::RuntimeMetrics::default()))).unwrap(),
+        );
+        let pool = Arc::new(
+            RpcPool::from_clients(vec![unreachable(), mock()], RpcPoolConfig::default())
+                .unwrap()
+                .with_metrics(metrics.clone()),
+        );
+        let client = pool.client(CommitmentConfig::confirmed());
+
+        // One observation per request, however many endpoints it tried
+        client.get_slot().await.unwrap();
+        client.get_slot().await.unwrap();
+        let output = metrics.render().await.unwrap();
+        assert!(output.contains("valence_runtime_rpc_latency_seconds_count 2"));
+    }
+
+    #[test]
+    fn test_empty_pool_rejected() {
+        assert!(RpcPool::from_clients(Vec::new(), RpcPoolConfig::default()).is_err());
+    }
+}
@@ -1,17 +1,35 @@
 //! Audit logging and compliance tracking
-
-use crate::Result;
+//!
+//! Entries form a BLAKE3 hash chain: each entry commits to the hash of its
+//! predecessor, so any edit, deletion or reordering of an exported log is
+//! detected by [`verify_segment`]. Periodic checkpoints signed by the runtime
+//! anchor the chain head to a known key.
+
+use crate::decoder::DecodedTransaction;
+use crate::{Result, RuntimeError};
 use async_trait::async_trait;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs::{File, OpenOptions};
 use tokio::io::AsyncWriteExt;
-use tracing::error;
+use tracing::{error, info};
+
+/// Hash linked by the first entry of a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// File holding signed checkpoints next to the log segments
+const CHECKPOINT_FILE: &str = "checkpoints.jsonl";
 
 /// Audit event types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AuditEventType {
     TransactionSigned,
     TransactionValidated,
@@ -44,6 +62,15 @@ pub struct AuditEntry {
     pub outcome: AuditOutcome,
     pub details: HashMap<String, serde_json::Value>,
     pub session_id: Option<String>,
+    /// Position in the hash chain, assigned by the logger
+    #[serde(default)]
+    pub sequence: u64,
+    /// Hash of the preceding entry
+    #[serde(default)]
+    pub previous_hash: String,
+    /// BLAKE3 hash over this entry, including `previous_hash`
+    #[serde(default)]
+    pub entry_hash: String,
 }
 
 /// Audit outcome
@@ -71,6 +98,9 @@ impl AuditEntry {
             outcome: AuditOutcome::Success,
             details: HashMap::new(),
             session_id: None,
+            sequence: 0,
+            previous_hash: String::new(),
+            entry_hash: String::new(),
         }
     }
 
@@ -84,7 +114,52 @@ impl AuditEntry {
             outcome: AuditOutcome::Error(error),
             details: HashMap::new(),
             session_id: None,
+            sequence: 0,
+            previous_hash: String::new(),
+            entry_hash: String::new(),
+        }
+    }
+
+    /// Hash of the entry's canonical JSON form, excluding `entry_hash` itself
+    pub fn compute_hash(&self) -> Result<String> {
+        // serde_json maps are ordered, so `details` hashes deterministically
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("entry_hash");
         }
+        Ok(blake3::hash(value.to_string().as_bytes()).to_hex().to_string())
+    }
+}
+
+/// Signed commitment to the chain head at a given sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    pub sequence: u64,
+    pub entry_hash: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub signer: Pubkey,
+    pub signature: Signature,
+}
+
+impl AuditCheckpoint {
+    /// Sign the chain head ending at `entry`
+    pub fn sign(signer: &Keypair, entry: &AuditEntry) -> Self {
+        Self {
+            sequence: entry.sequence,
+            entry_hash: entry.entry_hash.clone(),
+            timestamp: chrono::Utc::now(),
+            signer: signer.pubkey(),
+            signature: signer.sign_message(&Self::message(entry.sequence, &entry.entry_hash)),
+        }
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        self.signature
+            .verify(self.signer.as_ref(), &Self::message(self.sequence, &self.entry_hash))
+    }
+
+    fn message(sequence: u64, entry_hash: &str) -> Vec<u8> {
+        format!("valence-audit-checkpoint:{}:{}", sequence, entry_hash).into_bytes()
     }
 }
 
@@ -104,6 +179,9 @@ impl AuditEntryBuilder {
                 outcome: AuditOutcome::Success,
                 details: HashMap::new(),
                 session_id: None,
+                sequence: 0,
+                previous_hash: String::new(),
+                entry_hash: String::new(),
             },
         }
     }
@@ -118,6 +196,29 @@ impl AuditEntryBuilder {
         self
     }
 
+    /// Enrich the entry with a decoded transaction's Valence activity
+    ///
+    /// Sets the resource to the signature and the session to the first session
+    /// touched, and records the kernel instructions and transfers as details.
+    pub fn decoded_transaction(mut self, decoded: &DecodedTransaction) -> Self {
+        self.entry.resource = Some(decoded.signature.clone());
+        if let Some(session) = decoded.sessions().first() {
+            self.entry.session_id = Some(session.to_string());
+        }
+        if !decoded.success {
+            self.entry.outcome = AuditOutcome::Failure;
+        }
+        self.detail("slot".to_string(), decoded.slot)
+            .detail(
+                "valence_instructions".to_string(),
+                serde_json::to_value(&decoded.instructions).unwrap_or_default(),
+            )
+            .detail(
+                "transfers".to_string(),
+                serde_json::to_value(&decoded.transfers).unwrap_or_default(),
+            )
+    }
+
     pub fn build(self) -> AuditEntry { self.entry }
 }
 
@@ -125,37 +226,89 @@ impl AuditEntryBuilder {
 #[async_trait]
 pub trait AuditStorage: Send + Sync {
     async fn store(&self, entry: &AuditEntry) -> Result<()>;
-    async fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>>;
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>>;
+
+    /// Most recent entry, used to resume the hash chain after a restart
+    async fn last_entry(&self) -> Result<Option<AuditEntry>> {
+        Ok(None)
+    }
+
+    async fn store_checkpoint(&self, _checkpoint: &AuditCheckpoint) -> Result<()> {
+        Ok(())
+    }
 }
 
-/// Audit query filter
+/// Audit history query
+///
+/// Results are the most recent `limit` matches after skipping `offset`,
+/// returned in chain order.
 #[derive(Debug, Clone)]
-pub struct AuditFilter {
+pub struct AuditQuery {
     pub event_types: Option<Vec<AuditEventType>>,
     pub start_time: Option<chrono::DateTime<chrono::Utc>>,
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
     pub actor: Option<String>,
+    pub session_id: Option<String>,
     pub limit: Option<usize>,
+    pub offset: usize,
 }
 
-impl Default for AuditFilter {
+impl Default for AuditQuery {
     fn default() -> Self {
         Self {
             event_types: None,
             start_time: None,
             end_time: None,
             actor: None,
+            session_id: None,
             limit: Some(1000),
+            offset: 0,
         }
     }
 }
 
+impl AuditQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn actor(mut self, actor: String) -> Self { self.actor = Some(actor); self }
+    pub fn session_id(mut self, session_id: String) -> Self { self.session_id = Some(session_id); self }
+    pub fn since(mut self, start: chrono::DateTime<chrono::Utc>) -> Self { self.start_time = Some(start); self }
+    pub fn until(mut self, end: chrono::DateTime<chrono::Utc>) -> Self { self.end_time = Some(end); self }
+    pub fn limit(mut self, limit: usize) -> Self { self.limit = Some(limit); self }
+    pub fn offset(mut self, offset: usize) -> Self { self.offset = offset; self }
+
+    pub fn event_type(mut self, event_type: AuditEventType) -> Self {
+        self.event_types.get_or_insert_with(Vec::new).push(event_type);
+        self
+    }
+
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.event_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&entry.event_type))
+            && self.start_time.is_none_or(|start| entry.timestamp >= start)
+            && self.end_time.is_none_or(|end| entry.timestamp <= end)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|actor| entry.actor.as_ref() == Some(actor))
+            && self
+                .session_id
+                .as_ref()
+                .is_none_or(|session| entry.session_id.as_ref() == Some(session))
+    }
+}
+
 /// Audit configuration
 #[derive(Debug, Clone)]
 pub struct AuditConfig {
     pub enabled: bool,
     pub retention_days: u32,
     pub max_entries_per_file: usize,
+    /// Entries between signed checkpoints (0 disables checkpoints)
+    pub checkpoint_interval: u64,
 }
 
 impl Default for AuditConfig {
@@ -164,14 +317,28 @@ impl Default for AuditConfig {
             enabled: true,
             retention_days: 90,
             max_entries_per_file: 10000,
+            checkpoint_interval: 1000,
         }
     }
 }
 
 /// File-based audit storage
+///
+/// Entries are appended to `audit_<first sequence>.jsonl` segments. A segment
+/// is rotated once it holds `max_entries_per_file` entries and, if enabled,
+/// gzip-compressed to `.jsonl.gz`.
 pub struct FileAuditStorage {
     directory: PathBuf,
-    current_file: Arc<tokio::sync::Mutex<Option<File>>>,
+    max_entries_per_file: usize,
+    compress_rotated: bool,
+    current_file: Arc<tokio::sync::Mutex<Option<ActiveSegment>>>,
+}
+
+/// Segment currently being appended to
+struct ActiveSegment {
+    path: PathBuf,
+    file: File,
+    entries: usize,
 }
 
 impl FileAuditStorage {
@@ -179,46 +346,122 @@ impl FileAuditStorage {
         tokio::fs::create_dir_all(&directory).await?;
         Ok(Self {
             directory,
+            max_entries_per_file: AuditConfig::default().max_entries_per_file,
+            compress_rotated: true,
             current_file: Arc::new(tokio::sync::Mutex::new(None)),
         })
     }
 
-    async fn get_current_file(&self) -> Result<File> {
-        let mut current_file = self.current_file.lock().await;
-        
-        if current_file.is_none() {
-            let filename = format!("audit_{}.jsonl", chrono::Utc::now().format("%Y%m%d"));
-            let filepath = self.directory.join(filename);
-            
-            let file = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(filepath)
-                .await?;
-            
-            *current_file = Some(file);
+    /// Configure segment size and compression of rotated segments
+    pub fn with_rotation(mut self, max_entries_per_file: usize, compress_rotated: bool) -> Self {
+        self.max_entries_per_file = max_entries_per_file.max(1);
+        self.compress_rotated = compress_rotated;
+        self
+    }
+
+    /// Log segments in chain order
+    pub fn segments(&self) -> Result<Vec<PathBuf>> {
+        segment_files(&self.directory)
+    }
+
+    /// Path of the signed checkpoint file
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.directory.join(CHECKPOINT_FILE)
+    }
+
+    /// Resume the newest uncompressed segment if it has room, otherwise start one at `sequence`
+    async fn open_segment(&self, sequence: u64) -> Result<ActiveSegment> {
+        if let Some(path) = self.segments()?.pop().filter(|p| !is_compressed(p)) {
+            let entries = tokio::fs::read_to_string(&path).await?.lines().count();
+            if entries < self.max_entries_per_file {
+                let file = OpenOptions::new().append(true).open(&path).await?;
+                return Ok(ActiveSegment { path, file, entries });
+            }
         }
-        
-        // This is a simplified approach - in production you'd want to handle file rotation
-        Ok(OpenOptions::new().create(true).append(true).open(
-            self.directory.join(format!("audit_{}.jsonl", chrono::Utc::now().format("%Y%m%d")))
-        ).await?)
+
+        let path = self.directory.join(format!("audit_{:020}.jsonl", sequence));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        Ok(ActiveSegment { path, file, entries: 0 })
+    }
+
+    /// Close a full segment, compressing it if configured
+    async fn rotate(&self, segment: ActiveSegment) -> Result<()> {
+        drop(segment.file);
+        if !self.compress_rotated {
+            return Ok(());
+        }
+
+        let contents = tokio::fs::read(&segment.path).await?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&contents)?;
+        let compressed_path = segment.path.with_extension("jsonl.gz");
+        tokio::fs::write(&compressed_path, encoder.finish()?).await?;
+        tokio::fs::remove_file(&segment.path).await?;
+
+        info!("Rotated audit segment to {}", compressed_path.display());
+        Ok(())
     }
 }
 
 #[async_trait]
 impl AuditStorage for FileAuditStorage {
     async fn store(&self, entry: &AuditEntry) -> Result<()> {
-        let mut file = self.get_current_file().await?;
+        let mut current_file = self.current_file.lock().await;
+
+        let mut segment = match current_file.take() {
+            Some(segment) => segment,
+            None => self.open_segment(entry.sequence).await?,
+        };
+
         let json_line = serde_json::to_string(entry)?;
-        file.write_all(format!("{}\n", json_line).as_bytes()).await?;
-        file.flush().await?;
+        segment.file.write_all(format!("{}\n", json_line).as_bytes()).await?;
+        segment.file.flush().await?;
+        segment.entries += 1;
+
+        if segment.entries >= self.max_entries_per_file {
+            self.rotate(segment).await?;
+        } else {
+            *current_file = Some(segment);
+        }
         Ok(())
     }
 
-    async fn query(&self, _filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
-        // Simplified implementation - would need proper file reading and filtering
-        Ok(Vec::new())
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let mut matching = Vec::new();
+        for path in self.segments()? {
+            let entries = decode_segment(&path, &tokio::fs::read(&path).await?)?;
+            matching.extend(entries.into_iter().filter(|entry| query.matches(entry)));
+        }
+
+        matching.truncate(matching.len().saturating_sub(query.offset));
+        if let Some(limit) = query.limit {
+            let skip = matching.len().saturating_sub(limit);
+            matching.drain(..skip);
+        }
+        Ok(matching)
+    }
+
+    async fn last_entry(&self) -> Result<Option<AuditEntry>> {
+        match self.segments()?.pop() {
+            Some(path) => Ok(decode_segment(&path, &tokio::fs::read(&path).await?)?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &AuditCheckpoint) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.checkpoint_path())
+            .await?;
+        let json_line = serde_json::to_string(checkpoint)?;
+        file.write_all(format!("{}\n", json_line).as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
     }
 }
 
@@ -226,24 +469,72 @@ impl AuditStorage for FileAuditStorage {
 pub struct AuditLogger {
     storage: Arc<dyn AuditStorage>,
     config: AuditConfig,
+    chain_head: tokio::sync::Mutex<ChainHead>,
+    checkpoint_signer: Option<Arc<Keypair>>,
+}
+
+/// Next position in the hash chain
+struct ChainHead {
+    sequence: u64,
+    hash: String,
 }
 
 impl AuditLogger {
     pub async fn new(storage: Arc<dyn AuditStorage>, config: AuditConfig) -> Result<Self> {
-        Ok(Self { storage, config })
+        let chain_head = match storage.last_entry().await? {
+            Some(last) => ChainHead {
+                sequence: last.sequence + 1,
+                hash: last.entry_hash,
+            },
+            None => ChainHead {
+                sequence: 0,
+                hash: GENESIS_HASH.to_string(),
+            },
+        };
+
+        Ok(Self {
+            storage,
+            config,
+            chain_head: tokio::sync::Mutex::new(chain_head),
+            checkpoint_signer: None,
+        })
     }
 
-    /// Log an audit entry
-    pub async fn log(&self, entry: AuditEntry) -> Result<()> {
+    /// Sign a checkpoint every `checkpoint_interval` entries
+    pub fn with_checkpoint_signer(mut self, signer: Arc<Keypair>) -> Self {
+        self.checkpoint_signer = Some(signer);
+        self
+    }
+
+    /// Log an audit entry, linking it into the hash chain
+    pub async fn log(&self, mut entry: AuditEntry) -> Result<()> {
         if !self.config.enabled {
             return Ok(());
         }
 
+        // Held across the store so entries are persisted in chain order
+        let mut head = self.chain_head.lock().await;
+        entry.sequence = head.sequence;
+        entry.previous_hash = head.hash.clone();
+        entry.entry_hash = entry.compute_hash()?;
+
         if let Err(e) = self.storage.store(&entry).await {
             error!("Failed to store audit entry: {}", e);
             return Err(e);
         }
 
+        head.sequence += 1;
+        head.hash = entry.entry_hash.clone();
+
+        if let Some(signer) = &self.checkpoint_signer {
+            let interval = self.config.checkpoint_interval;
+            if interval > 0 && head.sequence % interval == 0 {
+                self.storage
+                    .store_checkpoint(&AuditCheckpoint::sign(signer, &entry))
+                    .await?;
+            }
+        }
+
         Ok(())
     }
 
@@ -258,6 +549,17 @@ impl AuditLogger {
         self.log(entry).await
     }
 
+    /// Log a transaction signing event enriched with its decoded Valence activity
+    pub async fn log_decoded_transaction(&self, actor: String, decoded: &DecodedTransaction) -> Result<()> {
+        let entry = AuditEntry::builder(AuditEventType::TransactionSigned)
+            .actor(actor)
+            .detail("transaction_hash".to_string(), decoded.signature.clone())
+            .decoded_transaction(decoded)
+            .build();
+
+        self.log(entry).await
+    }
+
     /// Log a security violation
     pub async fn log_security_violation(&self, actor: String, violation: String) -> Result<()> {
         let entry = AuditEntry::builder(AuditEventType::SecurityViolation)
@@ -269,10 +571,151 @@ impl AuditLogger {
         self.log(entry).await
     }
 
-    /// Query audit logs
-    pub async fn query(&self, filter: AuditFilter) -> Result<Vec<AuditEntry>> {
-        self.storage.query(&filter).await
+    /// Search audit history
+    pub async fn query(&self, query: AuditQuery) -> Result<Vec<AuditEntry>> {
+        self.storage.query(&query).await
+    }
+}
+
+// ================================
+// Verification
+// ================================
+
+/// Outcome of a successful segment verification
+#[derive(Debug, Clone, Default)]
+pub struct AuditVerification {
+    pub entries: usize,
+    pub first_sequence: Option<u64>,
+    pub last_sequence: Option<u64>,
+    pub last_hash: Option<String>,
+    pub checkpoints_verified: usize,
+}
+
+/// Validate the hash chain of an exported log segment
+///
+/// Checkpoints covering sequences inside the segment must match the chain
+/// and carry a valid signature; when `trusted_signers` is non-empty the
+/// signer must also be one of them.
+pub fn verify_segment(
+    entries: &[AuditEntry],
+    checkpoints: &[AuditCheckpoint],
+    trusted_signers: &[Pubkey],
+) -> Result<AuditVerification> {
+    let violation = |sequence: u64, reason: &str| {
+        RuntimeError::SecurityViolation(format!("Audit entry {}: {}", sequence, reason))
+    };
+
+    let mut hashes = HashMap::new();
+    let mut previous: Option<&AuditEntry> = None;
+    for entry in entries {
+        match previous {
+            Some(prev) if entry.sequence != prev.sequence + 1 => {
+                return Err(violation(entry.sequence, "sequence gap"))
+            }
+            Some(prev) if entry.previous_hash != prev.entry_hash => {
+                return Err(violation(entry.sequence, "broken hash link"))
+            }
+            None if entry.sequence == 0 && entry.previous_hash != GENESIS_HASH => {
+                return Err(violation(entry.sequence, "invalid genesis link"))
+            }
+            _ => {}
+        }
+        if entry.compute_hash()? != entry.entry_hash {
+            return Err(violation(entry.sequence, "hash mismatch"));
+        }
+        hashes.insert(entry.sequence, entry.entry_hash.as_str());
+        previous = Some(entry);
+    }
+
+    let mut checkpoints_verified = 0;
+    for checkpoint in checkpoints {
+        let Some(hash) = hashes.get(&checkpoint.sequence) else {
+            continue;
+        };
+        if *hash != checkpoint.entry_hash {
+            return Err(violation(checkpoint.sequence, "checkpoint does not match chain"));
+        }
+        if !checkpoint.verify_signature() {
+            return Err(violation(checkpoint.sequence, "invalid checkpoint signature"));
+        }
+        if !trusted_signers.is_empty() && !trusted_signers.contains(&checkpoint.signer) {
+            return Err(violation(checkpoint.sequence, "untrusted checkpoint signer"));
+        }
+        checkpoints_verified += 1;
+    }
+
+    Ok(AuditVerification {
+        entries: entries.len(),
+        first_sequence: entries.first().map(|e| e.sequence),
+        last_sequence: previous.map(|e| e.sequence),
+        last_hash: previous.map(|e| e.entry_hash.clone()),
+        checkpoints_verified,
+    })
+}
+
+/// Read entries from a `.jsonl` or `.jsonl.gz` segment
+pub fn read_segment(path: &Path) -> Result<Vec<AuditEntry>> {
+    decode_segment(path, &std::fs::read(path)?)
+}
+
+/// Read signed checkpoints written by [`FileAuditStorage`]
+pub fn read_checkpoints(path: &Path) -> Result<Vec<AuditCheckpoint>> {
+    read_json_lines(path, &std::fs::read(path)?)
+}
+
+/// Log segments in a directory, in chain order
+pub fn segment_files(directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut segments = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if name.starts_with("audit_") && (name.ends_with(".jsonl") || name.ends_with(".jsonl.gz")) {
+            segments.push(path);
+        }
+    }
+    segments.sort();
+    Ok(segments)
+}
+
+/// Verify every segment in an audit directory against its checkpoints
+pub fn verify_directory(directory: &Path, trusted_signers: &[Pubkey]) -> Result<AuditVerification> {
+    let mut entries = Vec::new();
+    for path in segment_files(directory)? {
+        entries.extend(read_segment(&path)?);
     }
+
+    let checkpoint_path = directory.join(CHECKPOINT_FILE);
+    let checkpoints = if checkpoint_path.exists() {
+        read_checkpoints(&checkpoint_path)?
+    } else {
+        Vec::new()
+    };
+
+    verify_segment(&entries, &checkpoints, trusted_signers)
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+fn decode_segment(path: &Path, bytes: &[u8]) -> Result<Vec<AuditEntry>> {
+    read_json_lines(path, bytes)
+}
+
+fn read_json_lines<T: serde::de::DeserializeOwned>(path: &Path, bytes: &[u8]) -> Result<Vec<T>> {
+    let contents = if is_compressed(path) {
+        let mut decoded = String::new();
+        GzDecoder::new(bytes).read_to_string(&mut decoded)?;
+        decoded
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(RuntimeError::from))
+        .collect()
 }
 
 #[cfg(test)]
@@ -300,6 +743,39 @@ mod tests {
         assert!(entry.details.contains_key("amount"));
     }
 
+    #[test]
+    fn test_audit_entry_decoded_transaction() {
+        use crate::decoder::{DecodedInstruction, InstructionPosition, KernelInstruction};
+
+        let session = Pubkey::new_unique();
+        let decoded = DecodedTransaction {
+            signature: "sig".to_string(),
+            slot: 7,
+            block_time: None,
+            success: false,
+            instructions: vec![DecodedInstruction {
+                position: InstructionPosition { index: 0, inner_index: None },
+                instruction: KernelInstruction::InvalidateSession { session },
+            }],
+            transfers: Vec::new(),
+        };
+
+        let entry = AuditEntry::builder(AuditEventType::TransactionSigned)
+            .decoded_transaction(&decoded)
+            .build();
+        assert_eq!(entry.resource.as_deref(), Some("sig"));
+        assert_eq!(entry.session_id, Some(session.to_string()));
+        assert!(matches!(entry.outcome, AuditOutcome::Failure));
+        assert_eq!(
+            entry.details["valence_instructions"][0]["instruction"]["instruction"],
+            "invalidate_session"
+        );
+        assert_eq!(
+            entry.details["valence_instructions"][0]["instruction"]["session"],
+            session.to_string()
+        );
+    }
+
     #[tokio::test]
     async fn test_file_storage() {
         let temp_dir = TempDir::new().unwrap();
@@ -308,4 +784,114 @@ mod tests {
         let entry = AuditEntry::success(AuditEventType::TransactionSigned, "test_user".to_string());
         assert!(storage.store(&entry).await.is_ok());
     }
+
+    async fn chained_log(dir: &TempDir, signer: &Arc<Keypair>, entries: usize) -> FileAuditStorage {
+        let storage = Arc::new(
+            FileAuditStorage::new(dir.path().to_path_buf())
+                .await
+                .unwrap()
+                .with_rotation(3, true),
+        );
+        let config = AuditConfig {
+            checkpoint_interval: 2,
+            ..AuditConfig::default()
+        };
+        let logger = AuditLogger::new(storage, config)
+            .await
+            .unwrap()
+            .with_checkpoint_signer(signer.clone());
+
+        for i in 0..entries {
+            logger
+                .log_transaction_signed("test_user".to_string(), format!("tx_{}", i))
+                .await
+                .unwrap();
+        }
+        FileAuditStorage::new(dir.path().to_path_buf()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hash_chain_rotation_and_checkpoints() {
+        let temp_dir = TempDir::new().unwrap();
+        let signer = Arc::new(Keypair::new());
+        let storage = chained_log(&temp_dir, &signer, 7).await;
+
+        let segments = storage.segments().unwrap();
+        assert_eq!(segments.len(), 3);
+        assert!(is_compressed(&segments[0]) && is_compressed(&segments[1]));
+        assert!(!is_compressed(&segments[2]));
+
+        let report = verify_directory(temp_dir.path(), &[signer.pubkey()]).unwrap();
+        assert_eq!(report.entries, 7);
+        assert_eq!(report.last_sequence, Some(6));
+        assert_eq!(report.checkpoints_verified, 3);
+
+        // The chain resumes from storage after a restart
+        let resumed = AuditLogger::new(Arc::new(storage), AuditConfig::default())
+            .await
+            .unwrap();
+        resumed
+            .log_security_violation("test_user".to_string(), "blocked".to_string())
+            .await
+            .unwrap();
+        assert_eq!(verify_directory(temp_dir.path(), &[]).unwrap().entries, 8);
+
+        let untrusted = Keypair::new().pubkey();
+        assert!(verify_directory(temp_dir.path(), &[untrusted]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FileAuditStorage::new(temp_dir.path().to_path_buf()).await.unwrap());
+        let logger = AuditLogger::new(storage, AuditConfig::default()).await.unwrap();
+
+        for i in 0..4 {
+            let entry = AuditEntry::builder(AuditEventType::SessionCreated)
+                .actor(format!("user_{}", i % 2))
+                .session_id(format!("session_{}", i))
+                .build();
+            logger.log(entry).await.unwrap();
+        }
+        logger
+            .log_security_violation("user_0".to_string(), "blocked".to_string())
+            .await
+            .unwrap();
+
+        let by_actor = logger.query(AuditQuery::new().actor("user_0".to_string())).await.unwrap();
+        assert_eq!(by_actor.len(), 3);
+
+        let sessions = logger
+            .query(AuditQuery::new().event_type(AuditEventType::SessionCreated).limit(2).offset(1))
+            .await
+            .unwrap();
+        let ids: Vec<_> = sessions.iter().map(|e| e.session_id.clone().unwrap()).collect();
+        assert_eq!(ids, vec!["session_1", "session_2"]);
+
+        let by_session = logger
+            .query(AuditQuery::new().session_id("session_3".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(by_session.len(), 1);
+        assert_eq!(by_session[0].sequence, 3);
+    }
+
+    #[tokio::test]
+    async fn test_tampering_detected() {
+        let temp_dir = TempDir::new().unwrap();
+        let signer = Arc::new(Keypair::new());
+        let storage = chained_log(&temp_dir, &signer, 2).await;
+        let path = storage.segments().unwrap().pop().unwrap();
+        let mut entries = read_segment(&path).unwrap();
+        assert!(verify_segment(&entries, &[], &[]).is_ok());
+
+        let mut edited = entries.clone();
+        edited[0].actor = Some("someone_else".to_string());
+        assert!(verify_segment(&edited, &[], &[]).is_err());
+
+        entries.remove(0);
+        let mut relinked = entries.clone();
+        relinked[0].sequence = 0;
+        assert!(verify_segment(&relinked, &[], &[]).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,204 @@
+//! Postgres-backed audit storage
+//!
+//! Each entry is stored whole as JSONB alongside indexed columns for the
+//! fields compliance queries filter on.
+
+use crate::security::audit::{AuditCheckpoint, AuditEntry, AuditQuery, AuditStorage};
+use crate::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio_postgres::{types::ToSql, Client, NoTls};
+use tracing::error;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS audit_entries (
+        sequence BIGINT PRIMARY KEY,
+        timestamp TIMESTAMPTZ NOT NULL,
+        event_type TEXT NOT NULL,
+        actor TEXT,
+        resource TEXT,
+        session_id TEXT,
+        previous_hash TEXT NOT NULL,
+        entry_hash TEXT NOT NULL,
+        entry JSONB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS audit_entries_actor_idx ON audit_entries (actor, timestamp);
+    CREATE INDEX IF NOT EXISTS audit_entries_event_type_idx ON audit_entries (event_type, timestamp);
+    CREATE INDEX IF NOT EXISTS audit_entries_session_idx ON audit_entries (session_id, timestamp);
+    CREATE INDEX IF NOT EXISTS audit_entries_timestamp_idx ON audit_entries (timestamp);
+    CREATE TABLE IF NOT EXISTS audit_checkpoints (
+        sequence BIGINT PRIMARY KEY,
+        checkpoint JSONB NOT NULL
+    );
+";
+
+type QueryParam = Box<dyn ToSql + Sync + Send>;
+
+/// Audit storage in a Postgres database
+pub struct PostgresAuditStorage {
+    client: Arc<Client>,
+}
+
+impl PostgresAuditStorage {
+    /// Connect using a libpq-style connection string and create the schema if missing
+    pub async fn connect(config: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(config, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("Audit database connection closed: {}", e);
+            }
+        });
+
+        let storage = Self::from_client(Arc::new(client));
+        storage.migrate().await?;
+        Ok(storage)
+    }
+
+    /// Use an existing client; call [`Self::migrate`] if the schema may be missing
+    pub fn from_client(client: Arc<Client>) -> Self {
+        Self { client }
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        self.client.batch_execute(SCHEMA).await?;
+        Ok(())
+    }
+}
+
+/// Build the SQL and parameters for a query
+fn build_query(query: &AuditQuery) -> (String, Vec<QueryParam>) {
+    let mut conditions = Vec::new();
+    let mut params: Vec<QueryParam> = Vec::new();
+    let mut bind = |condition: &str, param: QueryParam| {
+        params.push(param);
+        conditions.push(condition.replace('?', &format!("${}", params.len())));
+    };
+
+    if let Some(actor) = &query.actor {
+        bind("actor = ?", Box::new(actor.clone()));
+    }
+    if let Some(event_types) = &query.event_types {
+        let names: Vec<String> = event_types.iter().map(|t| t.to_string()).collect();
+        bind("event_type = ANY(?)", Box::new(names));
+    }
+    if let Some(session_id) = &query.session_id {
+        bind("session_id = ?", Box::new(session_id.clone()));
+    }
+    if let Some(start) = query.start_time {
+        bind("timestamp >= ?", Box::new(start));
+    }
+    if let Some(end) = query.end_time {
+        bind("timestamp <= ?", Box::new(end));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    let limit = match query.limit {
+        Some(limit) => {
+            params.push(Box::new(limit as i64));
+            format!(" LIMIT ${}", params.len())
+        }
+        None => String::new(),
+    };
+    params.push(Box::new(query.offset as i64));
+    let offset = format!(" OFFSET ${}", params.len());
+
+    // Select the most recent matches, then return them in chain order
+    let sql = format!(
+        "SELECT entry FROM (SELECT sequence, entry FROM audit_entries{} ORDER BY sequence DESC{}{}) recent ORDER BY sequence",
+        where_clause, limit, offset
+    );
+    (sql, params)
+}
+
+#[async_trait]
+impl AuditStorage for PostgresAuditStorage {
+    async fn store(&self, entry: &AuditEntry) -> Result<()> {
+        let record = serde_json::to_value(entry)?;
+        self.client
+            .execute(
+                "INSERT INTO audit_entries
+                    (sequence, timestamp, event_type, actor, resource, session_id, previous_hash, entry_hash, entry)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                &[
+                    &(entry.sequence as i64),
+                    &entry.timestamp,
+                    &entry.event_type.to_string(),
+                    &entry.actor,
+                    &entry.resource,
+                    &entry.session_id,
+                    &entry.previous_hash,
+                    &entry.entry_hash,
+                    &record,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let (sql, params) = build_query(query);
+        let params: Vec<&(dyn ToSql + Sync)> = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect();
+
+        self.client
+            .query(&sql, &params)
+            .await?
+            .into_iter()
+            .map(|row| Ok(serde_json::from_value(row.try_get("entry")?)?))
+            .collect()
+    }
+
+    async fn last_entry(&self) -> Result<Option<AuditEntry>> {
+        let row = self
+            .client
+            .query_opt("SELECT entry FROM audit_entries ORDER BY sequence DESC LIMIT 1", &[])
+            .await?;
+        match row {
+            Some(row) => Ok(Some(serde_json::from_value(row.try_get("entry")?)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn store_checkpoint(&self, checkpoint: &AuditCheckpoint) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO audit_checkpoints (sequence, checkpoint) VALUES ($1, $2)
+                 ON CONFLICT (sequence) DO NOTHING",
+                &[&(checkpoint.sequence as i64), &serde_json::to_value(checkpoint)?],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::audit::AuditEventType;
+
+    #[test]
+    fn test_build_query() {
+        let (sql, params) = build_query(&AuditQuery::default());
+        assert!(!sql.contains("WHERE"));
+        assert!(sql.contains("LIMIT $1 OFFSET $2"));
+        assert_eq!(params.len(), 2);
+
+        let query = AuditQuery::new()
+            .actor("alice".to_string())
+            .event_type(AuditEventType::SecurityViolation)
+            .session_id("session".to_string())
+            .since(chrono::Utc::now());
+        let (sql, params) = build_query(&query);
+        assert!(sql.contains(
+            "WHERE actor = $1 AND event_type = ANY($2) AND session_id = $3 AND timestamp >= $4"
+        ));
+        assert!(sql.contains("LIMIT $5 OFFSET $6"));
+        assert_eq!(params.len(), 6);
+    }
+}
@@ -0,0 +1,480 @@
+//! Ledger hardware wallet signing backend speaking the Solana app APDU protocol
+
+use crate::{
+    security::{
+        signing::{
+            merge_signatures, verify_message_signatures, SigningBackend, SigningPolicies,
+            SigningRequest, SigningResponse, SigningResult, SigningService, VerificationResult,
+        },
+        SecurityAnalysis, SecurityAnalyzer,
+    },
+    Result, RuntimeError,
+};
+use async_trait::async_trait;
+use solana_sdk::{
+    derivation_path::DerivationPath, message::Message, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::info;
+
+// ================================
+// APDU Protocol Constants
+// ================================
+
+/// Solana app instruction class
+const APDU_CLA: u8 = 0xe0;
+
+/// Get public key instruction
+const INS_GET_PUBKEY: u8 = 0x05;
+
+/// Sign message instruction
+const INS_SIGN_MESSAGE: u8 = 0x06;
+
+/// Do not require on-device confirmation
+const P1_NON_CONFIRM: u8 = 0x00;
+
+/// Require on-device confirmation
+const P1_CONFIRM: u8 = 0x01;
+
+/// Chunk continues a previous chunk
+const P2_EXTEND: u8 = 0x01;
+
+/// More chunks follow
+const P2_MORE: u8 = 0x02;
+
+/// Maximum APDU payload size
+const MAX_CHUNK_SIZE: usize = 255;
+
+/// Status word for success
+const SW_OK: u16 = 0x9000;
+
+/// Status word when the user rejects on device
+const SW_USER_REJECTED: u16 = 0x6985;
+
+// ================================
+// Transport
+// ================================
+
+/// Raw APDU transport to a Ledger device (USB HID, TCP speculos, ...)
+#[async_trait]
+pub trait LedgerTransport: Send + Sync {
+    /// Send an APDU and return the response including the trailing status word
+    async fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Errors reported by the Ledger device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerStatus {
+    Ok(Vec<u8>),
+    UserRejected,
+    Error(u16),
+}
+
+/// Encode an APDU command
+fn encode_apdu(ins: u8, p1: u8, p2: u8, data: &[u8]) -> Vec<u8> {
+    let mut apdu = Vec::with_capacity(5 + data.len());
+    apdu.extend_from_slice(&[APDU_CLA, ins, p1, p2, data.len() as u8]);
+    apdu.extend_from_slice(data);
+    apdu
+}
+
+/// Split a response into payload and status
+fn decode_response(response: &[u8]) -> Result<LedgerStatus> {
+    if response.len() < 2 {
+        return Err(RuntimeError::ConnectionError(
+            "Truncated Ledger response".to_string(),
+        ));
+    }
+
+    let (payload, status) = response.split_at(response.len() - 2);
+    match u16::from_be_bytes([status[0], status[1]]) {
+        SW_OK => Ok(LedgerStatus::Ok(payload.to_vec())),
+        SW_USER_REJECTED => Ok(LedgerStatus::UserRejected),
+        code => Ok(LedgerStatus::Error(code)),
+    }
+}
+
+/// Serialize a BIP44 derivation path as expected by the Solana app
+pub fn serialize_derivation_path(path: &DerivationPath) -> Vec<u8> {
+    let depth = if path.change().is_some() {
+        4
+    } else if path.account().is_some() {
+        3
+    } else {
+        2
+    };
+
+    let mut bytes = vec![depth];
+    for index in path.path() {
+        bytes.extend_from_slice(&index.to_bits().to_be_bytes());
+    }
+    bytes
+}
+
+/// Build the APDU sequence signing a message with a single derived key
+fn sign_message_apdus(path: &DerivationPath, message: &[u8]) -> Vec<Vec<u8>> {
+    // First chunk: signer count, derivation path, start of the message
+    let mut first = vec![1u8];
+    first.extend_from_slice(&serialize_derivation_path(path));
+
+    let first_len = message.len().min(MAX_CHUNK_SIZE - first.len());
+    let (head, tail) = message.split_at(first_len);
+    first.extend_from_slice(head);
+
+    let mut chunks = vec![(if tail.is_empty() { 0 } else { P2_MORE }, first)];
+    chunks.extend(
+        tail.chunks(MAX_CHUNK_SIZE)
+            .map(|chunk| (P2_EXTEND | P2_MORE, chunk.to_vec())),
+    );
+
+    // The final chunk clears the "more" flag
+    if chunks.len() > 1 {
+        if let Some(last) = chunks.last_mut() {
+            last.0 &= !P2_MORE;
+        }
+    }
+
+    chunks
+        .into_iter()
+        .map(|(p2, data)| encode_apdu(INS_SIGN_MESSAGE, P1_CONFIRM, p2, &data))
+        .collect()
+}
+
+// ================================
+// Ledger Signer
+// ================================
+
+/// Callback presenting a transaction summary to the operator before on-device approval
+pub type SummaryDisplay = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Signing backend backed by a Ledger device
+pub struct LedgerSigner {
+    transport: Arc<dyn LedgerTransport>,
+    analyzer: SecurityAnalyzer,
+    display: SummaryDisplay,
+    accounts: RwLock<HashMap<Pubkey, DerivationPath>>,
+    policies: RwLock<HashMap<Pubkey, SigningPolicies>>,
+}
+
+impl LedgerSigner {
+    /// Create a new Ledger signer that logs transaction summaries
+    pub fn new(transport: Arc<dyn LedgerTransport>, analyzer: SecurityAnalyzer) -> Self {
+        Self {
+            transport,
+            analyzer,
+            display: Arc::new(|summary| info!("Approve on Ledger:\n{}", summary)),
+            accounts: RwLock::new(HashMap::new()),
+            policies: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Use a custom summary display
+    pub fn with_display(mut self, display: SummaryDisplay) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Derive the key at `m/44'/501'/account'/change'` and make it available for signing
+    pub async fn add_account(&self, account: u32, change: Option<u32>) -> Result<Pubkey> {
+        let path = DerivationPath::new_bip44(Some(account), change);
+        let pubkey = self.get_pubkey(&path, false).await?;
+        self.accounts.write().await.insert(pubkey, path);
+        Ok(pubkey)
+    }
+
+    /// Read the public key at a derivation path
+    pub async fn get_pubkey(&self, path: &DerivationPath, confirm: bool) -> Result<Pubkey> {
+        let p1 = if confirm { P1_CONFIRM } else { P1_NON_CONFIRM };
+        let apdu = encode_apdu(INS_GET_PUBKEY, p1, 0, &serialize_derivation_path(path));
+
+        match decode_response(&self.transport.exchange(&apdu).await?)? {
+            LedgerStatus::Ok(bytes) => Pubkey::try_from(bytes.as_slice()).map_err(|_| {
+                RuntimeError::ConnectionError("Invalid public key from Ledger".to_string())
+            }),
+            LedgerStatus::UserRejected => Err(RuntimeError::AuthenticationFailed),
+            LedgerStatus::Error(code) => Err(RuntimeError::ConnectionError(format!(
+                "Ledger error 0x{:04x}",
+                code
+            ))),
+        }
+    }
+
+    /// Sign a serialized message; `None` when rejected on device
+    async fn sign_message(&self, path: &DerivationPath, message: &[u8]) -> Result<Option<Signature>> {
+        let mut status = LedgerStatus::Error(0);
+        for apdu in sign_message_apdus(path, message) {
+            status = decode_response(&self.transport.exchange(&apdu).await?)?;
+            if !matches!(status, LedgerStatus::Ok(_)) {
+                break;
+            }
+        }
+
+        match status {
+            LedgerStatus::Ok(bytes) => Signature::try_from(bytes.as_slice())
+                .map(Some)
+                .map_err(|_| {
+                    RuntimeError::ConnectionError("Invalid signature from Ledger".to_string())
+                }),
+            LedgerStatus::UserRejected => Ok(None),
+            LedgerStatus::Error(code) => Err(RuntimeError::ConnectionError(format!(
+                "Ledger error 0x{:04x}",
+                code
+            ))),
+        }
+    }
+
+    /// Human readable summary of the transaction shown before approval
+    pub fn summarize(request: &SigningRequest, analysis: &SecurityAnalysis) -> String {
+        let mut summary = format!(
+            "{} ({:?} risk)\n{}",
+            request.context.operation,
+            analysis.overall_risk_level,
+            request.transaction.metadata.description
+        );
+        for program in &analysis.programs {
+            summary.push_str(&format!(
+                "\n- {} {}{}",
+                program.name,
+                program.program_id,
+                if program.verified { "" } else { " (unverified)" }
+            ));
+        }
+        summary
+    }
+}
+
+#[async_trait]
+impl SigningService for LedgerSigner {
+    fn backend_type(&self) -> SigningBackend {
+        SigningBackend::HardwareWallet
+    }
+
+    async fn has_signer(&self, pubkey: &Pubkey) -> Result<bool> {
+        Ok(self.accounts.read().await.contains_key(pubkey))
+    }
+
+    async fn available_signers(&self) -> Result<Vec<Pubkey>> {
+        Ok(self.accounts.read().await.keys().copied().collect())
+    }
+
+    async fn sign_transaction(&self, request: SigningRequest) -> Result<SigningResponse> {
+        let message: Message = bincode::deserialize(&request.transaction.message)
+            .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+        let message_data = message.serialize();
+
+        let analysis = self
+            .analyzer
+            .analyze_transaction(&Transaction::new_unsigned(message));
+        (self.display)(&Self::summarize(&request, &analysis));
+
+        let accounts = self.accounts.read().await.clone();
+        let mut signatures = Vec::new();
+        for signer in &request.required_signers {
+            let Some(path) = accounts.get(signer) else {
+                continue;
+            };
+
+            match self.sign_message(path, &message_data).await? {
+                Some(signature) => signatures.push((*signer, signature)),
+                None => {
+                    return Ok(SigningResponse {
+                        request_id: request.request_id,
+                        result: SigningResult::Rejected {
+                            reason: "Rejected on Ledger device".to_string(),
+                            policy_violations: Vec::new(),
+                        },
+                        timestamp: chrono::Utc::now(),
+                    });
+                }
+            }
+        }
+
+        if signatures.is_empty() {
+            return Err(RuntimeError::SecurityViolation(
+                "No required signer is held by the Ledger".to_string(),
+            ));
+        }
+
+        let transaction = merge_signatures(&request.transaction, &signatures)?;
+        let signed_transaction = bincode::serialize(&transaction)
+            .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+
+        Ok(SigningResponse {
+            request_id: request.request_id,
+            result: SigningResult::Signed {
+                signatures: signatures.into_iter().map(|(_, s)| s).collect(),
+                signed_transaction,
+            },
+            timestamp: chrono::Utc::now(),
+        })
+    }
+
+    async fn verify_signatures(
+        &self,
+        transaction: &[u8],
+        signatures: &[Signature],
+        pubkeys: &[Pubkey],
+    ) -> Result<VerificationResult> {
+        Ok(verify_message_signatures(transaction, signatures, pubkeys))
+    }
+
+    async fn get_signing_policies(&self, pubkey: &Pubkey) -> Result<SigningPolicies> {
+        Ok(self
+            .policies
+            .read()
+            .await
+            .get(pubkey)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn update_signing_policies(
+        &self,
+        pubkey: &Pubkey,
+        policies: SigningPolicies,
+    ) -> Result<()> {
+        self.policies.write().await.insert(*pubkey, policies);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{signing::RiskLevel, SecurityContext};
+    use crate::{TransactionMetadata, UnsignedTransaction};
+    use solana_sdk::signature::{Keypair, Signer};
+    use std::sync::Mutex;
+
+    /// Simulated Solana app holding a single key
+    struct MockLedger {
+        keypair: Keypair,
+        reject: bool,
+        pending: Mutex<Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl LedgerTransport for MockLedger {
+        async fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>> {
+            let (ins, p2, data) = (apdu[1], apdu[3], &apdu[5..]);
+            let mut response = match ins {
+                INS_GET_PUBKEY => self.keypair.pubkey().to_bytes().to_vec(),
+                INS_SIGN_MESSAGE => {
+                    let mut pending = self.pending.lock().unwrap();
+                    if p2 & P2_EXTEND == 0 {
+                        // Skip signer count and the 4-level derivation path
+                        pending.clear();
+                        pending.extend_from_slice(&data[2 + 4 * 4..]);
+                    } else {
+                        pending.extend_from_slice(data);
+                    }
+                    if p2 & P2_MORE != 0 {
+                        Vec::new()
+                    } else if self.reject {
+                        return Ok(SW_USER_REJECTED.to_be_bytes().to_vec());
+                    } else {
+                        self.keypair.sign_message(&pending).as_ref().to_vec()
+                    }
+                }
+                _ => return Ok(0x6d00u16.to_be_bytes().to_vec()),
+            };
+            response.extend_from_slice(&SW_OK.to_be_bytes());
+            Ok(response)
+        }
+    }
+
+    fn request_for(payer: &Pubkey, instructions: usize) -> SigningRequest {
+        let instructions: Vec<_> = (0..instructions)
+            .map(|_| solana_sdk::system_instruction::transfer(payer, &Pubkey::new_unique(), 1))
+            .collect();
+        let message = Message::new(&instructions, Some(payer));
+        let unsigned = UnsignedTransaction {
+            message: bincode::serialize(&message).unwrap(),
+            recent_blockhash: solana_sdk::hash::Hash::default(),
+            signers: vec![*payer],
+            metadata: TransactionMetadata {
+                description: "Transfer".to_string(),
+                compute_units: None,
+                priority_fee: None,
+                simulation: None,
+            },
+        };
+        SigningRequest::new(unsigned, "transfer".to_string(), RiskLevel::High)
+    }
+
+    fn ledger(reject: bool) -> LedgerSigner {
+        let transport = Arc::new(MockLedger {
+            keypair: Keypair::new(),
+            reject,
+            pending: Mutex::new(Vec::new()),
+        });
+        LedgerSigner::new(transport, SecurityAnalyzer::new(SecurityContext::new()))
+    }
+
+    #[test]
+    fn test_derivation_path_serialization() {
+        let path = DerivationPath::new_bip44(Some(1), Some(0));
+        let bytes = serialize_derivation_path(&path);
+
+        assert_eq!(bytes[0], 4);
+        assert_eq!(&bytes[1..5], &(0x8000_0000u32 | 44).to_be_bytes());
+        assert_eq!(&bytes[5..9], &(0x8000_0000u32 | 501).to_be_bytes());
+        assert_eq!(&bytes[9..13], &(0x8000_0000u32 | 1).to_be_bytes());
+    }
+
+    #[test]
+    fn test_sign_message_chunking() {
+        let path = DerivationPath::new_bip44(Some(0), Some(0));
+        let apdus = sign_message_apdus(&path, &[7u8; 600]);
+
+        assert_eq!(apdus.len(), 3);
+        assert_eq!(apdus[0][3], P2_MORE);
+        assert_eq!(apdus[1][3], P2_EXTEND | P2_MORE);
+        assert_eq!(apdus[2][3], P2_EXTEND);
+        assert!(apdus.iter().all(|apdu| apdu.len() <= 5 + MAX_CHUNK_SIZE));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_signs_transaction() {
+        let summaries = Arc::new(Mutex::new(Vec::new()));
+        let captured = summaries.clone();
+        let signer = ledger(false).with_display(Arc::new(move |summary: &str| {
+            captured.lock().unwrap().push(summary.to_string())
+        }));
+
+        let pubkey = signer.add_account(0, Some(0)).await.unwrap();
+        assert!(signer.has_signer(&pubkey).await.unwrap());
+
+        // Enough instructions to require multiple APDU chunks
+        let response = signer
+            .sign_transaction(request_for(&pubkey, 6))
+            .await
+            .unwrap();
+
+        match response.result {
+            SigningResult::Signed {
+                signed_transaction,
+                ..
+            } => {
+                let transaction: Transaction = bincode::deserialize(&signed_transaction).unwrap();
+                assert!(transaction.verify().is_ok());
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(summaries.lock().unwrap()[0].contains("System Program"));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_rejection() {
+        let signer = ledger(true);
+        let pubkey = signer.add_account(0, None).await.unwrap();
+
+        let response = signer
+            .sign_transaction(request_for(&pubkey, 1))
+            .await
+            .unwrap();
+        assert!(matches!(response.result, SigningResult::Rejected { .. }));
+    }
+}
@@ -5,12 +5,22 @@ use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
 use std::collections::{HashMap, HashSet};
 
 pub mod audit;
+pub mod audit_postgres;
 pub mod validation;
 pub mod signing;
+pub mod remote_signer;
+pub mod ledger;
+pub mod mpc;
+pub mod policy;
 
-pub use audit::{AuditEntry, AuditLogger};
+pub use audit::{AuditEntry, AuditLogger, AuditQuery};
+pub use audit_postgres::PostgresAuditStorage;
 pub use validation::{TransactionValidator, ValidationResult, ValidationRule};
 pub use signing::{SigningService, CompositeSigningService, SigningRequest, SigningResponse};
+pub use remote_signer::{RemoteSigner, RemoteSignerConfig, RemoteSignerTls};
+pub use ledger::{LedgerSigner, LedgerTransport};
+pub use policy::{PolicyEngine, PolicyRule, PolicySet, RuleKind};
+pub use mpc::{CoSignerTransport, LocalCoSigner, MpcSigningCoordinator, QuorumPolicy};
 
 /// Security context for operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +82,8 @@ pub struct SecurityPolicies {
     pub program_restrictions: ProgramRestrictions,
     pub account_restrictions: AccountRestrictions,
     pub rate_limits: RateLimits,
+    #[serde(default)]
+    pub signing_quorum: mpc::QuorumPolicy,
 }
 
 /// Transaction limits
@@ -0,0 +1,748 @@
+//! Threshold (MPC) signing coordinator producing ed25519 signatures from co-signer shares
+//!
+//! Co-signers hold Shamir shares of a group key. Signing follows FROST's two
+//! rounds: each participant commits to a hiding and a binding nonce, then
+//! returns a partial signature once it has seen the full commitment list.
+//! Every signer derives its own binding factor from that list and the
+//! message, so no co-signer can bias the group commitment by choosing its
+//! nonce after seeing the others'. The partial signatures sum to a standard
+//! ed25519 signature for the group public key.
+
+use crate::{
+    security::{
+        signing::{
+            merge_signatures, verify_message_signatures, RiskLevel, SigningBackend,
+            SigningPolicies, SigningRequest, SigningResponse, SigningResult, SigningService,
+            VerificationResult,
+        },
+        SecurityPolicies,
+    },
+    Result, RuntimeError,
+};
+use async_trait::async_trait;
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_POINT,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use solana_sdk::{message::Message, pubkey::Pubkey, signature::Signature};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+// ================================
+// Key Shares
+// ================================
+
+/// A co-signer's share of the group signing key
+#[derive(Clone)]
+pub struct KeyShare {
+    /// Share index, starting at 1
+    pub id: u16,
+    pub secret: Scalar,
+    pub group_pubkey: Pubkey,
+}
+
+impl std::fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("id", &self.id)
+            .field("group_pubkey", &self.group_pubkey)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Split a fresh group key into `total` shares, any `threshold` of which can sign
+pub fn generate_key_shares(threshold: u16, total: u16) -> Result<(Pubkey, Vec<KeyShare>)> {
+    if threshold == 0 || threshold > total {
+        return Err(RuntimeError::InvalidConfiguration(format!(
+            "Invalid threshold {} of {}",
+            threshold, total
+        )));
+    }
+
+    // Random polynomial of degree threshold - 1 whose constant term is the group secret
+    let coefficients: Vec<Scalar> = (0..threshold).map(|_| random_scalar()).collect();
+    let group_point = ED25519_BASEPOINT_POINT * coefficients[0];
+    let group_pubkey = Pubkey::new_from_array(group_point.compress().to_bytes());
+
+    let shares = (1..=total)
+        .map(|id| {
+            let x = Scalar::from(u64::from(id));
+            let secret = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |acc, coefficient| acc * x + coefficient);
+            KeyShare {
+                id,
+                secret,
+                group_pubkey,
+            }
+        })
+        .collect();
+
+    Ok((group_pubkey, shares))
+}
+
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+/// Lagrange coefficient at zero for `id` over the participant set
+fn lagrange_coefficient(id: u16, participants: &[u16]) -> Result<Scalar> {
+    let x_i = Scalar::from(u64::from(id));
+    let mut numerator = Scalar::ONE;
+    let mut denominator = Scalar::ONE;
+
+    for &other in participants.iter().filter(|&&other| other != id) {
+        let x_j = Scalar::from(u64::from(other));
+        numerator *= x_j;
+        denominator *= x_j - x_i;
+    }
+
+    if denominator == Scalar::ZERO {
+        return Err(RuntimeError::SecurityViolation(
+            "Duplicate co-signer in participant set".to_string(),
+        ));
+    }
+    Ok(numerator * denominator.invert())
+}
+
+/// Domain separating FROST binding factors from other SHA-512 uses
+const BINDING_FACTOR_DOMAIN: &[u8] = b"valence-frost-ed25519-rho";
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// Binding factor of each commitment, in list order
+///
+/// Each factor covers the group key, the message and the whole commitment
+/// list, so changing any participant's commitment changes every factor.
+fn binding_factors(session: &SigningSession, commitments: &[NonceCommitment]) -> Result<Vec<Scalar>> {
+    if commitments.is_empty()
+        || commitments
+            .windows(2)
+            .any(|pair| pair[0].cosigner_id >= pair[1].cosigner_id)
+    {
+        return Err(RuntimeError::SecurityViolation(
+            "Commitment list must be non-empty and ordered by co-signer id".to_string(),
+        ));
+    }
+
+    let encoded: Vec<u8> = commitments
+        .iter()
+        .flat_map(|commitment| {
+            [
+                &commitment.cosigner_id.to_le_bytes()[..],
+                &commitment.hiding,
+                &commitment.binding,
+            ]
+            .concat()
+        })
+        .collect();
+    let message_hash = Sha512::digest(&session.message);
+    let commitments_hash = Sha512::digest(&encoded);
+
+    Ok(commitments
+        .iter()
+        .map(|commitment| {
+            hash_to_scalar(&[
+                BINDING_FACTOR_DOMAIN,
+                session.group_pubkey.as_ref(),
+                &message_hash,
+                &commitments_hash,
+                &commitment.cosigner_id.to_le_bytes(),
+            ])
+        })
+        .collect())
+}
+
+/// Group commitment `R = sum(D_i + rho_i * E_i)`
+fn group_commitment(commitments: &[NonceCommitment], factors: &[Scalar]) -> Result<[u8; 32]> {
+    let mut group_commitment = EdwardsPoint::default();
+    for (commitment, factor) in commitments.iter().zip(factors) {
+        group_commitment += decompress(&commitment.hiding)? + decompress(&commitment.binding)? * factor;
+    }
+    Ok(group_commitment.compress().to_bytes())
+}
+
+/// Ed25519 challenge `H(R || A || M)`
+fn challenge(group_commitment: &[u8; 32], group_pubkey: &Pubkey, message: &[u8]) -> Scalar {
+    hash_to_scalar(&[group_commitment, group_pubkey.as_ref(), message])
+}
+
+fn decompress(bytes: &[u8; 32]) -> Result<EdwardsPoint> {
+    CompressedEdwardsY(*bytes).decompress().ok_or_else(|| {
+        RuntimeError::SecurityViolation("Invalid curve point from co-signer".to_string())
+    })
+}
+
+// ================================
+// Co-signer Protocol
+// ================================
+
+/// Message being signed by the group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningSession {
+    pub request_id: String,
+    pub message: Vec<u8>,
+    pub group_pubkey: Pubkey,
+}
+
+/// Round one: a co-signer's hiding and binding nonce commitments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceCommitment {
+    pub cosigner_id: u16,
+    pub hiding: [u8; 32],
+    pub binding: [u8; 32],
+}
+
+/// Round two input: the commitments of every participant, ordered by id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningRound {
+    pub commitments: Vec<NonceCommitment>,
+}
+
+impl SigningRound {
+    pub fn participants(&self) -> Vec<u16> {
+        self.commitments.iter().map(|c| c.cosigner_id).collect()
+    }
+}
+
+/// Round two: a co-signer's partial signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub cosigner_id: u16,
+    pub share: [u8; 32],
+}
+
+/// Transport to a single co-signer (in-process, HTTP, message queue, ...)
+#[async_trait]
+pub trait CoSignerTransport: Send + Sync {
+    fn cosigner_id(&self) -> u16;
+    async fn commit(&self, session: &SigningSession) -> Result<NonceCommitment>;
+    async fn sign(&self, session: &SigningSession, round: &SigningRound) -> Result<PartialSignature>;
+    /// Forget any nonces committed for the session
+    ///
+    /// Called after every signing attempt, whether or not the co-signer was
+    /// part of the quorum, so no nonce outlives its round.
+    async fn discard(&self, session: &SigningSession) -> Result<()>;
+}
+
+/// A co-signer's secret nonces for one session
+struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+/// Co-signer holding its key share in process
+pub struct LocalCoSigner {
+    share: KeyShare,
+    nonces: Mutex<HashMap<String, SigningNonces>>,
+}
+
+impl LocalCoSigner {
+    pub fn new(share: KeyShare) -> Self {
+        Self {
+            share,
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl CoSignerTransport for LocalCoSigner {
+    fn cosigner_id(&self) -> u16 {
+        self.share.id
+    }
+
+    async fn commit(&self, session: &SigningSession) -> Result<NonceCommitment> {
+        let nonces = SigningNonces {
+            hiding: random_scalar(),
+            binding: random_scalar(),
+        };
+        let commitment = NonceCommitment {
+            cosigner_id: self.share.id,
+            hiding: (ED25519_BASEPOINT_POINT * nonces.hiding).compress().to_bytes(),
+            binding: (ED25519_BASEPOINT_POINT * nonces.binding).compress().to_bytes(),
+        };
+        self.nonces
+            .lock()
+            .await
+            .insert(session.request_id.clone(), nonces);
+
+        Ok(commitment)
+    }
+
+    async fn sign(&self, session: &SigningSession, round: &SigningRound) -> Result<PartialSignature> {
+        // Nonces are single use, even when the round is rejected below
+        let nonces = self
+            .nonces
+            .lock()
+            .await
+            .remove(&session.request_id)
+            .ok_or_else(|| {
+                RuntimeError::SecurityViolation("No nonce committed for session".to_string())
+            })?;
+
+        if session.group_pubkey != self.share.group_pubkey {
+            return Err(RuntimeError::SecurityViolation(
+                "Session targets a different group key".to_string(),
+            ));
+        }
+
+        let own = NonceCommitment {
+            cosigner_id: self.share.id,
+            hiding: (ED25519_BASEPOINT_POINT * nonces.hiding).compress().to_bytes(),
+            binding: (ED25519_BASEPOINT_POINT * nonces.binding).compress().to_bytes(),
+        };
+        let index = round
+            .commitments
+            .iter()
+            .position(|commitment| *commitment == own)
+            .ok_or_else(|| {
+                RuntimeError::SecurityViolation(
+                    "Round does not carry this co-signer's commitment".to_string(),
+                )
+            })?;
+
+        let factors = binding_factors(session, &round.commitments)?;
+        let group_commitment = group_commitment(&round.commitments, &factors)?;
+        let lambda = lagrange_coefficient(self.share.id, &round.participants())?;
+        let k = challenge(&group_commitment, &session.group_pubkey, &session.message);
+        let share = nonces.hiding + nonces.binding * factors[index] + k * lambda * self.share.secret;
+
+        Ok(PartialSignature {
+            cosigner_id: self.share.id,
+            share: share.to_bytes(),
+        })
+    }
+
+    async fn discard(&self, session: &SigningSession) -> Result<()> {
+        self.nonces.lock().await.remove(&session.request_id);
+        Ok(())
+    }
+}
+
+// ================================
+// Coordinator
+// ================================
+
+/// Quorum sizes required per risk level
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct QuorumPolicy {
+    /// Quorum used when no risk level specific size is set
+    pub default_quorum: Option<u16>,
+    pub by_risk_level: HashMap<RiskLevel, u16>,
+}
+
+impl QuorumPolicy {
+    /// Quorum required for a risk level, never below the key threshold
+    pub fn required(&self, risk_level: RiskLevel, threshold: u16) -> u16 {
+        self.by_risk_level
+            .get(&risk_level)
+            .copied()
+            .or(self.default_quorum)
+            .unwrap_or(threshold)
+            .max(threshold)
+    }
+}
+
+/// Coordinates threshold signing across co-signers
+pub struct MpcSigningCoordinator {
+    group_pubkey: Pubkey,
+    threshold: u16,
+    cosigners: Vec<Arc<dyn CoSignerTransport>>,
+    security_policies: SecurityPolicies,
+    policies: RwLock<HashMap<Pubkey, SigningPolicies>>,
+}
+
+impl MpcSigningCoordinator {
+    pub fn new(
+        group_pubkey: Pubkey,
+        threshold: u16,
+        cosigners: Vec<Arc<dyn CoSignerTransport>>,
+        security_policies: SecurityPolicies,
+    ) -> Self {
+        Self {
+            group_pubkey,
+            threshold,
+            cosigners,
+            security_policies,
+            policies: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn group_pubkey(&self) -> Pubkey {
+        self.group_pubkey
+    }
+
+    fn rejected(request_id: String, reason: String) -> SigningResponse {
+        SigningResponse {
+            request_id,
+            result: SigningResult::Rejected {
+                reason,
+                policy_violations: Vec::new(),
+            },
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    /// Run both signing rounds, then burn every nonce committed for the session
+    async fn threshold_sign(&self, session: &SigningSession, quorum: usize) -> Result<Option<Signature>> {
+        let result = self.signing_rounds(session, quorum).await;
+
+        // Co-signers outside the quorum committed too; their nonces must not be reused
+        let discarded = futures::future::join_all(
+            self.cosigners.iter().map(|cosigner| cosigner.discard(session)),
+        )
+        .await;
+        for (cosigner, discarded) in self.cosigners.iter().zip(discarded) {
+            if let Err(e) = discarded {
+                warn!("Co-signer {} failed to discard its nonces: {}", cosigner.cosigner_id(), e);
+            }
+        }
+
+        result
+    }
+
+    /// Run both signing rounds and aggregate the group signature
+    async fn signing_rounds(&self, session: &SigningSession, quorum: usize) -> Result<Option<Signature>> {
+        // Round one: gather commitments from every reachable co-signer
+        let commitments = futures::future::join_all(
+            self.cosigners.iter().map(|cosigner| cosigner.commit(session)),
+        )
+        .await;
+
+        let mut participants = Vec::new();
+        for (cosigner, commitment) in self.cosigners.iter().zip(commitments) {
+            if participants.len() == quorum {
+                break;
+            }
+            match commitment {
+                Ok(commitment) if commitment.cosigner_id == cosigner.cosigner_id() => {
+                    participants.push((cosigner.clone(), commitment));
+                }
+                Ok(_) => warn!("Co-signer {} answered with a foreign id", cosigner.cosigner_id()),
+                Err(e) => warn!("Co-signer {} failed to commit: {}", cosigner.cosigner_id(), e),
+            }
+        }
+
+        if participants.len() < quorum {
+            return Ok(None);
+        }
+        participants.sort_by_key(|(cosigner, _)| cosigner.cosigner_id());
+
+        // Round two: partial signatures once every participant has seen all commitments
+        let round = SigningRound {
+            commitments: participants.iter().map(|(_, c)| c.clone()).collect(),
+        };
+        let factors = binding_factors(session, &round.commitments)?;
+        let group_commitment = group_commitment(&round.commitments, &factors)?;
+        let partials = futures::future::join_all(
+            participants.iter().map(|(cosigner, _)| cosigner.sign(session, &round)),
+        )
+        .await;
+
+        let mut aggregate = Scalar::ZERO;
+        for partial in partials {
+            let share = Option::<Scalar>::from(Scalar::from_canonical_bytes(partial?.share))
+                .ok_or_else(|| {
+                    RuntimeError::SecurityViolation("Non-canonical signature share".to_string())
+                })?;
+            aggregate += share;
+        }
+
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&group_commitment);
+        bytes[32..].copy_from_slice(aggregate.as_bytes());
+        let signature = Signature::from(bytes);
+
+        if !signature.verify(self.group_pubkey.as_ref(), &session.message) {
+            return Err(RuntimeError::SecurityViolation(
+                "Aggregated signature failed verification".to_string(),
+            ));
+        }
+        Ok(Some(signature))
+    }
+}
+
+#[async_trait]
+impl SigningService for MpcSigningCoordinator {
+    fn backend_type(&self) -> SigningBackend {
+        SigningBackend::MPC
+    }
+
+    async fn has_signer(&self, pubkey: &Pubkey) -> Result<bool> {
+        Ok(pubkey == &self.group_pubkey)
+    }
+
+    async fn available_signers(&self) -> Result<Vec<Pubkey>> {
+        Ok(vec![self.group_pubkey])
+    }
+
+    async fn sign_transaction(&self, request: SigningRequest) -> Result<SigningResponse> {
+        if !request.required_signers.contains(&self.group_pubkey) {
+            return Err(RuntimeError::SecurityViolation(
+                "Group key is not a required signer".to_string(),
+            ));
+        }
+
+        let quorum = usize::from(
+            self.security_policies
+                .signing_quorum
+                .required(request.context.risk_level, self.threshold),
+        );
+        if self.cosigners.len() < quorum {
+            return Ok(Self::rejected(
+                request.request_id,
+                format!(
+                    "Quorum of {} exceeds {} configured co-signers",
+                    quorum,
+                    self.cosigners.len()
+                ),
+            ));
+        }
+
+        let message: Message = bincode::deserialize(&request.transaction.message)
+            .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+        let session = SigningSession {
+            request_id: request.request_id.clone(),
+            message: message.serialize(),
+            group_pubkey: self.group_pubkey,
+        };
+
+        info!(
+            "Collecting {} of {} co-signer shares for {}",
+            quorum,
+            self.cosigners.len(),
+            request.request_id
+        );
+
+        let Some(signature) = self.threshold_sign(&session, quorum).await? else {
+            return Ok(Self::rejected(
+                request.request_id,
+                format!("Fewer than {} co-signers available", quorum),
+            ));
+        };
+
+        let transaction = merge_signatures(&request.transaction, &[(self.group_pubkey, signature)])?;
+        let signed_transaction = bincode::serialize(&transaction)
+            .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+
+        Ok(SigningResponse {
+            request_id: request.request_id,
+            result: SigningResult::Signed {
+                signatures: vec![signature],
+                signed_transaction,
+            },
+            timestamp: chrono::Utc::now(),
+        })
+    }
+
+    async fn verify_signatures(
+        &self,
+        transaction: &[u8],
+        signatures: &[Signature],
+        pubkeys: &[Pubkey],
+    ) -> Result<VerificationResult> {
+        Ok(verify_message_signatures(transaction, signatures, pubkeys))
+    }
+
+    async fn get_signing_policies(&self, pubkey: &Pubkey) -> Result<SigningPolicies> {
+        Ok(self
+            .policies
+            .read()
+            .await
+            .get(pubkey)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn update_signing_policies(
+        &self,
+        pubkey: &Pubkey,
+        policies: SigningPolicies,
+    ) -> Result<()> {
+        self.policies.write().await.insert(*pubkey, policies);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionMetadata, UnsignedTransaction};
+    use solana_sdk::transaction::Transaction;
+
+    fn request_for(signer: &Pubkey, risk_level: RiskLevel) -> SigningRequest {
+        let instruction =
+            solana_sdk::system_instruction::transfer(signer, &Pubkey::new_unique(), 1);
+        let message = Message::new(&[instruction], Some(signer));
+        let unsigned = UnsignedTransaction {
+            message: bincode::serialize(&message).unwrap(),
+            recent_blockhash: solana_sdk::hash::Hash::default(),
+            signers: vec![*signer],
+            metadata: TransactionMetadata {
+                description: "Transfer".to_string(),
+                compute_units: None,
+                priority_fee: None,
+                simulation: None,
+            },
+        };
+        SigningRequest::new(unsigned, "transfer".to_string(), risk_level)
+    }
+
+    fn coordinator(
+        threshold: u16,
+        total: u16,
+        online: usize,
+        policies: SecurityPolicies,
+    ) -> MpcSigningCoordinator {
+        let (group_pubkey, shares) = generate_key_shares(threshold, total).unwrap();
+        let cosigners: Vec<Arc<dyn CoSignerTransport>> = shares
+            .into_iter()
+            .take(online)
+            .map(|share| Arc::new(LocalCoSigner::new(share)) as Arc<dyn CoSignerTransport>)
+            .collect();
+        MpcSigningCoordinator::new(group_pubkey, threshold, cosigners, policies)
+    }
+
+    #[tokio::test]
+    async fn test_threshold_signature_verifies() {
+        let coordinator = coordinator(2, 3, 3, SecurityPolicies::default());
+        let request = request_for(&coordinator.group_pubkey(), RiskLevel::Low);
+
+        let response = coordinator.sign_transaction(request).await.unwrap();
+        match response.result {
+            SigningResult::Signed {
+                signed_transaction,
+                ..
+            } => {
+                let transaction: Transaction = bincode::deserialize(&signed_transaction).unwrap();
+                assert!(transaction.verify().is_ok());
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_policy_quorum_enforced() {
+        let mut policies = SecurityPolicies::default();
+        policies
+            .signing_quorum
+            .by_risk_level
+            .insert(RiskLevel::Critical, 3);
+
+        // Only two of three co-signers are reachable
+        let coordinator = coordinator(2, 3, 2, policies);
+        let group = coordinator.group_pubkey();
+
+        let low = coordinator
+            .sign_transaction(request_for(&group, RiskLevel::Low))
+            .await
+            .unwrap();
+        assert!(matches!(low.result, SigningResult::Signed { .. }));
+
+        let critical = coordinator
+            .sign_transaction(request_for(&group, RiskLevel::Critical))
+            .await
+            .unwrap();
+        assert!(matches!(critical.result, SigningResult::Rejected { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_nonces_burned_outside_quorum() {
+        let (group_pubkey, shares) = generate_key_shares(2, 3).unwrap();
+        let locals: Vec<Arc<LocalCoSigner>> = shares
+            .into_iter()
+            .map(|share| Arc::new(LocalCoSigner::new(share)))
+            .collect();
+        let cosigners = locals
+            .iter()
+            .map(|local| local.clone() as Arc<dyn CoSignerTransport>)
+            .collect();
+        let coordinator =
+            MpcSigningCoordinator::new(group_pubkey, 2, cosigners, SecurityPolicies::default());
+
+        let response = coordinator
+            .sign_transaction(request_for(&group_pubkey, RiskLevel::Low))
+            .await
+            .unwrap();
+        assert!(matches!(response.result, SigningResult::Signed { .. }));
+
+        // The third co-signer committed but was left out of the quorum
+        for local in &locals {
+            assert!(local.nonces.lock().await.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cosigner_requires_own_commitment() {
+        let (group_pubkey, shares) = generate_key_shares(2, 2).unwrap();
+        let [first, second] = [&shares[0], &shares[1]].map(|share| LocalCoSigner::new(share.clone()));
+        let session = SigningSession {
+            request_id: "request".to_string(),
+            message: b"message".to_vec(),
+            group_pubkey,
+        };
+        let commitments = vec![
+            first.commit(&session).await.unwrap(),
+            second.commit(&session).await.unwrap(),
+        ];
+
+        let without_first = SigningRound {
+            commitments: commitments[1..].to_vec(),
+        };
+        assert!(first.sign(&session, &without_first).await.is_err());
+
+        // The rejected round still burned the nonces
+        let round = SigningRound { commitments };
+        assert!(first.sign(&session, &round).await.is_err());
+        assert!(second.sign(&session, &round).await.is_ok());
+    }
+
+    #[test]
+    fn test_binding_factors_cover_all_commitments() {
+        let session = SigningSession {
+            request_id: "request".to_string(),
+            message: b"message".to_vec(),
+            group_pubkey: Pubkey::new_unique(),
+        };
+        let commitment = |cosigner_id, binding| NonceCommitment {
+            cosigner_id,
+            hiding: [1; 32],
+            binding,
+        };
+        let commitments = vec![commitment(1, [2; 32]), commitment(2, [3; 32])];
+        let factors = binding_factors(&session, &commitments).unwrap();
+        assert_ne!(factors[0], factors[1]);
+
+        // Another signer's commitment changes every factor
+        let changed = vec![commitment(1, [2; 32]), commitment(2, [4; 32])];
+        assert_ne!(binding_factors(&session, &changed).unwrap()[0], factors[0]);
+
+        let unordered = vec![commitment(2, [3; 32]), commitment(1, [2; 32])];
+        assert!(binding_factors(&session, &unordered).is_err());
+    }
+
+    #[test]
+    fn test_quorum_never_below_threshold() {
+        let policy = QuorumPolicy {
+            default_quorum: Some(1),
+            by_risk_level: HashMap::new(),
+        };
+        assert_eq!(policy.required(RiskLevel::Low, 2), 2);
+        assert!(generate_key_shares(3, 2).is_err());
+    }
+}
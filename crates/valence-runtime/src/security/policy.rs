@@ -0,0 +1,479 @@
+//! Declarative transaction policies loaded from YAML or JSON
+//!
+//! A policy file lists rules, each with a stable `id` that is reported on
+//! violation:
+//!
+//! ```yaml
+//! rules:
+//!   - id: only-known-programs
+//!     type: allowed_programs
+//!     programs: ["11111111111111111111111111111111"]
+//!   - id: transfer-cap
+//!     type: max_lamports
+//!     max: 1000000000
+//! ```
+
+use crate::{transaction::builder::SimulationResult, Result, RuntimeError};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{message::Message, pubkey::Pubkey, transaction::Transaction};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+// ================================
+// Policy Definitions
+// ================================
+
+/// A set of declarative rules
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicySet {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// A single rule with its identifier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: RuleKind,
+}
+
+/// Rule types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleKind {
+    /// Only these programs may be invoked
+    AllowedPrograms {
+        #[serde(with = "pubkey_set")]
+        programs: HashSet<Pubkey>,
+    },
+    /// Cap on lamports moved by system program instructions
+    MaxLamports { max: u64 },
+    /// Instruction data prefixes (hex) that must not be invoked
+    ForbiddenDiscriminators {
+        #[serde(default, with = "optional_pubkey")]
+        program: Option<Pubkey>,
+        discriminators: Vec<String>,
+    },
+    /// The transaction must carry a successful simulation
+    RequireSimulationSuccess,
+    /// Lamports may only be sent to these destinations
+    DestinationAllowlist {
+        #[serde(with = "pubkey_set")]
+        destinations: HashSet<Pubkey>,
+    },
+}
+
+impl PolicySet {
+    /// Parse a policy set, treating `.yaml`/`.yml` paths as YAML and everything else as JSON
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let policies = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => Self::from_yaml(&contents)?,
+            _ => Self::from_json(&contents)?,
+        };
+        Ok(policies)
+    }
+
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        let policies: Self = serde_yaml::from_str(contents)
+            .map_err(|e| RuntimeError::InvalidConfiguration(format!("Invalid policy YAML: {}", e)))?;
+        policies.check()?;
+        Ok(policies)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let policies: Self = serde_json::from_str(contents)?;
+        policies.check()?;
+        Ok(policies)
+    }
+
+    /// Reject duplicate rule ids and malformed discriminators
+    fn check(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for rule in &self.rules {
+            if !ids.insert(rule.id.as_str()) {
+                return Err(RuntimeError::InvalidConfiguration(format!(
+                    "Duplicate policy rule id: {}",
+                    rule.id
+                )));
+            }
+            if let RuleKind::ForbiddenDiscriminators { discriminators, .. } = &rule.kind {
+                for discriminator in discriminators {
+                    hex::decode(discriminator).map_err(|e| {
+                        RuntimeError::InvalidConfiguration(format!(
+                            "Rule {}: invalid discriminator {}: {}",
+                            rule.id, discriminator, e
+                        ))
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate every rule, failing on the first violation
+    pub fn evaluate(
+        &self,
+        transaction: &Transaction,
+        simulation: Option<&SimulationResult>,
+    ) -> Result<()> {
+        // Rules cannot judge instructions whose accounts they cannot resolve
+        if let Some(reason) = unresolved_account(&transaction.message) {
+            return Err(RuntimeError::SecurityViolation(format!(
+                "Policy violated: {}",
+                reason
+            )));
+        }
+
+        for rule in &self.rules {
+            if let Some(reason) = rule.kind.violation(transaction, simulation) {
+                return Err(RuntimeError::SecurityViolation(format!(
+                    "Policy rule {} violated: {}",
+                    rule.id, reason
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RuleKind {
+    fn violation(
+        &self,
+        transaction: &Transaction,
+        simulation: Option<&SimulationResult>,
+    ) -> Option<String> {
+        let message = &transaction.message;
+        let instructions = message.instructions.iter().filter_map(|instruction| {
+            let program_id = message.account_keys.get(instruction.program_id_index as usize)?;
+            Some((*program_id, instruction))
+        });
+
+        match self {
+            RuleKind::AllowedPrograms { programs } => instructions
+                .map(|(program_id, _)| program_id)
+                .find(|program_id| !programs.contains(program_id))
+                .map(|program_id| format!("program {} is not allowed", program_id)),
+            RuleKind::MaxLamports { max } => {
+                let moved: u64 = lamport_transfers(transaction)
+                    .map(|(_, lamports)| lamports)
+                    .fold(0, u64::saturating_add);
+                (moved > *max).then(|| format!("moves {} lamports, limit is {}", moved, max))
+            }
+            RuleKind::ForbiddenDiscriminators {
+                program,
+                discriminators,
+            } => instructions
+                .filter(|(program_id, _)| program.is_none_or(|p| p == *program_id))
+                .find_map(|(program_id, instruction)| {
+                    discriminators
+                        .iter()
+                        .find(|d| {
+                            hex::decode(d)
+                                .map(|prefix| instruction.data.starts_with(&prefix))
+                                .unwrap_or(false)
+                        })
+                        .map(|d| format!("discriminator {} invoked on {}", d, program_id))
+                }),
+            RuleKind::RequireSimulationSuccess => match simulation {
+                Some(result) if result.success => None,
+                Some(result) => Some(format!(
+                    "simulation failed: {}",
+                    result.error.as_deref().unwrap_or("unknown error")
+                )),
+                None => Some("transaction was not simulated".to_string()),
+            },
+            RuleKind::DestinationAllowlist { destinations } => lamport_transfers(transaction)
+                .map(|(destination, _)| destination)
+                .find(|destination| !destinations.contains(destination))
+                .map(|destination| format!("destination {} is not allowlisted", destination)),
+        }
+    }
+}
+
+/// The first account index an instruction uses that the message does not contain
+fn unresolved_account(message: &Message) -> Option<String> {
+    message
+        .instructions
+        .iter()
+        .enumerate()
+        .find_map(|(position, instruction)| {
+            std::iter::once(instruction.program_id_index)
+                .chain(instruction.accounts.iter().copied())
+                .find(|&index| message.account_keys.get(index as usize).is_none())
+                .map(|index| {
+                    format!(
+                        "instruction {} references account index {} outside the message",
+                        position, index
+                    )
+                })
+        })
+}
+
+/// Destinations and amounts of system program lamport transfers
+fn lamport_transfers(transaction: &Transaction) -> impl Iterator<Item = (Pubkey, u64)> + '_ {
+    use solana_sdk::system_instruction::SystemInstruction;
+
+    let message = &transaction.message;
+    message.instructions.iter().filter_map(move |instruction| {
+        let program_id = message.account_keys.get(instruction.program_id_index as usize)?;
+        if *program_id != solana_sdk::system_program::id() {
+            return None;
+        }
+        let account = |index: usize| {
+            instruction
+                .accounts
+                .get(index)
+                .and_then(|&i| message.account_keys.get(i as usize))
+                .copied()
+        };
+        match bincode::deserialize::<SystemInstruction>(&instruction.data).ok()? {
+            SystemInstruction::Transfer { lamports } => Some((account(1)?, lamports)),
+            SystemInstruction::TransferWithSeed { lamports, .. } => Some((account(2)?, lamports)),
+            SystemInstruction::CreateAccount { lamports, .. }
+            | SystemInstruction::CreateAccountWithSeed { lamports, .. } => {
+                Some((account(1)?, lamports))
+            }
+            _ => None,
+        }
+    })
+}
+
+// ================================
+// Policy Engine
+// ================================
+
+/// Holds the active policy set and reloads it when its file changes
+pub struct PolicyEngine {
+    policies: RwLock<Arc<PolicySet>>,
+    source: Option<PathBuf>,
+    modified: RwLock<Option<SystemTime>>,
+}
+
+impl PolicyEngine {
+    pub fn new(policies: PolicySet) -> Self {
+        Self {
+            policies: RwLock::new(Arc::new(policies)),
+            source: None,
+            modified: RwLock::new(None),
+        }
+    }
+
+    /// Load policies from a file that can later be reloaded
+    pub fn from_path(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let policies = PolicySet::from_path(&path)?;
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+        Ok(Self {
+            policies: RwLock::new(Arc::new(policies)),
+            source: Some(path),
+            modified: RwLock::new(modified),
+        })
+    }
+
+    /// Currently active policy set
+    pub async fn policies(&self) -> Arc<PolicySet> {
+        self.policies.read().await.clone()
+    }
+
+    /// Replace the active policy set
+    pub async fn replace(&self, policies: PolicySet) {
+        *self.policies.write().await = Arc::new(policies);
+    }
+
+    /// Re-read the source file; an invalid file keeps the previous policies
+    pub async fn reload(&self) -> Result<()> {
+        let Some(path) = &self.source else {
+            return Ok(());
+        };
+        let policies = PolicySet::from_path(path)?;
+        *self.modified.write().await = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        info!("Reloaded {} policy rules from {}", policies.rules.len(), path.display());
+        self.replace(policies).await;
+        Ok(())
+    }
+
+    /// Reload whenever the source file's modification time changes
+    pub fn watch(
+        self: Arc<Self>,
+        poll_interval: Duration,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = interval.tick() => {
+                        let Some(path) = &self.source else { break };
+                        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+                        if modified != *self.modified.read().await {
+                            if let Err(e) = self.reload().await {
+                                warn!("Keeping previous policies: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Evaluate the active policy set
+    pub async fn evaluate(
+        &self,
+        transaction: &Transaction,
+        simulation: Option<&SimulationResult>,
+    ) -> Result<()> {
+        self.policies().await.evaluate(transaction, simulation)
+    }
+}
+
+// ================================
+// Serde Helpers
+// ================================
+
+mod pubkey_set {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(keys: &HashSet<Pubkey>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().map(|key| key.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<HashSet<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|key| Pubkey::from_str(key).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+mod optional_pubkey {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &Option<Pubkey>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match key {
+            Some(key) => serializer.serialize_some(&key.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Pubkey>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|key| Pubkey::from_str(&key).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{instruction::Instruction, message::Message};
+
+    fn transaction(instructions: &[Instruction], payer: &Pubkey) -> Transaction {
+        Transaction::new_unsigned(Message::new(instructions, Some(payer)))
+    }
+
+    #[test]
+    fn test_yaml_rules_report_rule_id() {
+        let payer = Pubkey::new_unique();
+        let allowed = Pubkey::new_unique();
+        let yaml = format!(
+            "rules:\n  - id: transfer-cap\n    type: max_lamports\n    max: 100\n  - id: destinations\n    type: destination_allowlist\n    destinations: [\"{}\"]\n",
+            allowed
+        );
+        let policies = PolicySet::from_yaml(&yaml).unwrap();
+
+        let ok = transaction(&[solana_sdk::system_instruction::transfer(&payer, &allowed, 100)], &payer);
+        assert!(policies.evaluate(&ok, None).is_ok());
+
+        let too_much = transaction(&[solana_sdk::system_instruction::transfer(&payer, &allowed, 101)], &payer);
+        let err = policies.evaluate(&too_much, None).unwrap_err();
+        assert!(matches!(err, RuntimeError::SecurityViolation(ref m) if m.contains("transfer-cap")));
+
+        let elsewhere = transaction(
+            &[solana_sdk::system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)],
+            &payer,
+        );
+        let err = policies.evaluate(&elsewhere, None).unwrap_err();
+        assert!(matches!(err, RuntimeError::SecurityViolation(ref m) if m.contains("destinations")));
+    }
+
+    #[test]
+    fn test_forbidden_discriminator_and_simulation() {
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let json = format!(
+            r#"{{"rules": [
+                {{"id": "no-close", "type": "forbidden_discriminators", "program": "{}", "discriminators": ["deadbeef"]}},
+                {{"id": "simulated", "type": "require_simulation_success"}}
+            ]}}"#,
+            program
+        );
+        let policies = PolicySet::from_json(&json).unwrap();
+        let simulation = SimulationResult {
+            success: true,
+            error: None,
+            logs: Vec::new(),
+            units_consumed: None,
+        };
+
+        let close = Instruction::new_with_bytes(program, &[0xde, 0xad, 0xbe, 0xef, 1], vec![]);
+        let err = policies
+            .evaluate(&transaction(&[close], &payer), Some(&simulation))
+            .unwrap_err();
+        assert!(err.to_string().contains("no-close"));
+
+        let other = Instruction::new_with_bytes(program, &[1, 2, 3, 4], vec![]);
+        let tx = transaction(&[other], &payer);
+        assert!(policies.evaluate(&tx, Some(&simulation)).is_ok());
+        assert!(policies.evaluate(&tx, None).unwrap_err().to_string().contains("simulated"));
+    }
+
+    #[test]
+    fn test_out_of_range_account_index_rejected() {
+        let payer = Pubkey::new_unique();
+        let policies = PolicySet::from_json(r#"{"rules": [{"id": "cap", "type": "max_lamports", "max": 5}]}"#).unwrap();
+
+        let mut bad_program = transaction(&[solana_sdk::system_instruction::transfer(&payer, &payer, 1)], &payer);
+        bad_program.message.instructions[0].program_id_index = 200;
+        let err = policies.evaluate(&bad_program, None).unwrap_err();
+        assert!(matches!(err, RuntimeError::SecurityViolation(ref m) if m.contains("account index 200")));
+
+        let mut bad_account = transaction(&[solana_sdk::system_instruction::transfer(&payer, &payer, 1)], &payer);
+        bad_account.message.instructions[0].accounts[1] = 9;
+        assert!(policies.evaluate(&bad_account, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hot_reload() {
+        let path = std::env::temp_dir().join(format!("valence-policy-{}.json", Pubkey::new_unique()));
+        std::fs::write(&path, r#"{"rules": []}"#).unwrap();
+        let engine = PolicyEngine::from_path(&path).unwrap();
+        assert!(engine.policies().await.rules.is_empty());
+
+        std::fs::write(&path, r#"{"rules": [{"id": "cap", "type": "max_lamports", "max": 5}]}"#).unwrap();
+        engine.reload().await.unwrap();
+        assert_eq!(engine.policies().await.rules.len(), 1);
+
+        // A broken file leaves the active policies untouched
+        std::fs::write(&path, "not json").unwrap();
+        assert!(engine.reload().await.is_err());
+        assert_eq!(engine.policies().await.rules.len(), 1);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
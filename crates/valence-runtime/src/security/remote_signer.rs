@@ -0,0 +1,581 @@
+//! Remote signer backend delegating signatures to an external HTTP service
+
+use crate::{
+    security::signing::{
+        merge_signatures, verify_message_signatures, SigningBackend, SigningPolicies,
+        SigningRequest, SigningResponse, SigningResult, SigningService, VerificationResult,
+    },
+    Result, RuntimeError,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use std::{collections::HashMap, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Header carrying the public key that signed the request body
+pub const SIGNER_HEADER: &str = "x-valence-signer";
+
+/// Header carrying the request body signature
+pub const SIGNATURE_HEADER: &str = "x-valence-signature";
+
+// ================================
+// Configuration
+// ================================
+
+/// Mutual TLS settings for the remote signer connection
+#[derive(Debug, Clone)]
+pub struct RemoteSignerTls {
+    /// PEM file holding the client certificate chain and private key
+    pub client_identity_pem: PathBuf,
+
+    /// Optional CA certificate used to verify the signer
+    pub ca_certificate_pem: Option<PathBuf>,
+}
+
+/// Remote signer configuration
+#[derive(Debug, Clone)]
+pub struct RemoteSignerConfig {
+    /// Base URL of the signing service
+    pub endpoint: String,
+
+    /// Client authentication over mutual TLS
+    pub tls: Option<RemoteSignerTls>,
+
+    /// Key used to sign request bodies so the signer can authenticate the runtime
+    pub request_signing_key: Option<Arc<Keypair>>,
+
+    /// Request timeout
+    pub timeout: Duration,
+}
+
+impl RemoteSignerConfig {
+    pub fn new(endpoint: String) -> Self {
+        Self {
+            endpoint,
+            tls: None,
+            request_signing_key: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+// ================================
+// Wire Types
+// ================================
+
+/// Request body posted to the signing service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignRequest {
+    pub request: SigningRequest,
+    pub policies: SigningPolicies,
+    pub policy_hash: String,
+}
+
+/// Signature returned by the signing service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignature {
+    pub pubkey: String,
+    pub signature: String,
+}
+
+/// Response body returned by the signing service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RemoteSignResponse {
+    Signed {
+        signatures: Vec<RemoteSignature>,
+        /// Hash of the policies the signer evaluated, echoed back for verification
+        policy_hash: String,
+    },
+    Rejected {
+        reason: String,
+        policy_violations: Vec<String>,
+    },
+    PendingApproval {
+        approval_id: String,
+        required_approvers: Vec<String>,
+    },
+}
+
+// ================================
+// Remote Signer
+// ================================
+
+/// Signing backend that delegates to an external signing service
+pub struct RemoteSigner {
+    config: RemoteSignerConfig,
+    client: reqwest::Client,
+    policies: RwLock<HashMap<Pubkey, SigningPolicies>>,
+}
+
+impl RemoteSigner {
+    /// Create a new remote signer
+    pub async fn new(config: RemoteSignerConfig) -> Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .use_rustls_tls()
+            .timeout(config.timeout);
+
+        if let Some(tls) = &config.tls {
+            let identity_pem = tokio::fs::read(&tls.client_identity_pem).await?;
+            let identity = reqwest::Identity::from_pem(&identity_pem).map_err(config_error)?;
+            builder = builder.identity(identity);
+
+            if let Some(ca_path) = &tls.ca_certificate_pem {
+                let ca_pem = tokio::fs::read(ca_path).await?;
+                let certificate = reqwest::Certificate::from_pem(&ca_pem).map_err(config_error)?;
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        Ok(Self {
+            client: builder.build().map_err(config_error)?,
+            config,
+            policies: RwLock::new(HashMap::new()),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.config.endpoint.trim_end_matches('/'), path)
+    }
+
+    /// Attach the request signing headers over `payload` when a request
+    /// signing key is configured
+    fn authenticate(
+        &self,
+        request: reqwest::RequestBuilder,
+        payload: &[u8],
+    ) -> reqwest::RequestBuilder {
+        match &self.config.request_signing_key {
+            Some(key) => request
+                .header(SIGNER_HEADER, key.pubkey().to_string())
+                .header(SIGNATURE_HEADER, key.sign_message(payload).to_string()),
+            None => request,
+        }
+    }
+
+    /// Get a resource, signing the method and path since there is no body
+    async fn get(&self, path: &str) -> Result<reqwest::Response> {
+        let request = self.client.get(self.url(path));
+        self.authenticate(request, get_signing_payload(path).as_bytes())
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(connection_error)
+    }
+
+    /// Post a JSON body, signing it when a request signing key is configured
+    async fn post<T: Serialize>(&self, path: &str, body: &T) -> Result<reqwest::Response> {
+        let body = serde_json::to_vec(body)?;
+        let request = self
+            .client
+            .post(self.url(path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        self.authenticate(request, &body)
+            .body(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(connection_error)
+    }
+
+    /// Policies for every required signer of a request, combined so each
+    /// signer's restrictions still hold
+    async fn request_policies(&self, request: &SigningRequest) -> SigningPolicies {
+        let policies = self.policies.read().await;
+        let mut merged = SigningPolicies::default();
+
+        for signer in &request.required_signers {
+            if let Some(signer_policies) = policies.get(signer) {
+                merged.max_transaction_value = match (
+                    merged.max_transaction_value,
+                    signer_policies.max_transaction_value,
+                ) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                merged
+                    .blocked_programs
+                    .extend(signer_policies.blocked_programs.iter().copied());
+                merged.allowed_programs = match (
+                    merged.allowed_programs.take(),
+                    &signer_policies.allowed_programs,
+                ) {
+                    (Some(mut allowed), Some(signer_allowed)) => {
+                        allowed.retain(|program| signer_allowed.contains(program));
+                        Some(allowed)
+                    }
+                    (allowed, signer_allowed) => allowed.or_else(|| signer_allowed.clone()),
+                };
+                if merged.rate_limit.is_none() {
+                    merged.rate_limit = signer_policies.rate_limit.clone();
+                }
+                if merged.time_restrictions.is_none() {
+                    merged.time_restrictions = signer_policies.time_restrictions.clone();
+                }
+                merged
+                    .approval_requirements
+                    .extend(signer_policies.approval_requirements.clone());
+            }
+        }
+
+        canonical_policies(merged)
+    }
+
+    /// Turn a service response into a signing response
+    pub fn process_response(
+        request: &SigningRequest,
+        expected_policy_hash: &str,
+        response: RemoteSignResponse,
+    ) -> Result<SigningResponse> {
+        let result = match response {
+            RemoteSignResponse::Signed {
+                signatures,
+                policy_hash,
+            } => {
+                if policy_hash != expected_policy_hash {
+                    return Err(RuntimeError::SecurityViolation(
+                        "Remote signer evaluated different signing policies".to_string(),
+                    ));
+                }
+
+                let signatures = signatures
+                    .iter()
+                    .map(|remote| {
+                        let pubkey = Pubkey::from_str(&remote.pubkey)
+                            .map_err(|e| RuntimeError::SecurityViolation(e.to_string()))?;
+                        let signature = Signature::from_str(&remote.signature)
+                            .map_err(|e| RuntimeError::SecurityViolation(e.to_string()))?;
+                        Ok((pubkey, signature))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let transaction = merge_signatures(&request.transaction, &signatures)?;
+                let signed_transaction = bincode::serialize(&transaction)
+                    .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+
+                SigningResult::Signed {
+                    signatures: signatures.into_iter().map(|(_, s)| s).collect(),
+                    signed_transaction,
+                }
+            }
+            RemoteSignResponse::Rejected {
+                reason,
+                policy_violations,
+            } => SigningResult::Rejected {
+                reason,
+                policy_violations,
+            },
+            RemoteSignResponse::PendingApproval {
+                approval_id,
+                required_approvers,
+            } => SigningResult::PendingApproval {
+                approval_id,
+                required_approvers,
+            },
+        };
+
+        Ok(SigningResponse {
+            request_id: request.request_id.clone(),
+            result,
+            timestamp: chrono::Utc::now(),
+        })
+    }
+}
+
+#[async_trait]
+impl SigningService for RemoteSigner {
+    fn backend_type(&self) -> SigningBackend {
+        SigningBackend::RemoteSigner
+    }
+
+    async fn has_signer(&self, pubkey: &Pubkey) -> Result<bool> {
+        Ok(self.available_signers().await?.contains(pubkey))
+    }
+
+    async fn available_signers(&self) -> Result<Vec<Pubkey>> {
+        let signers: Vec<String> = self
+            .get("signers")
+            .await?
+            .json()
+            .await
+            .map_err(connection_error)?;
+
+        Ok(signers
+            .iter()
+            .filter_map(|signer| Pubkey::from_str(signer).ok())
+            .collect())
+    }
+
+    async fn sign_transaction(&self, request: SigningRequest) -> Result<SigningResponse> {
+        info!(
+            "Requesting remote signature for {} ({})",
+            request.request_id, request.context.operation
+        );
+
+        let policies = self.request_policies(&request).await;
+        let policy_hash = policy_hash(&policies)?;
+        let body = RemoteSignRequest {
+            request: request.clone(),
+            policies,
+            policy_hash: policy_hash.clone(),
+        };
+
+        let response: RemoteSignResponse = self
+            .post("sign", &body)
+            .await?
+            .json()
+            .await
+            .map_err(connection_error)?;
+
+        Self::process_response(&request, &policy_hash, response).inspect_err(|e| {
+            warn!("Remote signing failed for {}: {}", request.request_id, e);
+        })
+    }
+
+    async fn verify_signatures(
+        &self,
+        transaction: &[u8],
+        signatures: &[Signature],
+        pubkeys: &[Pubkey],
+    ) -> Result<VerificationResult> {
+        Ok(verify_message_signatures(transaction, signatures, pubkeys))
+    }
+
+    async fn get_signing_policies(&self, pubkey: &Pubkey) -> Result<SigningPolicies> {
+        Ok(self
+            .policies
+            .read()
+            .await
+            .get(pubkey)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn update_signing_policies(
+        &self,
+        pubkey: &Pubkey,
+        policies: SigningPolicies,
+    ) -> Result<()> {
+        self.policies.write().await.insert(*pubkey, policies);
+        Ok(())
+    }
+}
+
+/// Hex-encoded SHA-256 over the JSON encoding of the canonical policies, so
+/// equal policies hash the same whatever order their programs were listed in
+pub fn policy_hash(policies: &SigningPolicies) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&canonical_policies(policies.clone()))?);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Policies with their program lists sorted and deduplicated
+fn canonical_policies(mut policies: SigningPolicies) -> SigningPolicies {
+    if let Some(allowed) = &mut policies.allowed_programs {
+        allowed.sort_unstable();
+        allowed.dedup();
+    }
+    policies.blocked_programs.sort_unstable();
+    policies.blocked_programs.dedup();
+    policies
+}
+
+/// Payload signed for a bodiless GET request
+fn get_signing_payload(path: &str) -> String {
+    format!("GET /{}", path)
+}
+
+fn config_error(err: reqwest::Error) -> RuntimeError {
+    RuntimeError::InvalidConfiguration(err.to_string())
+}
+
+fn connection_error(err: reqwest::Error) -> RuntimeError {
+    RuntimeError::ConnectionError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::signing::{ApprovalRequirement, RiskLevel};
+    use crate::{TransactionMetadata, UnsignedTransaction};
+    use solana_sdk::message::Message;
+
+    fn signing_request(payer: &Keypair) -> (SigningRequest, Message) {
+        let instruction =
+            solana_sdk::system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let message = Message::new(&[instruction], Some(&payer.pubkey()));
+        let unsigned = UnsignedTransaction {
+            message: bincode::serialize(&message).unwrap(),
+            recent_blockhash: solana_sdk::hash::Hash::default(),
+            signers: vec![payer.pubkey()],
+            metadata: TransactionMetadata {
+                description: "Test".to_string(),
+                compute_units: None,
+                priority_fee: None,
+                simulation: None,
+            },
+        };
+
+        (
+            SigningRequest::new(unsigned, "transfer".to_string(), RiskLevel::Low),
+            message,
+        )
+    }
+
+    #[test]
+    fn test_process_signed_response() {
+        let payer = Keypair::new();
+        let (request, message) = signing_request(&payer);
+        let expected_hash = policy_hash(&SigningPolicies::default()).unwrap();
+
+        let response = RemoteSignResponse::Signed {
+            signatures: vec![RemoteSignature {
+                pubkey: payer.pubkey().to_string(),
+                signature: payer.sign_message(&message.serialize()).to_string(),
+            }],
+            policy_hash: expected_hash.clone(),
+        };
+
+        let signed = RemoteSigner::process_response(&request, &expected_hash, response).unwrap();
+        match signed.result {
+            SigningResult::Signed {
+                signatures,
+                signed_transaction,
+            } => {
+                assert_eq!(signatures.len(), 1);
+                let transaction: solana_sdk::transaction::Transaction =
+                    bincode::deserialize(&signed_transaction).unwrap();
+                assert!(transaction.verify().is_ok());
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_policy_echo_mismatch_rejected() {
+        let payer = Keypair::new();
+        let (request, message) = signing_request(&payer);
+
+        let response = RemoteSignResponse::Signed {
+            signatures: vec![RemoteSignature {
+                pubkey: payer.pubkey().to_string(),
+                signature: payer.sign_message(&message.serialize()).to_string(),
+            }],
+            policy_hash: "different".to_string(),
+        };
+
+        let expected_hash = policy_hash(&SigningPolicies::default()).unwrap();
+        assert!(RemoteSigner::process_response(&request, &expected_hash, response).is_err());
+    }
+
+    #[test]
+    fn test_response_wire_format() {
+        let response: RemoteSignResponse = serde_json::from_str(
+            r#"{"status":"rejected","reason":"limit","policy_violations":["max value"]}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(response, RemoteSignResponse::Rejected { reason, .. } if reason == "limit"));
+    }
+
+    #[tokio::test]
+    async fn test_policies_are_per_signer() {
+        let signer = RemoteSigner::new(RemoteSignerConfig::new("http://127.0.0.1:1".to_string()))
+            .await
+            .unwrap();
+        let pubkey = Pubkey::new_unique();
+
+        let policies = SigningPolicies {
+            max_transaction_value: Some(10),
+            ..Default::default()
+        };
+        signer.update_signing_policies(&pubkey, policies).await.unwrap();
+
+        let stored = signer.get_signing_policies(&pubkey).await.unwrap();
+        assert_eq!(stored.max_transaction_value, Some(10));
+        assert_eq!(signer.backend_type(), SigningBackend::RemoteSigner);
+    }
+
+    #[test]
+    fn test_policy_hash_is_canonical() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let requirement = |required_approvers| ApprovalRequirement {
+            required_approvers,
+            timeout_seconds: 60,
+        };
+
+        let mut first = SigningPolicies {
+            allowed_programs: Some(vec![a, b]),
+            blocked_programs: vec![c, a],
+            ..Default::default()
+        };
+        first.approval_requirements.insert(RiskLevel::High, requirement(2));
+        first.approval_requirements.insert(RiskLevel::Critical, requirement(3));
+
+        let mut second = SigningPolicies {
+            allowed_programs: Some(vec![b, a]),
+            blocked_programs: vec![a, c, a],
+            ..Default::default()
+        };
+        second.approval_requirements.insert(RiskLevel::Critical, requirement(3));
+        second.approval_requirements.insert(RiskLevel::High, requirement(2));
+
+        assert_eq!(policy_hash(&first).unwrap(), policy_hash(&second).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_allowed_programs_intersect_across_signers() {
+        let signer = RemoteSigner::new(RemoteSignerConfig::new("http://127.0.0.1:1".to_string()))
+            .await
+            .unwrap();
+        let (first, second, unrestricted) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let (shared, only_first, only_second) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        for (key, allowed) in [
+            (&first, Some(vec![only_first, shared])),
+            (&unrestricted, None),
+            (&second, Some(vec![shared, only_second])),
+        ] {
+            let policies = SigningPolicies {
+                allowed_programs: allowed,
+                ..Default::default()
+            };
+            signer.update_signing_policies(&key.pubkey(), policies).await.unwrap();
+        }
+
+        let (mut request, _) = signing_request(&first);
+        request.required_signers = vec![unrestricted.pubkey(), first.pubkey(), second.pubkey()];
+
+        let merged = signer.request_policies(&request).await;
+        assert_eq!(merged.allowed_programs, Some(vec![shared]));
+    }
+
+    #[tokio::test]
+    async fn test_get_requests_are_signed() {
+        let key = Arc::new(Keypair::new());
+        let mut config = RemoteSignerConfig::new("http://127.0.0.1:1".to_string());
+        config.request_signing_key = Some(key.clone());
+        let signer = RemoteSigner::new(config).await.unwrap();
+
+        let request = signer
+            .authenticate(
+                signer.client.get(signer.url("signers")),
+                get_signing_payload("signers").as_bytes(),
+            )
+            .build()
+            .unwrap();
+        let header = |name| request.headers()[name].to_str().unwrap().to_string();
+
+        assert_eq!(header(SIGNER_HEADER), key.pubkey().to_string());
+        let signature = Signature::from_str(&header(SIGNATURE_HEADER)).unwrap();
+        assert!(signature.verify(key.pubkey().as_ref(), b"GET /signers"));
+    }
+}
\ No newline at end of file
@@ -1,14 +1,17 @@
 //! Abstract signing service interface for external signers
 
-use crate::{Result, RuntimeError, UnsignedTransaction};
+use crate::{monitoring::MetricsExporter, Result, RuntimeError, UnsignedTransaction};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{pubkey::Pubkey, signature::Signature};
-use std::collections::HashMap;
+use solana_sdk::{message::Message, pubkey::Pubkey, signature::Signature, transaction::Transaction};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
 
 /// Signing backend type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SigningBackend {
     LocalKeypair,
     HSM,
@@ -50,8 +53,8 @@ pub struct SigningContext {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
-/// Risk level assessment
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+/// Risk level assessment, ordered from lowest to highest risk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default)]
 pub enum RiskLevel {
     #[default]
     Low,
@@ -132,7 +135,8 @@ pub struct SigningPolicies {
     pub max_transaction_value: Option<u64>,
     pub allowed_programs: Option<Vec<Pubkey>>,
     pub blocked_programs: Vec<Pubkey>,
-    pub approval_requirements: HashMap<RiskLevel, ApprovalRequirement>,
+    /// Ordered so the policies serialize, and hash, the same way every time
+    pub approval_requirements: BTreeMap<RiskLevel, ApprovalRequirement>,
     pub rate_limit: Option<RateLimit>,
     pub time_restrictions: Option<TimeRestrictions>,
 }
@@ -162,11 +166,46 @@ pub struct TimeRestrictions {
 /// Composite signing service that supports multiple backends
 pub struct CompositeSigningService {
     default_backend: SigningBackend,
+    backends: RwLock<HashMap<SigningBackend, Arc<dyn SigningService>>>,
+    metrics: Option<Arc<MetricsExporter>>,
 }
 
 impl CompositeSigningService {
     pub fn new(default_backend: SigningBackend) -> Self {
-        Self { default_backend }
+        Self {
+            default_backend,
+            backends: RwLock::new(HashMap::new()),
+            metrics: None,
+        }
+    }
+
+    /// Record how long the backend takes to answer each signing request
+    pub fn with_metrics(mut self, metrics: Arc<MetricsExporter>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Register a backend, replacing any existing backend of the same type
+    pub async fn register_backend(&self, backend: Arc<dyn SigningService>) {
+        self.backends
+            .write()
+            .await
+            .insert(backend.backend_type(), backend);
+    }
+
+    /// Get the backend used for signing requests
+    async fn default_service(&self) -> Result<Arc<dyn SigningService>> {
+        self.backends
+            .read()
+            .await
+            .get(&self.default_backend)
+            .cloned()
+            .ok_or_else(|| {
+                RuntimeError::TransactionBuildError(format!(
+                    "No signing backend registered for {}",
+                    self.default_backend
+                ))
+            })
     }
 }
 
@@ -176,33 +215,127 @@ impl SigningService for CompositeSigningService {
         self.default_backend
     }
 
-    async fn has_signer(&self, _pubkey: &Pubkey) -> Result<bool> {
+    async fn has_signer(&self, pubkey: &Pubkey) -> Result<bool> {
+        let backends: Vec<_> = self.backends.read().await.values().cloned().collect();
+        for backend in backends {
+            if backend.has_signer(pubkey).await? {
+                return Ok(true);
+            }
+        }
         Ok(false)
     }
 
     async fn available_signers(&self) -> Result<Vec<Pubkey>> {
-        Ok(Vec::new())
+        let backends: Vec<_> = self.backends.read().await.values().cloned().collect();
+        let mut signers = Vec::new();
+        for backend in backends {
+            for signer in backend.available_signers().await? {
+                if !signers.contains(&signer) {
+                    signers.push(signer);
+                }
+            }
+        }
+        Ok(signers)
+    }
+
+    async fn sign_transaction(&self, request: SigningRequest) -> Result<SigningResponse> {
+        let backend = self.default_service().await?;
+        let started = Instant::now();
+        let response = backend.sign_transaction(request).await;
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_signing_latency(started.elapsed());
+        }
+        response
     }
 
-    async fn sign_transaction(&self, _request: SigningRequest) -> Result<SigningResponse> {
-        Err(RuntimeError::TransactionBuildError("Signing not implemented".to_string()))
+    async fn verify_signatures(&self, transaction: &[u8], signatures: &[Signature], pubkeys: &[Pubkey]) -> Result<VerificationResult> {
+        Ok(verify_message_signatures(transaction, signatures, pubkeys))
     }
 
-    async fn verify_signatures(&self, _transaction: &[u8], _signatures: &[Signature], _pubkeys: &[Pubkey]) -> Result<VerificationResult> {
-        Ok(VerificationResult {
-            valid: false,
-            signature_results: Vec::new(),
-            policy_violations: Vec::new(),
+    async fn get_signing_policies(&self, pubkey: &Pubkey) -> Result<SigningPolicies> {
+        match self.default_service().await {
+            Ok(backend) => backend.get_signing_policies(pubkey).await,
+            Err(_) => Ok(SigningPolicies::default()),
+        }
+    }
+
+    async fn update_signing_policies(&self, pubkey: &Pubkey, policies: SigningPolicies) -> Result<()> {
+        self.default_service()
+            .await?
+            .update_signing_policies(pubkey, policies)
+            .await
+    }
+}
+
+/// Verify signatures over serialized message bytes
+pub fn verify_message_signatures(
+    message: &[u8],
+    signatures: &[Signature],
+    pubkeys: &[Pubkey],
+) -> VerificationResult {
+    let signature_results: Vec<SignatureVerification> = signatures
+        .iter()
+        .zip(pubkeys)
+        .map(|(signature, pubkey)| {
+            let valid = signature.verify(pubkey.as_ref(), message);
+            SignatureVerification {
+                pubkey: *pubkey,
+                signature: *signature,
+                valid,
+                error: (!valid).then(|| "Invalid signature".to_string()),
+            }
         })
+        .collect();
+
+    let mut policy_violations = Vec::new();
+    if signatures.len() != pubkeys.len() {
+        policy_violations.push(format!(
+            "Expected {} signatures, got {}",
+            pubkeys.len(),
+            signatures.len()
+        ));
     }
 
-    async fn get_signing_policies(&self, _pubkey: &Pubkey) -> Result<SigningPolicies> {
-        Ok(SigningPolicies::default())
+    VerificationResult {
+        valid: policy_violations.is_empty() && signature_results.iter().all(|r| r.valid),
+        signature_results,
+        policy_violations,
     }
+}
 
-    async fn update_signing_policies(&self, _pubkey: &Pubkey, _policies: SigningPolicies) -> Result<()> {
-        Ok(())
+/// Merge externally produced signatures into an unsigned transaction.
+///
+/// Every signature is checked against the message before it is placed in the
+/// slot of its signer.
+pub fn merge_signatures(
+    unsigned: &UnsignedTransaction,
+    signatures: &[(Pubkey, Signature)],
+) -> Result<Transaction> {
+    let message: Message = bincode::deserialize(&unsigned.message)
+        .map_err(|e| RuntimeError::TransactionBuildError(e.to_string()))?;
+    let mut transaction = Transaction::new_unsigned(message);
+    let message_data = transaction.message_data();
+    let required = usize::from(transaction.message.header.num_required_signatures);
+
+    for (pubkey, signature) in signatures {
+        let index = transaction.message.account_keys[..required]
+            .iter()
+            .position(|key| key == pubkey)
+            .ok_or_else(|| {
+                RuntimeError::SecurityViolation(format!("{} is not a required signer", pubkey))
+            })?;
+
+        if !signature.verify(pubkey.as_ref(), &message_data) {
+            return Err(RuntimeError::SecurityViolation(format!(
+                "Invalid signature for {}",
+                pubkey
+            )));
+        }
+
+        transaction.signatures[index] = *signature;
     }
+
+    Ok(transaction)
 }
 
 /// Helper functions for creating signing requests
@@ -273,4 +406,73 @@ mod tests {
         let signers = service.available_signers().await.unwrap();
         assert!(signers.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_signing_latency_observed() {
+        use crate::security::remote_signer::{RemoteSigner, RemoteSignerConfig};
+
+        let metrics = Arc::new(
+            MetricsExporter::new(Arc::new(RwLock::new(crate::RuntimeMetrics::default()))).unwrap(),
+        );
+        let service = CompositeSigningService::new(SigningBackend::RemoteSigner)
+            .with_metrics(metrics.clone());
+        let unreachable = RemoteSignerConfig::new("http://127.0.0.1:1".to_string());
+        service
+            .register_backend(Arc::new(RemoteSigner::new(unreachable).await.unwrap()))
+            .await;
+
+        let unsigned_tx = UnsignedTransaction {
+            message: vec![1, 2, 3],
+            recent_blockhash: solana_sdk::hash::Hash::default(),
+            signers: vec![Pubkey::new_unique()],
+            metadata: crate::TransactionMetadata {
+                description: "Test".to_string(),
+                compute_units: None,
+                priority_fee: None,
+                simulation: None,
+            },
+        };
+        let request = SigningRequest::new(unsigned_tx, "test_operation".to_string(), RiskLevel::Low);
+
+        // Failed attempts still count towards signing latency
+        assert!(service.sign_transaction(request).await.is_err());
+        let output = metrics.render().await.unwrap();
+        assert!(output.contains("valence_runtime_signing_latency_seconds_count 1"));
+    }
+
+    #[test]
+    fn test_merge_signatures() {
+        use solana_sdk::signature::{Keypair, Signer};
+
+        let payer = Keypair::new();
+        let instruction = solana_sdk::system_instruction::transfer(
+            &payer.pubkey(),
+            &Pubkey::new_unique(),
+            1,
+        );
+        let message = Message::new(&[instruction], Some(&payer.pubkey()));
+        let unsigned = UnsignedTransaction {
+            message: bincode::serialize(&message).unwrap(),
+            recent_blockhash: solana_sdk::hash::Hash::default(),
+            signers: vec![payer.pubkey()],
+            metadata: crate::TransactionMetadata {
+                description: "Test".to_string(),
+                compute_units: None,
+                priority_fee: None,
+                simulation: None,
+            },
+        };
+
+        let signature = payer.sign_message(&message.serialize());
+        let transaction = merge_signatures(&unsigned, &[(payer.pubkey(), signature)]).unwrap();
+        assert!(transaction.is_signed());
+        assert!(transaction.verify().is_ok());
+
+        // Signatures from non-signers or over other data are rejected
+        let stranger = Keypair::new();
+        let foreign = stranger.sign_message(&message.serialize());
+        assert!(merge_signatures(&unsigned, &[(stranger.pubkey(), foreign)]).is_err());
+        let bogus = payer.sign_message(b"other data");
+        assert!(merge_signatures(&unsigned, &[(payer.pubkey(), bogus)]).is_err());
+    }
 }
\ No newline at end of file
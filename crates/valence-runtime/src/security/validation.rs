@@ -1,6 +1,7 @@
 //! Transaction validation before signing
 
 use crate::security::{SecurityAnalysis, SecurityAnalyzer, SecurityContext};
+use crate::security::policy::PolicyEngine;
 use crate::security::signing::{RiskLevel, SigningPolicies};
 use crate::{Result, RuntimeError, UnsignedTransaction};
 use async_trait::async_trait;
@@ -98,6 +99,7 @@ pub struct TransactionValidator {
     rules: Vec<Box<dyn ValidationRule>>,
     security_analyzer: SecurityAnalyzer,
     rpc_client: Arc<RpcClient>,
+    policy_engine: Option<Arc<PolicyEngine>>,
 }
 
 impl TransactionValidator {
@@ -109,7 +111,13 @@ impl TransactionValidator {
             Box::new(SecurityValidationRule),
         ];
 
-        Self { rules, security_analyzer, rpc_client }
+        Self { rules, security_analyzer, rpc_client, policy_engine: None }
+    }
+
+    /// Enforce declarative policies before the built-in rules
+    pub fn with_policy_engine(mut self, engine: Arc<PolicyEngine>) -> Self {
+        self.policy_engine = Some(engine);
+        self
     }
 
     /// Validate a transaction
@@ -129,6 +137,11 @@ impl TransactionValidator {
             .map_err(|e| RuntimeError::TransactionBuildError(format!("Failed to deserialize: {}", e)))?;
         let tx = Transaction { signatures: vec![], message };
 
+        // Policy violations are hard failures carrying the rule id
+        if let Some(engine) = &self.policy_engine {
+            engine.evaluate(&tx, transaction.metadata.simulation.as_ref()).await?;
+        }
+
         // Run validation rules
         for rule in &self.rules {
             let result = rule.validate(&tx, &context).await?;
@@ -1,12 +1,20 @@
 //! Session management and operations
 
-use crate::Result;
+use crate::{
+    monitoring::event_stream::{Event, EventStream},
+    types::{GuardHealth, SessionHealth, UnhealthyReason},
+    Result,
+};
+use anchor_lang::AccountDeserialize;
 use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+};
+use tracing::{debug, info, warn};
 
 use valence_kernel::{
     state::{Session, GuardAccount},
@@ -172,46 +180,16 @@ impl SessionMetrics {
 }
 
 impl SessionState {
-    /// Create SessionState from account data
-    pub fn from_account_data(data: &[u8]) -> crate::Result<Self> {
-        // This is a simplified implementation for demonstration
-        // In production, this would deserialize the actual account data
-        if data.len() < 32 {
-            return Err(crate::RuntimeError::InvalidAccountData);
-        }
-        
-        // Create a dummy session with minimal required fields
-        let session_pubkey = Pubkey::new_unique();
-        let session_data = Session {
-            namespace: valence_kernel::namespace::NamespacePath::new("default").unwrap_or_else(|_| {
-                // Create empty namespace path as fallback
-                valence_kernel::namespace::NamespacePath { path: [0u8; 256], len: 0 }
-            }),
-            guard_account: Pubkey::new_unique(),
-            account_lookup: Pubkey::new_unique(),
-            owner: session_pubkey,
-            shard: Pubkey::new_unique(),
-            parent_session: None,
-            usage_count: 0,
-            metadata: [0u8; 32],
-            created_at: 0,
-            updated_at: 0,
-            borrowed_accounts: Default::default(),
-            borrowed_bitmap: 0,
-            cpi_depth: 0,
-            active: true,
-            nonce: 0,
-            child_accounts: [Pubkey::default(); 8],
-            child_count: 0,
-            child_sessions: [Pubkey::default(); 8],
-            child_session_count: 0,
-        };
-        
+    /// Decode a kernel session account
+    pub fn from_account_data(session_pubkey: Pubkey, data: &[u8]) -> crate::Result<Self> {
+        let session_data = Session::try_deserialize(&mut &data[..])
+            .map_err(|_| crate::RuntimeError::InvalidAccountData)?;
+
         Ok(SessionState {
             session_pubkey,
+            guard_pubkey: Some(session_data.guard_account),
+            alt_pubkey: Some(session_data.account_lookup),
             session_data,
-            guard_pubkey: None,
-            alt_pubkey: None,
             last_sync_slot: 0,
             metrics: SessionMetrics::default(),
             namespace_info: None,
@@ -219,6 +197,152 @@ impl SessionState {
     }
 }
 
+// ================================
+// Session Health
+// ================================
+
+/// Health monitoring configuration
+#[derive(Debug, Clone)]
+pub struct SessionHealthConfig {
+    /// Interval between checks of every tracked session
+    pub check_interval: Duration,
+    /// Borrows held longer than this are reported as stale
+    pub max_borrow_age_secs: i64,
+}
+
+impl Default for SessionHealthConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(30),
+            max_borrow_age_secs: 300,
+        }
+    }
+}
+
+/// Result of a session health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHealthReport {
+    pub session: Pubkey,
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub lamports: u64,
+    pub guard: Option<GuardHealth>,
+    pub issues: Vec<UnhealthyReason>,
+}
+
+impl SessionHealthReport {
+    pub fn new(session: Pubkey) -> Self {
+        Self {
+            session,
+            checked_at: chrono::Utc::now(),
+            lamports: 0,
+            guard: None,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Overall status derived from the most severe issue
+    pub fn status(&self) -> SessionHealth {
+        if self.issues.contains(&UnhealthyReason::SessionClosed) {
+            return SessionHealth::Unavailable;
+        }
+
+        let describe = |critical: bool| {
+            self.issues
+                .iter()
+                .filter(|issue| issue.is_critical() == critical)
+                .map(|issue| issue.to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        };
+        if self.issues.iter().any(UnhealthyReason::is_critical) {
+            SessionHealth::Critical(describe(true))
+        } else if !self.issues.is_empty() {
+            SessionHealth::Warning(describe(false))
+        } else {
+            SessionHealth::Healthy
+        }
+    }
+
+    /// Issues not already present in `previous`
+    pub fn new_issues<'a>(
+        &'a self,
+        previous: &'a [UnhealthyReason],
+    ) -> impl Iterator<Item = &'a UnhealthyReason> + 'a {
+        self.issues
+            .iter()
+            .filter(move |issue| !previous.iter().any(|seen| seen.same_issue(issue)))
+    }
+
+    /// Whether the session can no longer recover and should stop being checked
+    pub fn is_terminal(&self) -> bool {
+        self.issues.iter().any(UnhealthyReason::is_terminal)
+    }
+
+    /// Check session state, borrows, nonce and balance
+    pub fn inspect_session(
+        &mut self,
+        session: &Session,
+        cached_nonce: Option<u64>,
+        rent_exempt_minimum: u64,
+        config: &SessionHealthConfig,
+    ) {
+        if !session.active {
+            self.issues.push(UnhealthyReason::Inactive);
+        }
+
+        let now = self.checked_at.timestamp();
+        for (slot, borrowed) in session.borrowed_accounts.iter().enumerate() {
+            let occupied = session.borrowed_bitmap & (1 << slot) != 0;
+            let borrowed_for_secs = now - borrowed.borrowed_at;
+            if occupied && borrowed_for_secs > config.max_borrow_age_secs {
+                self.issues.push(UnhealthyReason::StaleBorrow {
+                    account: borrowed.address,
+                    borrowed_for_secs,
+                });
+            }
+        }
+
+        if let Some(cached) = cached_nonce.filter(|cached| *cached != session.nonce) {
+            self.issues.push(UnhealthyReason::NonceDrift {
+                cached,
+                on_chain: session.nonce,
+            });
+        }
+
+        if self.lamports < rent_exempt_minimum {
+            self.issues.push(UnhealthyReason::LowBalance {
+                lamports: self.lamports,
+                rent_exempt_minimum,
+            });
+        }
+    }
+
+    /// Check that the guard exists and points back at this session
+    ///
+    /// `previous_errors` is the guard's consecutive failure count before this check.
+    pub fn inspect_guard(&mut self, guard_pubkey: Pubkey, account: Option<&Account>, previous_errors: u32) {
+        let guard = account.and_then(|account| GuardAccount::try_deserialize(&mut &account.data[..]).ok());
+
+        let issue = match guard {
+            None if account.is_none() => Some(UnhealthyReason::GuardMissing { guard: guard_pubkey }),
+            Some(guard) if guard.session == self.session => None,
+            _ => Some(UnhealthyReason::GuardInconsistent { guard: guard_pubkey }),
+        };
+
+        let (status, error_count) = match &issue {
+            Some(issue) => (SessionHealth::Critical(issue.to_string()), previous_errors + 1),
+            None => (SessionHealth::Healthy, 0),
+        };
+        self.guard = Some(GuardHealth {
+            guard_pubkey,
+            status,
+            last_check: self.checked_at,
+            error_count,
+        });
+        self.issues.extend(issue);
+    }
+}
+
 // ================================
 // Session Manager
 // ================================
@@ -228,6 +352,9 @@ pub struct SessionManager {
     rpc_client: Arc<RpcClient>,
     sessions: Arc<RwLock<HashMap<Pubkey, SessionCache>>>,
     metrics: Arc<RwLock<SessionManagerMetrics>>,
+    guard_errors: Arc<RwLock<HashMap<Pubkey, u32>>>,
+    shutdown_tx: broadcast::Sender<()>,
+    health_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 /// Cached session data
@@ -246,15 +373,22 @@ pub struct SessionManagerMetrics {
     pub cache_hits: u64,
     pub cache_misses: u64,
     pub load_errors: u32,
+    pub health_checks: u64,
+    pub unhealthy_sessions: u32,
 }
 
 impl SessionManager {
     /// Create a new session manager
     pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        let (shutdown_tx, _) = broadcast::channel(16);
+
         Self {
             rpc_client,
             sessions: Arc::new(RwLock::new(HashMap::new())),
             metrics: Arc::new(RwLock::new(SessionManagerMetrics::default())),
+            guard_errors: Arc::new(RwLock::new(HashMap::new())),
+            shutdown_tx,
+            health_handle: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -286,18 +420,10 @@ impl SessionManager {
             }
         };
 
-        // Parse session state (simplified)
-        let state = SessionState::from_account_data(&account.data)?;
+        let state = SessionState::from_account_data(session_pubkey, &account.data)?;
 
         // Update cache
-        {
-            let mut sessions = self.sessions.write().await;
-            sessions.insert(session_pubkey, SessionCache {
-                state: state.clone(),
-                last_updated: chrono::Utc::now(),
-                ttl_seconds: 300, // 5 minutes
-            });
-        }
+        self.cache_session(state.clone()).await;
 
         // Update metrics
         {
@@ -340,6 +466,145 @@ impl SessionManager {
         self.metrics.read().await.clone()
     }
 
+    /// Sessions currently tracked by the cache
+    pub async fn tracked_sessions(&self) -> Vec<Pubkey> {
+        self.sessions.read().await.keys().copied().collect()
+    }
+
+    /// Fetch a session and its guard and check their health
+    ///
+    /// The cached state is refreshed so nonce drift is reported once per change.
+    pub async fn check_session_health(
+        &self,
+        session_pubkey: Pubkey,
+        config: &SessionHealthConfig,
+    ) -> Result<SessionHealthReport> {
+        let commitment = self.rpc_client.commitment();
+        let mut health = SessionHealthReport::new(session_pubkey);
+        self.metrics.write().await.health_checks += 1;
+
+        let Some(account) = self
+            .rpc_client
+            .get_account_with_commitment(&session_pubkey, commitment)
+            .await?
+            .value
+        else {
+            health.issues.push(UnhealthyReason::SessionClosed);
+            return Ok(health);
+        };
+
+        let state = SessionState::from_account_data(session_pubkey, &account.data)?;
+        let cached_nonce = self
+            .sessions
+            .read()
+            .await
+            .get(&session_pubkey)
+            .map(|cached| cached.state.session_data.nonce);
+        let rent_exempt_minimum = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(account.data.len())
+            .await?;
+
+        health.lamports = account.lamports;
+        health.inspect_session(&state.session_data, cached_nonce, rent_exempt_minimum, config);
+
+        let guard_pubkey = state.session_data.guard_account;
+        let guard_account = self
+            .rpc_client
+            .get_account_with_commitment(&guard_pubkey, commitment)
+            .await?
+            .value;
+        let previous_errors = self.guard_errors.read().await.get(&guard_pubkey).copied().unwrap_or(0);
+        health.inspect_guard(guard_pubkey, guard_account.as_ref(), previous_errors);
+        if let Some(guard) = &health.guard {
+            self.guard_errors.write().await.insert(guard_pubkey, guard.error_count);
+        }
+
+        self.cache_session(state).await;
+        Ok(health)
+    }
+
+    /// Periodically check every tracked session, emitting `Event::SessionUnhealthy`
+    /// once per issue when it first appears
+    ///
+    /// Closed and inactive sessions are reported once and then dropped from the cache.
+    pub async fn start_health_monitor(
+        self: &Arc<Self>,
+        event_stream: Arc<EventStream>,
+        config: SessionHealthConfig,
+    ) -> Result<()> {
+        info!("Starting session health monitor");
+
+        let manager = Arc::clone(self);
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.check_interval);
+            // Issues already reported per session, so each is emitted once
+            let mut reported: HashMap<Pubkey, Vec<UnhealthyReason>> = HashMap::new();
+
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = interval.tick() => {
+                        let tracked = manager.tracked_sessions().await;
+                        reported.retain(|session, _| tracked.contains(session));
+
+                        let mut unhealthy = 0;
+                        for session in tracked {
+                            let health = match manager.check_session_health(session, &config).await {
+                                Ok(health) => health,
+                                Err(e) => {
+                                    warn!("Health check failed for session {}: {}", session, e);
+                                    continue;
+                                }
+                            };
+                            if health.issues.is_empty() {
+                                reported.remove(&session);
+                                continue;
+                            }
+
+                            unhealthy += 1;
+                            let previous = reported.remove(&session).unwrap_or_default();
+                            for reason in health.new_issues(&previous) {
+                                event_stream
+                                    .emit(Event::SessionUnhealthy { session, reason: reason.clone() })
+                                    .await;
+                            }
+                            if health.is_terminal() {
+                                manager.invalidate_session(&session).await;
+                            } else {
+                                reported.insert(session, health.issues);
+                            }
+                        }
+                        manager.metrics.write().await.unhealthy_sessions = unhealthy;
+                    }
+                }
+            }
+        });
+
+        *self.health_handle.write().await = Some(handle);
+        Ok(())
+    }
+
+    /// Stop the health monitor
+    pub async fn stop_health_monitor(&self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        if let Some(handle) = self.health_handle.write().await.take() {
+            let _ = handle.await;
+        }
+        Ok(())
+    }
+
+    async fn cache_session(&self, state: SessionState) {
+        let mut sessions = self.sessions.write().await;
+        sessions.insert(state.session_pubkey, SessionCache {
+            state,
+            last_updated: chrono::Utc::now(),
+            ttl_seconds: 300, // 5 minutes
+        });
+    }
+
     /// Check if cached session is expired
     fn is_cache_expired(&self, cached: &SessionCache) -> bool {
         let now = chrono::Utc::now();
@@ -382,4 +647,127 @@ mod tests {
         let metrics = manager.get_metrics().await;
         assert_eq!(metrics.total_sessions, 0);
     }
+
+    fn session_with_guard(guard_account: Pubkey) -> Session {
+        Session {
+            namespace: valence_kernel::namespace::NamespacePath::new("shard/session").unwrap(),
+            guard_account,
+            account_lookup: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            shard: Pubkey::new_unique(),
+            parent_session: None,
+            usage_count: 0,
+            metadata: [0u8; 32],
+            created_at: 0,
+            updated_at: 0,
+            borrowed_accounts: Default::default(),
+            borrowed_bitmap: 0,
+            cpi_depth: 0,
+            active: true,
+            nonce: 3,
+            child_accounts: [Pubkey::default(); 8],
+            child_count: 0,
+            child_sessions: [Pubkey::default(); 8],
+            child_session_count: 0,
+            stats_enabled: false,
+        }
+    }
+
+    fn guard_account(guard: &GuardAccount) -> Account {
+        let mut data = Vec::new();
+        anchor_lang::AccountSerialize::try_serialize(guard, &mut data).unwrap();
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: valence_kernel::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_healthy_session() {
+        let session_pubkey = Pubkey::new_unique();
+        let guard_pubkey = Pubkey::new_unique();
+        let session = session_with_guard(guard_pubkey);
+
+        let mut health = SessionHealthReport::new(session_pubkey);
+        health.lamports = 2_000_000;
+        health.inspect_session(&session, Some(3), 1_000_000, &SessionHealthConfig::default());
+        health.inspect_guard(
+            guard_pubkey,
+            Some(&guard_account(&GuardAccount::new(session_pubkey, false))),
+            2,
+        );
+
+        assert_eq!(health.status(), SessionHealth::Healthy, "{:?}", health.issues);
+        assert_eq!(health.guard.unwrap().error_count, 0);
+    }
+
+    #[test]
+    fn test_unhealthy_reasons() {
+        let session_pubkey = Pubkey::new_unique();
+        let guard_pubkey = Pubkey::new_unique();
+        let borrowed = Pubkey::new_unique();
+        let mut session = session_with_guard(guard_pubkey);
+        session.active = false;
+        session.borrowed_bitmap = 0b1;
+        session.borrowed_accounts[0] = valence_kernel::SessionBorrowedAccount {
+            address: borrowed,
+            borrowed_at: chrono::Utc::now().timestamp() - 600,
+            mode: 1,
+        };
+
+        let mut health = SessionHealthReport::new(session_pubkey);
+        health.lamports = 10;
+        health.inspect_session(&session, Some(2), 1_000, &SessionHealthConfig::default());
+        health.inspect_guard(
+            guard_pubkey,
+            Some(&guard_account(&GuardAccount::new(Pubkey::new_unique(), false))),
+            1,
+        );
+
+        assert_eq!(health.issues[0], UnhealthyReason::Inactive);
+        assert!(matches!(health.issues[1], UnhealthyReason::StaleBorrow { account, .. } if account == borrowed));
+        assert_eq!(health.issues[2], UnhealthyReason::NonceDrift { cached: 2, on_chain: 3 });
+        assert_eq!(
+            health.issues[3],
+            UnhealthyReason::LowBalance { lamports: 10, rent_exempt_minimum: 1_000 }
+        );
+        assert_eq!(health.issues[4], UnhealthyReason::GuardInconsistent { guard: guard_pubkey });
+        assert!(matches!(health.status(), SessionHealth::Critical(_)));
+        assert_eq!(health.guard.unwrap().error_count, 2);
+
+        let mut missing = SessionHealthReport::new(session_pubkey);
+        missing.inspect_guard(guard_pubkey, None, 0);
+        assert_eq!(missing.issues, vec![UnhealthyReason::GuardMissing { guard: guard_pubkey }]);
+
+        let mut closed = SessionHealthReport::new(session_pubkey);
+        closed.issues.push(UnhealthyReason::SessionClosed);
+        assert_eq!(closed.status(), SessionHealth::Unavailable);
+        assert!(closed.is_terminal());
+        assert!(!missing.is_terminal());
+    }
+
+    #[test]
+    fn test_new_issues_ignore_ongoing() {
+        let session_pubkey = Pubkey::new_unique();
+        let borrowed = Pubkey::new_unique();
+        let previous = vec![
+            UnhealthyReason::StaleBorrow { account: borrowed, borrowed_for_secs: 400 },
+            UnhealthyReason::LowBalance { lamports: 10, rent_exempt_minimum: 1_000 },
+        ];
+
+        let mut health = SessionHealthReport::new(session_pubkey);
+        health.issues = vec![
+            UnhealthyReason::StaleBorrow { account: borrowed, borrowed_for_secs: 430 },
+            UnhealthyReason::LowBalance { lamports: 5, rent_exempt_minimum: 1_000 },
+            UnhealthyReason::Inactive,
+        ];
+
+        let new: Vec<_> = health.new_issues(&previous).collect();
+        assert_eq!(new, vec![&UnhealthyReason::Inactive]);
+        assert!(health.is_terminal());
+        assert_eq!(health.new_issues(&health.issues).count(), 0);
+    }
 }
\ No newline at end of file
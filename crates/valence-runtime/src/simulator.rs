@@ -0,0 +1,498 @@
+//! Dry-run execution of protocol flows
+//!
+//! [`FlowSimulator`] walks a registered flow step by step, simulating each
+//! step's transaction instead of submitting it, and reports the expected
+//! account mutations, compute and fees. Account state produced by earlier
+//! steps is kept in a local overlay so mutations are reported against the
+//! state the flow itself would have produced. RPC simulation cannot be seeded
+//! with that overlay, so each step still executes against current chain state.
+
+use crate::{cluster::ClusterRegistry, coordination::Coordinator, Result, RuntimeError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_sdk::{
+    account::Account, instruction::Instruction, message::Message, pubkey::Pubkey,
+    transaction::Transaction,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tracing::info;
+
+// ================================
+// Report Types
+// ================================
+
+/// Account state relevant to a simulation report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data_len: usize,
+    /// BLAKE3 hash of the account data, hex encoded
+    pub data_hash: String,
+}
+
+impl From<&Account> for AccountSnapshot {
+    fn from(account: &Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            owner: account.owner,
+            data_len: account.data.len(),
+            data_hash: blake3::hash(&account.data).to_hex().to_string(),
+        }
+    }
+}
+
+/// Change to an account made by a simulated step; `None` means the account does not exist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountMutation {
+    pub address: Pubkey,
+    pub before: Option<AccountSnapshot>,
+    pub after: Option<AccountSnapshot>,
+}
+
+/// Outcome of simulating one transaction
+#[derive(Debug, Clone)]
+pub struct TransactionSimulation {
+    pub success: bool,
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub compute_units: Option<u64>,
+    pub fee_lamports: u64,
+    /// Post-simulation state of the requested addresses, in request order
+    pub post_accounts: Vec<Option<AccountSnapshot>>,
+}
+
+/// Simulation result for one flow step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepSimulation {
+    pub step: String,
+    pub cluster: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub compute_units: Option<u64>,
+    pub fee_lamports: u64,
+    pub mutations: Vec<AccountMutation>,
+}
+
+/// Simulation result for a whole flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowSimulationReport {
+    pub flow_id: String,
+    pub steps: Vec<StepSimulation>,
+    pub success: bool,
+    pub total_compute_units: u64,
+    pub total_fee_lamports: u64,
+}
+
+// ================================
+// Simulation Backend
+// ================================
+
+/// Chain access used by the flow simulator
+#[async_trait]
+pub trait SimulationBackend: Send + Sync {
+    /// Current state of the given accounts
+    async fn accounts(
+        &self,
+        cluster: Option<&str>,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<AccountSnapshot>>>;
+
+    /// Simulate the instructions and return the post state of `addresses`
+    async fn simulate(
+        &self,
+        cluster: Option<&str>,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        addresses: &[Pubkey],
+    ) -> Result<TransactionSimulation>;
+}
+
+/// Simulation over RPC `simulateTransaction` on the step's cluster
+pub struct RpcSimulationBackend {
+    clusters: Arc<ClusterRegistry>,
+}
+
+impl RpcSimulationBackend {
+    pub fn new(clusters: Arc<ClusterRegistry>) -> Self {
+        Self { clusters }
+    }
+}
+
+#[async_trait]
+impl SimulationBackend for RpcSimulationBackend {
+    async fn accounts(
+        &self,
+        cluster: Option<&str>,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<AccountSnapshot>>> {
+        let rpc_client = &self.clusters.resolve(cluster)?.rpc_client;
+        Ok(rpc_client
+            .get_multiple_accounts(addresses)
+            .await?
+            .iter()
+            .map(|account| account.as_ref().map(AccountSnapshot::from))
+            .collect())
+    }
+
+    async fn simulate(
+        &self,
+        cluster: Option<&str>,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        addresses: &[Pubkey],
+    ) -> Result<TransactionSimulation> {
+        let cluster = self.clusters.resolve(cluster)?;
+        let rpc_client = &cluster.rpc_client;
+
+        let blockhash = rpc_client.get_latest_blockhash().await?;
+        let message = Message::new_with_blockhash(instructions, Some(payer), &blockhash);
+        let fee_lamports = rpc_client.get_fee_for_message(&message).await?;
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(cluster.config.commitment),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: addresses.iter().map(Pubkey::to_string).collect(),
+            }),
+            ..Default::default()
+        };
+        let result = rpc_client
+            .simulate_transaction_with_config(&Transaction::new_unsigned(message), config)
+            .await?
+            .value;
+
+        let post_accounts = match result.accounts {
+            Some(accounts) => accounts
+                .iter()
+                .map(|account| {
+                    account
+                        .as_ref()
+                        .and_then(UiAccount::decode::<Account>)
+                        .map(|account| AccountSnapshot::from(&account))
+                })
+                .collect(),
+            None => vec![None; addresses.len()],
+        };
+
+        Ok(TransactionSimulation {
+            success: result.err.is_none(),
+            error: result.err.map(|e| e.to_string()),
+            logs: result.logs.unwrap_or_default(),
+            compute_units: result.units_consumed,
+            fee_lamports,
+            post_accounts,
+        })
+    }
+}
+
+// ================================
+// Flow Simulator
+// ================================
+
+/// Executes protocol flows against simulated transactions only
+pub struct FlowSimulator {
+    coordinator: Arc<Coordinator>,
+    backend: Arc<dyn SimulationBackend>,
+    payer: Pubkey,
+}
+
+impl FlowSimulator {
+    pub fn new(
+        coordinator: Arc<Coordinator>,
+        backend: Arc<dyn SimulationBackend>,
+        payer: Pubkey,
+    ) -> Self {
+        Self {
+            coordinator,
+            backend,
+            payer,
+        }
+    }
+
+    /// Simulate a registered flow from its first step
+    pub async fn simulate(
+        &self,
+        flow_id: &str,
+        context: &HashMap<String, serde_json::Value>,
+    ) -> Result<FlowSimulationReport> {
+        let flow = self.coordinator.flow(flow_id).await.ok_or_else(|| {
+            RuntimeError::CoordinationError(format!("Flow not found: {}", flow_id))
+        })?;
+        info!("Simulating flow {}", flow_id);
+
+        let mut overlay: HashMap<Pubkey, Option<AccountSnapshot>> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut steps = Vec::new();
+        let mut next = flow.steps.first().map(|step| step.name.clone());
+
+        while let Some(name) = next.take() {
+            let (index, step) = flow
+                .steps
+                .iter()
+                .enumerate()
+                .find(|(_, step)| step.name == name)
+                .ok_or_else(|| {
+                    RuntimeError::CoordinationError(format!("Step not found: {}", name))
+                })?;
+
+            // A dry run never retries, so revisiting a step means the flow loops
+            if !visited.insert(name.clone()) {
+                return Err(RuntimeError::CoordinationError(format!(
+                    "Flow {} revisits step {}",
+                    flow_id, name
+                )));
+            }
+
+            let simulation = self
+                .simulate_step(flow_id, &name, step.cluster.as_deref(), context, &mut overlay)
+                .await?;
+
+            next = if simulation.success {
+                step.on_success
+                    .clone()
+                    .or_else(|| flow.steps.get(index + 1).map(|step| step.name.clone()))
+            } else {
+                step.on_failure.clone()
+            };
+            steps.push(simulation);
+        }
+
+        Ok(FlowSimulationReport {
+            flow_id: flow_id.to_string(),
+            success: steps.last().is_some_and(|step| step.success),
+            total_compute_units: steps.iter().filter_map(|step| step.compute_units).sum(),
+            total_fee_lamports: steps.iter().map(|step| step.fee_lamports).sum(),
+            steps,
+        })
+    }
+
+    async fn simulate_step(
+        &self,
+        flow_id: &str,
+        step_name: &str,
+        cluster: Option<&str>,
+        context: &HashMap<String, serde_json::Value>,
+        overlay: &mut HashMap<Pubkey, Option<AccountSnapshot>>,
+    ) -> Result<StepSimulation> {
+        let mut report = StepSimulation {
+            step: step_name.to_string(),
+            cluster: cluster.map(str::to_string),
+            success: false,
+            error: None,
+            logs: Vec::new(),
+            compute_units: None,
+            fee_lamports: 0,
+            mutations: Vec::new(),
+        };
+
+        let instruction = match self
+            .coordinator
+            .build_step_instruction(flow_id, step_name, context)
+            .await
+        {
+            Ok(instruction) => instruction,
+            Err(e) => {
+                report.error = Some(e.to_string());
+                return Ok(report);
+            }
+        };
+
+        // The fee payer and every writable account can change
+        let mut addresses = vec![self.payer];
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_writable) {
+            if !addresses.contains(&meta.pubkey) {
+                addresses.push(meta.pubkey);
+            }
+        }
+
+        let unknown: Vec<Pubkey> = addresses
+            .iter()
+            .filter(|address| !overlay.contains_key(address))
+            .copied()
+            .collect();
+        if !unknown.is_empty() {
+            let current = self.backend.accounts(cluster, &unknown).await?;
+            overlay.extend(unknown.into_iter().zip(current));
+        }
+
+        let simulation = self
+            .backend
+            .simulate(cluster, &self.payer, &[instruction], &addresses)
+            .await?;
+
+        report.success = simulation.success;
+        report.error = simulation.error;
+        report.logs = simulation.logs;
+        report.compute_units = simulation.compute_units;
+        report.fee_lamports = simulation.fee_lamports;
+
+        if simulation.success {
+            for (address, after) in addresses.into_iter().zip(simulation.post_accounts) {
+                let before = overlay.insert(address, after.clone()).flatten();
+                if before != after {
+                    report.mutations.push(AccountMutation {
+                        address,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coordination::{FlowStep, KernelInstructionType, ProtocolFlow, RetryPolicy},
+        monitoring::event_stream::EventStream,
+    };
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+
+    const FEE: u64 = 5_000;
+
+    /// Backend charging a fixed fee per simulation and failing the `fail_call`-th one
+    struct MockBackend {
+        balance: Mutex<u64>,
+        fetched: Mutex<usize>,
+        fail_call: Option<usize>,
+        calls: Mutex<usize>,
+    }
+
+    impl MockBackend {
+        fn new(fail_call: Option<usize>) -> Self {
+            Self {
+                balance: Mutex::new(1_000_000),
+                fetched: Mutex::new(0),
+                fail_call,
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn snapshot(lamports: u64) -> AccountSnapshot {
+            AccountSnapshot {
+                lamports,
+                owner: solana_sdk::system_program::ID,
+                data_len: 0,
+                data_hash: blake3::hash(&[]).to_hex().to_string(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SimulationBackend for MockBackend {
+        async fn accounts(
+            &self,
+            _cluster: Option<&str>,
+            addresses: &[Pubkey],
+        ) -> Result<Vec<Option<AccountSnapshot>>> {
+            *self.fetched.lock().await += addresses.len();
+            let balance = *self.balance.lock().await;
+            Ok(addresses.iter().map(|_| Some(Self::snapshot(balance))).collect())
+        }
+
+        async fn simulate(
+            &self,
+            _cluster: Option<&str>,
+            _payer: &Pubkey,
+            _instructions: &[Instruction],
+            addresses: &[Pubkey],
+        ) -> Result<TransactionSimulation> {
+            let mut calls = self.calls.lock().await;
+            let failed = self.fail_call == Some(*calls);
+            *calls += 1;
+
+            let mut balance = self.balance.lock().await;
+            *balance -= FEE;
+            Ok(TransactionSimulation {
+                success: !failed,
+                error: failed.then(|| "custom program error: 0x1".to_string()),
+                logs: vec!["Program log: simulated".to_string()],
+                compute_units: Some(10_000),
+                fee_lamports: FEE,
+                post_accounts: addresses.iter().map(|_| Some(Self::snapshot(*balance))).collect(),
+            })
+        }
+    }
+
+    async fn coordinator() -> Arc<Coordinator> {
+        let coordinator = Arc::new(Coordinator::new(
+            Arc::new(RpcClient::new_mock("succeeds".to_string())),
+            Arc::new(EventStream::new()),
+        ));
+        let step = |name: &str, on_failure: Option<&str>| FlowStep {
+            name: name.to_string(),
+            description: name.to_string(),
+            instruction_type: KernelInstructionType::InitializeShard,
+            on_success: None,
+            on_failure: on_failure.map(str::to_string),
+            cluster: None,
+        };
+        coordinator
+            .register_flow(ProtocolFlow {
+                id: "flow".to_string(),
+                name: "Flow".to_string(),
+                steps: vec![
+                    step("first", None),
+                    step("second", Some("recover")),
+                    step("recover", None),
+                ],
+                timeout: Duration::from_secs(60),
+                retry_policy: RetryPolicy::default(),
+            })
+            .await
+            .unwrap();
+        coordinator
+    }
+
+    #[tokio::test]
+    async fn test_simulate_flow() {
+        let backend = Arc::new(MockBackend::new(None));
+        let payer = Pubkey::new_unique();
+        let simulator = FlowSimulator::new(coordinator().await, backend.clone(), payer);
+
+        let report = simulator.simulate("flow", &HashMap::new()).await.unwrap();
+        assert!(report.success);
+        assert_eq!(report.steps.len(), 3);
+        assert_eq!(report.total_compute_units, 30_000);
+        assert_eq!(report.total_fee_lamports, 3 * FEE);
+
+        // Later steps start from the overlay rather than refetching
+        assert_eq!(*backend.fetched.lock().await, 1);
+        let first = &report.steps[0].mutations[0];
+        let second = &report.steps[1].mutations[0];
+        assert_eq!(first.address, payer);
+        assert_eq!(second.before, first.after);
+        assert_eq!(second.after.as_ref().unwrap().lamports, 1_000_000 - 2 * FEE);
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_follows_fallback() {
+        let backend = Arc::new(MockBackend::new(Some(1)));
+        let simulator = FlowSimulator::new(coordinator().await, backend, Pubkey::new_unique());
+
+        let report = simulator.simulate("flow", &HashMap::new()).await.unwrap();
+        let steps: Vec<&str> = report.steps.iter().map(|step| step.step.as_str()).collect();
+        assert_eq!(steps, ["first", "second", "recover"]);
+        assert!(!report.steps[1].success);
+        assert!(report.steps[1].mutations.is_empty());
+        assert!(report.success);
+
+        assert!(simulator.simulate("missing", &HashMap::new()).await.is_err());
+    }
+}
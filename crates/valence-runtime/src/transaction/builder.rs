@@ -1,6 +1,6 @@
 //! Transaction construction for valence-kernel operations
 
-use crate::{Result, RuntimeError};
+use crate::{coordination::SpendLedger, rate_limit::RateLimiter, Result, RuntimeError};
 use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
@@ -16,7 +16,7 @@ use tracing::{debug, info};
 
 // Import kernel types
 use valence_kernel::{
-    KernelOperation, OperationBatch,
+    KernelOperation, OperationBatch, OperationData,
     MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS,
 };
 
@@ -68,6 +68,9 @@ pub struct TransactionBuilder {
     signers: Vec<Pubkey>,
     compute_units: Option<u32>,
     priority_fee: Option<u64>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    spend_ledger: Option<Arc<SpendLedger>>,
+    flow_id: Option<String>,
 }
 
 impl TransactionBuilder {
@@ -79,6 +82,9 @@ impl TransactionBuilder {
             signers: Vec::new(),
             compute_units: None,
             priority_fee: None,
+            rate_limiter: None,
+            spend_ledger: None,
+            flow_id: None,
         }
     }
 
@@ -103,6 +109,13 @@ impl TransactionBuilder {
         self
     }
 
+    /// Set the fee payer, which otherwise defaults to the first signer
+    pub fn with_fee_payer(mut self, payer: Pubkey) -> Self {
+        self.signers.retain(|signer| *signer != payer);
+        self.signers.insert(0, payer);
+        self
+    }
+
     /// Set compute unit limit
     pub fn with_compute_units(mut self, units: u32) -> Self {
         self.compute_units = Some(units);
@@ -115,6 +128,24 @@ impl TransactionBuilder {
         self
     }
 
+    /// Admit built transactions through a rate limiter
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Refuse to build once the ledger's daily budget is exhausted
+    pub fn with_spend_ledger(mut self, spend_ledger: Arc<SpendLedger>) -> Self {
+        self.spend_ledger = Some(spend_ledger);
+        self
+    }
+
+    /// Charge built transactions to a flow's rate limit
+    pub fn for_flow(mut self, flow_id: impl Into<String>) -> Self {
+        self.flow_id = Some(flow_id.into());
+        self
+    }
+
     /// Build instruction from components
     pub fn instruction(
         program_id: Pubkey,
@@ -141,6 +172,17 @@ impl TransactionBuilder {
     pub async fn build(mut self, description: String) -> Result<UnsignedTransaction> {
         info!("Building unsigned transaction: {}", description);
 
+        if let Some(spend_ledger) = &self.spend_ledger {
+            spend_ledger.check()?;
+        }
+
+        // Rate limit before touching RPC
+        if let Some(rate_limiter) = &self.rate_limiter {
+            let programs: Vec<Pubkey> =
+                self.instructions.iter().map(|ix| ix.program_id).collect();
+            rate_limiter.acquire(self.flow_id.as_deref(), &programs)?;
+        }
+
         // Add compute budget instructions if specified
         if let Some(units) = self.compute_units {
             let compute_budget_ix =
@@ -219,11 +261,15 @@ impl TransactionBuilder {
     }
 
     /// Build complete session workflow transaction
+    ///
+    /// `operation_data` is the session's operation data, which the
+    /// operations' CPIs reference by offset.
     pub async fn build_session_workflow(
         self,
         session_pubkey: Pubkey,
         operations: Vec<KernelOperation>,
         accounts: Vec<Pubkey>,
+        operation_data: &[u8],
     ) -> Result<UnsignedTransaction> {
         info!("Building session workflow transaction");
 
@@ -254,6 +300,7 @@ impl TransactionBuilder {
             operations_len,
             accounts: batch_accounts,
             accounts_len,
+            operation_data_hash: OperationData::hash(operation_data),
         };
 
         let batch_instruction = super::instructions::execute_batch_instruction(
@@ -0,0 +1,563 @@
+//! Jito bundle submission for ordered flow-step transactions
+//!
+//! A flow step that needs several transactions to land together, in order,
+//! submits them as one bundle to a Jito block engine. The bundle must pay a
+//! tip to one of the block engine's tip accounts; [`BundleSubmitter::tip_instruction`]
+//! builds that transfer so it can be added before the transactions are signed
+//! externally. When the block engine cannot be reached the transactions are
+//! sent one by one over RPC instead, which preserves their order but not
+//! their atomicity, and the tip transfer is still paid if it lands.
+//!
+//! Landed bundles report what their transactions cost, so flow budgets can
+//! be charged with the fees, tip and rent actually paid.
+
+use crate::{
+    coordination::FlowCost,
+    monitoring::event_stream::{Event, EventStream},
+    transaction::{
+        cost::{confirmed_cost, tip_transfers},
+        instructions::transfer_instruction,
+    },
+    Result, RuntimeError,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tokio::{sync::RwLock, time::Instant};
+use tracing::{debug, info, warn};
+
+/// Most transactions a block engine accepts in one bundle
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+/// Bundle submission configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleConfig {
+    /// Block engine base URL
+    pub block_engine_url: String,
+
+    /// Lamports tipped per bundle
+    pub tip_lamports: u64,
+
+    /// Tip accounts; fetched from the block engine when empty
+    pub tip_accounts: Vec<Pubkey>,
+
+    /// Timeout of each block engine request
+    pub request_timeout: Duration,
+
+    /// Interval between bundle status checks
+    pub poll_interval: Duration,
+
+    /// How long to wait for a bundle to land
+    pub landing_timeout: Duration,
+}
+
+impl Default for BundleConfig {
+    fn default() -> Self {
+        Self {
+            block_engine_url: "https://mainnet.block-engine.jito.wtf".to_string(),
+            tip_lamports: 10_000,
+            tip_accounts: Vec::new(),
+            request_timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_secs(2),
+            landing_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// How a bundle's transactions reached the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BundleRoute {
+    /// Landed atomically through the block engine
+    BlockEngine { bundle_id: String },
+    /// Sent sequentially over RPC after the block engine was unavailable
+    Rpc,
+}
+
+/// Result of a landed bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleOutcome {
+    pub route: BundleRoute,
+    /// Signatures in submission order
+    pub signatures: Vec<Signature>,
+    /// Slot the bundle, or its last transaction, landed in
+    pub slot: Option<u64>,
+    /// Lamports the landed transactions spent
+    pub cost: FlowCost,
+}
+
+/// Why a block engine request did not succeed
+enum BlockEngineError {
+    /// The block engine could not be reached or is overloaded
+    Unavailable(String),
+    /// The block engine refused the request
+    Rejected(String),
+}
+
+/// Submits ordered transactions as Jito bundles, falling back to RPC
+pub struct BundleSubmitter {
+    config: BundleConfig,
+    client: reqwest::Client,
+    rpc_client: Arc<RpcClient>,
+    event_stream: Arc<EventStream>,
+    tip_accounts: RwLock<Vec<Pubkey>>,
+}
+
+impl BundleSubmitter {
+    pub fn new(
+        config: BundleConfig,
+        rpc_client: Arc<RpcClient>,
+        event_stream: Arc<EventStream>,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .use_rustls_tls()
+            .timeout(config.request_timeout)
+            .build()
+            .map_err(|e| RuntimeError::InvalidConfiguration(e.to_string()))?;
+
+        Ok(Self {
+            tip_accounts: RwLock::new(config.tip_accounts.clone()),
+            config,
+            client,
+            rpc_client,
+            event_stream,
+        })
+    }
+
+    /// Transfer of the configured tip from `payer` to a random tip account
+    pub async fn tip_instruction(&self, payer: &Pubkey) -> Result<Instruction> {
+        let tip_accounts = self.tip_accounts().await.map_err(
+            |(BlockEngineError::Unavailable(e) | BlockEngineError::Rejected(e))| {
+                RuntimeError::ConnectionError(format!("Fetching tip accounts: {}", e))
+            },
+        )?;
+        let tip_account = tip_accounts
+            .choose(&mut rand::thread_rng())
+            .ok_or_else(|| RuntimeError::InvalidConfiguration("No tip accounts".to_string()))?;
+        Ok(transfer_instruction(
+            *payer,
+            *tip_account,
+            self.config.tip_lamports,
+        ))
+    }
+
+    /// Submit signed transactions in order and wait for them to land
+    pub async fn submit(
+        &self,
+        transactions: &[Transaction],
+        description: &str,
+    ) -> Result<BundleOutcome> {
+        if transactions.is_empty() || transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(RuntimeError::TransactionBuildError(format!(
+                "Bundles hold 1 to {} transactions, got {}",
+                MAX_BUNDLE_TRANSACTIONS,
+                transactions.len()
+            )));
+        }
+
+        let mut outcome = match self.send_bundle(transactions).await {
+            Ok(bundle_id) => {
+                info!("Submitted bundle {}: {}", bundle_id, description);
+                self.event_stream
+                    .emit(Event::BundleSubmitted {
+                        bundle_id: bundle_id.clone(),
+                        signatures: transactions
+                            .iter()
+                            .map(|transaction| transaction.signatures[0].to_string())
+                            .collect(),
+                        description: description.to_string(),
+                    })
+                    .await;
+                self.await_landing(bundle_id, transactions).await?
+            }
+            Err(BlockEngineError::Unavailable(reason)) => {
+                warn!("Block engine unavailable, submitting over RPC: {}", reason);
+                self.event_stream
+                    .emit(Event::Warning {
+                        context: format!("bundle: {}", description),
+                        message: format!(
+                            "Block engine unavailable, submitting over RPC: {}",
+                            reason
+                        ),
+                    })
+                    .await;
+                self.submit_sequentially(transactions, description).await?
+            }
+            Err(BlockEngineError::Rejected(reason)) => {
+                return Err(RuntimeError::BundleRejected(reason))
+            }
+        };
+
+        let tip_accounts = self.tip_accounts.read().await.clone();
+        for transaction in transactions {
+            let cost = confirmed_cost(&self.rpc_client, transaction, &tip_accounts).await;
+            outcome.cost = outcome.cost.combine(cost);
+        }
+        Ok(outcome)
+    }
+
+    /// Tip accounts from configuration or, once fetched, from the block engine
+    async fn tip_accounts(&self) -> std::result::Result<Vec<Pubkey>, BlockEngineError> {
+        {
+            let cached = self.tip_accounts.read().await;
+            if !cached.is_empty() {
+                return Ok(cached.clone());
+            }
+        }
+
+        let result = self.call("getTipAccounts", json!([])).await?;
+        let fetched: Vec<Pubkey> = serde_json::from_value::<Vec<String>>(result)
+            .map_err(|e| BlockEngineError::Rejected(e.to_string()))?
+            .iter()
+            .filter_map(|account| Pubkey::from_str(account).ok())
+            .collect();
+        *self.tip_accounts.write().await = fetched.clone();
+        Ok(fetched)
+    }
+
+    async fn send_bundle(
+        &self,
+        transactions: &[Transaction],
+    ) -> std::result::Result<String, BlockEngineError> {
+        // Block engines drop untipped bundles without reporting why
+        let tip_accounts = self.tip_accounts().await?;
+        let tipped = transactions
+            .iter()
+            .map(|transaction| tip_transfers(transaction, &tip_accounts))
+            .fold(0u64, u64::saturating_add);
+        if tipped < self.config.tip_lamports {
+            return Err(BlockEngineError::Rejected(format!(
+                "Bundle does not tip {} lamports to a tip account",
+                self.config.tip_lamports
+            )));
+        }
+
+        let encoded = transactions
+            .iter()
+            .map(|transaction| bincode::serialize(transaction).map(|bytes| STANDARD.encode(bytes)))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| BlockEngineError::Rejected(e.to_string()))?;
+        let result = self
+            .call("sendBundle", json!([encoded, { "encoding": "base64" }]))
+            .await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| BlockEngineError::Rejected(format!("Unexpected bundle id {}", result)))
+    }
+
+    /// Poll the bundle's status until it lands, fails or times out
+    async fn await_landing(
+        &self,
+        bundle_id: String,
+        transactions: &[Transaction],
+    ) -> Result<BundleOutcome> {
+        let deadline = Instant::now() + self.config.landing_timeout;
+
+        let error = loop {
+            match self
+                .call("getInflightBundleStatuses", json!([[bundle_id]]))
+                .await
+            {
+                Ok(result) => {
+                    let status = &result["value"][0];
+                    match status["status"].as_str() {
+                        Some("Landed") => {
+                            let slot = status["landed_slot"].as_u64().unwrap_or_default();
+                            self.event_stream
+                                .emit(Event::BundleLanded {
+                                    bundle_id: bundle_id.clone(),
+                                    slot,
+                                })
+                                .await;
+                            return Ok(BundleOutcome {
+                                route: BundleRoute::BlockEngine { bundle_id },
+                                signatures: transactions
+                                    .iter()
+                                    .map(|transaction| transaction.signatures[0])
+                                    .collect(),
+                                slot: Some(slot),
+                                cost: FlowCost::default(),
+                            });
+                        }
+                        Some("Failed") => break "Bundle failed to land".to_string(),
+                        // Pending, or not yet visible to the block engine
+                        other => debug!("Bundle {} status: {:?}", bundle_id, other),
+                    }
+                }
+                Err(BlockEngineError::Unavailable(e) | BlockEngineError::Rejected(e)) => {
+                    warn!("Bundle status check failed: {}", e);
+                }
+            }
+
+            if Instant::now() >= deadline {
+                break format!(
+                    "Bundle did not land within {:?}",
+                    self.config.landing_timeout
+                );
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        };
+
+        self.event_stream
+            .emit(Event::BundleFailed {
+                bundle_id,
+                error: error.clone(),
+            })
+            .await;
+        Err(RuntimeError::BundleRejected(error))
+    }
+
+    /// Send and confirm each transaction in order over RPC
+    async fn submit_sequentially(
+        &self,
+        transactions: &[Transaction],
+        description: &str,
+    ) -> Result<BundleOutcome> {
+        let mut signatures = Vec::with_capacity(transactions.len());
+        let mut slot = None;
+
+        for transaction in transactions {
+            let signature = self
+                .rpc_client
+                .send_and_confirm_transaction(transaction)
+                .await?;
+            self.event_stream
+                .emit(Event::TransactionSubmitted {
+                    signature: signature.to_string(),
+                    description: description.to_string(),
+                })
+                .await;
+
+            let status = self
+                .rpc_client
+                .get_signature_statuses(&[signature])
+                .await?
+                .value
+                .into_iter()
+                .next()
+                .flatten();
+            if let Some(status) = status {
+                slot = Some(status.slot);
+                self.event_stream
+                    .emit(Event::TransactionConfirmed {
+                        signature: signature.to_string(),
+                        slot: status.slot,
+                        error: status.err.map(|e| e.to_string()),
+                    })
+                    .await;
+            }
+            signatures.push(signature);
+        }
+
+        Ok(BundleOutcome {
+            route: BundleRoute::Rpc,
+            signatures,
+            slot,
+            cost: FlowCost::default(),
+        })
+    }
+
+    /// Call a block engine JSON-RPC method
+    async fn call(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> std::result::Result<Value, BlockEngineError> {
+        let url = format!(
+            "{}/api/v1/bundles",
+            self.config.block_engine_url.trim_end_matches('/')
+        );
+        let response = self
+            .client
+            .post(url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(|e| BlockEngineError::Unavailable(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(BlockEngineError::Unavailable(format!(
+                "{} returned {}",
+                method, status
+            )));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| BlockEngineError::Unavailable(e.to_string()))?;
+        if let Some(error) = body.get("error") {
+            return Err(BlockEngineError::Rejected(
+                error["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            ));
+        }
+        Ok(body["result"].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer};
+    use std::sync::Mutex;
+
+    fn tip_account() -> Pubkey {
+        Pubkey::new_from_array([7; 32])
+    }
+
+    fn config(block_engine_url: String) -> BundleConfig {
+        BundleConfig {
+            block_engine_url,
+            tip_lamports: 1_000,
+            poll_interval: Duration::from_millis(10),
+            landing_timeout: Duration::from_secs(5),
+            ..BundleConfig::default()
+        }
+    }
+
+    fn transaction(payer: &Keypair, instructions: &[Instruction]) -> Transaction {
+        Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &[payer],
+            Hash::new_unique(),
+        )
+    }
+
+    /// Block engine that lands bundles after one pending status check
+    async fn block_engine(methods: Arc<Mutex<Vec<String>>>) -> String {
+        let router = Router::new().route(
+            "/api/v1/bundles",
+            post(move |Json(request): Json<Value>| {
+                let methods = methods.clone();
+                async move {
+                    let method = request["method"].as_str().unwrap_or_default().to_string();
+                    let polls = {
+                        let mut methods = methods.lock().unwrap();
+                        methods.push(method.clone());
+                        methods.iter().filter(|m| *m == "getInflightBundleStatuses").count()
+                    };
+                    let result = match method.as_str() {
+                        "getTipAccounts" => json!([tip_account().to_string()]),
+                        "sendBundle" => json!("bundle-1"),
+                        _ if polls < 2 => json!({ "value": [{ "bundle_id": "bundle-1", "status": "Pending" }] }),
+                        _ => json!({ "value": [{ "bundle_id": "bundle-1", "status": "Landed", "landed_slot": 42 }] }),
+                    };
+                    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_bundle_lands_through_block_engine() {
+        let methods = Arc::new(Mutex::new(Vec::new()));
+        let url = block_engine(methods.clone()).await;
+        let event_stream = Arc::new(EventStream::new());
+        let mut events = event_stream.subscribe().await;
+        let submitter = BundleSubmitter::new(
+            config(url),
+            Arc::new(RpcClient::new_mock("succeeds".to_string())),
+            event_stream,
+        )
+        .unwrap();
+
+        let payer = Keypair::new();
+        let tip = submitter.tip_instruction(&payer.pubkey()).await.unwrap();
+        let noop = transfer_instruction(payer.pubkey(), Pubkey::new_unique(), 1);
+        let transactions = vec![transaction(&payer, &[noop]), transaction(&payer, &[tip])];
+
+        let outcome = submitter.submit(&transactions, "flow/step").await.unwrap();
+        assert_eq!(
+            outcome.route,
+            BundleRoute::BlockEngine {
+                bundle_id: "bundle-1".to_string()
+            }
+        );
+        assert_eq!(outcome.slot, Some(42));
+        assert_eq!(outcome.signatures[1], transactions[1].signatures[0]);
+
+        // Tip accounts are fetched once
+        assert_eq!(
+            *methods.lock().unwrap(),
+            vec![
+                "getTipAccounts",
+                "sendBundle",
+                "getInflightBundleStatuses",
+                "getInflightBundleStatuses"
+            ]
+        );
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            Event::BundleSubmitted { bundle_id, signatures, .. } if bundle_id == "bundle-1" && signatures.len() == 2
+        ));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            Event::BundleLanded { slot: 42, .. }
+        ));
+
+        // Untipped bundles are refused before reaching the block engine
+        let untipped = vec![transaction(
+            &payer,
+            &[transfer_instruction(payer.pubkey(), tip_account(), 10)],
+        )];
+        assert!(matches!(
+            submitter.submit(&untipped, "flow/step").await,
+            Err(RuntimeError::BundleRejected(_))
+        ));
+        assert!(submitter.submit(&[], "flow/step").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rpc_fallback_when_block_engine_unavailable() {
+        // Nothing listens on the block engine address
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let event_stream = Arc::new(EventStream::new());
+        let mut events = event_stream.subscribe().await;
+        let submitter = BundleSubmitter::new(
+            BundleConfig {
+                tip_accounts: vec![tip_account()],
+                ..config(url)
+            },
+            Arc::new(RpcClient::new_mock("succeeds".to_string())),
+            event_stream,
+        )
+        .unwrap();
+
+        let payer = Keypair::new();
+        let tip = submitter.tip_instruction(&payer.pubkey()).await.unwrap();
+        let transactions = vec![transaction(&payer, &[tip])];
+        let outcome = submitter.submit(&transactions, "flow/step").await.unwrap();
+        assert_eq!(outcome.route, BundleRoute::Rpc);
+        assert_eq!(outcome.signatures.len(), 1);
+        // The mock RPC reports a free transaction that moved no lamports
+        assert_eq!(outcome.cost, FlowCost::default());
+
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            Event::Warning { .. }
+        ));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            Event::TransactionSubmitted { .. }
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+//! Lamports spent by submitted transactions
+//!
+//! Flow budgets are charged with what confirmed transactions actually cost:
+//! the fee from their status meta, tips as the balance gained by tip
+//! accounts, and rent as the balance of accounts they funded from zero.
+
+use crate::{coordination::FlowCost, Result, RuntimeError};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+#[allow(deprecated)]
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, transaction::Transaction,
+};
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionStatusMeta};
+use tracing::warn;
+
+/// Base fee charged per signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Lamports a confirmed transaction spent, read from its status meta
+///
+/// Falls back to [`estimated_cost`] when the meta cannot be fetched, so a
+/// submitted transaction is never charged as free.
+pub async fn confirmed_cost(
+    rpc_client: &RpcClient,
+    transaction: &Transaction,
+    tip_accounts: &[Pubkey],
+) -> FlowCost {
+    match fetch_meta(rpc_client, transaction).await {
+        Ok(meta) => cost_from_meta(&transaction.message.account_keys, &meta, tip_accounts),
+        Err(e) => {
+            warn!(
+                "Estimating cost of {} without its status meta: {}",
+                transaction.signatures[0], e
+            );
+            estimated_cost(transaction, tip_accounts)
+        }
+    }
+}
+
+/// Cost of a transaction from its status meta
+///
+/// `account_keys` are the transaction's static keys, which index the meta's
+/// balances.
+pub fn cost_from_meta(
+    account_keys: &[Pubkey],
+    meta: &UiTransactionStatusMeta,
+    tip_accounts: &[Pubkey],
+) -> FlowCost {
+    let mut cost = FlowCost {
+        fees: meta.fee,
+        ..Default::default()
+    };
+
+    let balances = meta.pre_balances.iter().zip(&meta.post_balances);
+    for (key, (pre, post)) in account_keys.iter().zip(balances) {
+        if tip_accounts.contains(key) {
+            cost.tips = cost.tips.saturating_add(post.saturating_sub(*pre));
+        } else if *pre == 0 && *post > 0 {
+            cost.rent = cost.rent.saturating_add(*post);
+        }
+    }
+    cost
+}
+
+/// Lower bound on a transaction's cost from its message alone
+///
+/// Counts the base signature fee and system transfers to tip accounts.
+pub fn estimated_cost(transaction: &Transaction, tip_accounts: &[Pubkey]) -> FlowCost {
+    FlowCost {
+        fees: LAMPORTS_PER_SIGNATURE
+            .saturating_mul(transaction.message.header.num_required_signatures as u64),
+        tips: tip_transfers(transaction, tip_accounts),
+        rent: 0,
+    }
+}
+
+/// Lamports a transaction transfers to tip accounts through the system program
+#[allow(deprecated)]
+pub fn tip_transfers(transaction: &Transaction, tip_accounts: &[Pubkey]) -> u64 {
+    let keys = &transaction.message.account_keys;
+    transaction
+        .message
+        .instructions
+        .iter()
+        .filter(|instruction| {
+            let program = keys.get(instruction.program_id_index as usize);
+            let recipient = instruction
+                .accounts
+                .get(1)
+                .and_then(|index| keys.get(*index as usize));
+            program == Some(&solana_sdk::system_program::ID)
+                && recipient.is_some_and(|recipient| tip_accounts.contains(recipient))
+        })
+        .filter_map(|instruction| match bincode::deserialize(&instruction.data) {
+            Ok(SystemInstruction::Transfer { lamports }) => Some(lamports),
+            _ => None,
+        })
+        .fold(0u64, u64::saturating_add)
+}
+
+async fn fetch_meta(
+    rpc_client: &RpcClient,
+    transaction: &Transaction,
+) -> Result<UiTransactionStatusMeta> {
+    let confirmed = rpc_client
+        .get_transaction_with_config(
+            &transaction.signatures[0],
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+    confirmed.transaction.meta.ok_or_else(|| {
+        RuntimeError::TransactionBuildError(format!(
+            "Transaction {} has no status meta",
+            transaction.signatures[0]
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::instructions::transfer_instruction;
+    use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer};
+    use solana_transaction_status::option_serializer::OptionSerializer;
+
+    fn meta(fee: u64, pre_balances: Vec<u64>, post_balances: Vec<u64>) -> UiTransactionStatusMeta {
+        UiTransactionStatusMeta {
+            err: None,
+            status: Ok(()),
+            fee,
+            pre_balances,
+            post_balances,
+            inner_instructions: OptionSerializer::None,
+            log_messages: OptionSerializer::None,
+            pre_token_balances: OptionSerializer::None,
+            post_token_balances: OptionSerializer::None,
+            rewards: OptionSerializer::None,
+            loaded_addresses: OptionSerializer::Skip,
+            return_data: OptionSerializer::Skip,
+            compute_units_consumed: OptionSerializer::Skip,
+            cost_units: OptionSerializer::Skip,
+        }
+    }
+
+    #[test]
+    fn test_cost_from_meta() {
+        let payer = Pubkey::new_unique();
+        let tip_account = Pubkey::new_unique();
+        let created = Pubkey::new_unique();
+        let existing = Pubkey::new_unique();
+
+        let cost = cost_from_meta(
+            &[payer, tip_account, created, existing],
+            &meta(
+                7_500,
+                vec![10_000_000, 500, 0, 100],
+                vec![7_482_500, 1_500, 2_500_000, 110],
+            ),
+            &[tip_account],
+        );
+        assert_eq!(
+            cost,
+            FlowCost {
+                fees: 7_500,
+                tips: 1_000,
+                rent: 2_500_000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_estimated_cost() {
+        let payer = Keypair::new();
+        let tip_account = Pubkey::new_unique();
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                transfer_instruction(payer.pubkey(), tip_account, 1_000),
+                transfer_instruction(payer.pubkey(), Pubkey::new_unique(), 50),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::new_unique(),
+        );
+
+        let cost = estimated_cost(&transaction, &[tip_account]);
+        assert_eq!(cost.fees, LAMPORTS_PER_SIGNATURE);
+        assert_eq!(cost.tips, 1_000);
+    }
+}
@@ -1,6 +1,7 @@
 //! Kernel-specific instruction builders
 
 use crate::Result;
+use anchor_lang::InstructionData;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -8,11 +9,21 @@ use solana_sdk::{
 
 // Import kernel types
 use valence_kernel::{
-    state::CreateSessionParams,
+    instruction as kernel_instruction,
+    state::{CreateSessionParams, RegisteredAccount, RegisteredProgram},
     KernelOperation, OperationBatch,
     PROGRAM_ID as KERNEL_PROGRAM_ID,
 };
 
+/// Accounts created alongside a kernel session
+#[derive(Debug, Clone, Copy)]
+pub struct SessionAccounts {
+    pub owner: Pubkey,
+    pub session: Pubkey,
+    pub account_lookup: Pubkey,
+    pub guard: Pubkey,
+}
+
 /// Create a transfer instruction
 pub fn transfer_instruction(from: Pubkey, to: Pubkey, lamports: u64) -> Instruction {
     solana_sdk::system_instruction::transfer(&from, &to, lamports)
@@ -30,37 +41,69 @@ pub fn create_account_instruction(
 }
 
 /// Build valence-kernel session creation instruction
+///
+/// The session and account lookup are fresh accounts and must sign.
 pub fn create_session_instruction(
-    payer: Pubkey,
-    session: Pubkey,
+    accounts: SessionAccounts,
     shard: Pubkey,
-    _params: CreateSessionParams,
+    params: CreateSessionParams,
+    initial_borrowable: Vec<RegisteredAccount>,
+    initial_programs: Vec<RegisteredProgram>,
 ) -> Result<Instruction> {
-    // Note: This is a simplified version. Real implementation would use
-    // Anchor's instruction builders or manually construct the instruction data.
-    
-    let accounts = vec![
-        AccountMeta::new(payer, true),        // payer
-        AccountMeta::new(session, false),     // session account
-        AccountMeta::new_readonly(shard, false), // shard account
-        AccountMeta::new_readonly(solana_sdk::system_program::ID, false), // system program
-        AccountMeta::new_readonly(solana_sdk::sysvar::clock::ID, false),   // clock sysvar
+    let metas = vec![
+        AccountMeta::new(accounts.session, true),
+        AccountMeta::new(accounts.account_lookup, true),
+        AccountMeta::new_readonly(accounts.guard, false),
+        AccountMeta::new(accounts.owner, true),
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
     ];
 
-    // Serialize parameters (simplified - real implementation would use Anchor borsh)
-    let data = vec![
-        // Instruction discriminator for create_session_account
-        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
-        // Parameters would be serialized here
-    ];
+    let data = kernel_instruction::CreateSessionAccount {
+        shard,
+        params,
+        initial_borrowable,
+        initial_programs,
+    }
+    .data();
 
     Ok(Instruction {
         program_id: KERNEL_PROGRAM_ID,
-        accounts,
+        accounts: metas,
         data,
     })
 }
 
+/// Build session invalidation instruction
+pub fn invalidate_session_instruction(owner: Pubkey, session: Pubkey) -> Result<Instruction> {
+    Ok(Instruction {
+        program_id: KERNEL_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(session, false),
+            AccountMeta::new_readonly(owner, true),
+        ],
+        data: kernel_instruction::InvalidateSession {}.data(),
+    })
+}
+
+/// Build session close instruction, refunding the session, lookup table and guard rent to the owner
+pub fn close_session_instruction(
+    owner: Pubkey,
+    session: Pubkey,
+    account_lookup: Pubkey,
+    guard: Pubkey,
+) -> Result<Instruction> {
+    Ok(Instruction {
+        program_id: KERNEL_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(session, false),
+            AccountMeta::new(account_lookup, false),
+            AccountMeta::new(guard, false),
+            AccountMeta::new(owner, true),
+        ],
+        data: kernel_instruction::CloseSession {}.data(),
+    })
+}
+
 /// Build batch execution instruction
 pub fn execute_batch_instruction(
     session: Pubkey,
@@ -116,24 +159,25 @@ pub fn execute_batch_instruction(
 }
 
 /// Build guard account creation instruction
+///
+/// The guard is a fresh account and must sign.
 pub fn create_guard_instruction(
     payer: Pubkey,
     guard: Pubkey,
     session: Pubkey,
-    _allow_unregistered_cpi: bool,
+    allow_unregistered_cpi: bool,
 ) -> Result<Instruction> {
     let accounts = vec![
+        AccountMeta::new(guard, true),
         AccountMeta::new(payer, true),
-        AccountMeta::new(guard, false),
-        AccountMeta::new_readonly(session, false),
         AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
     ];
 
-    let data = vec![
-        // Instruction discriminator for create_guard_account
-        0xa0, 0xb0, 0xc0, 0xd0, 0xe0, 0xf0, 0x01, 0x02,
-        // Session pubkey (32 bytes)
-    ];
+    let data = kernel_instruction::CreateGuardAccount {
+        session,
+        allow_unregistered_cpi,
+    }
+    .data();
 
     Ok(Instruction {
         program_id: KERNEL_PROGRAM_ID,
@@ -184,37 +228,22 @@ pub fn manage_alt_instruction(
     authority: Pubkey,
     session: Pubkey,
     alt: Pubkey,
-    add_accounts: Vec<Pubkey>,
+    add_borrowable: Vec<RegisteredAccount>,
+    add_programs: Vec<RegisteredProgram>,
     remove_accounts: Vec<Pubkey>,
 ) -> Result<Instruction> {
-    let mut accounts = vec![
-        AccountMeta::new(authority, true),
-        AccountMeta::new(session, false),
+    let accounts = vec![
+        AccountMeta::new_readonly(session, false),
         AccountMeta::new(alt, false),
-        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        AccountMeta::new_readonly(authority, true),
     ];
 
-    // Add accounts to be managed
-    for account in &add_accounts {
-        accounts.push(AccountMeta::new_readonly(*account, false));
-    }
-
-    let mut data = vec![
-        // Instruction discriminator for manage_alt
-        0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00,
-    ];
-    
-    // Serialize add accounts
-    data.extend_from_slice(&(add_accounts.len() as u32).to_le_bytes());
-    for account in &add_accounts {
-        data.extend_from_slice(&account.to_bytes());
-    }
-    
-    // Serialize remove accounts
-    data.extend_from_slice(&(remove_accounts.len() as u32).to_le_bytes());
-    for account in &remove_accounts {
-        data.extend_from_slice(&account.to_bytes());
+    let data = kernel_instruction::ManageAlt {
+        add_borrowable,
+        add_programs,
+        remove_accounts,
     }
+    .data();
 
     Ok(Instruction {
         program_id: KERNEL_PROGRAM_ID,
@@ -230,5 +259,8 @@ pub fn estimate_kernel_operation_compute(operation: &KernelOperation) -> u64 {
         KernelOperation::ReleaseAccount { .. } => 3_000,
         KernelOperation::CallRegisteredFunction { .. } => 10_000,
         KernelOperation::UnsafeRawCpi { .. } => 15_000,
+        KernelOperation::AssertAccountData { .. } | KernelOperation::AssertTokenBalance { .. } => {
+            2_000
+        }
     }
 }
\ No newline at end of file
@@ -48,7 +48,7 @@ pub struct RuntimeMetrics {
 /// Runtime event types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RuntimeEvent {
-    SessionCreated { session: Pubkey, namespace: String },
+    SessionCreated(valence_common::events::SessionCreated),
     TransactionBuilt { description: String, signers: Vec<Pubkey> },
     ValidationCompleted { success: bool, errors: u32 },
     SecurityViolation { actor: String, violation: String },
@@ -98,6 +98,89 @@ pub enum SessionHealth {
     Unavailable,
 }
 
+/// Why a tracked session is unhealthy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnhealthyReason {
+    /// Session account no longer exists
+    SessionClosed,
+    /// Session was invalidated by a move or consume
+    Inactive,
+    /// Account has been borrowed for longer than allowed
+    StaleBorrow { account: Pubkey, borrowed_for_secs: i64 },
+    /// Guard account referenced by the session does not exist
+    GuardMissing { guard: Pubkey },
+    /// Guard account cannot be decoded or belongs to another session
+    GuardInconsistent { guard: Pubkey },
+    /// On-chain nonce changed since the session was cached
+    NonceDrift { cached: u64, on_chain: u64 },
+    /// Lamport balance is below the rent-exempt minimum
+    LowBalance { lamports: u64, rent_exempt_minimum: u64 },
+}
+
+impl UnhealthyReason {
+    /// Whether the session can no longer be used safely
+    pub fn is_critical(&self) -> bool {
+        matches!(
+            self,
+            UnhealthyReason::SessionClosed
+                | UnhealthyReason::Inactive
+                | UnhealthyReason::GuardMissing { .. }
+                | UnhealthyReason::GuardInconsistent { .. }
+        )
+    }
+
+    /// Whether the session can never become healthy again
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, UnhealthyReason::SessionClosed | UnhealthyReason::Inactive)
+    }
+
+    /// Whether two reports describe the same ongoing issue
+    ///
+    /// Borrow age and balance change between checks without the issue changing.
+    pub fn same_issue(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                UnhealthyReason::StaleBorrow { account, .. },
+                UnhealthyReason::StaleBorrow { account: other, .. },
+            ) => account == other,
+            (UnhealthyReason::LowBalance { .. }, UnhealthyReason::LowBalance { .. }) => true,
+            _ => self == other,
+        }
+    }
+}
+
+impl std::fmt::Display for UnhealthyReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnhealthyReason::SessionClosed => write!(f, "session account closed"),
+            UnhealthyReason::Inactive => write!(f, "session inactive"),
+            UnhealthyReason::StaleBorrow { account, borrowed_for_secs } => {
+                write!(f, "{} borrowed for {}s", account, borrowed_for_secs)
+            }
+            UnhealthyReason::GuardMissing { guard } => write!(f, "guard {} missing", guard),
+            UnhealthyReason::GuardInconsistent { guard } => {
+                write!(f, "guard {} does not belong to session", guard)
+            }
+            UnhealthyReason::NonceDrift { cached, on_chain } => {
+                write!(f, "nonce drifted from {} to {}", cached, on_chain)
+            }
+            UnhealthyReason::LowBalance { lamports, rent_exempt_minimum } => {
+                write!(f, "balance {} below rent-exempt minimum {}", lamports, rent_exempt_minimum)
+            }
+        }
+    }
+}
+
+/// Step taken by the session lifecycle manager
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LifecycleStep {
+    Created,
+    RegistrationsUpdated { added: usize, removed: usize },
+    ToppedUp { lamports: u64 },
+    Rotated { previous: Pubkey },
+    Closed,
+}
+
 /// Guard health check
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuardHealth {
@@ -0,0 +1,77 @@
+use crate::cpi_allowlist_address;
+use anchor_lang::{prelude::*, InstructionData};
+#[allow(deprecated)]
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::instruction::Instruction;
+use valence_kernel::instruction as kernel_instruction;
+
+/// Address of an upgradeable program's ProgramData account
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Instruction creating the kernel's CPI allowlist with `authority` managing it
+pub fn initialize_allowlist_instruction(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: valence_kernel::ID,
+        accounts: vec![
+            AccountMeta::new(cpi_allowlist_address(), false),
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+        data: kernel_instruction::InitializeAllowlist {}.data(),
+    }
+}
+
+/// Instruction allowing sessions to invoke `program_id`
+///
+/// Pass the program's ProgramData account for upgradeable programs so the
+/// kernel records the deployment slot; `reject_upgrades` requires it.
+pub fn add_to_allowlist_instruction(
+    authority: Pubkey,
+    program_id: Pubkey,
+    reject_upgrades: bool,
+    program_data: Option<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(cpi_allowlist_address(), false),
+        AccountMeta::new_readonly(authority, true),
+    ];
+    accounts.extend(program_data.map(|address| AccountMeta::new_readonly(address, false)));
+    Instruction {
+        program_id: valence_kernel::ID,
+        accounts,
+        data: kernel_instruction::AddProgramToCpiAllowlist {
+            program_id,
+            reject_upgrades,
+        }
+        .data(),
+    }
+}
+
+/// Instruction revoking sessions' permission to invoke `program_id`
+pub fn remove_from_allowlist_instruction(authority: Pubkey, program_id: Pubkey) -> Instruction {
+    Instruction {
+        program_id: valence_kernel::ID,
+        accounts: vec![
+            AccountMeta::new(cpi_allowlist_address(), false),
+            AccountMeta::new_readonly(authority, true),
+        ],
+        data: kernel_instruction::RemoveProgramFromCpiAllowlist { program_id }.data(),
+    }
+}
+
+/// Instruction growing a version 1 allowlist to the current layout
+///
+/// `authority` pays the rent of the larger account.
+pub fn migrate_allowlist_instruction(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: valence_kernel::ID,
+        accounts: vec![
+            AccountMeta::new(cpi_allowlist_address(), false),
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+        data: kernel_instruction::MigrateAllowlist {}.data(),
+    }
+}
@@ -0,0 +1,501 @@
+use crate::{
+    decode_transaction_error, program_accounts_config, session_owner_filters,
+    session_shard_filters, AccountCache, Confirmation, FeePayer, KernelSession,
+    KernelSessionBuilder, Result, SdkError, SendOptions, SessionBuilder, SessionHandle,
+    SigningPlan, Simulation, MINT_ACCOUNT_LEN,
+};
+use anchor_lang::prelude::*;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    signature::Signature,
+    signer::Signer,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::time::{sleep, Instant};
+use valence_kernel::{
+    state::{RegisteredAccount, RegisteredProgram, Session},
+    OperationBatch,
+};
+
+/// Non-blocking Valence client for use inside tokio services
+#[derive(Clone)]
+pub struct ValenceClientAsync {
+    pub rpc_client: Arc<RpcClient>,
+    pub payer: Arc<dyn Signer + Send + Sync>,
+    cache: Option<Arc<AccountCache>>,
+}
+
+impl ValenceClientAsync {
+    /// Create a new async Valence client
+    pub fn new(
+        rpc_url: String,
+        payer: Arc<dyn Signer + Send + Sync>,
+        commitment: Option<CommitmentConfig>,
+    ) -> Self {
+        let rpc_client = RpcClient::new_with_commitment(
+            rpc_url,
+            commitment.unwrap_or(CommitmentConfig::confirmed()),
+        );
+        Self::with_rpc_client(Arc::new(rpc_client), payer)
+    }
+
+    /// Create a client sharing an existing RPC client
+    pub fn with_rpc_client(
+        rpc_client: Arc<RpcClient>,
+        payer: Arc<dyn Signer + Send + Sync>,
+    ) -> Self {
+        Self {
+            rpc_client,
+            payer,
+            cache: None,
+        }
+    }
+
+    /// Cache account data read through `fetch` for `ttl`
+    pub fn with_account_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(Arc::new(AccountCache::new(ttl)));
+        self
+    }
+
+    /// Get the account cache, if enabled
+    pub fn account_cache(&self) -> Option<&Arc<AccountCache>> {
+        self.cache.as_ref()
+    }
+
+    /// Get the payer's public key
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    /// Get the RPC client
+    pub fn rpc_client(&self) -> &Arc<RpcClient> {
+        &self.rpc_client
+    }
+
+    /// Get an account
+    pub async fn get_account<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let account = self
+            .rpc_client
+            .get_account(address)
+            .await
+            .map_err(|_| SdkError::AccountNotFound(address.to_string()))?;
+        T::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| SdkError::Serialization(e.to_string()))
+    }
+
+    /// Fetch and decode an account, served from the cache when fresh
+    pub async fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let data = match self.cache.as_ref().and_then(|cache| cache.get(address)) {
+            Some(data) => data,
+            None => {
+                let account = self
+                    .rpc_client
+                    .get_account_with_commitment(address, self.rpc_client.commitment())
+                    .await?
+                    .value
+                    .ok_or_else(|| SdkError::AccountNotFound(address.to_string()))?;
+                if let Some(cache) = &self.cache {
+                    cache.insert(*address, account.data.clone());
+                }
+                account.data
+            }
+        };
+        T::try_deserialize(&mut data.as_slice()).map_err(|e| SdkError::Serialization(e.to_string()))
+    }
+
+    /// Fetch and decode all accounts of `program` matching `filters`
+    pub async fn fetch_all<T: AccountDeserialize>(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, T)>> {
+        let accounts = self
+            .rpc_client
+            .get_program_accounts_with_config(program, program_accounts_config(filters))
+            .await?;
+
+        accounts
+            .into_iter()
+            .map(|(address, account)| {
+                let decoded = T::try_deserialize(&mut account.data.as_slice())
+                    .map_err(|e| SdkError::Serialization(e.to_string()))?;
+                if let Some(cache) = &self.cache {
+                    cache.insert(address, account.data);
+                }
+                Ok((address, decoded))
+            })
+            .collect()
+    }
+
+    /// Fetch all kernel sessions owned by `owner`
+    pub async fn fetch_all_sessions_for_owner(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Session)>> {
+        self.fetch_all(&valence_kernel::ID, session_owner_filters(owner))
+            .await
+    }
+
+    /// Fetch all kernel sessions on `shard`
+    pub async fn fetch_all_sessions_for_shard(
+        &self,
+        shard: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Session)>> {
+        self.fetch_all(&valence_kernel::ID, session_shard_filters(shard))
+            .await
+    }
+
+    /// Sign instructions with the payer and `signers`, then send and confirm them
+    pub async fn send_instructions(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&(dyn Signer + Sync)],
+    ) -> Result<Signature> {
+        let confirmation = self
+            .send_with_options(instructions, signers, &SendOptions::default())
+            .await?;
+        Ok(confirmation.signature)
+    }
+
+    /// Send instructions and wait for confirmation, resending after blockhash expiry
+    ///
+    /// Program failures surface as typed errors such as [`SdkError::Kernel`].
+    pub async fn send_with_options(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&(dyn Signer + Sync)],
+        options: &SendOptions,
+    ) -> Result<Confirmation> {
+        let deadline = Instant::now() + options.timeout;
+        let mut attempts = 0;
+
+        loop {
+            if Instant::now() >= deadline {
+                return Err(SdkError::Timeout);
+            }
+            attempts += 1;
+
+            let (recent_blockhash, last_valid_block_height) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(options.commitment)
+                .await?;
+            let transaction = self.sign(instructions, signers, recent_blockhash)?;
+            let fee = self
+                .rpc_client
+                .get_fee_for_message(&transaction.message)
+                .await?;
+
+            if !options.skip_preflight {
+                let simulation = self
+                    .rpc_client
+                    .simulate_transaction_with_config(
+                        &transaction,
+                        RpcSimulateTransactionConfig {
+                            commitment: Some(options.commitment),
+                            ..Default::default()
+                        },
+                    )
+                    .await?
+                    .value;
+                match simulation.err {
+                    Some(TransactionError::BlockhashNotFound)
+                        if attempts <= options.max_retries =>
+                    {
+                        continue;
+                    }
+                    Some(TransactionError::BlockhashNotFound) => {
+                        return Err(SdkError::BlockhashExpired { attempts });
+                    }
+                    Some(err) => {
+                        let logs = simulation.logs.unwrap_or_default();
+                        return Err(decode_transaction_error(&err, instructions, &logs));
+                    }
+                    None => {}
+                }
+            }
+
+            let signature = self
+                .rpc_client
+                .send_transaction_with_config(
+                    &transaction,
+                    RpcSendTransactionConfig {
+                        // Preflight already ran above when requested
+                        skip_preflight: true,
+                        preflight_commitment: Some(options.commitment.commitment),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+
+            // Poll until confirmed, failed, or the blockhash can no longer land
+            loop {
+                if Instant::now() >= deadline {
+                    return Err(SdkError::Timeout);
+                }
+
+                let status = self
+                    .rpc_client
+                    .get_signature_statuses(&[signature])
+                    .await?
+                    .value
+                    .pop()
+                    .flatten();
+                if let Some(status) = status {
+                    if let Some(err) = &status.err {
+                        return Err(decode_transaction_error(err, instructions, &[]));
+                    }
+                    if status.satisfies_commitment(options.commitment) {
+                        return Ok(Confirmation {
+                            signature,
+                            slot: status.slot,
+                            fee,
+                            attempts,
+                        });
+                    }
+                } else {
+                    let block_height = self
+                        .rpc_client
+                        .get_block_height_with_commitment(options.commitment)
+                        .await?;
+                    if block_height > last_valid_block_height {
+                        break;
+                    }
+                }
+                sleep(Duration::from_millis(500)).await;
+            }
+
+            if attempts > options.max_retries {
+                return Err(SdkError::BlockhashExpired { attempts });
+            }
+        }
+    }
+
+    /// Simulate instructions signed by the payer and `signers` without sending them
+    ///
+    /// A failing simulation is decoded like a failed transaction.
+    pub async fn simulate(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&(dyn Signer + Sync)],
+    ) -> Result<Simulation> {
+        let recent_blockhash = self.rpc_client.get_latest_blockhash().await?;
+        let transaction = self.sign(instructions, signers, recent_blockhash)?;
+        let simulation = self
+            .rpc_client
+            .simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    commitment: Some(self.rpc_client.commitment()),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .value;
+
+        let logs = simulation.logs.unwrap_or_default();
+        match simulation.err {
+            Some(err) => Err(decode_transaction_error(&err, instructions, &logs)),
+            None => Ok(Simulation {
+                units_consumed: simulation.units_consumed,
+                logs,
+            }),
+        }
+    }
+
+    /// Sign a transaction with the payer and `signers`
+    fn sign(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&(dyn Signer + Sync)],
+        recent_blockhash: Hash,
+    ) -> Result<Transaction> {
+        let payer: &dyn Signer = self.payer.as_ref();
+        let mut all_signers = vec![payer];
+        all_signers.extend(signers.iter().map(|signer| *signer as &dyn Signer));
+
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&self.payer()));
+        transaction
+            .try_sign(&all_signers, recent_blockhash)
+            .map_err(|e| SdkError::TransactionFailed(e.to_string()))?;
+        Ok(transaction)
+    }
+
+    /// Send the transactions of a signing plan in order
+    ///
+    /// Each transaction is signed by the payer and whichever of `signers` it requires.
+    pub async fn send_plan(
+        &self,
+        plan: &SigningPlan,
+        signers: &[&(dyn Signer + Sync)],
+    ) -> Result<Vec<Signature>> {
+        if plan.payer != self.payer() {
+            return Err(SdkError::Unauthorized);
+        }
+
+        let mut signatures = Vec::with_capacity(plan.transactions.len());
+        for planned in &plan.transactions {
+            let recent_blockhash = self.rpc_client.get_latest_blockhash().await?;
+            let transaction = {
+                let message = planned.message(&plan.payer, recent_blockhash)?;
+                let payer: &dyn Signer = self.payer.as_ref();
+                let mut required = vec![payer];
+                for key in &planned.signers[1..] {
+                    let signer = signers
+                        .iter()
+                        .find(|signer| signer.pubkey() == *key)
+                        .ok_or_else(|| {
+                            SdkError::TransactionFailed(format!("Missing signer {}", key))
+                        })?;
+                    required.push(*signer as &dyn Signer);
+                }
+                VersionedTransaction::try_new(message, &required)
+                    .map_err(|e| SdkError::TransactionFailed(e.to_string()))?
+            };
+
+            signatures.push(
+                self.rpc_client
+                    .send_and_confirm_transaction(&transaction)
+                    .await
+                    .map_err(|e| SdkError::TransactionFailed(e.to_string()))?,
+            );
+        }
+        Ok(signatures)
+    }
+
+    // Session operations build their instruction up front, so the returned
+    // futures do not borrow the builder or handle and stay `Send`.
+
+    /// Create a session's guard account
+    pub fn create_guard(
+        &self,
+        builder: &SessionBuilder<'_>,
+        guard_pubkey: Pubkey,
+        session_pubkey: Pubkey,
+    ) -> impl Future<Output = Result<Signature>> + Send + '_ {
+        let instruction = builder.create_guard_instruction(guard_pubkey, session_pubkey);
+        self.send_instruction(instruction)
+    }
+
+    /// Create a session account and its ALT
+    pub fn create_session(
+        &self,
+        builder: &SessionBuilder<'_>,
+        session_pubkey: Pubkey,
+        alt_pubkey: Pubkey,
+        guard_pubkey: Pubkey,
+        shard: Pubkey,
+    ) -> impl Future<Output = Result<Signature>> + Send + '_ {
+        let instruction =
+            builder.create_session_instruction(session_pubkey, alt_pubkey, guard_pubkey, shard);
+        self.send_instruction(instruction)
+    }
+
+    /// Execute a batch of operations, submitted by the payer
+    pub fn execute_batch(
+        &self,
+        session: &SessionHandle<'_>,
+        batch: OperationBatch,
+        guard_pubkey: Pubkey,
+        cpi_allowlist: Pubkey,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> impl Future<Output = Result<Signature>> + Send + '_ {
+        let instruction = session.execute_batch_instruction(
+            batch,
+            guard_pubkey,
+            cpi_allowlist,
+            self.payer(),
+            remaining_accounts,
+        );
+        self.send_instruction(instruction)
+    }
+
+    /// Invalidate a session
+    pub fn invalidate_session(
+        &self,
+        session: &SessionHandle<'_>,
+    ) -> impl Future<Output = Result<Signature>> + Send + '_ {
+        let instruction = session.invalidate_instruction();
+        self.send_instruction(instruction)
+    }
+
+    /// Add or remove a session's ALT registrations
+    pub fn manage_alt(
+        &self,
+        session: &SessionHandle<'_>,
+        add_borrowable: Vec<RegisteredAccount>,
+        add_programs: Vec<RegisteredProgram>,
+        remove_accounts: Vec<Pubkey>,
+    ) -> impl Future<Output = Result<Signature>> + Send + '_ {
+        let instruction =
+            session.manage_alt_instruction(add_borrowable, add_programs, remove_accounts);
+        self.send_instruction(instruction)
+    }
+
+    /// Builder for a kernel session owned by the payer
+    pub fn kernel_session_builder(
+        &self,
+        shard: Pubkey,
+        namespace_path: impl Into<String>,
+    ) -> KernelSessionBuilder {
+        KernelSessionBuilder::new(self.payer(), shard, namespace_path)
+    }
+
+    /// Create a kernel session's guard, session and ALT, then its remaining registrations
+    pub async fn create_kernel_session(&self, session: &KernelSession) -> Result<Vec<Signature>> {
+        if session.owner != self.payer() {
+            return Err(SdkError::Unauthorized);
+        }
+
+        let [guard, session_account, account_lookup] = session.new_account_signers();
+        let mut signatures = vec![
+            self.send_instructions(
+                &[session.create_guard.clone(), session.create_session.clone()],
+                &[guard, session_account, account_lookup],
+            )
+            .await?,
+        ];
+        for registration in &session.registrations {
+            signatures.push(
+                self.send_instructions(std::slice::from_ref(registration), &[])
+                    .await?,
+            );
+        }
+        Ok(signatures)
+    }
+
+    /// Create a mint with the payer as mint authority
+    pub async fn create_mint(&self, mint: &(dyn Signer + Sync), decimals: u8) -> Result<Signature> {
+        let rent_lamports = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(MINT_ACCOUNT_LEN)
+            .await?;
+        let payer = self.payer();
+        let instructions = crate::create_mint_instructions(
+            &payer,
+            &mint.pubkey(),
+            &payer,
+            None,
+            decimals,
+            rent_lamports,
+        )?;
+        self.send_instructions(&instructions, &[mint]).await
+    }
+
+    async fn send_instruction(&self, instruction: Result<Instruction>) -> Result<Signature> {
+        self.send_instructions(&[instruction?], &[]).await
+    }
+}
+
+impl FeePayer for ValenceClientAsync {
+    fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+}
@@ -8,6 +8,12 @@ use solana_sdk::{
 };
 use std::rc::Rc;
 
+/// Client that pays for and signs kernel instructions built by the SDK
+pub trait FeePayer {
+    /// Public key of the fee payer and default authority
+    fn payer(&self) -> Pubkey;
+}
+
 /// Valence client for interacting with the protocol
 pub struct ValenceClient {
     pub client: Client<Rc<Keypair>>,
@@ -53,4 +59,10 @@ impl ValenceClient {
             .account(*address)
             .map_err(|_| SdkError::AccountNotFound(address.to_string()))
     }
-}
\ No newline at end of file
+}
+
+impl FeePayer for ValenceClient {
+    fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+}
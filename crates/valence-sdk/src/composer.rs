@@ -0,0 +1,211 @@
+use crate::{Result, SdkError};
+use anchor_lang::prelude::*;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+};
+use std::collections::HashSet;
+
+/// Maximum number of accounts a transaction may lock
+pub const DEFAULT_MAX_TRANSACTION_ACCOUNTS: usize = 64;
+
+/// Instructions that must land in the same transaction
+#[derive(Debug, Clone)]
+pub struct ComposerStep {
+    pub label: String,
+    pub instructions: Vec<Instruction>,
+}
+
+/// One transaction of a [`SigningPlan`]
+#[derive(Debug, Clone)]
+pub struct PlannedTransaction {
+    /// Labels of the steps packed into this transaction, in order
+    pub steps: Vec<String>,
+    pub instructions: Vec<Instruction>,
+    /// Accounts that must sign, fee payer first
+    pub signers: Vec<Pubkey>,
+    /// Lookup tables the transaction resolves accounts through
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+    /// Serialized size with signatures, in bytes
+    pub size: usize,
+    /// Number of distinct accounts referenced
+    pub account_count: usize,
+}
+
+impl PlannedTransaction {
+    /// Compile the transaction message against a recent blockhash
+    pub fn message(&self, payer: &Pubkey, recent_blockhash: Hash) -> Result<VersionedMessage> {
+        compile(payer, &self.instructions, &self.lookup_tables, recent_blockhash)
+    }
+}
+
+/// Ordered transactions produced by [`TransactionComposer`]
+#[derive(Debug, Clone)]
+pub struct SigningPlan {
+    pub payer: Pubkey,
+    pub transactions: Vec<PlannedTransaction>,
+}
+
+/// Packs instruction steps into as few transactions as limits allow
+///
+/// Steps keep their order and are never split. Accounts shared between
+/// instructions are counted once, and when lookup tables are attached,
+/// accounts found in them are loaded by index instead of by address.
+pub struct TransactionComposer {
+    payer: Pubkey,
+    steps: Vec<ComposerStep>,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+    max_accounts: usize,
+    max_size: usize,
+}
+
+impl TransactionComposer {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            steps: Vec::new(),
+            lookup_tables: Vec::new(),
+            max_accounts: DEFAULT_MAX_TRANSACTION_ACCOUNTS,
+            max_size: PACKET_DATA_SIZE,
+        }
+    }
+
+    /// Add a step whose instructions must execute atomically
+    pub fn add(mut self, label: impl Into<String>, instructions: Vec<Instruction>) -> Self {
+        self.steps.push(ComposerStep {
+            label: label.into(),
+            instructions,
+        });
+        self
+    }
+
+    /// Add a single-instruction step
+    pub fn add_instruction(self, label: impl Into<String>, instruction: Instruction) -> Self {
+        self.add(label, vec![instruction])
+    }
+
+    /// Resolve accounts through address lookup tables, producing v0 transactions
+    pub fn with_lookup_tables(mut self, lookup_tables: Vec<AddressLookupTableAccount>) -> Self {
+        self.lookup_tables = lookup_tables;
+        self
+    }
+
+    /// Set the maximum distinct accounts per transaction
+    pub fn max_accounts(mut self, max_accounts: usize) -> Self {
+        self.max_accounts = max_accounts;
+        self
+    }
+
+    /// Set the maximum serialized transaction size
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Pack the steps into an ordered signing plan
+    pub fn compose(self) -> Result<SigningPlan> {
+        let mut transactions = Vec::new();
+        let mut current: Option<PlannedTransaction> = None;
+
+        for step in &self.steps {
+            if step.instructions.is_empty() {
+                continue;
+            }
+
+            if let Some(planned) = &current {
+                let mut instructions = planned.instructions.clone();
+                instructions.extend(step.instructions.iter().cloned());
+                if let Some(mut merged) = self.plan(instructions)? {
+                    merged.steps = planned.steps.clone();
+                    merged.steps.push(step.label.clone());
+                    current = Some(merged);
+                    continue;
+                }
+                transactions.extend(current.take());
+            }
+
+            let mut planned = self.plan(step.instructions.clone())?.ok_or_else(|| {
+                SdkError::InvalidOperation(format!(
+                    "Step {} exceeds transaction limits on its own",
+                    step.label
+                ))
+            })?;
+            planned.steps.push(step.label.clone());
+            current = Some(planned);
+        }
+        transactions.extend(current);
+
+        Ok(SigningPlan {
+            payer: self.payer,
+            transactions,
+        })
+    }
+
+    /// Plan a transaction of `instructions`, or `None` when it exceeds the limits
+    fn plan(&self, instructions: Vec<Instruction>) -> Result<Option<PlannedTransaction>> {
+        let mut accounts = HashSet::from([self.payer]);
+        for instruction in &instructions {
+            accounts.insert(instruction.program_id);
+            accounts.extend(instruction.accounts.iter().map(|meta| meta.pubkey));
+        }
+        if accounts.len() > self.max_accounts {
+            return Ok(None);
+        }
+
+        let message = compile(&self.payer, &instructions, &self.lookup_tables, Hash::default())?;
+        let signer_count = message.header().num_required_signatures as usize;
+        // Compact-u16 signature count, then the signatures, then the message
+        let size = 1 + signer_count * 64 + message.serialize().len();
+        if size > self.max_size {
+            return Ok(None);
+        }
+
+        let signers = message.static_account_keys()[..signer_count].to_vec();
+        let lookup_tables = match &message {
+            VersionedMessage::V0(message) => self
+                .lookup_tables
+                .iter()
+                .filter(|table| {
+                    message
+                        .address_table_lookups
+                        .iter()
+                        .any(|lookup| lookup.account_key == table.key)
+                })
+                .cloned()
+                .collect(),
+            VersionedMessage::Legacy(_) => Vec::new(),
+        };
+
+        Ok(Some(PlannedTransaction {
+            steps: Vec::new(),
+            instructions,
+            signers,
+            lookup_tables,
+            size,
+            account_count: accounts.len(),
+        }))
+    }
+}
+
+/// Compile a legacy message, or a v0 message when lookup tables are given
+fn compile(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage> {
+    if lookup_tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &recent_blockhash,
+        )));
+    }
+
+    v0::Message::try_compile(payer, instructions, lookup_tables, recent_blockhash)
+        .map(VersionedMessage::V0)
+        .map_err(|e| SdkError::InvalidOperation(e.to_string()))
+}
+
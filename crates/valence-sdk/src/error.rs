@@ -1,6 +1,8 @@
 use anchor_client::ClientError;
 use anchor_lang::prelude::*;
 use thiserror::Error;
+use valence_functions::states::FunctionsError;
+use valence_kernel::errors::KernelError;
 
 #[derive(Error, Debug)]
 pub enum SdkError {
@@ -61,10 +63,47 @@ pub enum SdkError {
     
     #[error("Capability exhausted: no uses remaining")]
     CapabilityExhausted,
+
+    #[error("Kernel error: {0}")]
+    Kernel(KernelError),
+
+    #[error("Functions error: {0}")]
+    Functions(FunctionsError),
+
+    #[error("Program {program} failed with custom error {code}")]
+    ProgramFailed { program: Pubkey, code: u32 },
+
+    #[error(
+        "Registered function {} failed at batch operation {}: {source}",
+        failure.registry_id,
+        failure.operation_index
+    )]
+    FunctionFailed {
+        failure: crate::events::BatchFailed,
+        source: Box<SdkError>,
+    },
+
+    #[error("Blockhash expired before confirmation after {attempts} attempts")]
+    BlockhashExpired { attempts: u32 },
+
+    #[error("Confirmation timed out")]
+    Timeout,
+
+    #[error("IDL error: {0}")]
+    Idl(String),
+
+    #[error("Invalid argument {path}: {reason}")]
+    InvalidArgument { path: String, reason: String },
 }
 
 impl From<ClientError> for SdkError {
     fn from(err: ClientError) -> Self {
         Self::AnchorClient(Box::new(err))
     }
-}
\ No newline at end of file
+}
+
+impl From<solana_client::client_error::ClientError> for SdkError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        Self::SolanaClient(err.to_string())
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{Result, SdkError};
+use anchor_lang::{prelude::*, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::StreamExt;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::InstructionError, signature::Signature,
+    transaction::TransactionError,
+};
+use std::str::FromStr;
+use tokio::{sync::mpsc, task::JoinHandle};
+use valence_common::events::{
+    AuthorizationUsed, BatchExecuted, BatchInvalidated, CascadeInvalidationRequired,
+    ExecutionCallback, FunctionInvoked, SessionCreated, SessionInvalidated,
+};
+
+/// Typed Anchor event from the canonical Valence event schema
+#[derive(Debug, Clone)]
+pub enum ValenceEvent {
+    SessionCreated(SessionCreated),
+    BatchExecuted(BatchExecuted),
+    FunctionInvoked(FunctionInvoked),
+    ExecutionCallback(ExecutionCallback),
+    AuthorizationUsed(AuthorizationUsed),
+    SessionInvalidated(SessionInvalidated),
+    CascadeInvalidationRequired(CascadeInvalidationRequired),
+    BatchInvalidated(BatchInvalidated),
+}
+
+impl ValenceEvent {
+    /// Decode an event from its discriminator-prefixed data
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        fn event<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+            let mut body = data.strip_prefix(T::DISCRIMINATOR)?;
+            T::deserialize(&mut body).ok()
+        }
+
+        event(data)
+            .map(Self::SessionCreated)
+            .or_else(|| event(data).map(Self::BatchExecuted))
+            .or_else(|| event(data).map(Self::FunctionInvoked))
+            .or_else(|| event(data).map(Self::ExecutionCallback))
+            .or_else(|| event(data).map(Self::AuthorizationUsed))
+            .or_else(|| event(data).map(Self::SessionInvalidated))
+            .or_else(|| event(data).map(Self::CascadeInvalidationRequired))
+            .or_else(|| event(data).map(Self::BatchInvalidated))
+    }
+
+    /// Session the event concerns
+    pub fn session(&self) -> Pubkey {
+        match self {
+            Self::SessionCreated(event) => event.session,
+            Self::BatchExecuted(event) => event.session,
+            Self::FunctionInvoked(event) => event.session,
+            Self::ExecutionCallback(event) => event.session,
+            Self::AuthorizationUsed(event) => event.session,
+            Self::SessionInvalidated(event) => event.session,
+            Self::CascadeInvalidationRequired(event) => event.parent_session,
+            Self::BatchInvalidated(event) => event.parent,
+        }
+    }
+}
+
+/// Event with the transaction that emitted it
+#[derive(Debug, Clone)]
+pub struct DecodedEvent {
+    /// Transaction signature, correlating events from one execution
+    pub signature: Signature,
+    pub slot: u64,
+    pub event: ValenceEvent,
+}
+
+/// Decode kernel events from a transaction's logs
+///
+/// Only `Program data:` lines emitted while the kernel is the executing
+/// program are decoded, so other programs' events are not misread.
+pub fn decode_logs(logs: &[String]) -> Vec<ValenceEvent> {
+    let kernel = valence_kernel::ID.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if invocations.last() == Some(&kernel.as_str()) {
+                events.extend(
+                    STANDARD
+                        .decode(data)
+                        .ok()
+                        .and_then(|data| ValenceEvent::decode(&data)),
+                );
+            }
+        } else if let Some((program, outcome)) = rest.split_once(' ') {
+            if outcome.starts_with("invoke [") {
+                invocations.push(program);
+            } else if outcome == "success" || outcome.starts_with("failed") {
+                invocations.pop();
+            }
+        }
+    }
+    events
+}
+
+/// Registered function whose failure aborted a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchFailed {
+    pub session: Pubkey,
+    /// Index of the failing operation within the batch
+    pub operation_index: u8,
+    pub registry_id: u64,
+    pub program_id: Pubkey,
+    /// Error the function returned
+    pub error: InstructionError,
+}
+
+impl BatchFailed {
+    /// Error code of the function's error enum, for custom program errors
+    pub fn error_code(&self) -> Option<u32> {
+        match self.error {
+            InstructionError::Custom(code) => Some(code),
+            _ => None,
+        }
+    }
+}
+
+/// Attribute a failed batch transaction to the registered function that failed
+///
+/// A failing CPI aborts the transaction before the kernel regains control,
+/// so the failure is reconstructed from its logs: the last `FunctionInvoked`
+/// event whose function did not return successfully identifies the call.
+/// Returns `None` when the failure did not come from a registered function.
+pub fn decode_batch_failure(logs: &[String], error: &TransactionError) -> Option<BatchFailed> {
+    let TransactionError::InstructionError(_, error) = error else {
+        return None;
+    };
+    let kernel = valence_kernel::ID.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut pending: Option<FunctionInvoked> = None;
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if invocations.last() == Some(&kernel.as_str()) {
+                let event = STANDARD
+                    .decode(data)
+                    .ok()
+                    .and_then(|data| ValenceEvent::decode(&data));
+                if let Some(ValenceEvent::FunctionInvoked(event)) = event {
+                    pending = Some(event);
+                }
+            }
+        } else if let Some((program, outcome)) = rest.split_once(' ') {
+            if outcome.starts_with("invoke [") {
+                invocations.push(program);
+            } else if outcome == "success" || outcome.starts_with("failed") {
+                invocations.pop();
+                let returned = outcome == "success"
+                    && invocations.last() == Some(&kernel.as_str())
+                    && pending
+                        .as_ref()
+                        .is_some_and(|event| event.program_id.to_string() == program);
+                if returned {
+                    pending = None;
+                }
+            }
+        }
+    }
+
+    pending.map(|event| BatchFailed {
+        session: event.session,
+        operation_index: event.operation_index,
+        registry_id: event.registry_id,
+        program_id: event.program_id,
+        error: error.clone(),
+    })
+}
+
+/// Live subscription to kernel events
+pub struct ValenceEvents {
+    receiver: mpsc::UnboundedReceiver<DecodedEvent>,
+    task: JoinHandle<()>,
+}
+
+impl ValenceEvents {
+    /// Subscribe over websocket to logs of transactions mentioning the kernel
+    ///
+    /// Failed transactions are skipped, as their events were rolled back.
+    pub async fn subscribe(ws_url: &str, commitment: CommitmentConfig) -> Result<Self> {
+        let client = PubsubClient::new(ws_url)
+            .await
+            .map_err(|e| SdkError::SolanaClient(e.to_string()))?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+
+        let task = tokio::spawn(async move {
+            let subscription = client
+                .logs_subscribe(
+                    RpcTransactionLogsFilter::Mentions(vec![valence_kernel::ID.to_string()]),
+                    RpcTransactionLogsConfig {
+                        commitment: Some(commitment),
+                    },
+                )
+                .await;
+            let (mut stream, unsubscribe) = match subscription {
+                Ok(subscription) => {
+                    let _ = ready_tx.send(Ok(()));
+                    subscription
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(SdkError::SolanaClient(e.to_string())));
+                    return;
+                }
+            };
+
+            while let Some(response) = stream.next().await {
+                let logs = response.value;
+                if logs.err.is_some() {
+                    continue;
+                }
+                let Ok(signature) = Signature::from_str(&logs.signature) else {
+                    continue;
+                };
+                for event in decode_logs(&logs.logs) {
+                    let decoded = DecodedEvent {
+                        signature,
+                        slot: response.context.slot,
+                        event,
+                    };
+                    if sender.send(decoded).is_err() {
+                        unsubscribe().await;
+                        return;
+                    }
+                }
+            }
+        });
+
+        ready_rx
+            .await
+            .map_err(|_| SdkError::SolanaClient("Log subscription task ended".to_string()))??;
+        Ok(Self { receiver, task })
+    }
+
+    /// Next event, or `None` once the subscription closes
+    pub async fn next(&mut self) -> Option<DecodedEvent> {
+        self.receiver.recv().await
+    }
+
+    /// Next event concerning `session`
+    pub async fn next_for_session(&mut self, session: &Pubkey) -> Option<DecodedEvent> {
+        while let Some(decoded) = self.next().await {
+            if decoded.event.session() == *session {
+                return Some(decoded);
+            }
+        }
+        None
+    }
+}
+
+impl Drop for ValenceEvents {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoked_log(session: Pubkey, operation_index: u8, program_id: Pubkey) -> String {
+        let event = FunctionInvoked {
+            session,
+            operation_index,
+            registry_id: 1005,
+            program_id,
+        };
+        let mut data = FunctionInvoked::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("Program data: {}", STANDARD.encode(data))
+    }
+
+    #[test]
+    fn test_decode_batch_failure() {
+        let kernel = valence_kernel::ID;
+        let function = Pubkey::new_unique();
+        let session = Pubkey::new_unique();
+        let error = TransactionError::InstructionError(0, InstructionError::Custom(6002));
+
+        let logs = vec![
+            format!("Program {kernel} invoke [1]"),
+            invoked_log(session, 0, function),
+            format!("Program {function} invoke [2]"),
+            format!("Program {function} success"),
+            invoked_log(session, 2, function),
+            format!("Program {function} invoke [2]"),
+            format!("Program {function} failed: custom program error: 0x1772"),
+            format!("Program {kernel} failed: custom program error: 0x1772"),
+        ];
+        let failure = decode_batch_failure(&logs, &error).unwrap();
+        assert_eq!(failure.operation_index, 2);
+        assert_eq!(failure.program_id, function);
+        assert_eq!(failure.error_code(), Some(6002));
+
+        // The function returned; the kernel failed afterwards on its own
+        let logs = vec![
+            format!("Program {kernel} invoke [1]"),
+            invoked_log(session, 0, function),
+            format!("Program {function} invoke [2]"),
+            format!("Program {function} success"),
+            format!("Program {kernel} failed: custom program error: 0x1772"),
+        ];
+        assert!(decode_batch_failure(&logs, &error).is_none());
+    }
+}
@@ -0,0 +1,100 @@
+use anchor_lang::{prelude::*, Discriminator};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use valence_kernel::{state::Session, MAX_NAMESPACE_PATH_LEN};
+
+/// Offset of `Session::owner`: discriminator, namespace path, guard and lookup table
+pub const SESSION_OWNER_OFFSET: usize = 8 + MAX_NAMESPACE_PATH_LEN + 2 + 32 + 32;
+
+/// Offset of `Session::shard`, directly after the owner
+pub const SESSION_SHARD_OFFSET: usize = SESSION_OWNER_OFFSET + 32;
+
+/// Account data cached for a fixed time to live
+pub struct AccountCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Pubkey, (Instant, Vec<u8>)>>,
+}
+
+impl AccountCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached data for `address`, if fetched within the time to live
+    pub fn get(&self, address: &Pubkey) -> Option<Vec<u8>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(address) {
+            Some((fetched_at, data)) if fetched_at.elapsed() < self.ttl => Some(data.clone()),
+            Some(_) => {
+                entries.remove(address);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, address: Pubkey, data: Vec<u8>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(address, (Instant::now(), data));
+    }
+
+    /// Drop the cached data for `address`, e.g. after writing to it
+    pub fn invalidate(&self, address: &Pubkey) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(address);
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clear();
+    }
+}
+
+/// Filter matching accounts of Anchor account type `T`
+pub fn discriminator_filter<T: Discriminator>() -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, T::DISCRIMINATOR.to_vec()))
+}
+
+/// Filter matching a public key stored at `offset`
+pub fn pubkey_filter(offset: usize, key: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, key.to_bytes().to_vec()))
+}
+
+/// Filters matching sessions owned by `owner`
+pub fn session_owner_filters(owner: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        discriminator_filter::<Session>(),
+        pubkey_filter(SESSION_OWNER_OFFSET, owner),
+    ]
+}
+
+/// Filters matching sessions on `shard`
+pub fn session_shard_filters(shard: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        discriminator_filter::<Session>(),
+        pubkey_filter(SESSION_SHARD_OFFSET, shard),
+    ]
+}
+
+/// getProgramAccounts configuration with base64 data and `filters`
+pub fn program_accounts_config(filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
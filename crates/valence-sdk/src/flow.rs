@@ -0,0 +1,106 @@
+use crate::{KernelSession, Result, SdkError, SigningPlan, TransactionComposer};
+use anchor_lang::prelude::*;
+use solana_sdk::{instruction::Instruction, message::AddressLookupTableAccount};
+use valence_kernel::OperationBatch;
+
+pub use valence_common::pdas::CPI_ALLOWLIST_SEED;
+
+/// Address of the kernel's CPI allowlist
+pub fn cpi_allowlist_address() -> Pubkey {
+    valence_common::pdas::cpi_allowlist(&valence_kernel::ID).0
+}
+
+/// Declarative pipeline of steps run against one kernel session
+///
+/// Steps execute in the order they are added. Session setup, batches and
+/// custom instructions are packed into as few transactions as the limits
+/// allow, with the submitter paying fees.
+pub struct FlowBuilder<'a> {
+    session: &'a KernelSession,
+    cpi_allowlist: Pubkey,
+    composer: TransactionComposer,
+    submitter: Pubkey,
+    /// First invalid batch, reported by `build`
+    error: Option<SdkError>,
+}
+
+impl<'a> FlowBuilder<'a> {
+    /// Create a flow for `session` whose transactions `submitter` pays for
+    pub fn new(session: &'a KernelSession, submitter: Pubkey) -> Self {
+        Self {
+            session,
+            cpi_allowlist: cpi_allowlist_address(),
+            composer: TransactionComposer::new(submitter),
+            submitter,
+            error: None,
+        }
+    }
+
+    /// Use a CPI allowlist other than the kernel's default PDA
+    pub fn cpi_allowlist(mut self, cpi_allowlist: Pubkey) -> Self {
+        self.cpi_allowlist = cpi_allowlist;
+        self
+    }
+
+    /// Create the session's guard, session and ALT before the other steps
+    pub fn create_session(mut self) -> Self {
+        self.composer = self.composer.add(
+            "create_session",
+            vec![
+                self.session.create_guard.clone(),
+                self.session.create_session.clone(),
+            ],
+        );
+        for (index, registration) in self.session.registrations.iter().enumerate() {
+            self.composer = self
+                .composer
+                .add_instruction(format!("register_{}", index), registration.clone());
+        }
+        self
+    }
+
+    /// Execute an operation batch in the session
+    pub fn batch(
+        mut self,
+        label: impl Into<String>,
+        batch: OperationBatch,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Self {
+        let label = label.into();
+        if let Err(e) = batch.validate() {
+            self.error.get_or_insert(SdkError::InvalidOperation(format!(
+                "Batch {}: {}",
+                label, e
+            )));
+        }
+
+        let instruction = self.session.execute_batch_instruction(
+            batch,
+            self.cpi_allowlist,
+            self.submitter,
+            remaining_accounts,
+        );
+        self.composer = self.composer.add_instruction(label, instruction);
+        self
+    }
+
+    /// Add instructions that must land atomically, e.g. for other programs
+    pub fn step(mut self, label: impl Into<String>, instructions: Vec<Instruction>) -> Self {
+        self.composer = self.composer.add(label, instructions);
+        self
+    }
+
+    /// Resolve accounts through address lookup tables
+    pub fn with_lookup_tables(mut self, lookup_tables: Vec<AddressLookupTableAccount>) -> Self {
+        self.composer = self.composer.with_lookup_tables(lookup_tables);
+        self
+    }
+
+    /// Validate the batches and pack the steps into a signing plan
+    pub fn build(self) -> Result<SigningPlan> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.composer.compose()
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{Result, SdkError};
+use anchor_lang::prelude::*;
+use std::ops::Not;
+
+/// Maximum operations in a compiled guard
+pub const MAX_GUARD_OPS: usize = 64;
+
+/// Maximum keys referenced by a compiled guard's allowlists
+pub const MAX_GUARD_KEYS: usize = 32;
+
+/// Maximum evaluation stack depth of a compiled guard
+pub const MAX_GUARD_STACK_DEPTH: usize = 16;
+
+/// Authorization condition built from composable predicates
+///
+/// ```ignore
+/// let guard = GuardExpr::allowlist([alice, bob])
+///     .and(GuardExpr::time_window(start, end))
+///     .or(GuardExpr::session_owner());
+/// let compiled = guard.compile()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardExpr {
+    Always,
+    Never,
+    /// Caller is one of the listed keys
+    Allowlist(Vec<Pubkey>),
+    /// Clock is within `[start, end)`, as unix timestamps
+    TimeWindow {
+        start: i64,
+        end: i64,
+    },
+    /// Caller owns the session
+    SessionOwner,
+    /// Session has been used fewer than this many times
+    MaxUses(u64),
+    And(Box<GuardExpr>, Box<GuardExpr>),
+    Or(Box<GuardExpr>, Box<GuardExpr>),
+    Not(Box<GuardExpr>),
+}
+
+impl GuardExpr {
+    pub fn allowlist(keys: impl IntoIterator<Item = Pubkey>) -> Self {
+        Self::Allowlist(keys.into_iter().collect())
+    }
+
+    pub fn time_window(start: i64, end: i64) -> Self {
+        Self::TimeWindow { start, end }
+    }
+
+    pub fn session_owner() -> Self {
+        Self::SessionOwner
+    }
+
+    pub fn max_uses(uses: u64) -> Self {
+        Self::MaxUses(uses)
+    }
+
+    pub fn and(self, other: GuardExpr) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: GuardExpr) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Evaluate the expression directly, without compiling it
+    pub fn evaluate(&self, context: &GuardContext) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Allowlist(keys) => keys.contains(&context.caller),
+            Self::TimeWindow { start, end } => (*start..*end).contains(&context.unix_timestamp),
+            Self::SessionOwner => context.caller == context.session_owner,
+            Self::MaxUses(uses) => context.usage_count < *uses,
+            Self::And(left, right) => left.evaluate(context) && right.evaluate(context),
+            Self::Or(left, right) => left.evaluate(context) || right.evaluate(context),
+            Self::Not(inner) => !inner.evaluate(context),
+        }
+    }
+
+    /// Compile into postfix operations, checking the guard limits
+    pub fn compile(&self) -> Result<CompiledGuard> {
+        let mut compiled = CompiledGuard::default();
+        self.emit(&mut compiled)?;
+        compiled.validate()?;
+        Ok(compiled)
+    }
+
+    fn emit(&self, compiled: &mut CompiledGuard) -> Result<()> {
+        let op = match self {
+            Self::Always => GuardOp::Always,
+            Self::Never => GuardOp::Never,
+            Self::Allowlist(keys) => {
+                let offset = compiled.keys.len();
+                if offset + keys.len() > MAX_GUARD_KEYS {
+                    return Err(SdkError::InvalidOperation(format!(
+                        "Guard references more than {} keys",
+                        MAX_GUARD_KEYS
+                    )));
+                }
+                compiled.keys.extend_from_slice(keys);
+                GuardOp::CallerIn {
+                    offset: offset as u8,
+                    len: keys.len() as u8,
+                }
+            }
+            Self::TimeWindow { start, end } => GuardOp::TimeWindow {
+                start: *start,
+                end: *end,
+            },
+            Self::SessionOwner => GuardOp::SessionOwner,
+            Self::MaxUses(uses) => GuardOp::MaxUses(*uses),
+            Self::And(left, right) => {
+                left.emit(compiled)?;
+                right.emit(compiled)?;
+                GuardOp::And
+            }
+            Self::Or(left, right) => {
+                left.emit(compiled)?;
+                right.emit(compiled)?;
+                GuardOp::Or
+            }
+            Self::Not(inner) => {
+                inner.emit(compiled)?;
+                GuardOp::Not
+            }
+        };
+        compiled.ops.push(op);
+        Ok(())
+    }
+}
+
+impl Not for GuardExpr {
+    type Output = GuardExpr;
+
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
+}
+
+/// State a guard is evaluated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuardContext {
+    pub caller: Pubkey,
+    pub session_owner: Pubkey,
+    pub unix_timestamp: i64,
+    pub usage_count: u64,
+}
+
+/// Single postfix guard operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum GuardOp {
+    Always,
+    Never,
+    /// Caller is one of `keys[offset..offset + len]`
+    CallerIn {
+        offset: u8,
+        len: u8,
+    },
+    TimeWindow {
+        start: i64,
+        end: i64,
+    },
+    SessionOwner,
+    MaxUses(u64),
+    And,
+    Or,
+    Not,
+}
+
+/// Guard compiled into postfix operations over a shared key table
+#[derive(Debug, Clone, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct CompiledGuard {
+    pub keys: Vec<Pubkey>,
+    pub ops: Vec<GuardOp>,
+}
+
+impl CompiledGuard {
+    /// Check limits, key ranges and that the operations leave one result
+    pub fn validate(&self) -> Result<()> {
+        if self.ops.len() > MAX_GUARD_OPS {
+            return Err(SdkError::InvalidOperation(format!(
+                "Guard has more than {} operations",
+                MAX_GUARD_OPS
+            )));
+        }
+        if self.keys.len() > MAX_GUARD_KEYS {
+            return Err(SdkError::InvalidOperation(format!(
+                "Guard references more than {} keys",
+                MAX_GUARD_KEYS
+            )));
+        }
+
+        let mut depth = 0usize;
+        for op in &self.ops {
+            let (pops, pushes) = match op {
+                GuardOp::CallerIn { offset, len } => {
+                    if *offset as usize + *len as usize > self.keys.len() {
+                        return Err(SdkError::InvalidOperation(
+                            "Guard allowlist out of key table range".to_string(),
+                        ));
+                    }
+                    (0, 1)
+                }
+                GuardOp::And | GuardOp::Or => (2, 1),
+                GuardOp::Not => (1, 1),
+                _ => (0, 1),
+            };
+            depth = depth.checked_sub(pops).ok_or_else(|| {
+                SdkError::InvalidOperation("Guard operation is missing operands".to_string())
+            })? + pushes;
+            if depth > MAX_GUARD_STACK_DEPTH {
+                return Err(SdkError::InvalidOperation(format!(
+                    "Guard exceeds stack depth {}",
+                    MAX_GUARD_STACK_DEPTH
+                )));
+            }
+        }
+        if depth != 1 {
+            return Err(SdkError::InvalidOperation(
+                "Guard must produce exactly one result".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Evaluate the operations with a bounded stack
+    pub fn evaluate(&self, context: &GuardContext) -> Result<bool> {
+        self.validate()?;
+
+        let mut stack = Vec::with_capacity(MAX_GUARD_STACK_DEPTH);
+        for op in &self.ops {
+            let value = match *op {
+                GuardOp::Always => true,
+                GuardOp::Never => false,
+                GuardOp::CallerIn { offset, len } => {
+                    let start = offset as usize;
+                    self.keys[start..start + len as usize].contains(&context.caller)
+                }
+                GuardOp::TimeWindow { start, end } => {
+                    (start..end).contains(&context.unix_timestamp)
+                }
+                GuardOp::SessionOwner => context.caller == context.session_owner,
+                GuardOp::MaxUses(uses) => context.usage_count < uses,
+                GuardOp::And | GuardOp::Or => {
+                    // validate() guarantees the operands are present
+                    let right = stack.pop().unwrap_or_default();
+                    let left = stack.pop().unwrap_or_default();
+                    if *op == GuardOp::And {
+                        left && right
+                    } else {
+                        left || right
+                    }
+                }
+                GuardOp::Not => !stack.pop().unwrap_or_default(),
+            };
+            stack.push(value);
+        }
+        Ok(stack.pop().unwrap_or_default())
+    }
+
+    /// Rebuild the expression the operations were compiled from
+    pub fn decompile(&self) -> Result<GuardExpr> {
+        self.validate()?;
+
+        let mut stack: Vec<GuardExpr> = Vec::new();
+        for op in &self.ops {
+            let expr = match *op {
+                GuardOp::Always => GuardExpr::Always,
+                GuardOp::Never => GuardExpr::Never,
+                GuardOp::CallerIn { offset, len } => {
+                    let start = offset as usize;
+                    GuardExpr::Allowlist(self.keys[start..start + len as usize].to_vec())
+                }
+                GuardOp::TimeWindow { start, end } => GuardExpr::TimeWindow { start, end },
+                GuardOp::SessionOwner => GuardExpr::SessionOwner,
+                GuardOp::MaxUses(uses) => GuardExpr::MaxUses(uses),
+                GuardOp::And | GuardOp::Or | GuardOp::Not => {
+                    let right = stack.pop().unwrap_or(GuardExpr::Never);
+                    match op {
+                        GuardOp::Not => !right,
+                        GuardOp::And => stack.pop().unwrap_or(GuardExpr::Never).and(right),
+                        _ => stack.pop().unwrap_or(GuardExpr::Never).or(right),
+                    }
+                }
+            };
+            stack.push(expr);
+        }
+        Ok(stack.pop().unwrap_or(GuardExpr::Never))
+    }
+
+    /// Serialize for storage in a guard account
+    pub fn to_serialized(&self) -> Result<SerializedGuard> {
+        self.try_to_vec()
+            .map(SerializedGuard)
+            .map_err(|e| SdkError::Serialization(e.to_string()))
+    }
+}
+
+/// Borsh-encoded [`CompiledGuard`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializedGuard(pub Vec<u8>);
+
+impl SerializedGuard {
+    /// Decode and validate the compiled guard
+    pub fn decode(&self) -> Result<CompiledGuard> {
+        let compiled = CompiledGuard::try_from_slice(&self.0)
+            .map_err(|e| SdkError::Serialization(e.to_string()))?;
+        compiled.validate()?;
+        Ok(compiled)
+    }
+}
+
+/// Compile a guard expression into its serialized form
+pub fn compile_guard(guard: &GuardExpr) -> Result<SerializedGuard> {
+    guard.compile()?.to_serialized()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contexts(keys: &[Pubkey]) -> Vec<GuardContext> {
+        let mut contexts = Vec::new();
+        for caller in keys {
+            for session_owner in keys {
+                for unix_timestamp in [-1, 0, 50, 99, 100, 1_000] {
+                    for usage_count in [0, 1, 5, 10] {
+                        contexts.push(GuardContext {
+                            caller: *caller,
+                            session_owner: *session_owner,
+                            unix_timestamp,
+                            usage_count,
+                        });
+                    }
+                }
+            }
+        }
+        contexts
+    }
+
+    #[test]
+    fn test_compiled_guard_round_trip() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let guards = [
+            GuardExpr::Always,
+            !GuardExpr::Never,
+            GuardExpr::allowlist([keys[0], keys[1]]),
+            GuardExpr::allowlist([keys[0]])
+                .and(GuardExpr::time_window(0, 100))
+                .or(GuardExpr::session_owner()),
+            !(GuardExpr::max_uses(5).and(GuardExpr::allowlist([keys[2]])))
+                .or(GuardExpr::allowlist([]).and(GuardExpr::time_window(100, 0))),
+        ];
+
+        for guard in &guards {
+            let serialized = compile_guard(guard).unwrap();
+            let compiled = serialized.decode().unwrap();
+            assert_eq!(compiled.decompile().unwrap(), *guard);
+            for context in contexts(&keys) {
+                assert_eq!(
+                    compiled.evaluate(&context).unwrap(),
+                    guard.evaluate(&context)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_guard_limits() {
+        let deep =
+            (1..MAX_GUARD_STACK_DEPTH).fold(GuardExpr::Always, |expr, _| GuardExpr::Never.or(expr));
+        assert!(deep.compile().is_ok());
+        assert!(GuardExpr::Never.or(deep).compile().is_err());
+
+        let keys: Vec<Pubkey> = (0..=MAX_GUARD_KEYS).map(|_| Pubkey::new_unique()).collect();
+        assert!(GuardExpr::allowlist(keys).compile().is_err());
+
+        let malformed = CompiledGuard {
+            keys: Vec::new(),
+            ops: vec![GuardOp::Always, GuardOp::And],
+        };
+        assert!(malformed
+            .evaluate(&contexts(&[Pubkey::new_unique()])[0])
+            .is_err());
+    }
+}
@@ -0,0 +1,692 @@
+//! Instructions for programs the SDK only knows through their Anchor IDL
+//!
+//! Shards deployed by users have no compile-time bindings in this crate. An
+//! [`IdlProgram`] loads their IDL from a file or from the program's on-chain
+//! IDL account and builds instructions from JSON arguments, validating each
+//! argument against its declared type before Borsh-encoding it.
+
+use crate::{client::ValenceClient, Result, SdkError};
+use anchor_lang::{idl::IdlAccount, prelude::*};
+use anchor_lang_idl::types::{
+    Idl, IdlArrayLen, IdlDefinedFields, IdlInstruction, IdlInstructionAccount,
+    IdlInstructionAccountItem, IdlSeed, IdlSerialization, IdlType, IdlTypeDef, IdlTypeDefTy,
+};
+use serde_json::Value;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Offset of the compressed IDL in an IDL account: discriminator, authority and length
+const IDL_ACCOUNT_DATA_OFFSET: usize = 8 + 32 + 4;
+
+/// Where to load an IDL from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlSource {
+    /// JSON file produced by `anchor build`
+    Path(PathBuf),
+    /// IDL account published on chain for the program
+    Chain(Pubkey),
+}
+
+impl From<PathBuf> for IdlSource {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for IdlSource {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<Pubkey> for IdlSource {
+    fn from(program_id: Pubkey) -> Self {
+        Self::Chain(program_id)
+    }
+}
+
+/// A program ID loads from chain; anything else is a file path
+impl From<&str> for IdlSource {
+    fn from(path_or_chain: &str) -> Self {
+        match Pubkey::from_str(path_or_chain) {
+            Ok(program_id) => Self::Chain(program_id),
+            Err(_) => Self::Path(PathBuf::from(path_or_chain)),
+        }
+    }
+}
+
+impl ValenceClient {
+    /// Load a program's IDL to build its instructions dynamically
+    pub fn load_idl(&self, source: impl Into<IdlSource>) -> Result<IdlProgram> {
+        match source.into() {
+            IdlSource::Path(path) => IdlProgram::from_file(&path),
+            IdlSource::Chain(program_id) => {
+                let data = self
+                    .valence_kernel
+                    .rpc()
+                    .get_account_data(&IdlAccount::address(&program_id))
+                    .map_err(|_| {
+                        SdkError::Idl(format!("no IDL account published for {}", program_id))
+                    })?;
+                IdlProgram::from_idl_account(program_id, &data)
+            }
+        }
+    }
+}
+
+/// A program's IDL, for building its instructions at runtime
+#[derive(Debug, Clone)]
+pub struct IdlProgram {
+    program_id: Pubkey,
+    idl: Idl,
+}
+
+impl IdlProgram {
+    pub fn new(idl: Idl) -> Result<Self> {
+        let program_id = Pubkey::from_str(&idl.address)
+            .map_err(|_| SdkError::Idl(format!("invalid program address {}", idl.address)))?;
+        Ok(Self { program_id, idl })
+    }
+
+    /// Parse an IDL, including the legacy (pre Anchor 0.30) format
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        Self::parse(json, None)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read(path)
+            .map_err(|e| SdkError::Idl(format!("reading {}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Decode the data of a program's on-chain IDL account
+    pub fn from_idl_account(program_id: Pubkey, data: &[u8]) -> Result<Self> {
+        let header = data
+            .get(..IDL_ACCOUNT_DATA_OFFSET)
+            .ok_or_else(|| SdkError::Idl("IDL account too short".to_string()))?;
+        let len = u32::from_le_bytes(header[40..44].try_into().expect("4 bytes")) as usize;
+        let compressed = data
+            .get(IDL_ACCOUNT_DATA_OFFSET..IDL_ACCOUNT_DATA_OFFSET + len)
+            .ok_or_else(|| SdkError::Idl("IDL account data truncated".to_string()))?;
+
+        let mut json = Vec::new();
+        flate2::read::ZlibDecoder::new(compressed)
+            .read_to_end(&mut json)
+            .map_err(|e| SdkError::Idl(format!("decompressing IDL: {}", e)))?;
+        Self::parse(&json, Some(program_id))
+    }
+
+    /// Parse an IDL, filling in the address of a legacy IDL that omits it
+    fn parse(json: &[u8], program_id: Option<Pubkey>) -> Result<Self> {
+        let mut value: Value = serde_json::from_slice(json)
+            .map_err(|e| SdkError::Idl(format!("invalid IDL JSON: {}", e)))?;
+        if let (Some(program_id), Some(idl)) = (program_id, value.as_object_mut()) {
+            if !idl.contains_key("address") {
+                let metadata = idl
+                    .entry("metadata")
+                    .or_insert_with(|| Value::Object(Default::default()));
+                if let Some(metadata) = metadata.as_object_mut() {
+                    metadata
+                        .entry("address")
+                        .or_insert_with(|| Value::String(program_id.to_string()));
+                }
+            }
+        }
+
+        let json = serde_json::to_vec(&value).map_err(|e| SdkError::Idl(e.to_string()))?;
+        let idl = anchor_lang_idl::convert::convert_idl(&json)
+            .map_err(|e| SdkError::Idl(format!("unsupported IDL: {}", e)))?;
+        Self::new(idl)
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn idl(&self) -> &Idl {
+        &self.idl
+    }
+
+    pub fn instruction(&self, name: &str) -> Result<&IdlInstruction> {
+        self.idl
+            .instructions
+            .iter()
+            .find(|instruction| instruction.name == name)
+            .ok_or_else(|| {
+                SdkError::Idl(format!("{} has no instruction {}", self.idl.metadata.name, name))
+            })
+    }
+
+    /// Build an instruction from named accounts and a JSON object of arguments
+    ///
+    /// Accounts with a fixed address or a PDA derivable from constants and
+    /// other accounts may be omitted; omitted optional accounts are passed as
+    /// the program ID, as Anchor expects. Accounts nested in a composite are
+    /// named either plainly or as `composite.account`.
+    pub fn build_instruction(
+        &self,
+        name: &str,
+        accounts: &HashMap<String, Pubkey>,
+        args: &Value,
+    ) -> Result<Instruction> {
+        let instruction = self.instruction(name)?;
+        let mut metas = Vec::new();
+        for item in &instruction.accounts {
+            self.resolve_accounts(item, "", accounts, &mut metas)?;
+        }
+        let metas = metas
+            .into_iter()
+            .map(|(account, pubkey)| match pubkey {
+                Some(pubkey) => Ok((account, pubkey)),
+                None => self.derive_pda(account, accounts),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts: metas
+                .into_iter()
+                .map(|(account, pubkey)| AccountMeta {
+                    pubkey,
+                    is_signer: account.signer,
+                    is_writable: account.writable,
+                })
+                .collect(),
+            data: self.encode_instruction(name, args)?,
+        })
+    }
+
+    /// Instruction data: the discriminator followed by the Borsh-encoded arguments
+    pub fn encode_instruction(&self, name: &str, args: &Value) -> Result<Vec<u8>> {
+        let instruction = self.instruction(name)?;
+        let fields = args.as_object().ok_or_else(|| invalid(name, "expected an object of arguments"))?;
+        reject_unknown(name, fields, instruction.args.iter().map(|arg| arg.name.as_str()))?;
+
+        let mut data = instruction.discriminator.clone();
+        for arg in &instruction.args {
+            let path = format!("{}.{}", name, arg.name);
+            let value = fields.get(&arg.name).unwrap_or(&Value::Null);
+            if value.is_null() && !matches!(arg.ty, IdlType::Option(_)) {
+                return Err(invalid(&path, "missing argument"));
+            }
+            self.encode(&arg.ty, value, &path, &mut data)?;
+        }
+        Ok(data)
+    }
+
+    /// Flatten an instruction's accounts, leaving PDAs to derive once all are known
+    fn resolve_accounts<'a>(
+        &self,
+        item: &'a IdlInstructionAccountItem,
+        prefix: &str,
+        accounts: &HashMap<String, Pubkey>,
+        metas: &mut Vec<(&'a IdlInstructionAccount, Option<Pubkey>)>,
+    ) -> Result<()> {
+        match item {
+            IdlInstructionAccountItem::Composite(composite) => {
+                let prefix = format!("{}{}.", prefix, composite.name);
+                for item in &composite.accounts {
+                    self.resolve_accounts(item, &prefix, accounts, metas)?;
+                }
+            }
+            IdlInstructionAccountItem::Single(account) => {
+                let given = accounts
+                    .get(&format!("{}{}", prefix, account.name))
+                    .or_else(|| accounts.get(&account.name))
+                    .copied();
+                let fixed = account
+                    .address
+                    .as_deref()
+                    .map(|address| {
+                        Pubkey::from_str(address).map_err(|_| {
+                            SdkError::Idl(format!("invalid address for {}: {}", account.name, address))
+                        })
+                    })
+                    .transpose()?;
+                let pubkey = match given.or(fixed) {
+                    Some(pubkey) => Some(pubkey),
+                    None if account.pda.is_some() => None,
+                    None if account.optional => Some(self.program_id),
+                    None => return Err(invalid(&account.name, "missing account")),
+                };
+                metas.push((account, pubkey));
+            }
+        }
+        Ok(())
+    }
+
+    /// Derive an omitted PDA from its constant seeds and seeds naming given accounts
+    fn derive_pda<'a>(
+        &self,
+        account: &'a IdlInstructionAccount,
+        given: &HashMap<String, Pubkey>,
+    ) -> Result<(&'a IdlInstructionAccount, Pubkey)> {
+        let pda = account.pda.as_ref().expect("only PDAs are left unresolved");
+        let unresolvable = || invalid(&account.name, "PDA has non-constant seeds; pass the account");
+
+        let seed_bytes = |seed: &IdlSeed| -> Result<Vec<u8>> {
+            match seed {
+                IdlSeed::Const(seed) => Ok(seed.value.clone()),
+                IdlSeed::Account(seed) if seed.account.is_none() => given
+                    .get(&seed.path)
+                    .map(|pubkey| pubkey.to_bytes().to_vec())
+                    .ok_or_else(unresolvable),
+                _ => Err(unresolvable()),
+            }
+        };
+
+        let seeds = pda.seeds.iter().map(seed_bytes).collect::<Result<Vec<_>>>()?;
+        let program_id = match &pda.program {
+            Some(program) => Pubkey::try_from(seed_bytes(program)?.as_slice())
+                .map_err(|_| unresolvable())?,
+            None => self.program_id,
+        };
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        Ok((account, Pubkey::find_program_address(&seeds, &program_id).0))
+    }
+
+    fn type_def(&self, name: &str, path: &str) -> Result<&IdlTypeDef> {
+        self.idl
+            .types
+            .iter()
+            .find(|ty| ty.name == name)
+            .ok_or_else(|| invalid(path, &format!("type {} is not defined in the IDL", name)))
+    }
+
+    /// Validate `value` against `ty` and append its Borsh encoding
+    fn encode(&self, ty: &IdlType, value: &Value, path: &str, out: &mut Vec<u8>) -> Result<()> {
+        match ty {
+            IdlType::Bool => out.push(
+                value.as_bool().ok_or_else(|| invalid(path, "expected a boolean"))? as u8,
+            ),
+            IdlType::U8 => out.extend(unsigned::<u8>(value, path)?.to_le_bytes()),
+            IdlType::U16 => out.extend(unsigned::<u16>(value, path)?.to_le_bytes()),
+            IdlType::U32 => out.extend(unsigned::<u32>(value, path)?.to_le_bytes()),
+            IdlType::U64 => out.extend(unsigned::<u64>(value, path)?.to_le_bytes()),
+            IdlType::U128 => out.extend(unsigned::<u128>(value, path)?.to_le_bytes()),
+            IdlType::I8 => out.extend(signed::<i8>(value, path)?.to_le_bytes()),
+            IdlType::I16 => out.extend(signed::<i16>(value, path)?.to_le_bytes()),
+            IdlType::I32 => out.extend(signed::<i32>(value, path)?.to_le_bytes()),
+            IdlType::I64 => out.extend(signed::<i64>(value, path)?.to_le_bytes()),
+            IdlType::I128 => out.extend(signed::<i128>(value, path)?.to_le_bytes()),
+            IdlType::F32 => out.extend((float(value, path)? as f32).to_le_bytes()),
+            IdlType::F64 => out.extend(float(value, path)?.to_le_bytes()),
+            IdlType::String => {
+                let string = value.as_str().ok_or_else(|| invalid(path, "expected a string"))?;
+                out.extend((string.len() as u32).to_le_bytes());
+                out.extend(string.as_bytes());
+            }
+            IdlType::Pubkey => {
+                let pubkey = value
+                    .as_str()
+                    .and_then(|key| Pubkey::from_str(key).ok())
+                    .ok_or_else(|| invalid(path, "expected a base58 public key"))?;
+                out.extend(pubkey.to_bytes());
+            }
+            IdlType::Bytes => {
+                let items = array(value, path)?;
+                out.extend((items.len() as u32).to_le_bytes());
+                for (index, item) in items.iter().enumerate() {
+                    out.push(unsigned::<u8>(item, &format!("{}[{}]", path, index))?);
+                }
+            }
+            IdlType::Option(inner) => {
+                if value.is_null() {
+                    out.push(0);
+                } else {
+                    out.push(1);
+                    self.encode(inner, value, path, out)?;
+                }
+            }
+            IdlType::Vec(inner) => {
+                let items = array(value, path)?;
+                out.extend((items.len() as u32).to_le_bytes());
+                for (index, item) in items.iter().enumerate() {
+                    self.encode(inner, item, &format!("{}[{}]", path, index), out)?;
+                }
+            }
+            IdlType::Array(inner, IdlArrayLen::Value(len)) => {
+                let items = array(value, path)?;
+                if items.len() != *len {
+                    return Err(invalid(
+                        path,
+                        &format!("expected {} elements, got {}", len, items.len()),
+                    ));
+                }
+                for (index, item) in items.iter().enumerate() {
+                    self.encode(inner, item, &format!("{}[{}]", path, index), out)?;
+                }
+            }
+            IdlType::Defined { name, generics } if generics.is_empty() => {
+                self.encode_defined(self.type_def(name, path)?, value, path, out)?;
+            }
+            other => {
+                return Err(invalid(path, &format!("unsupported IDL type {:?}", other)));
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_defined(
+        &self,
+        def: &IdlTypeDef,
+        value: &Value,
+        path: &str,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        if def.serialization != IdlSerialization::Borsh || !def.generics.is_empty() {
+            return Err(invalid(
+                path,
+                &format!("type {} is not a plain Borsh type", def.name),
+            ));
+        }
+
+        match &def.ty {
+            IdlTypeDefTy::Struct { fields } => self.encode_fields(fields.as_ref(), value, path, out),
+            IdlTypeDefTy::Type { alias } => self.encode(alias, value, path, out),
+            IdlTypeDefTy::Enum { variants } => {
+                // Unit variants are named by a string, others by a single-key object
+                let (variant, fields) = match value {
+                    Value::String(variant) => (variant.as_str(), &Value::Null),
+                    Value::Object(object) if object.len() == 1 => {
+                        let (variant, fields) = object.iter().next().expect("one entry");
+                        (variant.as_str(), fields)
+                    }
+                    _ => return Err(invalid(path, "expected a variant name or {variant: fields}")),
+                };
+                let index = variants
+                    .iter()
+                    .position(|candidate| candidate.name == variant)
+                    .ok_or_else(|| {
+                        invalid(path, &format!("{} has no variant {}", def.name, variant))
+                    })?;
+                out.push(index as u8);
+                self.encode_fields(
+                    variants[index].fields.as_ref(),
+                    fields,
+                    &format!("{}.{}", path, variant),
+                    out,
+                )
+            }
+        }
+    }
+
+    fn encode_fields(
+        &self,
+        fields: Option<&IdlDefinedFields>,
+        value: &Value,
+        path: &str,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        match fields {
+            None => Ok(()),
+            Some(IdlDefinedFields::Named(fields)) => {
+                let object = value
+                    .as_object()
+                    .ok_or_else(|| invalid(path, "expected an object"))?;
+                reject_unknown(path, object, fields.iter().map(|field| field.name.as_str()))?;
+                for field in fields {
+                    let path = format!("{}.{}", path, field.name);
+                    let value = object.get(&field.name).unwrap_or(&Value::Null);
+                    if value.is_null() && !matches!(field.ty, IdlType::Option(_)) {
+                        return Err(invalid(&path, "missing field"));
+                    }
+                    self.encode(&field.ty, value, &path, out)?;
+                }
+                Ok(())
+            }
+            Some(IdlDefinedFields::Tuple(types)) => {
+                let items = array(value, path)?;
+                if items.len() != types.len() {
+                    return Err(invalid(
+                        path,
+                        &format!("expected {} fields, got {}", types.len(), items.len()),
+                    ));
+                }
+                for (index, (ty, item)) in types.iter().zip(items).enumerate() {
+                    self.encode(ty, item, &format!("{}[{}]", path, index), out)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn invalid(path: &str, reason: &str) -> SdkError {
+    SdkError::InvalidArgument {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Reject misspelled or extra fields rather than silently ignoring them
+fn reject_unknown<'a>(
+    path: &str,
+    object: &serde_json::Map<String, Value>,
+    known: impl Iterator<Item = &'a str>,
+) -> Result<()> {
+    let known: Vec<&str> = known.collect();
+    match object.keys().find(|key| !known.contains(&key.as_str())) {
+        Some(key) => Err(invalid(&format!("{}.{}", path, key), "unknown field")),
+        None => Ok(()),
+    }
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>> {
+    value.as_array().ok_or_else(|| invalid(path, "expected an array"))
+}
+
+/// Integers may be JSON numbers or, beyond 2^53, decimal strings
+fn unsigned<T: TryFrom<u128>>(value: &Value, path: &str) -> Result<T> {
+    let parsed = match value {
+        Value::Number(number) => number.as_u64().map(u128::from),
+        Value::String(string) => string.parse::<u128>().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| invalid(path, "expected an unsigned integer"))?;
+    T::try_from(parsed).map_err(|_| invalid(path, "integer out of range"))
+}
+
+fn signed<T: TryFrom<i128>>(value: &Value, path: &str) -> Result<T> {
+    let parsed = match value {
+        Value::Number(number) => number.as_i64().map(i128::from),
+        Value::String(string) => string.parse::<i128>().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| invalid(path, "expected an integer"))?;
+    T::try_from(parsed).map_err(|_| invalid(path, "integer out of range"))
+}
+
+fn float(value: &Value, path: &str) -> Result<f64> {
+    value.as_f64().ok_or_else(|| invalid(path, "expected a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[derive(AnchorSerialize)]
+    enum Speed {
+        #[allow(dead_code)]
+        Fast,
+        Slow { delay: u32 },
+    }
+
+    #[derive(AnchorSerialize)]
+    struct DepositParams {
+        speed: Speed,
+        targets: [Pubkey; 2],
+    }
+
+    fn idl(program_id: &Pubkey) -> Value {
+        serde_json::json!({
+            "address": program_id.to_string(),
+            "metadata": { "name": "vault_shard", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [
+                    {
+                        "name": "vault",
+                        "writable": true,
+                        "pda": { "seeds": [
+                            { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                            { "kind": "account", "path": "owner" }
+                        ]}
+                    },
+                    { "name": "owner", "writable": true, "signer": true },
+                    { "name": "referrer", "optional": true },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "memo", "type": { "option": "string" } },
+                    { "name": "params", "type": { "defined": { "name": "DepositParams" } } }
+                ]
+            }],
+            "types": [
+                {
+                    "name": "DepositParams",
+                    "type": { "kind": "struct", "fields": [
+                        { "name": "speed", "type": { "defined": { "name": "Speed" } } },
+                        { "name": "targets", "type": { "array": ["pubkey", 2] } }
+                    ]}
+                },
+                {
+                    "name": "Speed",
+                    "type": { "kind": "enum", "variants": [
+                        { "name": "Fast" },
+                        { "name": "Slow", "fields": [{ "name": "delay", "type": "u32" }] }
+                    ]}
+                }
+            ]
+        })
+    }
+
+    fn program() -> IdlProgram {
+        IdlProgram::from_json(idl(&Pubkey::new_unique()).to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_build_instruction() {
+        let program = program();
+        let (owner, targets) = (Pubkey::new_unique(), [Pubkey::new_unique(), Pubkey::new_unique()]);
+        let args = serde_json::json!({
+            "amount": "18446744073709551615",
+            "memo": null,
+            "params": {
+                "speed": { "Slow": { "delay": 30 } },
+                "targets": [targets[0].to_string(), targets[1].to_string()],
+            },
+        });
+        let accounts = HashMap::from([("owner".to_string(), owner)]);
+        let instruction = program.build_instruction("deposit", &accounts, &args).unwrap();
+
+        let mut expected = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        expected.extend(u64::MAX.to_le_bytes());
+        expected.push(0);
+        DepositParams {
+            speed: Speed::Slow { delay: 30 },
+            targets,
+        }
+        .serialize(&mut expected)
+        .unwrap();
+        assert_eq!(instruction.data, expected);
+
+        let vault = Pubkey::find_program_address(&[b"vault", owner.as_ref()], &program.program_id()).0;
+        let keys: Vec<Pubkey> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(
+            keys,
+            [vault, owner, program.program_id(), solana_sdk::system_program::ID]
+        );
+        assert!(instruction.accounts[1].is_signer && instruction.accounts[1].is_writable);
+        assert!(!instruction.accounts[3].is_writable);
+    }
+
+    #[test]
+    fn test_argument_validation() {
+        let program = program();
+        let accounts = HashMap::from([("owner".to_string(), Pubkey::new_unique())]);
+        let args = |params: Value| serde_json::json!({ "amount": 1, "params": params });
+        let targets = [Pubkey::new_unique().to_string(), Pubkey::new_unique().to_string()];
+
+        let error_path = |args: Value| match program.build_instruction("deposit", &accounts, &args) {
+            Err(SdkError::InvalidArgument { path, .. }) => path,
+            other => panic!("expected an invalid argument, got {other:?}"),
+        };
+        assert_eq!(
+            error_path(args(serde_json::json!({ "speed": "Fast", "targets": [targets[0]] }))),
+            "deposit.params.targets"
+        );
+        assert_eq!(
+            error_path(args(serde_json::json!({ "speed": "Medium", "targets": targets }))),
+            "deposit.params.speed"
+        );
+        assert_eq!(
+            error_path(args(serde_json::json!({ "speed": "Fast", "targets": targets, "extra": 1 }))),
+            "deposit.params.extra"
+        );
+        assert_eq!(
+            error_path(serde_json::json!({ "amount": -1, "params": {} })),
+            "deposit.amount"
+        );
+        assert_eq!(
+            error_path(serde_json::json!({ "amount": 1 })),
+            "deposit.params"
+        );
+
+        // The owner cannot be derived, so it must be passed
+        let error = program
+            .build_instruction(
+                "deposit",
+                &HashMap::new(),
+                &args(serde_json::json!({ "speed": "Fast", "targets": targets })),
+            )
+            .unwrap_err();
+        assert!(matches!(error, SdkError::InvalidArgument { path, .. } if path == "owner"));
+        assert!(matches!(
+            program.instruction("withdraw"),
+            Err(SdkError::Idl(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_idl_account() {
+        let program_id = Pubkey::new_unique();
+        let mut compressed = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        compressed.write_all(idl(&program_id).to_string().as_bytes()).unwrap();
+        let compressed = compressed.finish().unwrap();
+
+        let mut data = vec![0u8; 8];
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend((compressed.len() as u32).to_le_bytes());
+        data.extend(&compressed);
+        // IDL accounts are allocated with room to grow
+        data.extend([0u8; 64]);
+
+        let program = IdlProgram::from_idl_account(program_id, &data).unwrap();
+        assert_eq!(program.program_id(), program_id);
+        assert_eq!(program.idl().instructions[0].name, "deposit");
+
+        assert!(IdlProgram::from_idl_account(program_id, &data[..50]).is_err());
+    }
+
+    #[test]
+    fn test_idl_source() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            IdlSource::from(program_id.to_string().as_str()),
+            IdlSource::Chain(program_id)
+        );
+        assert_eq!(
+            IdlSource::from("target/idl/vault_shard.json"),
+            IdlSource::Path(PathBuf::from("target/idl/vault_shard.json"))
+        );
+    }
+}
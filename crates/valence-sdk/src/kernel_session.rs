@@ -0,0 +1,777 @@
+use crate::{Result, SdkError};
+use anchor_lang::{prelude::*, InstructionData};
+#[allow(deprecated)]
+use solana_sdk::ed25519_instruction;
+use solana_sdk::{instruction::Instruction, signature::Keypair, signer::Signer};
+use valence_kernel::{
+    instruction as kernel_instruction,
+    state::{CreateSessionParams, RegisteredAccount, RegisteredProgram},
+    BufferedOperation, OperationBatch, MAX_REGISTERED_ACCOUNTS,
+};
+
+/// Most operation data bytes written per instruction, leaving room in a
+/// transaction for a separate fee payer and a compute budget instruction
+const OPERATION_DATA_WRITE_SIZE: usize = 768;
+
+/// Builder for a valence-kernel session with its guard account and ALT
+///
+/// The kernel accepts at most `MAX_REGISTERED_ACCOUNTS` borrowable accounts
+/// and programs per instruction, so registrations beyond those included in
+/// session creation are added with follow-up `manage_alt` instructions.
+pub struct KernelSessionBuilder {
+    owner: Pubkey,
+    shard: Pubkey,
+    namespace_path: String,
+    parent_session: Option<Pubkey>,
+    allow_unregistered_cpi: bool,
+    metadata: [u8; 32],
+    borrowable: Vec<RegisteredAccount>,
+    programs: Vec<RegisteredProgram>,
+}
+
+impl KernelSessionBuilder {
+    /// Create a builder for a session owned by `owner` on `shard`
+    pub fn new(owner: Pubkey, shard: Pubkey, namespace_path: impl Into<String>) -> Self {
+        Self {
+            owner,
+            shard,
+            namespace_path: namespace_path.into(),
+            parent_session: None,
+            allow_unregistered_cpi: false,
+            metadata: [0u8; 32],
+            borrowable: Vec::new(),
+            programs: Vec::new(),
+        }
+    }
+
+    /// Set the parent session for hierarchical relationships
+    pub fn parent_session(mut self, parent: Pubkey) -> Self {
+        self.parent_session = Some(parent);
+        self
+    }
+
+    /// Enable unsafe raw CPI (opt-in to risk)
+    pub fn allow_unregistered_cpi(mut self) -> Self {
+        self.allow_unregistered_cpi = true;
+        self
+    }
+
+    /// Set metadata
+    pub fn metadata(mut self, metadata: [u8; 32]) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Register an account the session may borrow
+    pub fn borrowable(mut self, account: RegisteredAccount) -> Self {
+        self.borrowable.push(account);
+        self
+    }
+
+    /// Register a program the session may call
+    pub fn program(mut self, program: RegisteredProgram) -> Self {
+        self.programs.push(program);
+        self
+    }
+
+    /// Build the CreateSessionParams
+    pub fn build_params(&self) -> Result<CreateSessionParams> {
+        let namespace_bytes = self.namespace_path.as_bytes();
+        if namespace_bytes.is_empty() || namespace_bytes.len() > 128 {
+            return Err(SdkError::InvalidSessionConfig);
+        }
+
+        let mut namespace_path = [0u8; 128];
+        namespace_path[..namespace_bytes.len()].copy_from_slice(namespace_bytes);
+
+        Ok(CreateSessionParams {
+            namespace_path,
+            namespace_path_len: namespace_bytes.len() as u16,
+            metadata: self.metadata,
+            parent_session: self.parent_session,
+        })
+    }
+
+    /// Generate the session accounts and the instructions creating them
+    pub fn build(self) -> Result<KernelSession> {
+        let params = self.build_params()?;
+        let guard = Keypair::new();
+        let session = Keypair::new();
+        let account_lookup = Keypair::new();
+
+        let create_guard = Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new(guard.pubkey(), true),
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::CreateGuardAccount {
+                session: session.pubkey(),
+                allow_unregistered_cpi: self.allow_unregistered_cpi,
+            }
+            .data(),
+        };
+
+        let mut borrowable = self.borrowable.chunks(MAX_REGISTERED_ACCOUNTS);
+        let mut programs = self.programs.chunks(MAX_REGISTERED_ACCOUNTS);
+
+        let create_session = Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new(session.pubkey(), true),
+                AccountMeta::new(account_lookup.pubkey(), true),
+                AccountMeta::new_readonly(guard.pubkey(), false),
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::CreateSessionAccount {
+                shard: self.shard,
+                params,
+                initial_borrowable: borrowable.next().unwrap_or_default().to_vec(),
+                initial_programs: programs.next().unwrap_or_default().to_vec(),
+            }
+            .data(),
+        };
+
+        // Remaining registrations in chunks the kernel accepts
+        let mut registrations = Vec::new();
+        loop {
+            let (accounts, programs) = (borrowable.next(), programs.next());
+            if accounts.is_none() && programs.is_none() {
+                break;
+            }
+            registrations.push(manage_alt_instruction(
+                session.pubkey(),
+                account_lookup.pubkey(),
+                self.owner,
+                accounts.unwrap_or_default().to_vec(),
+                programs.unwrap_or_default().to_vec(),
+            ));
+        }
+
+        Ok(KernelSession {
+            owner: self.owner,
+            guard,
+            session,
+            account_lookup,
+            create_guard,
+            create_session,
+            registrations,
+        })
+    }
+}
+
+/// Addresses of an existing session's accounts, read from its session account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionAccounts {
+    pub session: Pubkey,
+    pub guard: Pubkey,
+    pub account_lookup: Pubkey,
+}
+
+impl SessionAccounts {
+    /// Addresses recorded in a fetched session account
+    pub fn from_session(session: Pubkey, state: &valence_kernel::Session) -> Self {
+        Self {
+            session,
+            guard: state.guard_account,
+            account_lookup: state.account_lookup,
+        }
+    }
+
+    /// Instruction executing `batch` in this session
+    pub fn execute_batch_instruction(
+        &self,
+        batch: OperationBatch,
+        cpi_allowlist: Pubkey,
+        caller: Pubkey,
+        tx_submitter: Pubkey,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new(self.session, false),
+            AccountMeta::new_readonly(self.guard, false),
+            AccountMeta::new_readonly(self.account_lookup, false),
+            AccountMeta::new_readonly(cpi_allowlist, false),
+            AccountMeta::new_readonly(caller, true),
+            AccountMeta::new(tx_submitter, true),
+            AccountMeta::new_readonly(solana_sdk::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::rent::ID, false),
+        ];
+        accounts.extend(remaining_accounts);
+
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts,
+            data: kernel_instruction::ExecuteBatch { batch }.data(),
+        }
+    }
+
+    /// Address of the session's operation data account
+    ///
+    /// Pass it as a read-only remaining account to batches whose CPIs carry data.
+    pub fn operation_data_address(&self) -> Pubkey {
+        valence_common::pdas::operation_data(&self.session, &valence_kernel::ID).0
+    }
+
+    /// Instruction creating the session's operation data account with room
+    /// for `capacity` bytes, signed by the owner
+    pub fn initialize_operation_data_instruction(
+        &self,
+        owner: Pubkey,
+        capacity: u32,
+        payer: Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session, false),
+                AccountMeta::new(self.operation_data_address(), false),
+                AccountMeta::new_readonly(owner, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::InitializeOperationData { capacity }.data(),
+        }
+    }
+
+    /// Instructions replacing the session's operation data with `data`
+    ///
+    /// The data is split into writes small enough for one transaction each;
+    /// they must execute in order.
+    pub fn write_operation_data_instructions(&self, owner: Pubkey, data: &[u8]) -> Vec<Instruction> {
+        let write = |offset: usize, chunk: &[u8]| Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session, false),
+                AccountMeta::new(self.operation_data_address(), false),
+                AccountMeta::new_readonly(owner, true),
+            ],
+            data: kernel_instruction::WriteOperationData {
+                offset: offset as u32,
+                data: chunk.to_vec(),
+            }
+            .data(),
+        };
+        if data.is_empty() {
+            return vec![write(0, data)];
+        }
+        data.chunks(OPERATION_DATA_WRITE_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| write(i * OPERATION_DATA_WRITE_SIZE, chunk))
+            .collect()
+    }
+
+    /// Instruction closing the session's operation data account
+    pub fn close_operation_data_instruction(&self, owner: Pubkey) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session, false),
+                AccountMeta::new(self.operation_data_address(), false),
+                AccountMeta::new(owner, true),
+            ],
+            data: kernel_instruction::CloseOperationData {}.data(),
+        }
+    }
+
+    /// Address of the session's batch buffer with `buffer_id`
+    pub fn batch_buffer_address(&self, buffer_id: u64) -> Pubkey {
+        valence_common::pdas::batch_buffer(&self.session, buffer_id, &valence_kernel::ID).0
+    }
+
+    /// Instruction opening a batch buffer of `capacity` bytes, signed by the owner
+    pub fn begin_batch_instruction(
+        &self,
+        owner: Pubkey,
+        buffer_id: u64,
+        capacity: u32,
+        payer: Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session, false),
+                AccountMeta::new(self.batch_buffer_address(buffer_id), false),
+                AccountMeta::new_readonly(owner, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::BeginBatch {
+                buffer_id,
+                capacity,
+            }
+            .data(),
+        }
+    }
+
+    /// Instruction appending accounts and operations to a batch buffer
+    ///
+    /// Operations index into every account appended so far, in order.
+    pub fn append_operations_instruction(
+        &self,
+        owner: Pubkey,
+        buffer_id: u64,
+        accounts: Vec<Pubkey>,
+        operations: Vec<BufferedOperation>,
+    ) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session, false),
+                AccountMeta::new(self.batch_buffer_address(buffer_id), false),
+                AccountMeta::new_readonly(owner, true),
+            ],
+            data: kernel_instruction::AppendOperations {
+                accounts,
+                operations,
+            }
+            .data(),
+        }
+    }
+
+    /// Instruction executing and closing a batch buffer, called by the owner
+    ///
+    /// `remaining_accounts` are passed as for [`execute_batch_instruction`].
+    ///
+    /// [`execute_batch_instruction`]: Self::execute_batch_instruction
+    pub fn finalize_batch_instruction(
+        &self,
+        buffer_id: u64,
+        cpi_allowlist: Pubkey,
+        owner: Pubkey,
+        tx_submitter: Pubkey,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new(self.session, false),
+            AccountMeta::new(self.batch_buffer_address(buffer_id), false),
+            AccountMeta::new_readonly(self.guard, false),
+            AccountMeta::new_readonly(self.account_lookup, false),
+            AccountMeta::new_readonly(cpi_allowlist, false),
+            AccountMeta::new_readonly(owner, true),
+            AccountMeta::new(tx_submitter, true),
+            AccountMeta::new_readonly(solana_sdk::sysvar::clock::ID, false),
+        ];
+        accounts.extend(remaining_accounts);
+
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts,
+            data: kernel_instruction::FinalizeBatch {}.data(),
+        }
+    }
+
+    /// Instruction closing a batch buffer without executing it
+    pub fn discard_batch_instruction(&self, owner: Pubkey, buffer_id: u64) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session, false),
+                AccountMeta::new(self.batch_buffer_address(buffer_id), false),
+                AccountMeta::new(owner, true),
+            ],
+            data: kernel_instruction::DiscardBatch {}.data(),
+        }
+    }
+}
+
+/// Accounts and ordered instructions produced by [`KernelSessionBuilder`]
+pub struct KernelSession {
+    pub owner: Pubkey,
+    pub guard: Keypair,
+    pub session: Keypair,
+    pub account_lookup: Keypair,
+    /// Creates the guard account; must precede session creation
+    pub create_guard: Instruction,
+    /// Creates the session and ALT with the first registrations
+    pub create_session: Instruction,
+    /// Registers the remaining accounts and programs in the ALT
+    pub registrations: Vec<Instruction>,
+}
+
+impl KernelSession {
+    /// All instructions in the order they must execute
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = vec![self.create_guard.clone(), self.create_session.clone()];
+        instructions.extend(self.registrations.iter().cloned());
+        instructions
+    }
+
+    /// Instruction registering more accounts and programs in the session's ALT
+    pub fn register_instruction(
+        &self,
+        borrowable: Vec<RegisteredAccount>,
+        programs: Vec<RegisteredProgram>,
+    ) -> Instruction {
+        manage_alt_instruction(
+            self.session.pubkey(),
+            self.account_lookup.pubkey(),
+            self.owner,
+            borrowable,
+            programs,
+        )
+    }
+
+    /// Instruction executing `batch` in this session, called by the owner
+    pub fn execute_batch_instruction(
+        &self,
+        batch: OperationBatch,
+        cpi_allowlist: Pubkey,
+        tx_submitter: Pubkey,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        self.execute_batch_as(batch, cpi_allowlist, self.owner, tx_submitter, remaining_accounts)
+    }
+
+    /// Instruction executing a batch the owner signed offline
+    ///
+    /// `submitter` calls and pays. The nonce account, instructions sysvar and
+    /// fee vault are appended to `remaining_accounts`, and the transaction must also
+    /// carry the owner's [`offline_signature_instruction`] before this one.
+    ///
+    /// [`offline_signature_instruction`]: Self::offline_signature_instruction
+    pub fn execute_offline_batch_instruction(
+        &self,
+        batch: OperationBatch,
+        cpi_allowlist: Pubkey,
+        submitter: Pubkey,
+        mut remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        remaining_accounts.push(AccountMeta::new(self.nonce_address(), false));
+        remaining_accounts.push(AccountMeta::new_readonly(
+            solana_sdk::sysvar::instructions::ID,
+            false,
+        ));
+        self.execute_batch_as(batch, cpi_allowlist, submitter, submitter, remaining_accounts)
+    }
+
+    fn execute_batch_as(
+        &self,
+        batch: OperationBatch,
+        cpi_allowlist: Pubkey,
+        caller: Pubkey,
+        tx_submitter: Pubkey,
+        mut remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        // The kernel checks relayers against the session's fee vault
+        let fee_vault = self.fee_vault_address();
+        if tx_submitter != self.owner
+            && !remaining_accounts.iter().any(|meta| meta.pubkey == fee_vault)
+        {
+            remaining_accounts.push(AccountMeta::new(fee_vault, false));
+        }
+        self.accounts()
+            .execute_batch_instruction(batch, cpi_allowlist, caller, tx_submitter, remaining_accounts)
+    }
+
+    /// Addresses of the session's accounts
+    pub fn accounts(&self) -> SessionAccounts {
+        SessionAccounts {
+            session: self.session.pubkey(),
+            guard: self.guard.pubkey(),
+            account_lookup: self.account_lookup.pubkey(),
+        }
+    }
+
+    /// Instruction setting the key whose approval every batch must carry
+    ///
+    /// `current_approval_signer` must sign when the guard already has one.
+    pub fn set_approval_signer_instruction(
+        &self,
+        approval_signer: Option<Pubkey>,
+        current_approval_signer: Option<Pubkey>,
+    ) -> Instruction {
+        // Anchor reads the program id as an absent optional account
+        let current = current_approval_signer.map_or_else(
+            || AccountMeta::new_readonly(valence_kernel::ID, false),
+            |signer| AccountMeta::new_readonly(signer, true),
+        );
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session.pubkey(), false),
+                AccountMeta::new(self.guard.pubkey(), false),
+                AccountMeta::new_readonly(self.owner, true),
+                current,
+            ],
+            data: kernel_instruction::SetGuardApprovalSigner { approval_signer }.data(),
+        }
+    }
+
+    /// Instruction requiring, or no longer requiring, batches to be committed
+    /// before they execute
+    pub fn set_require_commitment_instruction(&self, require_commitment: bool) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session.pubkey(), false),
+                AccountMeta::new(self.guard.pubkey(), false),
+                AccountMeta::new_readonly(self.owner, true),
+            ],
+            data: kernel_instruction::SetGuardRequireCommitment { require_commitment }.data(),
+        }
+    }
+
+    /// Instruction growing the session's guard from an earlier layout
+    ///
+    /// The owner pays the rent of the larger account.
+    pub fn migrate_guard_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session.pubkey(), false),
+                AccountMeta::new(self.guard.pubkey(), false),
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::MigrateGuardAccount {}.data(),
+        }
+    }
+
+    /// Instruction closing the session, its ALT and its guard, refunding their
+    /// rent to the owner
+    ///
+    /// The session must already be invalidated and hold no borrowed or child accounts.
+    pub fn close_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new(self.session.pubkey(), false),
+                AccountMeta::new(self.account_lookup.pubkey(), false),
+                AccountMeta::new(self.guard.pubkey(), false),
+                AccountMeta::new(self.owner, true),
+            ],
+            data: kernel_instruction::CloseSession {}.data(),
+        }
+    }
+
+    /// ed25519 precompile instruction approving `batch` for its next execution
+    ///
+    /// `usage_count` is the session's current usage count. The precompile
+    /// instruction must precede the batch in the same transaction, and the
+    /// batch must list the instructions sysvar among its remaining accounts.
+    pub fn batch_approval_instruction(
+        &self,
+        approver: &Keypair,
+        batch: &OperationBatch,
+        usage_count: u64,
+    ) -> Result<Instruction> {
+        let message = batch
+            .approval_message(&self.session.pubkey(), usage_count)
+            .map_err(|e| SdkError::Serialization(e.to_string()))?;
+        let signature: [u8; 64] = approver.sign_message(&message).into();
+        Ok(ed25519_instruction::new_ed25519_instruction_with_signature(
+            &message,
+            &signature,
+            &approver.pubkey().to_bytes(),
+        ))
+    }
+
+    /// Address of the session's commitment to `batch`
+    pub fn commitment_address(&self, batch: &OperationBatch) -> Result<Pubkey> {
+        let batch_hash = batch
+            .commitment_hash()
+            .map_err(|e| SdkError::Serialization(e.to_string()))?;
+        Ok(valence_common::pdas::batch_commitment(
+            &self.session.pubkey(),
+            &batch_hash,
+            &valence_kernel::ID,
+        )
+        .0)
+    }
+
+    /// Instruction committing to `batch` without revealing it
+    ///
+    /// The batch is revealed later with [`execute_batch_instruction`], passing
+    /// [`commitment_address`] as a writable remaining account.
+    ///
+    /// [`execute_batch_instruction`]: Self::execute_batch_instruction
+    /// [`commitment_address`]: Self::commitment_address
+    pub fn commit_batch_instruction(
+        &self,
+        batch: &OperationBatch,
+        earliest_slot: u64,
+        payer: Pubkey,
+    ) -> Result<Instruction> {
+        let batch_hash = batch
+            .commitment_hash()
+            .map_err(|e| SdkError::Serialization(e.to_string()))?;
+        Ok(Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session.pubkey(), false),
+                AccountMeta::new(self.commitment_address(batch)?, false),
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::CommitBatch {
+                batch_hash,
+                earliest_slot,
+            }
+            .data(),
+        })
+    }
+
+    /// Address of the session's nonce account for offline-signed batches
+    pub fn nonce_address(&self) -> Pubkey {
+        valence_common::pdas::session_nonce(&self.session.pubkey(), &valence_kernel::ID).0
+    }
+
+    /// Instruction creating the session's nonce account, signed by the owner
+    pub fn initialize_nonce_instruction(&self, payer: Pubkey) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session.pubkey(), false),
+                AccountMeta::new(self.nonce_address(), false),
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::InitializeSessionNonce {}.data(),
+        }
+    }
+
+    /// Address of the session's statistics account
+    ///
+    /// Once it is initialized, every batch and direct transfer of the session
+    /// must pass it as a writable remaining account.
+    pub fn stats_address(&self) -> Pubkey {
+        valence_common::pdas::session_stats(&self.session.pubkey(), &valence_kernel::ID).0
+    }
+
+    /// Instruction creating the session's statistics account, signed by the owner
+    pub fn initialize_stats_instruction(&self, payer: Pubkey) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new(self.session.pubkey(), false),
+                AccountMeta::new(self.stats_address(), false),
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::InitializeSessionStats {}.data(),
+        }
+    }
+
+    /// Address of the session's fee vault
+    ///
+    /// Batches a relayer submits carry it as a writable remaining account, so
+    /// the relayer is checked against the allowlist and reimbursed. The
+    /// execute instructions of [`KernelSession`] append it themselves.
+    pub fn fee_vault_address(&self) -> Pubkey {
+        valence_common::pdas::fee_vault(&self.session.pubkey(), &valence_kernel::ID).0
+    }
+
+    /// Instruction creating the session's fee vault, signed by the owner
+    pub fn initialize_fee_vault_instruction(
+        &self,
+        payer: Pubkey,
+        submitters: Vec<Pubkey>,
+        reimbursement_per_batch: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.session.pubkey(), false),
+                AccountMeta::new(self.fee_vault_address(), false),
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::InitializeFeeVault {
+                submitters,
+                reimbursement_per_batch,
+            }
+            .data(),
+        }
+    }
+
+    /// Instruction replacing the fee vault's allowlist and reimbursement
+    pub fn configure_fee_vault_instruction(
+        &self,
+        submitters: Vec<Pubkey>,
+        reimbursement_per_batch: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: self.fee_vault_accounts(),
+            data: kernel_instruction::ConfigureFeeVault {
+                submitters,
+                reimbursement_per_batch,
+            }
+            .data(),
+        }
+    }
+
+    /// Instruction returning `amount` of unused fee vault funds to the owner
+    pub fn withdraw_fee_vault_instruction(&self, amount: u64) -> Instruction {
+        Instruction {
+            program_id: valence_kernel::ID,
+            accounts: self.fee_vault_accounts(),
+            data: kernel_instruction::WithdrawFeeVault { amount }.data(),
+        }
+    }
+
+    fn fee_vault_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.session.pubkey(), false),
+            AccountMeta::new(self.fee_vault_address(), false),
+            AccountMeta::new(self.owner, true),
+        ]
+    }
+
+    /// ed25519 precompile instruction carrying the owner's offline signature
+    ///
+    /// `owner` may sign on a machine that never sees a blockhash; the result
+    /// stays valid until a batch executes at `nonce`.
+    pub fn offline_signature_instruction(
+        owner: &Keypair,
+        session: Pubkey,
+        batch: &OperationBatch,
+        nonce: u64,
+    ) -> Result<Instruction> {
+        let message = batch
+            .offline_message(&session, nonce)
+            .map_err(|e| SdkError::Serialization(e.to_string()))?;
+        let signature: [u8; 64] = owner.sign_message(&message).into();
+        Ok(ed25519_instruction::new_ed25519_instruction_with_signature(
+            &message,
+            &signature,
+            &owner.pubkey().to_bytes(),
+        ))
+    }
+
+    /// Fresh accounts that must sign the creation instructions
+    pub fn new_account_signers(&self) -> [&Keypair; 3] {
+        [&self.guard, &self.session, &self.account_lookup]
+    }
+}
+
+fn manage_alt_instruction(
+    session: Pubkey,
+    account_lookup: Pubkey,
+    owner: Pubkey,
+    add_borrowable: Vec<RegisteredAccount>,
+    add_programs: Vec<RegisteredProgram>,
+) -> Instruction {
+    Instruction {
+        program_id: valence_kernel::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(session, false),
+            AccountMeta::new(account_lookup, false),
+            AccountMeta::new_readonly(owner, true),
+        ],
+        data: kernel_instruction::ManageAlt {
+            add_borrowable,
+            add_programs,
+            remove_accounts: Vec::new(),
+        }
+        .data(),
+    }
+}
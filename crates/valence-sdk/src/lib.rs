@@ -1,15 +1,39 @@
 // Valence SDK - Clean, concise interface for interacting with the Valence protocol
 
+pub mod allowlist;
 pub mod client;
+pub mod async_client;
 pub mod error;
 pub mod session;
+pub mod kernel_session;
 pub mod compute;
+pub mod guard;
+pub mod composer;
+pub mod events;
+pub mod fetch;
+pub mod flow;
+pub mod idl;
+pub mod send;
+pub mod token;
 pub mod move_semantics;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+pub use allowlist::*;
 pub use client::*;
+pub use async_client::*;
 pub use error::*;
 pub use session::*;
+pub use kernel_session::*;
 pub use move_semantics::*;
+pub use composer::*;
+pub use events::*;
+pub use fetch::*;
+pub use flow::*;
+pub use guard::*;
+pub use idl::*;
+pub use send::*;
+pub use token::*;
 
 // Re-export commonly used types
 pub use anchor_client::{Client, Cluster};
@@ -22,6 +46,7 @@ pub use solana_sdk::{
 
 // Re-export valence types
 pub use valence_kernel::{
+    BufferedOperation,
     KernelOperation,
     OperationBatch,
     state::CreateSessionParams,
@@ -0,0 +1,107 @@
+use crate::SdkError;
+use anchor_lang::prelude::*;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{Instruction, InstructionError},
+    signature::Signature,
+    transaction::TransactionError,
+};
+use std::{str::FromStr, time::Duration};
+use valence_functions::states::FunctionsError;
+use valence_kernel::errors::KernelError;
+
+/// Options for sending and confirming transactions
+#[derive(Debug, Clone)]
+pub struct SendOptions {
+    /// Resends with a fresh blockhash after the previous one expires
+    pub max_retries: u32,
+    /// Skip simulating the transaction before sending
+    pub skip_preflight: bool,
+    /// Commitment the transaction must reach
+    pub commitment: CommitmentConfig,
+    /// Upper bound on the time spent across all attempts
+    pub timeout: Duration,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            skip_preflight: false,
+            commitment: CommitmentConfig::confirmed(),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A transaction confirmed at the requested commitment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Confirmation {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Fee charged, in lamports
+    pub fee: u64,
+    /// Send attempts made, including the successful one
+    pub attempts: u32,
+}
+
+/// A transaction simulated without being sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// Compute units the transaction consumed, when reported
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+/// Decode a failed transaction into a typed error
+///
+/// Program logs name the innermost failing program, so they are preferred;
+/// otherwise the failing top-level instruction's program is used. Failures
+/// inside a registered function called by a batch are wrapped in
+/// [`SdkError::FunctionFailed`], naming the operation and function.
+pub fn decode_transaction_error(
+    err: &TransactionError,
+    instructions: &[Instruction],
+    logs: &[String],
+) -> SdkError {
+    let decoded = match err {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            let program = failing_program(logs, *code)
+                .or_else(|| instructions.get(*index as usize).map(|ix| ix.program_id));
+            match program {
+                Some(program) => decode_program_error(program, *code),
+                None => SdkError::TransactionFailed(err.to_string()),
+            }
+        }
+        _ => SdkError::TransactionFailed(err.to_string()),
+    };
+
+    match crate::events::decode_batch_failure(logs, err) {
+        Some(failure) => SdkError::FunctionFailed {
+            failure,
+            source: Box::new(decoded),
+        },
+        None => decoded,
+    }
+}
+
+/// Map a program's custom error code to the matching typed error
+pub fn decode_program_error(program: Pubkey, code: u32) -> SdkError {
+    let decoded = if program == valence_kernel::ID {
+        KernelError::from_code(code).map(SdkError::Kernel)
+    } else if program == valence_functions::ID {
+        FunctionsError::from_code(code).map(SdkError::Functions)
+    } else {
+        None
+    };
+    decoded.unwrap_or(SdkError::ProgramFailed { program, code })
+}
+
+/// Find the program whose `custom program error` log matches `code`
+fn failing_program(logs: &[String], code: u32) -> Option<Pubkey> {
+    let suffix = format!(" failed: custom program error: {:#x}", code);
+    logs.iter().find_map(|line| {
+        let program = line.strip_prefix("Program ")?.strip_suffix(&suffix)?;
+        Pubkey::from_str(program).ok()
+    })
+}
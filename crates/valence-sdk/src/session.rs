@@ -1,16 +1,18 @@
-use crate::{Result, ValenceClient, SdkError};
+use crate::{FeePayer, Result, SdkError};
 use anchor_lang::prelude::*;
 use solana_sdk::instruction::Instruction;
 use valence_kernel::{
-    state::{CreateSessionParams, RegisteredAccount, RegisteredProgram},
+    state::{CreateSessionParams, OperationData, RegisteredAccount, RegisteredProgram},
     OperationBatch,
     KernelOperation,
+    ACCESS_MODE_READ, ACCESS_MODE_READ_WRITE, ACCESS_MODE_WRITE,
     MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_CPI_ACCOUNT_INDICES, MAX_OPERATION_DATA_SIZE,
+    MAX_OPERATION_DATA_ACCOUNT_SIZE, MAX_ASSERTION_DATA_SIZE,
 };
 
 /// Builder for creating sessions
 pub struct SessionBuilder<'a> {
-    client: &'a ValenceClient,
+    client: &'a dyn FeePayer,
     namespace_path: String,
     parent_session: Option<Pubkey>,
     allow_unregistered_cpi: bool,
@@ -21,7 +23,7 @@ pub struct SessionBuilder<'a> {
 
 impl<'a> SessionBuilder<'a> {
     /// Create a new session builder
-    pub fn new(client: &'a ValenceClient, namespace_path: String) -> Self {
+    pub fn new(client: &'a dyn FeePayer, namespace_path: String) -> Self {
         Self {
             client,
             namespace_path,
@@ -152,13 +154,13 @@ impl<'a> SessionBuilder<'a> {
 
 /// Handle to an active session
 pub struct SessionHandle<'a> {
-    client: &'a ValenceClient,
+    client: &'a dyn FeePayer,
     session_pubkey: Pubkey,
     alt_pubkey: Pubkey,
 }
 
 impl<'a> SessionHandle<'a> {
-    pub fn new(client: &'a ValenceClient, session_pubkey: Pubkey, alt_pubkey: Pubkey) -> Self {
+    pub fn new(client: &'a dyn FeePayer, session_pubkey: Pubkey, alt_pubkey: Pubkey) -> Self {
         Self {
             client,
             session_pubkey,
@@ -268,10 +270,45 @@ impl<'a> SessionHandle<'a> {
     }
 }
 
+/// Access requested when borrowing an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl AccessMode {
+    /// Kernel access mode constant
+    pub const fn as_u8(self) -> u8 {
+        match self {
+            Self::Read => ACCESS_MODE_READ,
+            Self::Write => ACCESS_MODE_WRITE,
+            Self::ReadWrite => ACCESS_MODE_READ_WRITE,
+        }
+    }
+}
+
+/// Fixed-size CPI fields shared by function calls and raw CPIs
+struct CpiArgs {
+    account_indices: [u8; MAX_CPI_ACCOUNT_INDICES],
+    account_indices_len: u8,
+    data_offset: u16,
+    data_len: u16,
+}
+
 /// Helper to build operation batches
+///
+/// Accounts are deduplicated into the batch account list and operations
+/// refer to them by index. CPI data is laid out in [`operation_data`], which
+/// must be written to the session's operation data account before the batch
+/// executes.
+///
+/// [`operation_data`]: Self::operation_data
 pub struct BatchBuilder {
     accounts: Vec<Pubkey>,
     operations: Vec<KernelOperation>,
+    operation_data: Vec<u8>,
 }
 
 impl Default for BatchBuilder {
@@ -285,9 +322,15 @@ impl BatchBuilder {
         Self {
             accounts: Vec::new(),
             operations: Vec::new(),
+            operation_data: Vec::new(),
         }
     }
 
+    /// Operation data the batch's CPIs reference
+    pub fn operation_data(&self) -> &[u8] {
+        &self.operation_data
+    }
+
     /// Add an account to the batch and return its index
     pub fn add_account(&mut self, account: Pubkey) -> u8 {
         if let Some(index) = self.accounts.iter().position(|&a| a == account) {
@@ -309,6 +352,11 @@ impl BatchBuilder {
         self
     }
 
+    /// Borrow an account with the given access
+    pub fn borrow(&mut self, account: Pubkey, mode: AccessMode) -> &mut Self {
+        self.borrow_account(account, mode.as_u8())
+    }
+
     /// Add a release account operation
     pub fn release_account(&mut self, account: Pubkey) -> &mut Self {
         let index = self.add_account(account);
@@ -318,6 +366,11 @@ impl BatchBuilder {
         self
     }
 
+    /// Release a borrowed account
+    pub fn release(&mut self, account: Pubkey) -> &mut Self {
+        self.release_account(account)
+    }
+
     /// Add a call to registered function
     pub fn call_registered_function(
         &mut self,
@@ -325,33 +378,146 @@ impl BatchBuilder {
         accounts: &[Pubkey],
         data: &[u8],
     ) -> Result<&mut Self> {
+        let args = self.cpi_args(accounts, data)?;
+
+        self.operations.push(KernelOperation::CallRegisteredFunction {
+            registry_id,
+            account_indices: args.account_indices,
+            account_indices_len: args.account_indices_len,
+            data_offset: args.data_offset,
+            data_len: args.data_len,
+        });
+        
+        Ok(self)
+    }
+
+    /// Call a function from the on-chain registry
+    pub fn call_function(
+        &mut self,
+        registry_id: u64,
+        accounts: &[Pubkey],
+        data: &[u8],
+    ) -> Result<&mut Self> {
+        self.call_registered_function(registry_id, accounts, data)
+    }
+
+    /// Invoke an arbitrary program; the session guard must allow unregistered CPI
+    pub fn raw_cpi(
+        &mut self,
+        program: Pubkey,
+        accounts: &[Pubkey],
+        data: &[u8],
+    ) -> Result<&mut Self> {
+        let program_index = self.add_account(program);
+        let args = self.cpi_args(accounts, data)?;
+
+        self.operations.push(KernelOperation::UnsafeRawCpi {
+            program_index,
+            account_indices: args.account_indices,
+            account_indices_len: args.account_indices_len,
+            data_offset: args.data_offset,
+            data_len: args.data_len,
+        });
+
+        Ok(self)
+    }
+
+    /// Fail the batch unless `account`'s data at `offset` starts with `expected`
+    pub fn assert_account_data(
+        &mut self,
+        account: Pubkey,
+        offset: u16,
+        expected: &[u8],
+    ) -> Result<&mut Self> {
+        if expected.is_empty() || expected.len() > MAX_ASSERTION_DATA_SIZE {
+            return Err(SdkError::InvalidOperation(
+                "Asserted data must be 1 to 32 bytes".to_string(),
+            ));
+        }
+        let mut fixed = [0u8; MAX_ASSERTION_DATA_SIZE];
+        fixed[..expected.len()].copy_from_slice(expected);
+
+        let account_index = self.add_account(account);
+        self.operations.push(KernelOperation::AssertAccountData {
+            account_index,
+            offset,
+            expected: fixed,
+            len: expected.len() as u8,
+        });
+        Ok(self)
+    }
+
+    /// Fail the batch unless the token account's balance is within `min..=max`
+    pub fn assert_token_balance(&mut self, account: Pubkey, min: u64, max: u64) -> Result<&mut Self> {
+        if min > max {
+            return Err(SdkError::InvalidOperation(
+                "Minimum balance exceeds maximum".to_string(),
+            ));
+        }
+        let account_index = self.add_account(account);
+        self.operations.push(KernelOperation::AssertTokenBalance {
+            account_index,
+            min,
+            max,
+        });
+        Ok(self)
+    }
+
+    /// Index CPI accounts and append data to the operation data
+    fn cpi_args(&mut self, accounts: &[Pubkey], data: &[u8]) -> Result<CpiArgs> {
         if accounts.len() > MAX_CPI_ACCOUNT_INDICES {
             return Err(SdkError::InvalidOperation("Too many accounts".to_string()));
         }
         if data.len() > MAX_OPERATION_DATA_SIZE {
             return Err(SdkError::InvalidOperation("Data too large".to_string()));
         }
+        let data_offset = self.operation_data.len();
+        if OperationData::space(data_offset + data.len()) > MAX_OPERATION_DATA_ACCOUNT_SIZE {
+            return Err(SdkError::InvalidOperation("Operation data full".to_string()));
+        }
 
         let mut account_indices = [0u8; MAX_CPI_ACCOUNT_INDICES];
         for (i, account) in accounts.iter().enumerate() {
             account_indices[i] = self.add_account(*account);
         }
+        self.operation_data.extend_from_slice(data);
 
-        let mut fixed_data = [0u8; MAX_OPERATION_DATA_SIZE];
-        fixed_data[..data.len()].copy_from_slice(data);
-
-        self.operations.push(KernelOperation::CallRegisteredFunction {
-            registry_id,
+        Ok(CpiArgs {
             account_indices,
             account_indices_len: accounts.len() as u8,
-            data: fixed_data,
+            data_offset: data_offset as u16,
             data_len: data.len() as u16,
-        });
-        
-        Ok(self)
+        })
     }
 
-    /// Build the operation batch
+    /// Check borrows and releases pair up within the batch
+    fn check_borrows(&self) -> Result<()> {
+        // Accounts borrowed before this batch may be released by it, so only
+        // transitions made by this batch are checked
+        let mut borrowed: Vec<(u8, bool)> = Vec::new();
+        for operation in &self.operations {
+            match operation {
+                KernelOperation::BorrowAccount { account_index, .. } => {
+                    match borrowed.iter_mut().find(|(index, _)| index == account_index) {
+                        Some((_, true)) => return Err(SdkError::AccountAlreadyBorrowed),
+                        Some(state) => state.1 = true,
+                        None => borrowed.push((*account_index, true)),
+                    }
+                }
+                KernelOperation::ReleaseAccount { account_index } => {
+                    match borrowed.iter_mut().find(|(index, _)| index == account_index) {
+                        Some((_, false)) => return Err(SdkError::AccountNotBorrowed),
+                        Some(state) => state.1 = false,
+                        None => borrowed.push((*account_index, false)),
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Build and validate the operation batch
     pub fn build(self) -> Result<OperationBatch> {
         if self.accounts.len() > MAX_BATCH_ACCOUNTS {
             return Err(SdkError::InvalidOperation("Too many accounts in batch".to_string()));
@@ -359,6 +525,7 @@ impl BatchBuilder {
         if self.operations.len() > MAX_BATCH_OPERATIONS {
             return Err(SdkError::InvalidOperation("Too many operations in batch".to_string()));
         }
+        self.check_borrows()?;
 
         let mut accounts = [Pubkey::default(); MAX_BATCH_ACCOUNTS];
         accounts[..self.accounts.len()].copy_from_slice(&self.accounts);
@@ -369,11 +536,16 @@ impl BatchBuilder {
             operations[i] = Some(op);
         }
 
-        Ok(OperationBatch {
+        let batch = OperationBatch {
             accounts,
             accounts_len: self.accounts.len() as u8,
             operations,
             operations_len,
-        })
+            operation_data_hash: OperationData::hash(&self.operation_data),
+        };
+        batch
+            .validate()
+            .map_err(|e| SdkError::InvalidOperation(e.to_string()))?;
+        Ok(batch)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,181 @@
+use crate::{decode_transaction_error, KernelSession, KernelSessionBuilder, Result, SdkError};
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+#[allow(deprecated)]
+use solana_sdk::system_instruction;
+use solana_sdk::{
+    instruction::Instruction, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+use valence_kernel::state::{RegisteredAccount, RegisteredProgram};
+
+/// Lamports given to payers created with [`ValenceTestContext::funded_payer`]
+pub const DEFAULT_PAYER_LAMPORTS: u64 = 10_000_000_000;
+
+/// Anchor's entrypoint ties the accounts slice and its contents to one
+/// lifetime, which `processor!` cannot express, so the slice is leaked for
+/// the duration of the test process.
+fn kernel_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    valence_kernel::entry(program_id, accounts, data)
+}
+
+/// valence-functions' entrypoint, leaking its accounts like [`kernel_entry`]
+fn functions_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    valence_functions::entry(program_id, accounts, data)
+}
+
+/// `ProgramTest` with the Valence programs preloaded
+///
+/// The kernel and valence-functions run natively unless `prefer_bpf` finds
+/// compiled programs in `SBF_OUT_DIR`.
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "valence_kernel",
+        valence_kernel::ID,
+        processor!(kernel_entry),
+    );
+    program_test.add_program(
+        "valence_functions",
+        valence_functions::ID,
+        processor!(functions_entry),
+    );
+    program_test
+}
+
+/// In-process bank with the Valence programs loaded
+pub struct ValenceTestContext {
+    pub context: ProgramTestContext,
+}
+
+impl ValenceTestContext {
+    /// Start a bank with the default [`program_test`] setup
+    pub async fn start() -> Self {
+        Self::start_with(program_test()).await
+    }
+
+    /// Start a bank from a customised `ProgramTest`
+    pub async fn start_with(program_test: ProgramTest) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+        }
+    }
+
+    /// The funded payer the bank was started with
+    pub fn payer(&self) -> &Keypair {
+        &self.context.payer
+    }
+
+    /// Sign instructions with the payer and `signers`, then process them
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<()> {
+        let recent_blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| SdkError::TransactionFailed(e.to_string()))?;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+
+        let mut transaction =
+            Transaction::new_with_payer(instructions, Some(&self.context.payer.pubkey()));
+        transaction
+            .try_sign(&all_signers, recent_blockhash)
+            .map_err(|e| SdkError::TransactionFailed(e.to_string()))?;
+
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| decode_banks_error(e, instructions))
+    }
+
+    /// Transfer `lamports` from the bank's payer to `recipient`
+    pub async fn fund(&mut self, recipient: &Pubkey, lamports: u64) -> Result<()> {
+        let transfer =
+            system_instruction::transfer(&self.context.payer.pubkey(), recipient, lamports);
+        self.process(&[transfer], &[]).await
+    }
+
+    /// Create a keypair funded with [`DEFAULT_PAYER_LAMPORTS`]
+    pub async fn funded_payer(&mut self) -> Result<Keypair> {
+        let payer = Keypair::new();
+        self.fund(&payer.pubkey(), DEFAULT_PAYER_LAMPORTS).await?;
+        Ok(payer)
+    }
+
+    /// Lamports held by `address`, zero if it does not exist
+    pub async fn balance(&mut self, address: &Pubkey) -> Result<u64> {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .map_err(|e| SdkError::TransactionFailed(e.to_string()))
+    }
+
+    /// Fetch and deserialize an Anchor account
+    pub async fn get_account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> Result<T> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .map_err(|e| SdkError::TransactionFailed(e.to_string()))?
+            .ok_or_else(|| SdkError::AccountNotFound(address.to_string()))?;
+        T::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| SdkError::Serialization(e.to_string()))
+    }
+
+    /// Create a ready session owned by `owner` with the builder's registrations
+    pub async fn create_session(
+        &mut self,
+        owner: &Keypair,
+        builder: KernelSessionBuilder,
+    ) -> Result<KernelSession> {
+        let session = builder.build()?;
+        if session.owner != owner.pubkey() {
+            return Err(SdkError::Unauthorized);
+        }
+
+        let [guard, session_account, account_lookup] = session.new_account_signers();
+        self.process(
+            &[session.create_guard.clone(), session.create_session.clone()],
+            &[owner, guard, session_account, account_lookup],
+        )
+        .await?;
+        for registration in &session.registrations {
+            self.process(std::slice::from_ref(registration), &[owner])
+                .await?;
+        }
+        Ok(session)
+    }
+
+    /// Register functions and borrowable accounts in an existing session's ALT
+    pub async fn register(
+        &mut self,
+        owner: &Keypair,
+        session: &KernelSession,
+        borrowable: Vec<RegisteredAccount>,
+        programs: Vec<RegisteredProgram>,
+    ) -> Result<()> {
+        let instruction = session.register_instruction(borrowable, programs);
+        self.process(&[instruction], &[owner]).await
+    }
+}
+
+/// Decode a failed bank transaction into a typed error
+fn decode_banks_error(err: BanksClientError, instructions: &[Instruction]) -> SdkError {
+    match err {
+        BanksClientError::TransactionError(err) => {
+            decode_transaction_error(&err, instructions, &[])
+        }
+        BanksClientError::SimulationError { err, logs, .. } => {
+            decode_transaction_error(&err, instructions, &logs)
+        }
+        err => SdkError::TransactionFailed(err.to_string()),
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{Result, SdkError};
+use anchor_lang::{prelude::*, InstructionData};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address,
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    },
+    token::spl_token::{self, native_mint},
+};
+#[allow(deprecated)]
+use solana_sdk::system_instruction;
+use solana_sdk::{instruction::Instruction, program_pack::Pack};
+use valence_kernel::{instruction as kernel_instruction, Namespace, NamespacePath};
+
+/// Size of an SPL token mint account
+pub const MINT_ACCOUNT_LEN: usize = spl_token::state::Mint::LEN;
+
+/// Instructions creating and initializing a mint at `mint`
+///
+/// `rent_lamports` must cover rent exemption for [`MINT_ACCOUNT_LEN`] bytes.
+pub fn create_mint_instructions(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+    rent_lamports: u64,
+) -> Result<Vec<Instruction>> {
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            rent_lamports,
+            MINT_ACCOUNT_LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            mint,
+            mint_authority,
+            freeze_authority,
+            decimals,
+        )
+        .map_err(|e| SdkError::InvalidOperation(e.to_string()))?,
+    ])
+}
+
+/// Mint `amount` tokens to `destination`
+pub fn mint_to_instruction(
+    mint: &Pubkey,
+    destination: &Pubkey,
+    mint_authority: &Pubkey,
+    amount: u64,
+) -> Result<Instruction> {
+    spl_token::instruction::mint_to(
+        &spl_token::ID,
+        mint,
+        destination,
+        mint_authority,
+        &[],
+        amount,
+    )
+    .map_err(|e| SdkError::InvalidOperation(e.to_string()))
+}
+
+/// Associated token account of `owner` for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, mint)
+}
+
+/// Create `owner`'s associated token account for `mint`, if missing
+pub fn create_associated_token_account_instruction(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    create_associated_token_account_idempotent(payer, owner, mint, &spl_token::ID)
+}
+
+/// Address of a session child account created with `namespace_suffix`
+///
+/// `namespace` is the session's namespace path, as stored on the session.
+pub fn session_child_account(
+    namespace: &NamespacePath,
+    namespace_suffix: &str,
+) -> Result<(Pubkey, u8)> {
+    let child = namespace
+        .child(namespace_suffix)
+        .map_err(|e| SdkError::InvalidOperation(e.to_string()))?;
+    Ok(Namespace::derive_pda(&child, &valence_kernel::ID))
+}
+
+/// Associated token account owned by a session child account
+pub fn session_child_token_account(
+    namespace: &NamespacePath,
+    namespace_suffix: &str,
+    mint: &Pubkey,
+) -> Result<Pubkey> {
+    let (child, _) = session_child_account(namespace, namespace_suffix)?;
+    Ok(associated_token_address(&child, mint))
+}
+
+/// Create a session child account through the kernel
+pub fn create_child_account_instruction(
+    session: &Pubkey,
+    namespace: &NamespacePath,
+    namespace_suffix: &str,
+    payer: &Pubkey,
+    initial_lamports: u64,
+    space: u64,
+    owner_program: Pubkey,
+) -> Result<Instruction> {
+    let (child, _) = session_child_account(namespace, namespace_suffix)?;
+    Ok(Instruction {
+        program_id: valence_kernel::ID,
+        accounts: vec![
+            AccountMeta::new(*session, false),
+            AccountMeta::new(child, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::rent::ID, false),
+        ],
+        data: kernel_instruction::CreateChildAccount {
+            namespace_suffix: namespace_suffix.to_string(),
+            initial_lamports,
+            space,
+            owner_program,
+        }
+        .data(),
+    })
+}
+
+/// Wrap `lamports` into `owner`'s wrapped SOL account, creating it if missing
+pub fn wrap_sol_instructions(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    lamports: u64,
+) -> Result<Vec<Instruction>> {
+    let account = associated_token_address(owner, &native_mint::ID);
+    Ok(vec![
+        create_associated_token_account_instruction(payer, owner, &native_mint::ID),
+        system_instruction::transfer(owner, &account, lamports),
+        spl_token::instruction::sync_native(&spl_token::ID, &account)
+            .map_err(|e| SdkError::InvalidOperation(e.to_string()))?,
+    ])
+}
+
+/// Close `owner`'s wrapped SOL account, returning all its lamports to `owner`
+pub fn unwrap_sol_instruction(owner: &Pubkey) -> Result<Instruction> {
+    let account = associated_token_address(owner, &native_mint::ID);
+    spl_token::instruction::close_account(&spl_token::ID, &account, owner, owner, &[])
+        .map_err(|e| SdkError::InvalidOperation(e.to_string()))
+}
+
+/// Token transfer checked against the mint's decimals
+///
+/// Accounts follow the kernel's `spl_transfer` order: source, destination,
+/// then authority, with the mint that `transfer_checked` requires.
+pub fn transfer_checked_instruction(
+    from: &Pubkey,
+    mint: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction> {
+    spl_token::instruction::transfer_checked(
+        &spl_token::ID,
+        from,
+        mint,
+        to,
+        authority,
+        &[],
+        amount,
+        decimals,
+    )
+    .map_err(|e| SdkError::InvalidOperation(e.to_string()))
+}
+
+/// Token transfer through the kernel's `spl_transfer`, authorized by the session owner
+pub fn kernel_spl_transfer_instruction(
+    session: &Pubkey,
+    guard_account: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: valence_kernel::ID,
+        accounts: vec![
+            AccountMeta::new(*session, false),
+            AccountMeta::new_readonly(*guard_account, false),
+            AccountMeta::new(*from, false),
+            AccountMeta::new(*to, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::clock::ID, false),
+        ],
+        data: kernel_instruction::SplTransfer { amount }.data(),
+    }
+}
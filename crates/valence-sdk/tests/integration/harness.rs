@@ -0,0 +1,331 @@
+// Scenario builders and account snapshots for the integration suite
+
+use anchor_lang::{prelude::*, InstructionData};
+use solana_sdk::{instruction::Instruction, signature::Keypair, signer::Signer};
+use valence_common::pdas;
+use valence_functions::{EscrowAgreement, EscrowInput, EscrowLockInput};
+use valence_kernel::{
+    instruction as kernel_instruction, state::RegisteredAccount, Session, ACCESS_MODE_READ_WRITE,
+};
+use valence_sdk::{
+    cpi_allowlist_address, create_child_account_instruction, initialize_allowlist_instruction,
+    session_child_account, testing::ValenceTestContext, KernelSession, KernelSessionBuilder,
+    OperationBatch, Result,
+};
+
+/// Builder for a bank with an allowlist and one ready session
+pub struct ScenarioBuilder {
+    borrowable: Vec<Pubkey>,
+    approval_signer: Option<Keypair>,
+    require_commitment: bool,
+    stats: bool,
+    invalidated: bool,
+}
+
+impl ScenarioBuilder {
+    pub fn new() -> Self {
+        Self {
+            borrowable: Vec::new(),
+            approval_signer: None,
+            require_commitment: false,
+            stats: false,
+            invalidated: false,
+        }
+    }
+
+    /// Register `account` as borrowable read-write by the session
+    pub fn borrowable(mut self, account: Pubkey) -> Self {
+        self.borrowable.push(account);
+        self
+    }
+
+    /// Require every batch to carry an approval from a fresh signer
+    pub fn approval_signer(mut self) -> Self {
+        self.approval_signer = Some(Keypair::new());
+        self
+    }
+
+    /// Require every batch to be committed before it executes
+    pub fn require_commitment(mut self) -> Self {
+        self.require_commitment = true;
+        self
+    }
+
+    /// Keep statistics for the session, so every batch must pass them
+    pub fn stats(mut self) -> Self {
+        self.stats = true;
+        self
+    }
+
+    /// Invalidate the session once it is created
+    pub fn invalidated(mut self) -> Self {
+        self.invalidated = true;
+        self
+    }
+
+    pub async fn build(self) -> Result<Scenario> {
+        let mut ctx = ValenceTestContext::start().await;
+        let owner = ctx.funded_payer().await?;
+
+        let initialize_allowlist = initialize_allowlist_instruction(owner.pubkey());
+        ctx.process(&[initialize_allowlist], &[&owner]).await?;
+
+        let builder = self.borrowable.iter().fold(
+            KernelSessionBuilder::new(owner.pubkey(), Pubkey::new_unique(), "integration"),
+            |builder, address| {
+                builder.borrowable(RegisteredAccount {
+                    address: *address,
+                    permissions: ACCESS_MODE_READ_WRITE,
+                    label: *b"scenario",
+                })
+            },
+        );
+        let session = ctx.create_session(&owner, builder).await?;
+
+        if let Some(approval_signer) = &self.approval_signer {
+            let instruction =
+                session.set_approval_signer_instruction(Some(approval_signer.pubkey()), None);
+            ctx.process(&[instruction], &[&owner]).await?;
+        }
+
+        if self.require_commitment {
+            let instruction = session.set_require_commitment_instruction(true);
+            ctx.process(&[instruction], &[&owner]).await?;
+        }
+
+        if self.stats {
+            let instruction = session.initialize_stats_instruction(owner.pubkey());
+            ctx.process(&[instruction], &[&owner]).await?;
+        }
+
+        if self.invalidated {
+            let invalidate = Instruction {
+                program_id: valence_kernel::ID,
+                accounts: vec![
+                    AccountMeta::new(session.session.pubkey(), false),
+                    AccountMeta::new_readonly(owner.pubkey(), true),
+                ],
+                data: kernel_instruction::InvalidateSession {}.data(),
+            };
+            ctx.process(&[invalidate], &[&owner]).await?;
+        }
+
+        Ok(Scenario {
+            ctx,
+            owner,
+            session,
+            approval_signer: self.approval_signer,
+        })
+    }
+}
+
+/// A running bank with a session ready to execute batches
+pub struct Scenario {
+    pub ctx: ValenceTestContext,
+    pub owner: Keypair,
+    pub session: KernelSession,
+    pub approval_signer: Option<Keypair>,
+}
+
+impl Scenario {
+    /// Execute `batch` as the session owner
+    pub async fn execute(&mut self, batch: OperationBatch) -> Result<()> {
+        let instruction = self.session.execute_batch_instruction(
+            batch,
+            cpi_allowlist_address(),
+            self.owner.pubkey(),
+            Vec::new(),
+        );
+        self.ctx.process(&[instruction], &[&self.owner]).await
+    }
+
+    /// Execute `batch` as the session owner, passing the session's statistics
+    pub async fn execute_counted(&mut self, batch: OperationBatch) -> Result<()> {
+        let instruction = self.session.execute_batch_instruction(
+            batch,
+            cpi_allowlist_address(),
+            self.owner.pubkey(),
+            vec![AccountMeta::new(self.session.stats_address(), false)],
+        );
+        self.ctx.process(&[instruction], &[&self.owner]).await
+    }
+
+    /// Execute `batch` as the owner with the scenario's approval attached
+    pub async fn execute_approved(&mut self, batch: OperationBatch) -> Result<()> {
+        let usage_count = self.snapshot().await?.usage_count;
+        let approver = self
+            .approval_signer
+            .as_ref()
+            .expect("scenario has no approval signer");
+        let approval = self
+            .session
+            .batch_approval_instruction(approver, &batch, usage_count)?;
+        let instruction = self.session.execute_batch_instruction(
+            batch,
+            cpi_allowlist_address(),
+            self.owner.pubkey(),
+            vec![AccountMeta::new_readonly(
+                solana_sdk::sysvar::instructions::ID,
+                false,
+            )],
+        );
+        self.ctx
+            .process(&[approval, instruction], &[&self.owner])
+            .await
+    }
+
+    /// Commit to `batch`, then execute it as the owner revealing the commitment
+    pub async fn execute_committed(&mut self, batch: OperationBatch) -> Result<()> {
+        let commit = self
+            .session
+            .commit_batch_instruction(&batch, 0, self.owner.pubkey())?;
+        self.ctx.process(&[commit], &[&self.owner]).await?;
+
+        let commitment = self.session.commitment_address(&batch)?;
+        let instruction = self.session.execute_batch_instruction(
+            batch,
+            cpi_allowlist_address(),
+            self.owner.pubkey(),
+            vec![AccountMeta::new(commitment, false)],
+        );
+        self.ctx.process(&[instruction], &[&self.owner]).await
+    }
+
+    /// Create a second guard naming the session, with no approval signer
+    pub async fn substitute_guard(&mut self) -> Result<Pubkey> {
+        let guard = Keypair::new();
+        let create_guard = Instruction {
+            program_id: valence_kernel::ID,
+            accounts: vec![
+                AccountMeta::new(guard.pubkey(), true),
+                AccountMeta::new(self.owner.pubkey(), true),
+                AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            ],
+            data: kernel_instruction::CreateGuardAccount {
+                session: self.session.session.pubkey(),
+                allow_unregistered_cpi: false,
+            }
+            .data(),
+        };
+        self.ctx
+            .process(&[create_guard], &[&self.owner, &guard])
+            .await?;
+        Ok(guard.pubkey())
+    }
+
+    /// Execute `batch` as the session owner, passing `guard` in place of the session's guard
+    pub async fn execute_with_guard(&mut self, batch: OperationBatch, guard: Pubkey) -> Result<()> {
+        let mut instruction = self.session.execute_batch_instruction(
+            batch,
+            cpi_allowlist_address(),
+            self.owner.pubkey(),
+            Vec::new(),
+        );
+        instruction.accounts[1] = AccountMeta::new_readonly(guard, false);
+        self.ctx.process(&[instruction], &[&self.owner]).await
+    }
+
+    /// Create a child account of the session under `namespace_suffix`
+    pub async fn child_account(&mut self, namespace_suffix: &str) -> Result<Pubkey> {
+        let session = self.session.session.pubkey();
+        let namespace = self.ctx.get_account::<Session>(&session).await?.namespace;
+        let instruction = create_child_account_instruction(
+            &session,
+            &namespace,
+            namespace_suffix,
+            &self.owner.pubkey(),
+            0,
+            8,
+            valence_kernel::ID,
+        )?;
+        self.ctx.process(&[instruction], &[&self.owner]).await?;
+        Ok(session_child_account(&namespace, namespace_suffix)?.0)
+    }
+
+    /// Lock an escrow over `input.child_account` against the session, signed by `depositor`
+    pub async fn lock_escrow(
+        &mut self,
+        depositor: &Keypair,
+        input: EscrowLockInput,
+    ) -> Result<Pubkey> {
+        let (agreement, _) = pdas::escrow_agreement(
+            &depositor.pubkey(),
+            &input.child_account,
+            &valence_functions::ID,
+        );
+        let lock = Instruction {
+            program_id: valence_functions::ID,
+            accounts: valence_functions::accounts::LockEscrow {
+                agreement,
+                vault: pdas::escrow_vault(&agreement, &valence_functions::ID).0,
+                session: self.session.session.pubkey(),
+                depositor: depositor.pubkey(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: valence_functions::instruction::LockEscrow { input }.data(),
+        };
+        self.ctx.process(&[lock], &[depositor]).await?;
+        Ok(agreement)
+    }
+
+    /// Apply `input` to the escrow at `agreement`, signed by `caller`
+    pub async fn escrow_operation(
+        &mut self,
+        agreement: Pubkey,
+        caller: &Keypair,
+        input: EscrowInput,
+    ) -> Result<()> {
+        let state: EscrowAgreement = self.ctx.get_account(&agreement).await?;
+        let operation = Instruction {
+            program_id: valence_functions::ID,
+            accounts: valence_functions::accounts::EscrowOperation {
+                agreement,
+                vault: pdas::escrow_vault(&agreement, &valence_functions::ID).0,
+                depositor: state.depositor,
+                beneficiary: state.beneficiary,
+                caller: caller.pubkey(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: valence_functions::instruction::EscrowOperation { input }.data(),
+        };
+        self.ctx.process(&[operation], &[caller]).await
+    }
+
+    /// Current state of the session account
+    pub async fn snapshot(&mut self) -> Result<SessionSnapshot> {
+        let session: Session = self
+            .ctx
+            .get_account(&self.session.session.pubkey())
+            .await?;
+        Ok(SessionSnapshot::from(&session))
+    }
+}
+
+/// The parts of a session a scenario asserts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionSnapshot {
+    pub active: bool,
+    pub usage_count: u64,
+    pub borrowed_count: u32,
+}
+
+impl SessionSnapshot {
+    /// A freshly created session
+    pub const NEW: Self = Self {
+        active: true,
+        usage_count: 0,
+        borrowed_count: 0,
+    };
+}
+
+impl From<&Session> for SessionSnapshot {
+    fn from(session: &Session) -> Self {
+        Self {
+            active: session.active,
+            usage_count: session.usage_count,
+            borrowed_count: session.borrowed_bitmap.count_ones(),
+        }
+    }
+}
@@ -0,0 +1,6 @@
+// Integration scenarios running the kernel in an in-process bank
+//
+// Run with `cargo test -p valence-sdk --features testing --test integration`.
+
+mod harness;
+mod scenarios;
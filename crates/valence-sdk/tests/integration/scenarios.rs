@@ -0,0 +1,419 @@
+// End-to-end kernel scenarios, asserting on the resulting session state
+
+use crate::harness::{ScenarioBuilder, SessionSnapshot};
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult, InstructionData};
+use solana_program_test::processor;
+use solana_sdk::{account::Account, instruction::Instruction, signature::Keypair, signer::Signer};
+use valence_functions::{
+    functions::{
+        escrow::EscrowError,
+        price_bound_guard::{
+            PriceGuardError, PYTH_MAGIC, PYTH_PRICE_ACCOUNT_TYPE, PYTH_STATUS_TRADING,
+        },
+        swap_adapter::{SwapAdapterError, ORCA_WHIRLPOOL_PROGRAM_ID, WHIRLPOOL_POOL_ACCOUNTS},
+    },
+    EscrowInput, EscrowLockInput, PriceBoundInput, SwapVenue,
+};
+use valence_kernel::{state::SessionStats, KernelError};
+use valence_sdk::{
+    testing::{program_test, ValenceTestContext},
+    AccessMode, BatchBuilder, OperationBatch, SdkError,
+};
+
+fn borrow_and_release(account: Pubkey) -> OperationBatch {
+    let mut batch = BatchBuilder::new();
+    batch.borrow(account, AccessMode::ReadWrite).release(account);
+    batch.build().unwrap()
+}
+
+fn borrow(account: Pubkey) -> OperationBatch {
+    let mut batch = BatchBuilder::new();
+    batch.borrow(account, AccessMode::ReadWrite);
+    batch.build().unwrap()
+}
+
+fn assert_kernel_error(result: valence_sdk::Result<()>, expected: KernelError) {
+    match result {
+        Err(SdkError::Kernel(err)) => assert_eq!(u32::from(err), u32::from(expected)),
+        other => panic!("expected {expected:?}, got {other:?}"),
+    }
+}
+
+/// valence-functions modules share one error code range, so their errors
+/// may decode as another functions error with the same code
+fn assert_functions_error<E: Into<u32> + std::fmt::Debug>(
+    result: valence_sdk::Result<()>,
+    expected: E,
+) {
+    let name = format!("{expected:?}");
+    let expected = expected.into();
+    match result {
+        Err(SdkError::Functions(err)) => assert_eq!(u32::from(err), expected),
+        Err(SdkError::ProgramFailed { code, .. }) => assert_eq!(code, expected),
+        other => panic!("expected {name}, got {other:?}"),
+    }
+}
+
+/// Stands in for Orca Whirlpool, accepting swaps signed by the user
+fn mock_whirlpool(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let user = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if data.len() != 8 + 8 + 8 + 16 + 1 + 1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_happy_path() {
+    let account = Pubkey::new_unique();
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(account)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(scenario.snapshot().await.unwrap(), SessionSnapshot::NEW);
+
+    scenario.execute(borrow_and_release(account)).await.unwrap();
+    scenario.execute(borrow(account)).await.unwrap();
+
+    assert_eq!(
+        scenario.snapshot().await.unwrap(),
+        SessionSnapshot {
+            active: true,
+            usage_count: 2,
+            borrowed_count: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_unregistered_account_rejected() {
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(Pubkey::new_unique())
+        .build()
+        .await
+        .unwrap();
+
+    let result = scenario.execute(borrow(Pubkey::new_unique())).await;
+    assert!(result.is_err());
+    assert_eq!(scenario.snapshot().await.unwrap(), SessionSnapshot::NEW);
+}
+
+#[tokio::test]
+async fn test_guard_rejection() {
+    let account = Pubkey::new_unique();
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(account)
+        .approval_signer()
+        .build()
+        .await
+        .unwrap();
+
+    assert_kernel_error(
+        scenario.execute(borrow_and_release(account)).await,
+        KernelError::MissingBatchApproval,
+    );
+    assert_eq!(scenario.snapshot().await.unwrap(), SessionSnapshot::NEW);
+
+    // The same batch passes once approved
+    scenario
+        .execute_approved(borrow_and_release(account))
+        .await
+        .unwrap();
+    assert_eq!(scenario.snapshot().await.unwrap().usage_count, 1);
+}
+
+#[tokio::test]
+async fn test_substituted_guard_rejected() {
+    let account = Pubkey::new_unique();
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(account)
+        .approval_signer()
+        .build()
+        .await
+        .unwrap();
+
+    // Another guard naming the session cannot stand in for the session's own
+    let guard = scenario.substitute_guard().await.unwrap();
+    assert_kernel_error(
+        scenario
+            .execute_with_guard(borrow_and_release(account), guard)
+            .await,
+        KernelError::InvalidSessionConfig,
+    );
+    assert_eq!(scenario.snapshot().await.unwrap(), SessionSnapshot::NEW);
+}
+
+#[tokio::test]
+async fn test_commitment_required() {
+    let account = Pubkey::new_unique();
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(account)
+        .require_commitment()
+        .build()
+        .await
+        .unwrap();
+
+    assert_kernel_error(
+        scenario.execute(borrow_and_release(account)).await,
+        KernelError::MissingBatchCommitment,
+    );
+
+    // A guard without the requirement cannot stand in for the session's own
+    let guard = scenario.substitute_guard().await.unwrap();
+    assert_kernel_error(
+        scenario
+            .execute_with_guard(borrow_and_release(account), guard)
+            .await,
+        KernelError::InvalidSessionConfig,
+    );
+    assert_eq!(scenario.snapshot().await.unwrap(), SessionSnapshot::NEW);
+
+    scenario
+        .execute_committed(borrow_and_release(account))
+        .await
+        .unwrap();
+    assert_eq!(scenario.snapshot().await.unwrap().usage_count, 1);
+}
+
+#[tokio::test]
+async fn test_stats_required_once_enabled() {
+    let account = Pubkey::new_unique();
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(account)
+        .stats()
+        .build()
+        .await
+        .unwrap();
+
+    assert_kernel_error(
+        scenario.execute(borrow_and_release(account)).await,
+        KernelError::MissingSessionStats,
+    );
+    assert_eq!(scenario.snapshot().await.unwrap(), SessionSnapshot::NEW);
+
+    scenario
+        .execute_counted(borrow_and_release(account))
+        .await
+        .unwrap();
+    let stats_address = scenario.session.stats_address();
+    let stats: SessionStats = scenario.ctx.get_account(&stats_address).await.unwrap();
+    assert_eq!(stats.batches_executed, 1);
+    assert_eq!(stats.operations.total(), 2);
+    assert_eq!(scenario.snapshot().await.unwrap().usage_count, 1);
+}
+
+#[tokio::test]
+async fn test_invalidated_session() {
+    let account = Pubkey::new_unique();
+    let mut scenario = ScenarioBuilder::new()
+        .borrowable(account)
+        .invalidated()
+        .build()
+        .await
+        .unwrap();
+
+    assert!(scenario.execute(borrow_and_release(account)).await.is_err());
+    assert_eq!(
+        scenario.snapshot().await.unwrap(),
+        SessionSnapshot {
+            active: false,
+            ..SessionSnapshot::NEW
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_close_session_reclaims_rent() {
+    let mut active = ScenarioBuilder::new().build().await.unwrap();
+    let close = active.session.close_instruction();
+    assert_kernel_error(
+        active.ctx.process(&[close], &[&active.owner]).await,
+        KernelError::SessionNotClosable,
+    );
+    assert_eq!(active.snapshot().await.unwrap(), SessionSnapshot::NEW);
+
+    let mut scenario = ScenarioBuilder::new().invalidated().build().await.unwrap();
+    let accounts = scenario.session.accounts();
+    let owner = scenario.owner.pubkey();
+    let mut rent = 0;
+    for address in [accounts.session, accounts.account_lookup, accounts.guard] {
+        rent += scenario.ctx.balance(&address).await.unwrap();
+    }
+    let owner_before = scenario.ctx.balance(&owner).await.unwrap();
+
+    let close = scenario.session.close_instruction();
+    scenario.ctx.process(&[close], &[&scenario.owner]).await.unwrap();
+
+    assert_eq!(scenario.ctx.balance(&owner).await.unwrap(), owner_before + rent);
+    for address in [accounts.session, accounts.account_lookup, accounts.guard] {
+        assert_eq!(scenario.ctx.balance(&address).await.unwrap(), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_escrow_holds_funds_until_release() {
+    const AMOUNT: u64 = 2_000_000_000;
+    let mut scenario = ScenarioBuilder::new().build().await.unwrap();
+    let child_account = scenario.child_account("escrow").await.unwrap();
+    let owner = scenario.owner.insecure_clone();
+    let beneficiary = Keypair::new();
+    let input = |child_account| EscrowLockInput {
+        beneficiary: beneficiary.pubkey(),
+        arbiter: None,
+        child_account,
+        amount: AMOUNT,
+        timeout_seconds: 3_600,
+    };
+
+    // Only a child account of the depositor's own session can be escrowed
+    assert_functions_error(
+        scenario
+            .lock_escrow(&owner, input(Pubkey::new_unique()))
+            .await
+            .map(drop),
+        EscrowError::InvalidTerms,
+    );
+    let stranger = scenario.ctx.funded_payer().await.unwrap();
+    assert_functions_error(
+        scenario
+            .lock_escrow(&stranger, input(child_account))
+            .await
+            .map(drop),
+        EscrowError::Unauthorized,
+    );
+
+    let before = scenario.ctx.balance(&owner.pubkey()).await.unwrap();
+    let agreement = scenario
+        .lock_escrow(&owner, input(child_account))
+        .await
+        .unwrap();
+    let vault = valence_common::pdas::escrow_vault(&agreement, &valence_functions::ID).0;
+    assert!(scenario.ctx.balance(&vault).await.unwrap() > AMOUNT);
+    assert!(scenario.ctx.balance(&owner.pubkey()).await.unwrap() < before - AMOUNT);
+
+    scenario
+        .escrow_operation(agreement, &owner, EscrowInput::Approve)
+        .await
+        .unwrap();
+    assert_eq!(
+        scenario.ctx.balance(&beneficiary.pubkey()).await.unwrap(),
+        0
+    );
+
+    // The second approval pays the beneficiary and empties the vault
+    scenario
+        .escrow_operation(agreement, &beneficiary, EscrowInput::Approve)
+        .await
+        .unwrap();
+    assert_eq!(
+        scenario.ctx.balance(&beneficiary.pubkey()).await.unwrap(),
+        AMOUNT
+    );
+    assert_eq!(scenario.ctx.balance(&vault).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_swap_invokes_venue() {
+    let mut program_test = program_test();
+    program_test.add_program(
+        "whirlpool",
+        ORCA_WHIRLPOOL_PROGRAM_ID,
+        processor!(mock_whirlpool),
+    );
+    let mut ctx = ValenceTestContext::start_with(program_test).await;
+    let user = Keypair::new();
+    let pool_accounts: Vec<Pubkey> = (0..WHIRLPOOL_POOL_ACCOUNTS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let swap = |venue_program| {
+        let mut accounts = valence_functions::accounts::Swap {
+            user: user.pubkey(),
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            venue_program,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(pool_accounts.iter().map(|key| AccountMeta::new(*key, false)));
+        Instruction {
+            program_id: valence_functions::ID,
+            accounts,
+            data: valence_functions::instruction::Swap {
+                amount_in: 1_000,
+                min_out: 990,
+                venue: SwapVenue::OrcaWhirlpool {
+                    a_to_b: true,
+                    sqrt_price_limit: 0,
+                },
+            }
+            .data(),
+        }
+    };
+
+    assert_functions_error(
+        ctx.process(&[swap(Pubkey::new_unique())], &[&user]).await,
+        SwapAdapterError::InvalidVenueProgram,
+    );
+    ctx.process(&[swap(ORCA_WHIRLPOOL_PROGRAM_ID)], &[&user])
+        .await
+        .unwrap();
+}
+
+/// Pyth v2 price account trading at `price * 10^-8`, published at slot 0
+fn pyth_price_account(price: i64) -> Vec<u8> {
+    let mut data = vec![0u8; 240];
+    data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes());
+    data[20..24].copy_from_slice(&(-8i32).to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[224..228].copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+    data
+}
+
+#[tokio::test]
+async fn test_price_bound_checked_on_chain() {
+    let oracle_program = Pubkey::new_unique();
+    let (in_bounds, out_of_bounds) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut program_test = program_test();
+    for (address, price) in [(in_bounds, 10_000_000_000), (out_of_bounds, 12_000_000_000)] {
+        program_test.add_account(
+            address,
+            Account {
+                lamports: 1_000_000_000,
+                data: pyth_price_account(price),
+                owner: oracle_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+    let mut ctx = ValenceTestContext::start_with(program_test).await;
+
+    // Bounds of $90.00 to $110.00
+    let check = |price_account| Instruction {
+        program_id: valence_functions::ID,
+        accounts: valence_functions::accounts::CheckPriceBound { price_account }
+            .to_account_metas(None),
+        data: valence_functions::instruction::CheckPriceBound {
+            input: PriceBoundInput {
+                oracle_program,
+                min_price: 9_000,
+                max_price: 11_000,
+                expo: -2,
+                max_staleness_slots: 1_000,
+                max_confidence_bps: 100,
+            },
+        }
+        .data(),
+    };
+
+    ctx.process(&[check(in_bounds)], &[]).await.unwrap();
+    assert_functions_error(
+        ctx.process(&[check(out_of_bounds)], &[]).await,
+        PriceGuardError::PriceOutOfBounds,
+    );
+}
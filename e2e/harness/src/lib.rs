@@ -0,0 +1,13 @@
+//! E2E harness - runs YAML scenarios against a local validator
+//!
+//! A [`Localnet`] boots `solana-test-validator` with the Valence programs (or
+//! connects to one started with docker compose), and a [`ScenarioRunner`]
+//! executes each [`Scenario`] through the SDK with a freshly funded payer.
+
+pub mod localnet;
+pub mod runner;
+pub mod scenario;
+
+pub use localnet::*;
+pub use runner::*;
+pub use scenario::*;
@@ -0,0 +1,126 @@
+//! Local validator with the Valence programs deployed
+//!
+//! Set `VALENCE_E2E_RPC_URL` to use an already running validator, such as
+//! the one started by `docker compose up` in this directory. Otherwise
+//! `solana-test-validator` is spawned and killed when the handle drops.
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+/// Environment variable naming an existing validator's RPC URL
+pub const RPC_URL_ENV: &str = "VALENCE_E2E_RPC_URL";
+
+const DEFAULT_RPC_PORT: u16 = 8899;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A program and the compiled `.so` deployed at its address
+#[derive(Debug, Clone)]
+pub struct ProgramDeployment {
+    pub program_id: Pubkey,
+    pub path: PathBuf,
+}
+
+/// Programs deployed by `just build`, relative to the e2e directory
+pub fn default_programs(e2e_dir: &Path) -> Vec<ProgramDeployment> {
+    let deploy = e2e_dir.join("../target/deploy");
+    vec![
+        ProgramDeployment {
+            program_id: valence_kernel::ID,
+            path: deploy.join("valence_kernel.so"),
+        },
+        ProgramDeployment {
+            program_id: valence_functions::ID,
+            path: deploy.join("valence_functions.so"),
+        },
+        ProgramDeployment {
+            program_id: test_shard::ID,
+            path: e2e_dir.join("target/deploy/test_shard.so"),
+        },
+    ]
+}
+
+/// A running validator, spawned by the harness or reached over RPC
+pub struct Localnet {
+    pub rpc_url: String,
+    validator: Option<Child>,
+}
+
+impl Localnet {
+    /// Use `VALENCE_E2E_RPC_URL` if set, otherwise spawn a validator with `programs`
+    pub async fn boot(programs: &[ProgramDeployment], ledger: &Path) -> Result<Self> {
+        let localnet = match std::env::var(RPC_URL_ENV) {
+            Ok(rpc_url) => Self {
+                rpc_url,
+                validator: None,
+            },
+            Err(_) => Self::spawn(programs, ledger)?,
+        };
+        localnet.wait_until_healthy().await?;
+        Ok(localnet)
+    }
+
+    fn spawn(programs: &[ProgramDeployment], ledger: &Path) -> Result<Self> {
+        let mut command = Command::new("solana-test-validator");
+        command
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(ledger)
+            .arg("--rpc-port")
+            .arg(DEFAULT_RPC_PORT.to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        for program in programs {
+            if !program.path.exists() {
+                return Err(anyhow!(
+                    "{} not found; run `just build` first",
+                    program.path.display()
+                ));
+            }
+            command
+                .arg("--bpf-program")
+                .arg(program.program_id.to_string())
+                .arg(&program.path);
+        }
+
+        let validator = command
+            .spawn()
+            .context("spawning solana-test-validator")?;
+        Ok(Self {
+            rpc_url: format!("http://127.0.0.1:{DEFAULT_RPC_PORT}"),
+            validator: Some(validator),
+        })
+    }
+
+    /// RPC client for the validator at confirmed commitment
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.rpc_url.clone(), CommitmentConfig::confirmed())
+    }
+
+    async fn wait_until_healthy(&self) -> Result<()> {
+        let rpc_client = self.rpc_client();
+        let deadline = tokio::time::Instant::now() + STARTUP_TIMEOUT;
+        while rpc_client.get_health().await.is_err() {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow!("validator at {} did not become healthy", self.rpc_url));
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Localnet {
+    fn drop(&mut self) {
+        if let Some(validator) = &mut self.validator {
+            let _ = validator.kill();
+            let _ = validator.wait();
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Executes scenarios against a localnet through the SDK
+
+use crate::scenario::{Operation, Scenario, Step};
+use anchor_lang::prelude::*;
+use anyhow::{anyhow, bail, Context, Result};
+#[allow(deprecated)]
+use solana_sdk::system_instruction;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use valence_kernel::{RegisteredAccount, Session};
+use valence_sdk::{
+    cpi_allowlist_address, initialize_allowlist_instruction, BatchBuilder, KernelSession,
+    SdkError, ValenceClientAsync,
+};
+
+/// Lamports airdropped to each scenario's payer
+pub const PAYER_LAMPORTS: u64 = 100_000_000_000;
+
+/// Runs scenarios, each with a fresh funded payer that owns its sessions
+pub struct ScenarioRunner {
+    rpc_url: String,
+}
+
+impl ScenarioRunner {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+        }
+    }
+
+    /// Run every step of `scenario`, stopping at the first failure
+    pub async fn run(&self, scenario: &Scenario) -> Result<()> {
+        let payer = Arc::new(Keypair::new());
+        let client = ValenceClientAsync::new(self.rpc_url.clone(), payer.clone(), None);
+        airdrop(&client, &payer.pubkey(), PAYER_LAMPORTS).await?;
+        ensure_cpi_allowlist(&client).await?;
+
+        let mut state = ScenarioState {
+            client,
+            accounts: HashMap::new(),
+            sessions: HashMap::new(),
+        };
+        for (index, step) in scenario.steps.iter().enumerate() {
+            state
+                .run_step(step)
+                .await
+                .with_context(|| format!("{}: step {index} ({step:?})", scenario.name))?;
+        }
+        Ok(())
+    }
+}
+
+struct ScenarioState {
+    client: ValenceClientAsync,
+    accounts: HashMap<String, Keypair>,
+    sessions: HashMap<String, KernelSession>,
+}
+
+impl ScenarioState {
+    /// Address of a named account, generated on first use
+    fn account(&mut self, name: &str) -> Pubkey {
+        self.accounts
+            .entry(name.to_string())
+            .or_insert_with(Keypair::new)
+            .pubkey()
+    }
+
+    fn session(&self, name: &str) -> Result<&KernelSession> {
+        self.sessions
+            .get(name)
+            .ok_or_else(|| anyhow!("unknown session {name}"))
+    }
+
+    async fn run_step(&mut self, step: &Step) -> Result<()> {
+        match step {
+            Step::Fund { account, lamports } => {
+                let recipient = self.account(account);
+                let transfer =
+                    system_instruction::transfer(&self.client.payer(), &recipient, *lamports);
+                self.client.send_instructions(&[transfer], &[]).await?;
+            }
+
+            Step::CreateSession {
+                session,
+                borrowable,
+            } => {
+                let addresses: Vec<Pubkey> =
+                    borrowable.iter().map(|name| self.account(name)).collect();
+                let builder = addresses.into_iter().fold(
+                    self.client
+                        .kernel_session_builder(test_shard::ID, format!("e2e/{session}")),
+                    |builder, address| {
+                        builder.borrowable(RegisteredAccount {
+                            address,
+                            permissions: valence_kernel::ACCESS_MODE_READ_WRITE,
+                            label: [0u8; 8],
+                        })
+                    },
+                );
+                let kernel_session = builder.build()?;
+                self.client.create_kernel_session(&kernel_session).await?;
+                self.sessions.insert(session.clone(), kernel_session);
+            }
+
+            Step::ExecuteBatch {
+                session,
+                operations,
+                expect_error,
+            } => {
+                let mut batch = BatchBuilder::new();
+                for operation in operations {
+                    match operation {
+                        Operation::Borrow { account, mode } => {
+                            batch.borrow(self.account(account), (*mode).into());
+                        }
+                        Operation::Release { account } => {
+                            batch.release(self.account(account));
+                        }
+                    }
+                }
+                let instruction = self.session(session)?.execute_batch_instruction(
+                    batch.build()?,
+                    cpi_allowlist_address(),
+                    self.client.payer(),
+                    Vec::new(),
+                );
+                let result = self.client.send_instructions(&[instruction], &[]).await;
+                match (result, expect_error) {
+                    (Ok(_), None) => {}
+                    (Ok(_), Some(expected)) => bail!("expected {expected}, batch succeeded"),
+                    (Err(SdkError::Kernel(err)), Some(expected))
+                        if format!("{err:?}") == *expected => {}
+                    (Err(err), _) => return Err(err.into()),
+                }
+            }
+
+            Step::AssertBalance { account, lamports } => {
+                let address = self.account(account);
+                let balance = self.client.rpc_client().get_balance(&address).await?;
+                if balance != *lamports {
+                    bail!("{account} has {balance} lamports, expected {lamports}");
+                }
+            }
+
+            Step::AssertSession {
+                session,
+                usage_count,
+                active,
+            } => {
+                let address = self.session(session)?.session.pubkey();
+                let state: Session = self.client.get_account(&address).await?;
+                if let Some(expected) = usage_count {
+                    if state.usage_count != *expected {
+                        bail!("{session} usage count is {}, expected {expected}", state.usage_count);
+                    }
+                }
+                if let Some(expected) = active {
+                    if state.active != *expected {
+                        bail!("{session} active is {}, expected {expected}", state.active);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Airdrop `lamports` to `recipient` and wait for it to land
+async fn airdrop(client: &ValenceClientAsync, recipient: &Pubkey, lamports: u64) -> Result<()> {
+    let rpc_client = client.rpc_client();
+    let signature = rpc_client.request_airdrop(recipient, lamports).await?;
+    for _ in 0..60 {
+        if rpc_client.confirm_transaction(&signature).await? {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    bail!("airdrop to {recipient} was not confirmed")
+}
+
+/// Initialize the kernel's CPI allowlist unless a previous run already did
+async fn ensure_cpi_allowlist(client: &ValenceClientAsync) -> Result<()> {
+    let cpi_allowlist = cpi_allowlist_address();
+    if client.rpc_client().get_account(&cpi_allowlist).await.is_ok() {
+        return Ok(());
+    }
+
+    let instruction = initialize_allowlist_instruction(client.payer());
+    client.send_instructions(&[instruction], &[]).await?;
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! YAML scenario format
+//!
+//! Accounts and sessions are referred to by name; the runner generates a
+//! keypair the first time a name is used.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use valence_sdk::AccessMode;
+
+/// A named sequence of steps run against a fresh payer
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// Steps and operations are written as single-key maps, e.g. `- fund: {...}`
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub steps: Vec<Step>,
+}
+
+impl Scenario {
+    /// Parse a scenario from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Read and parse a scenario file
+    pub fn load(path: &Path) -> Result<Self> {
+        let yaml = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Self::from_yaml(&yaml).with_context(|| format!("parsing {}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// Transfer lamports from the payer to a named account
+    Fund { account: String, lamports: u64 },
+    /// Create a session owned by the payer
+    CreateSession {
+        session: String,
+        #[serde(default)]
+        borrowable: Vec<String>,
+    },
+    /// Execute a batch in a session, optionally expecting a kernel error
+    ExecuteBatch {
+        session: String,
+        operations: Vec<Operation>,
+        #[serde(default)]
+        expect_error: Option<String>,
+    },
+    /// Check a named account's lamport balance
+    AssertBalance { account: String, lamports: u64 },
+    /// Check a session's usage count and whether it is active
+    AssertSession {
+        session: String,
+        #[serde(default)]
+        usage_count: Option<u64>,
+        #[serde(default)]
+        active: Option<bool>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Borrow { account: String, mode: Mode },
+    Release { account: String },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl From<Mode> for AccessMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Read => AccessMode::Read,
+            Mode::Write => AccessMode::Write,
+            Mode::ReadWrite => AccessMode::ReadWrite,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario = Scenario::from_yaml(
+            r#"
+name: borrow
+steps:
+  - fund: { account: alice, lamports: 1000 }
+  - create_session: { session: main, borrowable: [vault] }
+  - execute_batch:
+      session: main
+      operations:
+        - borrow: { account: vault, mode: read_write }
+        - release: { account: vault }
+  - execute_batch:
+      session: main
+      operations:
+        - borrow: { account: alice, mode: read }
+      expect_error: UnregisteredAccount
+  - assert_balance: { account: alice, lamports: 1000 }
+  - assert_session: { session: main, usage_count: 1 }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.name, "borrow");
+        assert_eq!(scenario.steps.len(), 6);
+        match &scenario.steps[3] {
+            Step::ExecuteBatch {
+                operations,
+                expect_error,
+                ..
+            } => {
+                assert!(matches!(&operations[0], Operation::Borrow { mode: Mode::Read, .. }));
+                assert_eq!(expect_error.as_deref(), Some("UnregisteredAccount"));
+            }
+            step => panic!("unexpected step {step:?}"),
+        }
+        match &scenario.steps[5] {
+            Step::AssertSession {
+                usage_count, active, ..
+            } => {
+                assert_eq!(*usage_count, Some(1));
+                assert_eq!(*active, None);
+            }
+            step => panic!("unexpected step {step:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_step() {
+        assert!(Scenario::from_yaml("name: x\nsteps:\n  - launch: {}\n").is_err());
+    }
+}
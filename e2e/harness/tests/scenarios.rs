@@ -0,0 +1,33 @@
+// Runs every scenario in scenarios/ against a local validator
+//
+// Requires the programs built with `just build`:
+//     cargo test -p e2e-harness --features e2e
+#![cfg(feature = "e2e")]
+
+use e2e_harness::{default_programs, Localnet, Scenario, ScenarioRunner};
+use std::path::Path;
+
+#[tokio::test]
+async fn test_scenarios() {
+    let harness_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let e2e_dir = harness_dir.parent().unwrap();
+    let ledger = e2e_dir.join(".test-ledger");
+    let localnet = Localnet::boot(&default_programs(e2e_dir), &ledger)
+        .await
+        .unwrap();
+    let runner = ScenarioRunner::new(localnet.rpc_url.clone());
+
+    let mut paths: Vec<_> = std::fs::read_dir(harness_dir.join("scenarios"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let scenario = Scenario::load(&path).unwrap();
+        runner.run(&scenario).await.unwrap();
+        println!("✓ {}", scenario.name);
+    }
+}
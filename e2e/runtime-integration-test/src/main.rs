@@ -160,6 +160,7 @@ async fn async_main() -> Result<()> {
         commitment: CommitmentConfig::confirmed(),
         max_retries: 3,
         enable_simulation: true,
+        ..Default::default()
     };
     
     // Create runtime asynchronously
@@ -190,7 +191,7 @@ async fn async_main() -> Result<()> {
     namespace_seed[..copy_len].copy_from_slice(&namespace_bytes[..copy_len]);
     
     let (session_pda, _) = Pubkey::find_program_address(
-        &[b"session", &namespace_seed],
+        &[valence_kernel::SESSION_ACCOUNT_SEED, &namespace_seed],
         &kernel_id,
     );
     
@@ -16,8 +16,8 @@ use valence_kernel::{
     instructions::batch_operations::{
         KernelOperation, OperationBatch, ACCESS_MODE_READ_WRITE,
     },
-    CreateSessionParams, RegisteredAccount, RegisteredProgram,
-    MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_CPI_ACCOUNT_INDICES, MAX_OPERATION_DATA_SIZE,
+    state::OperationData, CreateSessionParams, RegisteredAccount, RegisteredProgram,
+    MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_CPI_ACCOUNT_INDICES,
 };
 
 declare_id!("TestShard1111111111111111111111111111111111");
@@ -166,16 +166,25 @@ pub mod test_shard {
         account_indices[1] = 2; // token_account_b
         account_indices[2] = 3; // token_program
         
-        let mut data = [0u8; MAX_OPERATION_DATA_SIZE];
-        let amount_bytes = amount.to_le_bytes();
-        data[..8].copy_from_slice(&amount_bytes);
-        
+        // The function's data lives in the session's operation data
+        let data = amount.to_le_bytes();
+        let cpi_accounts = kernel_accounts::WriteOperationData {
+            session: ctx.accounts.session.to_account_info(),
+            operation_data: ctx.accounts.operation_data.to_account_info(),
+            owner: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(
+            ctx.accounts.kernel_program.to_account_info(),
+            cpi_accounts,
+        );
+        valence_kernel::cpi::write_operation_data(cpi_context, 0, data.to_vec())?;
+
         operations[op_count] = Some(KernelOperation::CallRegisteredFunction {
             registry_id: function_id,
             account_indices,
             account_indices_len: 3,
-            data,
-            data_len: 8,
+            data_offset: 0,
+            data_len: data.len() as u16,
         });
         op_count += 1;
 
@@ -191,6 +200,7 @@ pub mod test_shard {
             accounts_len: 4,
             operations,
             operations_len: op_count as u8,
+            operation_data_hash: OperationData::hash(&data),
         };
 
         // Execute through CPI
@@ -209,7 +219,8 @@ pub mod test_shard {
         let cpi_context = CpiContext::new(
             ctx.accounts.kernel_program.to_account_info(),
             cpi_accounts,
-        );
+        )
+        .with_remaining_accounts(vec![ctx.accounts.operation_data.to_account_info()]);
 
         valence_kernel::cpi::execute_batch(cpi_context, batch)?;
 
@@ -337,6 +348,10 @@ pub struct ExecuteRegisteredFunction<'info> {
     /// CHECK: Guard account for authorization
     pub guard_account: AccountInfo<'info>,
     
+    /// CHECK: Session operation data, initialized by the session owner
+    #[account(mut)]
+    pub operation_data: AccountInfo<'info>,
+    
     pub authority: Signer<'info>,
     
     #[account(mut)]
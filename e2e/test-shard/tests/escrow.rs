@@ -0,0 +1,174 @@
+// Integration tests for the escrow function composed by shards
+// Covers dispute resolution, timeout claims and party authorization
+
+use anchor_lang::prelude::*;
+use valence_functions::functions::escrow::*;
+use valence_functions::{AgreementStatus, Environment, EscrowAgreement};
+
+const LOCKED_AT: i64 = 1_700_000_000;
+const TIMEOUT: i64 = 24 * 60 * 60;
+
+struct Parties {
+    depositor: Pubkey,
+    beneficiary: Pubkey,
+    arbiter: Pubkey,
+}
+
+fn env(caller: Pubkey, timestamp: i64) -> Environment {
+    Environment {
+        caller,
+        timestamp,
+        slot: 1,
+        ..Default::default()
+    }
+}
+
+fn lock(arbiter: bool) -> (Parties, EscrowAgreement) {
+    let parties = Parties {
+        depositor: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        arbiter: Pubkey::new_unique(),
+    };
+    let input = EscrowLockInput {
+        beneficiary: parties.beneficiary,
+        arbiter: arbiter.then_some(parties.arbiter),
+        child_account: Pubkey::new_unique(),
+        amount: 1_000,
+        timeout_seconds: TIMEOUT,
+    };
+    let agreement = escrow_lock(input, &env(parties.depositor, LOCKED_AT)).unwrap();
+    (parties, agreement)
+}
+
+#[test]
+fn test_dispute_resolved_by_arbiter() {
+    let (parties, mut agreement) = lock(true);
+
+    escrow(
+        EscrowInput::Dispute,
+        &mut agreement,
+        &env(parties.depositor, LOCKED_AT + 60),
+    )
+    .unwrap();
+    assert_eq!(agreement.status, AgreementStatus::Disputed);
+
+    // Parties can no longer approve or claim once disputed
+    assert!(escrow(
+        EscrowInput::Approve,
+        &mut agreement,
+        &env(parties.beneficiary, LOCKED_AT + 120),
+    )
+    .is_err());
+    assert!(escrow(
+        EscrowInput::Claim,
+        &mut agreement,
+        &env(parties.beneficiary, LOCKED_AT + TIMEOUT),
+    )
+    .is_err());
+
+    // Only the arbiter resolves
+    let resolve = EscrowInput::Resolve { release: false };
+    assert!(escrow(
+        resolve.clone(),
+        &mut agreement,
+        &env(parties.beneficiary, LOCKED_AT + 180)
+    )
+    .is_err());
+    let outcome = escrow(
+        resolve,
+        &mut agreement,
+        &env(parties.arbiter, LOCKED_AT + 180),
+    )
+    .unwrap();
+    assert_eq!(
+        outcome,
+        EscrowOutcome::Refund {
+            to: parties.depositor,
+            amount: 1_000
+        }
+    );
+    assert_eq!(agreement.status, AgreementStatus::Refunded);
+}
+
+#[test]
+fn test_claim_after_timeout() {
+    let (parties, mut agreement) = lock(true);
+
+    assert!(escrow(
+        EscrowInput::Claim,
+        &mut agreement,
+        &env(parties.beneficiary, LOCKED_AT + TIMEOUT - 1),
+    )
+    .is_err());
+
+    // The dispute window closes at the timeout
+    assert!(escrow(
+        EscrowInput::Dispute,
+        &mut agreement,
+        &env(parties.depositor, LOCKED_AT + TIMEOUT),
+    )
+    .is_err());
+
+    assert!(escrow(
+        EscrowInput::Claim,
+        &mut agreement,
+        &env(parties.depositor, LOCKED_AT + TIMEOUT),
+    )
+    .is_err());
+    let outcome = escrow(
+        EscrowInput::Claim,
+        &mut agreement,
+        &env(parties.beneficiary, LOCKED_AT + TIMEOUT),
+    )
+    .unwrap();
+    assert_eq!(
+        outcome,
+        EscrowOutcome::Release {
+            to: parties.beneficiary,
+            amount: 1_000
+        }
+    );
+
+    // Settled escrows cannot pay out twice
+    assert!(escrow(
+        EscrowInput::Claim,
+        &mut agreement,
+        &env(parties.beneficiary, LOCKED_AT + TIMEOUT + 1),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_dispute_requires_arbiter_and_party() {
+    let (parties, mut agreement) = lock(false);
+    assert!(escrow(
+        EscrowInput::Dispute,
+        &mut agreement,
+        &env(parties.depositor, LOCKED_AT + 60),
+    )
+    .is_err());
+
+    let (parties, mut agreement) = lock(true);
+    assert!(escrow(
+        EscrowInput::Dispute,
+        &mut agreement,
+        &env(parties.arbiter, LOCKED_AT + 60),
+    )
+    .is_err());
+    assert!(escrow(
+        EscrowInput::Approve,
+        &mut agreement,
+        &env(Pubkey::new_unique(), LOCKED_AT + 60),
+    )
+    .is_err());
+    assert_eq!(agreement.status, AgreementStatus::Locked);
+}
+
+#[test]
+fn test_escrow_registered_in_kernel() {
+    let info =
+        valence_kernel::state::function_registry::FunctionInfo::get_registry_entry(FUNCTION_ID)
+            .unwrap();
+    assert!(info.is_active);
+    assert_eq!(&info.name[..info.name_len as usize], b"Escrow");
+}
@@ -10,7 +10,7 @@ use valence_kernel::{
         KernelOperation, OperationBatch, ExecuteBatch, 
         ACCESS_MODE_READ, ACCESS_MODE_READ_WRITE
     },
-    state::Session,
+    state::{OperationData, Session},
     MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_CPI_ACCOUNT_INDICES, MAX_OPERATION_DATA_SIZE,
 };
 
@@ -64,6 +64,10 @@ pub fn liquidate_position(
     let mut operations: [Option<KernelOperation>; MAX_BATCH_OPERATIONS] = Default::default();
     let mut op_count = 0;
     
+    // CPI data is laid out here and referenced by offset; the owner writes it
+    // to the session's operation data account before the batch executes
+    let mut operation_data = Vec::new();
+    
     // Step 1: Borrow required accounts
     operations[0] = Some(KernelOperation::BorrowAccount {
         account_index: 1,  // collateral_price_feed
@@ -80,22 +84,25 @@ pub fn liquidate_position(
     op_count = 3;
     
     // Step 2: Check collateral value via oracle
+    let (data_offset, data_len) = push_operation_data(&mut operation_data, &build_oracle_query_data()?);
     operations[3] = Some(KernelOperation::CallRegisteredFunction {
         registry_id: 1001,  // ORACLE_FUNCTION_ID
         account_indices: [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
         account_indices_len: 2,
-        data: build_oracle_query_data()?,
-        data_len: 32,
+        data_offset,
+        data_len,
     });
     op_count = 4;
     
     // Step 3: Execute liquidation on lending protocol
+    let (data_offset, data_len) =
+        push_operation_data(&mut operation_data, &build_liquidation_data(position_owner)?);
     operations[4] = Some(KernelOperation::CallRegisteredFunction {
         registry_id: 2001,  // LENDING_LIQUIDATE_FUNCTION_ID
         account_indices: [2, 3, 4, 5, 8, 0, 0, 0, 0, 0, 0, 0],
         account_indices_len: 5,
-        data: build_liquidation_data(position_owner)?,
-        data_len: MAX_OPERATION_DATA_SIZE as u16,
+        data_offset,
+        data_len,
     });
     op_count = 5;
     
@@ -109,6 +116,7 @@ pub fn liquidate_position(
         accounts_len: account_count as u8,
         operations,
         operations_len: op_count as u8,
+        operation_data_hash: OperationData::hash(&operation_data),
     };
     
     // This example shows the concept - in practice, you'd call execute_batch
@@ -160,6 +168,7 @@ pub fn create_token_accounts(
         accounts_len: 2,
         operations,
         operations_len: op_count as u8,
+        operation_data_hash: OperationData::hash(&[]),
     };
     
     msg!("Would execute batch with {} operations", op_count);
@@ -182,6 +191,7 @@ pub fn execute_governance_proposal(
     let mut operations: [Option<KernelOperation>; MAX_BATCH_OPERATIONS] = Default::default();
     let mut account_count = 0;
     let mut op_count = 0;
+    let mut operation_data = Vec::new();
     
     // Parse proposal actions and build operations dynamically
     for action in proposal.actions.iter() {
@@ -199,24 +209,27 @@ pub fn execute_governance_proposal(
                 op_count += 1;
                 
                 // Execute transfer
+                let (data_offset, data_len) = push_operation_data(&mut operation_data, &amount.to_le_bytes());
                 operations[op_count] = Some(KernelOperation::CallRegisteredFunction {
                     registry_id: 4001,  // TREASURY_TRANSFER_FUNCTION
                     account_indices: build_account_indices(&[from_idx, to_idx]),
                     account_indices_len: 2,
-                    data: amount.to_le_bytes().to_vec().try_into().unwrap(),
-                    data_len: 8,
+                    data_offset,
+                    data_len,
                 });
                 op_count += 1;
             }
             
             ProposalAction::UpdateParameter { param_id, value } => {
                 // Dynamic parameter updates based on governance
+                let (data_offset, data_len) =
+                    push_operation_data(&mut operation_data, &build_param_update_data(*param_id, *value)?);
                 operations[op_count] = Some(KernelOperation::CallRegisteredFunction {
                     registry_id: 5001,  // PARAM_UPDATE_FUNCTION
                     account_indices: [0; MAX_CPI_ACCOUNT_INDICES],
                     account_indices_len: 1,
-                    data: build_param_update_data(*param_id, *value)?,
-                    data_len: MAX_OPERATION_DATA_SIZE as u16,
+                    data_offset,
+                    data_len,
                 });
                 op_count += 1;
             }
@@ -224,12 +237,14 @@ pub fn execute_governance_proposal(
             ProposalAction::EnableFeature { feature_flag } => {
                 // Conditional feature activation
                 if should_enable_feature(feature_flag) {
+                    let (data_offset, data_len) =
+                        push_operation_data(&mut operation_data, &build_feature_enable_data(*feature_flag)?);
                     operations[op_count] = Some(KernelOperation::UnsafeRawCpi {
                         program_index: 0,  // Governance program
                         account_indices: [0; MAX_CPI_ACCOUNT_INDICES],
                         account_indices_len: 1,
-                        data: build_feature_enable_data(*feature_flag)?,
-                        data_len: MAX_OPERATION_DATA_SIZE as u16,
+                        data_offset,
+                        data_len,
                     });
                     op_count += 1;
                 }
@@ -243,6 +258,7 @@ pub fn execute_governance_proposal(
         accounts_len: account_count as u8,
         operations,
         operations_len: op_count as u8,
+        operation_data_hash: OperationData::hash(&operation_data),
     };
     
     // This example shows the concept - in practice, you'd call execute_batch
@@ -313,36 +329,36 @@ fn find_best_swap_route(_from: &Pubkey, _to: &Pubkey) -> Result<(Pubkey, Pubkey)
     Ok((Pubkey::new_unique(), Pubkey::new_unique()))
 }
 
-fn build_oracle_query_data() -> Result<[u8; MAX_OPERATION_DATA_SIZE]> {
-    let mut data = [0u8; MAX_OPERATION_DATA_SIZE];
+fn build_oracle_query_data() -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 32];
     // Add oracle query parameters here
     data[0] = 1; // query type
     Ok(data)
 }
 
-fn build_liquidation_data(_user: Pubkey) -> Result<[u8; MAX_OPERATION_DATA_SIZE]> {
-    let mut data = [0u8; MAX_OPERATION_DATA_SIZE];
+fn build_liquidation_data(_user: Pubkey) -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 72];
     // Add liquidation parameters here
     data[0] = 2; // liquidation type
     Ok(data)
 }
 
-fn build_swap_data() -> Result<[u8; MAX_OPERATION_DATA_SIZE]> {
-    let mut data = [0u8; MAX_OPERATION_DATA_SIZE];
+fn build_swap_data() -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 96];
     // Add swap parameters here
     data[0] = 3; // swap type
     Ok(data)
 }
 
-fn build_param_update_data(_id: u32, _value: u64) -> Result<[u8; MAX_OPERATION_DATA_SIZE]> {
-    let mut data = [0u8; MAX_OPERATION_DATA_SIZE];
+fn build_param_update_data(_id: u32, _value: u64) -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 12];
     // Add parameter update data here
     data[0] = 4; // update type
     Ok(data)
 }
 
-fn build_feature_enable_data(_flag: u32) -> Result<[u8; MAX_OPERATION_DATA_SIZE]> {
-    let mut data = [0u8; MAX_OPERATION_DATA_SIZE];
+fn build_feature_enable_data(_flag: u32) -> Result<Vec<u8>> {
+    let mut data = vec![0u8; 4];
     // Add feature flag data here
     data[0] = 5; // feature type
     Ok(data)
@@ -371,6 +387,13 @@ fn add_or_find_account(accounts: &mut [Pubkey; MAX_BATCH_ACCOUNTS], count: &mut
     Ok(index)
 }
 
+/// Append `data` to the operation data, returning the offset and length CPIs reference it by
+fn push_operation_data(operation_data: &mut Vec<u8>, data: &[u8]) -> (u16, u16) {
+    let data_offset = operation_data.len() as u16;
+    operation_data.extend_from_slice(data);
+    (data_offset, data.len() as u16)
+}
+
 fn build_account_indices(indices: &[u8]) -> [u8; MAX_CPI_ACCOUNT_INDICES] {
     let mut result = [0u8; MAX_CPI_ACCOUNT_INDICES];
     for (i, &idx) in indices.iter().enumerate().take(MAX_CPI_ACCOUNT_INDICES) {
@@ -416,7 +439,7 @@ fn main() {
     println!("- Batch operations enable atomic multi-step execution");
     println!("- Operations use indices to reference a flat account array");
     println!("- Current limits: {} accounts, {} operations per batch", MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS);
-    println!("- CPI operations limited to {} bytes data, {} account indices", MAX_OPERATION_DATA_SIZE, MAX_CPI_ACCOUNT_INDICES);
+    println!("- CPI operations reference up to {} bytes of operation data, {} account indices", MAX_OPERATION_DATA_SIZE, MAX_CPI_ACCOUNT_INDICES);
     println!();
     println!("Note: Examples are simplified for demonstration and would need");
     println!("proper ExecuteBatch context construction for real execution.");
@@ -14,9 +14,9 @@ use valence_kernel::{
     instructions::batch_operations::{
         KernelOperation, OperationBatch, ACCESS_MODE_READ_WRITE
     },
-    state::{RegisteredAccount, RegisteredProgram},
+    state::{OperationData, RegisteredAccount, RegisteredProgram},
     namespace::NamespacePath,
-    MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_OPERATION_DATA_SIZE,
+    MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS,
 };
 use valence_functions::functions::zk_verify::{
     ZkVerifyInput, ProofType, FUNCTION_ID as ZK_VERIFY_FUNCTION_ID
@@ -88,13 +88,13 @@ impl TransferLimitProof {
     }
 }
 
-/// Demonstrates creating a ZK-enabled transfer batch
+/// Demonstrates creating a ZK-enabled transfer batch and the operation data it references
 pub fn create_zk_transfer_batch(
     session: Pubkey,
     user_account: Pubkey,
     recipient_account: Pubkey,
     proof: &TransferLimitProof,
-) -> std::result::Result<OperationBatch, Box<dyn Error>> {
+) -> std::result::Result<(OperationBatch, Vec<u8>), Box<dyn Error>> {
     println!("Building ZK-verified transfer batch...");
 
     // Build the account array
@@ -110,6 +110,8 @@ pub fn create_zk_transfer_batch(
     // Build the operation sequence
     let mut operations: [Option<KernelOperation>; MAX_BATCH_OPERATIONS] = Default::default();
     let mut op_count = 0;
+    // Instruction data the CPIs reference, written to the session's operation data account
+    let mut operation_data = Vec::new();
 
     // Step 1: Borrow user account for transfer
     operations[0] = Some(KernelOperation::BorrowAccount {
@@ -120,29 +122,27 @@ pub fn create_zk_transfer_batch(
 
     // Step 2: Call ZK verification function
     let zk_input = proof.to_zk_verify_input();
-    let mut zk_data = [0u8; MAX_OPERATION_DATA_SIZE];
     let serialized = anchor_lang::AnchorSerialize::try_to_vec(&zk_input)?;
-    let data_len = serialized.len().min(MAX_OPERATION_DATA_SIZE);
-    zk_data[..data_len].copy_from_slice(&serialized[..data_len]);
-
+    let zk_offset = operation_data.len();
+    operation_data.extend_from_slice(&serialized);
     operations[1] = Some(KernelOperation::CallRegisteredFunction {
         registry_id: ZK_VERIFY_FUNCTION_ID,
         account_indices: [3, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], // ZK verifier + proof storage
         account_indices_len: 2,
-        data: zk_data,
-        data_len: data_len as u16,
+        data_offset: zk_offset as u16,
+        data_len: serialized.len() as u16,
     });
     op_count = 2;
 
     // Step 3: Execute transfer (simplified - would call actual transfer function)
-    let mut transfer_data = [0u8; MAX_OPERATION_DATA_SIZE];
-    transfer_data[..8].copy_from_slice(&proof.transfer_amount.to_le_bytes());
+    let transfer_offset = operation_data.len();
+    operation_data.extend_from_slice(&proof.transfer_amount.to_le_bytes());
 
     operations[2] = Some(KernelOperation::CallRegisteredFunction {
         registry_id: 2000, // Hypothetical transfer function ID
         account_indices: [1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], // user -> recipient
         account_indices_len: 2,
-        data: transfer_data,
+        data_offset: transfer_offset as u16,
         data_len: 8,
     });
     op_count = 3;
@@ -153,10 +153,11 @@ pub fn create_zk_transfer_batch(
         accounts_len: accounts_len as u8,
         operations,
         operations_len: op_count as u8,
+        operation_data_hash: OperationData::hash(&operation_data),
     };
 
     println!("ZK transfer batch created with {} operations", op_count);
-    Ok(batch)
+    Ok((batch, operation_data))
 }
 
 /// Simulates session setup for ZK transfers
@@ -254,7 +255,7 @@ fn main() -> std::result::Result<(), Box<dyn Error>> {
     let user_account = Pubkey::new_unique();
     let recipient_account = Pubkey::new_unique();
 
-    let batch = create_zk_transfer_batch(
+    let (batch, operation_data) = create_zk_transfer_batch(
         session,
         user_account,
         recipient_account,
@@ -263,6 +264,7 @@ fn main() -> std::result::Result<(), Box<dyn Error>> {
 
     println!("   Batch contains {} operations across {} accounts", 
         batch.operations_len, batch.accounts_len);
+    println!("   Operation data: {} bytes", operation_data.len());
     println!();
 
     // Step 5: Demonstrate the execution flow
@@ -0,0 +1,30 @@
+// Decoded guards evaluate like the expressions they decompile to
+#![no_main]
+
+use anchor_lang::prelude::Pubkey;
+use libfuzzer_sys::fuzz_target;
+use valence_sdk::{GuardContext, SerializedGuard};
+
+/// Evaluation context taken from the tail of the input
+fn context(data: &[u8], keys: &[Pubkey]) -> GuardContext {
+    let byte = |i: usize| data.get(data.len().wrapping_sub(i + 1)).copied().unwrap_or(0);
+    let key = |i: usize| match keys.len() {
+        0 => Pubkey::default(),
+        len => keys[byte(i) as usize % len],
+    };
+    GuardContext {
+        caller: key(0),
+        session_owner: key(1),
+        unix_timestamp: i64::from_le_bytes([byte(2), byte(3), byte(4), byte(5), 0, 0, 0, 0]),
+        usage_count: u64::from(byte(6)),
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(guard) = SerializedGuard(data.to_vec()).decode() else {
+        return;
+    };
+    let context = context(data, &guard.keys);
+    let result = guard.evaluate(&context).unwrap();
+    assert_eq!(guard.decompile().unwrap().evaluate(&context), result);
+});
@@ -0,0 +1,28 @@
+// Namespace paths built from strings or decoded from accounts must not panic
+#![no_main]
+
+use anchor_lang::AnchorDeserialize;
+use libfuzzer_sys::fuzz_target;
+use valence_kernel::NamespacePath;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(path) = std::str::from_utf8(data) {
+        if let Ok(namespace) = NamespacePath::new(path) {
+            assert_eq!(namespace.as_str().unwrap(), path);
+            if let Some(parent) = namespace.parent() {
+                assert!(parent.is_parent_of(&namespace));
+                assert_eq!(parent.depth() + 1, namespace.depth());
+            }
+            let _ = namespace.child("child");
+        }
+    }
+
+    // Decoded paths may carry any length
+    if let Ok(namespace) = NamespacePath::deserialize(&mut &data[..]) {
+        let _ = namespace.as_str();
+        let _ = namespace.depth();
+        if let Some(parent) = namespace.parent() {
+            let _ = parent.is_parent_of(&namespace);
+        }
+    }
+});
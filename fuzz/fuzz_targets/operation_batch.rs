@@ -0,0 +1,27 @@
+// Borsh-decoded batches must validate, estimate and hash without panicking
+#![no_main]
+
+use anchor_lang::AnchorDeserialize;
+use libfuzzer_sys::fuzz_target;
+use valence_kernel::{KernelOperation, OperationBatch, MAX_OPERATION_DATA_SIZE};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(batch) = OperationBatch::deserialize(&mut &data[..]) else {
+        return;
+    };
+
+    let valid = batch.validate().is_ok();
+    let _ = batch.compute_estimate();
+    let _ = batch.commitment_hash();
+
+    if valid {
+        // Validation bounds every length the executor slices by
+        for operation in batch.operations[..batch.operations_len as usize].iter().flatten() {
+            if let KernelOperation::CallRegisteredFunction { data_len, .. }
+            | KernelOperation::UnsafeRawCpi { data_len, .. } = operation
+            {
+                assert!(*data_len as usize <= MAX_OPERATION_DATA_SIZE);
+            }
+        }
+    }
+});
@@ -0,0 +1,277 @@
+// Escrow function with dispute window
+// Registry ID: 1006
+// Purpose: Release funds locked in an escrow vault on mutual approval or timeout
+
+use crate::{
+    states::{AgreementStatus, EscrowAgreement, StateValidator},
+    Environment,
+};
+use anchor_lang::prelude::*;
+
+/// Error type for escrow operations
+#[error_code]
+pub enum EscrowError {
+    #[msg("Invalid escrow terms")]
+    InvalidTerms,
+    #[msg("Caller is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("Escrow does not allow this action in its current status")]
+    InvalidStatus,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Escrow timeout has not passed")]
+    TimeoutNotReached,
+    #[msg("Escrow has no arbiter")]
+    NoArbiter,
+    #[msg("Account does not match the escrow agreement")]
+    AccountMismatch,
+    #[msg("Escrow vault holds less than the locked amount")]
+    InsufficientFunds,
+}
+
+/// Longest allowed escrow timeout (30 days in seconds)
+pub const MAX_TIMEOUT_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Terms of a new escrow, locked by the caller
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EscrowLockInput {
+    pub beneficiary: Pubkey,
+    pub arbiter: Option<Pubkey>,
+    /// Child account of the depositor's session the agreement is made for
+    pub child_account: Pubkey,
+    /// Lamports locked in the agreement's vault
+    pub amount: u64,
+    /// Seconds until the beneficiary may claim, closing the dispute window
+    pub timeout_seconds: i64,
+}
+
+/// Action on a locked escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum EscrowInput {
+    /// Depositor or beneficiary approves release
+    Approve,
+    /// Depositor or beneficiary disputes before the timeout
+    Dispute,
+    /// Arbiter settles a dispute, releasing or refunding the funds
+    Resolve { release: bool },
+    /// Beneficiary claims undisputed funds after the timeout
+    Claim,
+}
+
+/// Payout made out of the escrow vault
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, PartialEq, Eq)]
+pub enum EscrowOutcome {
+    /// Nothing to transfer yet
+    Pending,
+    Release {
+        to: Pubkey,
+        amount: u64,
+    },
+    Refund {
+        to: Pubkey,
+        amount: u64,
+    },
+}
+
+/// Create an escrow agreement with the caller as depositor
+///
+/// The `lock_escrow` instruction moves `amount` into the agreement's vault.
+#[allow(clippy::needless_pass_by_value)]
+pub fn escrow_lock(input: EscrowLockInput, env: &Environment) -> Result<EscrowAgreement> {
+    require!(
+        input.timeout_seconds > 0 && input.timeout_seconds <= MAX_TIMEOUT_SECONDS,
+        EscrowError::InvalidTerms
+    );
+
+    let agreement = EscrowAgreement {
+        depositor: env.caller,
+        beneficiary: input.beneficiary,
+        arbiter: input.arbiter,
+        child_account: input.child_account,
+        amount: input.amount,
+        created_at: env.timestamp,
+        release_after: env.timestamp + input.timeout_seconds,
+        depositor_approved: false,
+        beneficiary_approved: false,
+        status: AgreementStatus::Locked,
+        _reserved: [0u8; 32],
+    };
+    agreement
+        .validate()
+        .map_err(|_| error!(EscrowError::InvalidTerms))?;
+
+    msg!(
+        "Locked {} for {} until {}",
+        agreement.amount,
+        agreement.beneficiary,
+        agreement.release_after
+    );
+    Ok(agreement)
+}
+
+/// Escrow function with dispute window
+///
+/// Funds are released once both parties approve, or to the beneficiary once
+/// the timeout passes. Before the timeout either party may dispute, after
+/// which only the arbiter can settle.
+#[allow(clippy::needless_pass_by_value)]
+pub fn escrow(
+    input: EscrowInput,
+    agreement: &mut EscrowAgreement,
+    env: &Environment,
+) -> Result<EscrowOutcome> {
+    let release = EscrowOutcome::Release {
+        to: agreement.beneficiary,
+        amount: agreement.amount,
+    };
+    let refund = EscrowOutcome::Refund {
+        to: agreement.depositor,
+        amount: agreement.amount,
+    };
+
+    let outcome = match input {
+        EscrowInput::Approve => {
+            require!(
+                agreement.status == AgreementStatus::Locked,
+                EscrowError::InvalidStatus
+            );
+            if env.caller == agreement.depositor {
+                agreement.depositor_approved = true;
+            } else if env.caller == agreement.beneficiary {
+                agreement.beneficiary_approved = true;
+            } else {
+                return Err(EscrowError::Unauthorized.into());
+            }
+
+            if agreement.depositor_approved && agreement.beneficiary_approved {
+                agreement.status = AgreementStatus::Released;
+                release
+            } else {
+                EscrowOutcome::Pending
+            }
+        }
+        EscrowInput::Dispute => {
+            require!(
+                agreement.status == AgreementStatus::Locked,
+                EscrowError::InvalidStatus
+            );
+            require!(agreement.is_party(&env.caller), EscrowError::Unauthorized);
+            require!(agreement.arbiter.is_some(), EscrowError::NoArbiter);
+            require!(
+                agreement.in_dispute_window(env.timestamp),
+                EscrowError::DisputeWindowClosed
+            );
+
+            agreement.status = AgreementStatus::Disputed;
+            msg!("Escrow disputed by {}", env.caller);
+            EscrowOutcome::Pending
+        }
+        EscrowInput::Resolve {
+            release: to_beneficiary,
+        } => {
+            require!(
+                agreement.status == AgreementStatus::Disputed,
+                EscrowError::InvalidStatus
+            );
+            require!(
+                agreement.arbiter == Some(env.caller),
+                EscrowError::Unauthorized
+            );
+
+            if to_beneficiary {
+                agreement.status = AgreementStatus::Released;
+                release
+            } else {
+                agreement.status = AgreementStatus::Refunded;
+                refund
+            }
+        }
+        EscrowInput::Claim => {
+            require!(
+                agreement.status == AgreementStatus::Locked,
+                EscrowError::InvalidStatus
+            );
+            require_keys_eq!(env.caller, agreement.beneficiary, EscrowError::Unauthorized);
+            require!(
+                !agreement.in_dispute_window(env.timestamp),
+                EscrowError::TimeoutNotReached
+            );
+
+            agreement.status = AgreementStatus::Released;
+            release
+        }
+    };
+
+    msg!("Escrow outcome: {:?}", outcome);
+    Ok(outcome)
+}
+
+/// Metadata for function registry
+pub const FUNCTION_ID: u64 = 1006;
+pub const FUNCTION_NAME: &str = "escrow";
+pub const FUNCTION_VERSION: u16 = 1;
+pub const COMPUTE_UNITS: u64 = 6_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escrow_mutual_approval() {
+        let depositor = Pubkey::new_unique();
+        let beneficiary = Pubkey::new_unique();
+        let env = |caller| Environment {
+            caller,
+            timestamp: 1_000,
+            ..Default::default()
+        };
+
+        let input = EscrowLockInput {
+            beneficiary,
+            arbiter: None,
+            child_account: Pubkey::new_unique(),
+            amount: 500,
+            timeout_seconds: 3_600,
+        };
+        let mut agreement = escrow_lock(input, &env(depositor)).unwrap();
+        assert_eq!(agreement.release_after, 4_600);
+
+        let outcome = escrow(EscrowInput::Approve, &mut agreement, &env(depositor));
+        assert_eq!(outcome.unwrap(), EscrowOutcome::Pending);
+        let outcome = escrow(EscrowInput::Approve, &mut agreement, &env(beneficiary));
+        assert_eq!(
+            outcome.unwrap(),
+            EscrowOutcome::Release {
+                to: beneficiary,
+                amount: 500
+            }
+        );
+        assert!(agreement.is_settled());
+    }
+
+    #[test]
+    fn test_escrow_rejects_invalid_terms() {
+        let depositor = Pubkey::new_unique();
+        let env = Environment {
+            caller: depositor,
+            timestamp: 1_000,
+            ..Default::default()
+        };
+        let input = |beneficiary, arbiter, timeout_seconds| EscrowLockInput {
+            beneficiary,
+            arbiter,
+            child_account: Pubkey::new_unique(),
+            amount: 500,
+            timeout_seconds,
+        };
+
+        assert!(escrow_lock(input(depositor, None, 60), &env).is_err());
+        assert!(escrow_lock(input(Pubkey::new_unique(), Some(depositor), 60), &env).is_err());
+        assert!(escrow_lock(input(Pubkey::new_unique(), None, 0), &env).is_err());
+        assert!(escrow_lock(
+            input(Pubkey::new_unique(), None, MAX_TIMEOUT_SECONDS + 1),
+            &env
+        )
+        .is_err());
+    }
+}
@@ -21,8 +21,24 @@ pub mod math_add;
 /// Token account validation function
 pub mod token_validate;
 
+/// Oracle price bound guard
+pub mod price_bound_guard;
+
+/// Share-based vault function
+pub mod vault;
+
+/// AMM swap adapter function
+pub mod swap_adapter;
+
+/// Escrow function with dispute window
+pub mod escrow;
+
 // Re-export the functions for easy access
 pub use identity::identity;
 pub use zk_verify::zk_verify;
 pub use math_add::math_add;
-pub use token_validate::token_validate;
\ No newline at end of file
+pub use token_validate::token_validate;
+pub use price_bound_guard::price_bound_guard;
+pub use vault::vault;
+pub use swap_adapter::swap_adapter;
+pub use escrow::{escrow, escrow_lock};
\ No newline at end of file
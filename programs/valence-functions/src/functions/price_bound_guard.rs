@@ -0,0 +1,370 @@
+// Oracle price bound guard
+// Registry ID: 1004
+// Purpose: Approve operations only while an oracle price is fresh and within bounds
+//
+// Reads Pyth v2 price accounts and Switchboard v2 aggregator accounts.
+
+use anchor_lang::prelude::*;
+use valence_common::math::BPS_DENOMINATOR;
+
+/// Error type for price bound checks
+#[error_code]
+pub enum PriceGuardError {
+    #[msg("Price account is not a valid oracle price account")]
+    InvalidPriceAccount,
+    #[msg("Oracle price is not currently trading")]
+    PriceUnavailable,
+    #[msg("Oracle price is stale")]
+    StalePrice,
+    #[msg("Oracle confidence interval too wide")]
+    ConfidenceTooWide,
+    #[msg("Oracle price outside configured bounds")]
+    PriceOutOfBounds,
+}
+
+/// Magic number at the start of every Pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+
+/// Pyth account type of price accounts
+pub const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+
+/// Pyth aggregate status while the price is trading
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+// Offsets into a Pyth v2 price account
+const EXPO_OFFSET: usize = 20;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+const PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+/// Anchor discriminator of Switchboard v2 aggregator accounts
+pub const SWITCHBOARD_AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Offsets into a Switchboard v2 aggregator account's latest confirmed round
+const SB_NUM_SUCCESS_OFFSET: usize = 341;
+const SB_ROUND_OPEN_SLOT_OFFSET: usize = 350;
+const SB_RESULT_MANTISSA_OFFSET: usize = 366;
+const SB_RESULT_SCALE_OFFSET: usize = 382;
+const SB_STD_DEV_MANTISSA_OFFSET: usize = 386;
+const SB_STD_DEV_SCALE_OFFSET: usize = 402;
+const AGGREGATOR_ACCOUNT_MIN_LEN: usize = 406;
+
+/// Aggregate price read from an oracle account
+///
+/// Switchboard results carry up to 28 decimals, so values are kept as
+/// 128-bit integers. `status` uses the Pyth status codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i128,
+    pub conf: u128,
+    pub expo: i32,
+    pub status: u32,
+    pub publish_slot: u64,
+}
+
+/// Configured bounds, expressed as `value * 10^expo`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PriceBoundInput {
+    /// Program that must own the price account
+    pub oracle_program: Pubkey,
+    pub min_price: i64,
+    pub max_price: i64,
+    pub expo: i32,
+    /// Maximum slots since the price was published
+    pub max_staleness_slots: u64,
+    /// Maximum confidence interval relative to price, in basis points
+    pub max_confidence_bps: u16,
+}
+
+/// Price bound check result
+#[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+pub struct PriceBoundResult {
+    pub approved: bool,
+    pub price: i128,
+    pub expo: i32,
+    pub publish_slot: u64,
+}
+
+/// Parse the aggregate price of a Pyth v2 price account
+pub fn parse_pyth_price(data: &[u8]) -> Result<OraclePrice> {
+    if data.len() < PRICE_ACCOUNT_MIN_LEN {
+        return Err(PriceGuardError::InvalidPriceAccount.into());
+    }
+
+    let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+    if u32_at(0) != PYTH_MAGIC || u32_at(8) != PYTH_PRICE_ACCOUNT_TYPE {
+        return Err(PriceGuardError::InvalidPriceAccount.into());
+    }
+
+    Ok(OraclePrice {
+        price: u64_at(AGG_PRICE_OFFSET) as i64 as i128,
+        conf: u64_at(AGG_CONF_OFFSET) as u128,
+        expo: u32_at(EXPO_OFFSET) as i32,
+        status: u32_at(AGG_STATUS_OFFSET),
+        publish_slot: u64_at(AGG_PUB_SLOT_OFFSET),
+    })
+}
+
+/// Parse the latest confirmed round of a Switchboard v2 aggregator account
+///
+/// The round's standard deviation stands in for the confidence interval, and
+/// a round without successful oracle responses is reported as not trading.
+pub fn parse_switchboard_price(data: &[u8]) -> Result<OraclePrice> {
+    if data.len() < AGGREGATOR_ACCOUNT_MIN_LEN || data[..8] != SWITCHBOARD_AGGREGATOR_DISCRIMINATOR
+    {
+        return Err(PriceGuardError::InvalidPriceAccount.into());
+    }
+
+    let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let i128_at =
+        |offset: usize| i128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+    // Decimals are `mantissa * 10^-scale`; bring the deviation to the result's scale
+    let scale = u32_at(SB_RESULT_SCALE_OFFSET);
+    let std_scale = u32_at(SB_STD_DEV_SCALE_OFFSET);
+    let std_dev = i128_at(SB_STD_DEV_MANTISSA_OFFSET).unsigned_abs();
+    let conf = if std_scale >= scale {
+        std_dev.div_ceil(
+            10u128
+                .checked_pow(std_scale - scale)
+                .ok_or(PriceGuardError::InvalidPriceAccount)?,
+        )
+    } else {
+        10u128
+            .checked_pow(scale - std_scale)
+            .and_then(|factor| std_dev.checked_mul(factor))
+            .ok_or(PriceGuardError::InvalidPriceAccount)?
+    };
+    let expo = i32::try_from(scale).map_err(|_| PriceGuardError::InvalidPriceAccount)?;
+
+    Ok(OraclePrice {
+        price: i128_at(SB_RESULT_MANTISSA_OFFSET),
+        conf,
+        expo: -expo,
+        status: if u32_at(SB_NUM_SUCCESS_OFFSET) > 0 {
+            PYTH_STATUS_TRADING
+        } else {
+            0
+        },
+        publish_slot: u64_at(SB_ROUND_OPEN_SLOT_OFFSET),
+    })
+}
+
+/// Parse a Pyth price account or a Switchboard aggregator account
+pub fn parse_oracle_price(data: &[u8]) -> Result<OraclePrice> {
+    if data.len() >= 8 && data[..8] == SWITCHBOARD_AGGREGATOR_DISCRIMINATOR {
+        parse_switchboard_price(data)
+    } else {
+        parse_pyth_price(data)
+    }
+}
+
+/// Express `value * 10^from` at the finer exponent `to`, exactly
+fn to_exponent(value: i128, from: i32, to: i32) -> Option<i128> {
+    let shift = u32::try_from(from.checked_sub(to)?).ok()?;
+    value.checked_mul(10i128.checked_pow(shift)?)
+}
+
+/// Check an oracle price against the configured freshness and bounds
+pub fn check_price_bound(
+    input: &PriceBoundInput,
+    price: &OraclePrice,
+    current_slot: u64,
+) -> Result<PriceBoundResult> {
+    if price.status != PYTH_STATUS_TRADING || price.price <= 0 {
+        return Err(PriceGuardError::PriceUnavailable.into());
+    }
+    if current_slot.saturating_sub(price.publish_slot) > input.max_staleness_slots {
+        return Err(PriceGuardError::StalePrice.into());
+    }
+
+    // conf / price <= bps / 10_000, cross-multiplied
+    let conf_within = price
+        .conf
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .zip((price.price as u128).checked_mul(input.max_confidence_bps as u128))
+        .is_some_and(|(conf, max_conf)| conf <= max_conf);
+    if !conf_within {
+        return Err(PriceGuardError::ConfidenceTooWide.into());
+    }
+
+    // Compare at the finer exponent so no digit of the price is dropped
+    let expo = price.expo.min(input.expo);
+    let bounded = to_exponent(price.price, price.expo, expo)
+        .zip(to_exponent(input.min_price as i128, input.expo, expo))
+        .zip(to_exponent(input.max_price as i128, input.expo, expo))
+        .is_some_and(|((price, min), max)| (min..=max).contains(&price));
+    if !bounded {
+        return Err(PriceGuardError::PriceOutOfBounds.into());
+    }
+
+    Ok(PriceBoundResult {
+        approved: true,
+        price: price.price,
+        expo: price.expo,
+        publish_slot: price.publish_slot,
+    })
+}
+
+/// Oracle price bound guard
+///
+/// Approves the operation only when `price_account` is a Pyth price or
+/// Switchboard aggregator owned by the configured program, is trading, was
+/// published within the staleness window, and lies within the configured bounds.
+pub fn price_bound_guard(
+    input: PriceBoundInput,
+    price_account: &AccountInfo,
+    current_slot: u64,
+) -> Result<PriceBoundResult> {
+    require_keys_eq!(
+        *price_account.owner,
+        input.oracle_program,
+        PriceGuardError::InvalidPriceAccount
+    );
+
+    let price = parse_oracle_price(&price_account.try_borrow_data()?)?;
+    msg!(
+        "Checking price {}e{} published at slot {}",
+        price.price,
+        price.expo,
+        price.publish_slot
+    );
+    check_price_bound(&input, &price, current_slot)
+}
+
+/// Metadata for function registry
+pub const FUNCTION_ID: u64 = 1004;
+pub const FUNCTION_NAME: &str = "price_bound_guard";
+pub const FUNCTION_VERSION: u16 = 1;
+pub const COMPUTE_UNITS: u64 = 4_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_account(price: i64, conf: u64, expo: i32, status: u32, publish_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; PRICE_ACCOUNT_MIN_LEN];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data[AGG_PUB_SLOT_OFFSET..AGG_PUB_SLOT_OFFSET + 8]
+            .copy_from_slice(&publish_slot.to_le_bytes());
+        data
+    }
+
+    fn input() -> PriceBoundInput {
+        // Bounds of $90.00 to $110.00
+        PriceBoundInput {
+            oracle_program: Pubkey::new_unique(),
+            min_price: 9_000,
+            max_price: 11_000,
+            expo: -2,
+            max_staleness_slots: 25,
+            max_confidence_bps: 100,
+        }
+    }
+
+    #[test]
+    fn test_price_within_bounds() {
+        // $100.00000000 with a $0.50 confidence interval
+        let data = price_account(10_000_000_000, 50_000_000, -8, PYTH_STATUS_TRADING, 100);
+        let price = parse_pyth_price(&data).unwrap();
+
+        let result = check_price_bound(&input(), &price, 110).unwrap();
+        assert!(result.approved);
+        assert_eq!(result.publish_slot, 100);
+    }
+
+    #[test]
+    fn test_price_rejections() {
+        let check = |data: Vec<u8>, slot: u64| {
+            check_price_bound(&input(), &parse_pyth_price(&data).unwrap(), slot)
+        };
+
+        assert!(check(
+            price_account(10_000_000_000, 0, -8, PYTH_STATUS_TRADING, 100),
+            126
+        )
+        .is_err());
+        assert!(check(price_account(10_000_000_000, 0, -8, 0, 100), 100).is_err());
+        assert!(check(
+            price_account(10_000_000_000, 200_000_000, -8, PYTH_STATUS_TRADING, 100),
+            100
+        )
+        .is_err());
+        assert!(check(
+            price_account(8_999_999_999, 0, -8, PYTH_STATUS_TRADING, 100),
+            100
+        )
+        .is_err());
+        assert!(check(
+            price_account(11_000_000_001, 0, -8, PYTH_STATUS_TRADING, 100),
+            100
+        )
+        .is_err());
+        assert!(check(
+            price_account(11_000_000_000, 0, -8, PYTH_STATUS_TRADING, 100),
+            100
+        )
+        .is_ok());
+        assert!(check(
+            price_account(11_010_000_000, 0, -8, PYTH_STATUS_TRADING, 100),
+            100
+        )
+        .is_err());
+
+        let mut data = price_account(10_000_000_000, 0, -8, PYTH_STATUS_TRADING, 100);
+        data[0] = 0;
+        assert!(parse_pyth_price(&data).is_err());
+    }
+
+    fn aggregator_account(
+        result: i128,
+        scale: u32,
+        std_dev: i128,
+        num_success: u32,
+        round_open_slot: u64,
+    ) -> Vec<u8> {
+        let mut data = vec![0u8; AGGREGATOR_ACCOUNT_MIN_LEN];
+        data[..8].copy_from_slice(&SWITCHBOARD_AGGREGATOR_DISCRIMINATOR);
+        data[SB_NUM_SUCCESS_OFFSET..SB_NUM_SUCCESS_OFFSET + 4]
+            .copy_from_slice(&num_success.to_le_bytes());
+        data[SB_ROUND_OPEN_SLOT_OFFSET..SB_ROUND_OPEN_SLOT_OFFSET + 8]
+            .copy_from_slice(&round_open_slot.to_le_bytes());
+        data[SB_RESULT_MANTISSA_OFFSET..SB_RESULT_MANTISSA_OFFSET + 16]
+            .copy_from_slice(&result.to_le_bytes());
+        data[SB_RESULT_SCALE_OFFSET..SB_RESULT_SCALE_OFFSET + 4]
+            .copy_from_slice(&scale.to_le_bytes());
+        data[SB_STD_DEV_MANTISSA_OFFSET..SB_STD_DEV_MANTISSA_OFFSET + 16]
+            .copy_from_slice(&std_dev.to_le_bytes());
+        data[SB_STD_DEV_SCALE_OFFSET..SB_STD_DEV_SCALE_OFFSET + 4]
+            .copy_from_slice(&scale.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_switchboard_price() {
+        // $100 at 18 decimals, beyond the range of an i64
+        let data = aggregator_account(100 * 10i128.pow(18), 18, 10i128.pow(17), 3, 100);
+        let price = parse_oracle_price(&data).unwrap();
+        assert_eq!(price.expo, -18);
+        assert_eq!(price.conf, 10u128.pow(17));
+        assert!(check_price_bound(&input(), &price, 110).unwrap().approved);
+
+        // One unit at the 18th decimal above $110.00
+        let data = aggregator_account(110 * 10i128.pow(18) + 1, 18, 0, 3, 100);
+        assert!(check_price_bound(&input(), &parse_oracle_price(&data).unwrap(), 110).is_err());
+
+        // A round without successful responses has no price
+        let data = aggregator_account(100 * 10i128.pow(18), 18, 0, 0, 100);
+        assert!(check_price_bound(&input(), &parse_oracle_price(&data).unwrap(), 110).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+// Token swap adapter function
+// Registry ID: 1007
+// Purpose: Build venue-specific AMM swap instructions from one normalized interface
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+
+/// Error type for swap adapter
+#[error_code]
+pub enum SwapAdapterError {
+    #[msg("Swap amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Wrong number of pool accounts for venue")]
+    InvalidPoolAccounts,
+    #[msg("Venue program does not match the venue")]
+    InvalidVenueProgram,
+}
+
+/// Orca Whirlpool program
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey =
+    pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Raydium AMM v4 program
+pub const RAYDIUM_AMM_V4_PROGRAM_ID: Pubkey =
+    pubkey!("675kPX9MHTjS2zt1qfr1NZHUoVWu1BvM8LnWAJzjtVE3");
+
+/// SPL token program, which both venues transfer through
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Anchor discriminator of the Whirlpool `swap` instruction
+const WHIRLPOOL_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Raydium AMM v4 `swap_base_in` instruction tag
+const RAYDIUM_SWAP_BASE_IN_TAG: u8 = 9;
+
+/// Pool accounts expected for a Whirlpool swap:
+/// whirlpool, vault A, vault B, tick arrays 0-2, oracle
+pub const WHIRLPOOL_POOL_ACCOUNTS: usize = 7;
+
+/// Pool accounts expected for a Raydium AMM v4 swap:
+/// amm, amm authority, open orders, target orders, pool coin and pc vaults,
+/// serum program, market, bids, asks, event queue, coin and pc vaults, vault signer
+pub const RAYDIUM_POOL_ACCOUNTS: usize = 14;
+
+/// AMM the swap is routed through
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapVenue {
+    /// Orca Whirlpool, swapping token A for B when `a_to_b`
+    OrcaWhirlpool {
+        a_to_b: bool,
+        /// Price limit as a Q64.64 square root price
+        sqrt_price_limit: u128,
+    },
+    /// Raydium AMM v4, swapping the source token for the other side
+    RaydiumAmmV4,
+}
+
+/// Normalized swap request
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapInput {
+    pub amount_in: u64,
+    /// Minimum output, the swap fails on the venue below this
+    pub min_out: u64,
+    pub venue: SwapVenue,
+    /// Signer owning the source and destination token accounts
+    pub user: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    /// Venue pool accounts, in the order documented for the venue
+    pub pool_accounts: Vec<Pubkey>,
+}
+
+/// Token swap adapter function
+///
+/// Places the user's token accounts where the venue expects them and
+/// encodes the venue's instruction data, returning the instruction the
+/// `swap` instruction invokes. Output is enforced by the venue through `min_out`.
+#[allow(clippy::needless_pass_by_value)]
+pub fn swap_adapter(input: SwapInput) -> Result<Instruction> {
+    require!(input.amount_in > 0, SwapAdapterError::ZeroAmount);
+    msg!(
+        "Swapping {} (min out {}) via {:?}",
+        input.amount_in,
+        input.min_out,
+        input.venue
+    );
+
+    match input.venue {
+        SwapVenue::OrcaWhirlpool {
+            a_to_b,
+            sqrt_price_limit,
+        } => whirlpool_swap(&input, a_to_b, sqrt_price_limit),
+        SwapVenue::RaydiumAmmV4 => raydium_swap(&input),
+    }
+}
+
+fn whirlpool_swap(input: &SwapInput, a_to_b: bool, sqrt_price_limit: u128) -> Result<Instruction> {
+    let [whirlpool, vault_a, vault_b, tick_array_0, tick_array_1, tick_array_2, oracle] =
+        <[Pubkey; WHIRLPOOL_POOL_ACCOUNTS]>::try_from(input.pool_accounts.as_slice())
+            .map_err(|_| SwapAdapterError::InvalidPoolAccounts)?;
+
+    // Whirlpool takes the owner's accounts for tokens A and B, not source and destination
+    let (owner_a, owner_b) = if a_to_b {
+        (input.source, input.destination)
+    } else {
+        (input.destination, input.source)
+    };
+
+    let mut data = WHIRLPOOL_SWAP_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&input.amount_in.to_le_bytes());
+    data.extend_from_slice(&input.min_out.to_le_bytes());
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+
+    Ok(Instruction {
+        program_id: ORCA_WHIRLPOOL_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(SPL_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(input.user, true),
+            AccountMeta::new(whirlpool, false),
+            AccountMeta::new(owner_a, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(owner_b, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new(tick_array_0, false),
+            AccountMeta::new(tick_array_1, false),
+            AccountMeta::new(tick_array_2, false),
+            AccountMeta::new_readonly(oracle, false),
+        ],
+        data,
+    })
+}
+
+fn raydium_swap(input: &SwapInput) -> Result<Instruction> {
+    if input.pool_accounts.len() != RAYDIUM_POOL_ACCOUNTS {
+        return Err(SwapAdapterError::InvalidPoolAccounts.into());
+    }
+
+    // Authorities and programs are read-only, all other pool accounts are written
+    let readonly = [1, 6, 13];
+    let mut accounts = vec![AccountMeta::new_readonly(SPL_TOKEN_PROGRAM_ID, false)];
+    accounts.extend(input.pool_accounts.iter().enumerate().map(|(index, key)| {
+        if readonly.contains(&index) {
+            AccountMeta::new_readonly(*key, false)
+        } else {
+            AccountMeta::new(*key, false)
+        }
+    }));
+    accounts.push(AccountMeta::new(input.source, false));
+    accounts.push(AccountMeta::new(input.destination, false));
+    accounts.push(AccountMeta::new_readonly(input.user, true));
+
+    let mut data = vec![RAYDIUM_SWAP_BASE_IN_TAG];
+    data.extend_from_slice(&input.amount_in.to_le_bytes());
+    data.extend_from_slice(&input.min_out.to_le_bytes());
+
+    Ok(Instruction {
+        program_id: RAYDIUM_AMM_V4_PROGRAM_ID,
+        accounts,
+        data,
+    })
+}
+
+/// Metadata for function registry
+pub const FUNCTION_ID: u64 = 1007;
+pub const FUNCTION_NAME: &str = "swap_adapter";
+pub const FUNCTION_VERSION: u16 = 1;
+pub const COMPUTE_UNITS: u64 = 3_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(venue: SwapVenue, pool_accounts: usize) -> SwapInput {
+        SwapInput {
+            amount_in: 1_000,
+            min_out: 990,
+            venue,
+            user: Pubkey::new_unique(),
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            pool_accounts: (0..pool_accounts).map(|_| Pubkey::new_unique()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_whirlpool_swap() {
+        let venue = SwapVenue::OrcaWhirlpool {
+            a_to_b: false,
+            sqrt_price_limit: 0,
+        };
+        let input = input(venue, WHIRLPOOL_POOL_ACCOUNTS);
+        let ix = swap_adapter(input.clone()).unwrap();
+
+        assert_eq!(ix.program_id, ORCA_WHIRLPOOL_PROGRAM_ID);
+        assert_eq!(ix.accounts.len(), 11);
+        assert!(ix.accounts[1].is_signer);
+        // B to A swaps read from the owner's B account
+        assert_eq!(ix.accounts[3].pubkey, input.destination);
+        assert_eq!(ix.accounts[5].pubkey, input.source);
+        assert_eq!(ix.data.len(), 8 + 8 + 8 + 16 + 1 + 1);
+        assert_eq!(&ix.data[8..16], &1_000u64.to_le_bytes());
+        assert_eq!(ix.data[41], 0);
+    }
+
+    #[test]
+    fn test_raydium_swap() {
+        let input = input(SwapVenue::RaydiumAmmV4, RAYDIUM_POOL_ACCOUNTS);
+        let ix = swap_adapter(input.clone()).unwrap();
+
+        assert_eq!(ix.program_id, RAYDIUM_AMM_V4_PROGRAM_ID);
+        assert_eq!(ix.accounts.len(), 18);
+        assert!(!ix.accounts[2].is_writable);
+        assert!(ix.accounts[1].is_writable);
+        assert_eq!(ix.accounts[15].pubkey, input.source);
+        assert!(ix.accounts[17].is_signer);
+        assert_eq!(ix.data[0], RAYDIUM_SWAP_BASE_IN_TAG);
+        assert_eq!(&ix.data[9..17], &990u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_swap_rejects_bad_input() {
+        assert!(swap_adapter(input(SwapVenue::RaydiumAmmV4, 3)).is_err());
+
+        let mut zero = input(SwapVenue::RaydiumAmmV4, RAYDIUM_POOL_ACCOUNTS);
+        zero.amount_in = 0;
+        assert!(swap_adapter(zero).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+// Share-based vault function
+// Registry ID: 1005
+// Purpose: Deposit/withdraw accounting with deposit caps and a withdrawal queue
+
+use crate::{
+    states::{StateValidator, VaultPosition, VaultState, WithdrawalRequest},
+    Environment,
+};
+use anchor_lang::prelude::*;
+
+/// Error type for vault operations
+#[error_code]
+pub enum VaultError {
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Deposit exceeds vault cap")]
+    DepositCapExceeded,
+    #[msg("Deposit too small to mint shares")]
+    DepositTooSmall,
+    #[msg("Not enough unlocked shares")]
+    InsufficientShares,
+    #[msg("Share position is missing or not owned by the caller")]
+    InvalidPosition,
+    #[msg("Withdrawal queue is full")]
+    WithdrawalQueueFull,
+    #[msg("Only the vault authority may do this")]
+    Unauthorized,
+    #[msg("Vault accounting overflow")]
+    Overflow,
+    #[msg("Token account for the transfer is missing or does not match")]
+    MissingTokenAccount,
+}
+
+/// Vault operation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum VaultInput {
+    /// Deposit assets and mint shares to the caller's position
+    Deposit { amount: u64 },
+    /// Lock `shares` from the caller's position in a queued withdrawal
+    RequestWithdrawal { shares: u64 },
+    /// Pay queued withdrawals in order from the vault's balance (authority only)
+    ProcessWithdrawals,
+    /// Change the deposit cap (authority only, 0 for no cap)
+    SetDepositCap { deposit_cap: u64 },
+}
+
+/// Assets owed to a fulfilled withdrawal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawalPayout {
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub assets: u64,
+}
+
+/// Vault operation result
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, PartialEq, Eq)]
+pub enum VaultResult {
+    Deposited { shares: u64 },
+    WithdrawalQueued { position: u8 },
+    WithdrawalsProcessed { payouts: Vec<WithdrawalPayout> },
+    DepositCapSet,
+}
+
+/// Share-based vault function
+///
+/// Applies one operation to `state`. Deposits and withdrawal requests
+/// update the caller's `position`, which must belong to them. Token
+/// movements are left to the caller: deposits must transfer `amount` into
+/// the vault and each returned payout must be transferred out of it.
+///
+/// `vault_balance` is the vault token account's balance, and bounds the
+/// withdrawals `ProcessWithdrawals` can pay.
+pub fn vault(
+    input: VaultInput,
+    state: &mut VaultState,
+    position: Option<&mut VaultPosition>,
+    env: &Environment,
+    vault_balance: u64,
+) -> Result<VaultResult> {
+    let result = match input {
+        VaultInput::Deposit { amount } => {
+            let position = caller_position(position, env)?;
+            require!(amount > 0, VaultError::ZeroAmount);
+            require!(
+                amount <= state.remaining_capacity(),
+                VaultError::DepositCapExceeded
+            );
+
+            let shares = state
+                .shares_for_assets(amount)
+                .ok_or(VaultError::Overflow)?;
+            require!(shares > 0, VaultError::DepositTooSmall);
+
+            state.total_assets = state
+                .total_assets
+                .checked_add(amount)
+                .ok_or(VaultError::Overflow)?;
+            state.total_shares = state
+                .total_shares
+                .checked_add(shares)
+                .ok_or(VaultError::Overflow)?;
+            position.shares = position
+                .shares
+                .checked_add(shares)
+                .ok_or(VaultError::Overflow)?;
+
+            msg!("Deposited {} for {} shares", amount, shares);
+            VaultResult::Deposited { shares }
+        }
+        VaultInput::RequestWithdrawal { shares } => {
+            let position = caller_position(position, env)?;
+            require!(shares > 0, VaultError::ZeroAmount);
+            require!(shares <= position.shares, VaultError::InsufficientShares);
+            require!(
+                state.withdrawal_queue.len() < VaultState::MAX_WITHDRAWAL_QUEUE,
+                VaultError::WithdrawalQueueFull
+            );
+
+            // Queued shares leave the position until they are burned
+            position.shares -= shares;
+            state.withdrawal_queue.push(WithdrawalRequest {
+                owner: env.caller,
+                shares,
+                requested_at: env.timestamp,
+            });
+
+            let position = (state.withdrawal_queue.len() - 1) as u8;
+            msg!("Queued withdrawal of {} shares at {}", shares, position);
+            VaultResult::WithdrawalQueued { position }
+        }
+        VaultInput::ProcessWithdrawals => {
+            require_keys_eq!(env.caller, state.authority, VaultError::Unauthorized);
+
+            let mut available_liquidity = vault_balance;
+            let mut payouts = Vec::new();
+            while let Some(request) = state.withdrawal_queue.first() {
+                let assets = state
+                    .assets_for_shares(request.shares)
+                    .ok_or(VaultError::Overflow)?;
+                if assets > available_liquidity {
+                    break;
+                }
+
+                available_liquidity -= assets;
+                state.total_assets -= assets;
+                state.total_shares -= request.shares;
+                payouts.push(WithdrawalPayout {
+                    owner: request.owner,
+                    shares: request.shares,
+                    assets,
+                });
+                state.withdrawal_queue.remove(0);
+            }
+
+            msg!("Processed {} withdrawals", payouts.len());
+            VaultResult::WithdrawalsProcessed { payouts }
+        }
+        VaultInput::SetDepositCap { deposit_cap } => {
+            require_keys_eq!(env.caller, state.authority, VaultError::Unauthorized);
+            state.deposit_cap = deposit_cap;
+            VaultResult::DepositCapSet
+        }
+    };
+
+    state.validate()?;
+    Ok(result)
+}
+
+/// The position updated by a deposit or withdrawal request, checked to
+/// belong to the caller
+fn caller_position<'a>(
+    position: Option<&'a mut VaultPosition>,
+    env: &Environment,
+) -> Result<&'a mut VaultPosition> {
+    let position = position.ok_or(VaultError::InvalidPosition)?;
+    require_keys_eq!(position.owner, env.caller, VaultError::InvalidPosition);
+    Ok(position)
+}
+
+/// Metadata for function registry
+pub const FUNCTION_ID: u64 = 1005;
+pub const FUNCTION_NAME: &str = "vault";
+pub const FUNCTION_VERSION: u16 = 1;
+pub const COMPUTE_UNITS: u64 = 8_000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(caller: Pubkey) -> Environment {
+        Environment {
+            caller,
+            timestamp: 1_700_000_000,
+            slot: 1,
+            ..Default::default()
+        }
+    }
+
+    fn deposit(state: &mut VaultState, position: &mut VaultPosition, amount: u64) -> Result<VaultResult> {
+        let caller = position.owner;
+        vault(VaultInput::Deposit { amount }, state, Some(position), &env(caller), 0)
+    }
+
+    #[test]
+    fn test_vault_share_accounting() {
+        let authority = Pubkey::new_unique();
+        let alice = Pubkey::new_unique();
+        let mut state = VaultState::new(authority, Pubkey::new_unique(), 1_000);
+        let mut position = VaultPosition::new(Pubkey::new_unique(), alice);
+
+        let result = deposit(&mut state, &mut position, 100);
+        assert_eq!(result.unwrap(), VaultResult::Deposited { shares: 100 });
+
+        // Yield doubles the share price
+        state.total_assets += 100;
+        let result = deposit(&mut state, &mut position, 50);
+        assert_eq!(result.unwrap(), VaultResult::Deposited { shares: 25 });
+
+        assert!(deposit(&mut state, &mut position, 751).is_err());
+        assert_eq!(state.total_assets, 250);
+        assert_eq!(state.total_shares, 125);
+        assert_eq!(position.shares, 125);
+
+        // Shares are only credited to the caller's own position
+        let input = VaultInput::Deposit { amount: 10 };
+        let result = vault(input, &mut state, Some(&mut position), &env(authority), 0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_vault_withdrawal_queue() {
+        let authority = Pubkey::new_unique();
+        let vault_key = Pubkey::new_unique();
+        let mut state = VaultState::new(authority, Pubkey::new_unique(), 0);
+        let mut alice = VaultPosition::new(vault_key, Pubkey::new_unique());
+        let mut bob = VaultPosition::new(vault_key, Pubkey::new_unique());
+        deposit(&mut state, &mut alice, 200).unwrap();
+        deposit(&mut state, &mut bob, 100).unwrap();
+
+        let request = VaultInput::RequestWithdrawal { shares: 100 };
+        let alice_env = env(alice.owner);
+        vault(request.clone(), &mut state, Some(&mut alice), &alice_env, 0).unwrap();
+        vault(request.clone(), &mut state, Some(&mut alice), &alice_env, 0).unwrap();
+        assert_eq!(alice.shares, 0);
+
+        // Queued shares are locked and cannot be queued again
+        assert!(vault(request.clone(), &mut state, Some(&mut alice), &alice_env, 0).is_err());
+        let bob_env = env(bob.owner);
+        vault(request, &mut state, Some(&mut bob), &bob_env, 0).unwrap();
+
+        // Only the authority can process the queue
+        let result = vault(VaultInput::ProcessWithdrawals, &mut state, None, &bob_env, 300);
+        assert!(result.is_err());
+
+        // Only the first withdrawal fits the vault's balance
+        let result = vault(VaultInput::ProcessWithdrawals, &mut state, None, &env(authority), 150);
+        assert_eq!(
+            result.unwrap(),
+            VaultResult::WithdrawalsProcessed {
+                payouts: vec![WithdrawalPayout {
+                    owner: alice.owner,
+                    shares: 100,
+                    assets: 100,
+                }],
+            }
+        );
+        assert_eq!(state.withdrawal_queue.len(), 2);
+        assert_eq!(state.total_assets, 200);
+        assert_eq!(state.total_shares, 200);
+    }
+
+    #[test]
+    fn test_vault_set_deposit_cap() {
+        let authority = Pubkey::new_unique();
+        let mut state = VaultState::new(authority, Pubkey::new_unique(), 0);
+        let input = VaultInput::SetDepositCap { deposit_cap: 10 };
+
+        assert!(vault(input.clone(), &mut state, None, &env(Pubkey::new_unique()), 0).is_err());
+        vault(input, &mut state, None, &env(authority), 0).unwrap();
+        assert_eq!(state.remaining_capacity(), 10);
+    }
+}
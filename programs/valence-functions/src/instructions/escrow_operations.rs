@@ -0,0 +1,152 @@
+// Escrow instructions dispatching to the escrow function
+//
+// Locking moves the depositor's lamports into a vault PDA of this program,
+// keyed by the agreement. The escrow function decides the outcome; these
+// instructions pay it out of the vault with the vault's seeds, returning the
+// vault's rent reserve to the depositor once the agreement settles.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use valence_kernel::state::Session;
+
+use crate::functions::escrow::{
+    escrow, escrow_lock, EscrowError, EscrowInput, EscrowLockInput, EscrowOutcome,
+};
+use crate::states::{seeds, EscrowAgreement};
+use crate::Environment;
+
+// ================================
+// Lock Escrow Instruction
+// ================================
+
+#[derive(Accounts)]
+#[instruction(input: EscrowLockInput)]
+pub struct LockEscrow<'info> {
+    #[account(
+        init,
+        payer = depositor,
+        space = EscrowAgreement::LEN,
+        seeds = [seeds::ESCROW_AGREEMENT, depositor.key().as_ref(), input.child_account.as_ref()],
+        bump
+    )]
+    pub agreement: Box<Account<'info, EscrowAgreement>>,
+
+    /// Account holding the locked lamports until the agreement settles
+    #[account(
+        mut,
+        seeds = [seeds::ESCROW_VAULT, agreement.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// Depositor's kernel session, which must own the agreement's child account
+    #[account(
+        constraint = session.owner == depositor.key() @ EscrowError::Unauthorized,
+        constraint = session.is_child_account(&input.child_account) @ EscrowError::InvalidTerms
+    )]
+    pub session: Box<Account<'info, Session>>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create an escrow agreement over a child account of the signer's session
+/// and lock its amount in the agreement's vault
+#[allow(clippy::needless_pass_by_value)]
+pub fn lock_escrow(ctx: Context<LockEscrow>, input: EscrowLockInput) -> Result<()> {
+    let env = Environment::for_caller(ctx.accounts.depositor.key())?;
+    ctx.accounts.agreement.set_inner(escrow_lock(input, &env)?);
+
+    // The vault holds no data, so it only needs the rent-exempt minimum for
+    // an empty account on top of the locked amount
+    let reserve = Rent::get()?.minimum_balance(0);
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        ctx.accounts.agreement.amount.saturating_add(reserve),
+    )
+}
+
+// ================================
+// Escrow Operation Instruction
+// ================================
+
+#[derive(Accounts)]
+pub struct EscrowOperation<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::ESCROW_AGREEMENT, agreement.depositor.as_ref(), agreement.child_account.as_ref()],
+        bump
+    )]
+    pub agreement: Box<Account<'info, EscrowAgreement>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ESCROW_VAULT, agreement.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Checked against the agreement; receives refunds and the vault's rent reserve
+    #[account(mut, address = agreement.depositor @ EscrowError::AccountMismatch)]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the agreement; receives released funds
+    #[account(mut, address = agreement.beneficiary @ EscrowError::AccountMismatch)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Apply an approval, dispute, resolution or claim to an escrow agreement,
+/// paying out of the vault once it settles
+#[allow(clippy::needless_pass_by_value)]
+pub fn execute_escrow(ctx: Context<EscrowOperation>, input: EscrowInput) -> Result<EscrowOutcome> {
+    let env = Environment::for_caller(ctx.accounts.caller.key())?;
+    let accounts = ctx.accounts;
+    let outcome = escrow(input, &mut accounts.agreement, &env)?;
+
+    let (recipient, amount) = match &outcome {
+        EscrowOutcome::Pending => return Ok(outcome),
+        EscrowOutcome::Release { amount, .. } => (accounts.beneficiary.to_account_info(), *amount),
+        EscrowOutcome::Refund { amount, .. } => (accounts.depositor.to_account_info(), *amount),
+    };
+    let reserve = accounts
+        .vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(EscrowError::InsufficientFunds)?;
+
+    let agreement = accounts.agreement.key();
+    let bump = [ctx.bumps.vault];
+    let signer_seeds: &[&[u8]] = &[seeds::ESCROW_VAULT, agreement.as_ref(), &bump];
+    let pay = |to, lamports| {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                accounts.system_program.to_account_info(),
+                Transfer {
+                    from: accounts.vault.to_account_info(),
+                    to,
+                },
+                &[signer_seeds],
+            ),
+            lamports,
+        )
+    };
+
+    pay(recipient, amount)?;
+    if reserve > 0 {
+        pay(accounts.depositor.to_account_info(), reserve)?;
+    }
+
+    Ok(outcome)
+}
@@ -0,0 +1,12 @@
+// Instruction module for valence-functions
+// Exports the handlers dispatching to function implementations and their contexts
+
+pub mod escrow_operations;
+pub mod price_bound_operations;
+pub mod swap_operations;
+pub mod vault_operations;
+
+pub use escrow_operations::*;
+pub use price_bound_operations::*;
+pub use swap_operations::*;
+pub use vault_operations::*;
@@ -0,0 +1,28 @@
+// Price bound instruction dispatching to the oracle price bound guard
+//
+// The guard fails the instruction, and with it the surrounding transaction,
+// unless the oracle price is fresh and within bounds at the current slot.
+
+use anchor_lang::prelude::*;
+
+use crate::functions::price_bound_guard::{price_bound_guard, PriceBoundInput, PriceBoundResult};
+
+// ================================
+// Check Price Bound Instruction
+// ================================
+
+#[derive(Accounts)]
+pub struct CheckPriceBound<'info> {
+    /// CHECK: Owner checked against the input's oracle program, data parsed by the guard
+    pub price_account: UncheckedAccount<'info>,
+}
+
+/// Check `price_account` against the configured freshness and bounds
+#[allow(clippy::needless_pass_by_value)]
+pub fn execute_price_bound(
+    ctx: Context<CheckPriceBound>,
+    input: PriceBoundInput,
+) -> Result<PriceBoundResult> {
+    let current_slot = Clock::get()?.slot;
+    price_bound_guard(input, &ctx.accounts.price_account, current_slot)
+}
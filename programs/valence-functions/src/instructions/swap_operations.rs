@@ -0,0 +1,71 @@
+// Swap instruction dispatching to the swap adapter
+//
+// The adapter builds the venue's swap instruction from the accounts passed
+// here; this instruction invokes it, so the user's signature carries through
+// to the venue. Output is enforced by the venue through `min_out`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::Token;
+
+use crate::functions::swap_adapter::{swap_adapter, SwapAdapterError, SwapInput, SwapVenue};
+
+// ================================
+// Swap Instruction
+// ================================
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// Owner of the source and destination token accounts
+    pub user: Signer<'info>,
+
+    /// CHECK: Token account the venue debits, validated by the venue
+    #[account(mut)]
+    pub source: UncheckedAccount<'info>,
+
+    /// CHECK: Token account the venue credits, validated by the venue
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the program the adapter targets for the venue
+    pub venue_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Swap through `venue`, taking its pool accounts from the remaining
+/// accounts in the order documented for the venue
+pub fn execute_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    amount_in: u64,
+    min_out: u64,
+    venue: SwapVenue,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let instruction = swap_adapter(SwapInput {
+        amount_in,
+        min_out,
+        venue,
+        user: accounts.user.key(),
+        source: accounts.source.key(),
+        destination: accounts.destination.key(),
+        pool_accounts: ctx.remaining_accounts.iter().map(|account| account.key()).collect(),
+    })?;
+    require_keys_eq!(
+        instruction.program_id,
+        accounts.venue_program.key(),
+        SwapAdapterError::InvalidVenueProgram
+    );
+
+    let mut account_infos = vec![
+        accounts.token_program.to_account_info(),
+        accounts.user.to_account_info(),
+        accounts.source.to_account_info(),
+        accounts.destination.to_account_info(),
+        accounts.venue_program.to_account_info(),
+    ];
+    account_infos.extend_from_slice(ctx.remaining_accounts);
+    invoke(&instruction, &account_infos)?;
+
+    Ok(())
+}
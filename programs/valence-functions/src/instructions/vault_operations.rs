@@ -0,0 +1,208 @@
+// Vault instructions dispatching to the share-based vault function
+//
+// The vault function only does the share accounting. These instructions
+// load and store its state, move tokens between the caller and the vault's
+// token account, and pay processed withdrawals out of that account, which
+// is owned by the vault state PDA.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::functions::vault::{vault, VaultError, VaultInput, VaultResult};
+use crate::states::{seeds, VaultPosition, VaultState};
+use crate::Environment;
+
+// ================================
+// Initialize Vault Instruction
+// ================================
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = VaultState::LEN,
+        seeds = [seeds::VAULT_STATE, authority.key().as_ref(), asset_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// Token account holding the vault's assets
+    #[account(
+        init,
+        payer = authority,
+        seeds = [seeds::VAULT_TOKEN, vault_state.key().as_ref()],
+        bump,
+        token::mint = asset_mint,
+        token::authority = vault_state
+    )]
+    pub vault_token: Box<Account<'info, TokenAccount>>,
+
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Create a vault for `asset_mint` administered by the signer
+#[allow(clippy::needless_pass_by_value)]
+pub fn initialize_vault(ctx: Context<InitializeVault>, deposit_cap: u64) -> Result<()> {
+    ctx.accounts.vault_state.set_inner(VaultState::new(
+        ctx.accounts.authority.key(),
+        ctx.accounts.asset_mint.key(),
+        deposit_cap,
+    ));
+    Ok(())
+}
+
+// ================================
+// Open Vault Position Instruction
+// ================================
+
+#[derive(Accounts)]
+pub struct OpenVaultPosition<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = VaultPosition::LEN,
+        seeds = [seeds::VAULT_POSITION, vault_state.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Box<Account<'info, VaultPosition>>,
+
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the signer's empty share position in a vault
+#[allow(clippy::needless_pass_by_value)]
+pub fn open_vault_position(ctx: Context<OpenVaultPosition>) -> Result<()> {
+    ctx.accounts.position.set_inner(VaultPosition::new(
+        ctx.accounts.vault_state.key(),
+        ctx.accounts.owner.key(),
+    ));
+    Ok(())
+}
+
+// ================================
+// Vault Operation Instruction
+// ================================
+
+#[derive(Accounts)]
+pub struct VaultOperation<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::VAULT_STATE, vault_state.authority.as_ref(), vault_state.asset_mint.as_ref()],
+        bump
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::VAULT_TOKEN, vault_state.key().as_ref()],
+        bump
+    )]
+    pub vault_token: Box<Account<'info, TokenAccount>>,
+
+    /// Caller's share position, required by deposits and withdrawal requests
+    #[account(
+        mut,
+        seeds = [seeds::VAULT_POSITION, vault_state.key().as_ref(), caller.key().as_ref()],
+        bump
+    )]
+    pub position: Option<Box<Account<'info, VaultPosition>>>,
+
+    /// Caller's token account deposits are paid from
+    #[account(
+        mut,
+        token::mint = vault_state.asset_mint,
+        token::authority = caller
+    )]
+    pub caller_token: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Apply one vault operation
+///
+/// `ProcessWithdrawals` pays each payout to the next remaining account,
+/// which must be a token account of the asset owned by the payout's owner.
+pub fn execute_vault<'info>(
+    ctx: Context<'_, '_, 'info, 'info, VaultOperation<'info>>,
+    input: VaultInput,
+) -> Result<VaultResult> {
+    let env = Environment::for_caller(ctx.accounts.caller.key())?;
+    let accounts = ctx.accounts;
+    let deposit = match input {
+        VaultInput::Deposit { amount } => Some(amount),
+        _ => None,
+    };
+
+    let result = vault(
+        input,
+        &mut accounts.vault_state,
+        accounts.position.as_mut().map(|position| &mut ***position),
+        &env,
+        accounts.vault_token.amount,
+    )?;
+
+    if let Some(amount) = deposit {
+        let caller_token = accounts
+            .caller_token
+            .as_ref()
+            .ok_or(VaultError::MissingTokenAccount)?;
+        token::transfer(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                Transfer {
+                    from: caller_token.to_account_info(),
+                    to: accounts.vault_token.to_account_info(),
+                    authority: accounts.caller.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    if let VaultResult::WithdrawalsProcessed { payouts } = &result {
+        let authority = accounts.vault_state.authority;
+        let asset_mint = accounts.vault_state.asset_mint;
+        let bump = [ctx.bumps.vault_state];
+        let signer_seeds: &[&[u8]] = &[seeds::VAULT_STATE, authority.as_ref(), asset_mint.as_ref(), &bump];
+
+        require!(
+            ctx.remaining_accounts.len() >= payouts.len(),
+            VaultError::MissingTokenAccount
+        );
+        for (payout, recipient) in payouts.iter().zip(ctx.remaining_accounts) {
+            require_keys_eq!(*recipient.owner, token::ID, VaultError::MissingTokenAccount);
+            let recipient_token = TokenAccount::try_deserialize(&mut &recipient.try_borrow_data()?[..])?;
+            require_keys_eq!(recipient_token.owner, payout.owner, VaultError::MissingTokenAccount);
+            require_keys_eq!(recipient_token.mint, asset_mint, VaultError::MissingTokenAccount);
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: accounts.vault_token.to_account_info(),
+                        to: recipient.clone(),
+                        authority: accounts.vault_state.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                payout.assets,
+            )?;
+        }
+    }
+
+    Ok(result)
+}
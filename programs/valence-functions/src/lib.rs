@@ -56,6 +56,19 @@ impl Environment {
         }
     }
 
+    /// Environment of the current instruction called by `caller`
+    pub fn for_caller(caller: Pubkey) -> Result<Self> {
+        let clock = Clock::get()?;
+        Ok(Self::from_kernel_context(
+            clock.slot,
+            clock.epoch,
+            caller,
+            Pubkey::default(),
+            caller,
+            clock.unix_timestamp,
+        ))
+    }
+
     /// Check if the environment represents a valid state
     pub fn is_valid(&self) -> bool {
         self.timestamp > 0 && self.slot > 0
@@ -73,6 +86,9 @@ pub mod functions;
 /// State definitions for function data structures
 pub mod states;
 
+/// Instructions dispatching to functions that keep on-chain state
+pub mod instructions;
+
 // Removed: Complex escrow functionality (if not used by kernel)
 // Removed: Complex shard trait system (if not used by kernel)
 
@@ -86,6 +102,71 @@ pub use functions::*;
 /// Re-export state definitions
 pub use states::*;
 
+/// Re-export instruction contexts and arguments at crate root for Anchor's macro
+#[allow(ambiguous_glob_reexports)]
+pub use instructions::*;
+pub use functions::escrow::{EscrowInput, EscrowLockInput, EscrowOutcome};
+pub use functions::price_bound_guard::{PriceBoundInput, PriceBoundResult};
+pub use functions::swap_adapter::SwapVenue;
+pub use functions::vault::{VaultInput, VaultResult};
+
+// ================================
+// Program Instruction Handlers
+// ================================
+
+/// Entrypoint the kernel's registry resolves stateful functions to, so
+/// shards reach them through `CallRegisteredFunction`
+#[program]
+pub mod valence_functions {
+    use super::*;
+
+    /// Creates a vault and the token account holding its assets
+    pub fn initialize_vault(ctx: Context<InitializeVault>, deposit_cap: u64) -> Result<()> {
+        instructions::initialize_vault(ctx, deposit_cap)
+    }
+
+    /// Creates the signer's share position in a vault
+    pub fn open_vault_position(ctx: Context<OpenVaultPosition>) -> Result<()> {
+        instructions::open_vault_position(ctx)
+    }
+
+    /// Applies a deposit, withdrawal request or authority action to a vault
+    pub fn vault_operation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VaultOperation<'info>>,
+        input: VaultInput,
+    ) -> Result<VaultResult> {
+        instructions::execute_vault(ctx, input)
+    }
+
+    /// Creates an escrow agreement and locks its lamports in a vault
+    pub fn lock_escrow(ctx: Context<LockEscrow>, input: EscrowLockInput) -> Result<()> {
+        instructions::lock_escrow(ctx, input)
+    }
+
+    /// Applies an approval, dispute, resolution or claim to an escrow
+    pub fn escrow_operation(ctx: Context<EscrowOperation>, input: EscrowInput) -> Result<EscrowOutcome> {
+        instructions::execute_escrow(ctx, input)
+    }
+
+    /// Fails unless an oracle price is fresh and within the configured bounds
+    pub fn check_price_bound(
+        ctx: Context<CheckPriceBound>,
+        input: PriceBoundInput,
+    ) -> Result<PriceBoundResult> {
+        instructions::execute_price_bound(ctx, input)
+    }
+
+    /// Swaps the user's tokens through an AMM venue
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_in: u64,
+        min_out: u64,
+        venue: SwapVenue,
+    ) -> Result<()> {
+        instructions::execute_swap(ctx, amount_in, min_out, venue)
+    }
+}
+
 // ================================
 // Removed Complex Abstractions
 // ================================
@@ -1,6 +1,7 @@
 // State definitions for shard-specific data structures in valence-functions
 // Provides reusable state types for common shard patterns like escrow
 use anchor_lang::prelude::*;
+use valence_common::math::mul_div;
 
 // ================================
 // Common Error Types
@@ -21,6 +22,23 @@ pub enum FunctionsError {
     OperationFailed,
 }
 
+impl FunctionsError {
+    /// Every variant in declaration order, matching the Anchor error numbers
+    const ALL: [Self; 4] = [
+        Self::InvalidParameters,
+        Self::Unauthorized,
+        Self::InvalidState,
+        Self::OperationFailed,
+    ];
+
+    /// Decode an error from its Anchor error number
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(anchor_lang::error::ERROR_CODE_OFFSET)?;
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
 // ================================
 // Core State Type System
 // ================================
@@ -234,48 +252,337 @@ impl StateValidator for EscrowState {
     }
 }
 
+// ================================
+// Vault State
+// ================================
+
+/// Withdrawal waiting for vault liquidity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawalRequest {
+    /// Account that receives the withdrawn assets
+    pub owner: Pubkey,
+    /// Shares burned when the request is fulfilled
+    pub shares: u64,
+    /// Unix timestamp when the withdrawal was requested
+    pub requested_at: i64,
+}
+
+/// Shares held by one owner in a vault
+/// Queued withdrawals move shares out of the position, so they cannot be
+/// queued twice
+#[account]
+#[derive(Debug)]
+pub struct VaultPosition {
+    /// Vault the shares were minted by
+    pub vault: Pubkey,
+
+    /// Owner of the shares
+    pub owner: Pubkey,
+
+    /// Shares not locked in a queued withdrawal
+    pub shares: u64,
+
+    /// Reserved space for future upgrades
+    pub _reserved: [u8; 32],
+}
+
+impl VaultPosition {
+    /// Account space required for serialization
+    pub const LEN: usize = 8 +      // anchor discriminator
+        32 +     // vault pubkey
+        32 +     // owner pubkey
+        8 +      // shares
+        32; // reserved space
+
+    /// Create an empty position for `owner` in `vault`
+    pub fn new(vault: Pubkey, owner: Pubkey) -> Self {
+        Self {
+            vault,
+            owner,
+            shares: 0,
+            _reserved: [0u8; 32],
+        }
+    }
+}
+
+/// Share-based vault holding a single asset
+/// Deposits mint shares at the current exchange rate into the depositor's
+/// position and withdrawals are queued until the vault has liquidity to pay
+/// them out in order
+#[account]
+#[derive(Debug)]
+pub struct VaultState {
+    /// Authority allowed to change the deposit cap and process withdrawals
+    pub authority: Pubkey,
+
+    /// Asset held by the vault (SPL token mint)
+    pub asset_mint: Pubkey,
+
+    /// Assets held by the vault, including those owed to queued withdrawals
+    pub total_assets: u64,
+
+    /// Shares outstanding across all positions and queued withdrawals
+    pub total_shares: u64,
+
+    /// Maximum total assets accepted through deposits (0 for no cap)
+    pub deposit_cap: u64,
+
+    /// Pending withdrawals, fulfilled first in first out
+    pub withdrawal_queue: Vec<WithdrawalRequest>,
+
+    /// Reserved space for future upgrades
+    pub _reserved: [u8; 32],
+}
+
+impl VaultState {
+    /// Maximum pending withdrawals
+    pub const MAX_WITHDRAWAL_QUEUE: usize = 16;
+
+    /// Account space required for serialization
+    pub const LEN: usize = 8 +      // anchor discriminator
+        32 +     // authority pubkey
+        32 +     // asset_mint pubkey
+        8 +      // total_assets
+        8 +      // total_shares
+        8 +      // deposit_cap
+        4 + Self::MAX_WITHDRAWAL_QUEUE * (32 + 8 + 8) + // withdrawal_queue
+        32; // reserved space
+
+    /// Create an empty vault
+    pub fn new(authority: Pubkey, asset_mint: Pubkey, deposit_cap: u64) -> Self {
+        Self {
+            authority,
+            asset_mint,
+            total_assets: 0,
+            total_shares: 0,
+            deposit_cap,
+            withdrawal_queue: Vec::new(),
+            _reserved: [0u8; 32],
+        }
+    }
+
+    /// Shares minted for depositing `assets`, rounding down
+    /// The first deposit mints shares one to one
+    pub fn shares_for_assets(&self, assets: u64) -> Option<u64> {
+        if self.total_shares == 0 || self.total_assets == 0 {
+            return Some(assets);
+        }
+        mul_div(assets, self.total_shares, self.total_assets)
+    }
+
+    /// Assets paid out for redeeming `shares`, rounding down
+    pub fn assets_for_shares(&self, shares: u64) -> Option<u64> {
+        if self.total_shares == 0 {
+            return None;
+        }
+        mul_div(shares, self.total_assets, self.total_shares)
+    }
+
+    /// Assets that can still be deposited before reaching the cap
+    pub fn remaining_capacity(&self) -> u64 {
+        if self.deposit_cap == 0 {
+            u64::MAX - self.total_assets
+        } else {
+            self.deposit_cap.saturating_sub(self.total_assets)
+        }
+    }
+
+    /// Shares locked in queued withdrawals
+    pub fn queued_shares(&self) -> u64 {
+        self.withdrawal_queue.iter().map(|request| request.shares).sum()
+    }
+}
+
+impl StateValidator for VaultState {
+    /// Validate vault accounting consistency
+    fn validate(&self) -> Result<()> {
+        // Shares and assets are either both present or both absent
+        require!(
+            (self.total_shares == 0) == (self.total_assets == 0),
+            FunctionsError::InvalidState
+        );
+
+        require!(
+            self.withdrawal_queue.len() <= Self::MAX_WITHDRAWAL_QUEUE,
+            FunctionsError::InvalidState
+        );
+
+        // Queued withdrawals cannot lock more shares than exist
+        let queued = self
+            .withdrawal_queue
+            .iter()
+            .try_fold(0u64, |total, request| total.checked_add(request.shares))
+            .ok_or(FunctionsError::InvalidState)?;
+        require!(queued <= self.total_shares, FunctionsError::InvalidState);
+
+        Ok(())
+    }
+
+    /// Check if the state allows the specified operation
+    fn allows_operation(&self, operation: &str) -> bool {
+        match operation {
+            "deposit" => self.remaining_capacity() > 0,
+            "request_withdrawal" => {
+                self.total_shares > 0
+                    && self.withdrawal_queue.len() < Self::MAX_WITHDRAWAL_QUEUE
+            }
+            "process_withdrawals" => !self.withdrawal_queue.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+// ================================
+// Escrow Agreement State
+// ================================
+
+/// Lifecycle status of an escrow agreement
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgreementStatus {
+    /// Funds locked, awaiting approvals or the timeout
+    Locked,
+    /// A party disputed, awaiting the arbiter
+    Disputed,
+    /// Funds released to the beneficiary
+    Released,
+    /// Funds returned to the depositor
+    Refunded,
+}
+
+/// Escrow of lamports held in a vault PDA until the agreement settles
+/// Released when both parties approve or once the timeout passes undisputed,
+/// with an optional arbiter resolving disputes raised before the timeout
+#[account]
+#[derive(Debug)]
+pub struct EscrowAgreement {
+    /// Party that locked the funds
+    pub depositor: Pubkey,
+
+    /// Party receiving the funds on release
+    pub beneficiary: Pubkey,
+
+    /// Party resolving disputes (None disables disputes)
+    pub arbiter: Option<Pubkey>,
+
+    /// Child account of the depositor's session the agreement is made for
+    pub child_account: Pubkey,
+
+    /// Lamports locked in the agreement's vault
+    pub amount: u64,
+
+    /// Unix timestamp when the funds were locked
+    pub created_at: i64,
+
+    /// Unix timestamp after which the beneficiary may claim undisputed funds
+    /// Disputes can only be raised before this time
+    pub release_after: i64,
+
+    pub depositor_approved: bool,
+    pub beneficiary_approved: bool,
+
+    pub status: AgreementStatus,
+
+    /// Reserved space for future upgrades
+    pub _reserved: [u8; 32],
+}
+
+impl EscrowAgreement {
+    /// Account space required for serialization
+    pub const LEN: usize = 8 +      // anchor discriminator
+        32 +     // depositor pubkey
+        32 +     // beneficiary pubkey
+        1 + 32 + // Option<arbiter>
+        32 +     // child_account pubkey
+        8 +      // amount
+        8 +      // created_at timestamp
+        8 +      // release_after timestamp
+        1 + 1 +  // approvals
+        1 +      // status enum
+        32; // reserved space
+
+    /// Check if `key` is the depositor or the beneficiary
+    pub fn is_party(&self, key: &Pubkey) -> bool {
+        *key == self.depositor || *key == self.beneficiary
+    }
+
+    /// Check if the dispute window is still open at `timestamp`
+    pub fn in_dispute_window(&self, timestamp: i64) -> bool {
+        timestamp < self.release_after
+    }
+
+    /// Check if the funds have been paid out
+    pub fn is_settled(&self) -> bool {
+        matches!(
+            self.status,
+            AgreementStatus::Released | AgreementStatus::Refunded
+        )
+    }
+}
+
+impl StateValidator for EscrowAgreement {
+    /// Validate escrow agreement consistency
+    fn validate(&self) -> Result<()> {
+        require!(self.amount > 0, FunctionsError::InvalidParameters);
+        require!(
+            self.release_after > self.created_at,
+            FunctionsError::InvalidParameters
+        );
+        require!(
+            self.depositor != self.beneficiary,
+            FunctionsError::InvalidParameters
+        );
+
+        // The arbiter must be independent of both parties
+        if let Some(arbiter) = self.arbiter {
+            require!(!self.is_party(&arbiter), FunctionsError::InvalidParameters);
+        }
+
+        // Only agreements with an arbiter can be disputed
+        if self.status == AgreementStatus::Disputed {
+            require!(self.arbiter.is_some(), FunctionsError::InvalidState);
+        }
+
+        Ok(())
+    }
+
+    /// Check if the state allows the specified operation
+    fn allows_operation(&self, operation: &str) -> bool {
+        match operation {
+            "approve" | "claim" => self.status == AgreementStatus::Locked,
+            "dispute" => self.status == AgreementStatus::Locked && self.arbiter.is_some(),
+            "resolve" => self.status == AgreementStatus::Disputed,
+            _ => false,
+        }
+    }
+}
+
 // ================================
 // PDA Management System
 // ================================
 
 /// PDA seeds for deterministic address generation
-/// Provides consistent seed values across the application
+/// Defined once in valence-common and re-exported here
 pub mod seeds {
     /// Seed for escrow state PDAs
-    pub const ESCROW_STATE: &[u8] = b"escrow_state";
-    /// Seed for escrow vault PDAs (token holding accounts)
-    pub const ESCROW_VAULT: &[u8] = b"escrow_vault";
+    pub use valence_common::pdas::ESCROW_STATE_SEED as ESCROW_STATE;
+    /// Seed for escrow agreement PDAs
+    pub use valence_common::pdas::ESCROW_AGREEMENT_SEED as ESCROW_AGREEMENT;
+    /// Seed for escrow vault PDAs (accounts holding escrowed funds)
+    pub use valence_common::pdas::ESCROW_VAULT_SEED as ESCROW_VAULT;
+    /// Seed for vault state PDAs
+    pub use valence_common::pdas::VAULT_STATE_SEED as VAULT_STATE;
+    /// Seed for vault token account PDAs
+    pub use valence_common::pdas::VAULT_TOKEN_SEED as VAULT_TOKEN;
+    /// Seed for vault position PDAs
+    pub use valence_common::pdas::VAULT_POSITION_SEED as VAULT_POSITION;
 }
 
 /// PDA derivation utilities for consistent address generation
 /// Ensures deterministic account addresses across instructions
 pub mod pda {
-    use super::*;
-
-    /// Derive escrow state PDA address
-    /// Creates deterministic address based on seller, asset, and nonce
-    pub fn escrow_state(
-        seller: &Pubkey,
-        asset_mint: &Pubkey,
-        nonce: u64,
-        program_id: &Pubkey,
-    ) -> (Pubkey, u8) {
-        Pubkey::find_program_address(
-            &[
-                seeds::ESCROW_STATE,
-                seller.as_ref(),
-                asset_mint.as_ref(),
-                &nonce.to_le_bytes(),
-            ],
-            program_id,
-        )
-    }
-
-    /// Derive escrow vault PDA address for holding tokens
-    /// Creates deterministic address for token custody
-    pub fn escrow_vault(escrow_state: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[seeds::ESCROW_VAULT, escrow_state.as_ref()], program_id)
-    }
+    pub use valence_common::pdas::{
+        escrow_agreement, escrow_state, escrow_vault, vault_position, vault_state, vault_token,
+    };
 }
 
 // ================================
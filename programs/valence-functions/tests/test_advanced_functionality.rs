@@ -2,8 +2,8 @@
 // Tests complex scenarios, performance considerations, and edge cases
 
 use anchor_lang::prelude::*;
-use valence_functions::*;
-use valence_functions::states::{EscrowBuilder, EscrowState, EscrowStatus, pda};
+use ::valence_functions::*;
+use ::valence_functions::states::{EscrowBuilder, EscrowState, EscrowStatus, pda};
 
 #[cfg(test)]
 mod advanced_functionality_tests {
@@ -22,8 +22,8 @@ mod advanced_functionality_tests {
 
     /// Test suite for function composition and chaining
     mod function_composition_tests {
-        use valence_functions::functions::identity;
-        use valence_functions::functions::math_add::{self, AddInput};
+        use ::valence_functions::functions::identity;
+        use ::valence_functions::functions::math_add::{self, AddInput};
 
         #[test]
         fn test_function_chaining_identity_math() {
@@ -87,7 +87,7 @@ mod advanced_functionality_tests {
     /// Test suite for performance and resource usage
     mod performance_tests {
         use super::*;
-        use valence_functions::functions::{identity, math_add, token_validate};
+        use ::valence_functions::functions::{identity, math_add, token_validate};
 
         #[test]
         fn test_compute_unit_estimates_consistency() {
@@ -2,8 +2,8 @@
 // Comprehensive error scenario testing and boundary condition validation
 
 use anchor_lang::prelude::*;
-use valence_functions::*;
-use valence_functions::states::{EscrowBuilder, EscrowState, EscrowStatus, FunctionsError};
+use ::valence_functions::*;
+use ::valence_functions::states::{EscrowBuilder, EscrowState, EscrowStatus, FunctionsError};
 
 #[cfg(test)]
 mod error_handling_tests {
@@ -41,7 +41,7 @@ mod error_handling_tests {
     /// Test suite for math function error handling
     mod math_error_tests {
         use super::*;
-        use valence_functions::functions::math_add::*;
+        use ::valence_functions::functions::math_add::*;
 
         #[test]
         fn test_math_overflow_detection() {
@@ -107,7 +107,7 @@ mod error_handling_tests {
     /// Test suite for token validation error handling
     mod token_validation_error_tests {
         use super::*;
-        use valence_functions::functions::token_validate::*;
+        use ::valence_functions::functions::token_validate::*;
 
         #[test]
         fn test_token_insufficient_balance_error() {
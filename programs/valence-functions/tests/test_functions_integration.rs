@@ -2,8 +2,8 @@
 // Tests the main function implementations and their integration
 
 use anchor_lang::prelude::*;
-use valence_functions::functions::*;
-use valence_functions::*;
+use ::valence_functions::functions::*;
+use ::valence_functions::*;
 
 #[cfg(test)]
 mod function_integration_tests {
@@ -12,7 +12,7 @@ mod function_integration_tests {
     /// Test suite for identity function
     mod identity_tests {
         use super::*;
-        use valence_functions::functions::identity::*;
+        use ::valence_functions::functions::identity::*;
 
         #[test]
         fn test_identity_basic_functionality() {
@@ -52,7 +52,7 @@ mod function_integration_tests {
     /// Test suite for math_add function
     mod math_add_tests {
         use super::*;
-        use valence_functions::functions::math_add::*;
+        use ::valence_functions::functions::math_add::*;
 
         #[test]
         fn test_math_add_normal_operations() {
@@ -134,7 +134,7 @@ mod function_integration_tests {
     /// Test suite for token_validate function
     mod token_validate_tests {
         use super::*;
-        use valence_functions::functions::token_validate::*;
+        use ::valence_functions::functions::token_validate::*;
 
         #[test]
         fn test_token_validate_success() {
@@ -315,4 +315,24 @@ mod function_integration_tests {
             assert_eq!(token_validate::FUNCTION_VERSION, 1);
         }
     }
+    /// Test suite for kernel registry resolution
+    mod registry_tests {
+        use super::*;
+        use valence_kernel::state::function_registry::FunctionInfo;
+
+        #[test]
+        fn test_stateful_functions_resolve_to_program() {
+            // The kernel must CPI into this program to reach its stateful and guard functions
+            for function_id in [
+                price_bound_guard::FUNCTION_ID,
+                vault::FUNCTION_ID,
+                escrow::FUNCTION_ID,
+                swap_adapter::FUNCTION_ID,
+            ] {
+                let entry = FunctionInfo::get_registry_entry(function_id).unwrap();
+                assert_eq!(entry.program_id, ::valence_functions::ID);
+                assert!(entry.is_active);
+            }
+        }
+    }
 }
\ No newline at end of file
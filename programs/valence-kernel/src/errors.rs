@@ -221,5 +221,171 @@ pub enum KernelError {
     
     #[msg("State size exceeds maximum allowed")]
     NamespaceStateTooLarge, // 6908
+
+    // ===== Approval Errors (7000-7099) =====
+    #[msg("Batch requires an ed25519 approval from the guard's approval signer")]
+    MissingBatchApproval, // 7000
+
+    // ===== Commitment Errors (7100-7199) =====
+    #[msg("Batch requires a prior commitment")]
+    MissingBatchCommitment, // 7100
+
+    #[msg("Batch commitment cannot execute before its earliest slot")]
+    CommitmentNotReady, // 7101
+
+    // ===== Assertion Errors (7200-7299) =====
+    #[msg("Account data does not match the asserted bytes")]
+    AccountDataAssertionFailed, // 7200
+
+    #[msg("Token balance is outside the asserted range")]
+    TokenBalanceAssertionFailed, // 7201
+
+    // ===== Relayer Errors (7300-7399) =====
+    #[msg("Transaction submitter is not allowed by the session's fee vault")]
+    SubmitterNotAllowed, // 7300
+
+    // ===== Upgrade Safety Errors (7400-7499) =====
+    #[msg("Program was upgraded after it was allowlisted")]
+    ProgramUpgraded, // 7400
+
+    #[msg("Missing or invalid program data account")]
+    InvalidProgramData, // 7401
+
+    // ===== Chunked Batch Errors (7500-7599) =====
+    #[msg("Batch buffer has no room for the appended operations")]
+    BatchBufferFull, // 7500
+
+    // ===== Operation Data Errors (7600-7699) =====
+    #[msg("Operation data does not match the hash the batch was built with")]
+    OperationDataMismatch, // 7600
+
+    #[msg("Operation references data past the written operation data")]
+    OperationDataOutOfRange, // 7601
+
+    // ===== Statistics Errors (7700-7799) =====
+    #[msg("Session keeps statistics but its stats account was not passed")]
+    MissingSessionStats, // 7700
+
+    // ===== Session Closing Errors (7800-7899) =====
+    #[msg("Session must be invalidated and hold no borrowed or child accounts before closing")]
+    SessionNotClosable, // 7800
 }
 
+impl KernelError {
+    /// Every variant in declaration order, matching the Anchor error numbers
+    const ALL: [Self; 75] = [
+        Self::StateExpired,
+        Self::StateNotActive,
+        Self::StateLocked,
+        Self::InvalidStateTransition,
+        Self::StateAlreadyExists,
+        Self::Unauthorized,
+        Self::InsufficientPermissions,
+        Self::UsageLimitExceeded,
+        Self::SessionExpired,
+        Self::SessionStateNotFound,
+        Self::MaxBoundStatesExceeded,
+        Self::SessionPaused,
+        Self::InvalidSessionConfig,
+        Self::SessionInactive,
+        Self::GuardFailed,
+        Self::ExternalGuardRequired,
+        Self::InvalidGuardProgram,
+        Self::GuardDepthExceeded,
+        Self::ExternalGuardNoReturnData,
+        Self::ExternalGuardInvalidReturn,
+        Self::GuardDataTooLarge,
+        Self::InvalidGuardManifest,
+        Self::AccountDataTooSmall,
+        Self::AccountNotWritable,
+        Self::AccountOwnerMismatch,
+        Self::InvalidPDA,
+        Self::AccountAlreadyInitialized,
+        Self::InvalidVersion,
+        Self::InvalidReservedData,
+        Self::InvalidParameters,
+        Self::AccountAlreadyBorrowed,
+        Self::BorrowCapacityExceeded,
+        Self::AccountNotBorrowed,
+        Self::BorrowedAccountMismatch,
+        Self::MissingRequiredAccount,
+        Self::InvalidAccountData,
+        Self::AccountIndexOutOfBounds,
+        Self::InvalidProgramIndex,
+        Self::UnauthorizedAccount,
+        Self::TooManyAccounts,
+        Self::DuplicateAccount,
+        Self::UnregisteredAccount,
+        Self::AccountAlreadyExists,
+        Self::TooManyChildSessions,
+        Self::ComputeBudgetExceeded,
+        Self::CrossProgramInvocationDepthExceeded,
+        Self::TransactionTooLarge,
+        Self::ProgramNotAllowed,
+        Self::ProgramAlreadyAllowed,
+        Self::AllowlistFull,
+        Self::ReentrancyGuardViolation,
+        Self::InvalidTransaction,
+        Self::ReentrancyViolation,
+        Self::NamespaceEmptyPath,
+        Self::NamespaceInvalidPath,
+        Self::NamespaceEmptySegment,
+        Self::NamespaceInvalidSegment,
+        Self::NamespaceInsufficientPrivileges,
+        Self::NamespaceAlreadyExists,
+        Self::NamespaceNotFound,
+        Self::NamespaceHasChildren,
+        Self::NamespaceStateTooLarge,
+        Self::MissingBatchApproval,
+        Self::MissingBatchCommitment,
+        Self::CommitmentNotReady,
+        Self::AccountDataAssertionFailed,
+        Self::TokenBalanceAssertionFailed,
+        Self::SubmitterNotAllowed,
+        Self::ProgramUpgraded,
+        Self::InvalidProgramData,
+        Self::BatchBufferFull,
+        Self::OperationDataMismatch,
+        Self::OperationDataOutOfRange,
+        Self::MissingSessionStats,
+        Self::SessionNotClosable,
+    ];
+
+    /// Decode an error from its Anchor error number
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(anchor_lang::error::ERROR_CODE_OFFSET)?;
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code_round_trip() {
+        for error in KernelError::ALL {
+            let decoded = KernelError::from_code(error.into()).unwrap();
+            assert_eq!(decoded as u32, error as u32);
+        }
+        let past_end = u32::from(KernelError::SessionNotClosable) + 1;
+        assert!(KernelError::from_code(past_end).is_none());
+        assert!(KernelError::from_code(0).is_none());
+    }
+
+    #[test]
+    fn test_introspection_errors_do_not_alias() {
+        use valence_common::introspection::IntrospectionError;
+
+        // Guards propagate these from execute_batch alongside kernel errors
+        for error in [
+            IntrospectionError::InvalidInstructionsSysvar,
+            IntrospectionError::MissingInstruction,
+            IntrospectionError::InvalidEd25519Instruction,
+        ] {
+            assert!(KernelError::from_code(error.into()).is_none());
+        }
+    }
+}
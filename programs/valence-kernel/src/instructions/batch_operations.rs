@@ -25,9 +25,10 @@ use anchor_lang::solana_program;
 use crate::{
     errors::KernelError,
     validation,
-    state::{Session, GuardAccount, AllowlistAccount, SessionAccountLookup},
+    state::{program_deployment_slot, Session, GuardAccount, AllowlistAccount, SessionAccountLookup, SessionNonce, BatchCommitment, SessionStats, OperationCounts, FeeVault, OperationData},
     namespace::NamespacePath,
-    MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_OPERATION_DATA_SIZE, MAX_CPI_ACCOUNT_INDICES,
+    MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS, MAX_OPERATION_DATA_SIZE, MAX_OPERATION_DATA_ACCOUNT_SIZE,
+    MAX_CPI_ACCOUNT_INDICES,
 };
 
 // ================================
@@ -88,9 +89,9 @@ pub enum KernelOperation {
         account_indices: [u8; MAX_CPI_ACCOUNT_INDICES],
         /// Number of actual account indices
         account_indices_len: u8,
-        /// Fixed-size function-specific data
-        data: [u8; MAX_OPERATION_DATA_SIZE],
-        /// Actual data length
+        /// Offset of the function-specific data in the session's operation data
+        data_offset: u16,
+        /// Data length
         data_len: u16,
     },
 
@@ -104,11 +105,29 @@ pub enum KernelOperation {
         account_indices: [u8; MAX_CPI_ACCOUNT_INDICES],
         /// Number of actual account indices
         account_indices_len: u8,
-        /// Fixed-size raw instruction data
-        data: [u8; MAX_OPERATION_DATA_SIZE],
-        /// Actual data length
+        /// Offset of the raw instruction data in the session's operation data
+        data_offset: u16,
+        /// Data length
         data_len: u16,
     },
+
+    // ===== STATE ASSERTIONS =====
+    
+    /// Fail the batch unless `len` bytes at `offset` of an account's data
+    /// equal the first `len` bytes of `expected`
+    AssertAccountData {
+        account_index: u8,
+        offset: u16,
+        expected: [u8; MAX_ASSERTION_DATA_SIZE],
+        len: u8,
+    },
+    
+    /// Fail the batch unless an SPL token account's balance is within `min..=max`
+    AssertTokenBalance {
+        account_index: u8,
+        min: u64,
+        max: u64,
+    },
 }
 
 impl KernelOperation {
@@ -128,12 +147,30 @@ impl KernelOperation {
             }
             
             
-            Self::CallRegisteredFunction { account_indices, account_indices_len, data, data_len, .. } |
-            Self::UnsafeRawCpi { account_indices, account_indices_len, data, data_len, .. } => {
+            Self::CallRegisteredFunction { account_indices, account_indices_len, data_offset, data_len, .. } |
+            Self::UnsafeRawCpi { account_indices, account_indices_len, data_offset, data_len, .. } => {
+                require!(
+                    *account_indices_len as usize <= MAX_CPI_ACCOUNT_INDICES &&
+                    *data_offset as usize + *data_len as usize <= MAX_OPERATION_DATA_ACCOUNT_SIZE,
+                    KernelError::InvalidParameters
+                );
                 let indices_slice = &account_indices[..*account_indices_len as usize];
                 validation::validate_account_indices(indices_slice, 255)?;
-                let data_slice = &data[..*data_len as usize];
-                validation::validate_cpi_data(data_slice)?;
+                require!(
+                    *data_len as usize <= MAX_OPERATION_DATA_SIZE,
+                    KernelError::TransactionTooLarge
+                );
+            }
+            
+            Self::AssertAccountData { len, .. } => {
+                require!(
+                    *len > 0 && *len as usize <= MAX_ASSERTION_DATA_SIZE,
+                    KernelError::InvalidParameters
+                );
+            }
+            
+            Self::AssertTokenBalance { min, max, .. } => {
+                require!(min <= max, KernelError::InvalidParameters);
             }
             
             Self::ReleaseAccount{ .. } => {} // Other operations have no variable parameters to validate
@@ -141,6 +178,47 @@ impl KernelOperation {
         Ok(())
     }
     
+    /// Validate that every account index is below `accounts_len`
+    ///
+    /// # Errors
+    /// Returns `InvalidParameters` for out-of-range indices
+    pub fn validate_indices(&self, accounts_len: usize) -> Result<()> {
+        match self {
+            Self::BorrowAccount { account_index, .. } |
+            Self::ReleaseAccount { account_index } |
+            Self::AssertAccountData { account_index, .. } |
+            Self::AssertTokenBalance { account_index, .. } => {
+                require!(
+                    (*account_index as usize) < accounts_len,
+                    KernelError::InvalidParameters
+                );
+            }
+            
+            Self::UnsafeRawCpi { program_index, account_indices, account_indices_len, .. } => {
+                require!(
+                    (*program_index as usize) < accounts_len,
+                    KernelError::InvalidParameters
+                );
+                validate_indices_in_range(&account_indices[..*account_indices_len as usize], accounts_len)?;
+            }
+            
+            Self::CallRegisteredFunction { account_indices, account_indices_len, .. } => {
+                validate_indices_in_range(&account_indices[..*account_indices_len as usize], accounts_len)?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Bytes of the session's operation data this operation references
+    #[must_use]
+    pub const fn operation_data_len(&self) -> u16 {
+        match self {
+            Self::CallRegisteredFunction { data_len, .. } |
+            Self::UnsafeRawCpi { data_len, .. } => *data_len,
+            _ => 0,
+        }
+    }
+    
     /// Check if operation requires write access to session
     #[must_use]
     pub const fn requires_session_write(&self) -> bool {
@@ -160,6 +238,9 @@ impl KernelOperation {
             
             // CPI operations are expensive
             Self::CallRegisteredFunction { .. } | Self::UnsafeRawCpi { .. } => 50_000,
+            
+            // Assertions only read account data
+            Self::AssertAccountData { .. } | Self::AssertTokenBalance { .. } => 1_500,
         }
     }
 }
@@ -180,9 +261,55 @@ pub struct OperationBatch {
     pub operations: [Option<KernelOperation>; MAX_BATCH_OPERATIONS],
     /// Number of actual operations
     pub operations_len: u8,
+    /// `OperationData::hash` of the session's operation data when the batch
+    /// was built, checked when any operation references it
+    pub operation_data_hash: [u8; 32],
 }
 
 impl OperationBatch {
+    /// Message an approval signer signs to approve this batch
+    ///
+    /// Binds the batch to the session and its usage count, so an approval
+    /// cannot be replayed for a later execution or another session.
+    ///
+    /// # Errors
+    /// Returns an error if the batch cannot be serialized
+    pub fn approval_message(&self, session: &Pubkey, usage_count: u64) -> Result<[u8; 32]> {
+        let batch = self.try_to_vec()?;
+        Ok(solana_program::hash::hashv(&[
+            session.as_ref(),
+            &usage_count.to_le_bytes(),
+            &batch,
+        ])
+        .to_bytes())
+    }
+
+    /// Hash a session commits to before revealing this batch
+    ///
+    /// # Errors
+    /// Returns an error if the batch cannot be serialized
+    pub fn commitment_hash(&self) -> Result<[u8; 32]> {
+        Ok(solana_program::hash::hash(&self.try_to_vec()?).to_bytes())
+    }
+
+    /// Message a session owner signs to approve this batch offline
+    ///
+    /// Binds the batch to the session's nonce, which advances on execution.
+    /// Domain-separated from approval messages, which are signed per usage.
+    ///
+    /// # Errors
+    /// Returns an error if the batch cannot be serialized
+    pub fn offline_message(&self, session: &Pubkey, nonce: u64) -> Result<[u8; 32]> {
+        let batch = self.try_to_vec()?;
+        Ok(solana_program::hash::hashv(&[
+            OFFLINE_BATCH_DOMAIN,
+            session.as_ref(),
+            &nonce.to_le_bytes(),
+            &batch,
+        ])
+        .to_bytes())
+    }
+
     /// Validate the batch
     /// 
     /// # Errors
@@ -205,46 +332,21 @@ impl OperationBatch {
             let op = self.operations[i].as_ref()
                 .ok_or(KernelError::InvalidParameters)?;
             op.validate()?;
-            
-            // Validate account indices are within bounds
-            match op {
-                KernelOperation::BorrowAccount { account_index, .. } |
-                KernelOperation::ReleaseAccount { account_index } => {
-                    require!(
-                        (*account_index as usize) < self.accounts_len as usize,
-                        KernelError::InvalidParameters
-                    );
-                }
-                
-                
-                KernelOperation::UnsafeRawCpi { program_index, account_indices, account_indices_len, .. } => {
-                    require!(
-                        (*program_index as usize) < self.accounts_len as usize,
-                        KernelError::InvalidParameters
-                    );
-                    for &account_index in account_indices.iter().take(*account_indices_len as usize) {
-                        require!(
-                            (account_index as usize) < self.accounts_len as usize,
-                            KernelError::InvalidParameters
-                        );
-                    }
-                }
-                
-                KernelOperation::CallRegisteredFunction { account_indices, account_indices_len, .. } => {
-                    for &account_index in account_indices.iter().take(*account_indices_len as usize) {
-                        require!(
-                            (account_index as usize) < self.accounts_len as usize,
-                            KernelError::InvalidParameters
-                        );
-                    }
-                }
-                
-            }
+            op.validate_indices(self.accounts_len as usize)?;
         }
         
         Ok(())
     }
     
+    /// Check if any operation references the session's operation data
+    #[must_use]
+    pub fn references_operation_data(&self) -> bool {
+        self.operations[..self.operations_len as usize]
+            .iter()
+            .flatten()
+            .any(|op| op.operation_data_len() > 0)
+    }
+    
     /// Estimate total compute units
     #[must_use]
     pub fn compute_estimate(&self) -> u64 {
@@ -258,6 +360,17 @@ impl OperationBatch {
     }
 }
 
+/// Require every index in `indices` to be below `accounts_len`
+pub(crate) fn validate_indices_in_range(indices: &[u8], accounts_len: usize) -> Result<()> {
+    for &account_index in indices {
+        require!(
+            (account_index as usize) < accounts_len,
+            KernelError::InvalidParameters
+        );
+    }
+    Ok(())
+}
+
 // ================================
 // Constants
 // ================================
@@ -266,6 +379,15 @@ pub const ACCESS_MODE_READ: u8 = 1;
 pub const ACCESS_MODE_WRITE: u8 = 2;
 pub const ACCESS_MODE_READ_WRITE: u8 = 3;
 
+/// Most bytes a single `AssertAccountData` operation compares
+pub const MAX_ASSERTION_DATA_SIZE: usize = 32;
+
+/// Offset of the amount field in SPL token accounts (mint and owner precede it)
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Domain prefix of offline batch signing messages
+pub const OFFLINE_BATCH_DOMAIN: &[u8] = b"valence-offline-batch";
+
 // ================================
 // Batch Execution Handler
 // ================================
@@ -323,11 +445,11 @@ pub fn execute_batch(
         timestamp: clock.unix_timestamp,
     };
     
-    // Authorization checks
-    require!(
-        caller == session.owner,
-        KernelError::Unauthorized
-    );
+    // Authorization checks: the owner calls directly, or signed the batch
+    // offline at the session's current nonce
+    if caller != session.owner {
+        consume_offline_signature(ctx.remaining_accounts, &batch, &session_key, &session.owner)?;
+    }
     
     // Ensure session is still active
     require!(
@@ -335,20 +457,203 @@ pub fn execute_batch(
         KernelError::SessionInactive
     );
     
+    let usage_count = session.usage_count;
+    authorize_batch(
+        guard_account,
+        &session_key,
+        || batch.approval_message(&session_key, usage_count),
+        &batch.commitment_hash()?,
+        ctx.remaining_accounts,
+        clock,
+        ctx.accounts.tx_submitter.as_ref(),
+    )?;
+    
+    // CPI data is read from the session's operation data, which must still
+    // hold what the batch was built against
+    let operation_data = if batch.references_operation_data() {
+        Some(find_operation_data(ctx.remaining_accounts, &session_key, &batch.operation_data_hash)?)
+    } else {
+        None
+    };
+    
     // Process each operation
+    let mut linker = Linker {
+        session_key,
+        guard_account,
+        cpi_allowlist,
+        alt,
+        clock,
+        remaining_accounts: ctx.remaining_accounts,
+        accounts: &batch.accounts[..batch.accounts_len as usize],
+        operation_data,
+        cpi_data: Vec::with_capacity(MAX_OPERATION_DATA_SIZE),
+        operation_counts: OperationCounts::default(),
+        lamports_moved: 0,
+    };
     for i in 0..batch.operations_len as usize {
         let operation = batch.operations[i].as_ref()
             .ok_or(KernelError::InvalidParameters)?;
+        linker.execute(session, i as u8, operation)?;
+    }
+    
+    finish_batch(
+        session,
+        &session_key,
+        caller,
+        ctx.accounts.tx_submitter.as_ref(),
+        ctx.remaining_accounts,
+        clock,
+        &linker.operation_counts,
+        linker.lamports_moved,
+        batch.operations_len,
+    )
+}
+
+/// Check a batch against its guard's approval signer and commitment requirement
+///
+/// Guards with an approval signer require its ed25519 signature over the
+/// batch's `approval_message`, verified by the precompile earlier in this
+/// transaction. A commitment to `batch_hash` among the remaining accounts is
+/// consumed, refunding its rent to `tx_submitter`; guards may require one.
+pub(crate) fn authorize_batch(
+    guard_account: &GuardAccount,
+    session_key: &Pubkey,
+    approval_message: impl FnOnce() -> Result<[u8; 32]>,
+    batch_hash: &[u8; 32],
+    remaining_accounts: &[AccountInfo],
+    clock: &Clock,
+    tx_submitter: &AccountInfo,
+) -> Result<()> {
+    if let Some(approval_signer) = guard_account.approval_signer {
+        let message = approval_message()?;
+        let instructions_sysvar = find_instructions_sysvar(remaining_accounts)
+            .ok_or(KernelError::MissingBatchApproval)?;
+        require!(
+            valence_common::introspection::has_ed25519_signature(
+                instructions_sysvar,
+                &approval_signer,
+                &message,
+            )?,
+            KernelError::MissingBatchApproval
+        );
+    }
+    
+    let commitment = find_kernel_account::<BatchCommitment>(remaining_accounts, |commitment| {
+        commitment.session == *session_key && commitment.batch_hash == *batch_hash
+    });
+    match commitment {
+        Some((commitment_info, commitment)) => {
+            require!(
+                commitment.is_ready(clock.slot),
+                KernelError::CommitmentNotReady
+            );
+            close_kernel_account(commitment_info, tx_submitter)?;
+            msg!("Revealed batch committed at slot {}", commitment.committed_slot);
+        }
+        None => require!(
+            !guard_account.require_commitment,
+            KernelError::MissingBatchCommitment
+        ),
+    }
+    Ok(())
+}
+
+/// Record an executed batch on the session, its statistics and its fee vault
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn finish_batch(
+    session: &mut Session,
+    session_key: &Pubkey,
+    caller: Pubkey,
+    tx_submitter: &AccountInfo,
+    remaining_accounts: &[AccountInfo],
+    clock: &Clock,
+    operation_counts: &OperationCounts,
+    lamports_moved: u64,
+    operations: u8,
+) -> Result<()> {
+    // Increment usage counter
+    session.increment_usage(clock)?;
+
+    // Sessions that opted into statistics must pass their stats account
+    if let Some((stats_info, mut stats)) = find_session_stats(session, session_key, remaining_accounts)? {
+        stats.record_batch(operation_counts, lamports_moved, clock.slot);
+        stats.try_serialize(&mut &mut stats_info.try_borrow_mut_data()?[..])?;
+    }
+    
+    // Relayers submitting for the owner must pass the session's fee vault,
+    // whose allowlist admits them and whose funds reimburse them
+    if *tx_submitter.key != session.owner {
+        let (vault_info, vault) = find_kernel_account::<FeeVault>(remaining_accounts, |vault| {
+            vault.session == *session_key
+        })
+        .ok_or(KernelError::SubmitterNotAllowed)?;
+        require!(
+            vault.is_allowed(tx_submitter.key),
+            KernelError::SubmitterNotAllowed
+        );
+        let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+        let amount = vault.reimbursement(vault_info.lamports().saturating_sub(rent_exempt));
+        if amount > 0 {
+            transfer_lamports(vault_info, tx_submitter, amount)?;
+            msg!("Reimbursed submitter {} lamports", amount);
+        }
+    }
+
+    emit!(crate::BatchExecuted {
+        session: *session_key,
+        caller,
+        operations,
+        usage_count: session.usage_count,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+// ================================
+// On-chain Linker
+// ================================
+
+/// Executes operations against a flat account list for one session
+///
+/// CPI instruction data is staged in `cpi_data` and handed back after each
+/// invoke. The program heap is a bump allocator that never frees, so one
+/// buffer per batch bounds heap use regardless of how many CPIs it makes.
+pub(crate) struct Linker<'a, 'info> {
+    pub session_key: Pubkey,
+    pub guard_account: &'a GuardAccount,
+    pub cpi_allowlist: &'a AllowlistAccount,
+    pub alt: &'a SessionAccountLookup,
+    pub clock: &'a Clock,
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+    /// Accounts operations index into
+    pub accounts: &'a [Pubkey],
+    /// The session's operation data, when the batch references it
+    pub operation_data: Option<(&'a AccountInfo<'info>, OperationData)>,
+    /// Instruction data of the next CPI, reused across the batch's CPIs
+    pub cpi_data: Vec<u8>,
+    pub operation_counts: OperationCounts,
+    pub lamports_moved: u64,
+}
+
+impl<'info> Linker<'_, 'info> {
+    /// Account at `index` of the linked account list
+    fn account(&self, index: u8) -> Result<Pubkey> {
+        self.accounts
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| error!(KernelError::InvalidParameters))
+    }
+
+    /// Execute the operation at position `index` of its batch
+    pub fn execute(&mut self, session: &mut Session, index: u8, operation: &KernelOperation) -> Result<()> {
+        self.operation_counts.record(operation);
         match operation {
             KernelOperation::BorrowAccount { account_index, mode } => {
-                require!(
-                    (*account_index as usize) < batch.accounts_len as usize,
-                    KernelError::InvalidParameters
-                );
-                let account = &batch.accounts[*account_index as usize];
+                let account = self.account(*account_index)?;
                 
                 // Check if account is a child account of this session
-                let is_child_account = session.is_child_account(account);
+                let is_child_account = session.is_child_account(&account);
                 
                 // Verify account is either registered in ALT or is a child account
                 if is_child_account {
@@ -356,172 +661,416 @@ pub fn execute_batch(
                     // as long as the session created them
                     msg!("Allowing borrow of child account {}", account);
                 } else {
-                    alt.validate_borrowable(account, *mode)?;
+                    self.alt.validate_borrowable(&account, *mode)?;
                 }
                 
                 // Borrow the account
-                session.borrow_account(*account, *mode, clock)?;
+                session.borrow_account(account, *mode, self.clock)?;
                 
                 msg!("Borrowed account {} with mode {}", account, mode);
             }
             
             KernelOperation::ReleaseAccount { account_index } => {
-                require!(
-                    (*account_index as usize) < batch.accounts_len as usize,
-                    KernelError::InvalidParameters
-                );
-                let account = &batch.accounts[*account_index as usize];
+                let account = self.account(*account_index)?;
                 
-                session.release_account(account)?;
+                session.release_account(&account)?;
                 
                 msg!("Released account {}", account);
             }
             
+            KernelOperation::CallRegisteredFunction { registry_id, account_indices, account_indices_len, data_offset, data_len } => {
+                self.stage_operation_data(*data_offset, *data_len)?;
+                self.call_registered_function(
+                    session,
+                    index,
+                    *registry_id,
+                    &account_indices[..*account_indices_len as usize],
+                )?;
+            }
             
-            KernelOperation::CallRegisteredFunction { registry_id, account_indices, account_indices_len, data, data_len } => {
-                msg!("Calling registered function {} with {} accounts", 
-                    registry_id, account_indices_len);
-                
-                // Look up function in registry
-                let function_info = crate::state::function_registry::FunctionInfo::get_registry_entry(*registry_id)
-                    .ok_or(KernelError::InvalidParameters)?;
-                
-                // Verify function is active
-                require!(
-                    function_info.is_active,
-                    KernelError::InvalidParameters
-                );
-                
-                // Verify program is on allowlist
+            KernelOperation::UnsafeRawCpi { program_index, account_indices, account_indices_len, data_offset, data_len } => {
+                self.stage_operation_data(*data_offset, *data_len)?;
+                self.unsafe_raw_cpi(
+                    session,
+                    *program_index,
+                    &account_indices[..*account_indices_len as usize],
+                )?;
+            }
+            
+            KernelOperation::AssertAccountData { account_index, offset, expected, len } => {
+                let account = find_remaining_account(self.remaining_accounts, &self.account(*account_index)?)?;
+                let start = *offset as usize;
+                let data = account.try_borrow_data()?;
+                let actual = data.get(start..start + *len as usize)
+                    .ok_or(KernelError::AccountDataAssertionFailed)?;
                 require!(
-                    cpi_allowlist.is_allowed(&function_info.program_id),
-                    KernelError::ProgramNotAllowed
+                    actual == &expected[..*len as usize],
+                    KernelError::AccountDataAssertionFailed
                 );
-                
-                // Build account metas for CPI
-                let mut account_infos = Vec::with_capacity(*account_indices_len as usize);
-                let mut account_metas = Vec::with_capacity(*account_indices_len as usize);
-                
-                // Collect accounts for CPI
-                for &idx in account_indices.iter().take(*account_indices_len as usize) {
-                    require!(
-                        (idx as usize) < batch.accounts_len as usize,
-                        KernelError::InvalidParameters
-                    );
-                    let account_key = &batch.accounts[idx as usize];
-                    
-                    // Find this account in remaining_accounts
-                    let mut found = false;
-                    for remaining_account in ctx.remaining_accounts {
-                        if remaining_account.key() == *account_key {
-                            account_infos.push(remaining_account.clone());
-                            account_metas.push(solana_program::instruction::AccountMeta {
-                                pubkey: *account_key,
-                                is_signer: remaining_account.is_signer,
-                                is_writable: remaining_account.is_writable,
-                            });
-                            found = true;
-                            break;
-                        }
-                    }
-                    require!(found, KernelError::MissingRequiredAccount);
-                }
-                
-                // Increment CPI depth
-                session.check_and_increment_cpi_depth()?;
-                
-                // Build and invoke instruction
-                let ix = solana_program::instruction::Instruction {
-                    program_id: function_info.program_id,
-                    accounts: account_metas,
-                    data: data[..*data_len as usize].to_vec(),
-                };
-                
-                solana_program::program::invoke(&ix, &account_infos)?;
-                
-                // Decrement CPI depth
-                session.decrement_cpi_depth();
-                
-                msg!("Successfully called registered function {}", registry_id);
             }
             
-            KernelOperation::UnsafeRawCpi { program_index, account_indices, account_indices_len, data, data_len } => {
+            KernelOperation::AssertTokenBalance { account_index, min, max } => {
+                let account = find_remaining_account(self.remaining_accounts, &self.account(*account_index)?)?;
                 require!(
-                    (*program_index as usize) < batch.accounts_len as usize,
-                    KernelError::InvalidParameters
+                    account.owner == &anchor_spl::token::ID || account.owner == &anchor_spl::token_2022::ID,
+                    KernelError::AccountOwnerMismatch
                 );
-                let program_id = &batch.accounts[*program_index as usize];
-                
-                // CRITICAL SECURITY CHECK
-                let is_allowed = cpi_allowlist.is_allowed(program_id);
-                
-                if !is_allowed {
-                    // Program not on allowlist - check developer opt-in
-                    require!(
-                        guard_account.allow_unregistered_cpi,
-                        KernelError::ProgramNotAllowed
-                    );
-                    
-                    msg!("WARNING: Executing unsafe CPI to {} via developer opt-in", program_id);
-                }
-                
-                // Build account metas for CPI
-                let mut account_infos = Vec::with_capacity(*account_indices_len as usize);
-                let mut account_metas = Vec::with_capacity(*account_indices_len as usize);
-                
-                // Collect remaining accounts that match our indices
-                for &needed_idx in account_indices.iter().take(*account_indices_len as usize) {
-                    require!(
-                        (needed_idx as usize) < batch.accounts_len as usize,
-                        KernelError::InvalidParameters
-                    );
-                    let needed_key = &batch.accounts[needed_idx as usize];
-                    
-                    // Find this account in remaining_accounts
-                    let mut found = false;
-                    for remaining_account in ctx.remaining_accounts {
-                        if remaining_account.key() == *needed_key {
-                            account_infos.push(remaining_account.clone());
-                            account_metas.push(solana_program::instruction::AccountMeta {
-                                pubkey: *needed_key,
-                                is_signer: remaining_account.is_signer,
-                                is_writable: remaining_account.is_writable,
-                            });
-                            found = true;
-                            break;
-                        }
-                    }
-                    require!(found, KernelError::MissingRequiredAccount);
-                }
-                
+                let data = account.try_borrow_data()?;
+                let amount = data
+                    .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(KernelError::InvalidAccountData)?;
                 require!(
-                    account_infos.len() == *account_indices_len as usize,
-                    KernelError::InvalidParameters
+                    (*min..=*max).contains(&amount),
+                    KernelError::TokenBalanceAssertionFailed
                 );
-                
-                // Increment CPI depth
-                session.check_and_increment_cpi_depth()?;
-                
-                // Build and invoke instruction
-                let ix = solana_program::instruction::Instruction {
-                    program_id: *program_id,
-                    accounts: account_metas,
-                    data: data[..*data_len as usize].to_vec(),
-                };
-                
-                solana_program::program::invoke(&ix, &account_infos)?;
-                
-                // Decrement CPI depth
-                session.decrement_cpi_depth();
-                
-                msg!("Executed CPI to {}", program_id);
             }
         }
+        Ok(())
     }
-    
-    // Increment usage counter
-    session.increment_usage(clock)?;
-    
+
+    /// Stage `data` as the instruction data of the next CPI
+    pub fn stage_data(&mut self, data: &[u8]) {
+        self.cpi_data.clear();
+        self.cpi_data.extend_from_slice(data);
+    }
+
+    /// Stage `len` bytes at `offset` of the session's operation data as the
+    /// instruction data of the next CPI
+    fn stage_operation_data(&mut self, offset: u16, len: u16) -> Result<()> {
+        self.cpi_data.clear();
+        if len == 0 {
+            return Ok(());
+        }
+        let (account, operation_data) = self.operation_data
+            .as_ref()
+            .ok_or(KernelError::MissingRequiredAccount)?;
+        let account_data = account.try_borrow_data()?;
+        self.cpi_data.extend_from_slice(operation_data.range(&account_data, offset, len)?);
+        Ok(())
+    }
+
+    /// Call a registered function via the on-chain registry with the staged data
+    pub fn call_registered_function(
+        &mut self,
+        session: &mut Session,
+        index: u8,
+        registry_id: u64,
+        account_indices: &[u8],
+    ) -> Result<()> {
+        msg!("Calling registered function {} with {} accounts", 
+            registry_id, account_indices.len());
+        
+        // Look up function in registry
+        let function_info = crate::state::function_registry::FunctionInfo::get_registry_entry(registry_id)
+            .ok_or(KernelError::InvalidParameters)?;
+        
+        // Verify function is active
+        require!(
+            function_info.is_active,
+            KernelError::InvalidParameters
+        );
+        
+        // Verify program is on allowlist
+        require!(
+            self.cpi_allowlist.is_allowed(&function_info.program_id),
+            KernelError::ProgramNotAllowed
+        );
+        check_program_not_upgraded(self.cpi_allowlist, &function_info.program_id, self.remaining_accounts)?;
+        
+        let (account_infos, account_metas) = self.cpi_accounts(account_indices)?;
+        
+        // Increment CPI depth
+        session.check_and_increment_cpi_depth()?;
+        
+        // Record the call first, so a failure inside the function can
+        // be traced back to it from the transaction logs
+        emit!(crate::FunctionInvoked {
+            session: self.session_key,
+            operation_index: index,
+            registry_id,
+            program_id: function_info.program_id,
+        });
+        
+        self.invoke(session, function_info.program_id, account_metas, &account_infos)?;
+        
+        msg!("Successfully called registered function {}", registry_id);
+        Ok(())
+    }
+
+    /// Raw CPI to the program at `program_index` with the staged data
+    /// (requires `allow_unregistered_cpi` for unlisted programs)
+    pub fn unsafe_raw_cpi(
+        &mut self,
+        session: &mut Session,
+        program_index: u8,
+        account_indices: &[u8],
+    ) -> Result<()> {
+        let program_id = self.account(program_index)?;
+        
+        // CRITICAL SECURITY CHECK
+        let is_allowed = self.cpi_allowlist.is_allowed(&program_id);
+        
+        if !is_allowed {
+            // Program not on allowlist - check developer opt-in
+            require!(
+                self.guard_account.allow_unregistered_cpi,
+                KernelError::ProgramNotAllowed
+            );
+            
+            msg!("WARNING: Executing unsafe CPI to {} via developer opt-in", program_id);
+        }
+        check_program_not_upgraded(self.cpi_allowlist, &program_id, self.remaining_accounts)?;
+        
+        let (account_infos, account_metas) = self.cpi_accounts(account_indices)?;
+        
+        // Increment CPI depth
+        session.check_and_increment_cpi_depth()?;
+        
+        self.invoke(session, program_id, account_metas, &account_infos)?;
+        
+        msg!("Executed CPI to {}", program_id);
+        Ok(())
+    }
+
+    /// Remaining accounts and metas for the linked accounts at `account_indices`
+    #[allow(clippy::type_complexity)]
+    fn cpi_accounts(
+        &self,
+        account_indices: &[u8],
+    ) -> Result<(Vec<AccountInfo<'info>>, Vec<solana_program::instruction::AccountMeta>)> {
+        let mut account_infos = Vec::with_capacity(account_indices.len());
+        let mut account_metas = Vec::with_capacity(account_indices.len());
+        
+        for &idx in account_indices {
+            let account_key = self.account(idx)?;
+            let remaining_account = find_remaining_account(self.remaining_accounts, &account_key)?;
+            account_infos.push(remaining_account.clone());
+            account_metas.push(solana_program::instruction::AccountMeta {
+                pubkey: account_key,
+                is_signer: remaining_account.is_signer,
+                is_writable: remaining_account.is_writable,
+            });
+        }
+        Ok((account_infos, account_metas))
+    }
+
+    /// Invoke `program_id` with the staged data, tracking lamports moved and
+    /// releasing the CPI depth taken by the caller
+    fn invoke(
+        &mut self,
+        session: &mut Session,
+        program_id: Pubkey,
+        accounts: Vec<solana_program::instruction::AccountMeta>,
+        account_infos: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let ix = solana_program::instruction::Instruction {
+            program_id,
+            accounts,
+            data: core::mem::take(&mut self.cpi_data),
+        };
+        
+        let lamports_before: Vec<u64> = account_infos.iter().map(|account| account.lamports()).collect();
+        solana_program::program::invoke(&ix, account_infos)?;
+        self.cpi_data = ix.data;
+        self.lamports_moved = self.lamports_moved.saturating_add(lamports_out(account_infos, &lamports_before));
+        
+        // Decrement CPI depth
+        session.decrement_cpi_depth();
+        Ok(())
+    }
+}
+
+/// The session's operation data among the remaining accounts, checked
+/// against the hash the batch was built with
+fn find_operation_data<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    session_key: &Pubkey,
+    operation_data_hash: &[u8; 32],
+) -> Result<(&'a AccountInfo<'info>, OperationData)> {
+    let (account, operation_data) = remaining_accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID)
+        .find_map(|account| {
+            let data = account.try_borrow_data().ok()?;
+            let operation_data = OperationData::try_deserialize(&mut &data[..]).ok()?;
+            (operation_data.session == *session_key).then_some((account, operation_data))
+        })
+        .ok_or(KernelError::MissingRequiredAccount)?;
+    require!(
+        OperationData::hash(operation_data.written(&account.try_borrow_data()?)?) == *operation_data_hash,
+        KernelError::OperationDataMismatch
+    );
+    Ok((account, operation_data))
+}
+
+/// Instructions sysvar, when passed among the remaining accounts
+fn find_instructions_sysvar<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Option<&'a AccountInfo<'info>> {
+    remaining_accounts
+        .iter()
+        .find(|account| account.key == &solana_program::sysvar::instructions::ID)
+}
+
+/// Account passed among the remaining accounts with address `key`
+fn find_remaining_account<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    key: &Pubkey,
+) -> Result<&'a AccountInfo<'info>> {
+    remaining_accounts
+        .iter()
+        .find(|account| account.key == key)
+        .ok_or_else(|| error!(KernelError::MissingRequiredAccount))
+}
+
+/// Reject CPIs to a pinned program that was redeployed after it was allowlisted
+///
+/// The program's ProgramData account must be among the remaining accounts.
+fn check_program_not_upgraded(
+    cpi_allowlist: &AllowlistAccount,
+    program_id: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    let Some(deployed_slot) = cpi_allowlist.pinned_deployment(program_id) else {
+        return Ok(());
+    };
+    let (program_data, _) = Pubkey::find_program_address(
+        &[program_id.as_ref()],
+        &solana_program::bpf_loader_upgradeable::ID,
+    );
+    let program_data = find_remaining_account(remaining_accounts, &program_data)
+        .map_err(|_| error!(KernelError::InvalidProgramData))?;
+    require!(
+        program_deployment_slot(program_id, program_data)? == deployed_slot,
+        KernelError::ProgramUpgraded
+    );
+    Ok(())
+}
+
+/// Lamports that left `account_infos` since `before` was taken
+fn lamports_out(account_infos: &[AccountInfo], before: &[u64]) -> u64 {
+    account_infos
+        .iter()
+        .zip(before)
+        .fold(0u64, |total, (account, before)| {
+            total.saturating_add(before.saturating_sub(account.lamports()))
+        })
+}
+
+/// First writable kernel account among the remaining accounts of type `T` matching `predicate`
+pub(crate) fn find_kernel_account<'a, 'info, T: AccountDeserialize>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    predicate: impl Fn(&T) -> bool,
+) -> Option<(&'a AccountInfo<'info>, T)> {
+    remaining_accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID && account.is_writable)
+        .find_map(|account| {
+            let data = account.try_borrow_data().ok()?;
+            let value = T::try_deserialize(&mut &data[..]).ok()?;
+            predicate(&value).then_some((account, value))
+        })
+}
+
+/// The statistics account of a session that keeps statistics
+///
+/// Returns `MissingSessionStats` when the session keeps statistics but its
+/// stats PDA is not among the remaining accounts
+pub(crate) fn find_session_stats<'a, 'info>(
+    session: &Session,
+    session_key: &Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<Option<(&'a AccountInfo<'info>, SessionStats)>> {
+    if !session.stats_enabled {
+        return Ok(None);
+    }
+
+    let (stats_info, stats) = find_kernel_account::<SessionStats>(remaining_accounts, |stats| {
+        stats.session == *session_key
+    })
+    .ok_or(KernelError::MissingSessionStats)?;
+    let expected = Pubkey::create_program_address(
+        &[valence_common::pdas::SESSION_STATS_SEED, session_key.as_ref(), &[stats.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| KernelError::InvalidPDA)?;
+    require_keys_eq!(*stats_info.key, expected, KernelError::MissingSessionStats);
+
+    Ok(Some((stats_info, stats)))
+}
+
+/// Move lamports out of a kernel-owned account
+pub(crate) fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(KernelError::InvalidParameters)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(KernelError::InvalidParameters)?;
+    Ok(())
+}
+
+/// Close a kernel-owned account, moving its rent to `destination`
+pub(crate) fn close_kernel_account(account: &AccountInfo, destination: &AccountInfo) -> Result<()> {
+    transfer_lamports(account, destination, account.lamports())?;
+    account.assign(&solana_program::system_program::ID);
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Grow a kernel-owned account to `space` bytes, topping up its rent from `payer`
+pub(crate) fn grow_kernel_account<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(space)?;
+    Ok(())
+}
+
+/// Verify the owner's offline signature over `batch` and advance the session nonce
+fn consume_offline_signature(
+    remaining_accounts: &[AccountInfo],
+    batch: &OperationBatch,
+    session_key: &Pubkey,
+    owner: &Pubkey,
+) -> Result<()> {
+    let (nonce_info, mut nonce) = find_kernel_account::<SessionNonce>(remaining_accounts, |nonce| {
+        nonce.session == *session_key
+    })
+    .ok_or(KernelError::Unauthorized)?;
+    let instructions_sysvar = find_instructions_sysvar(remaining_accounts)
+        .ok_or(KernelError::Unauthorized)?;
+
+    let message = batch.offline_message(session_key, nonce.nonce)?;
+    require!(
+        valence_common::introspection::has_ed25519_signature(instructions_sysvar, owner, &message)?,
+        KernelError::Unauthorized
+    );
+
+    msg!("Executing offline-signed batch at nonce {}", nonce.nonce);
+    nonce.advance()?;
+    nonce.try_serialize(&mut &mut nonce_info.try_borrow_mut_data()?[..])?;
     Ok(())
 }
 
@@ -536,6 +1085,9 @@ pub struct ExecuteBatch<'info> {
     pub session: Box<Account<'info, Session>>,
     
     /// The guard configuration for this session
+    #[account(
+        constraint = guard_account.key() == session.guard_account @ KernelError::InvalidSessionConfig
+    )]
     pub guard_account: Box<Account<'info, GuardAccount>>,
     
     /// The session's account lookup table
@@ -547,10 +1099,12 @@ pub struct ExecuteBatch<'info> {
     /// Global CPI allowlist for security checks
     pub cpi_allowlist: Box<Account<'info, AllowlistAccount>>,
     
-    /// The caller executing the batch
+    /// The caller executing the batch: the session owner, or any submitter
+    /// of a batch the owner signed offline
     pub caller: Signer<'info>,
     
-    /// Transaction fee payer (who submitted the transaction)
+    /// Transaction fee payer (who submitted the transaction), refunded the
+    /// rent of any consumed batch commitment
     #[account(mut)]
     pub tx_submitter: Signer<'info>,
     
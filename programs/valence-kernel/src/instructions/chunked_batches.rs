@@ -0,0 +1,385 @@
+// Chunked batch execution for flows that exceed a single transaction
+//
+// A session owner opens a batch buffer with `begin_batch`, fills it with
+// accounts and operations over any number of `append_operations` calls, and
+// executes it atomically with `finalize_batch`. Buffered CPIs carry their data
+// and account indices in the buffer itself rather than referencing the
+// session's operation data, so a buffer is self-contained once appended.
+//
+// SECURITY INTEGRATION: Operations are validated as they are appended and
+// executed by the same linker as `execute_batch`, so allowlist, borrow, guard
+// approval and commitment rules apply unchanged. Buffers are owner-only; the
+// offline signing flow covers single-transaction batches.
+
+use anchor_lang::prelude::*;
+use valence_common::pdas::BATCH_BUFFER_SEED;
+use crate::{
+    errors::KernelError,
+    instructions::batch_operations::{
+        authorize_batch, finish_batch, validate_indices_in_range, KernelOperation, Linker,
+    },
+    state::{AllowlistAccount, BatchBuffer, GuardAccount, Session, SessionAccountLookup},
+    validation, MAX_BATCH_BUFFER_SIZE, MAX_BUFFERED_ACCOUNTS, MAX_BUFFERED_OPERATIONS,
+    MAX_CPI_ACCOUNT_INDICES, MAX_OPERATION_DATA_SIZE,
+};
+
+// ================================
+// Buffered Operations
+// ================================
+
+/// Operation stored in a batch buffer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum BufferedOperation {
+    /// Any kernel operation, indexing into the buffer's accounts
+    Kernel(KernelOperation),
+
+    /// Call a registered function with data carried in the buffer
+    CallRegisteredFunction {
+        registry_id: u64,
+        account_indices: Vec<u8>,
+        data: Vec<u8>,
+    },
+
+    /// Raw CPI with data carried in the buffer (requires `allow_unregistered_cpi`)
+    UnsafeRawCpi {
+        program_index: u8,
+        account_indices: Vec<u8>,
+        data: Vec<u8>,
+    },
+}
+
+impl BufferedOperation {
+    /// Validate parameters and account indices against `accounts_len` accounts
+    ///
+    /// # Errors
+    /// Returns validation errors for invalid parameters
+    pub fn validate(&self, accounts_len: usize) -> Result<()> {
+        match self {
+            Self::Kernel(operation) => {
+                operation.validate()?;
+                operation.validate_indices(accounts_len)?;
+                // Buffers commit to their contents, which operation data is not part of
+                require!(
+                    operation.operation_data_len() == 0,
+                    KernelError::InvalidParameters
+                );
+            }
+
+            Self::CallRegisteredFunction { account_indices, data, .. } => {
+                validate_cpi(account_indices, data, accounts_len)?;
+            }
+
+            Self::UnsafeRawCpi { program_index, account_indices, data } => {
+                require!(
+                    (*program_index as usize) < accounts_len,
+                    KernelError::InvalidParameters
+                );
+                validate_cpi(account_indices, data, accounts_len)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get estimated compute units for this operation
+    #[must_use]
+    pub const fn compute_estimate(&self) -> u64 {
+        match self {
+            Self::Kernel(operation) => operation.compute_estimate(),
+            Self::CallRegisteredFunction { .. } | Self::UnsafeRawCpi { .. } => 50_000,
+        }
+    }
+}
+
+fn validate_cpi(account_indices: &[u8], data: &[u8], accounts_len: usize) -> Result<()> {
+    require!(
+        account_indices.len() <= MAX_CPI_ACCOUNT_INDICES,
+        KernelError::InvalidParameters
+    );
+    validate_indices_in_range(account_indices, accounts_len)?;
+    validation::validate_cpi_data(data)
+}
+
+// ================================
+// Instruction Handlers
+// ================================
+
+/// Open a buffer of `capacity` bytes for a session's chunked batch
+///
+/// # Errors
+/// Returns errors for unauthorized callers, inactive sessions, or a
+/// capacity beyond `MAX_BATCH_BUFFER_SIZE`
+pub fn begin_batch(ctx: Context<BeginBatch>, buffer_id: u64, capacity: u32) -> Result<()> {
+    let session = &ctx.accounts.session;
+    require!(
+        ctx.accounts.owner.key() == session.owner,
+        KernelError::Unauthorized
+    );
+    require!(session.active, KernelError::SessionInactive);
+    require!(
+        BatchBuffer::space(capacity as usize) <= MAX_BATCH_BUFFER_SIZE,
+        KernelError::InvalidParameters
+    );
+
+    let buffer = &mut ctx.accounts.buffer;
+    buffer.session = session.key();
+    buffer.buffer_id = buffer_id;
+    buffer.accounts = Vec::new();
+    buffer.operations = Vec::new();
+    buffer.bump = ctx.bumps.buffer;
+
+    msg!("Opened batch buffer {} with {} bytes", buffer_id, capacity);
+    Ok(())
+}
+
+/// Append accounts and operations to a batch buffer
+///
+/// Operations may index any account appended so far, including `accounts`.
+///
+/// # Errors
+/// Returns errors for unauthorized callers, invalid operations, or a full buffer
+#[allow(clippy::needless_pass_by_value)]
+pub fn append_operations(
+    ctx: Context<AppendOperations>,
+    accounts: Vec<Pubkey>,
+    operations: Vec<BufferedOperation>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    // The heap never frees, so grow each list once rather than by doubling
+    let buffer = &mut ctx.accounts.buffer;
+    buffer.accounts.reserve_exact(accounts.len());
+    buffer.accounts.extend_from_slice(&accounts);
+    require!(
+        buffer.accounts.len() <= MAX_BUFFERED_ACCOUNTS,
+        KernelError::TooManyAccounts
+    );
+    for operation in &operations {
+        operation.validate(buffer.accounts.len())?;
+    }
+    buffer.operations.reserve_exact(operations.len());
+    buffer.operations.extend(operations);
+    require!(
+        buffer.operations.len() <= MAX_BUFFERED_OPERATIONS,
+        KernelError::InvalidParameters
+    );
+
+    require!(
+        buffer.serialized_len()? <= buffer.to_account_info().data_len(),
+        KernelError::BatchBufferFull
+    );
+
+    msg!(
+        "Batch buffer {} holds {} accounts and {} operations",
+        buffer.buffer_id,
+        buffer.accounts.len(),
+        buffer.operations.len()
+    );
+    Ok(())
+}
+
+/// Execute a batch buffer's operations atomically and close it
+///
+/// Remaining accounts are passed as for `execute_batch`. The buffer's rent
+/// is refunded to the transaction submitter.
+///
+/// # Errors
+/// Returns execution errors for invalid operations or failed validation
+pub fn finalize_batch(ctx: Context<FinalizeBatch>) -> Result<()> {
+    let session_key = ctx.accounts.session.key();
+    let session = &mut ctx.accounts.session;
+    let buffer = &ctx.accounts.buffer;
+    let guard_account = &ctx.accounts.guard_account;
+    let clock = &ctx.accounts.clock;
+    let caller = ctx.accounts.caller.key();
+
+    require!(
+        guard_account.session == session_key,
+        KernelError::InvalidSessionConfig
+    );
+    require!(caller == session.owner, KernelError::Unauthorized);
+    require!(session.active, KernelError::SessionInactive);
+    require!(!buffer.operations.is_empty(), KernelError::InvalidParameters);
+
+    // Hash the contents in place rather than serializing them again
+    let commitment_hash = {
+        let buffer_info = buffer.to_account_info();
+        let data = buffer_info.try_borrow_data()?;
+        BatchBuffer::hash_contents(buffer.contents(&data)?)
+    };
+    let usage_count = session.usage_count;
+    authorize_batch(
+        guard_account,
+        &session_key,
+        || Ok(BatchBuffer::approval_message_for(&session_key, usage_count, &commitment_hash)),
+        &commitment_hash,
+        ctx.remaining_accounts,
+        clock,
+        ctx.accounts.tx_submitter.as_ref(),
+    )?;
+
+    let mut linker = Linker {
+        session_key,
+        guard_account,
+        cpi_allowlist: &ctx.accounts.cpi_allowlist,
+        alt: &ctx.accounts.account_lookup,
+        clock,
+        remaining_accounts: ctx.remaining_accounts,
+        accounts: &buffer.accounts,
+        operation_data: None,
+        cpi_data: Vec::with_capacity(MAX_OPERATION_DATA_SIZE),
+        operation_counts: Default::default(),
+        lamports_moved: 0,
+    };
+    for (i, operation) in buffer.operations.iter().enumerate() {
+        let index = i as u8;
+        match operation {
+            BufferedOperation::Kernel(operation) => linker.execute(session, index, operation)?,
+            BufferedOperation::CallRegisteredFunction { registry_id, account_indices, data } => {
+                linker.operation_counts.record_buffered(operation);
+                linker.stage_data(data);
+                linker.call_registered_function(session, index, *registry_id, account_indices)?;
+            }
+            BufferedOperation::UnsafeRawCpi { program_index, account_indices, data } => {
+                linker.operation_counts.record_buffered(operation);
+                linker.stage_data(data);
+                linker.unsafe_raw_cpi(session, *program_index, account_indices)?;
+            }
+        }
+    }
+
+    msg!("Finalized batch buffer {}", buffer.buffer_id);
+    finish_batch(
+        session,
+        &session_key,
+        caller,
+        ctx.accounts.tx_submitter.as_ref(),
+        ctx.remaining_accounts,
+        clock,
+        &linker.operation_counts,
+        linker.lamports_moved,
+        buffer.operations.len() as u8,
+    )
+}
+
+/// Close a batch buffer without executing it, refunding its rent to the owner
+///
+/// # Errors
+/// Returns an error for unauthorized callers
+pub fn discard_batch(ctx: Context<DiscardBatch>) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+    msg!("Discarded batch buffer {}", ctx.accounts.buffer.buffer_id);
+    Ok(())
+}
+
+// ================================
+// Account Contexts
+// ================================
+
+/// Account context for opening a batch buffer
+#[derive(Accounts)]
+#[instruction(buffer_id: u64, capacity: u32)]
+pub struct BeginBatch<'info> {
+    /// The session the batch will execute in
+    pub session: Account<'info, Session>,
+
+    /// The buffer being created
+    #[account(
+        init,
+        payer = payer,
+        space = BatchBuffer::space(capacity as usize),
+        seeds = [BATCH_BUFFER_SEED, session.key().as_ref(), &buffer_id.to_le_bytes()],
+        bump
+    )]
+    pub buffer: Account<'info, BatchBuffer>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Account context for appending to a batch buffer
+#[derive(Accounts)]
+pub struct AppendOperations<'info> {
+    /// The session the batch will execute in
+    pub session: Account<'info, Session>,
+
+    /// The buffer being filled
+    #[account(mut, has_one = session @ KernelError::InvalidSessionConfig)]
+    pub buffer: Account<'info, BatchBuffer>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+}
+
+/// Account context for executing a batch buffer
+#[derive(Accounts)]
+pub struct FinalizeBatch<'info> {
+    /// The session being used for execution
+    #[account(mut)]
+    pub session: Box<Account<'info, Session>>,
+
+    /// The buffer being executed and closed
+    #[account(
+        mut,
+        has_one = session @ KernelError::InvalidSessionConfig,
+        close = tx_submitter
+    )]
+    pub buffer: Box<Account<'info, BatchBuffer>>,
+
+    /// The guard configuration for this session
+    #[account(
+        constraint = guard_account.key() == session.guard_account @ KernelError::InvalidSessionConfig
+    )]
+    pub guard_account: Box<Account<'info, GuardAccount>>,
+
+    /// The session's account lookup table
+    #[account(
+        constraint = account_lookup.session == session.key() @ KernelError::InvalidSessionConfig
+    )]
+    pub account_lookup: Box<Account<'info, SessionAccountLookup>>,
+
+    /// Global CPI allowlist for security checks
+    pub cpi_allowlist: Box<Account<'info, AllowlistAccount>>,
+
+    /// The session owner
+    pub caller: Signer<'info>,
+
+    /// Transaction fee payer, refunded the rent of the buffer and of any
+    /// consumed batch commitment
+    #[account(mut)]
+    pub tx_submitter: Signer<'info>,
+
+    /// Clock for timestamp operations
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// Account context for discarding a batch buffer
+#[derive(Accounts)]
+pub struct DiscardBatch<'info> {
+    /// The session the batch was built for
+    pub session: Account<'info, Session>,
+
+    /// The buffer being closed
+    #[account(
+        mut,
+        has_one = session @ KernelError::InvalidSessionConfig,
+        close = owner
+    )]
+    pub buffer: Account<'info, BatchBuffer>,
+
+    /// The session owner, refunded the buffer's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
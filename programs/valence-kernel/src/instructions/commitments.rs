@@ -0,0 +1,84 @@
+// Batch commitments for the commit-reveal execution flow
+//
+// A session commits to a batch by its hash and the earliest slot it may run,
+// then reveals the full batch through `execute_batch` once that slot arrives.
+// Until the reveal, observers learn nothing about the batch beyond the fact
+// that one was committed.
+//
+// SEPARATION OF CONCERNS: Committing is its own instruction so the commitment
+// lands in an earlier transaction than the batch; the batch engine only checks
+// and consumes it.
+
+use anchor_lang::prelude::*;
+use valence_common::pdas::BATCH_COMMITMENT_SEED;
+use crate::{
+    errors::KernelError,
+    state::{BatchCommitment, Session},
+};
+
+// ================================
+// Commit Batch Instruction
+// ================================
+
+/// Commit a session to executing the batch with `batch_hash`
+///
+/// `batch_hash` is `OperationBatch::commitment_hash` of the batch that will
+/// later be revealed, which may not execute before `earliest_slot`.
+///
+/// # Errors
+/// Returns errors for unauthorized callers, inactive sessions, or an
+/// existing commitment to the same batch
+#[allow(clippy::needless_pass_by_value)]
+pub fn commit_batch(
+    ctx: Context<CommitBatch>,
+    batch_hash: [u8; 32],
+    earliest_slot: u64,
+) -> Result<()> {
+    let session = &ctx.accounts.session;
+    require!(
+        ctx.accounts.owner.key() == session.owner,
+        KernelError::Unauthorized
+    );
+    require!(session.active, KernelError::SessionInactive);
+
+    let clock = Clock::get()?;
+    let commitment = &mut ctx.accounts.commitment;
+    **commitment = BatchCommitment {
+        session: session.key(),
+        batch_hash,
+        earliest_slot,
+        committed_slot: clock.slot,
+        bump: ctx.bumps.commitment,
+    };
+
+    msg!("Committed batch executable from slot {}", earliest_slot);
+    Ok(())
+}
+
+/// Account context for committing to a batch
+#[derive(Accounts)]
+#[instruction(batch_hash: [u8; 32])]
+pub struct CommitBatch<'info> {
+    /// The session committing to the batch
+    pub session: Account<'info, Session>,
+
+    /// The commitment being created
+    #[account(
+        init,
+        payer = payer,
+        space = BatchCommitment::space(),
+        seeds = [BATCH_COMMITMENT_SEED, session.key().as_ref(), batch_hash.as_ref()],
+        bump
+    )]
+    pub commitment: Account<'info, BatchCommitment>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
@@ -18,6 +18,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, Transfer};
 use crate::{
     errors::KernelError,
+    instructions::batch_operations::find_session_stats,
     state::{Session, GuardAccount},
 };
 
@@ -88,6 +89,13 @@ pub fn spl_transfer(
     // Update session usage
     session.increment_usage(clock)?;
     
+    // Sessions that opted into statistics must pass their stats account
+    let session_key = session.key();
+    if let Some((stats_info, mut stats)) = find_session_stats(session, &session_key, ctx.remaining_accounts)? {
+        stats.record_transfer(amount, clock.slot);
+        stats.try_serialize(&mut &mut stats_info.try_borrow_mut_data()?[..])?;
+    }
+    
     Ok(())
 }
 
@@ -3,6 +3,8 @@
 
 pub mod batch_operations;
 pub mod child_accounts;
+pub mod chunked_batches;
+pub mod commitments;
 pub mod direct_operations;
 pub mod namespaces;
 pub mod sessions;
@@ -10,6 +12,8 @@ pub mod shard;
 
 pub use batch_operations::*;
 pub use child_accounts::*;
+pub use chunked_batches::*;
+pub use commitments::*;
 pub use direct_operations::*;
 pub use namespaces::*;
 pub use sessions::*;
@@ -16,12 +16,14 @@
 // access to accounts outside their registered scope.
 
 use crate::{
-    state::{CreateSessionParams, GuardAccount, Session, SessionAccountLookup, RegisteredAccount, RegisteredProgram},
+    state::{CreateSessionParams, GuardAccount, Session, SessionAccountLookup, SessionNonce, SessionStats, FeeVault, OperationData, RegisteredAccount, RegisteredProgram},
     errors::KernelError,
+    instructions::batch_operations::{close_kernel_account, grow_kernel_account},
     NamespacePath,
-    MAX_CASCADE_DEPTH, MAX_BATCH_INVALIDATION_SIZE, MAX_REGISTERED_ACCOUNTS,
+    MAX_CASCADE_DEPTH, MAX_BATCH_INVALIDATION_SIZE, MAX_OPERATION_DATA_ACCOUNT_SIZE, MAX_REGISTERED_ACCOUNTS,
 };
 use anchor_lang::prelude::*;
+use valence_common::pdas::{FEE_VAULT_SEED, OPERATION_DATA_SEED, SESSION_NONCE_SEED, SESSION_STATS_SEED};
 
 // ================================
 // Guard Account Creation
@@ -67,6 +69,520 @@ pub struct CreateGuardAccount<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Set or clear the key whose ed25519 approval every batch must carry
+///
+/// Replacing or clearing an existing approval signer also requires its
+/// signature, so the session owner cannot drop the requirement alone.
+///
+/// # Errors
+/// Returns errors for unauthorized callers or a mismatched guard account
+#[allow(clippy::needless_pass_by_value)]
+pub fn set_guard_approval_signer(
+    ctx: Context<SetGuardApprovalSigner>,
+    approval_signer: Option<Pubkey>,
+) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let guard_account = &mut ctx.accounts.guard_account;
+
+    require!(
+        ctx.accounts.owner.key() == session.owner,
+        KernelError::Unauthorized
+    );
+    require!(
+        guard_account.session == session.key(),
+        KernelError::InvalidSessionConfig
+    );
+    if let Some(current) = guard_account.approval_signer {
+        require!(
+            ctx.accounts.current_approval_signer.as_ref().map(Signer::key) == Some(current),
+            KernelError::Unauthorized
+        );
+    }
+
+    guard_account.approval_signer = approval_signer;
+    msg!("Guard approval signer set to {:?}", approval_signer);
+    Ok(())
+}
+
+/// Account context for changing a guard's approval signer
+#[derive(Accounts)]
+pub struct SetGuardApprovalSigner<'info> {
+    /// The session the guard belongs to
+    pub session: Account<'info, Session>,
+
+    /// The guard account being updated
+    #[account(
+        mut,
+        constraint = guard_account.key() == session.guard_account @ KernelError::InvalidSessionConfig
+    )]
+    pub guard_account: Account<'info, GuardAccount>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The existing approval signer, required when one is set
+    pub current_approval_signer: Option<Signer<'info>>,
+}
+
+/// Require or stop requiring batches to be committed before execution
+///
+/// # Errors
+/// Returns errors for unauthorized callers or a mismatched guard account
+#[allow(clippy::needless_pass_by_value)]
+pub fn set_guard_require_commitment(
+    ctx: Context<SetGuardRequireCommitment>,
+    require_commitment: bool,
+) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let guard_account = &mut ctx.accounts.guard_account;
+
+    require!(
+        ctx.accounts.owner.key() == session.owner,
+        KernelError::Unauthorized
+    );
+    require!(
+        guard_account.session == session.key(),
+        KernelError::InvalidSessionConfig
+    );
+
+    guard_account.require_commitment = require_commitment;
+    Ok(())
+}
+
+/// Account context for changing a guard's commitment requirement
+#[derive(Accounts)]
+pub struct SetGuardRequireCommitment<'info> {
+    /// The session the guard belongs to
+    pub session: Account<'info, Session>,
+
+    /// The guard account being updated
+    #[account(
+        mut,
+        constraint = guard_account.key() == session.guard_account @ KernelError::InvalidSessionConfig
+    )]
+    pub guard_account: Account<'info, GuardAccount>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+}
+
+/// Migrate a guard account from an earlier layout
+///
+/// The account grows to the current size, with the session owner paying the
+/// additional rent. Policies added since the guard was created start disabled.
+///
+/// # Errors
+/// Returns errors for unauthorized callers, a mismatched guard account or a
+/// guard already at the current version
+#[allow(clippy::needless_pass_by_value)]
+pub fn migrate_guard_account(ctx: Context<MigrateGuardAccount>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    require!(
+        ctx.accounts.owner.key() == session.owner,
+        KernelError::Unauthorized
+    );
+
+    let guard_info = ctx.accounts.guard_account.to_account_info();
+    let guard_account = GuardAccount::from_legacy(&guard_info.try_borrow_data()?)?;
+    require!(
+        guard_account.session == session.key(),
+        KernelError::InvalidSessionConfig
+    );
+
+    grow_kernel_account(
+        &guard_info,
+        GuardAccount::space(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    guard_account.try_serialize(&mut &mut guard_info.try_borrow_mut_data()?[..])?;
+    msg!("Guard migrated to version {}", GuardAccount::VERSION);
+    Ok(())
+}
+
+/// Account context for migrating a guard account
+#[derive(Accounts)]
+pub struct MigrateGuardAccount<'info> {
+    /// The session the guard belongs to
+    pub session: Account<'info, Session>,
+
+    /// CHECK: read in its earlier layout by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        constraint = guard_account.key() == session.guard_account @ KernelError::InvalidSessionConfig
+    )]
+    pub guard_account: UncheckedAccount<'info>,
+
+    /// The session owner, paying for the larger account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ================================
+// Session Nonce
+// ================================
+
+/// Create the nonce account that lets the session owner sign batches offline
+///
+/// # Errors
+/// Returns errors for unauthorized callers or failed initialization
+#[allow(clippy::needless_pass_by_value)]
+pub fn initialize_session_nonce(ctx: Context<InitializeSessionNonce>) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    let session_nonce = &mut ctx.accounts.session_nonce;
+    **session_nonce = SessionNonce::new(ctx.accounts.session.key(), ctx.bumps.session_nonce);
+    Ok(())
+}
+
+/// Account context for session nonce creation
+#[derive(Accounts)]
+pub struct InitializeSessionNonce<'info> {
+    /// The session the nonce belongs to
+    pub session: Account<'info, Session>,
+
+    /// The nonce account being created
+    #[account(
+        init,
+        payer = payer,
+        space = SessionNonce::space(),
+        seeds = [SESSION_NONCE_SEED, session.key().as_ref()],
+        bump
+    )]
+    pub session_nonce: Account<'info, SessionNonce>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+// ================================
+// Session Statistics
+// ================================
+
+/// Create the statistics account that tracks a session's execution totals
+///
+/// # Errors
+/// Returns errors for unauthorized callers or failed initialization
+#[allow(clippy::needless_pass_by_value)]
+pub fn initialize_session_stats(ctx: Context<InitializeSessionStats>) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    let session_stats = &mut ctx.accounts.session_stats;
+    **session_stats = SessionStats::new(ctx.accounts.session.key(), ctx.bumps.session_stats);
+    ctx.accounts.session.stats_enabled = true;
+    Ok(())
+}
+
+/// Account context for session statistics creation
+#[derive(Accounts)]
+pub struct InitializeSessionStats<'info> {
+    /// The session the statistics belong to, which from now on requires
+    /// them on every execution
+    #[account(mut)]
+    pub session: Account<'info, Session>,
+
+    /// The statistics account being created
+    #[account(
+        init,
+        payer = payer,
+        space = SessionStats::space(),
+        seeds = [SESSION_STATS_SEED, session.key().as_ref()],
+        bump
+    )]
+    pub session_stats: Account<'info, SessionStats>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+// ================================
+// Fee Vault
+// ================================
+
+/// Create the fee vault that reimburses relayers submitting for the session
+///
+/// The vault is funded by transferring lamports to its address.
+///
+/// # Errors
+/// Returns errors for unauthorized callers or failed initialization
+#[allow(clippy::needless_pass_by_value)]
+pub fn initialize_fee_vault(
+    ctx: Context<InitializeFeeVault>,
+    submitters: Vec<Pubkey>,
+    reimbursement_per_batch: u64,
+) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    let fee_vault = &mut ctx.accounts.fee_vault;
+    **fee_vault = FeeVault::new(
+        ctx.accounts.session.key(),
+        reimbursement_per_batch,
+        ctx.bumps.fee_vault,
+    );
+    fee_vault.set_submitters(&submitters)
+}
+
+/// Account context for fee vault creation
+#[derive(Accounts)]
+pub struct InitializeFeeVault<'info> {
+    /// The session the vault pays for
+    pub session: Account<'info, Session>,
+
+    /// The fee vault being created
+    #[account(
+        init,
+        payer = payer,
+        space = FeeVault::space(),
+        seeds = [FEE_VAULT_SEED, session.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Replace the fee vault's submitter allowlist and reimbursement
+///
+/// # Errors
+/// Returns errors for unauthorized callers or too many submitters
+#[allow(clippy::needless_pass_by_value)]
+pub fn configure_fee_vault(
+    ctx: Context<ManageFeeVault>,
+    submitters: Vec<Pubkey>,
+    reimbursement_per_batch: u64,
+) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    let fee_vault = &mut ctx.accounts.fee_vault;
+    fee_vault.reimbursement_per_batch = reimbursement_per_batch;
+    fee_vault.set_submitters(&submitters)
+}
+
+/// Withdraw lamports above the rent-exempt minimum from the fee vault
+///
+/// # Errors
+/// Returns errors for unauthorized callers or insufficient vault funds
+#[allow(clippy::needless_pass_by_value)]
+pub fn withdraw_fee_vault(ctx: Context<ManageFeeVault>, amount: u64) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    let vault_info = ctx.accounts.fee_vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+    require!(
+        vault_info.lamports().saturating_sub(rent_exempt) >= amount,
+        KernelError::InvalidParameters
+    );
+    crate::instructions::batch_operations::transfer_lamports(
+        &vault_info,
+        ctx.accounts.owner.as_ref(),
+        amount,
+    )
+}
+
+/// Account context for fee vault management by the session owner
+#[derive(Accounts)]
+pub struct ManageFeeVault<'info> {
+    /// The session the vault pays for
+    pub session: Account<'info, Session>,
+
+    /// The fee vault being managed
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, session.key().as_ref()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// The session owner, who receives withdrawals
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// ================================
+// Operation Data
+// ================================
+
+/// Create the account holding instruction data referenced by the session's batch CPIs
+///
+/// # Errors
+/// Returns errors for unauthorized callers or a capacity beyond
+/// `MAX_OPERATION_DATA_ACCOUNT_SIZE`
+#[allow(clippy::needless_pass_by_value)]
+pub fn initialize_operation_data(
+    ctx: Context<InitializeOperationData>,
+    capacity: u32,
+) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+    require!(
+        OperationData::space(capacity as usize) <= MAX_OPERATION_DATA_ACCOUNT_SIZE,
+        KernelError::InvalidParameters
+    );
+
+    let operation_data = &mut ctx.accounts.operation_data;
+    operation_data.session = ctx.accounts.session.key();
+    operation_data.len = 0;
+    operation_data.bump = ctx.bumps.operation_data;
+    Ok(())
+}
+
+/// Account context for operation data creation
+#[derive(Accounts)]
+#[instruction(capacity: u32)]
+pub struct InitializeOperationData<'info> {
+    /// The session whose batches reference the data
+    pub session: Account<'info, Session>,
+
+    /// The operation data account being created
+    #[account(
+        init,
+        payer = payer,
+        space = OperationData::space(capacity as usize),
+        seeds = [OPERATION_DATA_SEED, session.key().as_ref()],
+        bump
+    )]
+    pub operation_data: Account<'info, OperationData>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+
+    /// The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/// Write `data` at `offset` of the operation data
+///
+/// The written data ends after `data`, so writing at offset 0 replaces the
+/// contents and later writes append or overwrite a tail. Batches built against
+/// the previous contents fail their operation data hash check.
+///
+/// # Errors
+/// Returns errors for unauthorized callers, gaps past the written data, or
+/// data beyond the account's capacity
+#[allow(clippy::needless_pass_by_value)]
+pub fn write_operation_data(
+    ctx: Context<WriteOperationData>,
+    offset: u32,
+    data: Vec<u8>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+
+    let operation_data = &mut ctx.accounts.operation_data;
+    require!(offset <= operation_data.len, KernelError::InvalidParameters);
+    let end = offset as usize + data.len();
+    let account_info = operation_data.to_account_info();
+    require!(
+        end <= OperationData::capacity(account_info.data_len()),
+        KernelError::AccountDataTooSmall
+    );
+
+    let start = OperationData::HEADER_SIZE + offset as usize;
+    account_info.try_borrow_mut_data()?[start..start + data.len()].copy_from_slice(&data);
+    operation_data.len = end as u32;
+
+    msg!("Operation data holds {} bytes", end);
+    Ok(())
+}
+
+/// Account context for writing operation data
+#[derive(Accounts)]
+pub struct WriteOperationData<'info> {
+    /// The session whose batches reference the data
+    pub session: Account<'info, Session>,
+
+    /// The operation data being written
+    #[account(
+        mut,
+        seeds = [OPERATION_DATA_SEED, session.key().as_ref()],
+        bump = operation_data.bump
+    )]
+    pub operation_data: Account<'info, OperationData>,
+
+    /// The session owner
+    pub owner: Signer<'info>,
+}
+
+/// Close the operation data account, refunding its rent to the owner
+///
+/// # Errors
+/// Returns an error for unauthorized callers
+pub fn close_operation_data(ctx: Context<CloseOperationData>) -> Result<()> {
+    require!(
+        ctx.accounts.owner.key() == ctx.accounts.session.owner,
+        KernelError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Account context for closing operation data
+#[derive(Accounts)]
+pub struct CloseOperationData<'info> {
+    /// The session whose batches reference the data
+    pub session: Account<'info, Session>,
+
+    /// The operation data being closed
+    #[account(
+        mut,
+        seeds = [OPERATION_DATA_SEED, session.key().as_ref()],
+        bump = operation_data.bump,
+        close = owner
+    )]
+    pub operation_data: Account<'info, OperationData>,
+
+    /// The session owner, refunded the account's rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
 // ================================
 // Session Creation
 // ================================
@@ -123,13 +639,14 @@ pub fn create_session_account(
     register_accounts_minimal(&mut ctx.accounts.account_lookup, initial_borrowable, initial_programs)?;
     
     // Create session with minimal stack usage
+    let clock = Clock::get()?;
     ctx.accounts.session.set_inner(Session::new(
         params, 
         owner_key, 
         shard, 
         ctx.accounts.guard_account.key(),
         ctx.accounts.account_lookup.key(),
-        &Clock::get()?
+        &clock
     )?);
 
     // Handle parent tracking with boxed session to reduce stack
@@ -144,6 +661,14 @@ pub fn create_session_account(
         }
     }
 
+    emit!(SessionCreated {
+        session: session_key,
+        owner: owner_key,
+        shard,
+        parent_session: ctx.accounts.session.parent_session,
+        timestamp: clock.unix_timestamp,
+    });
+
     Ok(())
 }
 
@@ -511,61 +1036,72 @@ pub struct InvalidateSessionBatch<'info> {
 }
 
 // ================================
-// Cascading Invalidation Events
+// Session Closing
 // ================================
 
-/// Event emitted when a session is invalidated
-#[event]
-pub struct SessionInvalidated {
-    /// The session that was invalidated
-    pub session: Pubkey,
-    /// Number of direct children invalidated
-    pub children_invalidated: u8,
-    /// Maximum cascade depth attempted
-    pub cascade_depth: u8,
-    /// Timestamp of invalidation
-    pub timestamp: i64,
-}
-
-/// Event emitted when cascade invalidation is required but couldn't complete due to limits
-#[event]
-pub struct CascadeInvalidationRequired {
-    /// Parent session that triggered the cascade
-    pub parent_session: Pubkey,
-    /// Child sessions that need invalidation
-    pub child_sessions: Vec<Pubkey>,
-    /// Remaining depth for cascade
-    pub depth_remaining: u8,
-    /// Reason cascade was deferred
-    pub reason: CascadeDeferReason,
-}
-
-/// Event emitted when a batch of sessions is invalidated
-#[event]
-pub struct BatchInvalidated {
-    /// Parent session that triggered the batch
-    pub parent: Pubkey,
-    /// Number of sessions successfully invalidated
-    pub invalidated: u32,
-    /// Total sessions attempted
-    pub total: u32,
-    /// Compute units consumed
-    pub compute_units_used: u64,
-}
-
-/// Reasons why cascade invalidation might be deferred
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub enum CascadeDeferReason {
-    /// Not enough compute units remaining
-    ComputeUnitsExhausted,
-    /// Maximum cascade depth reached
-    MaxDepthReached,
-    /// Child session account not found in remaining_accounts
-    ChildAccountNotFound,
-    /// Transaction size limits
-    TransactionTooLarge,
+/// Close an invalidated session, its account lookup table and its guard,
+/// refunding their rent to the owner
+///
+/// The session must hold no borrowed or child accounts, whose lamports would
+/// otherwise be stranded.
+///
+/// # Errors
+/// Returns errors for unauthorized callers or sessions still in use
+pub fn close_session(ctx: Context<CloseSession>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    require!(
+        !session.active && session.borrowed_bitmap == 0 && session.child_count == 0,
+        KernelError::SessionNotClosable
+    );
+
+    let owner = ctx.accounts.owner.to_account_info();
+    close_kernel_account(&ctx.accounts.guard_account, &owner)?;
+    msg!("Session {} closed by owner", session.key());
+    Ok(())
+}
+
+/// Account context for closing a session
+#[derive(Accounts)]
+pub struct CloseSession<'info> {
+    /// The session being closed
+    #[account(
+        mut,
+        close = owner,
+        constraint = session.owner == owner.key() @ KernelError::Unauthorized
+    )]
+    pub session: Account<'info, Session>,
+
+    /// The session's account lookup table
+    #[account(
+        mut,
+        close = owner,
+        address = session.account_lookup @ KernelError::InvalidSessionConfig
+    )]
+    pub account_lookup: Account<'info, SessionAccountLookup>,
+
+    /// CHECK: closed whatever its layout version
+    #[account(
+        mut,
+        owner = crate::ID,
+        address = session.guard_account @ KernelError::InvalidSessionConfig
+    )]
+    pub guard_account: UncheckedAccount<'info>,
+
+    /// The session owner, refunded the accounts' rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
 }
 
+// ================================
+// Session Events
+// ================================
+
+// Session events are defined in valence-common so clients decode the same schema
+pub use valence_common::events::{
+    BatchExecuted, BatchInvalidated, CascadeDeferReason, CascadeInvalidationRequired,
+    FunctionInvoked, SessionCreated, SessionInvalidated,
+};
+
 // ================================
 // Session Queries (View Functions)
 // ================================
@@ -14,8 +14,13 @@
 // including CPI allowlists and program-wide configuration that applies to all
 // sessions and operations within the kernel deployment.
 
-use crate::state::AllowlistAccount;
+use crate::{
+    errors::KernelError,
+    instructions::batch_operations::grow_kernel_account,
+    state::{program_deployment_slot, AllowlistAccount},
+};
 use anchor_lang::prelude::*;
+use valence_common::pdas::CPI_ALLOWLIST_SEED;
 
 // ================================
 // Shard Initialization
@@ -73,15 +78,26 @@ pub fn initialize_allowlist(ctx: Context<InitializeAllowlist>) -> Result<()> {
 
 /// Add a program to the allowlist
 /// 
+/// For upgradeable programs, pass the program's ProgramData account as the
+/// first remaining account to record its deployment slot. With
+/// `reject_upgrades`, CPIs to the program fail once it is redeployed.
+/// 
 /// # Errors
-/// Returns errors for authorization failures or allowlist full
+/// Returns errors for authorization failures, allowlist full, or an invalid
+/// ProgramData account
 #[allow(clippy::needless_pass_by_value)] // Anchor requires owned Context
 pub fn add_program_to_cpi_allowlist(
     ctx: Context<ManageAllowlist>, 
-    program_id: Pubkey
+    program_id: Pubkey,
+    reject_upgrades: bool,
 ) -> Result<()> {
+    let deployed_slot = match ctx.remaining_accounts.first() {
+        Some(program_data) => program_deployment_slot(&program_id, program_data)?,
+        None => 0,
+    };
+    
     let allowlist = &mut ctx.accounts.cpi_allowlist;
-    allowlist.add_program(program_id)?;
+    allowlist.add_program_with_deployment(program_id, deployed_slot, reject_upgrades)?;
     Ok(())
 }
 
@@ -99,6 +115,34 @@ pub fn remove_program_from_cpi_allowlist(
     Ok(())
 }
 
+/// Migrate a version 1 allowlist to the current layout
+/// 
+/// The account grows to hold deployment pinning, with the authority paying
+/// the additional rent. Programs already allowlisted stay unpinned.
+/// 
+/// # Errors
+/// Returns errors for authorization failures or an allowlist that is not at version 1
+#[allow(clippy::needless_pass_by_value)] // Anchor requires owned Context
+pub fn migrate_allowlist(ctx: Context<MigrateAllowlist>) -> Result<()> {
+    let allowlist_info = ctx.accounts.cpi_allowlist.to_account_info();
+    let allowlist = AllowlistAccount::from_v1(&allowlist_info.try_borrow_data()?)?;
+    require_keys_eq!(
+        allowlist.authority,
+        ctx.accounts.authority.key(),
+        KernelError::Unauthorized
+    );
+    
+    grow_kernel_account(
+        &allowlist_info,
+        AllowlistAccount::space(),
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    allowlist.try_serialize(&mut &mut allowlist_info.try_borrow_mut_data()?[..])?;
+    msg!("Allowlist migrated to version {}", AllowlistAccount::VERSION);
+    Ok(())
+}
+
 /// Initialize allowlist account context
 #[derive(Accounts)]
 pub struct InitializeAllowlist<'info> {
@@ -106,7 +150,7 @@ pub struct InitializeAllowlist<'info> {
         init,
         payer = authority,
         space = AllowlistAccount::space(),
-        seeds = [b"cpi_allowlist"],
+        seeds = [CPI_ALLOWLIST_SEED],
         bump
     )]
     pub cpi_allowlist: Account<'info, AllowlistAccount>,
@@ -122,11 +166,30 @@ pub struct InitializeAllowlist<'info> {
 pub struct ManageAllowlist<'info> {
     #[account(
         mut,
-        seeds = [b"cpi_allowlist"],
+        seeds = [CPI_ALLOWLIST_SEED],
         bump,
         has_one = authority
     )]
     pub cpi_allowlist: Account<'info, AllowlistAccount>,
     
     pub authority: Signer<'info>,
-}
\ No newline at end of file
+}
+
+/// Migrate allowlist account context
+#[derive(Accounts)]
+pub struct MigrateAllowlist<'info> {
+    /// CHECK: read in its version 1 layout by the handler
+    #[account(
+        mut,
+        seeds = [CPI_ALLOWLIST_SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub cpi_allowlist: UncheckedAccount<'info>,
+    
+    /// Allowlist authority, paying for the larger account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
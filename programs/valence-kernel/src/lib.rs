@@ -28,7 +28,7 @@ pub use state::*;
 // Re-export operation types from batch_operations
 pub use instructions::batch_operations::{
     KernelOperation, OperationBatch, TimestampCheck,
-    ACCESS_MODE_READ, ACCESS_MODE_WRITE, ACCESS_MODE_READ_WRITE,
+    ACCESS_MODE_READ, ACCESS_MODE_WRITE, ACCESS_MODE_READ_WRITE, MAX_ASSERTION_DATA_SIZE,
 };
 
 // Re-export all instruction items at crate root for Anchor's macro
@@ -40,7 +40,7 @@ pub use instructions::*;
 // ================================
 
 /// PDA seed for session accounts
-pub const SESSION_ACCOUNT_SEED: &[u8] = b"session";
+pub const SESSION_ACCOUNT_SEED: &[u8] = valence_common::pdas::SESSION_SEED;
 
 // ================================
 // Capacity Constants
@@ -54,7 +54,8 @@ pub const SESSION_ACCOUNT_SEED: &[u8] = b"session";
 //
 // If you need larger capacities, consider:
 // 1. Deploying multiple sessions for parallel operations
-// 2. Using multiple transactions for large batches
+// 2. Accumulating large batches in a batch buffer (begin_batch / append_operations /
+//    finalize_batch), which executes them in one instruction
 // 3. Implementing pagination patterns in your application
 
 /// Maximum number of accounts that can be registered per category in SessionAccountLookup
@@ -67,12 +68,27 @@ pub const MAX_BATCH_ACCOUNTS: usize = 12;
 /// Maximum number of operations that can be executed in a single batch
 pub const MAX_BATCH_OPERATIONS: usize = 5;
 
-/// Maximum size of data payload for function calls and raw CPI operations
-pub const MAX_OPERATION_DATA_SIZE: usize = 64;
+/// Maximum bytes of operation data a single function call or raw CPI references
+pub const MAX_OPERATION_DATA_SIZE: usize = validation::MAX_CPI_DATA_SIZE;
 
 /// Maximum number of account indices that can be passed to a CPI call
 pub const MAX_CPI_ACCOUNT_INDICES: usize = 12;
 
+/// Maximum size of a batch buffer account
+///
+/// A full buffer takes under 12 KiB of the 32 KiB program heap once
+/// deserialized, leaving the rest for the CPIs it executes.
+pub const MAX_BATCH_BUFFER_SIZE: usize = 8_192;
+
+/// Maximum number of accounts a batch buffer's operations can reference
+pub const MAX_BUFFERED_ACCOUNTS: usize = 64;
+
+/// Maximum number of operations a batch buffer can execute on finalize
+pub const MAX_BUFFERED_OPERATIONS: usize = 64;
+
+/// Maximum size of an operation data account, the most one instruction can allocate
+pub const MAX_OPERATION_DATA_ACCOUNT_SIZE: usize = 10_240;
+
 /// Maximum direct children per session - aligned with EVM
 pub const MAX_DIRECT_CHILDREN: u8 = 8;
 
@@ -119,6 +135,82 @@ pub mod valence_kernel {
         instructions::create_guard_account(ctx, session, allow_unregistered_cpi)
     }
     
+    /// Sets the key whose ed25519 approval is required for batch execution
+    pub fn set_guard_approval_signer(
+        ctx: Context<SetGuardApprovalSigner>,
+        approval_signer: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_guard_approval_signer(ctx, approval_signer)
+    }
+    
+    /// Requires batches to be committed before they execute
+    pub fn set_guard_require_commitment(
+        ctx: Context<SetGuardRequireCommitment>,
+        require_commitment: bool,
+    ) -> Result<()> {
+        instructions::set_guard_require_commitment(ctx, require_commitment)
+    }
+    
+    /// Grows a guard account from an earlier layout to the current one
+    pub fn migrate_guard_account(ctx: Context<MigrateGuardAccount>) -> Result<()> {
+        instructions::migrate_guard_account(ctx)
+    }
+    
+    /// Creates the nonce account used for offline-signed batches
+    pub fn initialize_session_nonce(ctx: Context<InitializeSessionNonce>) -> Result<()> {
+        instructions::initialize_session_nonce(ctx)
+    }
+    
+    /// Creates the account totalling a session's batches, operations, lamports and tokens moved, and last execution slot
+    pub fn initialize_session_stats(ctx: Context<InitializeSessionStats>) -> Result<()> {
+        instructions::initialize_session_stats(ctx)
+    }
+    
+    /// Creates the fee vault that reimburses relayers for a session
+    pub fn initialize_fee_vault(
+        ctx: Context<InitializeFeeVault>,
+        submitters: Vec<Pubkey>,
+        reimbursement_per_batch: u64,
+    ) -> Result<()> {
+        instructions::initialize_fee_vault(ctx, submitters, reimbursement_per_batch)
+    }
+    
+    /// Updates the relayer allowlist and reimbursement of a fee vault
+    pub fn configure_fee_vault(
+        ctx: Context<ManageFeeVault>,
+        submitters: Vec<Pubkey>,
+        reimbursement_per_batch: u64,
+    ) -> Result<()> {
+        instructions::configure_fee_vault(ctx, submitters, reimbursement_per_batch)
+    }
+    
+    /// Returns unused fee vault funds to the session owner
+    pub fn withdraw_fee_vault(ctx: Context<ManageFeeVault>, amount: u64) -> Result<()> {
+        instructions::withdraw_fee_vault(ctx, amount)
+    }
+    
+    /// Creates the account holding instruction data for a session's batch CPIs
+    pub fn initialize_operation_data(
+        ctx: Context<InitializeOperationData>,
+        capacity: u32,
+    ) -> Result<()> {
+        instructions::initialize_operation_data(ctx, capacity)
+    }
+    
+    /// Writes instruction data for batch CPIs at `offset` of the operation data
+    pub fn write_operation_data(
+        ctx: Context<WriteOperationData>,
+        offset: u32,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::write_operation_data(ctx, offset, data)
+    }
+    
+    /// Closes a session's operation data account
+    pub fn close_operation_data(ctx: Context<CloseOperationData>) -> Result<()> {
+        instructions::close_operation_data(ctx)
+    }
+    
     /// Establishes authorized execution context with initial registrations
     pub fn create_session_account(
         ctx: Context<CreateSession>,
@@ -174,6 +266,11 @@ pub mod valence_kernel {
         instructions::invalidate_session(ctx)
     }
     
+    /// Close an invalidated session with its lookup table and guard, refunding their rent
+    pub fn close_session(ctx: Context<CloseSession>) -> Result<()> {
+        instructions::close_session(ctx)
+    }
+    
     /// Invalidate multiple sessions in a single batch operation
     pub fn invalidate_session_batch(
         ctx: Context<InvalidateSessionBatch>,
@@ -190,6 +287,43 @@ pub mod valence_kernel {
         instructions::execute_batch(ctx, batch)
     }
     
+    /// Commit to a batch hash ahead of revealing it through `execute_batch`
+    pub fn commit_batch(
+        ctx: Context<CommitBatch>,
+        batch_hash: [u8; 32],
+        earliest_slot: u64,
+    ) -> Result<()> {
+        instructions::commit_batch(ctx, batch_hash, earliest_slot)
+    }
+    
+    /// Open a buffer accumulating a batch too large for one transaction
+    pub fn begin_batch(
+        ctx: Context<BeginBatch>,
+        buffer_id: u64,
+        capacity: u32,
+    ) -> Result<()> {
+        instructions::begin_batch(ctx, buffer_id, capacity)
+    }
+    
+    /// Append accounts and operations to a batch buffer
+    pub fn append_operations(
+        ctx: Context<AppendOperations>,
+        accounts: Vec<Pubkey>,
+        operations: Vec<BufferedOperation>,
+    ) -> Result<()> {
+        instructions::append_operations(ctx, accounts, operations)
+    }
+    
+    /// Execute a batch buffer atomically and close it
+    pub fn finalize_batch(ctx: Context<FinalizeBatch>) -> Result<()> {
+        instructions::finalize_batch(ctx)
+    }
+    
+    /// Close a batch buffer without executing it
+    pub fn discard_batch(ctx: Context<DiscardBatch>) -> Result<()> {
+        instructions::discard_batch(ctx)
+    }
+    
     /// Create a child account within the session's namespace
     pub fn create_child_account(
         ctx: Context<CreateChildAccount>,
@@ -214,9 +348,10 @@ pub mod valence_kernel {
     /// Grants permission for sessions to invoke specified program
     pub fn add_program_to_cpi_allowlist(
         ctx: Context<ManageAllowlist>,
-        program_id: Pubkey
+        program_id: Pubkey,
+        reject_upgrades: bool,
     ) -> Result<()> {
-        instructions::add_program_to_cpi_allowlist(ctx, program_id)
+        instructions::add_program_to_cpi_allowlist(ctx, program_id, reject_upgrades)
     }
     
     /// Revokes permission to invoke specified program
@@ -227,6 +362,11 @@ pub mod valence_kernel {
         instructions::remove_program_from_cpi_allowlist(ctx, program_id)
     }
     
+    /// Grows a version 1 allowlist to the current layout
+    pub fn migrate_allowlist(ctx: Context<MigrateAllowlist>) -> Result<()> {
+        instructions::migrate_allowlist(ctx)
+    }
+    
     
     // ===== DEDICATED HIGH-PERFORMANCE INSTRUCTIONS =====
     
@@ -75,12 +75,18 @@ impl NamespacePath {
         })
     }
     
+    /// Path bytes, or `None` if a deserialized length exceeds the buffer
+    fn bytes(&self) -> Option<&[u8]> {
+        self.path.get(..self.len as usize)
+    }
+    
     /// Get the path as a string slice
     /// 
     /// # Errors
-    /// Returns errors for invalid UTF-8 sequences
+    /// Returns errors for invalid UTF-8 sequences or an out-of-range length
     pub fn as_str(&self) -> Result<&str> {
-        std::str::from_utf8(&self.path[..self.len as usize])
+        let bytes = self.bytes().ok_or(KernelError::NamespaceInvalidPath)?;
+        std::str::from_utf8(bytes)
             .map_err(|_| KernelError::NamespaceInvalidPath.into())
     }
     
@@ -107,8 +113,12 @@ impl NamespacePath {
         }
         
         // Check if other starts with self + '/'
-        other.path[..self.len as usize] == self.path[..self.len as usize] &&
-        other.path[self.len as usize] == b'/'
+        match (self.bytes(), other.bytes()) {
+            (Some(parent), Some(child)) => {
+                child.starts_with(parent) && child.get(parent.len()) == Some(&b'/')
+            }
+            _ => false,
+        }
     }
     
     /// Get the depth (number of segments)
@@ -118,7 +128,9 @@ impl NamespacePath {
             return 0;
         }
         
-        let path_slice = &self.path[..self.len as usize];
+        let Some(path_slice) = self.bytes() else {
+            return 0;
+        };
         #[allow(clippy::naive_bytecount)] // Used in const context, avoids external deps
         u8::try_from(path_slice.iter().filter(|&&b| b == b'/').count()).unwrap_or(u8::MAX).saturating_add(1)
     }
@@ -168,7 +180,7 @@ pub struct Namespace {
 }
 
 impl Namespace {
-    pub const SEED_PREFIX: &'static [u8] = b"namespace";
+    pub const SEED_PREFIX: &'static [u8] = valence_common::pdas::NAMESPACE_SEED;
     
     /// Calculate exact space needed for this account
     #[must_use]
@@ -259,11 +271,7 @@ impl Namespace {
     /// Derive PDA for a namespace path
     #[must_use]
     pub fn derive_pda(path: &NamespacePath, program_id: &Pubkey) -> (Pubkey, u8) {
-        let path_bytes = &path.path[..path.len as usize];
-        Pubkey::find_program_address(
-            &[Self::SEED_PREFIX, path_bytes],
-            program_id,
-        )
+        valence_common::pdas::namespace(&path.path[..path.len as usize], program_id)
     }
 }
 
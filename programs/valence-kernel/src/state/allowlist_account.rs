@@ -33,13 +33,36 @@ pub struct AllowlistAccount {
     pub program_count: u8,
     /// Version for future upgrades
     pub version: u8,
+    /// Slot each program was last deployed at when allowlisted (0 if unknown)
+    pub deployed_slots: [u64; 32],
+    /// Whether CPIs to each program are rejected once it is upgraded
+    pub reject_upgrades: [bool; 32],
+}
+
+/// Allowlist layout before deployment pinning
+#[derive(AnchorDeserialize)]
+struct AllowlistAccountV1 {
+    authority: Pubkey,
+    allowed_programs: [Pubkey; 32],
+    program_count: u8,
+    version: u8,
 }
 
 impl AllowlistAccount {
     pub const MAX_PROGRAMS: usize = 32;
     
+    /// Current layout version; version 2 added deployment pinning
+    pub const VERSION: u8 = 2;
+    
     /// Calculate space needed for allowlist
     pub fn space() -> usize {
+        Self::v1_space() +
+        8 * Self::MAX_PROGRAMS + // deployed_slots
+        Self::MAX_PROGRAMS // reject_upgrades
+    }
+    
+    /// Space of a version 1 allowlist
+    pub fn v1_space() -> usize {
         8 + // discriminator
         32 + // authority
         32 * Self::MAX_PROGRAMS + // allowed_programs fixed array
@@ -47,6 +70,28 @@ impl AllowlistAccount {
         1 // version
     }
     
+    /// Rebuild a version 1 allowlist in the current layout, with no program pinned
+    ///
+    /// # Errors
+    /// Returns `InvalidVersion` if `data` is not a version 1 allowlist
+    pub fn from_v1(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == Self::v1_space() && data.starts_with(Self::DISCRIMINATOR),
+            KernelError::InvalidVersion
+        );
+        let v1 = AllowlistAccountV1::deserialize(&mut &data[8..])?;
+        require!(v1.version == 1, KernelError::InvalidVersion);
+        
+        Ok(Self {
+            authority: v1.authority,
+            allowed_programs: v1.allowed_programs,
+            program_count: v1.program_count,
+            version: Self::VERSION,
+            deployed_slots: [0; 32],
+            reject_upgrades: [false; 32],
+        })
+    }
+    
     /// Initialize new allowlist
     #[must_use]
     pub fn new(authority: Pubkey) -> Self {
@@ -54,7 +99,9 @@ impl AllowlistAccount {
             authority,
             allowed_programs: [Pubkey::default(); 32],
             program_count: 0,
-            version: 1,
+            version: Self::VERSION,
+            deployed_slots: [0; 32],
+            reject_upgrades: [false; 32],
         }
     }
     
@@ -72,6 +119,23 @@ impl AllowlistAccount {
     
     /// Add a program to the allowlist
     pub fn add_program(&mut self, program_id: Pubkey) -> Result<()> {
+        self.add_program_with_deployment(program_id, 0, false)
+    }
+    
+    /// Add a program to the allowlist, recording the slot it was deployed at
+    ///
+    /// With `reject_upgrades`, CPIs to the program are rejected once it is
+    /// redeployed after `deployed_slot`.
+    pub fn add_program_with_deployment(
+        &mut self,
+        program_id: Pubkey,
+        deployed_slot: u64,
+        reject_upgrades: bool,
+    ) -> Result<()> {
+        require!(
+            !reject_upgrades || deployed_slot > 0,
+            KernelError::InvalidProgramData
+        );
         require!(
             (self.program_count as usize) < Self::MAX_PROGRAMS,
             KernelError::AllowlistFull
@@ -82,11 +146,22 @@ impl AllowlistAccount {
             KernelError::ProgramAlreadyAllowed
         );
         
-        self.allowed_programs[self.program_count as usize] = program_id;
+        let index = self.program_count as usize;
+        self.allowed_programs[index] = program_id;
+        self.deployed_slots[index] = deployed_slot;
+        self.reject_upgrades[index] = reject_upgrades;
         self.program_count += 1;
         Ok(())
     }
     
+    /// Deployment slot a program is pinned to, if upgrades of it are rejected
+    pub fn pinned_deployment(&self, program_id: &Pubkey) -> Option<u64> {
+        let index = self.allowed_programs[..self.program_count as usize]
+            .iter()
+            .position(|p| p == program_id)?;
+        self.reject_upgrades[index].then_some(self.deployed_slots[index])
+    }
+    
     /// Remove a program from the allowlist
     pub fn remove_program(&mut self, program_id: &Pubkey) -> Result<()> {
         let active_slice = &mut self.allowed_programs[..self.program_count as usize];
@@ -96,12 +171,17 @@ impl AllowlistAccount {
             .ok_or(KernelError::ProgramNotAllowed)?;
         
         // Move the last element to the removed position
-        if index < (self.program_count as usize - 1) {
-            self.allowed_programs[index] = self.allowed_programs[self.program_count as usize - 1];
+        let last = self.program_count as usize - 1;
+        if index < last {
+            self.allowed_programs[index] = self.allowed_programs[last];
+            self.deployed_slots[index] = self.deployed_slots[last];
+            self.reject_upgrades[index] = self.reject_upgrades[last];
         }
         
         // Clear the last position and decrement count
-        self.allowed_programs[self.program_count as usize - 1] = Pubkey::default();
+        self.allowed_programs[last] = Pubkey::default();
+        self.deployed_slots[last] = 0;
+        self.reject_upgrades[last] = false;
         self.program_count -= 1;
         Ok(())
     }
@@ -124,4 +204,27 @@ fn is_solana_spl_program(program_id: &Pubkey) -> bool {
     Some(program_id) == spl_token.as_ref() ||
     Some(program_id) == spl_token_2022.as_ref() ||
     Some(program_id) == spl_associated_token.as_ref()
-}
\ No newline at end of file
+}
+
+/// Slot an upgradeable program was last deployed at, read from its ProgramData account
+///
+/// # Errors
+/// Returns `InvalidProgramData` if `program_data` is not the ProgramData account of `program_id`
+pub fn program_deployment_slot(program_id: &Pubkey, program_data: &AccountInfo) -> Result<u64> {
+    let loader = solana_program::bpf_loader_upgradeable::ID;
+    let (expected, _) = Pubkey::find_program_address(&[program_id.as_ref()], &loader);
+    require!(
+        program_data.key == &expected && program_data.owner == &loader,
+        KernelError::InvalidProgramData
+    );
+    
+    // UpgradeableLoaderState::ProgramData { slot, .. }: u32 tag 3, then the slot
+    let data = program_data.try_borrow_data()?;
+    require!(
+        data.len() >= 12 && data[..4] == 3u32.to_le_bytes(),
+        KernelError::InvalidProgramData
+    );
+    let mut slot = [0u8; 8];
+    slot.copy_from_slice(&data[4..12]);
+    Ok(u64::from_le_bytes(slot))
+}
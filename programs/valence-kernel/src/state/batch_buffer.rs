@@ -0,0 +1,143 @@
+// Scratch buffers for batches too large for a single transaction
+//
+// `execute_batch` carries its batch as fixed-size instruction data, bounded by
+// MAX_BATCH_OPERATIONS and MAX_BATCH_ACCOUNTS so it fits the transaction and
+// the program stack. Complex flows instead accumulate their accounts and
+// operations in a buffer PDA across several transactions, and the kernel
+// executes the whole buffer at once when it is finalized.
+//
+// KERNEL INTEGRATION: Finalizing runs the buffered operations through the same
+// linker, guard approval and commitment checks as `execute_batch`, then closes
+// the buffer. Approvals and commitments are taken over the buffer's contents,
+// domain-separated from those of single-transaction batches.
+//
+// HEAP USAGE: Programs get a 32 KiB heap that is never freed. A deserialized
+// buffer already occupies roughly its own size there, so the kernel measures
+// and hashes buffers without serializing them again.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use crate::{errors::KernelError, instructions::BufferedOperation};
+
+/// Domain prefix of buffered batch hashes and approval messages
+pub const BUFFERED_BATCH_DOMAIN: &[u8] = b"valence-buffered-batch";
+
+/// Accounts and operations accumulated for a chunked batch
+#[account]
+#[derive(Debug)]
+pub struct BatchBuffer {
+    /// The session the batch executes in
+    pub session: Pubkey,
+
+    /// Identifier distinguishing concurrent buffers of a session
+    pub buffer_id: u64,
+
+    /// Accounts the operations index into
+    pub accounts: Vec<Pubkey>,
+
+    /// Operations in execution order
+    pub operations: Vec<BufferedOperation>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl BatchBuffer {
+    /// Offset of the serialized accounts and operations in the account data
+    pub const CONTENTS_OFFSET: usize = 8 + 32 + 8;
+
+    /// Size of an empty buffer
+    pub const fn empty_space() -> usize {
+        8 +  // discriminator
+        32 + // session
+        8 +  // buffer_id
+        4 +  // accounts length
+        4 +  // operations length
+        1    // bump
+    }
+
+    /// Account size for `capacity` bytes of accounts and operations
+    pub const fn space(capacity: usize) -> usize {
+        Self::empty_space() + capacity
+    }
+
+    /// Bytes the buffer occupies when serialized, discriminator included
+    ///
+    /// # Errors
+    /// Returns an error if the buffer cannot be serialized
+    pub fn serialized_len(&self) -> Result<usize> {
+        let mut counter = ByteCounter(0);
+        self.serialize(&mut counter)?;
+        Ok(8 + counter.0)
+    }
+
+    /// The serialized accounts and operations within `data`, the buffer's
+    /// account data
+    ///
+    /// # Errors
+    /// Returns an error if `data` is shorter than the buffer
+    pub fn contents<'a>(&self, data: &'a [u8]) -> Result<&'a [u8]> {
+        // The bump follows the operations
+        let end = self.serialized_len()? - 1;
+        data.get(Self::CONTENTS_OFFSET..end)
+            .ok_or_else(|| error!(KernelError::InvalidParameters))
+    }
+
+    /// Hash identifying serialized buffer contents, for commitments
+    #[must_use]
+    pub fn hash_contents(contents: &[u8]) -> [u8; 32] {
+        solana_program::hash::hashv(&[BUFFERED_BATCH_DOMAIN, contents]).to_bytes()
+    }
+
+    /// Hash identifying the buffered batch, for commitments
+    ///
+    /// Equals [`Self::hash_contents`] over the buffer's account data.
+    ///
+    /// # Errors
+    /// Returns an error if the contents cannot be serialized
+    pub fn commitment_hash(&self) -> Result<[u8; 32]> {
+        let mut contents = self.accounts.try_to_vec()?;
+        self.operations.serialize(&mut contents)?;
+        Ok(Self::hash_contents(&contents))
+    }
+
+    /// Message an approval signer signs to approve the buffered batch
+    ///
+    /// Binds the contents to the session and its usage count, like
+    /// `OperationBatch::approval_message`.
+    ///
+    /// # Errors
+    /// Returns an error if the contents cannot be serialized
+    pub fn approval_message(&self, session: &Pubkey, usage_count: u64) -> Result<[u8; 32]> {
+        Ok(Self::approval_message_for(session, usage_count, &self.commitment_hash()?))
+    }
+
+    /// Approval message for contents hashing to `commitment_hash`
+    #[must_use]
+    pub fn approval_message_for(
+        session: &Pubkey,
+        usage_count: u64,
+        commitment_hash: &[u8; 32],
+    ) -> [u8; 32] {
+        solana_program::hash::hashv(&[
+            BUFFERED_BATCH_DOMAIN,
+            session.as_ref(),
+            &usage_count.to_le_bytes(),
+            commitment_hash,
+        ])
+        .to_bytes()
+    }
+}
+
+/// Writer counting the bytes serialized into it
+struct ByteCounter(usize);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+// Batch commitments for commit-reveal execution
+//
+// Sensitive batches such as liquidations are exposed to whoever sees them
+// before they land, including searchers co-signing bundles. Committing only
+// the batch hash first, and revealing the batch no earlier than a chosen slot,
+// keeps the contents private until the commitment can no longer be raced.
+//
+// KERNEL INTEGRATION: The batch execution engine consumes a matching
+// commitment passed among the remaining accounts, rejecting it before its
+// earliest slot and closing it afterwards. Guards with `require_commitment`
+// reject batches that arrive without one.
+use anchor_lang::prelude::*;
+
+/// Hash of a batch a session committed to execute
+#[account]
+#[derive(Debug)]
+pub struct BatchCommitment {
+    /// The session that made the commitment
+    pub session: Pubkey,
+
+    /// Hash of the committed `OperationBatch`
+    pub batch_hash: [u8; 32],
+
+    /// First slot at which the batch may execute
+    pub earliest_slot: u64,
+
+    /// Slot the commitment was made in
+    pub committed_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl BatchCommitment {
+    /// Calculate space needed for account
+    pub const fn space() -> usize {
+        8 +  // discriminator
+        32 + // session
+        32 + // batch_hash
+        8 +  // earliest_slot
+        8 +  // committed_slot
+        1    // bump
+    }
+
+    /// Check if the committed batch may execute at `slot`
+    pub fn is_ready(&self, slot: u64) -> bool {
+        slot >= self.earliest_slot
+    }
+}
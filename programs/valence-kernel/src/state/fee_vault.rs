@@ -0,0 +1,82 @@
+// Session fee vaults for relayer-submitted execution
+//
+// Shard end users should not need SOL to act through their sessions. A relayer
+// submits and pays for the transaction instead, and the session's fee vault
+// pays it back from lamports the session owner deposited in advance.
+//
+// KERNEL INTEGRATION: A batch whose submitter is not the session owner must pass
+// the session's fee vault among its remaining accounts. The batch engine
+// requires the submitter to be on the vault's allowlist and reimburses it a
+// fixed amount per batch, never dipping below the vault's rent-exempt minimum.
+// A vault with no reimbursement acts as a plain submitter allowlist.
+//
+// SECURITY MODEL: The vault is a kernel-owned PDA, so only the kernel moves its
+// lamports out: to allowlisted submitters per batch, or to the owner on withdrawal.
+use anchor_lang::prelude::*;
+use crate::errors::KernelError;
+
+/// Relayer allowlist and reimbursement funds of a session
+#[account]
+pub struct FeeVault {
+    /// The session this vault pays for
+    pub session: Pubkey,
+
+    /// Submitters allowed to relay batches and be reimbursed
+    pub submitters: [Pubkey; 4],
+
+    /// Number of active submitters
+    pub submitter_count: u8,
+
+    /// Lamports paid to the submitter per relayed batch
+    pub reimbursement_per_batch: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl FeeVault {
+    pub const MAX_SUBMITTERS: usize = 4;
+
+    /// Calculate space needed for account
+    pub const fn space() -> usize {
+        8 +  // discriminator
+        32 + // session
+        32 * Self::MAX_SUBMITTERS + // submitters
+        1 +  // submitter_count
+        8 +  // reimbursement_per_batch
+        1    // bump
+    }
+
+    /// Create a vault for `session` with no submitters
+    pub fn new(session: Pubkey, reimbursement_per_batch: u64, bump: u8) -> Self {
+        Self {
+            session,
+            submitters: [Pubkey::default(); Self::MAX_SUBMITTERS],
+            submitter_count: 0,
+            reimbursement_per_batch,
+            bump,
+        }
+    }
+
+    /// Replace the submitter allowlist
+    pub fn set_submitters(&mut self, submitters: &[Pubkey]) -> Result<()> {
+        require!(
+            submitters.len() <= Self::MAX_SUBMITTERS,
+            KernelError::InvalidParameters
+        );
+        self.submitters = [Pubkey::default(); Self::MAX_SUBMITTERS];
+        self.submitters[..submitters.len()].copy_from_slice(submitters);
+        self.submitter_count = submitters.len() as u8;
+        Ok(())
+    }
+
+    /// Check if `submitter` may relay batches for the session
+    pub fn is_allowed(&self, submitter: &Pubkey) -> bool {
+        self.submitters[..self.submitter_count as usize].contains(submitter)
+    }
+
+    /// Lamports to pay a submitter, given the vault's balance above rent
+    pub fn reimbursement(&self, available: u64) -> u64 {
+        self.reimbursement_per_batch.min(available)
+    }
+}
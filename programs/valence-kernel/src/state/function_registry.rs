@@ -14,6 +14,13 @@
 // implementations can be called through the kernel, preventing execution of
 // arbitrary programs while enabling extensibility through registered functions.
 use anchor_lang::prelude::*;
+use valence_common::pdas::{
+    function_entry, TOKEN_SWAP_FUNCTION_SEED, ZK_GATEWAY_FUNCTION_SEED,
+};
+
+/// Program dispatching the stateful functions of valence-functions
+pub const VALENCE_FUNCTIONS_PROGRAM_ID: Pubkey =
+    pubkey!("Va1enceFunc11111111111111111111111111111111");
 
 /// Information about a registered function
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -35,10 +42,7 @@ impl FunctionInfo {
             // Example: ZK Verification Gateway
             1000 => Some(FunctionInfo {
                 // Uses a deterministic PDA for the ZK gateway program
-                program_id: Pubkey::find_program_address(
-                    &[b"zk_gateway", &registry_id.to_le_bytes()],
-                    &crate::ID
-                ).0,
+                program_id: function_entry(ZK_GATEWAY_FUNCTION_SEED, registry_id, &crate::ID).0,
                 is_active: true,
                 name: *b"ZK Verification Gateway         ",
                 name_len: 23,
@@ -46,14 +50,39 @@ impl FunctionInfo {
             // Example: Token Swap Function  
             2000 => Some(FunctionInfo {
                 // Uses a deterministic PDA for the swap program
-                program_id: Pubkey::find_program_address(
-                    &[b"token_swap", &registry_id.to_le_bytes()],
-                    &crate::ID
-                ).0,
+                program_id: function_entry(TOKEN_SWAP_FUNCTION_SEED, registry_id, &crate::ID).0,
                 is_active: true,
                 name: *b"Token Swap                      ",
                 name_len: 10,
             }),
+            // Oracle price bound guard, dispatched by valence-functions' `check_price_bound`
+            1004 => Some(FunctionInfo {
+                program_id: VALENCE_FUNCTIONS_PROGRAM_ID,
+                is_active: true,
+                name: *b"Price Bound Guard               ",
+                name_len: 17,
+            }),
+            // Share-based vault function, dispatched by valence-functions' `vault_operation`
+            1005 => Some(FunctionInfo {
+                program_id: VALENCE_FUNCTIONS_PROGRAM_ID,
+                is_active: true,
+                name: *b"Vault                           ",
+                name_len: 5,
+            }),
+            // Escrow function with dispute window, dispatched by valence-functions' `escrow_operation`
+            1006 => Some(FunctionInfo {
+                program_id: VALENCE_FUNCTIONS_PROGRAM_ID,
+                is_active: true,
+                name: *b"Escrow                          ",
+                name_len: 6,
+            }),
+            // AMM swap adapter, dispatched by valence-functions' `swap`
+            1007 => Some(FunctionInfo {
+                program_id: VALENCE_FUNCTIONS_PROGRAM_ID,
+                is_active: true,
+                name: *b"Swap Adapter                    ",
+                name_len: 12,
+            }),
             _ => None,
         }
     }
@@ -13,6 +13,7 @@
 // SECURITY MODEL: Guard accounts implement a simple but effective security model
 // with flags controlling dangerous operations like unregistered CPI calls, providing
 // a balance between flexibility and security for different session requirements.
+use crate::errors::KernelError;
 use anchor_lang::prelude::*;
 
 /// Minimal guard account for session security policy
@@ -26,15 +27,65 @@ pub struct GuardAccount {
     
     /// Version for future upgrades
     pub version: u8,
+    
+    /// Key whose ed25519 signature over each batch must accompany execution
+    pub approval_signer: Option<Pubkey>,
+    
+    /// Whether batches must be committed before they execute
+    pub require_commitment: bool,
+}
+
+/// Guard layout before batch approvals
+#[derive(AnchorDeserialize)]
+struct GuardAccountV1 {
+    session: Pubkey,
+    allow_unregistered_cpi: bool,
+    version: u8,
 }
 
 impl GuardAccount {
+    /// Current layout version; version 2 added batch approvals and version 3
+    /// batch commitments
+    pub const VERSION: u8 = 3;
+    
     /// Calculate space needed for account
     pub const fn space() -> usize {
         8 +  // discriminator
         32 + // session
         1 +  // allow_unregistered_cpi
-        1    // version
+        1 +  // version
+        33 + // approval_signer
+        1    // require_commitment
+    }
+    
+    /// Rebuild a version 1 or 2 guard in the current layout
+    ///
+    /// Fields the guard predates start disabled.
+    ///
+    /// # Errors
+    /// Returns `InvalidVersion` if `data` is not a version 1 or 2 guard
+    pub fn from_legacy(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(Self::DISCRIMINATOR),
+            KernelError::InvalidVersion
+        );
+        // Each version appends to the previous one, so the version 1 fields
+        // prefix every earlier layout
+        let mut body = &data[8..];
+        let v1 = GuardAccountV1::deserialize(&mut body)?;
+        let approval_signer = match v1.version {
+            1 => None,
+            2 => Option::<Pubkey>::deserialize(&mut body)?,
+            _ => return err!(KernelError::InvalidVersion),
+        };
+        
+        Ok(Self {
+            session: v1.session,
+            allow_unregistered_cpi: v1.allow_unregistered_cpi,
+            version: Self::VERSION,
+            approval_signer,
+            require_commitment: false,
+        })
     }
     
     /// Create a new guard account
@@ -42,7 +93,9 @@ impl GuardAccount {
         Self {
             session,
             allow_unregistered_cpi,
-            version: 1,
+            version: Self::VERSION,
+            approval_signer: None,
+            require_commitment: false,
         }
     }
 }
\ No newline at end of file
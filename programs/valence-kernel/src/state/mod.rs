@@ -3,6 +3,12 @@
 // Account types (on-chain state)
 pub mod session_account;
 pub mod guard_account;
+pub mod session_nonce;
+pub mod batch_commitment;
+pub mod batch_buffer;
+pub mod operation_data;
+pub mod session_stats;
+pub mod fee_vault;
 pub mod allowlist_account;
 pub mod account_lookup;
 pub mod function_registry;
@@ -13,6 +19,12 @@ pub mod bitmap;
 // Re-exports
 pub use session_account::{Session, SessionBorrowedAccount, CreateSessionParams};
 pub use guard_account::GuardAccount;
-pub use allowlist_account::AllowlistAccount;
+pub use session_nonce::SessionNonce;
+pub use batch_commitment::BatchCommitment;
+pub use batch_buffer::BatchBuffer;
+pub use operation_data::OperationData;
+pub use session_stats::{OperationCounts, SessionStats};
+pub use fee_vault::FeeVault;
+pub use allowlist_account::{program_deployment_slot, AllowlistAccount};
 pub use account_lookup::{SessionAccountLookup, RegisteredAccount, RegisteredProgram};
 pub use bitmap::{BitMap, BitMap8};
\ No newline at end of file
@@ -0,0 +1,81 @@
+// Session-owned instruction data for batch CPIs
+//
+// `KernelOperation` must stay fixed-size to keep batches on the program stack,
+// which leaves no room for realistic CPI payloads inline (a swap alone needs
+// more than 100 bytes). CPI operations instead reference `data_offset` and
+// `data_len` into the session's operation data account, which the owner fills
+// with `write_operation_data` ahead of execution.
+//
+// LAYOUT: A small header followed by raw bytes up to the account's capacity.
+// Only the header is (de)serialized, so the payload is never copied wholesale;
+// the linker copies each referenced range into a single reused CPI buffer.
+//
+// SECURITY MODEL: Batches carry the hash of the data they reference, and the
+// kernel checks it against the account before executing. Approvals,
+// commitments and offline signatures over the batch therefore cover its data,
+// and rewriting the account after signing fails the batch.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use crate::errors::KernelError;
+
+/// Domain prefix of operation data hashes
+pub const OPERATION_DATA_DOMAIN: &[u8] = b"valence-operation-data";
+
+/// Header of a session's operation data account, followed by the data itself
+#[account]
+#[derive(Debug)]
+pub struct OperationData {
+    /// The session whose batches reference this data
+    pub session: Pubkey,
+
+    /// Bytes written after the header
+    pub len: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl OperationData {
+    /// Size of the header, discriminator included
+    pub const HEADER_SIZE: usize = 8 + // discriminator
+        32 + // session
+        4 +  // len
+        1;   // bump
+
+    /// Account size for `capacity` bytes of data
+    pub const fn space(capacity: usize) -> usize {
+        Self::HEADER_SIZE + capacity
+    }
+
+    /// Bytes of data the account has room for
+    pub const fn capacity(account_len: usize) -> usize {
+        account_len.saturating_sub(Self::HEADER_SIZE)
+    }
+
+    /// Written data, given the account's full data
+    ///
+    /// # Errors
+    /// Returns `InvalidAccountData` if `len` exceeds the account
+    pub fn written<'a>(&self, account_data: &'a [u8]) -> Result<&'a [u8]> {
+        account_data
+            .get(Self::HEADER_SIZE..Self::HEADER_SIZE + self.len as usize)
+            .ok_or_else(|| error!(KernelError::InvalidAccountData))
+    }
+
+    /// The `len` bytes at `offset` of the written data
+    ///
+    /// # Errors
+    /// Returns `OperationDataOutOfRange` for ranges past the written data
+    pub fn range<'a>(&self, account_data: &'a [u8], offset: u16, len: u16) -> Result<&'a [u8]> {
+        let start = offset as usize;
+        self.written(account_data)?
+            .get(start..start + len as usize)
+            .ok_or_else(|| error!(KernelError::OperationDataOutOfRange))
+    }
+
+    /// Hash a batch carries to bind the operation data it references
+    #[must_use]
+    pub fn hash(data: &[u8]) -> [u8; 32] {
+        solana_program::hash::hashv(&[OPERATION_DATA_DOMAIN, data]).to_bytes()
+    }
+}
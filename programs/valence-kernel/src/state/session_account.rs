@@ -128,6 +128,10 @@ pub struct Session {
     
     /// Number of child sessions created
     pub child_session_count: u8,
+    
+    /// Whether the session keeps a statistics account, which every execution
+    /// must then pass so no execution goes uncounted
+    pub stats_enabled: bool,
 }
 
 impl Session {
@@ -150,7 +154,8 @@ impl Session {
         8 * 32 +     // child_accounts array (aligned with EVM)
         1 +          // child_count
         8 * 32 +     // child_sessions array (aligned with EVM)
-        1;           // child_session_count
+        1 +          // child_session_count
+        1;           // stats_enabled
 
     /// Calculate space for account allocation
     #[must_use]
@@ -288,6 +293,7 @@ impl Session {
             child_count: 0,
             child_sessions: [Pubkey::default(); 8],
             child_session_count: 0,
+            stats_enabled: false,
         })
     }
     
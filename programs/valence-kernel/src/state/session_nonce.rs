@@ -0,0 +1,53 @@
+// Session nonce accounts for offline-signed batch execution
+//
+// An operator holding a session's owner key offline cannot sign transactions,
+// whose recent blockhash expires within minutes. Instead the owner signs the
+// batch itself, bound to the session's nonce, and any hot key later submits
+// it alongside an ed25519 precompile instruction carrying that signature.
+//
+// KERNEL INTEGRATION: The batch execution engine accepts a batch from a caller
+// other than the owner only when the session's nonce account is passed and
+// the owner's signature over the batch at the current nonce is verified. The
+// nonce then advances, so each signed batch executes at most once.
+use anchor_lang::prelude::*;
+use crate::errors::KernelError;
+
+/// Replay protection for a session's offline-signed batches
+#[account]
+pub struct SessionNonce {
+    /// The session this nonce belongs to
+    pub session: Pubkey,
+
+    /// Nonce the next offline-signed batch must be signed at
+    pub nonce: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SessionNonce {
+    /// Calculate space needed for account
+    pub const fn space() -> usize {
+        8 +  // discriminator
+        32 + // session
+        8 +  // nonce
+        1    // bump
+    }
+
+    /// Create a nonce account for `session`, starting at zero
+    pub fn new(session: Pubkey, bump: u8) -> Self {
+        Self {
+            session,
+            nonce: 0,
+            bump,
+        }
+    }
+
+    /// Consume the current nonce
+    pub fn advance(&mut self) -> Result<()> {
+        self.nonce = self.nonce
+            .checked_add(1)
+            .ok_or(KernelError::UsageLimitExceeded)?;
+        Ok(())
+    }
+}
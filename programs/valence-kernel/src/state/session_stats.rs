@@ -0,0 +1,140 @@
+// Per-session execution statistics for monitoring
+//
+// Monitoring systems need running totals for a session (batches, operations,
+// value moved) without replaying its transaction history. Sessions that opt
+// in keep those totals in one small PDA, updated as part of each execution.
+//
+// KERNEL INTEGRATION: Initializing the stats account marks the session, after
+// which the batch execution engine and direct operations reject executions
+// that do not pass it among their remaining accounts. Updates saturate rather
+// than fail, so the totals themselves never block execution.
+//
+// LIMITATIONS: A batch rejected by its guard reverts every write in its
+// transaction, including any to this account, so guard failures can only be
+// observed off-chain from failed transactions.
+use anchor_lang::prelude::*;
+use crate::instructions::{BufferedOperation, KernelOperation};
+
+/// Number of operations executed, by operation type
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationCounts {
+    pub borrow_account: u64,
+    pub release_account: u64,
+    pub call_registered_function: u64,
+    pub unsafe_raw_cpi: u64,
+    pub assertions: u64,
+}
+
+impl OperationCounts {
+    /// Serialized size
+    pub const LEN: usize = 5 * 8;
+
+    /// Count one executed operation
+    pub fn record(&mut self, operation: &KernelOperation) {
+        let count = match operation {
+            KernelOperation::BorrowAccount { .. } => &mut self.borrow_account,
+            KernelOperation::ReleaseAccount { .. } => &mut self.release_account,
+            KernelOperation::CallRegisteredFunction { .. } => &mut self.call_registered_function,
+            KernelOperation::UnsafeRawCpi { .. } => &mut self.unsafe_raw_cpi,
+            KernelOperation::AssertAccountData { .. }
+            | KernelOperation::AssertTokenBalance { .. } => &mut self.assertions,
+        };
+        *count = count.saturating_add(1);
+    }
+
+    /// Count one executed operation from a batch buffer
+    pub fn record_buffered(&mut self, operation: &BufferedOperation) {
+        let count = match operation {
+            BufferedOperation::Kernel(operation) => return self.record(operation),
+            BufferedOperation::CallRegisteredFunction { .. } => &mut self.call_registered_function,
+            BufferedOperation::UnsafeRawCpi { .. } => &mut self.unsafe_raw_cpi,
+        };
+        *count = count.saturating_add(1);
+    }
+
+    /// Add the counts of `other`
+    pub fn add(&mut self, other: &Self) {
+        self.borrow_account = self.borrow_account.saturating_add(other.borrow_account);
+        self.release_account = self.release_account.saturating_add(other.release_account);
+        self.call_registered_function = self
+            .call_registered_function
+            .saturating_add(other.call_registered_function);
+        self.unsafe_raw_cpi = self.unsafe_raw_cpi.saturating_add(other.unsafe_raw_cpi);
+        self.assertions = self.assertions.saturating_add(other.assertions);
+    }
+
+    /// Total operations across all types
+    pub fn total(&self) -> u64 {
+        self.borrow_account
+            .saturating_add(self.release_account)
+            .saturating_add(self.call_registered_function)
+            .saturating_add(self.unsafe_raw_cpi)
+            .saturating_add(self.assertions)
+    }
+}
+
+/// Running execution totals of a session
+#[account]
+pub struct SessionStats {
+    /// The session these statistics belong to
+    pub session: Pubkey,
+
+    /// Batches executed through `execute_batch`
+    pub batches_executed: u64,
+
+    /// Operations executed within those batches
+    pub operations: OperationCounts,
+
+    /// Lamports leaving accounts passed to CPIs made by batches
+    pub lamports_moved: u64,
+
+    /// Token amount moved through direct SPL transfers
+    pub tokens_moved: u64,
+
+    /// Slot of the most recent recorded execution
+    pub last_execution_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SessionStats {
+    /// Calculate space needed for account
+    pub const fn space() -> usize {
+        8 +  // discriminator
+        32 + // session
+        8 +  // batches_executed
+        OperationCounts::LEN + // operations
+        8 +  // lamports_moved
+        8 +  // tokens_moved
+        8 +  // last_execution_slot
+        1    // bump
+    }
+
+    /// Create empty statistics for `session`
+    pub fn new(session: Pubkey, bump: u8) -> Self {
+        Self {
+            session,
+            batches_executed: 0,
+            operations: OperationCounts::default(),
+            lamports_moved: 0,
+            tokens_moved: 0,
+            last_execution_slot: 0,
+            bump,
+        }
+    }
+
+    /// Record an executed batch
+    pub fn record_batch(&mut self, operations: &OperationCounts, lamports_moved: u64, slot: u64) {
+        self.batches_executed = self.batches_executed.saturating_add(1);
+        self.operations.add(operations);
+        self.lamports_moved = self.lamports_moved.saturating_add(lamports_moved);
+        self.last_execution_slot = slot;
+    }
+
+    /// Record a direct token transfer
+    pub fn record_transfer(&mut self, amount: u64, slot: u64) {
+        self.tokens_moved = self.tokens_moved.saturating_add(amount);
+        self.last_execution_slot = slot;
+    }
+}
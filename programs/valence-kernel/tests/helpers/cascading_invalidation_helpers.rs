@@ -7,7 +7,7 @@ pub fn create_test_session_with_defaults() -> Session {
     let params = CreateSessionParams {
         namespace_path: pad_namespace_path("test"),
         namespace_path_len: 4,
-        metadata: [0u8; 64],
+        metadata: [0u8; 32],
         parent_session: None,
     };
     
@@ -41,7 +41,7 @@ pub fn create_parent_child_sessions() -> (Session, Session, Pubkey, Pubkey) {
     let child_params = CreateSessionParams {
         namespace_path: pad_namespace_path("test/child"),
         namespace_path_len: 10,
-        metadata: [0u8; 64],
+        metadata: [0u8; 32],
         parent_session: Some(parent_key),
     };
     
@@ -98,7 +98,7 @@ pub fn create_session_hierarchy() -> Vec<(Session, Pubkey)> {
     let parent_params = CreateSessionParams {
         namespace_path: pad_namespace_path("grandparent/parent"),
         namespace_path_len: 18,
-        metadata: [0u8; 64],
+        metadata: [0u8; 32],
         parent_session: Some(grandparent_key),
     };
     
@@ -124,7 +124,7 @@ pub fn create_session_hierarchy() -> Vec<(Session, Pubkey)> {
         let child_params = CreateSessionParams {
             namespace_path: pad_namespace_path(&format!("grandparent/parent/child{}", i)),
             namespace_path_len: 25,
-            metadata: [0u8; 64],
+            metadata: [0u8; 32],
             parent_session: Some(parent_key),
         };
         
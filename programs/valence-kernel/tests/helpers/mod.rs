@@ -1 +1,36 @@
-pub mod cascading_invalidation_helpers;
\ No newline at end of file
+// Shared helpers for the kernel integration tests
+//
+// Each test file compiles as its own crate and uses only some of these.
+#![allow(dead_code)]
+
+pub mod cascading_invalidation_helpers;
+
+use anchor_lang::prelude::*;
+use valence_kernel::{KernelOperation, OperationBatch, MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS};
+
+/// Creates a batch over `accounts` running `operations` in order
+pub fn create_test_batch(
+    accounts: &[Pubkey],
+    operations: impl IntoIterator<Item = KernelOperation>,
+) -> OperationBatch {
+    let operations: Vec<KernelOperation> = operations.into_iter().collect();
+    assert!(accounts.len() <= MAX_BATCH_ACCOUNTS && operations.len() <= MAX_BATCH_OPERATIONS);
+
+    let mut batch = OperationBatch {
+        accounts: [Pubkey::default(); MAX_BATCH_ACCOUNTS],
+        accounts_len: accounts.len() as u8,
+        operations: std::array::from_fn(|_| None),
+        operations_len: operations.len() as u8,
+        operation_data_hash: [0; 32],
+    };
+    batch.accounts[..accounts.len()].copy_from_slice(accounts);
+    for (slot, operation) in batch.operations.iter_mut().zip(operations) {
+        *slot = Some(operation);
+    }
+    batch
+}
+
+/// Borrows the batch account at `account_index` in `mode`
+pub fn borrow(account_index: u8, mode: u8) -> KernelOperation {
+    KernelOperation::BorrowAccount { account_index, mode }
+}
@@ -0,0 +1,83 @@
+// Tests for the ed25519 batch approval message
+mod helpers;
+
+#[cfg(test)]
+mod batch_approval_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{GuardAccount, ACCESS_MODE_READ};
+    use crate::helpers::{borrow, create_test_batch};
+
+    #[test]
+    fn test_approval_message_binds_session_and_usage() {
+        let session = Pubkey::new_unique();
+        let batch = create_test_batch(&[Pubkey::new_unique()], [borrow(0, ACCESS_MODE_READ)]);
+        let message = batch.approval_message(&session, 0).unwrap();
+
+        assert_eq!(message, batch.approval_message(&session, 0).unwrap());
+        // A later execution of the same batch needs a fresh approval
+        assert_ne!(message, batch.approval_message(&session, 1).unwrap());
+        assert_ne!(
+            message,
+            batch.approval_message(&Pubkey::new_unique(), 0).unwrap()
+        );
+        assert_ne!(
+            message,
+            create_test_batch(&[Pubkey::new_unique()], [borrow(0, ACCESS_MODE_READ)])
+                .approval_message(&session, 0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_guard_defaults() {
+        let guard = GuardAccount::new(Pubkey::new_unique(), false);
+        assert!(guard.approval_signer.is_none());
+        assert!(!guard.require_commitment);
+        assert_eq!(guard.version, GuardAccount::VERSION);
+        assert_eq!(GuardAccount::space(), 8 + 32 + 1 + 1 + 33 + 1);
+    }
+
+    #[test]
+    fn test_guard_migrates_from_v1() {
+        let session = Pubkey::new_unique();
+
+        // Version 1: discriminator, session, allow_unregistered_cpi, version
+        let mut data = GuardAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(session.as_ref());
+        data.extend_from_slice(&[1, 1]);
+
+        let migrated = GuardAccount::from_legacy(&data).unwrap();
+        assert_eq!(migrated.session, session);
+        assert!(migrated.allow_unregistered_cpi);
+        assert!(migrated.approval_signer.is_none());
+        assert!(!migrated.require_commitment);
+
+        // The version byte keeps its offset, and the new fields fill the added space
+        let mut serialized = Vec::new();
+        migrated.try_serialize(&mut serialized).unwrap();
+        serialized.resize(GuardAccount::space(), 0);
+        assert_eq!(serialized[data.len() - 1], GuardAccount::VERSION);
+
+        // Current guards are not migrated again
+        assert!(GuardAccount::from_legacy(&serialized).is_err());
+    }
+
+    #[test]
+    fn test_guard_migrates_from_v2() {
+        let session = Pubkey::new_unique();
+        let approval_signer = Pubkey::new_unique();
+
+        // Version 2 appended the approval signer
+        let mut data = GuardAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(session.as_ref());
+        data.extend_from_slice(&[0, 2, 1]);
+        data.extend_from_slice(approval_signer.as_ref());
+
+        let migrated = GuardAccount::from_legacy(&data).unwrap();
+        assert_eq!(migrated.session, session);
+        assert!(!migrated.allow_unregistered_cpi);
+        assert_eq!(migrated.approval_signer, Some(approval_signer));
+        assert!(!migrated.require_commitment);
+        assert_eq!(migrated.version, GuardAccount::VERSION);
+    }
+}
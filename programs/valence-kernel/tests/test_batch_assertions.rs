@@ -0,0 +1,50 @@
+// Tests for account data and token balance assertions
+mod helpers;
+
+#[cfg(test)]
+mod batch_assertion_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{KernelOperation, OperationBatch, MAX_ASSERTION_DATA_SIZE};
+    use crate::helpers::create_test_batch;
+
+    fn batch(operation: KernelOperation) -> OperationBatch {
+        create_test_batch(&[Pubkey::new_unique()], [operation])
+    }
+
+    fn assert_data(account_index: u8, len: u8) -> KernelOperation {
+        KernelOperation::AssertAccountData {
+            account_index,
+            offset: 8,
+            expected: [1u8; MAX_ASSERTION_DATA_SIZE],
+            len,
+        }
+    }
+
+    #[test]
+    fn test_account_data_assertion_validation() {
+        assert!(batch(assert_data(0, 8)).validate().is_ok());
+        assert!(batch(assert_data(0, MAX_ASSERTION_DATA_SIZE as u8)).validate().is_ok());
+
+        assert!(batch(assert_data(0, 0)).validate().is_err());
+        assert!(batch(assert_data(0, MAX_ASSERTION_DATA_SIZE as u8 + 1)).validate().is_err());
+        // Account index outside the batch's account list
+        assert!(batch(assert_data(1, 8)).validate().is_err());
+    }
+
+    #[test]
+    fn test_token_balance_assertion_validation() {
+        let balance = |account_index, min, max| KernelOperation::AssertTokenBalance {
+            account_index,
+            min,
+            max,
+        };
+
+        assert!(batch(balance(0, 100, 100)).validate().is_ok());
+        assert!(batch(balance(0, 0, u64::MAX)).validate().is_ok());
+        assert!(batch(balance(0, 101, 100)).validate().is_err());
+        assert!(batch(balance(3, 0, 100)).validate().is_err());
+
+        let batch = batch(balance(0, 0, 100));
+        assert_eq!(batch.compute_estimate(), 1_500);
+    }
+}
@@ -0,0 +1,140 @@
+// Tests for chunked batches accumulated in batch buffers
+#[cfg(test)]
+mod batch_buffer_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{
+        state::{BatchBuffer, OperationCounts},
+        BufferedOperation, KernelOperation, ACCESS_MODE_WRITE, MAX_BATCH_BUFFER_SIZE,
+        MAX_CPI_ACCOUNT_INDICES, MAX_OPERATION_DATA_SIZE,
+    };
+
+    fn large_call(data_len: usize) -> BufferedOperation {
+        BufferedOperation::CallRegisteredFunction {
+            registry_id: 1005,
+            account_indices: vec![0, 1],
+            data: vec![7u8; data_len],
+        }
+    }
+
+    fn buffer(operations: Vec<BufferedOperation>) -> BatchBuffer {
+        BatchBuffer {
+            session: Pubkey::new_from_array([1u8; 32]),
+            buffer_id: 3,
+            accounts: vec![
+                Pubkey::new_from_array([2u8; 32]),
+                Pubkey::new_from_array([3u8; 32]),
+            ],
+            operations,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_buffered_cpi_validation() {
+        // Buffered CPIs carry up to the same data as operation data references
+        assert!(large_call(MAX_OPERATION_DATA_SIZE).validate(2).is_ok());
+        assert!(large_call(MAX_OPERATION_DATA_SIZE + 1).validate(2).is_err());
+
+        // Indices must reference accounts appended so far
+        assert!(large_call(8).validate(1).is_err());
+        let raw = BufferedOperation::UnsafeRawCpi {
+            program_index: 2,
+            account_indices: vec![0],
+            data: vec![],
+        };
+        assert!(raw.validate(2).is_err());
+        assert!(raw.validate(3).is_ok());
+
+        let too_many_accounts = BufferedOperation::CallRegisteredFunction {
+            registry_id: 1005,
+            account_indices: vec![0; MAX_CPI_ACCOUNT_INDICES + 1],
+            data: vec![],
+        };
+        assert!(too_many_accounts.validate(2).is_err());
+    }
+
+    #[test]
+    fn test_buffered_kernel_operations_validated() {
+        let borrow = |account_index| {
+            BufferedOperation::Kernel(KernelOperation::BorrowAccount {
+                account_index,
+                mode: ACCESS_MODE_WRITE,
+            })
+        };
+        assert!(borrow(1).validate(2).is_ok());
+        assert!(borrow(2).validate(2).is_err());
+        assert!(BufferedOperation::Kernel(KernelOperation::BorrowAccount {
+            account_index: 0,
+            mode: 9,
+        })
+        .validate(2)
+        .is_err());
+    }
+
+    #[test]
+    fn test_buffer_hashes_bind_contents() {
+        let buffer = buffer(vec![large_call(100)]);
+        let hash = buffer.commitment_hash().unwrap();
+
+        let mut reordered = buffer.clone();
+        reordered.accounts.reverse();
+        assert_ne!(hash, reordered.commitment_hash().unwrap());
+
+        // Only the contents are committed to
+        let mut other_id = buffer.clone();
+        other_id.buffer_id = 4;
+        assert_eq!(hash, other_id.commitment_hash().unwrap());
+
+        // On chain the contents are hashed in place from the account data
+        let mut data = Vec::new();
+        buffer.try_serialize(&mut data).unwrap();
+        data.extend_from_slice(&[0u8; 16]);
+        assert_eq!(
+            BatchBuffer::hash_contents(buffer.contents(&data).unwrap()),
+            hash
+        );
+        assert!(buffer.contents(&data[..BatchBuffer::CONTENTS_OFFSET]).is_err());
+
+        let session = buffer.session;
+        let message = buffer.approval_message(&session, 0).unwrap();
+        assert_ne!(message, buffer.approval_message(&session, 1).unwrap());
+        assert_ne!(
+            message,
+            buffer.approval_message(&Pubkey::new_unique(), 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_buffer_space() {
+        let empty = buffer(vec![]);
+        assert_eq!(
+            empty.serialized_len().unwrap(),
+            BatchBuffer::empty_space() + 2 * 32
+        );
+        assert!(BatchBuffer::space(MAX_BATCH_BUFFER_SIZE) > MAX_BATCH_BUFFER_SIZE);
+
+        let full = buffer(vec![large_call(1024); 7]);
+        assert!(full.serialized_len().unwrap() <= MAX_BATCH_BUFFER_SIZE);
+        let overflowing = buffer(vec![large_call(1024); 8]);
+        assert!(overflowing.serialized_len().unwrap() > MAX_BATCH_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_buffered_operation_counts() {
+        let mut counts = OperationCounts::default();
+        counts.record_buffered(&large_call(8));
+        counts.record_buffered(&BufferedOperation::UnsafeRawCpi {
+            program_index: 0,
+            account_indices: vec![],
+            data: vec![],
+        });
+        counts.record_buffered(&BufferedOperation::Kernel(
+            KernelOperation::ReleaseAccount { account_index: 0 },
+        ));
+
+        assert_eq!(counts.call_registered_function, 1);
+        assert_eq!(counts.unsafe_raw_cpi, 1);
+        assert_eq!(counts.release_account, 1);
+        assert_eq!(large_call(8).compute_estimate(), 50_000);
+    }
+}
@@ -0,0 +1,166 @@
+// Heap usage of full batch buffers
+//
+// Programs allocate from a 32 KiB bump heap that never frees. The allocator
+// below emulates it by charging every allocation and reallocation, so the
+// tests measure what appending to and finalizing a full buffer costs on chain.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct BumpHeap;
+
+thread_local! {
+    static CHARGED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn charge(layout: Layout, size: usize) {
+    CHARGED.with(|charged| {
+        if let Some(used) = charged.get() {
+            let aligned = (used + layout.align() - 1) & !(layout.align() - 1);
+            charged.set(Some(aligned + size));
+        }
+    });
+}
+
+unsafe impl GlobalAlloc for BumpHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        charge(layout, layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        charge(layout, new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static HEAP: BumpHeap = BumpHeap;
+
+/// Bytes `f` would take from a bump heap
+fn heap_used<R>(f: impl FnOnce() -> R) -> usize {
+    CHARGED.with(|charged| charged.set(Some(0)));
+    let result = f();
+    let used = CHARGED.with(|charged| charged.replace(None)).unwrap();
+    drop(result);
+    used
+}
+
+#[cfg(test)]
+mod batch_buffer_heap_tests {
+    use super::heap_used;
+    use anchor_lang::{prelude::*, InstructionData};
+    use valence_kernel::{
+        instruction::AppendOperations, state::BatchBuffer, BufferedOperation, KernelOperation,
+        MAX_BATCH_BUFFER_SIZE, MAX_BUFFERED_ACCOUNTS, MAX_BUFFERED_OPERATIONS,
+        MAX_CPI_ACCOUNT_INDICES,
+    };
+
+    /// Heap a full buffer may take on finalize, leaving 20 KiB of the 32 KiB
+    /// heap for the instruction's accounts and the CPIs it executes
+    const FINALIZE_BUDGET: usize = 12 * 1024;
+
+    /// Heap appending to a nearly full buffer may take
+    const APPEND_BUDGET: usize = 24 * 1024;
+
+    fn call(data_len: usize) -> BufferedOperation {
+        BufferedOperation::CallRegisteredFunction {
+            registry_id: 1005,
+            account_indices: vec![0; MAX_CPI_ACCOUNT_INDICES],
+            data: vec![7u8; data_len],
+        }
+    }
+
+    /// Buffer of `accounts` accounts and `operations` operations, padded with
+    /// call data to `size` bytes
+    ///
+    /// Kernel operations are the smallest to serialize relative to their size
+    /// in memory, so all but the padded calls are kernel operations.
+    fn buffer(accounts: usize, operations: usize, size: usize) -> BatchBuffer {
+        let calls = 12;
+        let mut buffer = BatchBuffer {
+            session: Pubkey::new_unique(),
+            buffer_id: 0,
+            accounts: vec![Pubkey::new_unique(); accounts],
+            operations: vec![
+                BufferedOperation::Kernel(KernelOperation::ReleaseAccount { account_index: 0 });
+                operations - calls
+            ],
+            bump: 255,
+        };
+        buffer.operations.extend(vec![call(0); calls]);
+
+        let padding = size - buffer.serialized_len().unwrap();
+        for (i, operation) in buffer.operations[operations - calls..].iter_mut().enumerate() {
+            let share = padding / calls + usize::from(i < padding % calls);
+            *operation = call(share);
+        }
+        assert_eq!(buffer.serialized_len().unwrap(), size);
+        buffer
+    }
+
+    fn account_data(buffer: &BatchBuffer) -> Vec<u8> {
+        let mut data = Vec::new();
+        buffer.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_finalize_full_buffer_within_budget() {
+        let full = buffer(
+            MAX_BUFFERED_ACCOUNTS,
+            MAX_BUFFERED_OPERATIONS,
+            MAX_BATCH_BUFFER_SIZE,
+        );
+        let data = account_data(&full);
+        let session = full.session;
+
+        // Deserializing the account, then hashing it for approvals and commitments
+        let used = heap_used(|| {
+            let buffer = BatchBuffer::try_deserialize(&mut data.as_slice()).unwrap();
+            let commitment_hash = BatchBuffer::hash_contents(buffer.contents(&data).unwrap());
+            let message = BatchBuffer::approval_message_for(&session, 0, &commitment_hash);
+            (buffer, message)
+        });
+        assert!(used <= FINALIZE_BUDGET, "finalize used {used} bytes");
+
+        // Hashing in place matches the off-chain commitment
+        let contents = full.contents(&data).unwrap();
+        assert_eq!(
+            BatchBuffer::hash_contents(contents),
+            full.commitment_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_append_to_full_buffer_within_budget() {
+        let chunk_accounts = 4;
+        let chunk = AppendOperations {
+            accounts: vec![Pubkey::new_unique(); chunk_accounts],
+            operations: vec![call(900), call(0), call(0), call(0)],
+        }
+        .data();
+        // Leave room for the chunk, less its instruction discriminator and list lengths
+        let existing = buffer(
+            MAX_BUFFERED_ACCOUNTS - chunk_accounts,
+            MAX_BUFFERED_OPERATIONS - 4,
+            MAX_BATCH_BUFFER_SIZE - (chunk.len() - 16),
+        );
+        let data = account_data(&existing);
+
+        let used = heap_used(|| {
+            let mut buffer = BatchBuffer::try_deserialize(&mut data.as_slice()).unwrap();
+            let args = AppendOperations::deserialize(&mut &chunk[8..]).unwrap();
+            buffer.accounts.reserve_exact(args.accounts.len());
+            buffer.accounts.extend_from_slice(&args.accounts);
+            buffer.operations.reserve_exact(args.operations.len());
+            buffer.operations.extend(args.operations);
+            let len = buffer.serialized_len().unwrap();
+            (buffer, len)
+        });
+        assert!(used <= APPEND_BUDGET, "append used {used} bytes");
+    }
+}
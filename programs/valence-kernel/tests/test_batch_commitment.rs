@@ -0,0 +1,37 @@
+// Tests for commit-reveal batch commitments
+mod helpers;
+
+#[cfg(test)]
+mod batch_commitment_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{state::BatchCommitment, ACCESS_MODE_WRITE};
+    use crate::helpers::{borrow, create_test_batch};
+
+    #[test]
+    fn test_commitment_hash_identifies_batch() {
+        let account = Pubkey::new_unique();
+        let batch = create_test_batch(&[account], [borrow(0, ACCESS_MODE_WRITE)]);
+        let hash = batch.commitment_hash().unwrap();
+
+        let same = create_test_batch(&[account], [borrow(0, ACCESS_MODE_WRITE)]);
+        assert_eq!(hash, same.commitment_hash().unwrap());
+        let read = create_test_batch(&[account], [borrow(0, 1)]);
+        assert_ne!(hash, read.commitment_hash().unwrap());
+    }
+
+    #[test]
+    fn test_commitment_ready_from_earliest_slot() {
+        let commitment = BatchCommitment {
+            session: Pubkey::new_unique(),
+            batch_hash: [0u8; 32],
+            earliest_slot: 100,
+            committed_slot: 90,
+            bump: 255,
+        };
+
+        assert!(!commitment.is_ready(99));
+        assert!(commitment.is_ready(100));
+        assert!(commitment.is_ready(101));
+        assert_eq!(BatchCommitment::space(), 8 + 32 + 32 + 8 + 8 + 1);
+    }
+}
@@ -0,0 +1,82 @@
+// Property tests for batch validation bounds and borrow registration
+#[cfg(test)]
+mod batch_property_tests {
+    use anchor_lang::prelude::*;
+    use proptest::{collection::vec, prelude::*};
+    use valence_kernel::{
+        KernelOperation, NamespacePath, OperationBatch, SessionAccountLookup,
+        ACCESS_MODE_READ_WRITE, MAX_BATCH_ACCOUNTS, MAX_BATCH_OPERATIONS,
+        MAX_CPI_ACCOUNT_INDICES, MAX_NAMESPACE_PATH_LEN, MAX_OPERATION_DATA_SIZE,
+        MAX_REGISTERED_ACCOUNTS,
+    };
+
+    fn pubkey() -> impl Strategy<Value = Pubkey> {
+        any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+    }
+
+    fn raw_cpi_batch(account_indices_len: u8, data_len: u16) -> OperationBatch {
+        let mut operations: [Option<KernelOperation>; MAX_BATCH_OPERATIONS] =
+            std::array::from_fn(|_| None);
+        operations[0] = Some(KernelOperation::UnsafeRawCpi {
+            program_index: 0,
+            account_indices: [0; MAX_CPI_ACCOUNT_INDICES],
+            account_indices_len,
+            data_offset: 0,
+            data_len,
+        });
+
+        OperationBatch {
+            accounts: [Pubkey::new_unique(); MAX_BATCH_ACCOUNTS],
+            accounts_len: 1,
+            operations,
+            operations_len: 1,
+            operation_data_hash: [0; 32],
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_cpi_lengths_bounded(account_indices_len in any::<u8>(), data_len in any::<u16>()) {
+            let within_bounds = account_indices_len as usize <= MAX_CPI_ACCOUNT_INDICES
+                && data_len as usize <= MAX_OPERATION_DATA_SIZE;
+            let batch = raw_cpi_batch(account_indices_len, data_len);
+            prop_assert_eq!(batch.validate().is_ok(), within_bounds);
+        }
+
+        #[test]
+        fn test_unregistered_account_cannot_be_borrowed(
+            registered in vec(pubkey(), 0..=MAX_REGISTERED_ACCOUNTS),
+            account in pubkey(),
+            mode in 1u8..=ACCESS_MODE_READ_WRITE,
+        ) {
+            let mut alt = SessionAccountLookup::new(Pubkey::new_unique(), Pubkey::new_unique());
+            for address in &registered {
+                let _ = alt.register_borrowable(*address, ACCESS_MODE_READ_WRITE, [0; 8]);
+            }
+            prop_assert_eq!(
+                alt.validate_borrowable(&account, mode).is_ok(),
+                registered.contains(&account)
+            );
+        }
+
+        #[test]
+        fn test_decoded_namespace_never_panics(
+            path in vec(any::<u8>(), 0..=MAX_NAMESPACE_PATH_LEN),
+            len in any::<u16>(),
+        ) {
+            let mut buffer = [0u8; MAX_NAMESPACE_PATH_LEN];
+            buffer[..path.len()].copy_from_slice(&path);
+            let namespace = NamespacePath { path: buffer, len };
+
+            if len as usize > MAX_NAMESPACE_PATH_LEN {
+                prop_assert!(namespace.as_str().is_err());
+                prop_assert_eq!(namespace.depth(), 0);
+            }
+            if let Some(parent) = namespace.parent() {
+                prop_assert!(parent.is_parent_of(&namespace));
+            }
+            let root = NamespacePath::new("root").unwrap();
+            let _ = root.is_parent_of(&namespace);
+        }
+    }
+}
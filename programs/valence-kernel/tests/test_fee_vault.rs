@@ -0,0 +1,43 @@
+// Tests for relayer allowlists and reimbursement from session fee vaults
+#[cfg(test)]
+mod fee_vault_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::state::FeeVault;
+
+    #[test]
+    fn test_submitter_allowlist() {
+        let relayer = Pubkey::new_unique();
+        let mut vault = FeeVault::new(Pubkey::new_unique(), 5_000, 255);
+        assert!(!vault.is_allowed(&relayer));
+        // Unused slots never match the default key
+        assert!(!vault.is_allowed(&Pubkey::default()));
+
+        vault.set_submitters(&[relayer]).unwrap();
+        assert!(vault.is_allowed(&relayer));
+        assert!(!vault.is_allowed(&Pubkey::new_unique()));
+
+        vault.set_submitters(&[]).unwrap();
+        assert!(!vault.is_allowed(&relayer));
+
+        let too_many: Vec<Pubkey> = (0..=FeeVault::MAX_SUBMITTERS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        assert!(vault.set_submitters(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_reimbursement_limited_by_funds() {
+        let vault = FeeVault::new(Pubkey::new_unique(), 5_000, 255);
+        assert_eq!(vault.reimbursement(10_000), 5_000);
+        assert_eq!(vault.reimbursement(1_200), 1_200);
+        assert_eq!(vault.reimbursement(0), 0);
+    }
+
+    #[test]
+    fn test_fee_vault_space_matches_serialized_size() {
+        let vault = FeeVault::new(Pubkey::new_unique(), 5_000, 255);
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), FeeVault::space());
+    }
+}
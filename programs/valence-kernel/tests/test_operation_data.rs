@@ -0,0 +1,107 @@
+// Tests for CPI data referenced from the session's operation data
+mod helpers;
+
+#[cfg(test)]
+mod operation_data_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{
+        state::OperationData, BufferedOperation, KernelOperation, OperationBatch,
+        MAX_CPI_ACCOUNT_INDICES, MAX_OPERATION_DATA_ACCOUNT_SIZE, MAX_OPERATION_DATA_SIZE,
+    };
+    use crate::helpers::create_test_batch;
+
+    fn batch(operation: KernelOperation) -> OperationBatch {
+        create_test_batch(&[Pubkey::new_unique()], [operation])
+    }
+
+    fn raw_cpi(data_offset: u16, data_len: u16) -> KernelOperation {
+        KernelOperation::UnsafeRawCpi {
+            program_index: 0,
+            account_indices: [0; MAX_CPI_ACCOUNT_INDICES],
+            account_indices_len: 0,
+            data_offset,
+            data_len,
+        }
+    }
+
+    /// Account data of an operation data account holding `written` bytes
+    fn account(written: &[u8], capacity: usize) -> (OperationData, Vec<u8>) {
+        let header = OperationData {
+            session: Pubkey::new_unique(),
+            len: written.len() as u32,
+            bump: 255,
+        };
+        let mut data = vec![0u8; OperationData::space(capacity)];
+        data[OperationData::HEADER_SIZE..][..written.len()].copy_from_slice(written);
+        (header, data)
+    }
+
+    #[test]
+    fn test_cpi_data_beyond_former_inline_limit() {
+        // Swaps and similar CPIs need well over the former 64 inline bytes
+        assert!(batch(raw_cpi(0, 128)).validate().is_ok());
+        assert!(batch(raw_cpi(512, MAX_OPERATION_DATA_SIZE as u16)).validate().is_ok());
+        assert!(batch(raw_cpi(0, MAX_OPERATION_DATA_SIZE as u16 + 1)).validate().is_err());
+
+        let past_account = MAX_OPERATION_DATA_ACCOUNT_SIZE as u16;
+        assert!(batch(raw_cpi(past_account, 1)).validate().is_err());
+    }
+
+    #[test]
+    fn test_references_operation_data() {
+        assert!(batch(raw_cpi(0, 1)).references_operation_data());
+        assert!(!batch(raw_cpi(40, 0)).references_operation_data());
+        assert!(!batch(KernelOperation::ReleaseAccount { account_index: 0 })
+            .references_operation_data());
+    }
+
+    #[test]
+    fn test_range_within_written_data() {
+        let (header, data) = account(&[1, 2, 3, 4, 5], 16);
+
+        assert_eq!(header.written(&data).unwrap(), &[1, 2, 3, 4, 5]);
+        assert_eq!(header.range(&data, 1, 3).unwrap(), &[2, 3, 4]);
+        assert_eq!(header.range(&data, 5, 0).unwrap(), &[] as &[u8]);
+
+        // Capacity past the written data is not readable
+        assert!(header.range(&data, 3, 3).is_err());
+        assert!(header.range(&data, 6, 0).is_err());
+
+        let truncated = &data[..OperationData::HEADER_SIZE + 2];
+        assert!(header.written(truncated).is_err());
+    }
+
+    #[test]
+    fn test_space_and_capacity() {
+        assert_eq!(OperationData::HEADER_SIZE, 8 + 32 + 4 + 1);
+        assert_eq!(OperationData::capacity(OperationData::space(300)), 300);
+        assert_eq!(OperationData::capacity(10), 0);
+
+        // The header serializes within HEADER_SIZE, ahead of the data
+        let (header, _) = account(&[], 0);
+        assert_eq!(8 + header.try_to_vec().unwrap().len(), OperationData::HEADER_SIZE);
+    }
+
+    #[test]
+    fn test_hash_binds_data() {
+        let hash = OperationData::hash(&[1, 2, 3]);
+        assert_eq!(hash, OperationData::hash(&[1, 2, 3]));
+        assert_ne!(hash, OperationData::hash(&[1, 2, 4]));
+        assert_ne!(hash, OperationData::hash(&[1, 2, 3, 0]));
+
+        // The hash is part of every message signed or committed over the batch
+        let session = Pubkey::new_unique();
+        let mut bound = batch(raw_cpi(0, 3));
+        let message = bound.approval_message(&session, 0).unwrap();
+        let commitment = bound.commitment_hash().unwrap();
+        bound.operation_data_hash = hash;
+        assert_ne!(message, bound.approval_message(&session, 0).unwrap());
+        assert_ne!(commitment, bound.commitment_hash().unwrap());
+    }
+
+    #[test]
+    fn test_buffered_operations_carry_their_own_data() {
+        assert!(BufferedOperation::Kernel(raw_cpi(0, 0)).validate(1).is_ok());
+        assert!(BufferedOperation::Kernel(raw_cpi(0, 8)).validate(1).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+// Tests for session nonces used by offline-signed batches
+mod helpers;
+
+#[cfg(test)]
+mod session_nonce_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{state::SessionNonce, ACCESS_MODE_READ};
+    use crate::helpers::{borrow, create_test_batch};
+
+    #[test]
+    fn test_nonce_advances() {
+        let session = Pubkey::new_unique();
+        let mut nonce = SessionNonce::new(session, 255);
+        assert_eq!(nonce.nonce, 0);
+
+        nonce.advance().unwrap();
+        assert_eq!(nonce.nonce, 1);
+
+        nonce.nonce = u64::MAX;
+        assert!(nonce.advance().is_err());
+        assert_eq!(SessionNonce::space(), 8 + 32 + 8 + 1);
+    }
+
+    #[test]
+    fn test_offline_message_is_single_use() {
+        let session = Pubkey::new_unique();
+        let batch = create_test_batch(&[Pubkey::new_unique()], [borrow(0, ACCESS_MODE_READ)]);
+        let message = batch.offline_message(&session, 0).unwrap();
+
+        assert_eq!(message, batch.offline_message(&session, 0).unwrap());
+        // Once the nonce advances the same signature no longer matches
+        assert_ne!(message, batch.offline_message(&session, 1).unwrap());
+        // Offline signatures cannot stand in for guard approvals
+        assert_ne!(message, batch.approval_message(&session, 0).unwrap());
+    }
+}
@@ -0,0 +1,66 @@
+// Tests for incremental session statistics
+#[cfg(test)]
+mod session_stats_tests {
+    use anchor_lang::prelude::*;
+    use valence_kernel::{
+        state::{OperationCounts, SessionStats},
+        KernelOperation, ACCESS_MODE_READ, MAX_CPI_ACCOUNT_INDICES,
+    };
+
+    fn registered_call() -> KernelOperation {
+        KernelOperation::CallRegisteredFunction {
+            registry_id: 1005,
+            account_indices: [0u8; MAX_CPI_ACCOUNT_INDICES],
+            account_indices_len: 0,
+            data_offset: 0,
+            data_len: 0,
+        }
+    }
+
+    #[test]
+    fn test_operation_counts_by_type() {
+        let mut counts = OperationCounts::default();
+        counts.record(&KernelOperation::BorrowAccount {
+            account_index: 0,
+            mode: ACCESS_MODE_READ,
+        });
+        counts.record(&registered_call());
+        counts.record(&registered_call());
+        counts.record(&KernelOperation::ReleaseAccount { account_index: 0 });
+
+        assert_eq!(counts.borrow_account, 1);
+        assert_eq!(counts.call_registered_function, 2);
+        assert_eq!(counts.release_account, 1);
+        assert_eq!(counts.unsafe_raw_cpi, 0);
+        assert_eq!(counts.total(), 4);
+    }
+
+    #[test]
+    fn test_stats_accumulate_across_executions() {
+        let mut stats = SessionStats::new(Pubkey::new_unique(), 255);
+        let mut counts = OperationCounts::default();
+        counts.record(&registered_call());
+
+        stats.record_batch(&counts, 5_000, 10);
+        stats.record_batch(&counts, 2_500, 12);
+        stats.record_transfer(1_000, 15);
+
+        assert_eq!(stats.batches_executed, 2);
+        assert_eq!(stats.operations.call_registered_function, 2);
+        assert_eq!(stats.lamports_moved, 7_500);
+        assert_eq!(stats.tokens_moved, 1_000);
+        assert_eq!(stats.last_execution_slot, 15);
+
+        // Totals saturate instead of failing execution
+        stats.record_transfer(u64::MAX, 16);
+        assert_eq!(stats.tokens_moved, u64::MAX);
+    }
+
+    #[test]
+    fn test_stats_space_matches_serialized_size() {
+        let stats = SessionStats::new(Pubkey::new_unique(), 255);
+        let mut data = Vec::new();
+        stats.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), SessionStats::space());
+    }
+}
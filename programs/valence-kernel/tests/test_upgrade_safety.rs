@@ -0,0 +1,103 @@
+// Tests for pinning allowlisted programs to their deployment slot
+#[cfg(test)]
+mod upgrade_safety_tests {
+    use anchor_lang::prelude::*;
+    #[allow(deprecated)]
+    use anchor_lang::solana_program::bpf_loader_upgradeable;
+    use valence_kernel::state::{program_deployment_slot, AllowlistAccount};
+
+    fn program_data_bytes(slot: u64) -> Vec<u8> {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&slot.to_le_bytes());
+        data.push(0); // no upgrade authority
+        data
+    }
+
+    #[test]
+    fn test_pinned_deployment_follows_swap_remove() {
+        let mut allowlist = AllowlistAccount::new(Pubkey::new_unique());
+        let unpinned = Pubkey::new_unique();
+        let pinned = Pubkey::new_unique();
+
+        allowlist.add_program_with_deployment(unpinned, 10, false).unwrap();
+        allowlist.add_program_with_deployment(pinned, 42, true).unwrap();
+        assert_eq!(allowlist.pinned_deployment(&unpinned), None);
+        assert_eq!(allowlist.pinned_deployment(&pinned), Some(42));
+
+        // Removing the first entry moves the pinned one into its slot
+        allowlist.remove_program(&unpinned).unwrap();
+        assert_eq!(allowlist.pinned_deployment(&pinned), Some(42));
+        assert_eq!(allowlist.deployed_slots[1], 0);
+        assert!(!allowlist.reject_upgrades[1]);
+    }
+
+    #[test]
+    fn test_pinning_requires_deployment_slot() {
+        let mut allowlist = AllowlistAccount::new(Pubkey::new_unique());
+        assert!(allowlist
+            .add_program_with_deployment(Pubkey::new_unique(), 0, true)
+            .is_err());
+        assert_eq!(allowlist.program_count, 0);
+    }
+
+    #[test]
+    fn test_allowlist_migrates_from_v1() {
+        let authority = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let mut programs = [Pubkey::default(); 32];
+        programs[0] = program;
+
+        // Version 1: discriminator, authority, programs, count, version
+        let mut data = AllowlistAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(authority.as_ref());
+        programs.iter().for_each(|p| data.extend_from_slice(p.as_ref()));
+        data.extend_from_slice(&[1, 1]);
+        assert_eq!(data.len(), AllowlistAccount::v1_space());
+
+        let migrated = AllowlistAccount::from_v1(&data).unwrap();
+        assert_eq!(migrated.authority, authority);
+        assert!(migrated.is_allowed(&program));
+        assert_eq!(migrated.pinned_deployment(&program), None);
+        assert_eq!(migrated.version, AllowlistAccount::VERSION);
+
+        // The version byte keeps its offset, and the new fields fill the added space
+        let mut serialized = Vec::new();
+        migrated.try_serialize(&mut serialized).unwrap();
+        assert_eq!(serialized.len(), AllowlistAccount::space());
+        assert_eq!(serialized[AllowlistAccount::v1_space() - 1], AllowlistAccount::VERSION);
+
+        // Current allowlists are not migrated again
+        assert!(AllowlistAccount::from_v1(&serialized).is_err());
+    }
+
+    #[test]
+    fn test_program_deployment_slot() {
+        let program_id = Pubkey::new_unique();
+        let (address, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+        let loader = bpf_loader_upgradeable::ID;
+        let mut lamports = 0;
+        let mut data = program_data_bytes(1234);
+        let program_data = AccountInfo::new(
+            &address, false, false, &mut lamports, &mut data, &loader, false, 0,
+        );
+        assert_eq!(program_deployment_slot(&program_id, &program_data).unwrap(), 1234);
+
+        // ProgramData of a different program is rejected
+        assert!(program_deployment_slot(&Pubkey::new_unique(), &program_data).is_err());
+    }
+
+    #[test]
+    fn test_program_deployment_slot_rejects_wrong_owner() {
+        let program_id = Pubkey::new_unique();
+        let (address, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = program_data_bytes(1234);
+        let program_data = AccountInfo::new(
+            &address, false, false, &mut lamports, &mut data, &owner, false, 0,
+        );
+        assert!(program_deployment_slot(&program_id, &program_data).is_err());
+    }
+}
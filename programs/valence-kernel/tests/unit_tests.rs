@@ -110,6 +110,7 @@ mod tests {
             accounts_len: 0,
             operations: default_operations,
             operations_len: 0,
+            operation_data_hash: [0; 32],
         };
         
         // Basic structure test - if we reach here, construction succeeded